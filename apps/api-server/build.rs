@@ -0,0 +1,435 @@
+//! Generates the route matrix served at `/api/admin/routes`.
+//!
+//! Starting from `handlers::configure_routes`, this follows the
+//! `scope`/`resource`/`route`/`service`/`configure` calls that register
+//! routes, then reads each handler's signature to find the access it
+//! requires: the `Admin` extractor means the admin role, `Identity` any
+//! signed-in user, `OptionalIdentity` optional sign-in and none of them a
+//! public route. `#[cfg]` attributes on the way are carried over, so the
+//! matrix lists exactly the routes of the features being built.
+//!
+//! Registrations in a form it does not follow fail the build rather than
+//! going missing from the matrix.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use quote::ToTokens;
+use syn::{Attribute, Expr, FnArg, Item, ItemFn, Lit, Pat, Stmt, Type};
+
+const HANDLERS: &str = "src/handlers";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", HANDLERS);
+
+    let mut matrix = Matrix::default();
+    let routes = matrix.config_fn(&[], "configure_routes");
+
+    let mut out = String::from("[\n");
+    for route in routes {
+        let handler = route.handler.join("::");
+        let access = matrix.access(&route.handler);
+        for cfg in &route.cfgs {
+            writeln!(out, "    {}", cfg).unwrap();
+        }
+        writeln!(
+            out,
+            "    RouteSpec {{ method: {:?}, path: {:?}, handler: {:?}, access: Access::{}, middleware: &{:?} }},",
+            route.method, route.path, handler, access, route.wraps
+        )
+        .unwrap();
+    }
+    out.push(']');
+
+    let dest = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("routes.rs");
+    std::fs::write(dest, out).unwrap();
+}
+
+/// A registered route, its path relative to the enclosing scope until the
+/// scope is done.
+#[derive(Clone)]
+struct Route {
+    method: String,
+    path: String,
+    /// Module path below `handlers`, then the function.
+    handler: Vec<String>,
+    wraps: Vec<String>,
+    cfgs: Vec<String>,
+}
+
+/// A scope or resource under construction.
+#[derive(Clone, Default)]
+struct Node {
+    path: String,
+    routes: Vec<Route>,
+    wraps: Vec<String>,
+}
+
+impl Node {
+    /// Its routes with the node's path and middleware applied.
+    fn finish(self) -> Vec<Route> {
+        self.routes
+            .into_iter()
+            .map(|mut route| {
+                route.path = format!("{}{}", self.path, route.path);
+                route.wraps.extend(self.wraps.iter().cloned());
+                route
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+enum Value {
+    /// The `ServiceConfig` a configure function registers on.
+    Config,
+    Node(Node),
+    /// `web::get().to(handler)` and friends.
+    Method {
+        method: String,
+        handler: Option<Vec<String>>,
+    },
+}
+
+/// Where a route-registering expression is evaluated.
+struct Scope<'a> {
+    module: &'a [String],
+    cfgs: Vec<String>,
+    locals: HashMap<String, Value>,
+    /// Routes registered directly on the `ServiceConfig`.
+    registered: Vec<Route>,
+}
+
+#[derive(Default)]
+struct Matrix {
+    files: HashMap<Vec<String>, syn::File>,
+}
+
+impl Matrix {
+    fn file(&mut self, module: &[String]) -> &syn::File {
+        self.files.entry(module.to_vec()).or_insert_with(|| {
+            let base = module
+                .iter()
+                .fold(PathBuf::from(HANDLERS), |path, m| path.join(m));
+            let path = [base.join("mod.rs"), base.with_extension("rs")]
+                .into_iter()
+                .find(|path| path.exists())
+                .unwrap_or_else(|| panic!("no source for handlers module {:?}", module));
+            parse(&path)
+        })
+    }
+
+    /// Every definition of `name` in `module`, one per `#[cfg]` variant.
+    fn functions(&mut self, module: &[String], name: &str) -> Vec<ItemFn> {
+        let functions: Vec<ItemFn> = self
+            .file(module)
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(function) if function.sig.ident == name => Some(function.clone()),
+                _ => None,
+            })
+            .collect();
+        assert!(
+            !functions.is_empty(),
+            "no function {} in handlers module {:?}",
+            name,
+            module
+        );
+        functions
+    }
+
+    /// Routes registered by a `fn(&mut web::ServiceConfig)`.
+    fn config_fn(&mut self, module: &[String], name: &str) -> Vec<Route> {
+        let mut routes = Vec::new();
+        for function in self.functions(module, name) {
+            let mut scope = Scope {
+                module,
+                cfgs: cfgs(&function.attrs),
+                locals: HashMap::new(),
+                registered: Vec::new(),
+            };
+            self.block(&mut scope, &function.block.stmts);
+            routes.extend(scope.registered);
+        }
+        routes
+    }
+
+    /// The scope or resource a function returns.
+    fn value_fn(&mut self, module: &[String], name: &str) -> Value {
+        let [function] = &self.functions(module, name)[..] else {
+            panic!("{} must be defined once to be followed", name);
+        };
+        let mut scope = Scope {
+            module,
+            cfgs: cfgs(&function.attrs),
+            locals: HashMap::new(),
+            registered: Vec::new(),
+        };
+        self.block(&mut scope, &function.block.stmts)
+            .unwrap_or_else(|| panic!("{} returns no scope", name))
+    }
+
+    /// Evaluate statements, returning the value of the tail expression.
+    fn block(&mut self, scope: &mut Scope, stmts: &[Stmt]) -> Option<Value> {
+        let mut tail = None;
+        for stmt in stmts {
+            match stmt {
+                Stmt::Local(local) => {
+                    let Pat::Ident(ident) = &local.pat else {
+                        continue;
+                    };
+                    let Some(init) = &local.init else { continue };
+                    // A cfg'd rebinding adds the routes under that cfg only
+                    let outer = scope.cfgs.len();
+                    scope.cfgs.extend(cfgs(&local.attrs));
+                    let value = self.expr(scope, &init.expr);
+                    scope.cfgs.truncate(outer);
+                    if let Some(value) = value {
+                        scope.locals.insert(ident.ident.to_string(), value);
+                    }
+                }
+                Stmt::Expr(expr, semi) => {
+                    let value = self.expr(scope, expr);
+                    if semi.is_none() {
+                        tail = value;
+                    }
+                }
+                _ => {}
+            }
+        }
+        tail
+    }
+
+    fn expr(&mut self, scope: &mut Scope, expr: &Expr) -> Option<Value> {
+        match expr {
+            Expr::Path(path) => {
+                let name = path.path.get_ident()?.to_string();
+                match name.as_str() {
+                    "cfg" => Some(Value::Config),
+                    _ => scope.locals.get(&name).cloned(),
+                }
+            }
+            Expr::Call(call) => {
+                let Expr::Path(func) = &*call.func else {
+                    return None;
+                };
+                let segments = segments(&func.path);
+                let outer = scope.cfgs.len();
+                scope.cfgs.extend(cfgs(&call.attrs));
+                let value = match segments.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+                    ["web", "scope"] | ["web", "resource"] => Some(Value::Node(Node {
+                        path: string_arg(&call.args[0]),
+                        ..Node::default()
+                    })),
+                    [
+                        "web",
+                        method @ ("get" | "post" | "put" | "patch" | "delete" | "head"),
+                    ] => Some(Value::Method {
+                        method: method.to_uppercase(),
+                        handler: None,
+                    }),
+                    _ => {
+                        let (module, name) = self.resolve(scope.module, &segments);
+                        if call.args.iter().any(is_config) {
+                            // Passing the `ServiceConfig` on, e.g. `admin::configure(cfg)`
+                            let routes = with_cfgs(self.config_fn(&module, &name), &scope.cfgs);
+                            scope.registered.extend(routes);
+                            Some(Value::Config)
+                        } else {
+                            Some(self.value_fn(&module, &name))
+                        }
+                    }
+                };
+                scope.cfgs.truncate(outer);
+                value
+            }
+            Expr::MethodCall(call) => {
+                let outer = scope.cfgs.len();
+                scope.cfgs.extend(cfgs(&call.attrs));
+                let receiver = self.expr(scope, &call.receiver);
+                let value = receiver.map(|receiver| self.method(scope, receiver, call));
+                scope.cfgs.truncate(outer);
+                value
+            }
+            _ => None,
+        }
+    }
+
+    fn method(&mut self, scope: &mut Scope, receiver: Value, call: &syn::ExprMethodCall) -> Value {
+        let method = call.method.to_string();
+        let args: Vec<&Expr> = call.args.iter().collect();
+        match (receiver, method.as_str()) {
+            (Value::Method { method, .. }, "to") => Value::Method {
+                method,
+                handler: Some(self.handler(scope.module, args[0])),
+            },
+            (receiver, "route") => {
+                let (path, route) = match args[..] {
+                    [path, route] => (string_arg(path), route),
+                    [route] => (String::new(), route),
+                    _ => panic!("unexpected route arguments"),
+                };
+                let Some(Value::Method {
+                    method,
+                    handler: Some(handler),
+                }) = self.expr(scope, route)
+                else {
+                    panic!("route {:?} without a handler", path);
+                };
+                let route = Route {
+                    method,
+                    path,
+                    handler,
+                    wraps: Vec::new(),
+                    cfgs: scope.cfgs.clone(),
+                };
+                add(scope, receiver, vec![route])
+            }
+            (receiver, "service") => {
+                let routes = match self.expr(scope, args[0]) {
+                    Some(Value::Node(node)) => node.finish(),
+                    _ => panic!("service argument is not a scope or resource"),
+                };
+                let routes = with_cfgs(routes, &scope.cfgs);
+                add(scope, receiver, routes)
+            }
+            (receiver, "configure") => {
+                let Expr::Path(func) = args[0] else {
+                    panic!("configure argument is not a function");
+                };
+                let (module, name) = self.resolve(scope.module, &segments(&func.path));
+                let routes = with_cfgs(self.config_fn(&module, &name), &scope.cfgs);
+                add(scope, receiver, routes)
+            }
+            (Value::Node(mut node), "wrap") => {
+                node.wraps.push(middleware(args[0]));
+                Value::Node(node)
+            }
+            (receiver, _) => receiver,
+        }
+    }
+
+    /// Module and function a (relative) path names.
+    fn resolve(&self, module: &[String], segments: &[String]) -> (Vec<String>, String) {
+        let (name, path) = segments.split_last().unwrap();
+        let mut module = module.to_vec();
+        module.extend(path.iter().cloned());
+        (module, name.clone())
+    }
+
+    fn handler(&self, module: &[String], expr: &Expr) -> Vec<String> {
+        let Expr::Path(path) = expr else {
+            panic!("handler is not a function path");
+        };
+        let (mut module, name) = self.resolve(module, &segments(&path.path));
+        module.push(name);
+        module
+    }
+
+    /// Access a handler requires, from its extractors.
+    fn access(&mut self, handler: &[String]) -> &'static str {
+        let (name, module) = handler.split_last().unwrap();
+        let function = &self.functions(module, name)[0];
+        let extractors: Vec<String> = function
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(arg) => match &*arg.ty {
+                    Type::Path(ty) => ty.path.segments.last().map(|s| s.ident.to_string()),
+                    _ => None,
+                },
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let has = |name: &str| extractors.iter().any(|e| e == name);
+        if has("Admin") {
+            "Admin"
+        } else if has("Identity") {
+            "Authenticated"
+        } else if has("OptionalIdentity") {
+            "Optional"
+        } else {
+            "Public"
+        }
+    }
+}
+
+/// Register `routes` on the receiver: straight into the `ServiceConfig`, or
+/// onto the scope being built.
+fn add(scope: &mut Scope, receiver: Value, routes: Vec<Route>) -> Value {
+    match receiver {
+        Value::Config => {
+            scope.registered.extend(routes);
+            Value::Config
+        }
+        Value::Node(mut node) => {
+            node.routes.extend(routes);
+            Value::Node(node)
+        }
+        Value::Method { .. } => panic!("routes registered on a method guard"),
+    }
+}
+
+fn with_cfgs(routes: Vec<Route>, cfgs: &[String]) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            for cfg in cfgs {
+                if !route.cfgs.contains(cfg) {
+                    route.cfgs.push(cfg.clone());
+                }
+            }
+            route
+        })
+        .collect()
+}
+
+/// Name of a middleware, with its argument if it is a path (e.g.
+/// `RequireEntitlement(Exports)`).
+fn middleware(expr: &Expr) -> String {
+    let Expr::Call(call) = expr else {
+        return expr.to_token_stream().to_string();
+    };
+    let Expr::Path(func) = &*call.func else {
+        return expr.to_token_stream().to_string();
+    };
+    let name = segments(&func.path)[0].clone();
+    match call.args.first() {
+        Some(Expr::Path(arg)) => format!("{}({})", name, segments(&arg.path).last().unwrap()),
+        _ => name,
+    }
+}
+
+fn is_config(expr: &Expr) -> bool {
+    matches!(expr, Expr::Path(path) if path.path.is_ident("cfg"))
+}
+
+fn cfgs(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .map(|attr| attr.to_token_stream().to_string())
+        .collect()
+}
+
+fn segments(path: &syn::Path) -> Vec<String> {
+    path.segments.iter().map(|s| s.ident.to_string()).collect()
+}
+
+fn string_arg(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => s.value(),
+            _ => panic!("route path is not a string"),
+        },
+        _ => panic!("route path is not a literal"),
+    }
+}
+
+fn parse(path: &Path) -> syn::File {
+    let source = std::fs::read_to_string(path).unwrap();
+    syn::parse_file(&source).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
@@ -1,8 +1,13 @@
 //! Cron-style job scheduler using tokio-cron-scheduler.
+//!
+//! Tasks run here in-process with no retries or bookkeeping. Periodic work
+//! that should be retried and show up in the queue stats belongs on the job
+//! queue instead, via `JobQueue::enqueue_recurring`.
 
-use std::sync::Arc;
 use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
 
+use crate::env;
+
 /// Scheduler configuration.
 #[derive(Debug, Clone)]
 pub struct SchedulerConfig {
@@ -19,9 +24,7 @@ impl Default for SchedulerConfig {
 impl SchedulerConfig {
     pub fn from_env() -> Self {
         Self {
-            enabled: std::env::var("SCHEDULER_ENABLED")
-                .map(|v| v != "false" && v != "0")
-                .unwrap_or(true),
+            enabled: env::SCHEDULER_ENABLED.flag(),
         }
     }
 }
@@ -68,32 +71,6 @@ impl Scheduler {
         Ok(id)
     }
 
-    /// Add a one-shot delayed job.
-    pub async fn add_one_shot<F, Fut>(
-        &self,
-        delay: std::time::Duration,
-        task: F,
-    ) -> Result<uuid::Uuid, JobSchedulerError>
-    where
-        F: FnOnce() -> Fut + Send + Sync + 'static,
-        Fut: std::future::Future<Output = ()> + Send + 'static,
-    {
-        let task = Arc::new(tokio::sync::Mutex::new(Some(task)));
-
-        let job = Job::new_one_shot_async(delay, move |_uuid, _lock| {
-            let task = task.clone();
-            Box::pin(async move {
-                if let Some(t) = task.lock().await.take() {
-                    t().await;
-                }
-            })
-        })?;
-
-        let id = self.inner.add(job).await?;
-        tracing::info!(delay_secs = delay.as_secs(), job_id = %id, "One-shot job scheduled");
-        Ok(id)
-    }
-
     /// Start the scheduler.
     pub async fn start(&self) -> Result<(), JobSchedulerError> {
         if !self.config.enabled {
@@ -1,8 +1,11 @@
 //! Application configuration loaded from environment variables.
 
-use std::env;
+use std::time::Duration;
 
-use apex_infra::database::{DatabaseConfig, SecondaryDbConfig};
+use apex_core::domain::FeatureFlags;
+use apex_infra::database::{DatabaseConfig, SecondaryDbConfig, TenancyMode};
+
+use crate::env;
 
 /// Application configuration.
 #[derive(Debug, Clone)]
@@ -10,36 +13,66 @@ pub struct AppConfig {
     pub host: String,
     pub port: u16,
     pub database: Option<DatabaseConfig>,
+    /// Sandbox mode: outbound integrations are recorded instead of delivered.
+    pub sandbox: bool,
+    /// How often in-process usage counters are written to storage.
+    pub usage_flush_interval: Duration,
+    /// How long to wait for running jobs to finish on shutdown.
+    pub job_shutdown_timeout: Duration,
+    /// Deadline set on each request's context.
+    pub request_timeout: Duration,
+    /// Feature flag defaults; admins can override them per request.
+    pub feature_flags: FeatureFlags,
 }
 
 impl AppConfig {
     /// Load configuration from environment variables.
     pub fn from_env() -> Self {
-        let database = env::var("DATABASE_URL").ok().map(|main_url| {
+        let database = env::DATABASE_URL.raw().map(|main_url| {
             // Parse secondary databases from SECONDARY_DB_* env vars
             let secondary_databases = Self::parse_secondary_databases();
 
             DatabaseConfig {
                 main_url,
-                main_max_connections: env::var("DB_MAX_CONNECTIONS")
-                    .ok()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(100),
-                main_min_connections: env::var("DB_MIN_CONNECTIONS")
-                    .ok()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(10),
+                main_max_connections: env::DB_MAX_CONNECTIONS.get(),
+                main_min_connections: env::DB_MIN_CONNECTIONS.get(),
                 secondary_databases,
+                connect_attempts: env::DB_CONNECT_ATTEMPTS.get::<u32>().max(1),
+                connect_backoff: env::DB_CONNECT_BACKOFF_MS.millis(),
+                tenancy: TenancyMode::parse(&env::TENANCY_MODE.string()).unwrap_or_default(),
+                slow_query_threshold: env::DB_SLOW_QUERY_MS.millis(),
             }
         });
 
         Self {
-            host: env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            port: env::var("PORT")
-                .ok()
-                .and_then(|p| p.parse().ok())
-                .unwrap_or(8080),
+            host: env::HOST.string(),
+            port: env::PORT.get(),
             database,
+            sandbox: Self::parse_sandbox(),
+            usage_flush_interval: env::USAGE_FLUSH_INTERVAL_SECS.secs(),
+            job_shutdown_timeout: env::JOB_SHUTDOWN_TIMEOUT_SECS.secs(),
+            request_timeout: env::REQUEST_TIMEOUT_SECS.secs(),
+            feature_flags: Self::parse_feature_flags(),
+        }
+    }
+
+    /// Parse feature flag defaults from FEATURE_FLAGS.
+    /// Example: FEATURE_FLAGS=new_checkout,beta_search=off
+    fn parse_feature_flags() -> FeatureFlags {
+        FeatureFlags::parse(&env::FEATURE_FLAGS.string()).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Ignoring invalid FEATURE_FLAGS");
+            FeatureFlags::new()
+        })
+    }
+
+    /// Sandbox is on by default everywhere except production, so staging and
+    /// development never reach real third parties unless explicitly opted in.
+    /// Set SANDBOX_MODE=false to deliver for real outside production.
+    fn parse_sandbox() -> bool {
+        if env::SANDBOX_MODE.is_set() {
+            env::SANDBOX_MODE.flag()
+        } else {
+            !apex_infra::env::is_production()
         }
     }
 
@@ -49,7 +82,7 @@ impl AppConfig {
     fn parse_secondary_databases() -> Vec<SecondaryDbConfig> {
         let mut secondary = Vec::new();
 
-        for (key, value) in env::vars() {
+        for (key, value) in std::env::vars() {
             if let Some(name) = key.strip_prefix("SECONDARY_DB_") {
                 let parts: Vec<&str> = value.splitn(2, ',').collect();
                 if let Some(url) = parts.first() {
@@ -0,0 +1,299 @@
+//! Environment variables read by the server itself.
+//!
+//! Declared like the infrastructure's in [`apex_infra::env`]; [`catalog`]
+//! puts both lists together for startup validation and `--env-reference`.
+
+use apex_infra::env::EnvKind::{Decimal, Flag, Integer, OneOf, Text};
+use apex_infra::env::{Catalog, EnvVar};
+
+// Server
+
+pub const HOST: EnvVar =
+    EnvVar::new("HOST", Text, "Address the server listens on.").with_default("127.0.0.1");
+pub const PORT: EnvVar =
+    EnvVar::new("PORT", Integer, "Port the server listens on.").with_default("8080");
+pub const REQUEST_TIMEOUT_SECS: EnvVar = EnvVar::new(
+    "REQUEST_TIMEOUT_SECS",
+    Integer,
+    "Deadline set on each request's context.",
+)
+.with_default("30");
+pub const USAGE_FLUSH_INTERVAL_SECS: EnvVar = EnvVar::new(
+    "USAGE_FLUSH_INTERVAL_SECS",
+    Integer,
+    "How often usage counters are written to storage.",
+)
+.with_default("60");
+pub const JOB_SHUTDOWN_TIMEOUT_SECS: EnvVar = EnvVar::new(
+    "JOB_SHUTDOWN_TIMEOUT_SECS",
+    Integer,
+    "How long shutdown waits for running jobs.",
+)
+.with_default("30");
+pub const FEATURE_FLAGS: EnvVar = EnvVar::new(
+    "FEATURE_FLAGS",
+    Text,
+    "Feature flag defaults, e.g. `new_checkout,beta_search=off`.",
+);
+pub const SANDBOX_MODE: EnvVar = EnvVar::new(
+    "SANDBOX_MODE",
+    Flag,
+    "Record outbound integrations instead of delivering them; on outside production when unset.",
+);
+pub const CANARY_ROLLOUTS: EnvVar = EnvVar::new(
+    "CANARY_ROLLOUTS",
+    Text,
+    "Percentage of users in each canary, e.g. `plan_v2=10`.",
+);
+pub const API_DEPRECATIONS: EnvVar = EnvVar::new(
+    "API_DEPRECATIONS",
+    Text,
+    "Deprecated API versions and their sunset dates, e.g. `v1=2027-06-30`.",
+);
+pub const TENANT_BASE_DOMAIN: EnvVar = EnvVar::new(
+    "TENANT_BASE_DOMAIN",
+    Text,
+    "Domain whose subdomains name tenants, e.g. `apex.example.com`.",
+);
+
+// Database
+
+pub const DATABASE_URL: EnvVar = EnvVar::new(
+    "DATABASE_URL",
+    Text,
+    "Main database; users and posts are kept in memory when unset.",
+)
+.for_feature("postgres");
+pub const DB_MAX_CONNECTIONS: EnvVar =
+    EnvVar::new("DB_MAX_CONNECTIONS", Integer, "Main pool size.")
+        .with_default("100")
+        .for_feature("postgres");
+pub const DB_MIN_CONNECTIONS: EnvVar =
+    EnvVar::new("DB_MIN_CONNECTIONS", Integer, "Idle connections kept open.")
+        .with_default("10")
+        .for_feature("postgres");
+pub const DB_CONNECT_ATTEMPTS: EnvVar = EnvVar::new(
+    "DB_CONNECT_ATTEMPTS",
+    Integer,
+    "Connection attempts at startup.",
+)
+.with_default("5")
+.for_feature("postgres");
+pub const DB_CONNECT_BACKOFF_MS: EnvVar = EnvVar::new(
+    "DB_CONNECT_BACKOFF_MS",
+    Integer,
+    "Wait before the second attempt, doubled after each.",
+)
+.with_default("500")
+.for_feature("postgres");
+pub const TENANCY_MODE: EnvVar = EnvVar::new(
+    "TENANCY_MODE",
+    OneOf(&["none", "column"]),
+    "How tenants' posts are kept apart.",
+)
+.with_default("none")
+.for_feature("postgres");
+pub const DB_SLOW_QUERY_MS: EnvVar = EnvVar::new(
+    "DB_SLOW_QUERY_MS",
+    Integer,
+    "Queries at least this slow are logged at WARN with their SQL; 0 logs none.",
+)
+.with_default("500")
+.for_feature("postgres");
+pub const SECONDARY_DB: EnvVar = EnvVar::new(
+    "SECONDARY_DB_<NAME>",
+    Text,
+    "Secondary database as `<url>,<max connections>`.",
+)
+.for_feature("postgres");
+
+// Logging and alerts
+
+pub const RUST_LOG: EnvVar = EnvVar::new("RUST_LOG", Text, "Log filter.")
+    .with_default("info,api_server=debug,apex_infra=debug");
+pub const LOG_FORMAT: EnvVar = EnvVar::new(
+    "LOG_FORMAT",
+    OneOf(&["pretty", "json"]),
+    "Log output format.",
+)
+.with_default("pretty");
+pub const OTEL_SERVICE_NAME: EnvVar = EnvVar::new(
+    "OTEL_SERVICE_NAME",
+    Text,
+    "Service name on logs and alerts.",
+)
+.with_default("apex-api");
+pub const ALERTS_ENABLED: EnvVar = EnvVar::new(
+    "ALERTS_ENABLED",
+    Flag,
+    "Send critical errors and alert rules to the alert webhook.",
+)
+.with_default("true");
+pub const ALERT_WEBHOOK_URL: EnvVar = EnvVar::new(
+    "ALERT_WEBHOOK_URL",
+    Text,
+    "Webhook alerts are posted to (Slack, Discord, etc.).",
+);
+pub const ALERT_ERROR_RATE_PERCENT: EnvVar = EnvVar::new(
+    "ALERT_ERROR_RATE_PERCENT",
+    Decimal,
+    "Alert when this share of requests fail.",
+);
+pub const ALERT_P99_MS: EnvVar = EnvVar::new(
+    "ALERT_P99_MS",
+    Integer,
+    "Alert when the p99 latency exceeds this.",
+);
+pub const ALERT_QUEUE_DEPTH: EnvVar = EnvVar::new(
+    "ALERT_QUEUE_DEPTH",
+    Integer,
+    "Alert when this many jobs are waiting.",
+);
+pub const ALERT_RULES_INTERVAL_SECS: EnvVar = EnvVar::new(
+    "ALERT_RULES_INTERVAL_SECS",
+    Integer,
+    "How often the alert rules are evaluated.",
+)
+.with_default("60");
+pub const ALERT_COOLDOWN_SECS: EnvVar = EnvVar::new(
+    "ALERT_COOLDOWN_SECS",
+    Integer,
+    "Minimum time between two alerts of a rule.",
+)
+.with_default("900");
+pub const ALERT_MIN_REQUESTS: EnvVar = EnvVar::new(
+    "ALERT_MIN_REQUESTS",
+    Integer,
+    "Requests needed in an interval before the request rules fire.",
+)
+.with_default("20");
+pub const METRICS_ENABLED: EnvVar = EnvVar::new(
+    "METRICS_ENABLED",
+    Flag,
+    "Serve Prometheus metrics at /metrics.",
+)
+.with_default("false");
+pub const METRICS_MAX_SERIES: EnvVar = EnvVar::new(
+    "METRICS_MAX_SERIES",
+    Integer,
+    "Series kept per metric family.",
+)
+.with_default("2000");
+pub const HEAP_PROFILE_DIR: EnvVar = EnvVar::new(
+    "HEAP_PROFILE_DIR",
+    Text,
+    "Directory heap profiles are written to; the temp directory when unset.",
+)
+.for_feature("jemalloc");
+
+// Uploads
+
+pub const UPLOAD_MAX_BYTES: EnvVar = EnvVar::new(
+    "UPLOAD_MAX_BYTES",
+    Integer,
+    "Largest file `POST /api/uploads` accepts.",
+)
+.with_default("10485760")
+.for_feature("storage");
+pub const UPLOAD_ALLOWED_TYPES: EnvVar = EnvVar::new(
+    "UPLOAD_ALLOWED_TYPES",
+    Text,
+    "Media types uploads may have, e.g. `image/*,application/pdf`; `*/*` for any.",
+)
+.with_default("image/*,application/pdf,text/plain")
+.for_feature("storage");
+pub const UPLOAD_SCAN_ENABLED: EnvVar = EnvVar::new(
+    "UPLOAD_SCAN_ENABLED",
+    Flag,
+    "Queue a `scan_attachment` job for each upload.",
+)
+.with_default("true")
+.for_feature("storage");
+
+// Background work
+
+pub const SCHEDULER_ENABLED: EnvVar =
+    EnvVar::new("SCHEDULER_ENABLED", Flag, "Run the cron scheduler.")
+        .with_default("true")
+        .for_feature("scheduler");
+pub const WS_HEARTBEAT_SECS: EnvVar = EnvVar::new(
+    "WS_HEARTBEAT_SECS",
+    Integer,
+    "Interval between WebSocket pings.",
+)
+.with_default("25")
+.for_feature("websocket");
+pub const WS_HEARTBEAT_TIMEOUT_SECS: EnvVar = EnvVar::new(
+    "WS_HEARTBEAT_TIMEOUT_SECS",
+    Integer,
+    "How long a WebSocket client has to answer a ping.",
+)
+.with_default("10")
+.for_feature("websocket");
+pub const SSE_KEEPALIVE_SECS: EnvVar = EnvVar::new(
+    "SSE_KEEPALIVE_SECS",
+    Integer,
+    "Interval between keep-alive comments on idle event streams.",
+)
+.with_default("15");
+
+/// Every variable read by the server.
+pub static VARS: &[EnvVar] = &[
+    HOST,
+    PORT,
+    REQUEST_TIMEOUT_SECS,
+    USAGE_FLUSH_INTERVAL_SECS,
+    JOB_SHUTDOWN_TIMEOUT_SECS,
+    FEATURE_FLAGS,
+    SANDBOX_MODE,
+    CANARY_ROLLOUTS,
+    API_DEPRECATIONS,
+    TENANT_BASE_DOMAIN,
+    DATABASE_URL,
+    DB_MAX_CONNECTIONS,
+    DB_MIN_CONNECTIONS,
+    DB_CONNECT_ATTEMPTS,
+    DB_CONNECT_BACKOFF_MS,
+    TENANCY_MODE,
+    DB_SLOW_QUERY_MS,
+    SECONDARY_DB,
+    RUST_LOG,
+    LOG_FORMAT,
+    OTEL_SERVICE_NAME,
+    ALERTS_ENABLED,
+    ALERT_WEBHOOK_URL,
+    ALERT_ERROR_RATE_PERCENT,
+    ALERT_P99_MS,
+    ALERT_QUEUE_DEPTH,
+    ALERT_RULES_INTERVAL_SECS,
+    ALERT_COOLDOWN_SECS,
+    ALERT_MIN_REQUESTS,
+    METRICS_ENABLED,
+    METRICS_MAX_SERIES,
+    HEAP_PROFILE_DIR,
+    UPLOAD_MAX_BYTES,
+    UPLOAD_ALLOWED_TYPES,
+    UPLOAD_SCAN_ENABLED,
+    SCHEDULER_ENABLED,
+    WS_HEARTBEAT_SECS,
+    WS_HEARTBEAT_TIMEOUT_SECS,
+    SSE_KEEPALIVE_SECS,
+];
+
+/// The infrastructure's variables and the server's.
+pub fn catalog() -> Catalog {
+    Catalog::infra().with(VARS, feature_enabled)
+}
+
+/// Compile-time features the server's variables depend on.
+const FEATURES: &[(&str, bool)] = &[
+    ("postgres", cfg!(feature = "postgres")),
+    ("scheduler", cfg!(feature = "scheduler")),
+    ("websocket", cfg!(feature = "websocket")),
+    ("storage", cfg!(feature = "storage")),
+    ("jemalloc", cfg!(feature = "jemalloc")),
+];
+
+fn feature_enabled(feature: &str) -> bool {
+    FEATURES.contains(&(feature, true))
+}
@@ -0,0 +1,47 @@
+//! Field guards on the caller's [`Identity`].
+
+use async_graphql::{Context, Guard, Result};
+use uuid::Uuid;
+
+use crate::middleware::auth::ADMIN_ROLE;
+use crate::middleware::error::AppError;
+
+use super::{error, identity};
+
+/// Any signed-in user.
+pub struct SignedIn;
+
+impl Guard for SignedIn {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        identity(ctx)
+            .map(|_| ())
+            .ok_or_else(|| error(AppError::Unauthorized))
+    }
+}
+
+/// The admin role.
+pub struct AdminOnly;
+
+impl Guard for AdminOnly {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        match identity(ctx) {
+            Some(identity) if identity.has_role(ADMIN_ROLE) => Ok(()),
+            Some(_) => Err(error(AppError::Forbidden)),
+            None => Err(error(AppError::Unauthorized)),
+        }
+    }
+}
+
+/// The user with this id, or an admin: for fields that are nobody else's
+/// business, such as an email address.
+pub struct SelfOrAdmin(pub Uuid);
+
+impl Guard for SelfOrAdmin {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        match identity(ctx) {
+            Some(identity) if identity.user_id == self.0 || identity.has_role(ADMIN_ROLE) => Ok(()),
+            Some(_) => Err(error(AppError::Forbidden)),
+            None => Err(error(AppError::Unauthorized)),
+        }
+    }
+}
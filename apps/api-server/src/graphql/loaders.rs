@@ -0,0 +1,41 @@
+//! Batched loads for resolvers.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::dataloader::Loader;
+use uuid::Uuid;
+
+use apex_core::domain::{Filter, PageRequest, User};
+use apex_core::error::RepoError;
+use apex_core::ports::UserRepository;
+
+use crate::middleware::error::AppError;
+
+/// Users by id, all of a batch in one `find_page` query.
+pub struct UserLoader {
+    users: Arc<dyn UserRepository>,
+}
+
+impl UserLoader {
+    pub fn new(users: Arc<dyn UserRepository>) -> Self {
+        Self { users }
+    }
+}
+
+impl Loader<Uuid> for UserLoader {
+    type Value = User;
+    type Error = Arc<RepoError>;
+
+    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, User>, Self::Error> {
+        let request =
+            PageRequest::new(0, keys.len() as u64).filtered_by(Filter::is_in("id", keys.to_vec()));
+        let page = self.users.find_page(request).await.map_err(Arc::new)?;
+        Ok(page.items.into_iter().map(|user| (user.id, user)).collect())
+    }
+}
+
+/// A GraphQL error for a failed batch.
+pub fn error(err: Arc<RepoError>) -> async_graphql::Error {
+    super::error(AppError::Internal(err.to_string()))
+}
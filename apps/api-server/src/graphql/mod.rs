@@ -0,0 +1,177 @@
+//! GraphQL API over users and posts, at `/api/graphql`.
+//!
+//! Queries read what the REST endpoints would let the caller read: posts
+//! they wrote or that belong to their organization, published posts for
+//! anyone, accounts for admins. Field guards ([`guards`]) check the caller's
+//! [`Identity`], which the handler adds to the request data when the
+//! request is signed in.
+//!
+//! Authors are loaded through a [`DataLoader`] created per request, so the
+//! authors of a page of posts take one query rather than one per post, and
+//! nothing loaded for one caller is served to another.
+
+mod guards;
+mod loaders;
+mod types;
+
+use actix_web::{ResponseError, web};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, Request, Result, Schema,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use apex_core::domain::{PageCursor, PageRequest};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppError;
+use crate::state::AppState;
+
+use guards::{AdminOnly, SignedIn};
+use loaders::UserLoader;
+use types::{PostObject, PostPage, UserObject};
+
+pub type ApexSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// Deepest nesting a query may have.
+const MAX_DEPTH: usize = 8;
+/// Most fields a query may resolve, lists counted once.
+const MAX_COMPLEXITY: usize = 500;
+
+/// The schema, without per-request data.
+pub fn schema() -> ApexSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// `request` with the data its resolvers need: the app state, fresh
+/// loaders and the caller, if signed in.
+pub fn with_caller(
+    request: Request,
+    state: web::Data<AppState>,
+    identity: Option<Identity>,
+) -> Request {
+    let users = DataLoader::new(UserLoader::new(state.users.load()), tokio::spawn)
+        .max_batch_size(PageRequest::MAX_LIMIT as usize);
+    let request = request.data(state).data(users);
+    match identity {
+        Some(identity) => request.data(identity),
+        None => request,
+    }
+}
+
+/// The caller, if signed in.
+fn identity<'a>(ctx: &Context<'a>) -> Option<&'a Identity> {
+    ctx.data_opt::<Identity>()
+}
+
+fn state<'a>(ctx: &Context<'a>) -> &'a AppState {
+    ctx.data_unchecked::<web::Data<AppState>>()
+}
+
+/// A GraphQL error for `err`, with the status the REST API would answer
+/// with in its `status` extension. Server errors are logged, not shown.
+fn error(err: impl Into<AppError>) -> async_graphql::Error {
+    let err = err.into();
+    let status = err.status_code();
+    let message = if status.is_server_error() {
+        tracing::error!(error = %err, "GraphQL resolver failed");
+        "Internal server error".to_string()
+    } else {
+        err.to_string()
+    };
+    async_graphql::Error::new(message).extend_with(|_, e| e.set("status", status.as_u16()))
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// The signed-in user.
+    #[graphql(guard = "SignedIn")]
+    async fn me(&self, ctx: &Context<'_>) -> Result<UserObject> {
+        let identity = identity(ctx).ok_or_else(|| error(AppError::Unauthorized))?;
+        let user = state(ctx)
+            .user_service()
+            .get(identity.user_id)
+            .await
+            .map_err(error)?;
+        Ok(UserObject(user))
+    }
+
+    /// Any user, by id.
+    #[graphql(guard = "AdminOnly")]
+    async fn user(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<UserObject>> {
+        let users = ctx.data_unchecked::<DataLoader<UserLoader>>();
+        let user = users.load_one(id).await.map_err(loaders::error)?;
+        Ok(user.map(UserObject))
+    }
+
+    /// A post the caller may read: their own, their organization's or a
+    /// published one. Null for any other.
+    async fn post(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<PostObject>> {
+        let post = state(ctx)
+            .posts
+            .load()
+            .find_by_id(id)
+            .await
+            .map_err(error)?;
+        let now = Utc::now();
+        let readable = |post: &apex_core::domain::Post| match identity(ctx) {
+            Some(identity) => post.readable_by(
+                identity.user_id,
+                identity.org.as_ref().map(|org| org.id),
+                now,
+            ),
+            None => post.is_published(now),
+        };
+        Ok(post.filter(readable).map(PostObject))
+    }
+
+    /// The caller's posts, newest first, paged by cursor.
+    #[graphql(guard = "SignedIn")]
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        #[graphql(default = 20)] limit: u64,
+    ) -> Result<PostPage> {
+        let identity = identity(ctx).ok_or_else(|| error(AppError::Unauthorized))?;
+        let after = after
+            .as_deref()
+            .map(PageCursor::decode)
+            .transpose()
+            .map_err(error)?;
+        let (posts, next) = state(ctx)
+            .post_service()
+            .list(
+                identity.user_id,
+                after,
+                limit.clamp(1, PageRequest::MAX_LIMIT),
+            )
+            .await
+            .map_err(error)?;
+        Ok(PostPage {
+            items: posts.into_iter().map(PostObject).collect(),
+            next_cursor: next.map(|cursor| cursor.encode()),
+        })
+    }
+
+    /// Published posts, newest first.
+    async fn published_posts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20)] limit: u64,
+    ) -> Result<Vec<PostObject>> {
+        let posts = state(ctx)
+            .posts
+            .load()
+            .list_published(Utc::now(), limit.clamp(1, PageRequest::MAX_LIMIT))
+            .await
+            .map_err(error)?;
+        Ok(posts.into_iter().map(PostObject).collect())
+    }
+}
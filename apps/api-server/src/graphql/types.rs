@@ -0,0 +1,95 @@
+//! Output types over the domain's users and posts.
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Object, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use apex_core::domain::{Post, User};
+
+use super::guards::SelfOrAdmin;
+use super::loaders::{self, UserLoader};
+use super::state;
+
+pub struct UserObject(pub User);
+
+#[Object(name = "User")]
+impl UserObject {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    #[graphql(guard = "SelfOrAdmin(self.0.id)")]
+    async fn email(&self) -> String {
+        self.0.email.to_string()
+    }
+
+    #[graphql(guard = "SelfOrAdmin(self.0.id)")]
+    async fn roles(&self) -> Vec<String> {
+        self.0.roles.clone()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+}
+
+pub struct PostObject(pub Post);
+
+#[Object(name = "Post")]
+impl PostObject {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    /// Owning organization, if the post is org-scoped.
+    async fn organization_id(&self) -> Option<Uuid> {
+        self.0.organization_id
+    }
+
+    async fn version(&self) -> i64 {
+        self.0.version
+    }
+
+    /// Views so far, including those not flushed to the database yet.
+    async fn view_count(&self, ctx: &Context<'_>) -> i64 {
+        self.0.view_count + state(ctx).post_views.pending(self.0.id).await
+    }
+
+    async fn published_at(&self) -> Option<DateTime<Utc>> {
+        self.0.published_at
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// The author, loaded with the other authors of the response.
+    async fn author(&self, ctx: &Context<'_>) -> Result<Option<UserObject>> {
+        let users = ctx.data_unchecked::<DataLoader<UserLoader>>();
+        let user = users
+            .load_one(self.0.user_id)
+            .await
+            .map_err(loaders::error)?;
+        Ok(user.map(UserObject))
+    }
+}
+
+/// One page of posts and the cursor of the next.
+#[derive(SimpleObject)]
+pub struct PostPage {
+    pub items: Vec<PostObject>,
+    pub next_cursor: Option<String>,
+}
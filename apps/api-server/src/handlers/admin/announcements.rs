@@ -0,0 +1,159 @@
+//! Announcement management.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use apex_core::domain::{Announcement, Audience, apply_patch, parse_client_id};
+use apex_infra::announcements::{ANNOUNCEMENT_PUBLISHED, ANNOUNCEMENTS_CHANNEL};
+use apex_infra::{InMemoryPubSub, TypedPubSub};
+use apex_shared::dto::{AnnouncementRequest, AnnouncementResponse};
+
+use crate::handlers::announcements::to_response;
+use crate::handlers::id_taken;
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+use crate::middleware::patch::PatchBody;
+use crate::middleware::validation::ValidatedJson;
+use crate::state::AppState;
+
+const LIST_LIMIT: u64 = 100;
+
+/// GET /api/admin/announcements - Every announcement, including past and scheduled ones
+pub async fn list(_admin: Admin, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let announcements = state.announcements.list_recent(LIST_LIMIT).await?;
+    let body: Vec<AnnouncementResponse> = announcements.iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// POST /api/admin/announcements - Publish an announcement
+///
+/// Announcements that are live right away are pushed to WebSocket clients
+/// in the audience's room.
+pub async fn create(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    pubsub: web::Data<Arc<TypedPubSub<InMemoryPubSub>>>,
+    body: ValidatedJson<AnnouncementRequest>,
+) -> AppResult<HttpResponse> {
+    let req = body.into_inner();
+    let audience = parse_audience(req.audience.as_deref())?;
+    let mut announcement = Announcement::new(
+        req.title,
+        req.body,
+        audience,
+        req.starts_at.unwrap_or_else(chrono::Utc::now),
+        req.ends_at,
+    )?;
+
+    if let Some(id) = req.id.as_deref().map(parse_client_id).transpose()? {
+        // A retry gets the announcement the first attempt published
+        if let Some(existing) = state.announcements.get(id).await? {
+            if existing.title == announcement.title {
+                return Ok(HttpResponse::Ok().json(to_response(&existing)));
+            }
+            return Err(id_taken(id));
+        }
+        announcement.id = id;
+    }
+
+    let announcement = state.announcements.create(announcement).await?;
+    tracing::info!(
+        admin_id = %admin.user_id,
+        announcement_id = %announcement.id,
+        audience = %announcement.audience,
+        "Announcement published"
+    );
+
+    if announcement.is_live(chrono::Utc::now()) {
+        push(&pubsub, &announcement).await;
+    }
+
+    Ok(HttpResponse::Created().json(to_response(&announcement)))
+}
+
+/// PUT /api/admin/announcements/{id} - Replace an announcement's content and schedule
+pub async fn update(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: ValidatedJson<AnnouncementRequest>,
+) -> AppResult<HttpResponse> {
+    let announcement = find(&state, path.into_inner()).await?;
+    revise(&state, announcement, body.into_inner()).await
+}
+
+/// PATCH /api/admin/announcements/{id} - Merge patch or JSON patch of the PUT body
+pub async fn patch(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: PatchBody,
+) -> AppResult<HttpResponse> {
+    let announcement = find(&state, path.into_inner()).await?;
+    let current = AnnouncementRequest {
+        id: None,
+        title: announcement.title.clone(),
+        body: announcement.body.clone(),
+        audience: Some(announcement.audience.to_string()),
+        starts_at: Some(announcement.starts_at),
+        ends_at: announcement.ends_at,
+    };
+    let req = apply_patch(&current, &body.0)?;
+    revise(&state, announcement, req).await
+}
+
+async fn find(state: &AppState, id: uuid::Uuid) -> AppResult<Announcement> {
+    state
+        .announcements
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Announcement {} not found", id)))
+}
+
+async fn revise(
+    state: &AppState,
+    mut announcement: Announcement,
+    req: AnnouncementRequest,
+) -> AppResult<HttpResponse> {
+    let audience = parse_audience(req.audience.as_deref())?;
+    announcement.revise(
+        req.title,
+        req.body,
+        audience,
+        req.starts_at.unwrap_or(announcement.starts_at),
+        req.ends_at,
+    )?;
+
+    let announcement = state.announcements.save(announcement).await?;
+    Ok(HttpResponse::Ok().json(to_response(&announcement)))
+}
+
+/// DELETE /api/admin/announcements/{id}
+pub async fn delete(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    state.announcements.delete(path.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn parse_audience(audience: Option<&str>) -> AppResult<Audience> {
+    Ok(audience
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or(Audience::Everyone))
+}
+
+async fn push(pubsub: &TypedPubSub<InMemoryPubSub>, announcement: &Announcement) {
+    if let Err(e) = pubsub
+        .publish_json(
+            ANNOUNCEMENTS_CHANNEL,
+            ANNOUNCEMENT_PUBLISHED,
+            to_response(announcement),
+        )
+        .await
+    {
+        tracing::warn!(error = %e, announcement_id = %announcement.id, "Failed to push announcement");
+    }
+}
@@ -0,0 +1,163 @@
+//! Two-person approval of destructive admin actions.
+//!
+//! Purging the job queue, releasing a legal hold and granting the admin role
+//! are staged instead of run, and run here once a second admin approves them.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use apex_core::domain::{AdminAction, ApprovalStatus, LegalHoldChange, PendingOperation};
+use apex_core::ports::JobQueue;
+use apex_infra::InMemoryJobQueue;
+use apex_shared::dto::PendingOperationResponse;
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: u64 = 50;
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListApprovalsQuery {
+    /// Only return operations in this status, e.g. `pending`.
+    pub status: Option<String>,
+    pub limit: Option<u64>,
+}
+
+/// GET /api/admin/approvals - Staged operations, newest first
+pub async fn list(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    query: web::Query<ListApprovalsQuery>,
+) -> AppResult<HttpResponse> {
+    let status = query
+        .status
+        .as_deref()
+        .map(str::parse::<ApprovalStatus>)
+        .transpose()?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let operations = state.approvals.list(status, limit).await?;
+
+    let body: Vec<PendingOperationResponse> = operations.into_iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// GET /api/admin/approvals/{id}
+pub async fn get(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let operation = state.approvals.get(path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(to_response(operation)))
+}
+
+/// POST /api/admin/approvals/{id}/approve - Approve and run a staged operation
+///
+/// Only another admin than the one who staged it can approve it. The
+/// response carries the outcome of running it.
+pub async fn approve(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let operation = state
+        .approvals
+        .approve(path.into_inner(), admin.user_id)
+        .await?;
+    let result = execute(&state, &queue, &operation).await;
+    let operation = state.approvals.finish(operation, result).await?;
+    Ok(HttpResponse::Ok().json(to_response(operation)))
+}
+
+/// POST /api/admin/approvals/{id}/reject - Reject, or withdraw one's own operation
+pub async fn reject(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let operation = state
+        .approvals
+        .reject(path.into_inner(), admin.user_id)
+        .await?;
+    Ok(HttpResponse::Ok().json(to_response(operation)))
+}
+
+/// Stage `action` for approval: 202 with the operation to approve.
+pub(super) async fn stage(
+    state: &AppState,
+    action: AdminAction,
+    admin_id: uuid::Uuid,
+) -> AppResult<HttpResponse> {
+    let operation = state.approvals.stage(action, admin_id).await?;
+    Ok(HttpResponse::Accepted().json(to_response(operation)))
+}
+
+/// Run an approved operation, describing what it did.
+async fn execute(
+    state: &AppState,
+    queue: &InMemoryJobQueue,
+    operation: &PendingOperation,
+) -> Result<String, String> {
+    match &operation.action {
+        AdminAction::PurgePendingJobs => {
+            let purged = queue.purge().await.map_err(|e| e.to_string())?;
+            Ok(format!("Purged {} pending jobs", purged))
+        }
+        AdminAction::ReleaseLegalHold { user_id, reason } => {
+            if !state
+                .legal_holds
+                .load()
+                .is_held(*user_id)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                return Ok(format!("User {} was not under legal hold", user_id));
+            }
+            // The change is the requester's; the operation records the approver
+            let change = LegalHoldChange::new(
+                *user_id,
+                false,
+                format!("{} (operation {})", reason, operation.id),
+                operation.requested_by,
+            )
+            .map_err(|e| e.to_string())?;
+            state
+                .legal_holds
+                .load()
+                .record(change)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!("Released the legal hold on user {}", user_id))
+        }
+        AdminAction::GrantRole { user_id, role } => {
+            let (_, changed) = state
+                .user_service()
+                .grant_role(*user_id, role)
+                .await
+                .map_err(|e| e.to_string())?;
+            if !changed {
+                return Ok(format!("User {} already had the {} role", user_id, role));
+            }
+            Ok(format!("Granted the {} role to user {}", role, user_id))
+        }
+        AdminAction::Unknown => Err("Unknown action".to_string()),
+    }
+}
+
+fn to_response(operation: PendingOperation) -> PendingOperationResponse {
+    PendingOperationResponse {
+        id: operation.id.to_string(),
+        action: serde_json::to_value(&operation.action).unwrap_or_default(),
+        status: operation.status.to_string(),
+        requested_by: operation.requested_by.to_string(),
+        requested_at: operation.requested_at.to_rfc3339(),
+        expires_at: operation.expires_at.to_rfc3339(),
+        decided_by: operation.decided_by.map(|id| id.to_string()),
+        decided_at: operation.decided_at.map(|at| at.to_rfc3339()),
+        outcome: operation.outcome,
+    }
+}
@@ -0,0 +1,36 @@
+//! Canary rollout monitoring.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use apex_shared::dto::{CanaryRolloutResponse, CanaryVariantResponse};
+
+use crate::middleware::auth::Admin;
+use crate::middleware::canary::{CanaryRollouts, Variant};
+
+/// GET /api/admin/canaries - Configured rollouts with per-variant traffic
+pub async fn list(_admin: Admin, rollouts: web::Data<Arc<CanaryRollouts>>) -> HttpResponse {
+    let body: Vec<CanaryRolloutResponse> = rollouts
+        .rollouts()
+        .iter()
+        .map(|rollout| CanaryRolloutResponse {
+            name: rollout.name.clone(),
+            percent: rollout.percent,
+            variants: [Variant::Stable, Variant::Canary]
+                .into_iter()
+                .map(|variant| {
+                    let stats = rollouts.stats(&rollout.name, variant);
+                    let requests = stats.requests.max(1) as f64;
+                    CanaryVariantResponse {
+                        variant: variant.as_str().to_string(),
+                        requests: stats.requests,
+                        errors: stats.errors,
+                        error_rate: stats.errors as f64 * 100.0 / requests,
+                        avg_latency_ms: stats.total_latency.as_secs_f64() * 1000.0 / requests,
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+    HttpResponse::Ok().json(body)
+}
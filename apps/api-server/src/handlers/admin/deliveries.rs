@@ -0,0 +1,97 @@
+//! Outbound webhook delivery audit and replay handlers.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use apex_core::domain::WebhookDelivery;
+use apex_infra::AuditedWebhookSender;
+use apex_shared::dto::WebhookDeliveryResponse;
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: u64 = 50;
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListDeliveriesQuery {
+    /// Only return deliveries that did not get a 2xx response.
+    #[serde(default)]
+    pub failed: bool,
+    pub limit: Option<u64>,
+}
+
+/// GET /api/admin/deliveries
+pub async fn list(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    query: web::Query<ListDeliveriesQuery>,
+) -> AppResult<HttpResponse> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let deliveries = state
+        .deliveries
+        .load()
+        .list_recent(query.failed, limit)
+        .await?;
+
+    let body: Vec<WebhookDeliveryResponse> = deliveries.into_iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// GET /api/admin/deliveries/{id}
+pub async fn get(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let delivery = find_delivery(&state, path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(to_response(delivery)))
+}
+
+/// POST /api/admin/deliveries/{id}/replay - Re-send a delivery as a new attempt
+pub async fn replay(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    webhooks: web::Data<Arc<AuditedWebhookSender>>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let original = find_delivery(&state, path.into_inner()).await?;
+
+    tracing::info!(
+        admin_id = %admin.user_id,
+        delivery_id = %original.id,
+        url = %original.url,
+        "Replaying webhook delivery"
+    );
+
+    let replayed = webhooks.replay(&original).await?;
+    Ok(HttpResponse::Created().json(to_response(replayed)))
+}
+
+async fn find_delivery(state: &AppState, id: uuid::Uuid) -> AppResult<WebhookDelivery> {
+    state
+        .deliveries
+        .load()
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Delivery {} not found", id)))
+}
+
+fn to_response(delivery: WebhookDelivery) -> WebhookDeliveryResponse {
+    WebhookDeliveryResponse {
+        succeeded: delivery.succeeded(),
+        id: delivery.id.to_string(),
+        url: delivery.url,
+        payload: delivery.payload,
+        response_status: delivery.response_status,
+        response_body: delivery.response_body,
+        error: delivery.error,
+        latency_ms: delivery.latency_ms,
+        attempt: delivery.attempt,
+        replay_of: delivery.replay_of.map(|id| id.to_string()),
+        request_id: delivery.request_id,
+        created_at: delivery.created_at.to_rfc3339(),
+    }
+}
@@ -0,0 +1,25 @@
+//! Dependency health, as last seen by the watchdog.
+
+use actix_web::{HttpResponse, web};
+
+use apex_infra::DependencyWatchdog;
+use apex_shared::dto::DependencyStatusResponse;
+
+use crate::middleware::auth::Admin;
+
+/// GET /api/admin/dependencies - Watched dependencies and which are degraded
+pub async fn list(_admin: Admin, watchdog: web::Data<DependencyWatchdog>) -> HttpResponse {
+    let statuses: Vec<DependencyStatusResponse> = watchdog
+        .statuses()
+        .into_iter()
+        .map(|status| DependencyStatusResponse {
+            name: status.name,
+            degraded: status.degraded,
+            consecutive_failures: status.consecutive_failures,
+            last_error: status.last_error,
+            last_checked: status.last_checked,
+            since: status.since,
+        })
+        .collect();
+    HttpResponse::Ok().json(statuses)
+}
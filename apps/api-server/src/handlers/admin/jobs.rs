@@ -0,0 +1,180 @@
+//! Job queue inspection and maintenance handlers: the backend of the job
+//! dashboard.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use apex_core::domain::AdminAction;
+use apex_core::ports::{DeadJob, Job, JobQueue};
+use apex_infra::InMemoryJobQueue;
+use apex_shared::dto::{DeadJobResponse, JobResponse, PurgeQueueResponse, QueueStatsResponse};
+
+use super::approvals;
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+/// Characters of a job's payload shown in listings.
+const PAYLOAD_PREVIEW_CHARS: usize = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ListJobsQuery {
+    /// Only return jobs of this type.
+    #[serde(rename = "type")]
+    pub job_type: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl ListJobsQuery {
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// GET /api/admin/jobs - Queue counters
+pub async fn stats(
+    _admin: Admin,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+) -> AppResult<HttpResponse> {
+    let stats = queue.stats().await?;
+    Ok(HttpResponse::Ok().json(QueueStatsResponse {
+        pending: stats.pending,
+        processing: stats.processing,
+        completed: stats.completed,
+        failed: stats.failed,
+        dead: stats.dead,
+    }))
+}
+
+/// GET /api/admin/jobs/pending - Jobs waiting for a worker, oldest first
+pub async fn pending(
+    _admin: Admin,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    query: web::Query<ListJobsQuery>,
+) -> AppResult<HttpResponse> {
+    let jobs = queue
+        .list_pending(query.job_type.as_deref(), query.limit())
+        .await?;
+    let body: Vec<JobResponse> = jobs.into_iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// DELETE /api/admin/jobs/pending/{id} - Drop a job before a worker picks it up
+pub async fn delete_pending(
+    Admin(admin): Admin,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let job_id = path.into_inner();
+    if !queue.delete_pending(&job_id).await? {
+        return Err(AppError::NotFound(format!(
+            "Pending job {} not found",
+            job_id
+        )));
+    }
+
+    tracing::info!(admin_id = %admin.user_id, job_id = %job_id, "Pending job deleted by admin");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// GET /api/admin/jobs/processing - Jobs workers are running
+pub async fn processing(
+    _admin: Admin,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    query: web::Query<ListJobsQuery>,
+) -> AppResult<HttpResponse> {
+    let jobs = queue
+        .list_processing(query.job_type.as_deref(), query.limit())
+        .await?;
+    let body: Vec<JobResponse> = jobs.into_iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// DELETE /api/admin/jobs/pending - Drop every job not yet picked up
+///
+/// Staged for a second admin's approval (202) unless approvals are off.
+pub async fn purge(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+) -> AppResult<HttpResponse> {
+    if state.approvals.required() {
+        return approvals::stage(&state, AdminAction::PurgePendingJobs, admin.user_id).await;
+    }
+
+    let purged = queue.purge().await?;
+    tracing::warn!(admin_id = %admin.user_id, purged = purged, "Job queue purged by admin");
+    Ok(HttpResponse::Ok().json(PurgeQueueResponse { purged }))
+}
+
+/// GET /api/admin/jobs/dead - Permanently failed jobs, newest first
+pub async fn dead(
+    _admin: Admin,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    query: web::Query<ListJobsQuery>,
+) -> AppResult<HttpResponse> {
+    let jobs = queue
+        .list_dead(query.job_type.as_deref(), query.limit())
+        .await?;
+    let body: Vec<DeadJobResponse> = jobs.into_iter().map(to_dead_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// POST /api/admin/jobs/dead/{id}/retry - Re-enqueue a dead job with its attempts reset
+pub async fn retry(
+    Admin(admin): Admin,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let job_id = path.into_inner();
+    if !queue.retry_dead(&job_id).await? {
+        return Err(AppError::NotFound(format!("Dead job {} not found", job_id)));
+    }
+
+    tracing::info!(admin_id = %admin.user_id, job_id = %job_id, "Dead job retried by admin");
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// DELETE /api/admin/jobs/dead/{id} - Discard a dead job
+pub async fn delete_dead(
+    Admin(admin): Admin,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let job_id = path.into_inner();
+    if !queue.delete_dead(&job_id).await? {
+        return Err(AppError::NotFound(format!("Dead job {} not found", job_id)));
+    }
+
+    tracing::info!(admin_id = %admin.user_id, job_id = %job_id, "Dead job deleted by admin");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn to_response(job: Job) -> JobResponse {
+    let payload = job.payload.to_string();
+    let payload_truncated = payload.chars().count() > PAYLOAD_PREVIEW_CHARS;
+    JobResponse {
+        id: job.id,
+        job_type: job.job_type,
+        payload_preview: payload.chars().take(PAYLOAD_PREVIEW_CHARS).collect(),
+        payload_truncated,
+        payload_bytes: payload.len(),
+        attempts: job.attempts,
+        max_attempts: job.max_attempts,
+        unique_key: job.unique_key,
+        created_at: job.created_at.to_rfc3339(),
+        scheduled_at: job.scheduled_at.map(|at| at.to_rfc3339()),
+    }
+}
+
+fn to_dead_response(dead: DeadJob) -> DeadJobResponse {
+    DeadJobResponse {
+        error: dead.error,
+        failed_at: dead.failed_at.to_rfc3339(),
+        job: to_response(dead.job),
+    }
+}
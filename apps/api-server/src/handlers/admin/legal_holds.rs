@@ -0,0 +1,78 @@
+//! Legal holds on user data.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::{AdminAction, LegalHoldChange};
+use apex_shared::dto::{LegalHoldChangeResponse, LegalHoldResponse, SetLegalHoldRequest};
+
+use super::approvals;
+use crate::middleware::auth::Admin;
+use crate::middleware::error::AppResult;
+use crate::middleware::validation::ValidatedJson;
+use crate::state::AppState;
+
+/// GET /api/admin/users/{id}/legal-hold - Hold state and its audit trail
+pub async fn get(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let user_id = path.into_inner();
+    let history = state.legal_holds.load().history(user_id).await?;
+    Ok(HttpResponse::Ok().json(to_response(user_id, history)))
+}
+
+/// PUT /api/admin/users/{id}/legal-hold - Place or release a hold
+///
+/// Setting the state the user is already in records nothing. Releasing a
+/// hold is staged for a second admin's approval (202) unless approvals are
+/// off.
+pub async fn set(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: ValidatedJson<SetLegalHoldRequest>,
+) -> AppResult<HttpResponse> {
+    let user_id = path.into_inner();
+    let body = body.into_inner();
+    let change = LegalHoldChange::new(user_id, body.held, body.reason, admin.user_id)?;
+
+    let held = state.legal_holds.load().is_held(user_id).await?;
+    if held && !change.held && state.approvals.required() {
+        let action = AdminAction::ReleaseLegalHold {
+            user_id,
+            reason: change.reason,
+        };
+        return approvals::stage(&state, action, admin.user_id).await;
+    }
+
+    if held != change.held {
+        state.legal_holds.load().record(change).await?;
+        tracing::warn!(
+            admin_id = %admin.user_id,
+            user_id = %user_id,
+            held = body.held,
+            "Legal hold changed"
+        );
+    }
+
+    let history = state.legal_holds.load().history(user_id).await?;
+    Ok(HttpResponse::Ok().json(to_response(user_id, history)))
+}
+
+fn to_response(user_id: uuid::Uuid, history: Vec<LegalHoldChange>) -> LegalHoldResponse {
+    LegalHoldResponse {
+        user_id: user_id.to_string(),
+        held: history.first().is_some_and(|change| change.held),
+        history: history
+            .into_iter()
+            .map(|change| LegalHoldChangeResponse {
+                id: change.id.to_string(),
+                held: change.held,
+                reason: change.reason,
+                changed_by: change.changed_by.to_string(),
+                changed_at: change.changed_at.to_rfc3339(),
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,29 @@
+//! Allocator stats and heap profiles, with the `jemalloc` feature.
+
+use actix_web::{HttpResponse, web};
+
+use apex_shared::dto::HeapProfileResponse;
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+use crate::observability::MemoryProfiler;
+
+/// GET /api/admin/runtime/memory - Allocator counters
+pub async fn stats(_admin: Admin, profiler: web::Data<MemoryProfiler>) -> AppResult<HttpResponse> {
+    let stats = profiler
+        .stats()
+        .map_err(|e| AppError::Internal(format!("Failed to read allocator stats: {}", e)))?;
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+/// POST /api/admin/runtime/memory/profile - Write a heap profile to disk
+pub async fn profile(
+    Admin(admin): Admin,
+    profiler: web::Data<MemoryProfiler>,
+) -> AppResult<HttpResponse> {
+    let path = profiler.dump().map_err(AppError::BadRequest)?;
+    tracing::info!(admin_id = %admin.user_id, path = %path.display(), "Heap profile written");
+    Ok(HttpResponse::Created().json(HeapProfileResponse {
+        path: path.display().to_string(),
+    }))
+}
@@ -0,0 +1,12 @@
+//! Metric cardinality self-check.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use crate::middleware::auth::Admin;
+use crate::observability::RequestSeries;
+
+/// GET /api/admin/metrics - Metric families and how many series each has
+pub async fn series(_admin: Admin, series: web::Data<Arc<RequestSeries>>) -> HttpResponse {
+    HttpResponse::Ok().json(series.report())
+}
@@ -0,0 +1,102 @@
+//! Admin-only route handlers.
+
+mod announcements;
+mod approvals;
+mod canaries;
+mod deliveries;
+mod dependencies;
+mod jobs;
+mod legal_holds;
+#[cfg(feature = "jemalloc")]
+mod memory;
+mod metrics;
+mod plans;
+mod posts;
+mod pubsub;
+#[cfg(feature = "websocket")]
+mod realtime;
+mod roles;
+mod routes;
+mod runtime;
+mod shadow;
+#[cfg(feature = "postgres")]
+mod sql;
+
+use actix_web::web;
+
+/// Configure admin routes. Every handler requires the `Admin` extractor.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    #[cfg(feature = "postgres")]
+    cfg.service(
+        web::scope("/admin/sql")
+            .route("", web::post().to(sql::query))
+            .route("/databases", web::get().to(sql::databases)),
+    );
+
+    #[cfg(feature = "jemalloc")]
+    cfg.service(
+        web::scope("/admin/runtime/memory")
+            .route("", web::get().to(memory::stats))
+            .route("/profile", web::post().to(memory::profile)),
+    );
+
+    #[cfg(feature = "websocket")]
+    cfg.service(web::scope("/admin/realtime").route("", web::get().to(realtime::stats)));
+
+    cfg.service(
+        web::scope("/admin")
+            .service(
+                web::scope("/deliveries")
+                    .route("", web::get().to(deliveries::list))
+                    .route("/{id}", web::get().to(deliveries::get))
+                    .route("/{id}/replay", web::post().to(deliveries::replay)),
+            )
+            .service(
+                web::scope("/approvals")
+                    .route("", web::get().to(approvals::list))
+                    .route("/{id}", web::get().to(approvals::get))
+                    .route("/{id}/approve", web::post().to(approvals::approve))
+                    .route("/{id}/reject", web::post().to(approvals::reject)),
+            )
+            .service(
+                web::scope("/announcements")
+                    .route("", web::get().to(announcements::list))
+                    .route("", web::post().to(announcements::create))
+                    .route("/{id}", web::put().to(announcements::update))
+                    .route("/{id}", web::patch().to(announcements::patch))
+                    .route("/{id}", web::delete().to(announcements::delete)),
+            )
+            .service(
+                web::scope("/jobs")
+                    .route("", web::get().to(jobs::stats))
+                    .route("/pending", web::get().to(jobs::pending))
+                    .route("/pending", web::delete().to(jobs::purge))
+                    .route("/pending/{id}", web::delete().to(jobs::delete_pending))
+                    .route("/processing", web::get().to(jobs::processing))
+                    .route("/dead", web::get().to(jobs::dead))
+                    .route("/dead/{id}", web::delete().to(jobs::delete_dead))
+                    .route("/dead/{id}/retry", web::post().to(jobs::retry)),
+            )
+            .route("/accounts/{id}/plan", web::put().to(plans::set))
+            .route("/canaries", web::get().to(canaries::list))
+            .route("/dependencies", web::get().to(dependencies::list))
+            .route("/metrics", web::get().to(metrics::series))
+            .route("/posts", web::get().to(posts::list))
+            .route("/pubsub", web::get().to(pubsub::stats))
+            .route("/routes", web::get().to(routes::list))
+            .route("/users/{id}/legal-hold", web::get().to(legal_holds::get))
+            .route("/users/{id}/legal-hold", web::put().to(legal_holds::set))
+            .route("/users/{id}/roles", web::get().to(roles::list))
+            .route("/users/{id}/roles/{role}", web::put().to(roles::grant))
+            .route("/users/{id}/roles/{role}", web::delete().to(roles::revoke))
+            .route("/runtime", web::get().to(runtime::get))
+            .route("/runtime/tokio", web::get().to(runtime::tokio_metrics))
+            .route("/runtime/tasks", web::get().to(runtime::tasks))
+            .service(
+                web::scope("/shadow")
+                    .route("", web::get().to(shadow::stats))
+                    .route("/diffs", web::get().to(shadow::diffs))
+                    .route("/diffs", web::delete().to(shadow::clear)),
+            ),
+    );
+}
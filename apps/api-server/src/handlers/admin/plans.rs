@@ -0,0 +1,27 @@
+//! Account plan assignment.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::Plan;
+use apex_shared::dto::SetPlanRequest;
+
+use crate::handlers::plans::to_response;
+use crate::middleware::auth::Admin;
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// PUT /api/admin/accounts/{id}/plan - Assign a plan to an organization or user
+pub async fn set(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<SetPlanRequest>,
+) -> AppResult<HttpResponse> {
+    let account_id = path.into_inner();
+    let plan: Plan = body.plan.parse()?;
+
+    state.entitlements.set_plan(account_id, plan).await?;
+    tracing::info!(account_id = %account_id, plan = %plan, "Account plan changed");
+
+    Ok(HttpResponse::Ok().json(to_response(account_id, plan)))
+}
@@ -0,0 +1,92 @@
+//! Posts of every author, for moderation.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use apex_core::domain::{Filter, PageRequest, PostStatus, Sort};
+use apex_shared::dto::OffsetPage;
+
+use crate::handlers::posts::to_response;
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+const MAX_LIMIT: u64 = 100;
+
+/// Fields `sort` may name.
+const SORT_FIELDS: &[&str] = &[
+    "created_at",
+    "updated_at",
+    "published_at",
+    "title",
+    "view_count",
+];
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// `draft`, `scheduled` or `published`.
+    pub status: Option<String>,
+    pub author_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    /// Name of a tag the posts carry.
+    pub tag: Option<String>,
+    /// A field, `-` first for descending; newest first by default.
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub offset: u64,
+    pub limit: Option<u64>,
+}
+
+/// GET /api/admin/posts - Posts that are not deleted, paged by offset and
+/// narrowed by `status`, `author_id`, `organization_id` and `tag`
+pub async fn list(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    query: web::Query<ListQuery>,
+) -> AppResult<HttpResponse> {
+    let query = query.into_inner();
+    let sort: Sort = query.sort.as_deref().unwrap_or("-created_at").parse()?;
+    if !SORT_FIELDS.contains(&sort.field.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "Cannot sort by {}; use one of {}",
+            sort.field,
+            SORT_FIELDS.join(", ")
+        )));
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(PageRequest::DEFAULT_LIMIT)
+        .clamp(1, MAX_LIMIT);
+    let mut request = PageRequest::new(query.offset, limit).sorted_by(sort);
+    if let Some(status) = query.status {
+        let status: PostStatus = status.parse()?;
+        request = request.filtered_by(status.filter(chrono::Utc::now()));
+    }
+    if let Some(author_id) = query.author_id {
+        request = request.filtered_by(Filter::eq("user_id", author_id));
+    }
+    if let Some(organization_id) = query.organization_id {
+        request = request.filtered_by(Filter::eq("organization_id", organization_id));
+    }
+    if let Some(tag) = query.tag {
+        let tagged = state.tag_service().tagged(&tag).await?;
+        request = request.filtered_by(Filter::is_in("id", tagged));
+    }
+
+    let page = state.post_service().search(request).await?;
+    let next_offset = page.next_offset();
+    let mut items = Vec::with_capacity(page.items.len());
+    for post in page.items {
+        let view_count = post.view_count + state.post_views.pending(post.id).await;
+        items.push(to_response(post, view_count));
+    }
+    Ok(HttpResponse::Ok().json(OffsetPage {
+        items,
+        total: page.total,
+        offset: page.offset,
+        limit: page.limit,
+        next_offset,
+    }))
+}
@@ -0,0 +1,30 @@
+//! Pub/sub delivery counters.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use apex_core::ports::PubSub;
+use apex_infra::{InMemoryPubSub, TypedPubSub};
+use apex_shared::dto::PubSubStatsResponse;
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::AppResult;
+
+/// GET /api/admin/pubsub - Publish, delivery, drop and lag counters
+pub async fn stats(
+    _admin: Admin,
+    pubsub: web::Data<Arc<TypedPubSub<InMemoryPubSub>>>,
+) -> AppResult<HttpResponse> {
+    let stats = pubsub.inner().stats();
+    let millis = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    Ok(HttpResponse::Ok().json(PubSubStatsResponse {
+        published: stats.published,
+        delivered: stats.delivered,
+        lagged: stats.lagged,
+        redelivered: stats.redelivered,
+        disconnected: stats.disconnected,
+        subscriptions: stats.subscriptions,
+        avg_handler_ms: stats.mean_handler_time().map(millis).unwrap_or_default(),
+        max_handler_ms: millis(stats.max_handler_time),
+    }))
+}
@@ -0,0 +1,39 @@
+//! WebSocket connection quality per namespace.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use apex_shared::dto::{NamespaceMetricsResponse, RealtimeMetricsResponse};
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::AppResult;
+use crate::websocket::ConnectionMetrics;
+
+/// GET /api/admin/realtime - Connections, reconnects and heartbeat round
+/// trips per namespace
+pub async fn stats(
+    _admin: Admin,
+    metrics: web::Data<Arc<ConnectionMetrics>>,
+) -> AppResult<HttpResponse> {
+    let millis = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    let namespaces: Vec<NamespaceMetricsResponse> = metrics
+        .snapshot()
+        .into_iter()
+        .map(|ns| NamespaceMetricsResponse {
+            namespace: ns.namespace,
+            connected: ns.connected,
+            connects: ns.connects,
+            reconnects: ns.reconnects,
+            disconnects: ns.disconnects,
+            heartbeats: ns.heartbeats,
+            missed_heartbeats: ns.missed_heartbeats,
+            rtt_p50_ms: ns.rtt_p50.map(millis),
+            rtt_p99_ms: ns.rtt_p99.map(millis),
+            rtt_max_ms: ns.rtt_max.map(millis),
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(RealtimeMetricsResponse {
+        connected: namespaces.iter().map(|ns| ns.connected).sum(),
+        namespaces,
+    }))
+}
@@ -0,0 +1,87 @@
+//! Roles granted to users.
+//!
+//! Roles are copied into tokens when they are issued, so a change applies
+//! from the user's next login.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::{AdminAction, User};
+use apex_shared::dto::UserRolesResponse;
+
+use super::approvals;
+use crate::middleware::auth::{ADMIN_ROLE, Admin};
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// GET /api/admin/users/{id}/roles - Roles of a user
+pub async fn list(
+    _admin: Admin,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let user = state.user_service().get(path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(to_response(user)))
+}
+
+/// PUT /api/admin/users/{id}/roles/{role} - Grant a role
+///
+/// Granting a role the user already has changes nothing. Granting the admin
+/// role is staged for a second admin's approval (202) unless approvals are
+/// off.
+pub async fn grant(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    path: web::Path<(uuid::Uuid, String)>,
+) -> AppResult<HttpResponse> {
+    let (user_id, role) = path.into_inner();
+    if role == ADMIN_ROLE && state.approvals.required() {
+        let user = state.user_service().get(user_id).await?;
+        if !user.has_role(&role) {
+            let action = AdminAction::GrantRole { user_id, role };
+            return approvals::stage(&state, action, admin.user_id).await;
+        }
+        return Ok(HttpResponse::Ok().json(to_response(user)));
+    }
+    let (user, changed) = state.user_service().grant_role(user_id, &role).await?;
+    if changed {
+        tracing::warn!(
+            admin_id = %admin.user_id,
+            user_id = %user_id,
+            role = %role,
+            "Role granted"
+        );
+    }
+    Ok(HttpResponse::Ok().json(to_response(user)))
+}
+
+/// DELETE /api/admin/users/{id}/roles/{role} - Revoke a role
+///
+/// Admins cannot revoke their own admin role, so the last admin cannot
+/// lock everyone out.
+pub async fn revoke(
+    Admin(admin): Admin,
+    state: web::Data<AppState>,
+    path: web::Path<(uuid::Uuid, String)>,
+) -> AppResult<HttpResponse> {
+    let (user_id, role) = path.into_inner();
+    let (user, changed) = state
+        .user_service()
+        .revoke_role(admin.user_id, user_id, &role)
+        .await?;
+    if changed {
+        tracing::warn!(
+            admin_id = %admin.user_id,
+            user_id = %user_id,
+            role = %role,
+            "Role revoked"
+        );
+    }
+    Ok(HttpResponse::Ok().json(to_response(user)))
+}
+
+fn to_response(user: User) -> UserRolesResponse {
+    UserRolesResponse {
+        user_id: user.id.to_string(),
+        roles: user.roles,
+    }
+}
@@ -0,0 +1,45 @@
+//! Route matrix.
+
+use actix_web::{HttpRequest, HttpResponse};
+
+use apex_shared::dto::RouteResponse;
+
+use crate::middleware::auth::Admin;
+#[cfg(feature = "rate-limit")]
+use crate::middleware::versioning::unversioned;
+use crate::routes::ROUTES;
+
+/// GET /api/admin/routes - Every route with its required access, middleware
+/// and rate limit
+pub async fn list(_admin: Admin, req: HttpRequest) -> HttpResponse {
+    #[cfg(feature = "rate-limit")]
+    let policy = req.app_data::<actix_web::web::Data<apex_infra::RateLimitPolicy>>();
+    #[cfg(not(feature = "rate-limit"))]
+    let _ = req;
+
+    let body: Vec<RouteResponse> = ROUTES
+        .iter()
+        .map(|route| {
+            #[cfg(feature = "rate-limit")]
+            let rate_limit = policy.map(|policy| {
+                policy
+                    .limiter_for(&unversioned(route.path))
+                    .0
+                    .unwrap_or("default")
+                    .to_string()
+            });
+            #[cfg(not(feature = "rate-limit"))]
+            let rate_limit = None;
+
+            RouteResponse {
+                method: route.method.to_string(),
+                path: route.path.to_string(),
+                handler: route.handler.to_string(),
+                access: route.access.as_str().to_string(),
+                middleware: route.middleware.iter().map(|m| m.to_string()).collect(),
+                rate_limit,
+            }
+        })
+        .collect();
+    HttpResponse::Ok().json(body)
+}
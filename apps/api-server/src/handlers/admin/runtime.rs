@@ -0,0 +1,49 @@
+//! Runtime report of this instance.
+
+use actix_web::{HttpResponse, web};
+use tokio::runtime::Handle;
+
+use apex_shared::dto::TokioMetricsResponse;
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+use crate::observability::{RuntimeReport, executor};
+
+/// GET /api/admin/runtime - Features, listeners, workers and backend latency
+pub async fn get(_admin: Admin, runtime: web::Data<RuntimeReport>) -> AppResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(runtime.snapshot().await))
+}
+
+/// GET /api/admin/runtime/tokio - Scheduler metrics of the main runtime and
+/// of the HTTP worker serving this request
+pub async fn tokio_metrics(
+    _admin: Admin,
+    runtime: web::Data<RuntimeReport>,
+) -> AppResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(TokioMetricsResponse {
+        runtimes: vec![
+            executor::runtime_metrics("main", runtime.main_runtime()),
+            executor::runtime_metrics("http", &Handle::current()),
+        ],
+    }))
+}
+
+/// GET /api/admin/runtime/tasks - Stack traces of every task, as text
+pub async fn tasks(_admin: Admin, runtime: web::Data<RuntimeReport>) -> AppResult<HttpResponse> {
+    let unavailable = || {
+        AppError::NotFound(
+            "Task dumps need a build with --cfg tokio_unstable and the taskdump feature"
+                .to_string(),
+        )
+    };
+    let main = executor::task_dump(runtime.main_runtime())
+        .await
+        .ok_or_else(unavailable)?;
+    let http = executor::task_dump(&Handle::current())
+        .await
+        .ok_or_else(unavailable)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(format!("# main\n\n{}\n\n# http\n\n{}\n", main, http)))
+}
@@ -0,0 +1,81 @@
+//! Shadow traffic results.
+//!
+//! Off unless `SHADOW_URL` is set.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+
+use apex_infra::shadow::{ShadowDiff, ShadowTraffic};
+use apex_shared::dto::{ShadowDiffResponse, ShadowStatsResponse};
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListDiffsQuery {
+    pub limit: Option<usize>,
+}
+
+/// GET /api/admin/shadow - Mirroring settings and counters
+pub async fn stats(
+    _admin: Admin,
+    shadow: Option<web::Data<ShadowTraffic>>,
+) -> AppResult<HttpResponse> {
+    let shadow = enabled(shadow)?;
+    let config = shadow.config();
+    let stats = shadow.stats();
+    Ok(HttpResponse::Ok().json(ShadowStatsResponse {
+        shadow_url: config.url.clone().unwrap_or_default(),
+        sample_rate: config.sample_rate,
+        methods: config.methods.clone(),
+        mirrored: stats.mirrored,
+        matched: stats.matched,
+        mismatched: stats.mismatched,
+        failed: stats.failed,
+    }))
+}
+
+/// GET /api/admin/shadow/diffs - Responses that differed, newest first
+pub async fn diffs(
+    _admin: Admin,
+    shadow: Option<web::Data<ShadowTraffic>>,
+    query: web::Query<ListDiffsQuery>,
+) -> AppResult<HttpResponse> {
+    let shadow = enabled(shadow)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let body: Vec<ShadowDiffResponse> = shadow.diffs(limit).into_iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// DELETE /api/admin/shadow/diffs - Forget recorded differences
+pub async fn clear(
+    Admin(admin): Admin,
+    shadow: Option<web::Data<ShadowTraffic>>,
+) -> AppResult<HttpResponse> {
+    let shadow = enabled(shadow)?;
+    shadow.clear();
+    tracing::info!(admin_id = %admin.user_id, "Shadow traffic differences cleared");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn enabled(shadow: Option<web::Data<ShadowTraffic>>) -> AppResult<web::Data<ShadowTraffic>> {
+    shadow.ok_or_else(|| AppError::NotFound("Shadow traffic is not configured".to_string()))
+}
+
+fn to_response(diff: ShadowDiff) -> ShadowDiffResponse {
+    ShadowDiffResponse {
+        id: diff.id.to_string(),
+        method: diff.method,
+        path: diff.path,
+        primary_status: diff.primary_status,
+        shadow_status: diff.shadow_status,
+        differences: diff.differences,
+        error: diff.error,
+        primary_latency_ms: diff.primary_latency_ms,
+        shadow_latency_ms: diff.shadow_latency_ms,
+        recorded_at: diff.recorded_at.to_rfc3339(),
+    }
+}
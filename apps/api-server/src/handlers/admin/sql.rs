@@ -0,0 +1,75 @@
+//! Read-only SQL console over the secondary databases.
+//!
+//! Off unless `SQL_CONSOLE_ENABLED` is set. Every query is logged with the
+//! admin who ran it, whether it succeeded or not.
+
+use actix_web::{HttpResponse, web};
+
+use apex_infra::database::{SqlConsole, SqlConsoleError};
+use apex_shared::dto::{SqlQueryRequest, SqlQueryResponse};
+
+use crate::middleware::auth::Admin;
+use crate::middleware::error::{AppError, AppResult};
+
+/// GET /api/admin/sql/databases - Databases the console can query (the
+/// Postgres ones)
+pub async fn databases(
+    _admin: Admin,
+    console: Option<web::Data<SqlConsole>>,
+) -> AppResult<HttpResponse> {
+    let console = enabled(console)?;
+    Ok(HttpResponse::Ok().json(console.databases()))
+}
+
+/// POST /api/admin/sql - Run a read-only, parameterized query
+pub async fn query(
+    Admin(admin): Admin,
+    console: Option<web::Data<SqlConsole>>,
+    body: web::Json<SqlQueryRequest>,
+) -> AppResult<HttpResponse> {
+    let console = enabled(console)?;
+    let req = body.into_inner();
+
+    let result = console
+        .query(&req.database, &req.query, &req.params, req.limit)
+        .await;
+    match &result {
+        Ok(rows) => tracing::info!(
+            admin_id = %admin.user_id,
+            database = %req.database,
+            query = %req.query,
+            params = req.params.len(),
+            rows = rows.rows.len(),
+            truncated = rows.truncated,
+            elapsed_ms = rows.elapsed.as_millis() as u64,
+            "SQL console query"
+        ),
+        Err(e) => tracing::warn!(
+            admin_id = %admin.user_id,
+            database = %req.database,
+            query = %req.query,
+            params = req.params.len(),
+            error = %e,
+            "SQL console query rejected"
+        ),
+    }
+
+    let rows = result.map_err(|e| match e {
+        SqlConsoleError::UnknownDatabase(_) => AppError::NotFound(e.to_string()),
+        SqlConsoleError::UnsupportedBackend(_) => AppError::NotImplemented(e.to_string()),
+        SqlConsoleError::NotReadOnly | SqlConsoleError::Query(_) => {
+            AppError::BadRequest(e.to_string())
+        }
+    })?;
+    Ok(HttpResponse::Ok().json(SqlQueryResponse {
+        row_count: rows.rows.len(),
+        columns: rows.columns,
+        rows: rows.rows,
+        truncated: rows.truncated,
+        elapsed_ms: rows.elapsed.as_millis() as u64,
+    }))
+}
+
+fn enabled(console: Option<web::Data<SqlConsole>>) -> AppResult<web::Data<SqlConsole>> {
+    console.ok_or_else(|| AppError::NotFound("The SQL console is not enabled".to_string()))
+}
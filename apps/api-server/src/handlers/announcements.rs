@@ -0,0 +1,39 @@
+//! Announcement handlers.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::{Announcement, Viewer};
+use apex_shared::dto::AnnouncementResponse;
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// GET /api/announcements - Announcements currently showing to the caller
+pub async fn list(identity: Identity, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let plan = state.entitlements.plan_for(identity.account_id()).await?;
+    let viewer = Viewer {
+        roles: &identity.roles,
+        plan,
+        organization_id: identity.org.as_ref().map(|org| org.id),
+    };
+
+    let announcements = state
+        .announcements
+        .live_for(&viewer, chrono::Utc::now())
+        .await?;
+
+    let body: Vec<AnnouncementResponse> = announcements.iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+pub(super) fn to_response(announcement: &Announcement) -> AnnouncementResponse {
+    AnnouncementResponse {
+        id: announcement.id.to_string(),
+        title: announcement.title.clone(),
+        body: announcement.body.clone(),
+        audience: announcement.audience.to_string(),
+        starts_at: announcement.starts_at.to_rfc3339(),
+        ends_at: announcement.ends_at.map(|at| at.to_rfc3339()),
+    }
+}
@@ -0,0 +1,97 @@
+//! Attachment handlers: uploaded files' metadata, with signed links to
+//! download their bytes.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use apex_core::domain::Attachment;
+use apex_core::services::AttachmentService;
+use apex_infra::LocalStorage;
+use apex_shared::dto::AttachmentResponse;
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// How long download links handed out with an attachment work.
+const DOWNLOAD_LINK_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// GET /api/attachments/{id} - An attachment the caller can read, with a
+/// download link
+pub async fn get(
+    identity: Identity,
+    state: web::Data<AppState>,
+    storage: web::Data<LocalStorage>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let service = service(&state, storage);
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    let attachment = service
+        .get(identity.user_id, org_id, path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(to_response(&service, attachment)?))
+}
+
+/// GET /api/posts/{id}/attachments - The attachments on a post the caller
+/// can read, oldest first
+pub async fn list_for_post(
+    identity: Identity,
+    state: web::Data<AppState>,
+    storage: web::Data<LocalStorage>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let service = service(&state, storage);
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    let attachments = service
+        .list_for_post(identity.user_id, org_id, path.into_inner())
+        .await?;
+    let items = attachments
+        .into_iter()
+        .map(|attachment| to_response(&service, attachment))
+        .collect::<AppResult<Vec<_>>>()?;
+    Ok(HttpResponse::Ok().json(items))
+}
+
+/// DELETE /api/attachments/{id} - Delete one of the caller's attachments,
+/// freeing its bytes from the storage quota
+pub async fn delete(
+    identity: Identity,
+    state: web::Data<AppState>,
+    storage: web::Data<LocalStorage>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let attachment = service(&state, storage)
+        .delete(identity.user_id, path.into_inner())
+        .await?;
+    state
+        .storage
+        .record(attachment.account_id(), -attachment.size_bytes)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub(super) fn service(state: &AppState, storage: web::Data<LocalStorage>) -> AttachmentService {
+    let storage: Arc<LocalStorage> = storage.into_inner();
+    state.attachment_service(storage)
+}
+
+pub(super) fn to_response(
+    service: &AttachmentService,
+    attachment: Attachment,
+) -> AppResult<AttachmentResponse> {
+    let download_url = service.download_url(&attachment, DOWNLOAD_LINK_TTL)?;
+    Ok(AttachmentResponse {
+        id: attachment.id.to_string(),
+        user_id: attachment.user_id.to_string(),
+        organization_id: attachment.organization_id.map(|id| id.to_string()),
+        post_id: attachment.post_id.map(|id| id.to_string()),
+        filename: attachment.filename,
+        content_type: attachment.content_type,
+        size_bytes: attachment.size_bytes,
+        created_at: attachment.created_at.to_rfc3339(),
+        download_url,
+    })
+}
@@ -3,12 +3,16 @@
 use actix_web::{HttpResponse, web};
 use std::sync::Arc;
 
-use apex_core::domain::User;
 use apex_core::ports::{PasswordService, TokenService};
-use apex_shared::dto::{AuthResponse, LoginRequest, RegisterUserRequest, UserResponse};
+use apex_core::services::IssuedToken;
+use apex_shared::dto::{
+    AuthResponse, ChangePasswordRequest, LoginRequest, RegisterUserRequest, UserResponse,
+};
 
+use super::storage_response;
 use crate::middleware::auth::Identity;
-use crate::middleware::error::{AppError, AppResult};
+use crate::middleware::error::AppResult;
+use crate::middleware::validation::ValidatedJson;
 use crate::state::AppState;
 
 /// POST /api/auth/register
@@ -16,44 +20,17 @@ pub async fn register(
     state: web::Data<AppState>,
     token_service: web::Data<Arc<dyn TokenService>>,
     password_service: web::Data<Arc<dyn PasswordService>>,
-    body: web::Json<RegisterUserRequest>,
+    body: ValidatedJson<RegisterUserRequest>,
 ) -> AppResult<HttpResponse> {
     let req = body.into_inner();
-
-    // Validate input
-    if req.email.is_empty() || !req.email.contains('@') {
-        return Err(AppError::BadRequest("Invalid email address".to_string()));
-    }
-    if req.password.len() < 8 {
-        return Err(AppError::BadRequest(
-            "Password must be at least 8 characters".to_string(),
-        ));
-    }
-
-    // Check if user already exists
-    if let Some(_) = state.users.find_by_email(&req.email).await? {
-        return Err(AppError::Conflict("Email already registered".to_string()));
-    }
-
-    // Hash password
-    let password_hash = password_service
-        .hash(&req.password)
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    // Create user
-    let user = User::new(req.email.clone(), password_hash);
-    let saved_user = state.users.save(user).await?;
-
-    // Generate token
-    let token = token_service
-        .generate_token(saved_user.id, &saved_user.email, vec!["user".to_string()])
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    Ok(HttpResponse::Created().json(AuthResponse {
-        access_token: token,
-        token_type: "Bearer".to_string(),
-        expires_in: token_service.expiration_seconds() as u64,
-    }))
+    let token = state
+        .auth_service(
+            token_service.get_ref().clone(),
+            password_service.get_ref().clone(),
+        )
+        .register(&req.email, &req.password)
+        .await?;
+    Ok(HttpResponse::Created().json(auth_response(token)))
 }
 
 /// POST /api/auth/login
@@ -61,43 +38,57 @@ pub async fn login(
     state: web::Data<AppState>,
     token_service: web::Data<Arc<dyn TokenService>>,
     password_service: web::Data<Arc<dyn PasswordService>>,
-    body: web::Json<LoginRequest>,
+    body: ValidatedJson<LoginRequest>,
 ) -> AppResult<HttpResponse> {
     let req = body.into_inner();
-
-    // Find user by email
-    let user = state
-        .users
-        .find_by_email(&req.email)
-        .await?
-        .ok_or_else(|| AppError::Unauthorized)?;
-
-    // Verify password
-    let valid = password_service
-        .verify(&req.password, &user.password_hash)
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    if !valid {
-        return Err(AppError::Unauthorized);
-    }
-
-    // Generate token
-    let token = token_service
-        .generate_token(user.id, &user.email, vec!["user".to_string()])
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    Ok(HttpResponse::Ok().json(AuthResponse {
-        access_token: token,
-        token_type: "Bearer".to_string(),
-        expires_in: token_service.expiration_seconds() as u64,
-    }))
+    let token = state
+        .auth_service(
+            token_service.get_ref().clone(),
+            password_service.get_ref().clone(),
+        )
+        .login(&req.email, &req.password)
+        .await?;
+    Ok(HttpResponse::Ok().json(auth_response(token)))
 }
 
-/// GET /api/auth/me - Protected route
-pub async fn me(identity: Identity) -> AppResult<HttpResponse> {
+/// GET /api/auth/me - The caller's account
+pub async fn me(identity: Identity, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let user = state.user_service().get(identity.user_id).await?;
+    let storage = state.storage.usage(user.id).await?;
     Ok(HttpResponse::Ok().json(UserResponse {
-        id: identity.user_id.to_string(),
-        email: identity.email,
-        created_at: chrono::Utc::now().to_rfc3339(), // Would normally come from DB
+        id: user.id.to_string(),
+        email: user.email.into_inner(),
+        created_at: user.created_at.to_rfc3339(),
+        storage: Some(storage_response(storage)),
     }))
 }
+
+/// PUT /api/auth/password - Change the caller's password
+///
+/// The new password may not repeat any of the caller's last
+/// `PASSWORD_HISTORY` passwords. Tokens issued before stay valid.
+pub async fn change_password(
+    identity: Identity,
+    state: web::Data<AppState>,
+    token_service: web::Data<Arc<dyn TokenService>>,
+    password_service: web::Data<Arc<dyn PasswordService>>,
+    body: ValidatedJson<ChangePasswordRequest>,
+) -> AppResult<HttpResponse> {
+    let req = body.into_inner();
+    state
+        .auth_service(
+            token_service.get_ref().clone(),
+            password_service.get_ref().clone(),
+        )
+        .change_password(identity.user_id, &req.current_password, &req.new_password)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn auth_response(token: IssuedToken) -> AuthResponse {
+    AuthResponse {
+        access_token: token.access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: token.expires_in,
+    }
+}
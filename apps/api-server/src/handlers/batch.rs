@@ -0,0 +1,207 @@
+//! Batch requests: several API calls in one round trip.
+//!
+//! Each sub-request runs in process through a copy of the app built for
+//! batches, with the caller's headers and connection, so it passes through
+//! the same auth, consent checks and rate limiting as if the client had sent
+//! it itself. That copy leaves out usage metering and API quotas: the batch
+//! is counted once, as the request the client made. Requests run in order; a
+//! failed one does not stop the rest.
+
+use std::rc::Rc;
+
+use actix_http::Request;
+use actix_service::IntoServiceFactory;
+use actix_web::body::{MessageBody, to_bytes};
+use actix_web::dev::{
+    AppConfig, Payload, Service, ServiceFactory, ServiceRequest, ServiceResponse,
+};
+use actix_web::http::{Method, Uri, header};
+use actix_web::{App, Error, HttpRequest, HttpResponse, web};
+use futures::future::LocalBoxFuture;
+use tokio::sync::OnceCell;
+
+use apex_shared::dto::{BatchItemRequest, BatchItemResponse, BatchRequest, BatchResponse};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::middleware::versioning::unversioned;
+
+const MAX_REQUESTS: usize = 20;
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Headers not copied onto sub-requests: they describe the batch request's
+/// body or transport, or would let a sub-request claim another client.
+const DROPPED_HEADERS: [&str; 7] = [
+    "content-length",
+    "content-type",
+    "connection",
+    "transfer-encoding",
+    "accept-encoding",
+    "forwarded",
+    "x-forwarded-for",
+];
+
+/// A sub-request's status and response body.
+type Dispatch = Rc<dyn Fn(Request) -> LocalBoxFuture<'static, Result<(u16, web::Bytes), Error>>>;
+
+/// Runs batched sub-requests through the app built for them, one per
+/// worker. The app's services are started on the worker's first batch.
+pub struct BatchDispatcher {
+    start: Box<dyn Fn() -> LocalBoxFuture<'static, Result<Dispatch, ()>>>,
+    service: OnceCell<Dispatch>,
+}
+
+impl BatchDispatcher {
+    pub fn new<T, B>(app: App<T>) -> Self
+    where
+        T: ServiceFactory<
+                ServiceRequest,
+                Config = (),
+                Response = ServiceResponse<B>,
+                Error = Error,
+                InitError = (),
+            > + 'static,
+        T::Future: 'static,
+        B: MessageBody + 'static,
+    {
+        let factory = app.into_factory();
+        let start = move || {
+            let started = factory.new_service(AppConfig::default());
+            Box::pin(async move {
+                let service = Rc::new(started.await?);
+                let dispatch: Dispatch = Rc::new(move |request| {
+                    let service = service.clone();
+                    Box::pin(async move {
+                        let response = service.call(request).await?;
+                        let status = response.status().as_u16();
+                        let body = to_bytes(response.into_body()).await.unwrap_or_default();
+                        Ok((status, body))
+                    })
+                });
+                Ok(dispatch)
+            }) as LocalBoxFuture<'static, Result<Dispatch, ()>>
+        };
+
+        Self {
+            start: Box::new(start),
+            service: OnceCell::new(),
+        }
+    }
+
+    async fn send(&self, req: &HttpRequest, item: &BatchItemRequest) -> BatchItemResponse {
+        let (method, uri) = match check(item) {
+            Ok(checked) => checked,
+            Err(message) => return error_response(400, message),
+        };
+        let dispatch = match self.service.get_or_try_init(|| (self.start)()).await {
+            Ok(dispatch) => dispatch,
+            Err(()) => {
+                tracing::error!("Failed to start the batch app");
+                return error_response(500, "Request could not be completed".to_string());
+            }
+        };
+
+        let body = item
+            .body
+            .as_ref()
+            .map(|body| web::Bytes::from(body.to_string()));
+        let mut request = match &body {
+            Some(body) => Request::with_payload(Payload::from(body.clone())),
+            None => Request::new(),
+        };
+        let head = request.head_mut();
+        head.method = method;
+        head.uri = uri;
+        head.version = req.version();
+        // The caller's own connection, so limits and audit logs see them
+        head.peer_addr = req.peer_addr();
+        for (name, value) in req.headers() {
+            if !DROPPED_HEADERS.contains(&name.as_str()) {
+                head.headers.append(name.clone(), value.clone());
+            }
+        }
+        if let Some(body) = &body {
+            let content_type = item.content_type.as_deref().unwrap_or("application/json");
+            let Ok(content_type) = header::HeaderValue::from_str(content_type) else {
+                return error_response(400, format!("Invalid content type: {}", content_type));
+            };
+            head.headers.insert(header::CONTENT_TYPE, content_type);
+            head.headers.insert(
+                header::CONTENT_LENGTH,
+                header::HeaderValue::from(body.len()),
+            );
+        }
+
+        let (status, bytes) = match dispatch(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                let response = e.error_response();
+                let status = response.status().as_u16();
+                let body = to_bytes(response.into_body()).await.unwrap_or_default();
+                (status, body)
+            }
+        };
+        let body = if bytes.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap_or_else(|_| {
+                serde_json::Value::String(String::from_utf8_lossy(&bytes).into_owned())
+            })
+        };
+
+        BatchItemResponse { status, body }
+    }
+}
+
+/// Validate a sub-request, returning its method and URI.
+fn check(item: &BatchItemRequest) -> Result<(Method, Uri), String> {
+    let method = item.method.to_ascii_uppercase();
+    if !METHODS.contains(&method.as_str()) {
+        return Err(format!("Unsupported method: {}", item.method));
+    }
+    if !item.path.starts_with("/api/") || item.path.contains("..") {
+        return Err(format!("Path must be under /api/: {}", item.path));
+    }
+    let path = item.path.split(['?', '#']).next().unwrap_or_default();
+    if unversioned(path) == "/api/batch" {
+        return Err("Batches cannot be nested".to_string());
+    }
+    let method = Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+    let uri = item
+        .path
+        .parse()
+        .map_err(|_| format!("Invalid path: {}", item.path))?;
+    Ok((method, uri))
+}
+
+fn error_response(status: u16, message: String) -> BatchItemResponse {
+    BatchItemResponse {
+        status,
+        body: serde_json::json!({ "detail": message }),
+    }
+}
+
+/// POST /api/batch - Run up to 20 API requests in order
+pub async fn execute(
+    _identity: Identity,
+    req: HttpRequest,
+    body: web::Json<BatchRequest>,
+) -> AppResult<HttpResponse> {
+    let requests = body.into_inner().requests;
+    if requests.len() > MAX_REQUESTS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} requests per batch",
+            MAX_REQUESTS
+        )));
+    }
+    let dispatcher = req
+        .app_data::<BatchDispatcher>()
+        .ok_or_else(|| AppError::NotFound("Batches are not available".to_string()))?;
+
+    let mut responses = Vec::with_capacity(requests.len());
+    for item in &requests {
+        responses.push(dispatcher.send(&req, item).await);
+    }
+
+    Ok(HttpResponse::Ok().json(BatchResponse { responses }))
+}
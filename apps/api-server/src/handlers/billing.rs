@@ -0,0 +1,124 @@
+//! Subscription and billing provider handlers.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::{Plan, Subscription};
+use apex_shared::dto::{StartTrialRequest, SubscriptionResponse};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// GET /api/billing/subscription - Subscription of the caller's account
+pub async fn subscription(
+    identity: Identity,
+    state: web::Data<AppState>,
+) -> AppResult<HttpResponse> {
+    let sub = state
+        .subscriptions
+        .current(identity.account_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound("No subscription".to_string()))?;
+    Ok(HttpResponse::Ok().json(to_response(&sub)))
+}
+
+/// POST /api/billing/trial - Start a trial of a plan (org tokens: owner/admin)
+pub async fn start_trial(
+    identity: Identity,
+    state: web::Data<AppState>,
+    body: web::Json<StartTrialRequest>,
+) -> AppResult<HttpResponse> {
+    if let Some(org) = &identity.org
+        && !org.role.can_manage_members()
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    let plan: Plan = body.plan.parse()?;
+    if plan == Plan::Free {
+        return Err(AppError::BadRequest(
+            "The free plan has no trial".to_string(),
+        ));
+    }
+
+    let sub = state
+        .subscriptions
+        .start_trial(identity.account_id(), plan)
+        .await?;
+    tracing::info!(account_id = %sub.account_id, plan = %plan, "Trial started");
+
+    Ok(HttpResponse::Created().json(to_response(&sub)))
+}
+
+/// POST /api/billing/stripe/webhook - Subscription events from Stripe
+///
+/// Events that do not apply to the subscription's current state (e.g.
+/// delivered out of order) are logged and acknowledged, so Stripe does not
+/// retry them forever.
+#[cfg(feature = "billing")]
+pub async fn stripe_webhook(
+    req: actix_web::HttpRequest,
+    state: web::Data<AppState>,
+    verifier: Option<web::Data<apex_infra::StripeWebhookVerifier>>,
+    body: web::Bytes,
+) -> AppResult<HttpResponse> {
+    use apex_core::ports::SubscriptionError;
+    use apex_infra::billing::stripe::{StripeError, parse_event};
+
+    let verifier =
+        verifier.ok_or_else(|| AppError::NotFound("Stripe is not configured".to_string()))?;
+    let signature = req
+        .headers()
+        .get("Stripe-Signature")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(AppError::Unauthorized)?;
+
+    verifier
+        .verify(&body, signature, chrono::Utc::now())
+        .map_err(|e| match e {
+            StripeError::Malformed(msg) => AppError::BadRequest(msg),
+            _ => AppError::Unauthorized,
+        })?;
+
+    let update = match parse_event(&body) {
+        Ok(Some(update)) => update,
+        Ok(None) => return Ok(HttpResponse::Ok().finish()),
+        Err(e) => return Err(AppError::BadRequest(e.to_string())),
+    };
+
+    match state
+        .subscriptions
+        .apply_external(
+            update.account_id,
+            &update.external_id,
+            update.plan,
+            update.events,
+        )
+        .await
+    {
+        Ok(sub) => tracing::info!(
+            account_id = %sub.account_id,
+            status = %sub.status,
+            plan = %sub.plan,
+            "Subscription updated from Stripe"
+        ),
+        Err(SubscriptionError::Invalid(e)) => tracing::warn!(
+            error = %e,
+            account_id = %update.account_id,
+            "Ignoring Stripe event that does not apply to the subscription"
+        ),
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn to_response(sub: &Subscription) -> SubscriptionResponse {
+    SubscriptionResponse {
+        account_id: sub.account_id.to_string(),
+        plan: sub.plan.to_string(),
+        status: sub.status.to_string(),
+        trial_ends_at: sub.trial_ends_at.map(|at| at.to_rfc3339()),
+        current_period_end: sub.current_period_end.map(|at| at.to_rfc3339()),
+    }
+}
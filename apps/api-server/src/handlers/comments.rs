@@ -0,0 +1,93 @@
+//! Comment handlers, nested under a post.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use apex_core::domain::{Comment, PageRequest, Sort};
+use apex_shared::dto::{CommentResponse, CreateCommentRequest, OffsetPage};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppResult;
+use crate::middleware::validation::ValidatedJson;
+use crate::state::AppState;
+
+const MAX_LIMIT: u64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    #[serde(default)]
+    pub offset: u64,
+    pub limit: Option<u64>,
+}
+
+/// GET /api/posts/{id}/comments - A post's comments, oldest first, paged by
+/// offset
+pub async fn list(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    query: web::Query<ListQuery>,
+) -> AppResult<HttpResponse> {
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    let limit = query
+        .limit
+        .unwrap_or(PageRequest::DEFAULT_LIMIT)
+        .clamp(1, MAX_LIMIT);
+    let request = PageRequest::new(query.offset, limit).sorted_by(Sort::asc("created_at"));
+    let page = state
+        .comment_service()
+        .list(identity.user_id, org_id, path.into_inner(), request)
+        .await?;
+
+    let next_offset = page.next_offset();
+    Ok(HttpResponse::Ok().json(OffsetPage {
+        items: page.items.into_iter().map(to_response).collect(),
+        total: page.total,
+        offset: page.offset,
+        limit: page.limit,
+        next_offset,
+    }))
+}
+
+/// POST /api/posts/{id}/comments - Comment on a post the caller can read
+pub async fn create(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    body: ValidatedJson<CreateCommentRequest>,
+) -> AppResult<HttpResponse> {
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    let comment = state
+        .comment_service()
+        .create(identity.user_id, org_id, path.into_inner(), &body.body)
+        .await?;
+    Ok(HttpResponse::Created().json(to_response(comment)))
+}
+
+/// DELETE /api/posts/{id}/comments/{comment_id} - Delete one of the
+/// caller's comments, or any comment on one of their posts
+pub async fn delete(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> AppResult<HttpResponse> {
+    let (post_id, id) = path.into_inner();
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    state
+        .comment_service()
+        .delete(identity.user_id, org_id, post_id, id)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+fn to_response(comment: Comment) -> CommentResponse {
+    CommentResponse {
+        id: comment.id.to_string(),
+        post_id: comment.post_id.to_string(),
+        user_id: comment.user_id.to_string(),
+        body: comment.body,
+        created_at: comment.created_at.to_rfc3339(),
+        updated_at: comment.updated_at.to_rfc3339(),
+    }
+}
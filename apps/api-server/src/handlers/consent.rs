@@ -0,0 +1,71 @@
+//! Policy consent handlers.
+
+use actix_web::{HttpRequest, HttpResponse, http::header, web};
+
+use apex_core::domain::{PolicyAcceptance, PolicyDocument};
+use apex_shared::dto::{AcceptPolicyRequest, PolicyConsentResponse};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// GET /api/consent - Current policy versions and whether the caller accepted them
+pub async fn status(identity: Identity, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let body = consent_status(&state, identity.user_id).await?;
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// POST /api/consent - Accept the current version of a policy
+///
+/// The acceptance is stored with the caller's IP address and user agent as
+/// the audit record.
+pub async fn accept(
+    identity: Identity,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: web::Json<AcceptPolicyRequest>,
+) -> AppResult<HttpResponse> {
+    let policy: PolicyDocument = body.policy.parse()?;
+    let ip_address = req
+        .connection_info()
+        .realip_remote_addr()
+        .map(str::to_string);
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let acceptance = PolicyAcceptance::new(identity.user_id, policy, body.version.clone())
+        .with_origin(ip_address, user_agent);
+    state.consent.accept(acceptance).await?;
+
+    let body = consent_status(&state, identity.user_id).await?;
+    Ok(HttpResponse::Created().json(body))
+}
+
+async fn consent_status(
+    state: &AppState,
+    user_id: uuid::Uuid,
+) -> AppResult<Vec<PolicyConsentResponse>> {
+    let history = state.consent.history(user_id).await?;
+
+    Ok(state
+        .consent
+        .versions()
+        .iter()
+        .map(|(policy, current)| {
+            // History is newest first
+            let latest = history.iter().find(|a| a.policy == policy);
+            PolicyConsentResponse {
+                policy: policy.to_string(),
+                current_version: current.to_string(),
+                accepted_version: latest.map(|a| a.version.clone()),
+                accepted_at: latest.map(|a| a.accepted_at.to_rfc3339()),
+                required: !history
+                    .iter()
+                    .any(|a| a.policy == policy && a.version == current),
+            }
+        })
+        .collect())
+}
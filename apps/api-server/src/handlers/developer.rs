@@ -0,0 +1,176 @@
+//! Developer portal handlers: OAuth client registration.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use apex_core::domain::{OAuthClient, apply_patch, parse_client_id};
+use apex_core::ports::PasswordService;
+use apex_shared::dto::{OAuthClientRequest, OAuthClientResponse};
+
+use super::id_taken;
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::middleware::patch::PatchBody;
+use crate::state::AppState;
+
+/// GET /api/developer/clients - OAuth clients registered by the caller
+pub async fn list(identity: Identity, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let clients = state
+        .oauth_clients
+        .load()
+        .list_by_owner(identity.user_id)
+        .await?;
+    let body: Vec<OAuthClientResponse> = clients.iter().map(|c| to_response(c, None)).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// POST /api/developer/clients - Register a client; the secret is only returned here
+pub async fn create(
+    identity: Identity,
+    state: web::Data<AppState>,
+    password_service: web::Data<Arc<dyn PasswordService>>,
+    body: web::Json<OAuthClientRequest>,
+) -> AppResult<HttpResponse> {
+    let req = body.into_inner();
+    let client_id = req.id.as_deref().map(parse_client_id).transpose()?;
+    if let Some(id) = client_id
+        && let Some(existing) = state.oauth_clients.load().find_by_id(id).await?
+    {
+        // A retry gets the client the first attempt registered. Its secret
+        // was only in that response; rotate it if the response was lost.
+        if existing.owner_id == identity.user_id && existing.name == req.name {
+            return Ok(HttpResponse::Ok().json(to_response(&existing, None)));
+        }
+        return Err(id_taken(id));
+    }
+
+    let secret = OAuthClient::generate_secret();
+    let mut client = OAuthClient::new(
+        identity.user_id,
+        req.name,
+        req.redirect_uris,
+        req.scopes,
+        hash_secret(&password_service, &secret)?,
+    )?;
+    if let Some(id) = client_id {
+        client.id = id;
+    }
+
+    let client = state.oauth_clients.load().insert(client).await?;
+    tracing::info!(owner_id = %client.owner_id, client_id = %client.client_id, "OAuth client registered");
+
+    Ok(HttpResponse::Created().json(to_response(&client, Some(secret))))
+}
+
+/// GET /api/developer/clients/{id}
+pub async fn get(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let client = owned_client(&state, &identity, path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(to_response(&client, None)))
+}
+
+/// PUT /api/developer/clients/{id} - Replace name, redirect URIs and scopes
+pub async fn update(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<OAuthClientRequest>,
+) -> AppResult<HttpResponse> {
+    let client = owned_client(&state, &identity, path.into_inner()).await?;
+    revise(&state, client, body.into_inner()).await
+}
+
+/// PATCH /api/developer/clients/{id} - Merge patch or JSON patch of the PUT body
+pub async fn patch(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: PatchBody,
+) -> AppResult<HttpResponse> {
+    let client = owned_client(&state, &identity, path.into_inner()).await?;
+    let current = OAuthClientRequest {
+        id: None,
+        name: client.name.clone(),
+        redirect_uris: client.redirect_uris.clone(),
+        scopes: client.scopes.clone(),
+    };
+    let req = apply_patch(&current, &body.0)?;
+    revise(&state, client, req).await
+}
+
+async fn revise(
+    state: &AppState,
+    mut client: OAuthClient,
+    req: OAuthClientRequest,
+) -> AppResult<HttpResponse> {
+    client.revise(req.name, req.redirect_uris, req.scopes)?;
+
+    let client = state.oauth_clients.load().save(client).await?;
+    Ok(HttpResponse::Ok().json(to_response(&client, None)))
+}
+
+/// POST /api/developer/clients/{id}/secret - Issue a new secret, revoking the old one
+pub async fn rotate_secret(
+    identity: Identity,
+    state: web::Data<AppState>,
+    password_service: web::Data<Arc<dyn PasswordService>>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let mut client = owned_client(&state, &identity, path.into_inner()).await?;
+    let secret = OAuthClient::generate_secret();
+    client.rotate_secret(hash_secret(&password_service, &secret)?);
+
+    let client = state.oauth_clients.load().save(client).await?;
+    tracing::info!(client_id = %client.client_id, "OAuth client secret rotated");
+
+    Ok(HttpResponse::Ok().json(to_response(&client, Some(secret))))
+}
+
+/// DELETE /api/developer/clients/{id}
+pub async fn delete(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let client = owned_client(&state, &identity, path.into_inner()).await?;
+    state.oauth_clients.load().delete(client.id).await?;
+    tracing::info!(client_id = %client.client_id, "OAuth client deleted");
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// The client, if the caller registered it. Other users' clients read as missing.
+async fn owned_client(
+    state: &AppState,
+    identity: &Identity,
+    id: uuid::Uuid,
+) -> AppResult<OAuthClient> {
+    state
+        .oauth_clients
+        .load()
+        .find_by_id(id)
+        .await?
+        .filter(|client| client.owner_id == identity.user_id)
+        .ok_or_else(|| AppError::NotFound(format!("OAuth client {} not found", id)))
+}
+
+fn hash_secret(password_service: &Arc<dyn PasswordService>, secret: &str) -> AppResult<String> {
+    password_service
+        .hash(secret)
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+fn to_response(client: &OAuthClient, client_secret: Option<String>) -> OAuthClientResponse {
+    OAuthClientResponse {
+        id: client.id.to_string(),
+        client_id: client.client_id.clone(),
+        name: client.name.clone(),
+        redirect_uris: client.redirect_uris.clone(),
+        scopes: client.scopes.clone(),
+        created_at: client.created_at.to_rfc3339(),
+        client_secret,
+    }
+}
@@ -0,0 +1,94 @@
+//! Custom domain handlers.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::CustomDomain;
+use apex_shared::dto::{CreateCustomDomainRequest, CustomDomainResponse, DomainChallengeResponse};
+
+use super::orgs::require_membership;
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// GET /api/orgs/{id}/domains
+pub async fn list(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let org_id = path.into_inner();
+    require_membership(&state, org_id, identity.user_id).await?;
+
+    let domains = state.domains.list(org_id).await?;
+    let body: Vec<CustomDomainResponse> = domains.iter().map(to_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// POST /api/orgs/{id}/domains - Add a domain (owner/admin only)
+///
+/// The response carries the TXT record to publish before verifying.
+pub async fn create(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<CreateCustomDomainRequest>,
+) -> AppResult<HttpResponse> {
+    let org_id = path.into_inner();
+    require_manager(&state, org_id, &identity).await?;
+
+    let domain = state.domains.add(org_id, &body.hostname).await?;
+    Ok(HttpResponse::Created().json(to_response(&domain)))
+}
+
+/// POST /api/orgs/{id}/domains/{domain_id}/verify - Check the TXT record
+/// (owner/admin only)
+pub async fn verify(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> AppResult<HttpResponse> {
+    let (org_id, domain_id) = path.into_inner();
+    require_manager(&state, org_id, &identity).await?;
+
+    let domain = state.domains.verify(org_id, domain_id).await?;
+    Ok(HttpResponse::Ok().json(to_response(&domain)))
+}
+
+/// DELETE /api/orgs/{id}/domains/{domain_id} (owner/admin only)
+pub async fn delete(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> AppResult<HttpResponse> {
+    let (org_id, domain_id) = path.into_inner();
+    require_manager(&state, org_id, &identity).await?;
+
+    state.domains.remove(org_id, domain_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn require_manager(
+    state: &AppState,
+    org_id: uuid::Uuid,
+    identity: &Identity,
+) -> AppResult<()> {
+    let membership = require_membership(state, org_id, identity.user_id).await?;
+    if !membership.role.can_manage_members() {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+fn to_response(domain: &CustomDomain) -> CustomDomainResponse {
+    CustomDomainResponse {
+        id: domain.id.to_string(),
+        hostname: domain.hostname.clone(),
+        verified: domain.is_verified(),
+        verified_at: domain.verified_at.map(|at| at.to_rfc3339()),
+        verification: DomainChallengeResponse {
+            record_type: "TXT".to_string(),
+            name: domain.challenge_name(),
+            value: domain.challenge_value(),
+        },
+    }
+}
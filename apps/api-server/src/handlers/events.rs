@@ -0,0 +1,107 @@
+//! Server-Sent Events: a user's realtime events over a plain HTTP response.
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse, http::header, web};
+use futures::StreamExt;
+
+use apex_infra::user_events::UserEvent;
+use apex_infra::{InMemoryPubSub, UserEventHub};
+use apex_shared::dto::NotificationResponse;
+
+use crate::env;
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+
+/// How long clients wait before reconnecting, sent as the stream's `retry`.
+const RETRY: Duration = Duration::from_secs(3);
+
+/// How often an idle stream gets a comment, so proxies keep it open.
+#[derive(Debug, Clone, Copy)]
+pub struct EventStreamConfig {
+    pub keep_alive: Duration,
+}
+
+impl EventStreamConfig {
+    /// `SSE_KEEPALIVE_SECS`, 15 by default.
+    pub fn from_env() -> Self {
+        // Zero would send nothing but comments
+        let secs = env::SSE_KEEPALIVE_SECS
+            .parse::<u64>()
+            .filter(|&secs| secs > 0)
+            .or_else(|| env::SSE_KEEPALIVE_SECS.default?.parse().ok())
+            .unwrap_or(15);
+        Self {
+            keep_alive: Duration::from_secs(secs),
+        }
+    }
+}
+
+/// GET /api/events - The caller's events as `text/event-stream`
+///
+/// A lighter alternative to the WebSocket for clients that only listen.
+/// Every event has an `id`; reconnecting with the last one in
+/// `Last-Event-ID` resumes after it. A `truncated` event means some were
+/// missed and the client should refetch what it shows.
+pub async fn stream(
+    identity: Identity,
+    hub: web::Data<UserEventHub<InMemoryPubSub>>,
+    config: web::Data<EventStreamConfig>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let last_seen = req
+        .headers()
+        .get("Last-Event-ID")
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .ok_or_else(|| AppError::BadRequest("Invalid Last-Event-ID".to_string()))
+        })
+        .transpose()?;
+
+    let events = hub.open(identity.user_id, last_seen).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to open event stream");
+        AppError::Internal("Event stream unavailable".to_string())
+    })?;
+
+    let mut opening = format!("retry: {}\n\n", RETRY.as_millis());
+    if events.truncated {
+        opening.push_str("event: truncated\ndata: {}\n\n");
+    }
+    let keep_alive = config.keep_alive;
+    let ticks = tokio::time::interval_at(tokio::time::Instant::now() + keep_alive, keep_alive);
+    let body = futures::stream::once(async { Ok::<_, Infallible>(Bytes::from(opening)) }).chain(
+        futures::stream::unfold((events, ticks), |(mut events, mut ticks)| async move {
+            let frame = tokio::select! {
+                event = events.next() => frame(event?),
+                _ = ticks.tick() => Bytes::from_static(b": keep-alive\n\n"),
+            };
+            Some((Ok(frame), (events, ticks)))
+        }),
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Stop nginx from buffering the stream
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body))
+}
+
+/// An event in the stream's wire format.
+fn frame(event: UserEvent) -> Bytes {
+    let seq = event.seq;
+    let name = event.event.clone();
+    let data = serde_json::to_string(&NotificationResponse {
+        id: event.id.to_string(),
+        event: event.event,
+        timestamp: event.timestamp.to_rfc3339(),
+        data: event.data,
+    })
+    .unwrap_or_else(|_| "{}".to_string());
+    Bytes::from(format!("id: {}\nevent: {}\ndata: {}\n\n", seq, name, data))
+}
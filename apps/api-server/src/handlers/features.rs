@@ -0,0 +1,18 @@
+//! Feature flag handlers.
+
+use actix_web::HttpResponse;
+
+use apex_shared::dto::FeatureFlagsResponse;
+
+use crate::middleware::feature_flags::Flags;
+
+/// GET /api/features - Flags as evaluated for this request, overrides included
+pub async fn list(flags: Flags) -> HttpResponse {
+    HttpResponse::Ok().json(FeatureFlagsResponse {
+        flags: flags
+            .iter()
+            .map(|(flag, enabled)| (flag.to_string(), enabled))
+            .collect(),
+        overridden: flags.overridden(),
+    })
+}
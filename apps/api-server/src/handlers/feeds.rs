@@ -0,0 +1,57 @@
+//! Public sitemap and RSS feed handlers.
+
+use actix_web::{HttpRequest, HttpResponse, http::header, web};
+use chrono::{DateTime, Utc};
+
+use apex_infra::feeds::RenderedFeed;
+
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// GET /sitemap.xml - Published posts, for crawlers
+pub async fn sitemap(req: HttpRequest, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let feed = state.feeds.sitemap().await?;
+    Ok(respond(&req, feed, "application/xml"))
+}
+
+/// GET /feed.xml - RSS feed of the latest published posts
+pub async fn rss(req: HttpRequest, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let feed = state.feeds.rss().await?;
+    Ok(respond(&req, feed, "application/rss+xml"))
+}
+
+/// The feed, or 304 Not Modified when the client's conditional headers
+/// show it has it already.
+fn respond(req: &HttpRequest, feed: RenderedFeed, content_type: &str) -> HttpResponse {
+    let headers = req.headers();
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok());
+    let if_modified_since = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .map(|at| at.with_timezone(&Utc));
+
+    let not_modified = feed.not_modified(if_none_match, if_modified_since);
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response.insert_header((header::ETAG, feed.etag.clone()));
+    if let Some(modified) = feed.last_modified {
+        response.insert_header((
+            header::LAST_MODIFIED,
+            modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        ));
+    }
+
+    if not_modified {
+        response.finish()
+    } else {
+        response
+            .content_type(format!("{}; charset=utf-8", content_type))
+            .body(feed.body)
+    }
+}
@@ -0,0 +1,41 @@
+//! Stored file downloads, authorized by a signed link rather than a session.
+
+use actix_web::{HttpResponse, http::header, web};
+use serde::Deserialize;
+
+use apex_core::ports::StorageService;
+use apex_infra::LocalStorage;
+
+use crate::middleware::error::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct SignedLink {
+    expires: i64,
+    signature: String,
+}
+
+/// GET /api/files/{key} - Download a file with a signed, unexpired link
+pub async fn download(
+    storage: web::Data<LocalStorage>,
+    key: web::Path<String>,
+    link: web::Query<SignedLink>,
+) -> Result<HttpResponse, AppError> {
+    if !storage.verify(&key, link.expires, &link.signature) {
+        return Err(AppError::Unauthorized);
+    }
+
+    let object = storage
+        .get(&key)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+
+    let filename = key.rsplit('/').next().unwrap_or(&key);
+    Ok(HttpResponse::Ok()
+        .content_type(object.content_type)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        ))
+        .body(object.bytes))
+}
@@ -0,0 +1,21 @@
+//! GraphQL endpoint.
+
+use actix_web::web;
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+
+use crate::graphql::{self, ApexSchema};
+use crate::middleware::auth::OptionalIdentity;
+use crate::state::AppState;
+
+/// POST /api/graphql - Queries over users and posts. Signed out, only
+/// published posts and public user fields are readable; field guards
+/// decide the rest
+pub async fn execute(
+    schema: web::Data<ApexSchema>,
+    state: web::Data<AppState>,
+    identity: OptionalIdentity,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let request = graphql::with_caller(request.into_inner(), state, identity.0);
+    schema.execute(request).await.into()
+}
@@ -1,26 +1,76 @@
-//! Health check endpoint.
+//! Liveness and readiness probes.
 
 use actix_web::{HttpResponse, web};
 use serde::Serialize;
+use std::collections::BTreeMap;
 
-use crate::state::AppState;
+use apex_infra::HealthCheckRegistry;
 
 #[derive(Serialize)]
-pub struct HealthResponse {
+pub struct LivenessResponse {
     pub status: &'static str,
     pub version: &'static str,
     pub timestamp: String,
 }
 
-/// Health check endpoint - returns server status.
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    /// `ready`, or `unavailable` while any dependency is down.
+    pub status: &'static str,
+    pub checks: BTreeMap<String, DependencyCheck>,
+}
+
+#[derive(Serialize)]
+pub struct DependencyCheck {
+    /// `up` or `down`.
+    pub status: &'static str,
+    pub latency_ms: u64,
+}
+
+/// Liveness probe - the process is up and answering. Dependencies are left
+/// out on purpose: an outage of one should not get every instance restarted.
 ///
-/// GET /api/health
-pub async fn health_check(_state: web::Data<AppState>) -> HttpResponse {
-    let response = HealthResponse {
+/// GET /healthz
+pub async fn liveness() -> HttpResponse {
+    HttpResponse::Ok().json(LivenessResponse {
         status: "ok",
         version: env!("CARGO_PKG_VERSION"),
         timestamp: chrono::Utc::now().to_rfc3339(),
-    };
+    })
+}
+
+/// Readiness probe - pings the database, Redis and the job queue now, and
+/// answers 503 while any of them is down so load balancers route elsewhere.
+/// Why a check failed is logged rather than served; admins see it on
+/// `/api/admin/dependencies`.
+///
+/// GET /readyz
+pub async fn readiness(registry: web::Data<HealthCheckRegistry>) -> HttpResponse {
+    let results = registry.check_all().await;
+
+    let mut checks = BTreeMap::new();
+    for result in &results {
+        if let Some(error) = &result.error {
+            tracing::warn!(dependency = %result.name, %error, "Readiness check failed");
+        }
+        checks.insert(
+            result.name.clone(),
+            DependencyCheck {
+                status: if result.is_up() { "up" } else { "down" },
+                latency_ms: result.latency.as_millis() as u64,
+            },
+        );
+    }
 
-    HttpResponse::Ok().json(response)
+    if results.iter().all(|result| result.is_up()) {
+        HttpResponse::Ok().json(ReadinessResponse {
+            status: "ready",
+            checks,
+        })
+    } else {
+        HttpResponse::ServiceUnavailable().json(ReadinessResponse {
+            status: "unavailable",
+            checks,
+        })
+    }
 }
@@ -0,0 +1,41 @@
+//! Prometheus scrape endpoint.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use crate::middleware::error::{AppError, AppResult};
+use crate::observability::RequestSeries;
+use crate::state::AppState;
+
+/// GET /metrics - Request series, and the database's query series, in the
+/// Prometheus text format
+///
+/// Served only with `METRICS_ENABLED=true`; keep it off the public network
+/// or behind the proxy's own authentication.
+pub async fn prometheus(
+    series: web::Data<Arc<RequestSeries>>,
+    state: web::Data<AppState>,
+) -> AppResult<HttpResponse> {
+    if !series.exposed() {
+        return Err(AppError::NotFound("Metrics are not enabled".to_string()));
+    }
+    let mut body = series.render();
+    body.push_str(&query_series(&state));
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(body))
+}
+
+#[cfg(feature = "postgres")]
+fn query_series(state: &AppState) -> String {
+    state
+        .db
+        .as_ref()
+        .map(|db| db.queries.render())
+        .unwrap_or_default()
+}
+
+#[cfg(not(feature = "postgres"))]
+fn query_series(_state: &AppState) -> String {
+    String::new()
+}
@@ -1,53 +1,140 @@
 //! API route handlers.
 
+mod features;
 mod health;
+mod metrics;
+mod site;
 
+#[cfg(feature = "auth")]
+mod admin;
+#[cfg(feature = "auth")]
+mod announcements;
+#[cfg(all(feature = "auth", feature = "storage"))]
+mod attachments;
 #[cfg(feature = "auth")]
 mod auth;
+#[cfg(feature = "auth")]
+mod batch;
+#[cfg(feature = "auth")]
+mod billing;
+#[cfg(feature = "auth")]
+mod comments;
+#[cfg(feature = "auth")]
+mod consent;
+#[cfg(feature = "auth")]
+mod developer;
+#[cfg(feature = "auth")]
+mod domains;
+#[cfg(feature = "auth")]
+mod events;
+#[cfg(feature = "auth")]
+mod feeds;
+#[cfg(feature = "storage")]
+mod files;
+#[cfg(feature = "graphql")]
+mod graphql;
+#[cfg(feature = "auth")]
+mod notifications;
+#[cfg(feature = "auth")]
+mod orgs;
+#[cfg(feature = "auth")]
+mod plans;
+#[cfg(feature = "auth")]
+mod posts;
+#[cfg(feature = "auth")]
+mod settings;
+#[cfg(feature = "auth")]
+mod sync;
+#[cfg(feature = "auth")]
+mod tags;
+#[cfg(all(feature = "auth", feature = "storage"))]
+mod uploads;
+#[cfg(feature = "auth")]
+mod usage;
 
 use actix_web::web;
 
-/// Configure all API routes.
+#[cfg(feature = "auth")]
+pub use batch::BatchDispatcher;
+#[cfg(feature = "auth")]
+pub use events::EventStreamConfig;
+#[cfg(all(feature = "auth", feature = "storage"))]
+pub use uploads::UploadPolicy;
+
+#[cfg(feature = "auth")]
+use crate::middleware::error::AppError;
+
+/// Configure all API routes, one scope per API version. Unversioned
+/// `/api/...` requests are routed to one of them by
+/// `middleware::versioning::ApiVersioning`.
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::scope("/api")
-            .route("/health", web::get().to(health::health_check))
-            .configure(configure_auth_routes),
-    );
+    cfg.service(web::scope("/api/v1").configure(configure_v1))
+        .service(web::scope("/api/v2").configure(configure_v2))
+        .configure(configure_probe_routes)
+        .configure(configure_feed_routes)
+        .configure(configure_site_routes)
+        .configure(configure_metrics_routes);
 }
 
-/// Configure auth routes with stricter rate limiting.
-#[cfg(all(feature = "auth", feature = "rate-limit"))]
-fn configure_auth_routes(cfg: &mut web::ServiceConfig) {
-    use crate::middleware::rate_limit::RateLimitMiddleware;
-    use apex_infra::{InMemoryRateLimiter, RateLimitConfig};
-    use std::sync::Arc;
-    use std::time::Duration;
-
-    // Stricter rate limit for auth: 10 requests per 60 seconds
-    // This helps prevent brute-force login attempts
-    let auth_limiter = Arc::new(InMemoryRateLimiter::new(RateLimitConfig {
-        max_requests: 10,
-        window: Duration::from_secs(60),
-    }));
+/// Configure version 1 of the API.
+fn configure_v1(cfg: &mut web::ServiceConfig) {
+    cfg.route("/features", web::get().to(features::list))
+        .configure(configure_auth_routes)
+        .configure(configure_org_routes)
+        .configure(configure_admin_routes)
+        .configure(configure_file_routes)
+        .configure(configure_graphql_routes);
+}
 
-    cfg.service(
-        web::scope("/auth")
-            .wrap(RateLimitMiddleware::new(auth_limiter))
-            .route("/register", web::post().to(auth::register))
-            .route("/login", web::post().to(auth::login))
-            .route("/me", web::get().to(auth::me)),
-    );
+/// Configure version 2 of the API: version 1's routes until the two
+/// diverge. A v2 route registered ahead of `configure_v1` takes over its
+/// path and method.
+fn configure_v2(cfg: &mut web::ServiceConfig) {
+    configure_v1(cfg);
 }
 
-/// Configure auth routes without rate limiting (rate-limit feature disabled).
-#[cfg(all(feature = "auth", not(feature = "rate-limit")))]
+/// Configure the liveness and readiness probes, at the site root where
+/// orchestrators look for them.
+fn configure_probe_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/healthz", web::get().to(health::liveness))
+        .route("/readyz", web::get().to(health::readiness));
+}
+
+/// Configure the Prometheus scrape endpoint, at the site root where
+/// scrapers look for it.
+fn configure_metrics_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(metrics::prometheus));
+}
+
+/// Configure `robots.txt` and the well-known documents, at the site root.
+fn configure_site_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/robots.txt", web::get().to(site::robots))
+        .route("/.well-known/{name}", web::get().to(site::well_known));
+}
+
+/// Configure the public sitemap and feed, at the site root where crawlers
+/// and feed readers look for them.
+#[cfg(feature = "auth")]
+fn configure_feed_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/sitemap.xml", web::get().to(feeds::sitemap))
+        .route("/feed.xml", web::get().to(feeds::rss));
+}
+
+#[cfg(not(feature = "auth"))]
+fn configure_feed_routes(_cfg: &mut web::ServiceConfig) {
+    // Posts require authentication
+}
+
+/// Configure auth routes. Their stricter rate limit (against brute-force
+/// login attempts) is part of the rate limit policy, see `RATE_LIMIT_ROUTES`.
+#[cfg(feature = "auth")]
 fn configure_auth_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
             .route("/register", web::post().to(auth::register))
             .route("/login", web::post().to(auth::login))
-            .route("/me", web::get().to(auth::me)),
+            .route("/me", web::get().to(auth::me))
+            .route("/password", web::put().to(auth::change_password)),
     );
 }
 
@@ -55,3 +142,183 @@ fn configure_auth_routes(cfg: &mut web::ServiceConfig) {
 fn configure_auth_routes(_cfg: &mut web::ServiceConfig) {
     // No auth routes when feature is disabled
 }
+
+/// Configure organization, invitation, custom domain, settings, plan, post, billing, usage,
+/// announcement, consent, developer portal, offline sync and batch routes.
+#[cfg(feature = "auth")]
+fn configure_org_routes(cfg: &mut web::ServiceConfig) {
+    use crate::middleware::consent::ConsentCheck;
+    use crate::middleware::entitlement::RequireEntitlement;
+    use apex_core::domain::Entitlement;
+
+    cfg.service(
+        web::scope("/orgs")
+            .route("", web::post().to(orgs::create))
+            .route("", web::get().to(orgs::list))
+            .route("/current", web::get().to(orgs::current))
+            .route("/{id}/members", web::get().to(orgs::members))
+            .route("/{id}/invitations", web::post().to(orgs::invite))
+            .route("/{id}/switch", web::post().to(orgs::switch))
+            .route("/{id}/domains", web::get().to(domains::list))
+            .route("/{id}/domains", web::post().to(domains::create))
+            .route(
+                "/{id}/domains/{domain_id}/verify",
+                web::post().to(domains::verify),
+            )
+            .route(
+                "/{id}/domains/{domain_id}",
+                web::delete().to(domains::delete),
+            )
+            .route("/{id}/settings", web::get().to(settings::get_org))
+            .route("/{id}/settings", web::patch().to(settings::update_org)),
+    )
+    .service(
+        web::scope("/settings")
+            .route("/me", web::get().to(settings::get_mine))
+            .route("/me", web::patch().to(settings::update_mine)),
+    )
+    .route("/plan", web::get().to(plans::current))
+    .service(
+        web::scope("/posts")
+            .route("", web::get().to(posts::list))
+            .route("", web::post().to(posts::create))
+            .route("/{id}", web::get().to(posts::get))
+            .route("/{id}", web::put().to(posts::update))
+            .route("/{id}", web::delete().to(posts::delete))
+            .route("/{id}/publish", web::post().to(posts::publish))
+            .route("/{id}/publish", web::delete().to(posts::unpublish))
+            .route("/{id}/comments", web::get().to(comments::list))
+            .route("/{id}/comments", web::post().to(comments::create))
+            .route(
+                "/{id}/comments/{comment_id}",
+                web::delete().to(comments::delete),
+            )
+            .route("/{id}/tags", web::get().to(tags::list))
+            .route("/{id}/tags", web::put().to(tags::set))
+            .configure(configure_post_attachment_routes),
+    )
+    .route("/announcements", web::get().to(announcements::list))
+    .route("/notifications/poll", web::get().to(notifications::poll))
+    .route("/events", web::get().to(events::stream))
+    .route("/consent", web::get().to(consent::status))
+    .route("/consent", web::post().to(consent::accept))
+    .service(
+        web::scope("/developer/clients")
+            .route("", web::get().to(developer::list))
+            .route("", web::post().to(developer::create))
+            .route("/{id}", web::get().to(developer::get))
+            .route("/{id}", web::put().to(developer::update))
+            .route("/{id}", web::patch().to(developer::patch))
+            .route("/{id}", web::delete().to(developer::delete))
+            .route("/{id}/secret", web::post().to(developer::rotate_secret)),
+    )
+    .service(
+        web::scope("/sync")
+            .route("/pull", web::get().to(sync::pull))
+            .route("/push", web::post().to(sync::push)),
+    )
+    .route("/batch", web::post().to(batch::execute))
+    .service(configure_billing_routes())
+    .route("/usage", web::get().to(usage::get_usage))
+    .service(
+        web::resource("/usage/export")
+            .wrap(RequireEntitlement::new(Entitlement::Exports))
+            .wrap(ConsentCheck::enforce())
+            .route(web::get().to(usage::export_usage)),
+    )
+    .route(
+        "/invitations/{token}/accept",
+        web::post().to(orgs::accept_invitation),
+    );
+}
+
+#[cfg(feature = "auth")]
+fn configure_billing_routes() -> actix_web::Scope {
+    use crate::middleware::consent::ConsentCheck;
+
+    let scope = web::scope("/billing")
+        .route("/subscription", web::get().to(billing::subscription))
+        .service(
+            web::resource("/trial")
+                .wrap(ConsentCheck::enforce())
+                .route(web::post().to(billing::start_trial)),
+        );
+
+    #[cfg(feature = "billing")]
+    let scope = scope.route("/stripe/webhook", web::post().to(billing::stripe_webhook));
+
+    scope
+}
+
+#[cfg(not(feature = "auth"))]
+fn configure_org_routes(_cfg: &mut web::ServiceConfig) {
+    // Organizations require authentication
+}
+
+/// Configure admin routes (require the admin role).
+#[cfg(feature = "auth")]
+fn configure_admin_routes(cfg: &mut web::ServiceConfig) {
+    admin::configure(cfg);
+}
+
+#[cfg(not(feature = "auth"))]
+fn configure_admin_routes(_cfg: &mut web::ServiceConfig) {
+    // Admin routes require authentication
+}
+
+/// Configure stored file downloads, authorized by signed links.
+#[cfg(feature = "storage")]
+fn configure_file_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/files/{key:.*}", web::get().to(files::download));
+    #[cfg(feature = "auth")]
+    cfg.route("/uploads", web::post().to(uploads::create))
+        .route("/attachments/{id}", web::get().to(attachments::get))
+        .route("/attachments/{id}", web::delete().to(attachments::delete));
+}
+
+#[cfg(not(feature = "storage"))]
+fn configure_file_routes(_cfg: &mut web::ServiceConfig) {
+    // No file storage when feature is disabled
+}
+
+/// Configure the GraphQL endpoint.
+#[cfg(feature = "graphql")]
+fn configure_graphql_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/graphql", web::post().to(graphql::execute));
+}
+
+#[cfg(not(feature = "graphql"))]
+fn configure_graphql_routes(_cfg: &mut web::ServiceConfig) {
+    // No GraphQL when feature is disabled
+}
+
+/// Attachment routes nested under `/posts`.
+#[cfg(all(feature = "auth", feature = "storage"))]
+fn configure_post_attachment_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route(
+        "/{id}/attachments",
+        web::get().to(attachments::list_for_post),
+    );
+}
+
+#[cfg(all(feature = "auth", not(feature = "storage")))]
+fn configure_post_attachment_routes(_cfg: &mut web::ServiceConfig) {
+    // No attachments without file storage
+}
+
+/// Error for a create whose client-generated ID belongs to another resource.
+#[cfg(feature = "auth")]
+fn id_taken(id: uuid::Uuid) -> AppError {
+    AppError::Conflict(format!("Id {} is already in use", id))
+}
+
+#[cfg(feature = "auth")]
+fn storage_response(
+    usage: apex_core::domain::StorageUsage,
+) -> apex_shared::dto::StorageUsageResponse {
+    apex_shared::dto::StorageUsageResponse {
+        plan: usage.plan.to_string(),
+        used_bytes: usage.used_bytes,
+        quota_bytes: usage.quota_bytes,
+    }
+}
@@ -0,0 +1,85 @@
+//! Long-polling fallback for realtime notifications.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use apex_core::domain::Viewer;
+use apex_infra::NotificationLog;
+use apex_shared::dto::{NotificationPollResponse, NotificationResponse};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// Longest a poll waits, and how long it waits by default.
+const MAX_WAIT: Duration = Duration::from_secs(25);
+
+/// Time left for the response once a poll stops waiting.
+const RESPONSE_MARGIN: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Deserialize)]
+pub struct PollQuery {
+    /// Cursor of the previous poll; omit to wait for what comes next.
+    pub cursor: Option<String>,
+    /// Seconds to wait for a notification, at most 25 (the default).
+    pub timeout: Option<u64>,
+}
+
+/// GET /api/notifications/poll?cursor=&timeout= - Notifications after the
+/// cursor, waiting for one if there is none yet
+///
+/// For clients that cannot hold a WebSocket, e.g. behind proxies that cut
+/// long-lived connections. Poll again with the returned cursor; when
+/// `truncated` is set notifications were missed and the client should
+/// refetch what it shows.
+pub async fn poll(
+    identity: Identity,
+    state: web::Data<AppState>,
+    log: web::Data<Arc<NotificationLog>>,
+    query: web::Query<PollQuery>,
+) -> AppResult<HttpResponse> {
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(str::parse::<u64>)
+        .transpose()
+        .map_err(|_| AppError::BadRequest("Invalid cursor".to_string()))?;
+
+    // Answer before the request deadline cuts the poll off
+    let mut wait = query
+        .timeout
+        .map_or(MAX_WAIT, Duration::from_secs)
+        .min(MAX_WAIT);
+    if let Some(remaining) = apex_infra::context::current().and_then(|c| c.remaining()) {
+        wait = wait.min(remaining.saturating_sub(RESPONSE_MARGIN));
+    }
+
+    let plan = state.entitlements.plan_for(identity.account_id()).await?;
+    let viewer = Viewer {
+        roles: &identity.roles,
+        plan,
+        organization_id: identity.org.as_ref().map(|org| org.id),
+    };
+    let page = log
+        .poll(cursor, wait, |notification| {
+            notification.audience.includes(&viewer)
+        })
+        .await;
+
+    Ok(HttpResponse::Ok().json(NotificationPollResponse {
+        notifications: page
+            .notifications
+            .into_iter()
+            .map(|notification| NotificationResponse {
+                id: notification.id.to_string(),
+                event: notification.event,
+                timestamp: notification.timestamp.to_rfc3339(),
+                data: notification.data,
+            })
+            .collect(),
+        cursor: page.cursor.to_string(),
+        truncated: page.truncated,
+    }))
+}
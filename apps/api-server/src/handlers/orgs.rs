@@ -0,0 +1,327 @@
+//! Organization, membership and invitation handlers.
+
+use actix_web::{HttpResponse, web};
+use std::sync::Arc;
+
+use apex_core::domain::{
+    Email, Invitation, Membership, OrgRole, OrgSettings, Organization, SettingsScope,
+    parse_client_id,
+};
+use apex_core::ports::{OrgClaim, TokenService};
+use apex_shared::dto::{
+    AuthResponse, CreateInvitationRequest, CreateOrganizationRequest, InvitationResponse,
+    MembershipResponse, OrganizationResponse,
+};
+
+use super::{id_taken, storage_response};
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::middleware::tenant::Tenant;
+use crate::middleware::validation::ValidatedJson;
+use crate::state::AppState;
+
+/// POST /api/orgs - Create an organization owned by the caller
+pub async fn create(
+    identity: Identity,
+    state: web::Data<AppState>,
+    body: ValidatedJson<CreateOrganizationRequest>,
+) -> AppResult<HttpResponse> {
+    let req = body.into_inner();
+    let mut org = Organization::new(req.name, req.slug)?;
+
+    if let Some(id) = req.id.as_deref().map(parse_client_id).transpose()? {
+        // A retry gets the organization the first attempt created
+        if let Some(existing) = state.organizations.load().find_by_id(id).await? {
+            let owner = state
+                .memberships
+                .load()
+                .find_membership(id, identity.user_id)
+                .await?
+                .is_some_and(|m| m.role == OrgRole::Owner);
+            if owner && existing.slug == org.slug {
+                return Ok(HttpResponse::Ok().json(org_response(existing)));
+            }
+            return Err(id_taken(id));
+        }
+        org.id = id;
+    }
+
+    if state
+        .organizations
+        .load()
+        .find_by_slug(&org.slug)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict("Slug already taken".to_string()));
+    }
+
+    let org = state.organizations.load().insert(org).await?;
+    state
+        .memberships
+        .load()
+        .save(Membership::new(org.id, identity.user_id, OrgRole::Owner))
+        .await?;
+
+    Ok(HttpResponse::Created().json(org_response(org)))
+}
+
+/// GET /api/orgs - Organizations the caller belongs to
+pub async fn list(identity: Identity, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let orgs = state
+        .organizations
+        .load()
+        .list_for_user(identity.user_id)
+        .await?;
+    let body: Vec<OrganizationResponse> = orgs.into_iter().map(org_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// GET /api/orgs/current - Organization the caller's token is scoped to,
+/// or whose custom domain the request came in on
+pub async fn current(
+    identity: Identity,
+    tenant: Option<Tenant>,
+    state: web::Data<AppState>,
+) -> AppResult<HttpResponse> {
+    let org_id = match (identity.org, tenant) {
+        (Some(org), _) => org.id,
+        (None, Some(tenant)) => {
+            require_membership(&state, tenant.organization_id, identity.user_id).await?;
+            tenant.organization_id
+        }
+        (None, None) => return Err(AppError::NotFound("No active organization".to_string())),
+    };
+    let org = find_org(&state, org_id).await?;
+    let storage = state.storage.usage(org.id).await?;
+    Ok(HttpResponse::Ok().json(OrganizationResponse {
+        storage: Some(storage_response(storage)),
+        ..org_response(org)
+    }))
+}
+
+/// GET /api/orgs/{id}/members
+pub async fn members(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let org_id = path.into_inner();
+    require_membership(&state, org_id, identity.user_id).await?;
+
+    let members = state
+        .memberships
+        .load()
+        .list_by_organization(org_id)
+        .await?;
+    let body: Vec<MembershipResponse> = members.into_iter().map(membership_response).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// POST /api/orgs/{id}/invitations - Invite an email address (owner/admin only)
+///
+/// The invitation token is returned to the caller, who is responsible for
+/// delivering it to the invitee.
+pub async fn invite(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: ValidatedJson<CreateInvitationRequest>,
+) -> AppResult<HttpResponse> {
+    let org_id = path.into_inner();
+    let req = body.into_inner();
+
+    let membership = require_membership(&state, org_id, identity.user_id).await?;
+    let settings: OrgSettings = state
+        .settings
+        .get(SettingsScope::Organization(org_id))
+        .await?;
+    if !membership.role.can_manage_members() && !settings.allow_member_invites {
+        return Err(AppError::Forbidden);
+    }
+
+    let role: OrgRole = match req.role {
+        Some(role) => role.parse()?,
+        None => settings.invite_role(membership.role),
+    };
+    // Nobody can grant a role above their own
+    if role > membership.role {
+        return Err(AppError::Forbidden);
+    }
+    let email = Email::parse(&req.email)?;
+
+    let mut invitation = Invitation::new(org_id, email, role, identity.user_id);
+    if let Some(id) = req.id.as_deref().map(parse_client_id).transpose()? {
+        // A retry gets the invitation the first attempt created
+        if let Some(existing) = state.invitations.load().find_by_id(id).await? {
+            let same = existing.organization_id == org_id
+                && existing.invited_by == identity.user_id
+                && existing.email == invitation.email;
+            if same {
+                return Ok(HttpResponse::Ok().json(invitation_response(existing)));
+            }
+            return Err(id_taken(id));
+        }
+        invitation.id = id;
+    }
+    let invitation = state.invitations.load().insert(invitation).await?;
+
+    tracing::info!(
+        org_id = %org_id,
+        invitation_id = %invitation.id,
+        invited_by = %identity.user_id,
+        "Organization invitation created"
+    );
+
+    Ok(HttpResponse::Created().json(invitation_response(invitation)))
+}
+
+fn invitation_response(invitation: Invitation) -> InvitationResponse {
+    InvitationResponse {
+        id: invitation.id.to_string(),
+        organization_id: invitation.organization_id.to_string(),
+        email: invitation.email.into(),
+        role: invitation.role.to_string(),
+        token: invitation.token,
+        expires_at: invitation.expires_at.to_rfc3339(),
+    }
+}
+
+/// POST /api/invitations/{token}/accept
+pub async fn accept_invitation(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let mut invitation = state
+        .invitations
+        .load()
+        .find_by_token(&path.into_inner())
+        .await?
+        .filter(Invitation::is_pending)
+        .ok_or_else(|| AppError::NotFound("Invitation not found or expired".to_string()))?;
+
+    if !invitation
+        .email
+        .as_str()
+        .eq_ignore_ascii_case(&identity.email)
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    if state
+        .memberships
+        .load()
+        .find_membership(invitation.organization_id, identity.user_id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict("Already a member".to_string()));
+    }
+
+    let settings: OrgSettings = state
+        .settings
+        .get(SettingsScope::Organization(invitation.organization_id))
+        .await?;
+    if let Some(max_members) = settings.max_members {
+        let members = state
+            .memberships
+            .load()
+            .list_by_organization(invitation.organization_id)
+            .await?;
+        if members.len() >= max_members as usize {
+            return Err(AppError::Conflict(
+                "Organization has reached its member limit".to_string(),
+            ));
+        }
+    }
+
+    let membership = state
+        .memberships
+        .load()
+        .save(Membership::new(
+            invitation.organization_id,
+            identity.user_id,
+            invitation.role,
+        ))
+        .await?;
+
+    invitation.accepted_at = Some(chrono::Utc::now());
+    state.invitations.load().save(invitation).await?;
+
+    Ok(HttpResponse::Created().json(membership_response(membership)))
+}
+
+/// POST /api/orgs/{id}/switch - Issue a token scoped to the organization
+pub async fn switch(
+    identity: Identity,
+    state: web::Data<AppState>,
+    token_service: web::Data<Arc<dyn TokenService>>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let org_id = path.into_inner();
+    let membership = require_membership(&state, org_id, identity.user_id).await?;
+
+    let subscription = state.subscriptions.claim_for(org_id).await?;
+    let token = token_service
+        .generate_org_token(
+            identity.user_id,
+            &identity.email,
+            identity.roles,
+            OrgClaim {
+                id: org_id,
+                role: membership.role,
+            },
+            subscription,
+        )
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(AuthResponse {
+        access_token: token,
+        token_type: "Bearer".to_string(),
+        expires_in: token_service.expiration_seconds() as u64,
+    }))
+}
+
+async fn find_org(state: &AppState, id: uuid::Uuid) -> AppResult<Organization> {
+    state
+        .organizations
+        .load()
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Organization {} not found", id)))
+}
+
+/// Look up the caller's membership. Non-members get a 404 so organization
+/// IDs cannot be probed.
+pub(super) async fn require_membership(
+    state: &AppState,
+    org_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> AppResult<Membership> {
+    state
+        .memberships
+        .load()
+        .find_membership(org_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Organization {} not found", org_id)))
+}
+
+fn org_response(org: Organization) -> OrganizationResponse {
+    OrganizationResponse {
+        id: org.id.to_string(),
+        name: org.name,
+        slug: org.slug,
+        created_at: org.created_at.to_rfc3339(),
+        storage: None,
+    }
+}
+
+fn membership_response(membership: Membership) -> MembershipResponse {
+    MembershipResponse {
+        organization_id: membership.organization_id.to_string(),
+        user_id: membership.user_id.to_string(),
+        role: membership.role.to_string(),
+        created_at: membership.created_at.to_rfc3339(),
+    }
+}
@@ -0,0 +1,25 @@
+//! Plan and entitlement handlers.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::Plan;
+use apex_shared::dto::PlanResponse;
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// GET /api/plan - Plan of the caller's account, so clients can gate features
+pub async fn current(identity: Identity, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let account_id = identity.account_id();
+    let plan = state.entitlements.plan_for(account_id).await?;
+    Ok(HttpResponse::Ok().json(to_response(account_id, plan)))
+}
+
+pub(super) fn to_response(account_id: uuid::Uuid, plan: Plan) -> PlanResponse {
+    PlanResponse {
+        account_id: account_id.to_string(),
+        plan: plan.to_string(),
+        entitlements: plan.entitlements().iter().map(|e| e.to_string()).collect(),
+    }
+}
@@ -0,0 +1,166 @@
+//! Post handlers.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use apex_core::domain::{PageCursor, Post};
+use apex_shared::dto::{CreatePostRequest, CursorPage, PostResponse, UpdatePostRequest};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppResult;
+use crate::middleware::validation::ValidatedJson;
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// `next_cursor` of the previous page; omit for the newest posts.
+    pub after: Option<String>,
+    pub limit: Option<u64>,
+}
+
+/// POST /api/posts - Write a post, in the caller's active organization if
+/// any
+pub async fn create(
+    identity: Identity,
+    state: web::Data<AppState>,
+    body: ValidatedJson<CreatePostRequest>,
+) -> AppResult<HttpResponse> {
+    let body = body.into_inner();
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    let post = state
+        .post_service()
+        .create(identity.user_id, org_id, body.title, body.content)
+        .await?;
+    Ok(HttpResponse::Created().json(to_response(post, 0)))
+}
+
+/// GET /api/posts - The caller's posts, newest first, paged by cursor
+pub async fn list(
+    identity: Identity,
+    state: web::Data<AppState>,
+    query: web::Query<ListQuery>,
+) -> AppResult<HttpResponse> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let after = query.after.as_deref().map(PageCursor::decode).transpose()?;
+    let (posts, next) = state
+        .post_service()
+        .list(identity.user_id, after, limit)
+        .await?;
+
+    let mut items = Vec::with_capacity(posts.len());
+    for post in posts {
+        let view_count = post.view_count + state.post_views.pending(post.id).await;
+        items.push(to_response(post, view_count));
+    }
+    Ok(HttpResponse::Ok().json(CursorPage {
+        items,
+        next_cursor: next.map(|cursor| cursor.encode()),
+    }))
+}
+
+/// GET /api/posts/{id} - A post of the caller or their active organization.
+/// Counts a view.
+pub async fn get(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    let post = state
+        .post_service()
+        .get(identity.user_id, org_id, path.into_inner())
+        .await?;
+
+    state.post_views.record(post.id).await;
+    let view_count = post.view_count + state.post_views.pending(post.id).await;
+
+    Ok(HttpResponse::Ok().json(to_response(post, view_count)))
+}
+
+/// PUT /api/posts/{id} - Replace the title and content of one of the
+/// caller's posts
+pub async fn update(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    body: ValidatedJson<UpdatePostRequest>,
+) -> AppResult<HttpResponse> {
+    let body = body.into_inner();
+    let post = state
+        .post_service()
+        .update(
+            identity.user_id,
+            path.into_inner(),
+            body.title,
+            body.content,
+            body.version,
+        )
+        .await?;
+    let view_count = post.view_count + state.post_views.pending(post.id).await;
+    Ok(HttpResponse::Ok().json(to_response(post, view_count)))
+}
+
+/// DELETE /api/posts/{id} - Delete one of the caller's posts
+pub async fn delete(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    state
+        .post_service()
+        .delete(identity.user_id, path.into_inner())
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/posts/{id}/publish - Make one of the caller's posts public, in
+/// the sitemap and feed
+pub async fn publish(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let (post, _) = state
+        .post_service()
+        .publish(identity.user_id, path.into_inner())
+        .await?;
+    published_response(&state, post).await
+}
+
+/// DELETE /api/posts/{id}/publish - Take one of the caller's posts out of
+/// public view
+pub async fn unpublish(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let (post, _) = state
+        .post_service()
+        .unpublish(identity.user_id, path.into_inner())
+        .await?;
+    published_response(&state, post).await
+}
+
+async fn published_response(state: &AppState, post: Post) -> AppResult<HttpResponse> {
+    let view_count = post.view_count + state.post_views.pending(post.id).await;
+    Ok(HttpResponse::Ok().json(to_response(post, view_count)))
+}
+
+pub(super) fn to_response(post: Post, view_count: i64) -> PostResponse {
+    PostResponse {
+        id: post.id.to_string(),
+        user_id: post.user_id.to_string(),
+        organization_id: post.organization_id.map(|id| id.to_string()),
+        title: post.title,
+        content: post.content,
+        version: post.version,
+        view_count,
+        published_at: post.published_at.map(|at| at.to_rfc3339()),
+        created_at: post.created_at.to_rfc3339(),
+        updated_at: post.updated_at.to_rfc3339(),
+    }
+}
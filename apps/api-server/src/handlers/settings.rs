@@ -0,0 +1,69 @@
+//! Settings handlers for users and organizations.
+
+use actix_web::{HttpResponse, web};
+
+use apex_core::domain::{OrgSettings, SettingsScope, UserSettings};
+
+use super::orgs::require_membership;
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::middleware::patch::PatchBody;
+use crate::state::AppState;
+
+/// GET /api/settings/me
+pub async fn get_mine(identity: Identity, state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let settings: UserSettings = state
+        .settings
+        .get(SettingsScope::User(identity.user_id))
+        .await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// PATCH /api/settings/me - Merge patch (`null` resets a field) or JSON patch
+pub async fn update_mine(
+    identity: Identity,
+    state: web::Data<AppState>,
+    body: PatchBody,
+) -> AppResult<HttpResponse> {
+    let settings: UserSettings = state
+        .settings
+        .update(SettingsScope::User(identity.user_id), &body.0)
+        .await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// GET /api/orgs/{id}/settings - Any member may read
+pub async fn get_org(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+) -> AppResult<HttpResponse> {
+    let org_id = path.into_inner();
+    require_membership(&state, org_id, identity.user_id).await?;
+
+    let settings: OrgSettings = state
+        .settings
+        .get(SettingsScope::Organization(org_id))
+        .await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// PATCH /api/orgs/{id}/settings - Owner/admin only
+pub async fn update_org(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<uuid::Uuid>,
+    body: PatchBody,
+) -> AppResult<HttpResponse> {
+    let org_id = path.into_inner();
+    let membership = require_membership(&state, org_id, identity.user_id).await?;
+    if !membership.role.can_manage_members() {
+        return Err(AppError::Forbidden);
+    }
+
+    let settings: OrgSettings = state
+        .settings
+        .update(SettingsScope::Organization(org_id), &body.0)
+        .await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
@@ -0,0 +1,32 @@
+//! `robots.txt` and well-known document handlers.
+
+use actix_web::{HttpResponse, http::header, web};
+
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// Crawlers and clients fetch these often; they change only on restart.
+const CACHE_CONTROL: &str = "public, max-age=3600";
+
+/// GET /robots.txt - Crawler rules
+pub async fn robots(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header((header::CACHE_CONTROL, CACHE_CONTROL))
+        .body(state.well_known.robots())
+}
+
+/// GET /.well-known/{name} - A registered well-known document
+pub async fn well_known(
+    state: web::Data<AppState>,
+    name: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let document = state
+        .well_known
+        .get(&name)
+        .ok_or_else(|| AppError::NotFound(format!("No well-known document {}", name)))?;
+    Ok(HttpResponse::Ok()
+        .content_type(document.content_type)
+        .insert_header((header::CACHE_CONTROL, CACHE_CONTROL))
+        .body(document.body))
+}
@@ -0,0 +1,200 @@
+//! Offline sync handlers: pull changes since a cursor, push queued mutations.
+//!
+//! Clients keep a local copy of their posts, pull changes with the cursor of
+//! their last pull and push edits made offline together with the version they
+//! were based on. A post changed on the server since then comes back as a
+//! conflict with the server copy, for the client to merge and push again.
+
+use actix_web::{HttpResponse, web};
+use serde::Deserialize;
+
+use apex_core::DomainError;
+use apex_core::domain::{
+    Post, PostMutation, SettingsScope, SyncCursor, SyncOutcome, UserSettings, parse_client_id,
+};
+use apex_core::error::RepoError;
+use apex_shared::dto::{
+    SyncMutationRequest, SyncMutationResult, SyncPostResponse, SyncProfileResponse,
+    SyncPullResponse, SyncPushRequest, SyncPushResponse,
+};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 500;
+const MAX_MUTATIONS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct PullQuery {
+    /// Cursor from the previous pull; omit for a full sync.
+    pub cursor: Option<String>,
+    pub limit: Option<u64>,
+}
+
+/// GET /api/sync/pull - Posts changed since the cursor, plus the profile
+pub async fn pull(
+    identity: Identity,
+    state: web::Data<AppState>,
+    query: web::Query<PullQuery>,
+) -> AppResult<HttpResponse> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let after = query
+        .cursor
+        .as_deref()
+        .map(SyncCursor::decode)
+        .transpose()?;
+
+    // One extra row tells whether there is another page
+    let mut posts = state
+        .posts
+        .load()
+        .list_changes(identity.user_id, after, limit + 1)
+        .await?;
+    let has_more = posts.len() as u64 > limit;
+    posts.truncate(limit as usize);
+
+    let cursor = posts.last().map(SyncCursor::after).or(after);
+    let settings: UserSettings = state
+        .settings
+        .get(SettingsScope::User(identity.user_id))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(SyncPullResponse {
+        posts: posts.iter().map(to_response).collect(),
+        profile: SyncProfileResponse {
+            id: identity.user_id.to_string(),
+            email: identity.email,
+            settings: serde_json::to_value(settings)
+                .map_err(|e| AppError::Internal(e.to_string()))?,
+        },
+        cursor: cursor.map(|c| c.encode()),
+        has_more,
+    }))
+}
+
+/// POST /api/sync/push - Apply mutations in order, reporting each outcome
+pub async fn push(
+    identity: Identity,
+    state: web::Data<AppState>,
+    body: web::Json<SyncPushRequest>,
+) -> AppResult<HttpResponse> {
+    let mutations = body.into_inner().mutations;
+    if mutations.len() > MAX_MUTATIONS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} mutations per push",
+            MAX_MUTATIONS
+        )));
+    }
+
+    let mut results = Vec::with_capacity(mutations.len());
+    for mutation in mutations {
+        let result = match to_mutation(&mutation) {
+            Ok(mutation) => apply(&state, &identity, mutation).await?,
+            Err(e) => rejected(mutation_id(&mutation), e),
+        };
+        results.push(result);
+    }
+
+    Ok(HttpResponse::Ok().json(SyncPushResponse { results }))
+}
+
+async fn apply(
+    state: &AppState,
+    identity: &Identity,
+    mutation: PostMutation,
+) -> AppResult<SyncMutationResult> {
+    let id = mutation.id();
+    let current = state.posts.load().find_by_id(id).await?;
+    let expected_version = current.as_ref().map(|post| post.version);
+
+    let result = |status, post| result(id.to_string(), status, post);
+    let outcome = match mutation.resolve(identity.user_id, current) {
+        Ok(outcome) => outcome,
+        Err(e) => return Ok(rejected(id.to_string(), e)),
+    };
+
+    Ok(match outcome {
+        SyncOutcome::Apply(post) => {
+            match state
+                .posts
+                .load()
+                .save_if_version(post.clone(), expected_version)
+                .await
+            {
+                Ok(()) => result("applied", Some(&post)),
+                // Another write landed between reading and saving
+                Err(RepoError::Conflict(_)) => {
+                    let current = state.posts.load().find_by_id(id).await?;
+                    result("conflict", current.as_ref())
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        SyncOutcome::Unchanged(post) => result("unchanged", post.as_ref()),
+        SyncOutcome::Conflict(post) => result("conflict", post.as_ref()),
+    })
+}
+
+fn mutation_id(mutation: &SyncMutationRequest) -> String {
+    match mutation {
+        SyncMutationRequest::Upsert { id, .. } | SyncMutationRequest::Delete { id, .. } => {
+            id.clone()
+        }
+    }
+}
+
+fn to_mutation(mutation: &SyncMutationRequest) -> Result<PostMutation, DomainError> {
+    let id = parse_client_id(&mutation_id(mutation))?;
+    Ok(match mutation.clone() {
+        SyncMutationRequest::Upsert {
+            base_version,
+            title,
+            content,
+            ..
+        } => PostMutation::Upsert {
+            id,
+            base_version,
+            title,
+            content,
+        },
+        SyncMutationRequest::Delete { base_version, .. } => {
+            PostMutation::Delete { id, base_version }
+        }
+    })
+}
+
+fn result(id: String, status: &str, post: Option<&Post>) -> SyncMutationResult {
+    SyncMutationResult {
+        id,
+        status: status.to_string(),
+        post: post.map(to_response),
+        error: None,
+    }
+}
+
+fn rejected(id: String, error: DomainError) -> SyncMutationResult {
+    let error = match error {
+        DomainError::Unauthorized => "Not your post".to_string(),
+        DomainError::Validation(msg) => msg,
+        other => other.to_string(),
+    };
+    SyncMutationResult {
+        error: Some(error),
+        ..result(id, "rejected", None)
+    }
+}
+
+fn to_response(post: &Post) -> SyncPostResponse {
+    SyncPostResponse {
+        id: post.id.to_string(),
+        title: post.title.clone(),
+        content: post.content.clone(),
+        version: post.version,
+        deleted: post.is_deleted(),
+        published_at: post.published_at.map(|at| at.to_rfc3339()),
+        created_at: post.created_at.to_rfc3339(),
+        updated_at: post.updated_at.to_rfc3339(),
+    }
+}
@@ -0,0 +1,48 @@
+//! Post tag handlers.
+
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use apex_core::domain::Tag;
+use apex_shared::dto::{PostTagsResponse, SetTagsRequest};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppResult;
+use crate::state::AppState;
+
+/// GET /api/posts/{id}/tags - The tags on a post the caller can read
+pub async fn list(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let post_id = path.into_inner();
+    let org_id = identity.org.as_ref().map(|org| org.id);
+    let tags = state
+        .tag_service()
+        .tags_of(identity.user_id, org_id, post_id)
+        .await?;
+    Ok(HttpResponse::Ok().json(to_response(post_id, tags)))
+}
+
+/// PUT /api/posts/{id}/tags - Replace the tags on one of the caller's posts
+pub async fn set(
+    identity: Identity,
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    body: web::Json<SetTagsRequest>,
+) -> AppResult<HttpResponse> {
+    let post_id = path.into_inner();
+    let tags = state
+        .tag_service()
+        .set_tags(identity.user_id, post_id, &body.tags)
+        .await?;
+    Ok(HttpResponse::Ok().json(to_response(post_id, tags)))
+}
+
+fn to_response(post_id: Uuid, tags: Vec<Tag>) -> PostTagsResponse {
+    PostTagsResponse {
+        post_id: post_id.to_string(),
+        tags: tags.into_iter().map(|tag| tag.name).collect(),
+    }
+}
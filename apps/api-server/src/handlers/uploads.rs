@@ -0,0 +1,205 @@
+//! Multipart uploads, streamed into file storage as they arrive.
+
+use std::sync::Arc;
+
+use actix_multipart::Multipart;
+use actix_web::{HttpRequest, HttpResponse, http::header, web};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use apex_core::ports::{ByteStream, Job, JobQueue, StorageError};
+use apex_infra::{InMemoryJobQueue, LocalStorage};
+
+use super::attachments;
+use crate::env;
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// Multipart field holding the file.
+const FILE_FIELD: &str = "file";
+
+/// Job queued for each upload, the hook for a virus scanner.
+const SCAN_JOB: &str = "scan_attachment";
+
+/// What uploads may be, from `UPLOAD_MAX_BYTES` and `UPLOAD_ALLOWED_TYPES`,
+/// and whether each is queued for scanning (`UPLOAD_SCAN_ENABLED`).
+#[derive(Debug, Clone)]
+pub struct UploadPolicy {
+    pub max_bytes: u64,
+    /// Media types such as `application/pdf`, or `image/*` for any image.
+    pub allowed_types: Vec<String>,
+    pub scan: bool,
+}
+
+impl UploadPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            max_bytes: env::UPLOAD_MAX_BYTES.get(),
+            allowed_types: env::UPLOAD_ALLOWED_TYPES
+                .list()
+                .into_iter()
+                .map(|pattern| pattern.to_ascii_lowercase())
+                .collect(),
+            scan: env::UPLOAD_SCAN_ENABLED.flag(),
+        }
+    }
+
+    fn allows(&self, content_type: &str) -> bool {
+        let (kind, _) = content_type.split_once('/').unwrap_or((content_type, ""));
+        self.allowed_types
+            .iter()
+            .any(|pattern| match pattern.as_str() {
+                "*" | "*/*" => true,
+                pattern => match pattern.strip_suffix("/*") {
+                    Some(prefix) => prefix == kind,
+                    None => pattern == content_type,
+                },
+            })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadQuery {
+    /// One of the caller's posts to attach the file to.
+    pub post_id: Option<Uuid>,
+}
+
+/// Chunks handed over from the request body, which can't leave its thread.
+struct Chunks(mpsc::Receiver<Result<Vec<u8>, StorageError>>);
+
+#[async_trait]
+impl ByteStream for Chunks {
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, StorageError> {
+        self.0.recv().await.transpose()
+    }
+}
+
+/// POST /api/uploads - Upload the `file` field of a multipart body, on its
+/// own or onto one of the caller's posts (`?post_id=`). The bytes go to
+/// storage as they arrive; files over `UPLOAD_MAX_BYTES` are refused with
+/// 413, media types outside `UPLOAD_ALLOWED_TYPES` with 415. Each upload
+/// counts against the storage quota and is queued for scanning.
+pub async fn create(
+    identity: Identity,
+    state: web::Data<AppState>,
+    storage: web::Data<LocalStorage>,
+    policy: web::Data<UploadPolicy>,
+    queue: web::Data<Arc<InMemoryJobQueue>>,
+    req: HttpRequest,
+    mut payload: Multipart,
+) -> AppResult<HttpResponse> {
+    let query = web::Query::<UploadQuery>::from_query(req.query_string())
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let account_id = identity.account_id();
+    let org_id = identity.org.as_ref().map(|org| org.id);
+
+    // Turn away bodies the quota has no room for before reading them
+    let declared = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or_default()
+        .min(policy.max_bytes);
+    state.storage.check(account_id, declared as i64).await?;
+
+    let mut field = loop {
+        match payload.try_next().await {
+            Ok(Some(field)) if field.name() == Some(FILE_FIELD) => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => {
+                return Err(AppError::BadRequest(format!(
+                    "Missing multipart field `{}`",
+                    FILE_FIELD
+                )));
+            }
+            Err(e) => return Err(AppError::BadRequest(e.to_string())),
+        }
+    };
+    let content_type = field
+        .content_type()
+        .map(|mime| mime.essence_str().to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    if !policy.allows(&content_type) {
+        return Err(AppError::UnsupportedMediaType(format!(
+            "Files of type {} can't be uploaded",
+            content_type
+        )));
+    }
+    let filename = field
+        .content_disposition()
+        .and_then(|disposition| disposition.get_filename())
+        .unwrap_or("upload")
+        .to_string();
+
+    let (sender, receiver) = mpsc::channel(4);
+    let max_bytes = policy.max_bytes;
+    let pump = async move {
+        let mut size = 0u64;
+        loop {
+            let chunk = match field.try_next().await {
+                Ok(Some(bytes)) => {
+                    size += bytes.len() as u64;
+                    if size > max_bytes {
+                        Err(StorageError::TooLarge(max_bytes))
+                    } else {
+                        Ok(bytes.to_vec())
+                    }
+                }
+                Ok(None) => return,
+                Err(e) => Err(StorageError::Source(e.to_string())),
+            };
+            let failed = chunk.is_err();
+            // Stop once storage gave up on the upload or was told to
+            if sender.send(chunk).await.is_err() || failed {
+                return;
+            }
+        }
+    };
+
+    let service = attachments::service(&state, storage);
+    let mut chunks = Chunks(receiver);
+    let upload = service.upload_stream(
+        identity.user_id,
+        org_id,
+        query.post_id,
+        &filename,
+        &content_type,
+        &mut chunks,
+    );
+    let ((), uploaded) = futures::join!(pump, upload);
+    let attachment = uploaded?;
+
+    // The declared length may have understated the file
+    if let Err(e) = state.storage.check(account_id, attachment.size_bytes).await {
+        let _ = service.delete(identity.user_id, attachment.id).await;
+        return Err(e.into());
+    }
+    state
+        .storage
+        .record(account_id, attachment.size_bytes)
+        .await?;
+
+    if policy.scan {
+        let job = Job::new(
+            SCAN_JOB,
+            serde_json::json!({
+                "attachment_id": attachment.id,
+                "key": attachment.key,
+                "content_type": attachment.content_type,
+                "size_bytes": attachment.size_bytes,
+            }),
+        )
+        .for_account(account_id);
+        // The file is stored either way; a scan that never runs is logged
+        if let Err(e) = queue.enqueue(job).await {
+            tracing::error!(attachment_id = %attachment.id, "Failed to queue upload scan: {}", e);
+        }
+    }
+
+    Ok(HttpResponse::Created().json(attachments::to_response(&service, attachment)?))
+}
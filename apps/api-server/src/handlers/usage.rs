@@ -0,0 +1,104 @@
+//! Usage reporting handlers.
+
+use actix_web::{HttpResponse, web};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use apex_core::domain::{UsageTotal, billing_period};
+use apex_shared::dto::{QuotaUsageResponse, UsageMetricResponse, UsageResponse};
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::{AppError, AppResult};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// Billing month as YYYY-MM; defaults to the current month.
+    pub period: Option<String>,
+}
+
+/// GET /api/usage - Usage for the caller's account
+///
+/// Org-scoped tokens report the organization's usage, which requires an
+/// owner or admin role. Also lists the consumption of the account's API
+/// quotas in their current windows, whatever the period queried.
+pub async fn get_usage(
+    identity: Identity,
+    state: web::Data<AppState>,
+    query: web::Query<UsageQuery>,
+) -> AppResult<HttpResponse> {
+    let (period_start, totals) = load_usage(&identity, &state, &query).await?;
+    let quotas = state.api_quotas.usage(identity.account_id()).await?;
+
+    Ok(HttpResponse::Ok().json(UsageResponse {
+        account_id: identity.account_id().to_string(),
+        period: period_start.format("%Y-%m").to_string(),
+        metrics: totals
+            .into_iter()
+            .map(|total| UsageMetricResponse {
+                metric: total.metric.to_string(),
+                quantity: total.quantity,
+            })
+            .collect(),
+        quotas: quotas
+            .into_iter()
+            .map(|usage| QuotaUsageResponse {
+                period: usage.period.to_string(),
+                limit: usage.limit,
+                used: usage.used,
+                remaining: usage.remaining(),
+                resets_at: usage.resets_at.to_rfc3339(),
+            })
+            .collect(),
+    }))
+}
+
+/// GET /api/usage/export - Usage as CSV (requires the exports entitlement)
+pub async fn export_usage(
+    identity: Identity,
+    state: web::Data<AppState>,
+    query: web::Query<UsageQuery>,
+) -> AppResult<HttpResponse> {
+    let (period_start, totals) = load_usage(&identity, &state, &query).await?;
+    let period = period_start.format("%Y-%m");
+
+    let mut csv = String::from("account_id,period,metric,quantity\n");
+    for total in totals {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            total.account_id, period, total.metric, total.quantity
+        ));
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"usage-{}.csv\"", period),
+        ))
+        .body(csv))
+}
+
+async fn load_usage(
+    identity: &Identity,
+    state: &AppState,
+    query: &UsageQuery,
+) -> AppResult<(NaiveDate, Vec<UsageTotal>)> {
+    if let Some(org) = &identity.org
+        && !org.role.can_manage_members()
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    let period_start = match &query.period {
+        Some(period) => NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d")
+            .map_err(|_| AppError::BadRequest("period must be formatted as YYYY-MM".to_string()))?,
+        None => billing_period(chrono::Utc::now()),
+    };
+
+    let totals = state
+        .usage
+        .totals(identity.account_id(), period_start)
+        .await?;
+    Ok((period_start, totals))
+}
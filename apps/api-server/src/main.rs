@@ -5,20 +5,27 @@
 //! ## Feature Flags
 //!
 //! - `full` (default) - All features enabled
-//! - `minimal` - Bare HTTP server only
+//! - `minimal` - Health and feature endpoints, nothing persisted
 //! - `postgres` - PostgreSQL database support
 //! - `auth` - JWT authentication
 //! - `rate-limit` - Request rate limiting
 //! - `scheduler` - Cron job scheduling
 //! - `websocket` - WebSocket support
+//! - `storage` - File storage and scheduled reports
+//! - `encryption` - Job payloads encrypted at rest
+//! - `graphql` - GraphQL endpoint over users and posts
 //! - `otel` - OpenTelemetry tracing
+//! - `taskdump` - Task dumps (needs `--cfg tokio_unstable`)
+//! - `jemalloc` - jemalloc allocator with stats and heap profiles
 
+use actix_web::middleware::Condition;
 use actix_web::{App, HttpServer, web};
 use std::sync::Arc;
 use tokio::signal;
 use tracing_actix_web::TracingLogger;
 
 mod config;
+mod env;
 mod handlers;
 mod middleware;
 mod observability;
@@ -28,9 +35,15 @@ mod telemetry;
 #[cfg(feature = "scheduler")]
 mod background;
 
+#[cfg(feature = "auth")]
+mod routes;
+
 #[cfg(feature = "websocket")]
 mod websocket;
 
+#[cfg(feature = "graphql")]
+mod graphql;
+
 use config::AppConfig;
 use observability::RequestIdMiddleware;
 use state::AppState;
@@ -39,17 +52,36 @@ use telemetry::TelemetryConfig;
 #[cfg(feature = "auth")]
 use apex_core::ports::{PasswordService, TokenService};
 
-#[cfg(feature = "rate-limit")]
-use apex_core::ports::RateLimiter;
+use apex_core::ports::{DependencyProbe, JobQueue, WebhookSender};
+
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     // Load .env file if present
     dotenvy::dotenv().ok();
 
-    // Initialize telemetry (tracing, alerts)
+    // Document every supported variable instead of starting
+    if std::env::args().any(|arg| arg == "--env-reference") {
+        print!("{}", env::catalog().reference());
+        return Ok(());
+    }
+
+    // Initialize telemetry (tracing, alerts) first, so configuration
+    // problems are logged
     let telemetry_config = TelemetryConfig::from_env();
-    telemetry::init_telemetry(&telemetry_config);
+    let alert_dispatcher = telemetry::init_telemetry(&telemetry_config);
+
+    // Refuse to start on malformed or missing variables rather than run on
+    // defaults nobody asked for
+    if let Err(errors) = env::catalog().validate() {
+        for error in &errors {
+            tracing::error!(%error, "Invalid environment variable");
+        }
+        std::process::exit(1);
+    }
 
     // Load configuration
     let config = AppConfig::from_env();
@@ -57,12 +89,52 @@ async fn main() -> std::io::Result<()> {
     tracing::info!(
         host = %config.host,
         port = %config.port,
+        sandbox = config.sandbox,
         "Starting Apex API Server"
     );
 
+    if config.sandbox {
+        tracing::warn!("Sandbox mode enabled: outbound integrations are recorded, not delivered");
+    }
+
+    // Seal sensitive columns (COLUMN_ENCRYPTION_KEY) before the first row
+    // is read or written
+    #[cfg(feature = "postgres")]
+    install_column_cipher().await;
+
     // Build application state
     let state = AppState::new(config.database.as_ref()).await;
 
+    // Outbound webhooks - recorded instead of delivered in sandbox mode,
+    // audited either way so failed deliveries can be inspected and replayed
+    let webhooks = Arc::new(apex_infra::AuditedWebhookSender::new(
+        build_webhook_sender(config.sandbox),
+        state.deliveries.load(),
+    ));
+
+    // Roll usage counters up into storage periodically
+    let usage_meter = state.usage.clone();
+    let usage_flusher = usage_meter.start_flusher(config.usage_flush_interval);
+
+    let alert_sender = telemetry::alert_sender(&telemetry_config, webhooks.clone());
+    if let Some(dispatcher) = alert_dispatcher {
+        dispatcher.start(alert_sender.clone());
+    }
+
+    // Probe the database and Redis, reconnecting and flagging them when they
+    // stop answering, and alert when they degrade and recover
+    let probes = dependency_probes(&state);
+    let watchdog = Arc::new(build_watchdog(&probes));
+    watchdog.start();
+    observability::alert_on_dependency_events(&watchdog, alert_sender.clone());
+    // Cache in Redis while it answers, in memory while it does not
+    state.fail_over_cache(&watchdog);
+
+    // Request counts and latencies, evaluated by the alert rules
+    let request_metrics = Arc::new(observability::RequestMetrics::new());
+    // Labelled series for Prometheus (METRICS_ENABLED, METRICS_MAX_SERIES)
+    let request_series = Arc::new(observability::RequestSeries::from_env());
+
     // Create services based on features
     #[cfg(feature = "auth")]
     let token_service: Arc<dyn TokenService> = Arc::new(apex_infra::JwtTokenService::from_env());
@@ -71,30 +143,180 @@ async fn main() -> std::io::Result<()> {
     let password_service: Arc<dyn PasswordService> =
         Arc::new(apex_infra::Argon2PasswordService::new());
 
+    // Write the fixture users and posts before serving
+    #[cfg(feature = "auth")]
+    if std::env::args().any(|arg| arg == "--seed") {
+        let password_hash = password_service
+            .hash(apex_infra::seed::SEED_PASSWORD)
+            .expect("Failed to hash the seed password");
+        seed_fixtures(&state, password_hash).await;
+    }
+
     #[cfg(feature = "rate-limit")]
-    let rate_limiter: Arc<dyn RateLimiter> = Arc::new(apex_infra::InMemoryRateLimiter::from_env());
+    let rate_limit_policy = Arc::new(apex_infra::RateLimitPolicy::from_env());
+
+    // Stripe webhook signature verification, when a secret is configured
+    #[cfg(all(feature = "auth", feature = "billing"))]
+    let stripe_verifier = apex_infra::StripeWebhookVerifier::from_env().map(Arc::new);
+
+    // Job queue (always available - in-memory fallback), each job run in its own span
+    let job_queue = apex_infra::InMemoryJobQueue::from_env()
+        .with_middleware(apex_infra::jobs::TracingJobMiddleware);
+
+    // Seal job payloads at rest when JOB_ENCRYPTION_KEY is set
+    #[cfg(feature = "encryption")]
+    let job_queue = {
+        use apex_infra::jobs::PayloadEncryption;
+        match PayloadEncryption::from_secrets(&apex_infra::EnvSecrets)
+            .await
+            .expect("Failed to load job encryption keys")
+        {
+            Some(encryption) => {
+                tracing::info!("Job payloads are encrypted at rest");
+                job_queue.with_middleware(encryption)
+            }
+            None => job_queue,
+        }
+    };
+
+    // Keep erasure jobs away from users under legal hold (LEGAL_HOLD_JOB_TYPES)
+    let job_queue = job_queue.with_middleware(apex_infra::jobs::LegalHoldGuard::from_env(
+        state.legal_holds.load(),
+    ));
 
-    // Job queue (always available - in-memory fallback)
-    let job_queue = Arc::new(apex_infra::InMemoryJobQueue::from_env());
+    // Pace job types that call rate-limited providers (JOB_TYPE_RATE_LIMITS)
+    #[cfg(feature = "rate-limit")]
+    let job_queue = job_queue.with_middleware(apex_infra::jobs::ThrottleJobMiddleware::from_env());
+
+    let job_queue = Arc::new(job_queue);
+
+    // Readiness probes ping the watched dependencies and the job queue
+    let readiness = Arc::new(build_readiness(probes, &job_queue));
+
+    // Summary of this instance, logged once bound and served to admins
+    let runtime = web::Data::new(observability::RuntimeReport::new(
+        &config,
+        state.db.clone(),
+        job_queue.config().workers,
+    ));
+
+    // File storage, served through signed download links
+    #[cfg(feature = "storage")]
+    let file_storage = Arc::new(apex_infra::LocalStorage::from_env());
+
+    // Scheduled reports over the secondary databases (REPORTS_FILE)
+    #[cfg(all(feature = "postgres", feature = "auth", feature = "storage"))]
+    let reports = {
+        let definitions = apex_infra::reports::reports_from_env();
+        match &state.db {
+            Some(db) if !definitions.is_empty() => {
+                Some(Arc::new(apex_infra::ReportGenerator::new(
+                    definitions,
+                    db.clone(),
+                    file_storage.clone(),
+                    state.announcements.clone(),
+                    apex_infra::reports::ReportConfig::from_env(),
+                )))
+            }
+            _ => None,
+        }
+    };
 
     // Start job workers
     let jq = job_queue.clone();
+    let usage = usage_meter.clone();
+    let subscriptions = state.subscriptions.clone();
+    #[cfg(feature = "auth")]
+    let post_views = state.post_views.clone();
+    #[cfg(all(feature = "postgres", feature = "auth", feature = "storage"))]
+    let report_generator = reports.clone();
     tokio::spawn(async move {
+        use apex_core::domain::UsageMetric;
         use apex_core::ports::{JobQueue, JobResult};
 
         if let Err(e) = jq
-            .start_worker(|job| {
+            .start_worker(move |job| {
+                if let Some(account_id) = job.account_id {
+                    usage.record(account_id, UsageMetric::JobsExecuted, 1);
+                }
+                let subscriptions = subscriptions.clone();
+                #[cfg(feature = "auth")]
+                let post_views = post_views.clone();
+                #[cfg(all(feature = "postgres", feature = "auth", feature = "storage"))]
+                let report_generator = report_generator.clone();
                 Box::pin(async move {
                     tracing::info!(job_id = %job.id, job_type = %job.job_type, "Processing job");
                     match job.job_type.as_str() {
                         "email" => {
-                            tracing::info!("Sending email: {:?}", job.payload);
+                            tracing::info!(
+                                payload = %apex_core::pii::redact(&job.payload),
+                                "Sending email"
+                            );
+                            JobResult::Success
+                        }
+                        "welcome_email" => match serde_json::from_value(job.payload.clone()) {
+                            Ok(apex_core::domain::DomainEvent::UserRegistered {
+                                user_id,
+                                email,
+                            }) => {
+                                tracing::info!(
+                                    %user_id,
+                                    email = %apex_core::pii::Sensitive(&email),
+                                    "Sending welcome email"
+                                );
+                                JobResult::Success
+                            }
+                            _ => JobResult::Failed("Not a user.registered event".into()),
+                        },
+                        "scan_attachment" => {
+                            // Hook for a virus scanner; uploads are accepted as they are
+                            tracing::info!(
+                                key = %job.payload["key"].as_str().unwrap_or_default(),
+                                "Scanning upload"
+                            );
                             JobResult::Success
                         }
                         "cleanup" => {
                             tracing::info!("Running cleanup");
                             JobResult::Success
                         }
+                        "expire_trials" => {
+                            match subscriptions.expire_trials(chrono::Utc::now()).await {
+                                Ok(expired) => {
+                                    if expired > 0 {
+                                        tracing::info!(expired, "Expired ended trials");
+                                    }
+                                    JobResult::SuccessWith(
+                                        serde_json::json!({ "expired": expired }),
+                                    )
+                                }
+                                Err(e) => JobResult::Retry(e.to_string()),
+                            }
+                        }
+                        #[cfg(feature = "auth")]
+                        "flush_post_views" => match post_views.flush().await {
+                            Ok(posts) => {
+                                JobResult::SuccessWith(serde_json::json!({ "posts": posts }))
+                            }
+                            Err(e) => JobResult::Retry(e.to_string()),
+                        },
+                        #[cfg(all(feature = "postgres", feature = "auth", feature = "storage"))]
+                        "generate_report" => {
+                            let name = job.payload["report"].as_str().unwrap_or_default();
+                            match &report_generator {
+                                Some(generator) => match generator.run(name).await {
+                                    Ok(run) => JobResult::SuccessWith(serde_json::json!({
+                                        "key": run.key,
+                                        "rows": run.rows,
+                                    })),
+                                    Err(apex_infra::reports::ReportError::UnknownReport(_)) => {
+                                        JobResult::Failed(format!("Unknown report: {}", name))
+                                    }
+                                    Err(e) => JobResult::Retry(e.to_string()),
+                                },
+                                None => JobResult::Failed("Reports are not configured".into()),
+                            }
+                        }
                         _ => {
                             tracing::warn!("Unknown job type: {}", job.job_type);
                             JobResult::Failed(format!("Unknown job type: {}", job.job_type))
@@ -108,9 +330,69 @@ async fn main() -> std::io::Result<()> {
         }
     });
 
+    // Recurring jobs go through the queue, so they get retries, stats and
+    // dead-lettering like any other job
+    {
+        use apex_core::ports::{Job, JobQueue};
+
+        // Expire ended trials (hourly)
+        if let Err(e) = job_queue
+            .clone()
+            .enqueue_recurring(
+                Job::new("expire_trials", serde_json::json!({})),
+                "0 0 * * * *",
+            )
+            .await
+        {
+            tracing::error!(error = %e, "Failed to register recurring job");
+        }
+
+        // Move counted post views to the database (every minute)
+        #[cfg(feature = "auth")]
+        if let Err(e) = job_queue
+            .clone()
+            .enqueue_recurring(
+                Job::new("flush_post_views", serde_json::json!({})),
+                "0 * * * * *",
+            )
+            .await
+        {
+            tracing::error!(error = %e, "Failed to register recurring job");
+        }
+
+        // Each report on its own schedule
+        #[cfg(all(feature = "postgres", feature = "auth", feature = "storage"))]
+        for report in reports.iter().flat_map(|generator| generator.reports()) {
+            if let Err(e) = job_queue
+                .clone()
+                .enqueue_recurring(
+                    Job::new(
+                        "generate_report",
+                        serde_json::json!({ "report": report.name }),
+                    ),
+                    &report.schedule,
+                )
+                .await
+            {
+                tracing::error!(report = %report.name, error = %e, "Failed to schedule report");
+            }
+        }
+    }
+
+    // Threshold alerts on error rate, latency and queue depth
+    if telemetry_config.alerts_enabled {
+        observability::AlertRulesEngine::new(
+            observability::AlertRulesConfig::from_env(),
+            request_metrics.clone(),
+            job_queue.clone(),
+            alert_sender.clone(),
+        )
+        .start();
+    }
+
     // Initialize scheduler if enabled
     #[cfg(feature = "scheduler")]
-    {
+    let mut scheduler = {
         use background::{Scheduler, SchedulerConfig};
 
         let scheduler_config = SchedulerConfig::from_env();
@@ -127,57 +409,236 @@ async fn main() -> std::io::Result<()> {
             .ok();
 
         scheduler.start().await.expect("Failed to start scheduler");
+        scheduler
+    };
+
+    // Read-only SQL console over the secondary databases, off unless enabled
+    #[cfg(feature = "postgres")]
+    let sql_console = {
+        let console_config = apex_infra::database::SqlConsoleConfig::from_env();
+        match &state.db {
+            Some(db) if console_config.enabled => Some(Arc::new(
+                apex_infra::database::SqlConsole::new(db.clone(), console_config),
+            )),
+            _ => None,
+        }
+    };
+
+    // In-process pub/sub, used to fan realtime events out to WebSocket clients.
+    // Messages are stamped with this instance as their producer.
+    let pubsub = Arc::new(apex_infra::TypedPubSub::new(
+        Arc::new(apex_infra::InMemoryPubSub::from_env()),
+        format!(
+            "{}-{}",
+            telemetry_config.service_name,
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        ),
+    ));
+
+    // Services announce their changes on the same pub/sub, and as jobs for
+    // work that must not be lost: registrations get a welcome email, feed
+    // caches are dropped when public posts change
+    #[cfg(feature = "auth")]
+    {
+        state.events.swap(Arc::new(
+            apex_infra::EventFanOut::new(pubsub.clone(), job_queue.clone())
+                .with_job("user.registered", "welcome_email"),
+        ));
+        match state.feeds.clone().follow_events(&pubsub).await {
+            Ok(subscription) => subscription.detach(),
+            Err(e) => tracing::error!(error = %e, "Failed to follow domain events for feeds"),
+        }
     }
 
+    // Recent notifications for long-polling clients, fed from the same pub/sub
+    #[cfg(feature = "auth")]
+    let notifications = {
+        let log = Arc::new(apex_infra::NotificationLog::from_env());
+        match log.clone().follow_announcements(&pubsub).await {
+            // Followed for the life of the server
+            Ok(subscription) => subscription.detach(),
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to follow announcements for long polling")
+            }
+        }
+        log
+    };
+
+    // Each user's events for their event streams, replayed on reconnection
+    #[cfg(feature = "auth")]
+    let user_events = Arc::new(apex_infra::UserEventHub::from_env(pubsub.clone()));
+
     // Initialize WebSocket layer if enabled
     #[cfg(feature = "websocket")]
+    let connection_metrics = Arc::new(websocket::ConnectionMetrics::new());
+    #[cfg(feature = "websocket")]
     let (_socket_layer, _io) = {
         use websocket::WsState;
-        let pubsub = Arc::new(apex_infra::InMemoryPubSub::default());
-        let ws_state = WsState { pubsub };
+        let ws_state = WsState {
+            pubsub: pubsub.clone(),
+            metrics: connection_metrics.clone(),
+            heartbeat: websocket::Heartbeat::from_env(),
+        };
         websocket::create_socketio_layer(ws_state)
     };
 
     // Start HTTP server with graceful shutdown
-    let server = HttpServer::new(move || {
-        #[cfg(feature = "rate-limit")]
-        let rate_limiter_clone = rate_limiter.clone();
+    let shutdown_queue = job_queue.clone();
+    let feature_flags = web::Data::new(config.feature_flags.clone());
 
-        #[cfg(feature = "auth")]
-        let token_service_clone = token_service.clone();
+    // Mirror sampled requests to a shadow deployment, off unless SHADOW_URL is set
+    let shadow_traffic = build_shadow_traffic();
 
-        #[cfg(feature = "auth")]
-        let password_service_clone = password_service.clone();
-
-        // Build app with all middleware upfront
-        #[cfg(all(feature = "rate-limit"))]
-        let app = App::new()
-            .wrap(TracingLogger::default())
-            .wrap(RequestIdMiddleware)
-            .wrap(middleware::rate_limit::RateLimitMiddleware::new(
-                rate_limiter_clone,
+    // Traffic split between rewritten endpoints and the handlers they replace
+    let canary_rollouts = Arc::new(middleware::canary::CanaryRollouts::from_env());
+    // Deprecated API versions, announced in response headers until their sunset
+    let api_versions = Arc::new(middleware::versioning::VersionPolicy::from_env());
+    let request_timeout = config.request_timeout;
+    #[cfg(feature = "auth")]
+    let event_streams = web::Data::new(handlers::EventStreamConfig::from_env());
+    #[cfg(all(feature = "auth", feature = "storage"))]
+    let upload_policy = web::Data::new(handlers::UploadPolicy::from_env());
+    let runtime_report = runtime.clone();
+    #[cfg(feature = "jemalloc")]
+    let memory_profiler = web::Data::new(observability::MemoryProfiler::from_env());
+    #[cfg(feature = "graphql")]
+    let graphql_schema = web::Data::new(graphql::schema());
+    let server = HttpServer::new(move || {
+        // Built twice per worker: the app serving clients, and the copy
+        // batched sub-requests run through, which leaves out what a batch
+        // is only counted for once (metering, quotas) or not at all (shadow
+        // traffic)
+        let build = |for_batch: bool| {
+            #[cfg(feature = "rate-limit")]
+            let rate_limit_policy_clone = rate_limit_policy.clone();
+
+            #[cfg(feature = "auth")]
+            let token_service_clone = token_service.clone();
+
+            #[cfg(feature = "auth")]
+            let password_service_clone = password_service.clone();
+
+            // Build app with all middleware upfront
+            #[cfg(feature = "rate-limit")]
+            let app = App::new()
+                .wrap(TracingLogger::default())
+                .wrap(Condition::new(
+                    !for_batch,
+                    middleware::shadow::ShadowTrafficMiddleware::new(shadow_traffic.clone()),
+                ))
+                .wrap(middleware::canary::CanaryRouting::new(
+                    canary_rollouts.clone(),
+                ))
+                .wrap(RequestIdMiddleware::new(request_timeout))
+                .wrap(observability::RequestMetricsMiddleware::new(
+                    request_metrics.clone(),
+                    request_series.clone(),
+                ))
+                .wrap(middleware::rate_limit::RateLimitMiddleware::new(
+                    rate_limit_policy_clone,
+                ));
+
+            #[cfg(not(feature = "rate-limit"))]
+            let app = App::new()
+                .wrap(TracingLogger::default())
+                .wrap(Condition::new(
+                    !for_batch,
+                    middleware::shadow::ShadowTrafficMiddleware::new(shadow_traffic.clone()),
+                ))
+                .wrap(middleware::canary::CanaryRouting::new(
+                    canary_rollouts.clone(),
+                ))
+                .wrap(RequestIdMiddleware::new(request_timeout))
+                .wrap(observability::RequestMetricsMiddleware::new(
+                    request_metrics.clone(),
+                    request_series.clone(),
+                ));
+
+            #[cfg(feature = "auth")]
+            let app = app
+                .wrap(Condition::new(
+                    !for_batch,
+                    middleware::metering::UsageMeteringMiddleware::new(state.usage.clone()),
+                ))
+                .wrap(Condition::new(!for_batch, middleware::quota::ApiQuotaCheck))
+                .wrap(middleware::consent::ConsentCheck::flag())
+                .wrap(middleware::tenant::TenantHost::from_env());
+
+            // Outermost, so the other middleware see the versioned path
+            let app = app.wrap(middleware::versioning::ApiVersioning::new(
+                api_versions.clone(),
             ));
 
-        #[cfg(not(feature = "rate-limit"))]
-        let app = App::new()
-            .wrap(TracingLogger::default())
-            .wrap(RequestIdMiddleware);
-
-        // Add data
-        let app = app
-            .app_data(web::Data::new(state.clone()))
-            .app_data(web::Data::new(job_queue.clone()));
-
+            // Add data
+            let app = app
+                .app_data(web::Data::new(state.clone()))
+                .app_data(web::Data::new(job_queue.clone()))
+                .app_data(web::Data::new(webhooks.clone()))
+                .app_data(web::Data::new(pubsub.clone()))
+                .app_data(feature_flags.clone())
+                .app_data(web::Data::new(canary_rollouts.clone()))
+                .app_data(web::Data::new(request_series.clone()))
+                .app_data(web::Data::from(watchdog.clone()))
+                .app_data(web::Data::from(readiness.clone()))
+                .app_data(runtime.clone());
+
+            #[cfg(feature = "jemalloc")]
+            let app = app.app_data(memory_profiler.clone());
+
+            #[cfg(feature = "websocket")]
+            let app = app.app_data(web::Data::new(connection_metrics.clone()));
+
+            #[cfg(feature = "rate-limit")]
+            let app = app.app_data(web::Data::from(rate_limit_policy.clone()));
+
+            #[cfg(feature = "auth")]
+            let app = app
+                .app_data(web::Data::new(token_service_clone))
+                .app_data(web::Data::new(password_service_clone))
+                .app_data(web::Data::new(notifications.clone()))
+                .app_data(web::Data::from(user_events.clone()))
+                .app_data(event_streams.clone());
+
+            #[cfg(all(feature = "auth", feature = "billing"))]
+            let app = match &stripe_verifier {
+                Some(verifier) => app.app_data(web::Data::from(verifier.clone())),
+                None => app,
+            };
+
+            let app = match &shadow_traffic {
+                Some(shadow) => app.app_data(web::Data::from(shadow.clone())),
+                None => app,
+            };
+
+            #[cfg(feature = "storage")]
+            let app = app.app_data(web::Data::from(file_storage.clone()));
+
+            #[cfg(all(feature = "auth", feature = "storage"))]
+            let app = app.app_data(upload_policy.clone());
+
+            #[cfg(feature = "graphql")]
+            let app = app.app_data(graphql_schema.clone());
+
+            #[cfg(feature = "postgres")]
+            let app = match &sql_console {
+                Some(console) => app.app_data(web::Data::from(console.clone())),
+                None => app,
+            };
+
+            // Configure routes
+            app.configure(handlers::configure_routes)
+        };
+
+        let app = build(false);
         #[cfg(feature = "auth")]
-        let app = app
-            .app_data(web::Data::new(token_service_clone))
-            .app_data(web::Data::new(password_service_clone));
-
-        // Configure routes
-        app.configure(handlers::configure_routes)
+        let app = app.app_data(handlers::BatchDispatcher::new(build(true)));
+        app
     })
-    .bind((config.host.as_str(), config.port))?
-    .run();
+    .bind((config.host.as_str(), config.port))?;
+
+    runtime_report.set_addresses(server.addrs());
+    runtime_report.log().await;
+    let server = server.run();
 
     // Graceful shutdown handling
     let server_handle = server.handle();
@@ -188,7 +649,171 @@ async fn main() -> std::io::Result<()> {
         server_handle.stop(true).await;
     });
 
-    server.await
+    server.await?;
+
+    // Stop firing cron jobs before the queue they may enqueue on
+    #[cfg(feature = "scheduler")]
+    if let Err(e) = scheduler.shutdown().await {
+        tracing::error!(error = %e, "Failed to stop the scheduler");
+    }
+
+    // Stop taking jobs and let running ones finish
+    {
+        use apex_core::ports::JobQueue;
+
+        if let Err(e) = shutdown_queue.shutdown(config.job_shutdown_timeout).await {
+            tracing::error!(error = %e, "Failed to shut down job queue cleanly");
+        }
+    }
+
+    // Persist whatever usage was counted since the last flush
+    usage_flusher.abort();
+    if let Err(e) = usage_meter.flush().await {
+        tracing::error!(error = %e, "Failed to flush usage counters on shutdown");
+    }
+
+    Ok(())
+}
+
+/// Install the column cipher from `COLUMN_ENCRYPTION_KEY`. Without a key,
+/// sensitive columns are written in plaintext, which production refuses.
+#[cfg(feature = "postgres")]
+async fn install_column_cipher() {
+    match apex_infra::ColumnCipher::from_secrets(&apex_infra::EnvSecrets).await {
+        Ok(Some(cipher)) => {
+            cipher
+                .install()
+                .expect("Column cipher installed more than once");
+            tracing::info!("Sensitive columns are encrypted at rest");
+        }
+        Ok(None) if apex_infra::env::is_production() => {
+            tracing::error!("COLUMN_ENCRYPTION_KEY is required in production");
+            std::process::exit(1);
+        }
+        Ok(None) => {
+            tracing::warn!(
+                "COLUMN_ENCRYPTION_KEY not set - sensitive columns are stored in plaintext"
+            );
+        }
+        Err(error) => {
+            tracing::error!(%error, "Failed to load column encryption keys");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Seed the fixtures for `RUST_ENV`, exiting on failure: a half-seeded
+/// dataset is worse than none.
+#[cfg(feature = "auth")]
+async fn seed_fixtures(state: &AppState, password_hash: String) {
+    let seeded = match apex_infra::SeedEnvironment::from_env() {
+        Ok(environment) => {
+            let mut ctx = apex_infra::SeedContext::new(
+                environment,
+                state.users.load(),
+                state.posts.load(),
+                password_hash,
+            );
+            apex_infra::SeedRunner::fixtures().run(&mut ctx).await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(error) = seeded {
+        tracing::error!(%error, "Seeding failed");
+        std::process::exit(1);
+    }
+}
+
+/// Build the outbound webhook sender, swapping in the recording fake in sandbox mode.
+fn build_webhook_sender(sandbox: bool) -> Arc<dyn WebhookSender> {
+    if sandbox {
+        return Arc::new(apex_infra::RecordingWebhookSender::default());
+    }
+
+    #[cfg(feature = "webhooks")]
+    {
+        Arc::new(apex_infra::HttpWebhookSender::from_env())
+    }
+
+    #[cfg(not(feature = "webhooks"))]
+    {
+        tracing::warn!("webhooks feature disabled - outbound webhooks will only be recorded");
+        Arc::new(apex_infra::RecordingWebhookSender::default())
+    }
+}
+
+/// Probes of the database, when there is one, and Redis, when `REDIS_URL`
+/// is set.
+#[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+fn dependency_probes(state: &AppState) -> Vec<Arc<dyn DependencyProbe>> {
+    let mut probes: Vec<Arc<dyn DependencyProbe>> = Vec::new();
+
+    #[cfg(feature = "postgres")]
+    if let Some(db) = &state.db {
+        for probe in apex_infra::health::DatabaseProbe::all(db) {
+            probes.push(Arc::new(probe));
+        }
+    }
+
+    if apex_infra::env::REDIS_URL.is_set() {
+        probes.push(Arc::new(apex_infra::health::RedisProbe::new(
+            apex_infra::cache::RedisConfig::from_env(),
+        )));
+    }
+
+    probes
+}
+
+/// Build the dependency watchdog over `probes`.
+fn build_watchdog(probes: &[Arc<dyn DependencyProbe>]) -> apex_infra::DependencyWatchdog {
+    probes.iter().cloned().fold(
+        apex_infra::DependencyWatchdog::new(apex_infra::WatchdogConfig::from_env()),
+        apex_infra::DependencyWatchdog::with_probe,
+    )
+}
+
+/// Build the readiness checks over the watchdog's `probes`, sharing their
+/// connections, and the job queue.
+fn build_readiness<Q: JobQueue + 'static>(
+    probes: Vec<Arc<dyn DependencyProbe>>,
+    job_queue: &Arc<Q>,
+) -> apex_infra::HealthCheckRegistry {
+    let timeout = apex_infra::WatchdogConfig::from_env().timeout;
+    probes
+        .into_iter()
+        .fold(
+            apex_infra::HealthCheckRegistry::new(timeout),
+            apex_infra::HealthCheckRegistry::with_check,
+        )
+        .with_check(Arc::new(apex_infra::health::JobQueueProbe::new(
+            job_queue.clone(),
+        )))
+}
+
+/// Build shadow traffic mirroring when a shadow URL is configured.
+fn build_shadow_traffic() -> Option<Arc<apex_infra::shadow::ShadowTraffic>> {
+    let shadow_config = apex_infra::shadow::ShadowConfig::from_env();
+    let url = shadow_config.url.clone()?;
+
+    #[cfg(feature = "webhooks")]
+    {
+        tracing::info!(
+            shadow_url = %url,
+            sample_rate = shadow_config.sample_rate,
+            "Shadow traffic mirroring enabled"
+        );
+        let mirror = apex_infra::shadow::HttpTrafficMirror::new(url, shadow_config.timeout);
+        Some(Arc::new(apex_infra::shadow::ShadowTraffic::new(
+            shadow_config,
+            Arc::new(mirror),
+        )))
+    }
+
+    #[cfg(not(feature = "webhooks"))]
+    {
+        tracing::warn!(shadow_url = %url, "webhooks feature disabled - shadow traffic is not mirrored");
+        None
+    }
 }
 
 /// Wait for shutdown signals (Ctrl+C or SIGTERM).
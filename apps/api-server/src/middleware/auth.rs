@@ -1,10 +1,10 @@
 //! Authentication middleware and extractors.
 
-use actix_web::{FromRequest, HttpRequest, dev::Payload, http::header};
+use actix_web::{FromRequest, HttpMessage, HttpRequest, dev::Payload, http::header};
 use std::future::{Ready, ready};
 use std::sync::Arc;
 
-use apex_core::ports::{AuthError, TokenClaims, TokenService};
+use apex_core::ports::{AuthError, OrgClaim, TokenClaims, TokenService};
 
 /// Authenticated user identity extractor.
 ///
@@ -19,13 +19,26 @@ pub struct Identity {
     pub user_id: uuid::Uuid,
     pub email: String,
     pub roles: Vec<String>,
+    /// Active organization the token was issued for.
+    pub org: Option<OrgClaim>,
 }
 
+/// Role granting access to the admin endpoints.
+pub const ADMIN_ROLE: &str = apex_core::domain::User::ADMIN_ROLE;
+
+/// Role for staff and QA accounts that are not full admins.
+pub const INTERNAL_ROLE: &str = "internal";
+
 impl Identity {
     /// Check if the user has a specific role.
     pub fn has_role(&self, role: &str) -> bool {
         self.roles.iter().any(|r| r == role)
     }
+
+    /// Billing account: the active organization, or the user themselves.
+    pub fn account_id(&self) -> uuid::Uuid {
+        self.org.as_ref().map(|org| org.id).unwrap_or(self.user_id)
+    }
 }
 
 impl From<TokenClaims> for Identity {
@@ -34,10 +47,23 @@ impl From<TokenClaims> for Identity {
             user_id: claims.user_id,
             email: claims.email,
             roles: claims.roles,
+            org: claims.org,
         }
     }
 }
 
+/// Claims of the request's Bearer token, if it is valid. For middleware
+/// that needs the caller without authenticating the request the way the
+/// [`Identity`] extractor does.
+pub(crate) fn bearer_claims(req: &HttpRequest) -> Option<TokenClaims> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .zip(req.app_data::<actix_web::web::Data<Arc<dyn TokenService>>>())
+        .and_then(|(token, tokens)| tokens.validate_token(token).ok())
+}
+
 /// Error type for authentication failures.
 #[derive(Debug)]
 pub struct AuthenticationError(pub AuthError);
@@ -63,8 +89,10 @@ impl actix_web::ResponseError for AuthenticationError {
         use apex_shared::ErrorResponse;
 
         let error = match &self.0 {
-            AuthError::TokenExpired => ErrorResponse::new(401, "Token Expired")
-                .with_detail("Your authentication token has expired. Please login again."),
+            AuthError::TokenExpired => {
+                ErrorResponse::new(401, apex_shared::client_error::TOKEN_EXPIRED_TITLE)
+                    .with_detail("Your authentication token has expired. Please login again.")
+            }
             AuthError::InvalidToken(msg) => {
                 ErrorResponse::new(401, "Invalid Token").with_detail(msg.clone())
             }
@@ -121,15 +149,41 @@ impl FromRequest for Identity {
 
         // Validate token
         match token_service.validate_token(token) {
-            Ok(claims) => ready(Ok(Identity::from(claims))),
+            Ok(claims) => {
+                let identity = Identity::from(claims);
+                // On a tenant's host, or naming a tenant in the header, tokens
+                // of other tenants are refused
+                let tenant = req
+                    .extensions()
+                    .get::<crate::middleware::tenant::Tenant>()
+                    .map(|tenant| tenant.organization_id);
+                if let (Some(tenant), Some(org)) = (tenant, &identity.org)
+                    && org.id != tenant
+                {
+                    return ready(Err(AuthenticationError(AuthError::InsufficientPermissions)));
+                }
+                // Make the caller visible to middleware (e.g. usage metering)
+                req.extensions_mut().insert(identity.clone());
+                // ...and to services, through the request context
+                apex_infra::context::update(|context| {
+                    context.user_id = Some(identity.user_id);
+                    // Only a token scoped to the organization proves membership:
+                    // tenant-scoped repositories narrow and stamp by this one,
+                    // not by the tenant the request names
+                    context.tenant_id = identity.org.as_ref().map(|org| org.id);
+                });
+                ready(Ok(identity))
+            }
             Err(e) => ready(Err(AuthenticationError(e))),
         }
     }
 }
 
 /// Optional identity extractor - doesn't fail if not authenticated.
+#[cfg(feature = "graphql")]
 pub struct OptionalIdentity(pub Option<Identity>);
 
+#[cfg(feature = "graphql")]
 impl FromRequest for OptionalIdentity {
     type Error = actix_web::Error;
     type Future = Ready<Result<Self, Self::Error>>;
@@ -141,3 +195,112 @@ impl FromRequest for OptionalIdentity {
         }
     }
 }
+
+/// Admin identity extractor - requires an authenticated user with the admin role.
+///
+/// Responds 401 when unauthenticated and 403 when the role is missing.
+pub struct Admin(pub Identity);
+
+impl FromRequest for Admin {
+    type Error = AuthenticationError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        match Identity::from_request(req, payload).into_inner() {
+            Ok(identity) if identity.has_role(ADMIN_ROLE) => ready(Ok(Admin(identity))),
+            Ok(_) => ready(Err(AuthenticationError(AuthError::InsufficientPermissions))),
+            Err(e) => ready(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, dev::Service, test, web};
+    use std::time::Duration;
+
+    use apex_core::domain::Post;
+    use apex_core::ports::{BaseRepository, NoopEventDispatcher, PostRepository};
+    use apex_core::services::PostService;
+    use apex_infra::auth::JwtTokenService;
+    use apex_infra::database::InMemoryPostRepository;
+    use apex_infra::tenancy::TenantScopedPostRepository;
+    use uuid::Uuid;
+
+    use crate::middleware::tenant::Tenant;
+    use crate::observability::RequestIdMiddleware;
+
+    async fn create(identity: Identity, posts: web::Data<PostService>) -> HttpResponse {
+        let org_id = identity.org.as_ref().map(|org| org.id);
+        let post = posts
+            .create(identity.user_id, org_id, "Mine".into(), "".into())
+            .await
+            .unwrap();
+        HttpResponse::Ok().json(post.organization_id)
+    }
+
+    async fn get(
+        identity: Identity,
+        posts: web::Data<PostService>,
+        path: web::Path<Uuid>,
+    ) -> HttpResponse {
+        let org_id = identity.org.as_ref().map(|org| org.id);
+        match posts.get(identity.user_id, org_id, path.into_inner()).await {
+            Ok(_) => HttpResponse::Ok().finish(),
+            Err(_) => HttpResponse::NotFound().finish(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_a_named_tenant_does_not_scope_an_unscoped_token() {
+        let inner = Arc::new(InMemoryPostRepository::new());
+        let victim = Uuid::new_v4();
+        let theirs = Post::new(Uuid::new_v4(), "Theirs".into(), "".into()).in_organization(victim);
+        inner.save(theirs.clone()).await.unwrap();
+        let posts = PostService::new(
+            Arc::new(TenantScopedPostRepository::new(inner.clone())),
+            Arc::new(NoopEventDispatcher),
+        );
+        let tokens: Arc<dyn TokenService> = Arc::new(JwtTokenService::from_env());
+        let token = tokens
+            .generate_token(Uuid::new_v4(), "user@example.com", vec![], None)
+            .unwrap();
+
+        // As TenantHost would for `X-Tenant-ID: <victim>`
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(tokens))
+                .app_data(web::Data::new(posts))
+                .route("/posts", web::post().to(create))
+                .route("/posts/{id}", web::get().to(get))
+                .wrap(RequestIdMiddleware::new(Duration::from_secs(5)))
+                .wrap_fn(move |req, srv| {
+                    req.extensions_mut().insert(Tenant {
+                        organization_id: victim,
+                    });
+                    srv.call(req)
+                }),
+        )
+        .await;
+        let bearer = (header::AUTHORIZATION, format!("Bearer {}", token));
+
+        let request = test::TestRequest::post()
+            .uri("/posts")
+            .insert_header(bearer.clone())
+            .to_request();
+        let org: Option<Uuid> = test::call_and_read_body_json(&app, request).await;
+        assert_eq!(org, None);
+        assert_eq!(
+            inner.find_by_organization_id(victim).await.unwrap(),
+            vec![theirs.clone()]
+        );
+
+        let request = test::TestRequest::get()
+            .uri(&format!("/posts/{}", theirs.id))
+            .insert_header(bearer)
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 404);
+    }
+}
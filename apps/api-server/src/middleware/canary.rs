@@ -0,0 +1,354 @@
+//! Canary routing for incremental rollouts of rewritten endpoints.
+//!
+//! The rewrite is registered next to the current handler, guarded by
+//! [`canary`], and the rollout gets a share of traffic from `CANARY_ROLLOUTS`:
+//! ```ignore
+//! web::resource("/plan")
+//!     .route(web::get().guard(canary("plan_v2")).to(plans::current_v2))
+//!     .route(web::get().to(plans::current))
+//! ```
+//! [`CanaryRouting`] assigns each request a variant of every rollout and
+//! records requests, server errors and latency per variant, so the rewrite
+//! can be compared against the handler it replaces before widening it.
+//!
+//! No endpoint is being rolled out, so the guard is only built for tests;
+//! drop its `cfg(test)` along with the first canary route.
+
+#[cfg(test)]
+use actix_web::guard::{Guard, GuardContext};
+use actix_web::{
+    Error, FromRequest, HttpMessage,
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+};
+use std::collections::HashMap;
+use std::future::{Future, Ready, ready};
+use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::env;
+use crate::middleware::feature_flags::Flags;
+
+/// Side of a rollout a request is served by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    Stable,
+    Canary,
+}
+
+#[cfg(feature = "auth")]
+impl Variant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Variant::Stable => "stable",
+            Variant::Canary => "canary",
+        }
+    }
+}
+
+/// A rewritten endpoint being rolled out.
+#[derive(Debug, Clone)]
+pub struct Rollout {
+    pub name: String,
+    /// Share of users sent to the canary, 0 to 100.
+    pub percent: u8,
+}
+
+impl Rollout {
+    /// Feature flag that sends a request to the canary regardless of the
+    /// percentage, e.g. with `X-Feature-Override: canary.plan_v2`.
+    pub fn flag(&self) -> String {
+        format!("canary.{}", self.name)
+    }
+
+    /// Whether `user_key` falls into the canary share. The same user always
+    /// lands on the same side, so their experience does not flip between
+    /// requests; each rollout buckets users independently.
+    fn includes(&self, user_key: &str) -> bool {
+        let mut hasher = std::hash::DefaultHasher::new();
+        (self.name.as_str(), user_key).hash(&mut hasher);
+        hasher.finish() % 100 < u64::from(self.percent)
+    }
+}
+
+/// Request counters of one variant of a rollout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VariantStats {
+    pub requests: u64,
+    /// Requests answered with a 5xx status.
+    pub errors: u64,
+    pub total_latency: Duration,
+}
+
+/// Configured rollouts and their per-variant metrics.
+#[derive(Default)]
+pub struct CanaryRollouts {
+    rollouts: Vec<Rollout>,
+    stats: Mutex<HashMap<(String, Variant), VariantStats>>,
+}
+
+impl CanaryRollouts {
+    pub fn new(rollouts: Vec<Rollout>) -> Self {
+        Self {
+            rollouts,
+            stats: Mutex::default(),
+        }
+    }
+
+    /// Load rollouts from `CANARY_ROLLOUTS`, e.g. `plan_v2=10,search_v2=50`.
+    /// Malformed entries are skipped with a warning.
+    pub fn from_env() -> Self {
+        let spec = env::CANARY_ROLLOUTS.string();
+        let rollouts = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let parsed = entry.split_once('=').and_then(|(name, percent)| {
+                    let percent: u8 = percent.trim().parse().ok().filter(|p| *p <= 100)?;
+                    let name = name.trim();
+                    (!name.is_empty()).then(|| Rollout {
+                        name: name.to_string(),
+                        percent,
+                    })
+                });
+                if parsed.is_none() {
+                    tracing::warn!(entry = %entry, "Ignoring malformed canary rollout");
+                }
+                parsed
+            })
+            .collect();
+        Self::new(rollouts)
+    }
+
+    pub fn rollouts(&self) -> &[Rollout] {
+        &self.rollouts
+    }
+
+    /// Counters of a rollout's variant since startup.
+    #[cfg(feature = "auth")]
+    pub fn stats(&self, rollout: &str, variant: Variant) -> VariantStats {
+        self.stats
+            .lock()
+            .unwrap()
+            .get(&(rollout.to_string(), variant))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn record(&self, rollout: String, variant: Variant, server_error: bool, elapsed: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry((rollout, variant)).or_default();
+        entry.requests += 1;
+        if server_error {
+            entry.errors += 1;
+        }
+        entry.total_latency += elapsed;
+    }
+}
+
+/// Variants assigned to a request, and the rollout whose route served it.
+struct Assignments {
+    variants: HashMap<String, Variant>,
+    served: Option<String>,
+}
+
+/// Route guard matching requests assigned to the canary of `rollout`.
+///
+/// Register the canary route before the stable one for the same path, so
+/// requests it rejects fall through to the current handler. Rollouts not in
+/// `CANARY_ROLLOUTS` never match.
+#[cfg(test)]
+pub fn canary(rollout: &'static str) -> impl Guard {
+    move |ctx: &GuardContext<'_>| {
+        let mut extensions = ctx.req_data_mut();
+        let Some(assignments) = extensions.get_mut::<Assignments>() else {
+            return false;
+        };
+        let Some(variant) = assignments.variants.get(rollout).copied() else {
+            return false;
+        };
+        assignments.served = Some(rollout.to_string());
+        variant == Variant::Canary
+    }
+}
+
+/// Assigns rollout variants and records per-variant metrics.
+pub struct CanaryRouting {
+    rollouts: Arc<CanaryRollouts>,
+}
+
+impl CanaryRouting {
+    pub fn new(rollouts: Arc<CanaryRollouts>) -> Self {
+        Self { rollouts }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CanaryRouting
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CanaryRoutingService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CanaryRoutingService {
+            service,
+            rollouts: self.rollouts.clone(),
+        }))
+    }
+}
+
+pub struct CanaryRoutingService<S> {
+    service: S,
+    rollouts: Arc<CanaryRollouts>,
+}
+
+impl<S, B> Service<ServiceRequest> for CanaryRoutingService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.rollouts.rollouts().is_empty() {
+            return Box::pin(self.service.call(req));
+        }
+
+        let flags = Flags::from_request(req.request(), &mut Payload::None)
+            .into_inner()
+            .ok();
+        let user_key = user_key(&req);
+        let variants = self
+            .rollouts
+            .rollouts()
+            .iter()
+            .map(|rollout| {
+                let forced = flags
+                    .as_ref()
+                    .is_some_and(|flags| flags.is_enabled(&rollout.flag()));
+                let variant = if forced || rollout.includes(&user_key) {
+                    Variant::Canary
+                } else {
+                    Variant::Stable
+                };
+                (rollout.name.clone(), variant)
+            })
+            .collect();
+        req.extensions_mut().insert(Assignments {
+            variants,
+            served: None,
+        });
+
+        let rollouts = self.rollouts.clone();
+        let started = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            let served = res
+                .request()
+                .extensions_mut()
+                .remove::<Assignments>()
+                .and_then(|mut assignments| {
+                    let rollout = assignments.served?;
+                    let variant = assignments.variants.remove(&rollout)?;
+                    Some((rollout, variant))
+                });
+            if let Some((rollout, variant)) = served {
+                rollouts.record(
+                    rollout,
+                    variant,
+                    res.status().is_server_error(),
+                    started.elapsed(),
+                );
+            }
+            Ok(res)
+        })
+    }
+}
+
+/// Key users are bucketed by: the authenticated user, or a random key per
+/// request for anonymous callers.
+fn user_key(req: &ServiceRequest) -> String {
+    #[cfg(feature = "auth")]
+    if let Ok(identity) =
+        crate::middleware::auth::Identity::from_request(req.request(), &mut Payload::None)
+            .into_inner()
+    {
+        return identity.user_id.to_string();
+    }
+
+    #[cfg(not(feature = "auth"))]
+    let _ = req;
+
+    uuid::Uuid::new_v4().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, test, web};
+
+    fn rollout(name: &str, percent: u8) -> Rollout {
+        Rollout {
+            name: name.to_string(),
+            percent,
+        }
+    }
+
+    fn requests(rollouts: &CanaryRollouts, rollout: &str, variant: Variant) -> u64 {
+        let stats = rollouts.stats.lock().unwrap();
+        stats
+            .get(&(rollout.to_string(), variant))
+            .map_or(0, |stats| stats.requests)
+    }
+
+    #[actix_web::test]
+    async fn test_requests_are_served_and_counted_by_variant() {
+        let rollouts = Arc::new(CanaryRollouts::new(vec![
+            rollout("plan_v2", 100),
+            rollout("search_v2", 0),
+        ]));
+        let app = test::init_service(
+            App::new()
+                .service(
+                    web::resource("/plan")
+                        .route(web::get().guard(canary("plan_v2")).to(|| async { "v2" }))
+                        .route(web::get().to(|| async { "v1" })),
+                )
+                .service(
+                    web::resource("/search")
+                        .route(web::get().guard(canary("search_v2")).to(|| async { "v2" }))
+                        .route(
+                            web::get()
+                                .to(|| async { HttpResponse::InternalServerError().finish() }),
+                        ),
+                )
+                .wrap(CanaryRouting::new(rollouts.clone())),
+        )
+        .await;
+
+        let request = test::TestRequest::get().uri("/plan").to_request();
+        assert_eq!(test::call_and_read_body(&app, request).await, "v2");
+        let request = test::TestRequest::get().uri("/search").to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), 500);
+
+        assert_eq!(requests(&rollouts, "plan_v2", Variant::Canary), 1);
+        assert_eq!(requests(&rollouts, "search_v2", Variant::Stable), 1);
+        let stats = rollouts.stats.lock().unwrap();
+        assert_eq!(stats[&("search_v2".to_string(), Variant::Stable)].errors, 1);
+    }
+}
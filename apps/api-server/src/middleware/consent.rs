@@ -0,0 +1,127 @@
+//! Policy consent middleware.
+
+use actix_web::{
+    Error, FromRequest, HttpMessage,
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::header::{HeaderName, HeaderValue},
+    web,
+};
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppError;
+use crate::state::AppState;
+
+/// Response header listing the policies the caller has to (re-)accept.
+pub const CONSENT_REQUIRED_HEADER: &str = "x-consent-required";
+
+/// Checks that the caller accepted the current policy versions.
+///
+/// `ConsentCheck::flag()` wraps the whole app: authenticated responses get an
+/// `X-Consent-Required` header naming outstanding policies, so clients can
+/// prompt for re-consent. `ConsentCheck::enforce()` wraps sensitive routes and
+/// rejects callers with outstanding policies with 451.
+pub struct ConsentCheck {
+    enforce: bool,
+}
+
+impl ConsentCheck {
+    pub fn flag() -> Self {
+        Self { enforce: false }
+    }
+
+    pub fn enforce() -> Self {
+        Self { enforce: true }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ConsentCheck
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ConsentCheckService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ConsentCheckService {
+            service: Rc::new(service),
+            enforce: self.enforce,
+        }))
+    }
+}
+
+pub struct ConsentCheckService<S> {
+    service: Rc<S>,
+    enforce: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for ConsentCheckService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let enforce = self.enforce;
+
+        Box::pin(async move {
+            let state = req
+                .app_data::<web::Data<AppState>>()
+                .cloned()
+                .ok_or_else(|| AppError::Internal("AppState not configured".to_string()))?;
+
+            if enforce {
+                let identity = Identity::from_request(req.request(), &mut Payload::None)
+                    .into_inner()
+                    .map_err(Error::from)?;
+                let outstanding = state
+                    .consent
+                    .outstanding(identity.user_id)
+                    .await
+                    .map_err(AppError::from)?;
+                if !outstanding.is_empty() {
+                    return Err(AppError::ConsentRequired(outstanding).into());
+                }
+                return service.call(req).await;
+            }
+
+            // Flag only requests a handler authenticated
+            let mut res = service.call(req).await?;
+            let user_id = res
+                .request()
+                .extensions()
+                .get::<Identity>()
+                .map(|identity| identity.user_id);
+            if let Some(user_id) = user_id {
+                match state.consent.outstanding(user_id).await {
+                    Ok(outstanding) if !outstanding.is_empty() => {
+                        let policies = outstanding
+                            .iter()
+                            .map(|policy| policy.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        if let Ok(value) = HeaderValue::from_str(&policies) {
+                            res.headers_mut()
+                                .insert(HeaderName::from_static(CONSENT_REQUIRED_HEADER), value);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Failed to check policy consent"),
+                }
+            }
+            Ok(res)
+        })
+    }
+}
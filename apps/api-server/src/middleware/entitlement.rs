@@ -0,0 +1,93 @@
+//! Plan entitlement middleware.
+
+use actix_web::{
+    Error, FromRequest,
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    web,
+};
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use apex_core::domain::Entitlement;
+
+use crate::middleware::auth::Identity;
+use crate::middleware::error::AppError;
+use crate::state::AppState;
+
+/// Rejects requests whose account plan lacks an entitlement.
+///
+/// Wrap a scope or resource to declare what it requires:
+/// ```ignore
+/// web::scope("/exports").wrap(RequireEntitlement::new(Entitlement::Exports))
+/// ```
+/// Unauthenticated requests get 401; accounts on a plan without the
+/// entitlement get 402 naming the cheapest plan that has it.
+pub struct RequireEntitlement {
+    entitlement: Entitlement,
+}
+
+impl RequireEntitlement {
+    pub fn new(entitlement: Entitlement) -> Self {
+        Self { entitlement }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireEntitlement
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequireEntitlementService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireEntitlementService {
+            service: Rc::new(service),
+            entitlement: self.entitlement,
+        }))
+    }
+}
+
+pub struct RequireEntitlementService<S> {
+    service: Rc<S>,
+    entitlement: Entitlement,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireEntitlementService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let entitlement = self.entitlement;
+
+        Box::pin(async move {
+            let identity = Identity::from_request(req.request(), &mut Payload::None)
+                .into_inner()
+                .map_err(Error::from)?;
+            let state = req
+                .app_data::<web::Data<AppState>>()
+                .cloned()
+                .ok_or_else(|| AppError::Internal("AppState not configured".to_string()))?;
+
+            state
+                .entitlements
+                .check(identity.account_id(), entitlement)
+                .await
+                .map_err(AppError::from)?;
+
+            service.call(req).await
+        })
+    }
+}
@@ -1,7 +1,12 @@
 //! Error handling middleware - RFC 7807 compliant responses.
 
 use actix_web::{HttpResponse, ResponseError, http::StatusCode};
+#[cfg(feature = "auth")]
+use apex_core::domain::PolicyDocument;
+use apex_core::domain::{Entitlement, Plan, QuotaUsage};
 use apex_shared::ErrorResponse;
+#[cfg(feature = "auth")]
+use apex_shared::FieldError;
 use std::fmt;
 
 /// Application-level error type that converts to RFC 7807 responses.
@@ -13,7 +18,40 @@ pub enum AppError {
     Forbidden,
     Conflict(String),
     Internal(String),
-    Validation(Vec<String>),
+    /// The account's plan lacks an entitlement (402 with an upgrade hint).
+    MissingEntitlement {
+        entitlement: Entitlement,
+        plan: Plan,
+    },
+    /// Storing `requested` more bytes would go over the account's storage
+    /// quota (402 when a bigger plan has room, 413 otherwise).
+    StorageQuotaExceeded {
+        plan: Plan,
+        used: i64,
+        quota: i64,
+        requested: i64,
+        upgrade: Option<Plan>,
+    },
+    /// The account used up a daily or monthly API quota (429 until it resets).
+    QuotaExceeded(QuotaUsage),
+    /// The user has to accept the current version of these policies first (451).
+    #[cfg(feature = "auth")]
+    ConsentRequired(Vec<PolicyDocument>),
+    /// Request fields that failed validation (422), see `ValidatedJson`.
+    #[cfg(feature = "auth")]
+    Validation(Vec<FieldError>),
+    /// The `Accept` header asks for an API version that is not served (406).
+    NotAcceptable(String),
+    /// The API version is past its sunset date (410).
+    Gone(String),
+    /// The request body is over a size limit (413).
+    PayloadTooLarge(String),
+    /// An upload's media type is not accepted (415).
+    #[cfg(all(feature = "auth", feature = "storage"))]
+    UnsupportedMediaType(String),
+    /// The feature is not available for this setup, e.g. its database (501).
+    #[cfg(all(feature = "auth", feature = "postgres"))]
+    NotImplemented(String),
 }
 
 impl fmt::Display for AppError {
@@ -25,7 +63,41 @@ impl fmt::Display for AppError {
             AppError::Forbidden => write!(f, "Forbidden"),
             AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
             AppError::Internal(msg) => write!(f, "Internal error: {}", msg),
-            AppError::Validation(errors) => write!(f, "Validation errors: {:?}", errors),
+            AppError::MissingEntitlement { entitlement, plan } => {
+                write!(f, "The {} plan does not include {}", plan, entitlement)
+            }
+            AppError::StorageQuotaExceeded {
+                plan,
+                quota,
+                requested,
+                ..
+            } => write!(
+                f,
+                "Storing {} more bytes would exceed the {} byte quota of the {} plan",
+                requested, quota, plan
+            ),
+            AppError::QuotaExceeded(usage) => write!(
+                f,
+                "The {} quota of {} requests is used up",
+                usage.period, usage.limit
+            ),
+            #[cfg(feature = "auth")]
+            AppError::ConsentRequired(policies) => {
+                let policies: Vec<_> = policies.iter().map(|p| p.as_str()).collect();
+                write!(f, "Accept the current {} policy first", policies.join(", "))
+            }
+            #[cfg(feature = "auth")]
+            AppError::Validation(errors) => {
+                let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "Invalid input: {}", errors.join(", "))
+            }
+            AppError::NotAcceptable(msg) => write!(f, "Not acceptable: {}", msg),
+            AppError::Gone(msg) => write!(f, "Gone: {}", msg),
+            AppError::PayloadTooLarge(msg) => write!(f, "Payload too large: {}", msg),
+            #[cfg(all(feature = "auth", feature = "storage"))]
+            AppError::UnsupportedMediaType(msg) => write!(f, "Unsupported media type: {}", msg),
+            #[cfg(all(feature = "auth", feature = "postgres"))]
+            AppError::NotImplemented(msg) => write!(f, "Not implemented: {}", msg),
         }
     }
 }
@@ -39,7 +111,29 @@ impl ResponseError for AppError {
             AppError::Forbidden => StatusCode::FORBIDDEN,
             AppError::Conflict(_) => StatusCode::CONFLICT,
             AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            // Payment Required when an upgrade would help, Forbidden when no plan offers it
+            AppError::MissingEntitlement { entitlement, .. } => {
+                match Plan::cheapest_with(*entitlement) {
+                    Some(_) => StatusCode::PAYMENT_REQUIRED,
+                    None => StatusCode::FORBIDDEN,
+                }
+            }
+            AppError::StorageQuotaExceeded { upgrade, .. } => match upgrade {
+                Some(_) => StatusCode::PAYMENT_REQUIRED,
+                None => StatusCode::PAYLOAD_TOO_LARGE,
+            },
+            AppError::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            #[cfg(feature = "auth")]
+            AppError::ConsentRequired(_) => StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+            #[cfg(feature = "auth")]
             AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            AppError::Gone(_) => StatusCode::GONE,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            #[cfg(all(feature = "auth", feature = "storage"))]
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            #[cfg(all(feature = "auth", feature = "postgres"))]
+            AppError::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
         }
     }
 
@@ -55,12 +149,76 @@ impl ResponseError for AppError {
                 tracing::error!("Internal error: {}", detail);
                 ErrorResponse::internal_error()
             }
-            AppError::Validation(errors) => {
-                ErrorResponse::new(422, "Validation Failed").with_detail(errors.join(", "))
+            AppError::MissingEntitlement { entitlement, plan } => {
+                let error = match Plan::cheapest_with(*entitlement) {
+                    Some(required) => ErrorResponse::new(402, "Upgrade Required")
+                        .with_extension("required_plan", required.as_str()),
+                    None => ErrorResponse::new(403, "Feature Unavailable"),
+                };
+                error
+                    .with_detail(self.to_string())
+                    .with_extension("entitlement", entitlement.as_str())
+                    .with_extension("current_plan", plan.as_str())
+            }
+            AppError::StorageQuotaExceeded {
+                plan,
+                used,
+                quota,
+                requested,
+                upgrade,
+            } => {
+                let error = match upgrade {
+                    Some(required) => ErrorResponse::new(402, "Storage Quota Exceeded")
+                        .with_extension("required_plan", required.as_str()),
+                    None => ErrorResponse::new(413, "Storage Quota Exceeded"),
+                };
+                error
+                    .with_detail(self.to_string())
+                    .with_extension("current_plan", plan.as_str())
+                    .with_extension("used_bytes", *used)
+                    .with_extension("quota_bytes", *quota)
+                    .with_extension("requested_bytes", *requested)
+            }
+            AppError::QuotaExceeded(usage) => ErrorResponse::new(429, "Quota Exceeded")
+                .with_detail(self.to_string())
+                .with_extension("period", usage.period.as_str())
+                .with_extension("limit", usage.limit)
+                .with_extension("used", usage.used)
+                .with_extension("resets_at", usage.resets_at.to_rfc3339()),
+            #[cfg(feature = "auth")]
+            AppError::ConsentRequired(policies) => ErrorResponse::new(451, "Consent Required")
+                .with_detail(self.to_string())
+                .with_extension(
+                    "policies",
+                    policies.iter().map(|p| p.as_str()).collect::<Vec<_>>(),
+                ),
+            #[cfg(feature = "auth")]
+            AppError::Validation(errors) => ErrorResponse::new(422, "Validation Failed")
+                .with_detail(self.to_string())
+                .with_extension("errors", serde_json::to_value(errors).unwrap_or_default()),
+            AppError::NotAcceptable(detail) => {
+                ErrorResponse::new(406, "Not Acceptable").with_detail(detail)
+            }
+            AppError::Gone(detail) => ErrorResponse::new(410, "Gone").with_detail(detail),
+            AppError::PayloadTooLarge(detail) => {
+                ErrorResponse::new(413, "Payload Too Large").with_detail(detail)
+            }
+            #[cfg(all(feature = "auth", feature = "storage"))]
+            AppError::UnsupportedMediaType(detail) => {
+                ErrorResponse::new(415, "Unsupported Media Type").with_detail(detail)
+            }
+            #[cfg(all(feature = "auth", feature = "postgres"))]
+            AppError::NotImplemented(detail) => {
+                ErrorResponse::new(501, "Not Implemented").with_detail(detail)
             }
         };
 
-        HttpResponse::build(self.status_code()).json(error)
+        let mut response = HttpResponse::build(self.status_code());
+        if let AppError::QuotaExceeded(usage) = self {
+            let retry_after = (usage.resets_at - chrono::Utc::now()).num_seconds().max(1);
+            response.insert_header(("Retry-After", retry_after.to_string()));
+        }
+        response.json(error)
     }
 }
 
@@ -86,6 +244,7 @@ impl From<apex_core::error::RepoError> for AppError {
                 AppError::NotFound("Resource not found".to_string())
             }
             apex_core::error::RepoError::Constraint(msg) => AppError::Conflict(msg),
+            apex_core::error::RepoError::Conflict(msg) => AppError::Conflict(msg),
             apex_core::error::RepoError::Connection(msg) => {
                 tracing::error!("Database connection error: {}", msg);
                 AppError::Internal("Database error".to_string())
@@ -98,5 +257,164 @@ impl From<apex_core::error::RepoError> for AppError {
     }
 }
 
+impl From<apex_core::ports::SettingsError> for AppError {
+    fn from(err: apex_core::ports::SettingsError) -> Self {
+        match err {
+            apex_core::ports::SettingsError::Invalid(e) => e.into(),
+            apex_core::ports::SettingsError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::SubscriptionError> for AppError {
+    fn from(err: apex_core::ports::SubscriptionError) -> Self {
+        match err {
+            apex_core::ports::SubscriptionError::Invalid(e) => e.into(),
+            apex_core::ports::SubscriptionError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::ConsentError> for AppError {
+    fn from(err: apex_core::ports::ConsentError) -> Self {
+        match err {
+            apex_core::ports::ConsentError::Invalid(e) => e.into(),
+            apex_core::ports::ConsentError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::ApprovalError> for AppError {
+    fn from(err: apex_core::ports::ApprovalError) -> Self {
+        match err {
+            apex_core::ports::ApprovalError::NotFound(id) => {
+                AppError::NotFound(format!("Operation {} not found", id))
+            }
+            apex_core::ports::ApprovalError::SelfApproval => AppError::Forbidden,
+            apex_core::ports::ApprovalError::AlreadyDecided(status) => {
+                AppError::Conflict(format!("Operation is already {}", status))
+            }
+            apex_core::ports::ApprovalError::Invalid(e) => e.into(),
+            apex_core::ports::ApprovalError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::EntitlementError> for AppError {
+    fn from(err: apex_core::ports::EntitlementError) -> Self {
+        match err {
+            apex_core::ports::EntitlementError::Missing { entitlement, plan } => {
+                AppError::MissingEntitlement { entitlement, plan }
+            }
+            apex_core::ports::EntitlementError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::StorageQuotaError> for AppError {
+    fn from(err: apex_core::ports::StorageQuotaError) -> Self {
+        match err {
+            apex_core::ports::StorageQuotaError::Exceeded {
+                plan,
+                used,
+                quota,
+                requested,
+                upgrade,
+            } => AppError::StorageQuotaExceeded {
+                plan,
+                used,
+                quota,
+                requested,
+                upgrade,
+            },
+            apex_core::ports::StorageQuotaError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::ApiQuotaError> for AppError {
+    fn from(err: apex_core::ports::ApiQuotaError) -> Self {
+        match err {
+            apex_core::ports::ApiQuotaError::Exceeded(usage) => AppError::QuotaExceeded(usage),
+            apex_core::ports::ApiQuotaError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::CustomDomainError> for AppError {
+    fn from(err: apex_core::ports::CustomDomainError) -> Self {
+        use apex_core::ports::CustomDomainError;
+
+        match err {
+            CustomDomainError::Invalid(e) => e.into(),
+            CustomDomainError::Taken(_) => AppError::Conflict(err.to_string()),
+            CustomDomainError::NotFound => AppError::NotFound(err.to_string()),
+            CustomDomainError::ChallengeFailed { .. } => AppError::BadRequest(err.to_string()),
+            CustomDomainError::Dns(e) => {
+                tracing::error!("DNS lookup error: {}", e);
+                AppError::Internal("DNS lookup failed".to_string())
+            }
+            CustomDomainError::Repo(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::AuthError> for AppError {
+    fn from(err: apex_core::ports::AuthError) -> Self {
+        use apex_core::ports::AuthError;
+
+        match err {
+            AuthError::InsufficientPermissions => AppError::Forbidden,
+            AuthError::HashingError(_) => AppError::Internal(err.to_string()),
+            AuthError::InvalidCredentials
+            | AuthError::TokenExpired
+            | AuthError::InvalidToken(_)
+            | AuthError::MissingAuth => AppError::Unauthorized,
+        }
+    }
+}
+
+impl From<apex_core::services::ServiceError> for AppError {
+    fn from(err: apex_core::services::ServiceError) -> Self {
+        match err {
+            apex_core::services::ServiceError::Invalid(e) => e.into(),
+            apex_core::services::ServiceError::Repo(e) => e.into(),
+            apex_core::services::ServiceError::Auth(e) => e.into(),
+            apex_core::services::ServiceError::Conflict(msg) => AppError::Conflict(msg),
+            apex_core::services::ServiceError::Storage(e) => e.into(),
+        }
+    }
+}
+
+impl From<apex_core::ports::StorageError> for AppError {
+    fn from(err: apex_core::ports::StorageError) -> Self {
+        use apex_core::ports::StorageError;
+
+        match err {
+            StorageError::TooLarge(_) => AppError::PayloadTooLarge(err.to_string()),
+            StorageError::Source(msg) => AppError::BadRequest(msg),
+            StorageError::InvalidKey(_) | StorageError::Backend(_) => {
+                tracing::error!("Storage error: {}", err);
+                AppError::Internal("Storage error".to_string())
+            }
+        }
+    }
+}
+
+impl From<apex_core::ports::JobQueueError> for AppError {
+    fn from(err: apex_core::ports::JobQueueError) -> Self {
+        match err {
+            apex_core::ports::JobQueueError::QueueFull
+            | apex_core::ports::JobQueueError::ShuttingDown => AppError::Conflict(err.to_string()),
+            apex_core::ports::JobQueueError::InvalidSchedule(msg) => AppError::BadRequest(msg),
+            apex_core::ports::JobQueueError::EnqueueError(msg)
+            | apex_core::ports::JobQueueError::Backend(msg) => {
+                tracing::error!("Job queue error: {}", msg);
+                AppError::Internal("Job queue error".to_string())
+            }
+        }
+    }
+}
+
 /// Result type alias for handlers.
 pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,111 @@
+//! Feature flag extractor with per-request overrides.
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+use std::future::{Ready, ready};
+
+use apex_core::domain::FeatureFlags;
+
+use crate::middleware::error::AppError;
+
+/// Header overriding flags for a single request, in the `FEATURE_FLAGS`
+/// format: `new_checkout,beta_search=off`.
+pub const FEATURE_OVERRIDE_HEADER: &str = "X-Feature-Override";
+
+/// Feature flags as evaluated for the current request.
+///
+/// ```ignore
+/// async fn checkout(flags: Flags) -> impl Responder {
+///     if flags.is_enabled("new_checkout") { ... }
+/// }
+/// ```
+///
+/// Admin and internal identities can send [`FEATURE_OVERRIDE_HEADER`] to try
+/// unreleased behavior in production without changing it for anyone else.
+/// The header is ignored for everyone else, so clients cannot opt themselves
+/// into flags.
+#[derive(Debug, Clone)]
+pub struct Flags {
+    flags: FeatureFlags,
+    overridden: bool,
+}
+
+impl Flags {
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.flags.is_enabled(flag)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.flags.iter()
+    }
+
+    /// Whether an override header was applied.
+    pub fn overridden(&self) -> bool {
+        self.overridden
+    }
+}
+
+impl FromRequest for Flags {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let defaults = req
+            .app_data::<web::Data<FeatureFlags>>()
+            .map(|flags| flags.get_ref().clone())
+            .unwrap_or_default();
+
+        let Some(header) = req.headers().get(FEATURE_OVERRIDE_HEADER) else {
+            return ready(Ok(Flags {
+                flags: defaults,
+                overridden: false,
+            }));
+        };
+
+        ready(apply_override(req, defaults, header))
+    }
+}
+
+#[cfg(feature = "auth")]
+fn apply_override(
+    req: &HttpRequest,
+    defaults: FeatureFlags,
+    header: &actix_web::http::header::HeaderValue,
+) -> Result<Flags, AppError> {
+    use crate::middleware::auth::{ADMIN_ROLE, INTERNAL_ROLE, Identity};
+
+    let privileged = Identity::from_request(req, &mut Payload::None)
+        .into_inner()
+        .ok()
+        .filter(|identity| identity.has_role(ADMIN_ROLE) || identity.has_role(INTERNAL_ROLE));
+    let Some(identity) = privileged else {
+        tracing::debug!("Ignoring feature override from an unprivileged caller");
+        return Ok(Flags {
+            flags: defaults,
+            overridden: false,
+        });
+    };
+
+    let spec = header
+        .to_str()
+        .map_err(|_| AppError::BadRequest(format!("Invalid {} header", FEATURE_OVERRIDE_HEADER)))?;
+    let overrides = FeatureFlags::parse(spec).map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    tracing::info!(user_id = %identity.user_id, overrides = %spec, "Feature flags overridden");
+    Ok(Flags {
+        flags: defaults.overridden(&overrides),
+        overridden: true,
+    })
+}
+
+#[cfg(not(feature = "auth"))]
+fn apply_override(
+    _req: &HttpRequest,
+    defaults: FeatureFlags,
+    _header: &actix_web::http::header::HeaderValue,
+) -> Result<Flags, AppError> {
+    // Without authentication nobody is allowed to override
+    Ok(Flags {
+        flags: defaults,
+        overridden: false,
+    })
+}
@@ -0,0 +1,77 @@
+//! Usage metering middleware.
+
+use actix_web::{
+    Error, HttpMessage,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+};
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use apex_core::domain::UsageMetric;
+use apex_infra::UsageMeter;
+
+use crate::middleware::auth::Identity;
+
+/// Counts one API call per authenticated request against the caller's account.
+///
+/// Requests are attributed once a handler has extracted an `Identity`, so
+/// unauthenticated and rejected requests are not billed.
+pub struct UsageMeteringMiddleware {
+    meter: Arc<UsageMeter>,
+}
+
+impl UsageMeteringMiddleware {
+    pub fn new(meter: Arc<UsageMeter>) -> Self {
+        Self { meter }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for UsageMeteringMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = UsageMeteringMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(UsageMeteringMiddlewareService {
+            service,
+            meter: self.meter.clone(),
+        }))
+    }
+}
+
+pub struct UsageMeteringMiddlewareService<S> {
+    service: S,
+    meter: Arc<UsageMeter>,
+}
+
+impl<S, B> Service<ServiceRequest> for UsageMeteringMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let meter = self.meter.clone();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            if let Some(identity) = res.request().extensions().get::<Identity>() {
+                meter.record(identity.account_id(), UsageMetric::ApiCalls, 1);
+            }
+            Ok(res)
+        })
+    }
+}
@@ -1,9 +1,34 @@
 //! Middleware modules.
 
+pub mod canary;
 pub mod error;
+pub mod feature_flags;
+pub mod shadow;
+pub mod versioning;
 
 #[cfg(feature = "auth")]
 pub mod auth;
 
+#[cfg(feature = "auth")]
+pub mod consent;
+
+#[cfg(feature = "auth")]
+pub mod entitlement;
+
+#[cfg(feature = "auth")]
+pub mod metering;
+
+#[cfg(feature = "auth")]
+pub mod patch;
+
+#[cfg(feature = "auth")]
+pub mod quota;
+
+#[cfg(feature = "auth")]
+pub mod tenant;
+
+#[cfg(feature = "auth")]
+pub mod validation;
+
 #[cfg(feature = "rate-limit")]
 pub mod rate_limit;
@@ -0,0 +1,47 @@
+//! Request bodies of `PATCH` endpoints.
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, http::header, web};
+use std::future::Future;
+use std::pin::Pin;
+
+use apex_core::domain::Patch;
+
+use crate::middleware::error::AppError;
+
+/// Content type of RFC 6902 JSON Patch bodies.
+pub const JSON_PATCH: &str = "application/json-patch+json";
+
+/// A patch read from the request body.
+///
+/// `application/json-patch+json` bodies are lists of JSON Patch operations;
+/// anything else, including plain `application/json`, is a JSON Merge Patch
+/// (`application/merge-patch+json`).
+pub struct PatchBody(pub Patch);
+
+impl FromRequest for PatchBody {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json_patch = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with(JSON_PATCH));
+        let body = web::Bytes::from_request(req, payload);
+
+        Box::pin(async move {
+            let body = body
+                .await
+                .map_err(|e| AppError::BadRequest(e.to_string()))?;
+            let invalid =
+                |e: serde_json::Error| AppError::BadRequest(format!("Invalid patch: {}", e));
+            let patch = if json_patch {
+                Patch::Json(serde_json::from_slice(&body).map_err(invalid)?)
+            } else {
+                Patch::Merge(serde_json::from_slice(&body).map_err(invalid)?)
+            };
+            Ok(PatchBody(patch))
+        })
+    }
+}
@@ -0,0 +1,123 @@
+//! Daily and monthly API quota middleware.
+//!
+//! Every request with a valid Bearer token counts against the quotas of the
+//! caller's account (`API_QUOTAS`). Once one is used up, requests get a 429
+//! until its window resets; `GET /api/usage` stays reachable so clients can
+//! see why. Counted responses carry `X-Quota-Limit`, `X-Quota-Remaining` and
+//! `X-Quota-Reset` (seconds until the window resets) for the quota closest
+//! to running out.
+
+use actix_web::{
+    Error,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+    web,
+};
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use apex_core::domain::QuotaUsage;
+use apex_core::ports::ApiQuotaError;
+
+use crate::middleware::auth::{Identity, bearer_claims};
+use crate::middleware::error::AppError;
+use crate::middleware::versioning::unversioned;
+use crate::state::AppState;
+
+/// Path clients check their consumption on, never refused.
+const USAGE_PATH: &str = "/api/usage";
+
+/// Set the `X-Quota-*` headers for the quota with the fewest requests left.
+fn insert_quota_headers(headers: &mut HeaderMap, usage: &[QuotaUsage]) {
+    let Some(tightest) = usage.iter().min_by_key(|usage| usage.remaining()) else {
+        return;
+    };
+    let reset = (tightest.resets_at - chrono::Utc::now())
+        .num_seconds()
+        .max(0) as u64;
+    for (name, value) in [
+        ("x-quota-limit", tightest.limit),
+        ("x-quota-remaining", tightest.remaining()),
+        ("x-quota-reset", reset),
+    ] {
+        headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+    }
+}
+
+/// Enforces the account's API quotas.
+pub struct ApiQuotaCheck;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiQuotaCheck
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ApiQuotaCheckService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiQuotaCheckService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct ApiQuotaCheckService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiQuotaCheckService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let quotas = req
+                .app_data::<web::Data<AppState>>()
+                .map(|state| state.api_quotas.clone())
+                .filter(|quotas| !quotas.is_empty() && unversioned(req.path()) != USAGE_PATH);
+            let Some(quotas) = quotas else {
+                return service.call(req).await;
+            };
+            let Some(account_id) =
+                bearer_claims(req.request()).map(|claims| Identity::from(claims).account_id())
+            else {
+                return service.call(req).await;
+            };
+
+            let usage = match quotas.consume(account_id).await {
+                Ok(usage) => usage,
+                Err(ApiQuotaError::Exceeded(usage)) => {
+                    tracing::warn!(
+                        account_id = %account_id,
+                        period = %usage.period,
+                        "API quota exceeded"
+                    );
+                    return Err(AppError::QuotaExceeded(usage).into());
+                }
+                // Fail open, like the rate limiter
+                Err(ApiQuotaError::Repo(e)) => {
+                    tracing::error!(error = %e, "Failed to check API quota, failing open");
+                    Vec::new()
+                }
+            };
+
+            let mut res = service.call(req).await?;
+            insert_quota_headers(res.headers_mut(), &usage);
+            Ok(res)
+        })
+    }
+}
@@ -1,25 +1,204 @@
 //! Rate limiting middleware.
+//!
+//! Which limiter a request counts against comes from a [`RateLimitPolicy`],
+//! so routes needing tighter limits (e.g. `/api/auth/*`) are configured there
+//! rather than by wrapping their scopes. Patterns are matched without the
+//! API version, so they cover every version of a route.
+//!
+//! Who a request counts for comes from a [`KeyExtractor`]: by default the
+//! signed-in user, falling back to the client IP, so users behind one NAT
+//! don't share a budget.
+//!
+//! The budget itself depends on the caller's [`RateLimitTier`], resolved
+//! from the token claims: admins, paying subscribers, other signed-in users
+//! and anonymous callers can each get their own limit (`RATE_LIMIT_TIERS`).
+//!
+//! Before any of that, the policy's allow- and denylists are consulted by
+//! client IP and signed-in user: allowlisted clients (health checkers,
+//! internal services) are never limited and denylisted ones always get a 403.
+//! The client IP is the connection's peer; `Forwarded` and `X-Forwarded-For`
+//! only count when that peer is one of the policy's [`TrustedProxies`].
+//!
+//! Every checked response carries `X-RateLimit-Limit`, `X-RateLimit-Remaining`
+//! and `X-RateLimit-Reset` (seconds until the full budget is back, or until
+//! the next request is allowed once limited), so clients can pace themselves
+//! before hitting a 429.
 
 use actix_web::{
     Error, HttpResponse,
     body::EitherBody,
     dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::header::{self, HeaderMap, HeaderName, HeaderValue},
 };
+use apex_core::ports::{RateLimitResult, RateLimitTier};
 use apex_shared::ErrorResponse;
 use std::future::{Future, Ready, ready};
+use std::net::IpAddr;
 use std::pin::Pin;
+use std::rc::Rc;
 use std::sync::Arc;
 
-use apex_core::ports::RateLimiter;
+use apex_infra::RateLimitPolicy;
+use apex_infra::rate_limit::{ClientAccess, TrustedProxies};
+
+#[cfg(feature = "auth")]
+use apex_core::ports::TokenClaims;
+
+#[cfg(feature = "auth")]
+use crate::middleware::auth::bearer_claims;
+use crate::middleware::versioning::unversioned;
+
+/// Who sent a request, resolved once when it arrives.
+pub struct Caller {
+    /// Client address, per the policy's [`TrustedProxies`].
+    pub ip: Option<IpAddr>,
+    /// Claims of the request's Bearer token, if it is valid.
+    #[cfg(feature = "auth")]
+    pub claims: Option<TokenClaims>,
+}
+
+impl Caller {
+    fn resolve(req: &ServiceRequest, proxies: &TrustedProxies) -> Self {
+        let header = |name: HeaderName| {
+            let values: Vec<&str> = req
+                .headers()
+                .get_all(name)
+                .filter_map(|value| value.to_str().ok())
+                .collect();
+            (!values.is_empty()).then(|| values.join(","))
+        };
+        let ip = if proxies.is_empty() {
+            req.peer_addr().map(|addr| addr.ip())
+        } else {
+            proxies.client_ip(
+                req.peer_addr().map(|addr| addr.ip()),
+                header(header::FORWARDED).as_deref(),
+                header(header::X_FORWARDED_FOR).as_deref(),
+            )
+        };
+        Self {
+            ip,
+            #[cfg(feature = "auth")]
+            claims: bearer_claims(req.request()),
+        }
+    }
+
+    /// User of the request's Bearer token, if it is valid.
+    #[cfg(feature = "auth")]
+    pub fn user_id(&self) -> Option<uuid::Uuid> {
+        self.claims.as_ref().map(|claims| claims.user_id)
+    }
+
+    /// Without authentication no request has a user.
+    #[cfg(not(feature = "auth"))]
+    pub fn user_id(&self) -> Option<uuid::Uuid> {
+        None
+    }
+
+    /// Tier whose budget the request counts against: admin by role, premium
+    /// with a paid plan whose subscription grants access, otherwise user
+    /// when signed in and anonymous when not.
+    #[cfg(feature = "auth")]
+    fn tier(&self) -> RateLimitTier {
+        use apex_core::domain::Plan;
+
+        let Some(claims) = &self.claims else {
+            return RateLimitTier::Anonymous;
+        };
+        if claims.roles.iter().any(|role| role == "admin") {
+            RateLimitTier::Admin
+        } else if claims
+            .subscription
+            .as_ref()
+            .is_some_and(|sub| sub.plan > Plan::Free && sub.status.grants_access())
+        {
+            RateLimitTier::Premium
+        } else {
+            RateLimitTier::User
+        }
+    }
+
+    /// Without authentication every caller is anonymous.
+    #[cfg(not(feature = "auth"))]
+    fn tier(&self) -> RateLimitTier {
+        RateLimitTier::Anonymous
+    }
+}
+
+/// Attributes a request to the client whose budget it counts against.
+pub trait KeyExtractor: Send + Sync {
+    fn key(&self, req: &ServiceRequest, caller: &Caller) -> String;
+}
+
+impl<F> KeyExtractor for F
+where
+    F: Fn(&ServiceRequest, &Caller) -> String + Send + Sync,
+{
+    fn key(&self, req: &ServiceRequest, caller: &Caller) -> String {
+        self(req, caller)
+    }
+}
+
+/// Keys on the client IP (`ip:<addr>`).
+pub struct IpKey;
+
+impl KeyExtractor for IpKey {
+    fn key(&self, _req: &ServiceRequest, caller: &Caller) -> String {
+        match caller.ip {
+            Some(ip) => format!("ip:{}", ip),
+            None => "ip:unknown".to_string(),
+        }
+    }
+}
+
+/// Keys on the user of a valid Bearer token (`user:<id>`), and on the
+/// client IP without one.
+#[cfg(feature = "auth")]
+pub struct IdentityKey;
+
+#[cfg(feature = "auth")]
+impl KeyExtractor for IdentityKey {
+    fn key(&self, req: &ServiceRequest, caller: &Caller) -> String {
+        match caller.user_id() {
+            Some(user_id) => format!("user:{}", user_id),
+            None => IpKey.key(req, caller),
+        }
+    }
+}
+
+/// Whole seconds until `result` resets, rounded up so a client waiting the
+/// advertised time is never early.
+fn reset_secs(result: &RateLimitResult) -> u64 {
+    result.reset_after.as_secs() + u64::from(result.reset_after.subsec_nanos() > 0)
+}
+
+/// Set the `X-RateLimit-*` headers describing `result`.
+fn insert_rate_limit_headers(headers: &mut HeaderMap, result: &RateLimitResult) {
+    let reset = reset_secs(result);
+    for (name, value) in [
+        ("x-ratelimit-limit", u64::from(result.limit)),
+        ("x-ratelimit-remaining", u64::from(result.remaining)),
+        ("x-ratelimit-reset", reset),
+    ] {
+        headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+    }
+}
 
 /// Rate limiting middleware factory.
 pub struct RateLimitMiddleware {
-    limiter: Arc<dyn RateLimiter>,
+    policy: Arc<RateLimitPolicy>,
+    keys: Arc<dyn KeyExtractor>,
 }
 
 impl RateLimitMiddleware {
-    pub fn new(limiter: Arc<dyn RateLimiter>) -> Self {
-        Self { limiter }
+    /// Keyed by [`IdentityKey`] with the `auth` feature, by [`IpKey`]
+    /// without it.
+    pub fn new(policy: Arc<RateLimitPolicy>) -> Self {
+        #[cfg(feature = "auth")]
+        let keys: Arc<dyn KeyExtractor> = Arc::new(IdentityKey);
+        #[cfg(not(feature = "auth"))]
+        let keys: Arc<dyn KeyExtractor> = Arc::new(IpKey);
+        Self { policy, keys }
     }
 }
 
@@ -36,15 +215,17 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(RateLimitMiddlewareService {
-            service,
-            limiter: self.limiter.clone(),
+            service: Rc::new(service),
+            policy: self.policy.clone(),
+            keys: self.keys.clone(),
         }))
     }
 }
 
 pub struct RateLimitMiddlewareService<S> {
-    service: S,
-    limiter: Arc<dyn RateLimiter>,
+    service: Rc<S>,
+    policy: Arc<RateLimitPolicy>,
+    keys: Arc<dyn KeyExtractor>,
 }
 
 impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
@@ -59,55 +240,77 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let limiter = self.limiter.clone();
-
-        // Get client identifier (IP address or user ID)
-        let key = req
-            .connection_info()
-            .realip_remote_addr()
-            .unwrap_or("unknown")
-            .to_string();
-
-        // Check rate limit synchronously before calling inner service
-        // We need to check first, then either proceed or reject
-        let check_result = {
-            // Use block_on for the sync check - in production, consider using
-            // a different approach or the keyed rate limiter that's sync
-            futures::executor::block_on(limiter.check(&key))
+        let caller = Caller::resolve(&req, self.policy.proxies());
+        let access = self.policy.access();
+        let access = if access.is_empty() {
+            ClientAccess::Limit
+        } else {
+            access.check(caller.ip, caller.user_id())
         };
+        match access {
+            ClientAccess::Allow => {
+                let fut = self.service.call(req);
+                return Box::pin(async move { Ok(fut.await?.map_into_left_body()) });
+            }
+            ClientAccess::Deny => {
+                tracing::warn!(path = %req.path(), "Request from a denylisted client refused");
+                let response = HttpResponse::Forbidden().json(ErrorResponse::new(403, "Forbidden"));
+                let (http_req, _payload) = req.into_parts();
+                let srv_response = ServiceResponse::new(http_req, response);
+                return Box::pin(async move { Ok(srv_response.map_into_right_body()) });
+            }
+            ClientAccess::Limit => {}
+        }
+
+        let (pattern, limiter) = self.policy.limiter_for(&unversioned(req.path()));
+        let limiter = limiter.clone();
 
-        match check_result {
-            Ok(result) if !result.allowed => {
-                // Rate limited - return 429 immediately
-                tracing::warn!("Rate limit exceeded for key: {}", key);
+        // Scope the client to the route rule, so a limiter shared between
+        // rules keeps separate counts
+        let client = self.keys.key(&req, &caller);
+        let key = match pattern {
+            Some(pattern) => format!("{}:{}", pattern, client),
+            None => client,
+        };
+        let tier = caller.tier();
+        let service = self.service.clone();
 
-                let error = ErrorResponse::new(429, "Too Many Requests").with_detail(format!(
-                    "Rate limit exceeded. Try again in {} seconds.",
-                    result.reset_after.as_secs()
-                ));
+        // The check runs in the returned future, so a limiter waiting on its
+        // backend (e.g. Redis) never blocks the worker thread
+        Box::pin(async move {
+            let result = match limiter.check_tier(&key, tier).await {
+                Ok(result) if !result.allowed => {
+                    tracing::warn!(tier = %tier, "Rate limit exceeded for key: {}", key);
+                    let retry_after = reset_secs(&result);
 
-                let response = HttpResponse::TooManyRequests()
-                    .insert_header(("X-RateLimit-Remaining", "0"))
-                    .insert_header(("Retry-After", result.reset_after.as_secs().to_string()))
-                    .json(error);
+                    let error = ErrorResponse::new(429, "Too Many Requests")
+                        .with_detail(format!(
+                            "Rate limit exceeded. Try again in {} seconds.",
+                            retry_after
+                        ))
+                        .with_extension("retry_after", retry_after);
 
-                let (http_req, _payload) = req.into_parts();
-                let srv_response = ServiceResponse::new(http_req, response);
+                    let mut response = HttpResponse::TooManyRequests()
+                        .insert_header(("Retry-After", retry_after.to_string()))
+                        .json(error);
+                    insert_rate_limit_headers(response.headers_mut(), &result);
 
-                Box::pin(async move { Ok(srv_response.map_into_right_body()) })
-            }
-            Ok(_) | Err(_) => {
-                // Allowed or error (fail open) - proceed with request
-                if check_result.is_err() {
-                    tracing::error!("Rate limiter error, failing open");
+                    let (http_req, _payload) = req.into_parts();
+                    return Ok(ServiceResponse::new(http_req, response).map_into_right_body());
                 }
+                Ok(result) => Some(result),
+                // Fail open; without a check result there is no budget to report
+                Err(e) => {
+                    tracing::error!(error = %e, "Rate limiter error, failing open");
+                    None
+                }
+            };
 
-                let fut = self.service.call(req);
-                Box::pin(async move {
-                    let res = fut.await?;
-                    Ok(res.map_into_left_body())
-                })
+            let mut res = service.call(req).await?;
+            if let Some(result) = result {
+                insert_rate_limit_headers(res.headers_mut(), &result);
             }
-        }
+            Ok(res.map_into_left_body())
+        })
     }
 }
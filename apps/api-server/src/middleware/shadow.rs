@@ -0,0 +1,155 @@
+//! Shadow traffic mirroring middleware.
+
+use actix_web::{
+    Error, HttpMessage,
+    body::{BodySize, BoxBody, MessageBody},
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::header,
+    web::{Bytes, BytesMut},
+};
+use futures::StreamExt;
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Instant;
+
+use apex_core::ports::MirrorRequest;
+use apex_infra::shadow::{PrimaryResponse, ShadowTraffic};
+
+/// Mirrors sampled requests to the shadow deployment once they are answered.
+/// Passes everything through when shadow traffic is not configured.
+///
+/// Only requests and responses with a known length within the configured
+/// limit are mirrored; streamed bodies pass through untouched. See
+/// [`ShadowTraffic`] for what is sanitized and recorded.
+pub struct ShadowTrafficMiddleware {
+    shadow: Option<Arc<ShadowTraffic>>,
+}
+
+impl ShadowTrafficMiddleware {
+    pub fn new(shadow: Option<Arc<ShadowTraffic>>) -> Self {
+        Self { shadow }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ShadowTrafficMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = ShadowTrafficService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ShadowTrafficService {
+            service: Rc::new(service),
+            shadow: self.shadow.clone(),
+        }))
+    }
+}
+
+pub struct ShadowTrafficService<S> {
+    service: Rc<S>,
+    shadow: Option<Arc<ShadowTraffic>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ShadowTrafficService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        let sampled = self.shadow.as_ref().filter(|shadow| {
+            request_body_len(&req).is_some_and(|len| shadow.sample(req.method().as_str(), len))
+        });
+        let Some(shadow) = sampled.cloned() else {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_boxed_body()) });
+        };
+
+        Box::pin(async move {
+            let method = req.method().to_string();
+            let path = req
+                .uri()
+                .path_and_query()
+                .map(|pq| pq.to_string())
+                .unwrap_or_else(|| req.path().to_string());
+            let headers = req
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect();
+
+            // Buffer the body so both the handler and the shadow get it
+            let mut payload = req.take_payload();
+            let mut body = BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                body.extend_from_slice(&chunk?);
+            }
+            let body = body.freeze();
+            req.set_payload(Payload::from(body.clone()));
+
+            let started = Instant::now();
+            let res = service.call(req).await?;
+            let latency = started.elapsed();
+
+            // Only buffer responses of a known, acceptable size
+            let max_body_bytes = shadow.config().max_body_bytes as u64;
+            if !matches!(res.response().body().size(), BodySize::Sized(len) if len <= max_body_bytes)
+            {
+                return Ok(res.map_into_boxed_body());
+            }
+
+            let (http_req, res) = res.into_parts();
+            let status = res.status().as_u16();
+            let (res, response_body) = res.into_parts();
+            let response_body: Bytes = actix_web::body::to_bytes(response_body)
+                .await
+                .map_err(|e| actix_web::error::ErrorInternalServerError(e.into()))?;
+
+            shadow.mirror(
+                MirrorRequest {
+                    method,
+                    path,
+                    headers,
+                    body: body.to_vec(),
+                },
+                PrimaryResponse {
+                    status,
+                    body: response_body.to_vec(),
+                    latency,
+                },
+            );
+
+            Ok(ServiceResponse::new(
+                http_req,
+                res.set_body(BoxBody::new(response_body)),
+            ))
+        })
+    }
+}
+
+/// Length of the request body, when it is known up front. Chunked requests
+/// have none and are not mirrored.
+fn request_body_len(req: &ServiceRequest) -> Option<usize> {
+    if req.headers().contains_key(header::TRANSFER_ENCODING) {
+        return None;
+    }
+    match req.headers().get(header::CONTENT_LENGTH) {
+        Some(value) => value.to_str().ok()?.parse().ok(),
+        None => Some(0),
+    }
+}
@@ -0,0 +1,171 @@
+//! Tenant resolution.
+//!
+//! A request is for a tenant when its `Host` is an organization's verified
+//! custom domain, a subdomain of `TENANT_BASE_DOMAIN` named after an
+//! organization's slug (`acme.apex.example.com`), or when it names the
+//! organization, by id or slug, in the `X-Tenant-ID` header. Such requests
+//! carry a [`Tenant`] in their extensions. The `Identity` extractor refuses
+//! tokens scoped to another organization, so a tenant's host only serves
+//! that tenant.
+
+use actix_web::{
+    Error, FromRequest, HttpMessage, HttpRequest,
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    web,
+};
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::env;
+use crate::middleware::error::AppError;
+use crate::state::AppState;
+
+/// Header naming the tenant, by organization id or slug.
+pub const TENANT_HEADER: &str = "X-Tenant-ID";
+
+/// Organization the request names through its host or header.
+#[derive(Debug, Clone)]
+pub struct Tenant {
+    pub organization_id: uuid::Uuid,
+}
+
+/// Extracts the [`Tenant`]; 404 on requests that name none. Use
+/// `Option<Tenant>` where both are fine.
+impl FromRequest for Tenant {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Tenant>()
+                .cloned()
+                .ok_or_else(|| AppError::NotFound("Not a custom domain".to_string())),
+        )
+    }
+}
+
+/// Resolves the request's host and `X-Tenant-ID` header to a [`Tenant`].
+/// Lookup failures are logged and the request is served as if on the
+/// primary domain.
+pub struct TenantHost {
+    /// Domain whose subdomains name tenants, from `TENANT_BASE_DOMAIN`.
+    base_domain: Option<Rc<str>>,
+}
+
+impl TenantHost {
+    pub fn from_env() -> Self {
+        Self {
+            base_domain: env::TENANT_BASE_DOMAIN
+                .raw()
+                .map(|domain| domain.trim_matches('.').to_ascii_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .map(Rc::from),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TenantHost
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TenantHostService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TenantHostService {
+            service: Rc::new(service),
+            base_domain: self.base_domain.clone(),
+        }))
+    }
+}
+
+pub struct TenantHostService<S> {
+    service: Rc<S>,
+    base_domain: Option<Rc<str>>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenantHostService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let base_domain = self.base_domain.clone();
+
+        Box::pin(async move {
+            let state = req.app_data::<web::Data<AppState>>().cloned();
+            let host = req.connection_info().host().to_ascii_lowercase();
+            // Without the port; bracketed IPv6 addresses are no domain anyway
+            let host = host
+                .rsplit_once(':')
+                .map_or(host.as_str(), |(host, _)| host)
+                .to_string();
+            let header = req
+                .headers()
+                .get(TENANT_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_string());
+
+            if let Some(state) = state {
+                match resolve(&state, &host, base_domain.as_deref(), header).await {
+                    Ok(Some(tenant)) => {
+                        req.extensions_mut().insert(tenant);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!(host = %host, error = %e, "Failed to resolve tenant"),
+                }
+            }
+            service.call(req).await
+        })
+    }
+}
+
+/// The tenant `host` or the tenant header names, in that order.
+async fn resolve(
+    state: &AppState,
+    host: &str,
+    base_domain: Option<&str>,
+    header: Option<String>,
+) -> Result<Option<Tenant>, apex_core::error::RepoError> {
+    if let Some(organization_id) = state.domains.resolve(host).await? {
+        return Ok(Some(Tenant { organization_id }));
+    }
+
+    let subdomain = base_domain.and_then(|base| {
+        host.strip_suffix(base)?
+            .strip_suffix('.')
+            .filter(|slug| !slug.is_empty() && !slug.contains('.'))
+    });
+    if let Some(slug) = subdomain
+        && let Some(org) = state.organizations.load().find_by_slug(slug).await?
+    {
+        return Ok(Some(Tenant {
+            organization_id: org.id,
+        }));
+    }
+
+    let Some(header) = header.filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    let organizations = state.organizations.load();
+    let org = match header.parse::<uuid::Uuid>() {
+        Ok(id) => organizations.find_by_id(id).await?,
+        Err(_) => organizations.find_by_slug(&header).await?,
+    };
+    Ok(org.map(|org| Tenant {
+        organization_id: org.id,
+    }))
+}
@@ -0,0 +1,97 @@
+//! Request bodies validated on the way in.
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use validator::{Validate, ValidationErrors, ValidationErrorsKind};
+
+use apex_shared::FieldError;
+
+use crate::middleware::error::AppError;
+
+/// A JSON body that passed the `Validate` rules of its DTO.
+///
+/// Use it in place of `web::Json`. A body that is not valid JSON for `T`
+/// fails with 400; one that parses but breaks a rule fails with 422, listing
+/// every failing field in the problem's `errors`:
+/// ```ignore
+/// async fn register(body: ValidatedJson<RegisterUserRequest>) -> AppResult<HttpResponse> {
+///     let req = body.into_inner();
+///     // ...
+/// }
+/// ```
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidatedJson<T> {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+
+        Box::pin(async move {
+            let value = json
+                .await
+                .map_err(|e| AppError::BadRequest(e.to_string()))?
+                .into_inner();
+            value
+                .validate()
+                .map_err(|errors| AppError::Validation(field_errors(&errors)))?;
+            Ok(ValidatedJson(value))
+        })
+    }
+}
+
+/// Every failed rule, sorted by field path.
+fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut fields = Vec::new();
+    collect(errors, "", &mut fields);
+    fields.sort_by(|a, b| a.field.cmp(&b.field));
+    fields
+}
+
+fn collect(errors: &ValidationErrors, prefix: &str, fields: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{prefix}.{field}")
+        };
+        match kind {
+            ValidationErrorsKind::Field(failures) => {
+                fields.extend(failures.iter().map(|failure| {
+                    FieldError {
+                        field: path.clone(),
+                        code: failure.code.to_string(),
+                        message: failure
+                            .message
+                            .as_ref()
+                            .map_or_else(|| "is invalid".to_string(), ToString::to_string),
+                    }
+                }))
+            }
+            ValidationErrorsKind::Struct(nested) => collect(nested, &path, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect(nested, &format!("{path}[{index}]"), fields);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! API versions: negotiation, routing and deprecation notices.
+//!
+//! Each version is mounted under its own scope (`/api/v1/...`,
+//! `/api/v2/...`). Unversioned `/api/...` requests get the version their
+//! `Accept` header asks for (`application/vnd.apex.v2+json`), or
+//! [`ApiVersion::DEFAULT`] without one: [`ApiVersioning`] rewrites them onto
+//! that version's scope before routing, so handlers, metrics and the rate
+//! limiter only ever see versioned paths. Responses name the version that
+//! served them in `Api-Version`.
+//!
+//! Versions listed in `API_DEPRECATIONS` (e.g. `v1=2027-06-30`) answer with
+//! `Deprecation`, `Sunset` and a `Link` to the same resource in the next
+//! version, and with 410 Gone once their sunset date has passed.
+
+use actix_web::{
+    Error, FromRequest, HttpRequest,
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::{
+        Uri,
+        header::{self, HeaderName, HeaderValue},
+    },
+};
+use chrono::{NaiveDate, Utc};
+use std::borrow::Cow;
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::env;
+use crate::middleware::error::AppError;
+
+const PREFIX: &str = "/api/";
+
+/// A version of the HTTP API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// Versions served, oldest first.
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+    /// Version of requests that do not name one.
+    pub const DEFAULT: ApiVersion = ApiVersion::V1;
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+            ApiVersion::V2 => "v2",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| version.as_str() == s)
+    }
+
+    /// The version clients of this one should move to.
+    pub fn successor(&self) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| version > self)
+    }
+}
+
+/// The version segment of an `/api/v{n}/...` path, served or not.
+fn path_version(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix(PREFIX)?;
+    let segment = rest.split('/').next()?;
+    let digits = segment.strip_prefix('v')?;
+    (!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())).then_some(segment)
+}
+
+/// `path` without its version segment: `/api/v1/auth/login` is
+/// `/api/auth/login`. Path-based settings such as `RATE_LIMIT_ROUTES` are
+/// written against these, so they apply to every version.
+pub fn unversioned(path: &str) -> Cow<'_, str> {
+    match path_version(path) {
+        Some(segment) => Cow::Owned(format!(
+            "{}{}",
+            PREFIX.trim_end_matches('/'),
+            &path[PREFIX.len() + segment.len()..]
+        )),
+        None => Cow::Borrowed(path),
+    }
+}
+
+/// `path`, an unversioned API path, in `version`'s scope.
+fn versioned(path: &str, version: ApiVersion) -> String {
+    format!(
+        "{}{}{}",
+        PREFIX,
+        version.as_str(),
+        &path[PREFIX.len() - 1..]
+    )
+}
+
+/// Version named by an `Accept` header, e.g. `application/vnd.apex.v2+json`.
+/// Fails on a version that is not served rather than answering with another.
+fn accepted_version(accept: &str) -> Result<Option<ApiVersion>, String> {
+    for media_type in accept.split(',') {
+        let essence = media_type.split(';').next().unwrap_or_default().trim();
+        let Some(name) = essence
+            .strip_prefix("application/vnd.apex.")
+            .and_then(|rest| rest.strip_suffix("+json"))
+        else {
+            continue;
+        };
+        return ApiVersion::parse(name)
+            .map(Some)
+            .ok_or_else(|| format!("API version {} is not served", name));
+    }
+    Ok(None)
+}
+
+/// Version a request is for: the one in its path, else the one its
+/// `Accept` header asks for, else [`ApiVersion::DEFAULT`].
+pub fn negotiate(req: &HttpRequest) -> Result<ApiVersion, String> {
+    if let Some(segment) = path_version(req.path()) {
+        return ApiVersion::parse(segment)
+            .ok_or_else(|| format!("API version {} is not served", segment));
+    }
+    let accept = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    Ok(accepted_version(accept)?.unwrap_or(ApiVersion::DEFAULT))
+}
+
+/// Extractor for the version serving a request, for handlers whose
+/// response differs between versions.
+impl FromRequest for ApiVersion {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(negotiate(req).map_err(|e| AppError::NotAcceptable(e).into()))
+    }
+}
+
+/// A version on its way out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deprecation {
+    pub version: ApiVersion,
+    /// Day the version stops being served, if decided.
+    pub sunset: Option<NaiveDate>,
+}
+
+impl Deprecation {
+    fn is_sunset(&self, today: NaiveDate) -> bool {
+        self.sunset.is_some_and(|sunset| today >= sunset)
+    }
+
+    /// The `Sunset` header value (an HTTP date).
+    fn sunset_header(&self) -> Option<String> {
+        self.sunset.map(|sunset| {
+            sunset
+                .and_time(chrono::NaiveTime::MIN)
+                .and_utc()
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string()
+        })
+    }
+}
+
+/// Deprecated versions, from `API_DEPRECATIONS`.
+#[derive(Debug, Default)]
+pub struct VersionPolicy {
+    deprecations: Vec<Deprecation>,
+}
+
+impl VersionPolicy {
+    pub fn new(deprecations: Vec<Deprecation>) -> Self {
+        Self { deprecations }
+    }
+
+    /// Load deprecations from `API_DEPRECATIONS`, e.g. `v1=2027-06-30` or
+    /// just `v1` while the sunset date is open. Malformed entries are
+    /// skipped with a warning.
+    pub fn from_env() -> Self {
+        let deprecations = parse_deprecations(&env::API_DEPRECATIONS.string())
+            .into_iter()
+            .filter_map(|parsed| {
+                parsed
+                    .inspect_err(|entry| {
+                        tracing::warn!(entry = %entry, "Ignoring malformed API deprecation")
+                    })
+                    .ok()
+            })
+            .collect();
+        Self::new(deprecations)
+    }
+
+    pub fn deprecation(&self, version: ApiVersion) -> Option<&Deprecation> {
+        self.deprecations
+            .iter()
+            .find(|deprecation| deprecation.version == version)
+    }
+}
+
+/// Parse `API_DEPRECATIONS` entries, returning malformed ones as errors.
+fn parse_deprecations(spec: &str) -> Vec<Result<Deprecation, String>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (version, sunset) = match entry.split_once('=') {
+                Some((version, sunset)) => (version.trim(), Some(sunset.trim())),
+                None => (entry, None),
+            };
+            let version = ApiVersion::parse(version).ok_or_else(|| entry.to_string())?;
+            let sunset = sunset
+                .map(|sunset| NaiveDate::parse_from_str(sunset, "%Y-%m-%d"))
+                .transpose()
+                .map_err(|_| entry.to_string())?;
+            Ok(Deprecation { version, sunset })
+        })
+        .collect()
+}
+
+/// Routes `/api/...` requests to their version's scope and adds the
+/// version and deprecation headers.
+pub struct ApiVersioning {
+    policy: Arc<VersionPolicy>,
+}
+
+impl ApiVersioning {
+    pub fn new(policy: Arc<VersionPolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiVersioning
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ApiVersioningService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiVersioningService {
+            service,
+            policy: self.policy.clone(),
+        }))
+    }
+}
+
+pub struct ApiVersioningService<S> {
+    service: S,
+    policy: Arc<VersionPolicy>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiVersioningService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        if !req.path().starts_with(PREFIX) {
+            return Box::pin(self.service.call(req));
+        }
+
+        let version = match negotiate(req.request()) {
+            Ok(version) => version,
+            // An unknown version in the path is simply not routed
+            Err(_) if path_version(req.path()).is_some() => {
+                return Box::pin(self.service.call(req));
+            }
+            Err(e) => return Box::pin(ready(Err(AppError::NotAcceptable(e).into()))),
+        };
+
+        if path_version(req.path()).is_none() {
+            let path = versioned(req.path(), version);
+            let path_and_query = match req.query_string() {
+                "" => path,
+                query => format!("{}?{}", path, query),
+            };
+            let mut parts = req.head().uri.clone().into_parts();
+            parts.path_and_query = path_and_query.parse().ok();
+            if let Ok(uri) = Uri::from_parts(parts) {
+                req.match_info_mut().get_mut().update(&uri);
+                req.head_mut().uri = uri;
+            }
+        }
+
+        let deprecation = self.policy.deprecation(version).cloned();
+        if let Some(deprecation) = &deprecation
+            && deprecation.is_sunset(Utc::now().date_naive())
+        {
+            let detail = match version.successor() {
+                Some(successor) => format!(
+                    "API {} is no longer served, use {}",
+                    version.as_str(),
+                    successor.as_str()
+                ),
+                None => format!("API {} is no longer served", version.as_str()),
+            };
+            return Box::pin(ready(Err(AppError::Gone(detail).into())));
+        }
+
+        let successor_link = version.successor().map(|successor| {
+            format!(
+                "<{}>; rel=\"successor-version\"",
+                versioned(&unversioned(req.path()), successor)
+            )
+        });
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let headers = res.headers_mut();
+            headers.insert(
+                HeaderName::from_static("api-version"),
+                HeaderValue::from_static(version.as_str()),
+            );
+            if let Some(deprecation) = deprecation {
+                headers.insert(
+                    HeaderName::from_static("deprecation"),
+                    HeaderValue::from_static("true"),
+                );
+                let sunset = deprecation.sunset_header();
+                for (name, value) in [("sunset", sunset), ("link", successor_link)] {
+                    if let Some(value) = value.and_then(|v| HeaderValue::from_str(&v).ok()) {
+                        headers.insert(HeaderName::from_static(name), value);
+                    }
+                }
+            }
+            Ok(res)
+        })
+    }
+}
@@ -5,6 +5,9 @@
 
 use std::sync::Arc;
 use tokio::sync::mpsc;
+
+use apex_core::pii::{FieldClass, classify, mask};
+use apex_core::ports::{WebhookRequest, WebhookSender};
 use tracing::{Event, Subscriber};
 use tracing_subscriber::{Layer, layer::Context};
 
@@ -15,6 +18,8 @@ pub struct AlertMessage {
     pub message: String,
     pub target: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// The event's other fields, internal ones left out and sensitive ones
+    /// masked.
     pub fields: Vec<(String, String)>,
 }
 
@@ -54,13 +59,18 @@ pub struct ConsoleAlertSender;
 #[async_trait::async_trait]
 impl AlertSender for ConsoleAlertSender {
     async fn send(&self, alert: AlertMessage) -> Result<(), AlertError> {
+        let fields: String = alert
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}\n", name, value))
+            .collect();
         eprintln!(
             "\n🚨 CRITICAL ALERT 🚨\n\
              Level: {}\n\
              Target: {}\n\
              Message: {}\n\
-             Time: {}\n",
-            alert.level, alert.target, alert.message, alert.timestamp
+             Time: {}\n{}",
+            alert.level, alert.target, alert.message, alert.timestamp, fields
         );
         Ok(())
     }
@@ -69,35 +79,43 @@ impl AlertSender for ConsoleAlertSender {
 /// Webhook alert sender - sends alerts to a webhook URL (Slack, Discord, etc.).
 pub struct WebhookAlertSender {
     url: String,
-    client: reqwest::Client,
+    webhooks: Arc<dyn WebhookSender>,
 }
 
 impl WebhookAlertSender {
-    pub fn new(url: String) -> Self {
-        Self {
-            url,
-            client: reqwest::Client::new(),
-        }
+    pub fn new(url: String, webhooks: Arc<dyn WebhookSender>) -> Self {
+        Self { url, webhooks }
     }
 }
 
 #[async_trait::async_trait]
 impl AlertSender for WebhookAlertSender {
     async fn send(&self, alert: AlertMessage) -> Result<(), AlertError> {
+        let fields: String = alert
+            .fields
+            .iter()
+            .map(|(name, value)| format!("\n*{}:* {}", name, value))
+            .collect();
         let payload = serde_json::json!({
             "text": format!(
-                "🚨 *CRITICAL ERROR*\n*Target:* {}\n*Message:* {}\n*Time:* {}",
-                alert.target, alert.message, alert.timestamp
+                "🚨 *CRITICAL ERROR*\n*Target:* {}\n*Message:* {}\n*Time:* {}{}",
+                alert.target, alert.message, alert.timestamp, fields
             )
         });
 
-        self.client
-            .post(&self.url)
-            .json(&payload)
-            .send()
+        let response = self
+            .webhooks
+            .send(&WebhookRequest::new(self.url.clone(), payload))
             .await
             .map_err(|e| AlertError::SendError(e.to_string()))?;
 
+        if !response.is_success() {
+            return Err(AlertError::SendError(format!(
+                "Webhook responded with status {}",
+                response.status
+            )));
+        }
+
         Ok(())
     }
 }
@@ -105,14 +123,37 @@ impl AlertSender for WebhookAlertSender {
 /// Tracing layer that sends alerts on ERROR-level events.
 pub struct AlertLayer {
     sender: mpsc::Sender<AlertMessage>,
+    min_level: tracing::Level,
+}
+
+/// Receiving half of an [`AlertLayer`].
+///
+/// Alerts raised before [`AlertDispatcher::start`] are buffered (up to the
+/// channel capacity), so the layer can be installed before the services it
+/// delivers through exist.
+pub struct AlertDispatcher {
+    receiver: mpsc::Receiver<AlertMessage>,
 }
 
 impl AlertLayer {
-    /// Create a new alert layer with the given sender.
-    pub fn new(alert_sender: Arc<dyn AlertSender>) -> Self {
-        let (tx, mut rx) = mpsc::channel::<AlertMessage>(100);
+    /// Create a new alert layer and the dispatcher that delivers its alerts.
+    pub fn new(config: AlertConfig) -> (Self, AlertDispatcher) {
+        let (tx, rx) = mpsc::channel::<AlertMessage>(config.buffer_size);
+
+        let layer = Self {
+            sender: tx,
+            min_level: config.min_level,
+        };
+
+        (layer, AlertDispatcher { receiver: rx })
+    }
+}
+
+impl AlertDispatcher {
+    /// Spawn the background task that sends alerts through `alert_sender`.
+    pub fn start(self, alert_sender: Arc<dyn AlertSender>) {
+        let mut rx = self.receiver;
 
-        // Spawn background task to process alerts
         tokio::spawn(async move {
             while let Some(alert) = rx.recv().await {
                 if let Err(e) = alert_sender.send(alert).await {
@@ -120,22 +161,11 @@ impl AlertLayer {
                 }
             }
         });
-
-        Self { sender: tx }
-    }
-
-    /// Create an alert layer that logs to console.
-    pub fn console() -> Self {
-        Self::new(Arc::new(ConsoleAlertSender))
-    }
-
-    /// Create an alert layer that sends to a webhook.
-    pub fn webhook(url: String) -> Self {
-        Self::new(Arc::new(WebhookAlertSender::new(url)))
     }
 }
 
-/// Visitor to extract fields from events.
+/// Visitor to extract fields from events. Alerts leave the system, so
+/// internal fields are left out and sensitive ones masked.
 struct FieldVisitor {
     message: String,
     fields: Vec<(String, String)>,
@@ -148,6 +178,15 @@ impl FieldVisitor {
             fields: Vec::new(),
         }
     }
+
+    fn push(&mut self, field: &tracing::field::Field, value: String) {
+        let value = match classify(field.name()) {
+            FieldClass::Public => value,
+            FieldClass::Internal => return,
+            FieldClass::Sensitive => mask(value.trim_matches('"')),
+        };
+        self.fields.push((field.name().to_string(), value));
+    }
 }
 
 impl tracing::field::Visit for FieldVisitor {
@@ -155,8 +194,7 @@ impl tracing::field::Visit for FieldVisitor {
         if field.name() == "message" {
             self.message = format!("{:?}", value);
         } else {
-            self.fields
-                .push((field.name().to_string(), format!("{:?}", value)));
+            self.push(field, format!("{:?}", value));
         }
     }
 
@@ -164,8 +202,7 @@ impl tracing::field::Visit for FieldVisitor {
         if field.name() == "message" {
             self.message = value.to_string();
         } else {
-            self.fields
-                .push((field.name().to_string(), value.to_string()));
+            self.push(field, value.to_string());
         }
     }
 }
@@ -175,8 +212,8 @@ where
     S: Subscriber,
 {
     fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
-        // Only alert on ERROR level
-        if *event.metadata().level() != tracing::Level::ERROR {
+        // Only alert at or above the configured severity
+        if *event.metadata().level() > self.min_level {
             return;
         }
 
@@ -0,0 +1,54 @@
+//! Alerts on dependencies degrading and recovering.
+
+use std::sync::Arc;
+
+use apex_infra::health::{DependencyEvent, DependencyWatchdog};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::alert::{AlertMessage, AlertSender};
+
+/// Send an alert for each degradation and recovery `watchdog` reports, so
+/// on-call hears when an outage ends as well as when it starts.
+pub fn alert_on_dependency_events(
+    watchdog: &DependencyWatchdog,
+    sender: Arc<dyn AlertSender>,
+) -> tokio::task::JoinHandle<()> {
+    let mut events = watchdog.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "Missed dependency events");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let (level, dependency, message) = match event {
+                DependencyEvent::Degraded { dependency, error } => (
+                    "ALERT",
+                    dependency.clone(),
+                    format!("{} is degraded: {}", dependency, error),
+                ),
+                DependencyEvent::Recovered {
+                    dependency,
+                    down_for,
+                } => (
+                    "RECOVERED",
+                    dependency.clone(),
+                    format!("{} recovered after {}s", dependency, down_for.as_secs()),
+                ),
+            };
+            let alert = AlertMessage {
+                level: level.to_string(),
+                message,
+                target: format!("dependency::{}", dependency),
+                timestamp: chrono::Utc::now(),
+                fields: vec![],
+            };
+            if let Err(e) = sender.send(alert).await {
+                tracing::error!(%dependency, error = %e, "Failed to send dependency alert");
+            }
+        }
+    })
+}
@@ -0,0 +1,73 @@
+//! Tokio runtime metrics and task dumps.
+//!
+//! actix-web runs each HTTP worker on its own single-threaded runtime, and
+//! the jobs, pub/sub and flushers spawned at startup on the main one. A
+//! blocking call stalls every task on its runtime: it shows up here as busy
+//! time climbing while tasks pile up in the queue.
+//!
+//! Poll counts, local queue depths and task dumps need tokio's unstable
+//! APIs: build with `RUSTFLAGS="--cfg tokio_unstable"`, plus the `taskdump`
+//! feature for dumps (Linux on x86_64 or aarch64 only).
+
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use apex_shared::dto::{TokioRuntimeResponse, TokioWorkerResponse};
+
+/// Metrics of the runtime behind `handle`.
+pub fn runtime_metrics(name: &str, handle: &Handle) -> TokioRuntimeResponse {
+    let metrics = handle.metrics();
+    let workers = (0..metrics.num_workers())
+        .map(|worker| TokioWorkerResponse {
+            worker,
+            busy_ms: metrics.worker_total_busy_duration(worker).as_secs_f64() * 1000.0,
+            park_count: metrics.worker_park_count(worker),
+            #[cfg(tokio_unstable)]
+            poll_count: Some(metrics.worker_poll_count(worker)),
+            #[cfg(not(tokio_unstable))]
+            poll_count: None,
+            #[cfg(tokio_unstable)]
+            mean_poll_us: Some(metrics.worker_mean_poll_time(worker).as_secs_f64() * 1e6),
+            #[cfg(not(tokio_unstable))]
+            mean_poll_us: None,
+            #[cfg(tokio_unstable)]
+            local_queue_depth: Some(metrics.worker_local_queue_depth(worker)),
+            #[cfg(not(tokio_unstable))]
+            local_queue_depth: None,
+        })
+        .collect();
+
+    TokioRuntimeResponse {
+        name: name.to_string(),
+        flavor: match handle.runtime_flavor() {
+            RuntimeFlavor::CurrentThread => "current_thread",
+            RuntimeFlavor::MultiThread => "multi_thread",
+            _ => "other",
+        }
+        .to_string(),
+        alive_tasks: metrics.num_alive_tasks(),
+        global_queue_depth: metrics.global_queue_depth(),
+        #[cfg(tokio_unstable)]
+        blocking_threads: Some(metrics.num_blocking_threads()),
+        #[cfg(not(tokio_unstable))]
+        blocking_threads: None,
+        workers,
+    }
+}
+
+/// Stack traces of every task on the runtime behind `handle`, or `None`
+/// when this build cannot take them.
+#[cfg(all(tokio_unstable, feature = "taskdump"))]
+pub async fn task_dump(handle: &Handle) -> Option<String> {
+    let dump = handle.dump().await;
+    let traces: Vec<String> = dump
+        .tasks()
+        .iter()
+        .map(|task| format!("task {}:\n{}", task.id(), task.trace()))
+        .collect();
+    Some(traces.join("\n\n"))
+}
+
+#[cfg(not(all(tokio_unstable, feature = "taskdump")))]
+pub async fn task_dump(_handle: &Handle) -> Option<String> {
+    None
+}
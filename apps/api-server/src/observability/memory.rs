@@ -0,0 +1,77 @@
+//! Allocator stats and heap profiles, with the `jemalloc` feature.
+//!
+//! Heap profiles are only collected when jemalloc was started with
+//! profiling on, e.g. `_RJEM_MALLOC_CONF=prof:true,lg_prof_sample:19`.
+//! Dumps are written for `jeprof`; sampling costs a little CPU, so leave it
+//! off unless memory growth needs explaining. For a full allocation trace,
+//! run a build without the feature under heaptrack instead.
+
+use std::ffi::CString;
+use std::path::PathBuf;
+
+use tikv_jemalloc_ctl::{epoch, profiling, raw, stats};
+
+use apex_shared::dto::MemoryStatsResponse;
+
+use crate::env;
+
+/// Reads jemalloc's counters and writes heap profiles.
+pub struct MemoryProfiler {
+    profile_dir: PathBuf,
+}
+
+impl MemoryProfiler {
+    pub fn new(profile_dir: PathBuf) -> Self {
+        Self { profile_dir }
+    }
+
+    /// Profiles go to `HEAP_PROFILE_DIR`, the temp directory by default.
+    pub fn from_env() -> Self {
+        Self::new(
+            env::HEAP_PROFILE_DIR
+                .raw()
+                .map(PathBuf::from)
+                .unwrap_or_else(std::env::temp_dir),
+        )
+    }
+
+    /// Current allocator counters. jemalloc caches them, so they are
+    /// refreshed first.
+    pub fn stats(&self) -> Result<MemoryStatsResponse, tikv_jemalloc_ctl::Error> {
+        epoch::advance()?;
+        Ok(MemoryStatsResponse {
+            allocator: "jemalloc".to_string(),
+            allocated_bytes: stats::allocated::read()?,
+            active_bytes: stats::active::read()?,
+            resident_bytes: stats::resident::read()?,
+            mapped_bytes: stats::mapped::read()?,
+            retained_bytes: stats::retained::read()?,
+            profiling: self.profiling(),
+        })
+    }
+
+    /// Whether jemalloc is sampling allocations for heap profiles.
+    pub fn profiling(&self) -> bool {
+        profiling::prof::read().unwrap_or(false)
+    }
+
+    /// Write a heap profile, returning where it went.
+    pub fn dump(&self) -> Result<PathBuf, String> {
+        if !self.profiling() {
+            return Err(
+                "Heap profiling is off; start with _RJEM_MALLOC_CONF=prof:true".to_string(),
+            );
+        }
+
+        let path = self.profile_dir.join(format!(
+            "heap-{}-{}.prof",
+            std::process::id(),
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f")
+        ));
+        let c_path = CString::new(path.to_string_lossy().as_bytes()).map_err(|e| e.to_string())?;
+        // SAFETY: `prof.dump` takes a NUL-terminated path, which `c_path`
+        // is and outlives the call.
+        unsafe { raw::write(b"prof.dump\0", c_path.as_ptr()) }.map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
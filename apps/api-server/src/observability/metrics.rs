@@ -0,0 +1,165 @@
+//! In-process request metrics feeding the alert rules.
+//!
+//! Counts requests, server errors and latencies over a rolling window that
+//! the rules engine drains on every evaluation. The same middleware feeds
+//! the labelled [`RequestSeries`] scraped at `/metrics`.
+
+use actix_web::{
+    Error,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+};
+use std::future::{Future, Ready, ready};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::series::RequestSeries;
+
+/// Most latency samples kept per window; later requests are still counted
+/// but their latencies are sampled.
+const MAX_SAMPLES: usize = 10_000;
+
+/// Request counts and latencies since the last [`RequestMetrics::take_window`].
+#[derive(Default)]
+pub struct RequestMetrics {
+    window: Mutex<Window>,
+}
+
+#[derive(Default)]
+struct Window {
+    requests: u64,
+    errors: u64,
+    latencies: Vec<Duration>,
+}
+
+/// Summary of one metrics window.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WindowSnapshot {
+    pub requests: u64,
+    /// Requests answered with a 5xx status.
+    pub errors: u64,
+    pub p99: Option<Duration>,
+}
+
+impl WindowSnapshot {
+    /// Share of requests that were server errors, in percent.
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.errors as f64 * 100.0 / self.requests as f64
+    }
+}
+
+impl RequestMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finished request.
+    pub fn record(&self, server_error: bool, elapsed: Duration) {
+        let mut window = self.window.lock().unwrap();
+        window.requests += 1;
+        if server_error {
+            window.errors += 1;
+        }
+        if window.latencies.len() < MAX_SAMPLES {
+            window.latencies.push(elapsed);
+        } else {
+            // Replace a pseudo-random sample so late requests still count
+            let slot = (window.requests as usize).wrapping_mul(2_654_435_761) % MAX_SAMPLES;
+            window.latencies[slot] = elapsed;
+        }
+    }
+
+    /// Summarize the current window and start a new one.
+    pub fn take_window(&self) -> WindowSnapshot {
+        let mut window = std::mem::take(&mut *self.window.lock().unwrap());
+        window.latencies.sort_unstable();
+        let p99 = (!window.latencies.is_empty()).then(|| {
+            let index = (window.latencies.len() * 99).div_ceil(100) - 1;
+            window.latencies[index]
+        });
+
+        WindowSnapshot {
+            requests: window.requests,
+            errors: window.errors,
+            p99,
+        }
+    }
+}
+
+/// Middleware recording every request's status and latency into [`RequestMetrics`]
+/// and [`RequestSeries`].
+pub struct RequestMetricsMiddleware {
+    metrics: Arc<RequestMetrics>,
+    series: Arc<RequestSeries>,
+}
+
+impl RequestMetricsMiddleware {
+    pub fn new(metrics: Arc<RequestMetrics>, series: Arc<RequestSeries>) -> Self {
+        Self { metrics, series }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetricsMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestMetricsService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsService {
+            service,
+            metrics: self.metrics.clone(),
+            series: self.series.clone(),
+        }))
+    }
+}
+
+pub struct RequestMetricsService<S> {
+    service: S,
+    metrics: Arc<RequestMetrics>,
+    series: Arc<RequestSeries>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let metrics = self.metrics.clone();
+        let series = self.series.clone();
+        // The pattern, not the path, so IDs in URLs do not become series
+        let route = req.match_pattern();
+        let method = req.method().clone();
+        let started = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await;
+            let status = match &res {
+                Ok(res) => res.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+            let elapsed = started.elapsed();
+            metrics.record(status.is_server_error(), elapsed);
+            series.record(method.as_str(), route.as_deref(), status.as_u16(), elapsed);
+            res
+        })
+    }
+}
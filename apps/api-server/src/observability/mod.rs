@@ -1,7 +1,28 @@
-//! Observability module - tracing, request IDs, and alerting.
+//! Observability module - tracing, request IDs, metrics, alerting, and
+//! masking personal data in logs.
 
 mod alert;
+mod dependencies;
+#[cfg(feature = "auth")]
+pub mod executor;
+#[cfg(feature = "jemalloc")]
+mod memory;
+mod metrics;
+mod pii;
 mod request_id;
+mod rules;
+mod runtime;
+mod series;
 
-pub use alert::AlertLayer;
+pub use alert::{
+    AlertConfig, AlertDispatcher, AlertLayer, AlertSender, ConsoleAlertSender, WebhookAlertSender,
+};
+pub use dependencies::alert_on_dependency_events;
+#[cfg(feature = "jemalloc")]
+pub use memory::MemoryProfiler;
+pub use metrics::{RequestMetrics, RequestMetricsMiddleware};
+pub use pii::{PiiFields, PiiJson, PiiJsonFields};
 pub use request_id::RequestIdMiddleware;
+pub use rules::{AlertRulesConfig, AlertRulesEngine};
+pub use runtime::RuntimeReport;
+pub use series::RequestSeries;
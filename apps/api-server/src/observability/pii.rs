@@ -0,0 +1,189 @@
+//! Log formatting that masks sensitive fields.
+//!
+//! The stock formatters write field values as recorded, so these replace
+//! them for both log formats: fields [`classify`] calls sensitive are
+//! masked by name, in events and spans alike.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+use apex_core::pii::{FieldClass, classify, mask};
+
+/// Debug output of a value, without the quotes strings get.
+fn debug_string(value: &dyn fmt::Debug) -> String {
+    let debug = format!("{:?}", value);
+    match debug.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(unquoted) => unquoted.to_string(),
+        None => debug,
+    }
+}
+
+/// `name=value` fields for the text format.
+pub struct PiiFields;
+
+impl<'writer> FormatFields<'writer> for PiiFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = TextVisitor {
+            writer,
+            result: Ok(()),
+            empty: true,
+        };
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+struct TextVisitor<'writer> {
+    writer: Writer<'writer>,
+    result: fmt::Result,
+    empty: bool,
+}
+
+impl Visit for TextVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.result.is_err() {
+            return;
+        }
+        let separator = if self.empty { "" } else { " " };
+        self.empty = false;
+        self.result = match (field.name(), classify(field.name())) {
+            ("message", _) => write!(self.writer, "{}{:?}", separator, value),
+            (name, FieldClass::Sensitive) => write!(
+                self.writer,
+                "{}{}={}",
+                separator,
+                name,
+                mask(&debug_string(value))
+            ),
+            (name, _) => write!(self.writer, "{}{}={:?}", separator, name, value),
+        };
+    }
+}
+
+/// Fields as a JSON object, sensitive values masked.
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl JsonVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let value = match (classify(field.name()), value) {
+            (FieldClass::Sensitive, Value::String(s)) => Value::String(mask(&s)),
+            (FieldClass::Sensitive, _) => Value::String("***".to_string()),
+            (_, value) => value,
+        };
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::String(debug_string(value)));
+    }
+}
+
+/// Span fields for the JSON format, kept as a JSON object so [`PiiJson`]
+/// can nest them.
+pub struct PiiJsonFields;
+
+impl<'writer> FormatFields<'writer> for PiiJsonFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        write!(writer, "{}", Value::Object(visitor.0))
+    }
+
+    fn add_fields(
+        &self,
+        current: &mut FormattedFields<Self>,
+        fields: &tracing::span::Record<'_>,
+    ) -> fmt::Result {
+        let mut visitor = JsonVisitor(serde_json::from_str(&current.fields).unwrap_or_default());
+        fields.record(&mut visitor);
+        current.fields = Value::Object(visitor.0).to_string();
+        Ok(())
+    }
+}
+
+/// One JSON object per event: timestamp, level, fields, target, and the
+/// current span and span list with their fields, like the stock JSON format.
+pub struct PiiJson;
+
+impl<S, N> FormatEvent<S, N> for PiiJson
+where
+    S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let mut fields = JsonVisitor::default();
+        event.record(&mut fields);
+
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".to_string(),
+            Value::from(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true)),
+        );
+        line.insert("level".to_string(), Value::from(meta.level().to_string()));
+        line.insert("fields".to_string(), Value::Object(fields.0));
+        line.insert("target".to_string(), Value::from(meta.target()));
+
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope
+                .from_root()
+                .map(|span| {
+                    let mut object: Map<String, Value> = span
+                        .extensions()
+                        .get::<FormattedFields<N>>()
+                        .and_then(|fields| serde_json::from_str(&fields.fields).ok())
+                        .unwrap_or_default();
+                    object.insert("name".to_string(), Value::from(span.name()));
+                    Value::Object(object)
+                })
+                .collect();
+            if let Some(current) = spans.last() {
+                line.insert("span".to_string(), current.clone());
+            }
+            line.insert("spans".to_string(), Value::Array(spans));
+        }
+
+        let mut out = Value::Object(line).to_string();
+        out.push('\n');
+        writer.write_str(&out)
+    }
+}
@@ -1,20 +1,38 @@
-//! Request ID middleware - generates unique IDs for each request.
+//! Request ID middleware - generates unique IDs for each request and runs
+//! the request inside its [`RequestContext`].
 
+#[cfg(feature = "auth")]
+use actix_web::HttpMessage;
 use actix_web::{
-    Error, HttpMessage,
+    Error,
     dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
     http::header::{HeaderName, HeaderValue},
 };
 use std::future::{Future, Ready, ready};
 use std::pin::Pin;
+use std::time::Duration;
 use uuid::Uuid;
 
+use apex_core::RequestContext;
+
 /// Header name for request ID.
 pub static REQUEST_ID_HEADER: &str = "X-Request-ID";
 
 /// Middleware that generates a unique request ID for each request.
 /// The ID is added to response headers and available in tracing spans.
-pub struct RequestIdMiddleware;
+///
+/// The request runs with a [`RequestContext`] carrying the ID, the preferred
+/// locale and a deadline `request_timeout` away; the `Identity` extractor
+/// adds the caller once authenticated.
+pub struct RequestIdMiddleware {
+    request_timeout: Duration,
+}
+
+impl RequestIdMiddleware {
+    pub fn new(request_timeout: Duration) -> Self {
+        Self { request_timeout }
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
 where
@@ -29,12 +47,16 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(RequestIdService { service }))
+        ready(Ok(RequestIdService {
+            service,
+            request_timeout: self.request_timeout,
+        }))
     }
 }
 
 pub struct RequestIdService<S> {
     service: S,
+    request_timeout: Duration,
 }
 
 impl<S, B> Service<ServiceRequest> for RequestIdService<S>
@@ -58,9 +80,6 @@ where
             .map(String::from)
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
-        // Store request ID in extensions for handlers to access
-        req.extensions_mut().insert(RequestId(request_id.clone()));
-
         // Add request ID to tracing span
         let span = tracing::info_span!(
             "request",
@@ -70,11 +89,26 @@ where
 
         tracing::debug!("Processing request with ID: {}", request_id);
 
-        let fut = self.service.call(req);
+        let mut context =
+            RequestContext::new(request_id.clone()).with_timeout(self.request_timeout);
+        if let Some(locale) = preferred_locale(&req) {
+            context = context.with_locale(locale);
+        }
+        // The tenant the request names scopes anonymous reads; `Identity`
+        // replaces it with the token's organization, if any
+        #[cfg(feature = "auth")]
+        {
+            context.tenant_id = req
+                .extensions()
+                .get::<crate::middleware::tenant::Tenant>()
+                .map(|tenant| tenant.organization_id);
+        }
+
+        let fut = apex_infra::context::sync_scope(context.clone(), || self.service.call(req));
         let request_id_header = request_id.clone();
 
         Box::pin(async move {
-            let mut res = fut.await?;
+            let mut res = apex_infra::context::scope(context, fut).await?;
 
             // Add request ID to response headers
             res.headers_mut().insert(
@@ -88,28 +122,10 @@ where
     }
 }
 
-/// Request ID extractor for handlers.
-#[derive(Debug, Clone)]
-pub struct RequestId(pub String);
-
-impl RequestId {
-    pub fn as_str(&self) -> &str {
-        &self.0
-    }
-}
-
-/// Extractor to get request ID in handlers.
-impl actix_web::FromRequest for RequestId {
-    type Error = actix_web::Error;
-    type Future = Ready<Result<Self, Self::Error>>;
-
-    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
-        let request_id = req
-            .extensions()
-            .get::<RequestId>()
-            .cloned()
-            .unwrap_or_else(|| RequestId(Uuid::new_v4().to_string()));
-
-        ready(Ok(request_id))
-    }
+/// First language of the `Accept-Language` header, e.g. `de-CH` for
+/// `de-CH, de;q=0.9, en;q=0.8`.
+fn preferred_locale(req: &ServiceRequest) -> Option<String> {
+    let header = req.headers().get("accept-language")?.to_str().ok()?;
+    let first = header.split(',').next()?.split(';').next()?.trim();
+    (!first.is_empty() && first != "*").then(|| first.to_string())
 }
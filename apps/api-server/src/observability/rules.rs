@@ -0,0 +1,233 @@
+//! Metric alert rules.
+//!
+//! A small rules engine that checks request error rate, p99 latency and job
+//! queue depth against thresholds on a fixed interval and notifies through
+//! the configured [`AlertSender`]. Each rule has a cooldown so a sustained
+//! breach alerts once per cooldown rather than on every evaluation.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use apex_core::ports::JobQueue;
+
+use super::alert::{AlertMessage, AlertSender};
+use super::metrics::{RequestMetrics, WindowSnapshot};
+use crate::env;
+
+/// Metric a rule watches and the threshold it must stay under.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertCondition {
+    /// Percentage of requests answered with a 5xx, above the threshold.
+    ErrorRate(f64),
+    /// p99 request latency above the threshold.
+    P99Latency(Duration),
+    /// Pending jobs above the threshold.
+    QueueDepth(usize),
+}
+
+/// A named threshold.
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub name: String,
+    pub condition: AlertCondition,
+}
+
+impl AlertRule {
+    pub fn new(name: impl Into<String>, condition: AlertCondition) -> Self {
+        Self {
+            name: name.into(),
+            condition,
+        }
+    }
+}
+
+/// Configuration for the rules engine.
+#[derive(Debug, Clone)]
+pub struct AlertRulesConfig {
+    pub rules: Vec<AlertRule>,
+    /// How often rules are evaluated; also the metrics window length.
+    pub interval: Duration,
+    /// Minimum time between two alerts from the same rule.
+    pub cooldown: Duration,
+    /// Windows with fewer requests don't trigger the error rate rule, so a
+    /// single failure on an idle server doesn't read as 100% errors.
+    pub min_requests: u64,
+}
+
+impl Default for AlertRulesConfig {
+    fn default() -> Self {
+        Self {
+            rules: vec![],
+            interval: Duration::from_secs(60),
+            cooldown: Duration::from_secs(900),
+            min_requests: 20,
+        }
+    }
+}
+
+impl AlertRulesConfig {
+    /// Load rules from environment variables. Only thresholds that are set
+    /// become rules:
+    /// - `ALERT_ERROR_RATE_PERCENT`
+    /// - `ALERT_P99_MS`
+    /// - `ALERT_QUEUE_DEPTH`
+    pub fn from_env() -> Self {
+        let mut rules = vec![];
+
+        if let Some(percent) = env::ALERT_ERROR_RATE_PERCENT.parse::<f64>() {
+            rules.push(AlertRule::new(
+                "error_rate",
+                AlertCondition::ErrorRate(percent),
+            ));
+        }
+        if let Some(ms) = env::ALERT_P99_MS.parse::<u64>() {
+            rules.push(AlertRule::new(
+                "p99_latency",
+                AlertCondition::P99Latency(Duration::from_millis(ms)),
+            ));
+        }
+        if let Some(depth) = env::ALERT_QUEUE_DEPTH.parse::<usize>() {
+            rules.push(AlertRule::new(
+                "queue_depth",
+                AlertCondition::QueueDepth(depth),
+            ));
+        }
+
+        Self {
+            rules,
+            interval: env::ALERT_RULES_INTERVAL_SECS.secs(),
+            cooldown: env::ALERT_COOLDOWN_SECS.secs(),
+            min_requests: env::ALERT_MIN_REQUESTS.get(),
+        }
+    }
+}
+
+/// Evaluates [`AlertRule`]s against request metrics and the job queue.
+pub struct AlertRulesEngine<Q> {
+    config: AlertRulesConfig,
+    metrics: Arc<RequestMetrics>,
+    queue: Arc<Q>,
+    sender: Arc<dyn AlertSender>,
+    last_fired: HashMap<String, Instant>,
+}
+
+impl<Q: JobQueue + 'static> AlertRulesEngine<Q> {
+    pub fn new(
+        config: AlertRulesConfig,
+        metrics: Arc<RequestMetrics>,
+        queue: Arc<Q>,
+        sender: Arc<dyn AlertSender>,
+    ) -> Self {
+        Self {
+            config,
+            metrics,
+            queue,
+            sender,
+            last_fired: HashMap::new(),
+        }
+    }
+
+    /// Spawn the evaluation loop. Does nothing without rules.
+    pub fn start(mut self) -> Option<tokio::task::JoinHandle<()>> {
+        if self.config.rules.is_empty() {
+            return None;
+        }
+
+        tracing::info!(
+            rules = self.config.rules.len(),
+            interval_secs = self.config.interval.as_secs(),
+            "Alert rules started"
+        );
+
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.interval);
+            // The first tick fires immediately; skip it so the first window is full
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                self.evaluate().await;
+            }
+        }))
+    }
+
+    /// Check every rule once, alerting on breaches outside their cooldown.
+    async fn evaluate(&mut self) {
+        let window = self.metrics.take_window();
+        let depth = if self.watches_queue() {
+            match self.queue.stats().await {
+                Ok(stats) => Some(stats.pending),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to read job queue stats for alert rules");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let now = Instant::now();
+        for rule in self.config.rules.clone() {
+            let Some(message) = self.breach(&rule, &window, depth) else {
+                continue;
+            };
+            if let Some(last) = self.last_fired.get(&rule.name)
+                && now.duration_since(*last) < self.config.cooldown
+            {
+                continue;
+            }
+            self.last_fired.insert(rule.name.clone(), now);
+
+            tracing::warn!(rule = %rule.name, "{}", message);
+            let alert = AlertMessage {
+                level: "ALERT".to_string(),
+                message,
+                target: format!("alert_rule::{}", rule.name),
+                timestamp: chrono::Utc::now(),
+                fields: vec![],
+            };
+            if let Err(e) = self.sender.send(alert).await {
+                tracing::error!(rule = %rule.name, error = %e, "Failed to send rule alert");
+            }
+        }
+    }
+
+    fn watches_queue(&self) -> bool {
+        self.config
+            .rules
+            .iter()
+            .any(|rule| matches!(rule.condition, AlertCondition::QueueDepth(_)))
+    }
+
+    /// Describe the breach if `rule` is breached.
+    fn breach(
+        &self,
+        rule: &AlertRule,
+        window: &WindowSnapshot,
+        depth: Option<usize>,
+    ) -> Option<String> {
+        match rule.condition {
+            AlertCondition::ErrorRate(threshold) => {
+                let rate = window.error_rate();
+                (window.requests >= self.config.min_requests && rate > threshold).then(|| {
+                    format!(
+                        "Error rate {:.1}% above {}% ({} of {} requests)",
+                        rate, threshold, window.errors, window.requests
+                    )
+                })
+            }
+            AlertCondition::P99Latency(threshold) => {
+                window.p99.filter(|p99| *p99 > threshold).map(|p99| {
+                    format!(
+                        "p99 latency {}ms above {}ms",
+                        p99.as_millis(),
+                        threshold.as_millis()
+                    )
+                })
+            }
+            AlertCondition::QueueDepth(threshold) => depth
+                .filter(|depth| *depth > threshold)
+                .map(|depth| format!("Job queue depth {} above {}", depth, threshold)),
+        }
+    }
+}
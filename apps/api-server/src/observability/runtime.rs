@@ -0,0 +1,204 @@
+//! Startup summary of what this instance runs with.
+//!
+//! Logged once the listeners are bound and served at `/api/admin/runtime`,
+//! so a deploy can be checked without reading the environment it got.
+
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, Utc};
+#[cfg(feature = "auth")]
+use tokio::runtime::Handle;
+
+use apex_infra::database::DatabaseConnections;
+use apex_shared::dto::{BackendStatusResponse, RuntimeResponse, WorkerCountsResponse};
+
+use crate::config::AppConfig;
+
+/// Compile-time features of this build.
+const FEATURES: &[(&str, bool)] = &[
+    ("postgres", cfg!(feature = "postgres")),
+    ("auth", cfg!(feature = "auth")),
+    ("rate-limit", cfg!(feature = "rate-limit")),
+    ("scheduler", cfg!(feature = "scheduler")),
+    ("websocket", cfg!(feature = "websocket")),
+    ("webhooks", cfg!(feature = "webhooks")),
+    ("billing", cfg!(feature = "billing")),
+    ("otel", cfg!(feature = "otel")),
+];
+
+/// Backends that live in the server process rather than behind a connection.
+const IN_PROCESS: &[&str] = &[
+    "cache",
+    "job_queue",
+    "pubsub",
+    #[cfg(feature = "rate-limit")]
+    "rate_limiter",
+];
+
+/// Features, listeners, workers and backends of the running instance.
+pub struct RuntimeReport {
+    started_at: DateTime<Utc>,
+    sandbox: bool,
+    addresses: OnceLock<Vec<SocketAddr>>,
+    workers: WorkerCountsResponse,
+    #[cfg(feature = "postgres")]
+    db: Option<Arc<DatabaseConnections>>,
+    #[cfg(feature = "auth")]
+    main_runtime: Handle,
+}
+
+impl RuntimeReport {
+    /// Call from the main runtime, which is recorded as the one running
+    /// background work.
+    pub fn new(
+        config: &AppConfig,
+        db: Option<Arc<DatabaseConnections>>,
+        job_workers: usize,
+    ) -> Self {
+        #[cfg(not(feature = "postgres"))]
+        let _ = db;
+
+        Self {
+            started_at: Utc::now(),
+            sandbox: config.sandbox,
+            addresses: OnceLock::new(),
+            workers: WorkerCountsResponse {
+                // What actix-web starts when no worker count is set
+                http: std::thread::available_parallelism().map_or(2, NonZeroUsize::get),
+                jobs: job_workers,
+            },
+            #[cfg(feature = "postgres")]
+            db,
+            #[cfg(feature = "auth")]
+            main_runtime: Handle::current(),
+        }
+    }
+
+    /// The runtime jobs, pub/sub and other background tasks run on.
+    #[cfg(feature = "auth")]
+    pub fn main_runtime(&self) -> &Handle {
+        &self.main_runtime
+    }
+
+    /// Record the addresses the server ended up bound to.
+    pub fn set_addresses(&self, addresses: Vec<SocketAddr>) {
+        let _ = self.addresses.set(addresses);
+    }
+
+    /// The report, with backends checked just now.
+    pub async fn snapshot(&self) -> RuntimeResponse {
+        RuntimeResponse {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: self.started_at,
+            features: FEATURES
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            sandbox: self.sandbox,
+            addresses: self
+                .addresses
+                .get()
+                .map(|addresses| addresses.iter().map(ToString::to_string).collect())
+                .unwrap_or_default(),
+            workers: self.workers.clone(),
+            backends: self.backends().await,
+        }
+    }
+
+    /// Log the report as the startup banner: one summary line, then one
+    /// line per backend so each can be filtered on.
+    pub async fn log(&self) {
+        let report = self.snapshot().await;
+        tracing::info!(
+            version = %report.version,
+            features = %report.features.join(","),
+            sandbox = report.sandbox,
+            addresses = %report.addresses.join(","),
+            http_workers = report.workers.http,
+            job_workers = report.workers.jobs,
+            "Runtime summary"
+        );
+        for backend in &report.backends {
+            if backend.status == "down" {
+                tracing::warn!(
+                    backend = %backend.name,
+                    kind = %backend.kind,
+                    error = backend.error.as_deref().unwrap_or_default(),
+                    "Backend unreachable"
+                );
+            } else {
+                tracing::info!(
+                    backend = %backend.name,
+                    kind = %backend.kind,
+                    status = %backend.status,
+                    latency_ms = backend.latency_ms,
+                    "Backend"
+                );
+            }
+        }
+    }
+
+    async fn backends(&self) -> Vec<BackendStatusResponse> {
+        let mut backends = self.databases().await;
+        backends.extend(IN_PROCESS.iter().map(|name| BackendStatusResponse {
+            name: name.to_string(),
+            kind: "memory".to_string(),
+            status: "in_process".to_string(),
+            latency_ms: None,
+            error: None,
+        }));
+        backends
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn databases(&self) -> Vec<BackendStatusResponse> {
+        let Some(db) = &self.db else {
+            return vec![no_database()];
+        };
+        db.ping()
+            .await
+            .into_iter()
+            .map(|(name, result)| {
+                let name = match name {
+                    "main" => "database".to_string(),
+                    name => format!("database:{}", name),
+                };
+                match result {
+                    Ok(latency) => BackendStatusResponse {
+                        name,
+                        kind: "postgres".to_string(),
+                        status: "up".to_string(),
+                        latency_ms: Some(latency.as_secs_f64() * 1000.0),
+                        error: None,
+                    },
+                    Err(e) => BackendStatusResponse {
+                        name,
+                        kind: "postgres".to_string(),
+                        status: "down".to_string(),
+                        latency_ms: None,
+                        error: Some(e.to_string()),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    async fn databases(&self) -> Vec<BackendStatusResponse> {
+        vec![no_database()]
+    }
+}
+
+/// Repositories fall back to stubs without a database.
+fn no_database() -> BackendStatusResponse {
+    BackendStatusResponse {
+        name: "database".to_string(),
+        kind: "stub".to_string(),
+        status: "disabled".to_string(),
+        latency_ms: None,
+        error: None,
+    }
+}
@@ -0,0 +1,234 @@
+//! Labelled request series in the Prometheus text format, with bounded
+//! cardinality.
+//!
+//! Requests are labelled by the route pattern they matched
+//! (`/api/orgs/{id}`), never their URL, and requests no route matched share
+//! the `unmatched` route, so scanners probing random paths add no series.
+//! Label values outside their allowlist read as `other`. Once a family has
+//! `METRICS_MAX_SERIES` series, new label combinations are folded into one
+//! overflow series labelled `other` throughout, and counted, instead of
+//! growing the family further.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[cfg(feature = "auth")]
+use apex_shared::dto::{MetricFamilyResponse, MetricsReportResponse};
+
+use crate::env;
+
+/// Label value standing in for anything outside an allowlist.
+pub const OTHER: &str = "other";
+
+/// Route label of requests no route matched.
+pub const UNMATCHED: &str = "unmatched";
+
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+const STATUS_CLASSES: &[&str] = &["1xx", "2xx", "3xx", "4xx", "5xx"];
+
+/// A label and the values it may take. Route patterns have no fixed list:
+/// the registered routes bound them.
+struct Label {
+    name: &'static str,
+    allowed: Option<&'static [&'static str]>,
+}
+
+const METHOD: Label = Label {
+    name: "method",
+    allowed: Some(METHODS),
+};
+const ROUTE: Label = Label {
+    name: "route",
+    allowed: None,
+};
+const STATUS: Label = Label {
+    name: "status",
+    allowed: Some(STATUS_CLASSES),
+};
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Counter,
+    /// Sum and count only, no quantiles.
+    Summary,
+}
+
+impl Kind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Summary => "summary",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Sample {
+    count: u64,
+    sum: f64,
+}
+
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    kind: Kind,
+    labels: &'static [Label],
+    series: HashMap<Vec<String>, Sample>,
+    /// Observations folded into the overflow series.
+    overflowed: u64,
+}
+
+impl Family {
+    fn new(name: &'static str, help: &'static str, kind: Kind, labels: &'static [Label]) -> Self {
+        Self {
+            name,
+            help,
+            kind,
+            labels,
+            series: HashMap::new(),
+            overflowed: 0,
+        }
+    }
+
+    fn observe(&mut self, values: &[&str], value: f64, max_series: usize) {
+        let mut key: Vec<String> = self
+            .labels
+            .iter()
+            .zip(values)
+            .map(|(label, value)| match label.allowed {
+                Some(allowed) if !allowed.contains(value) => OTHER.to_string(),
+                _ => value.to_string(),
+            })
+            .collect();
+        // Keep one slot free for the overflow series
+        if !self.series.contains_key(&key) && self.series.len() + 1 >= max_series {
+            key = vec![OTHER.to_string(); self.labels.len()];
+            self.overflowed += 1;
+        }
+        let sample = self.series.entry(key).or_default();
+        sample.count += 1;
+        sample.sum += value;
+    }
+
+    fn render(&self, out: &mut String) {
+        writeln!(out, "# HELP {} {}", self.name, self.help).unwrap();
+        writeln!(out, "# TYPE {} {}", self.name, self.kind.as_str()).unwrap();
+        let mut series: Vec<_> = self.series.iter().collect();
+        series.sort_by(|a, b| a.0.cmp(b.0));
+        for (values, sample) in series {
+            let labels = self
+                .labels
+                .iter()
+                .zip(values)
+                .map(|(label, value)| format!("{}=\"{}\"", label.name, escape(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            match self.kind {
+                Kind::Counter => {
+                    writeln!(out, "{}{{{}}} {}", self.name, labels, sample.count).unwrap()
+                }
+                Kind::Summary => {
+                    writeln!(out, "{}_sum{{{}}} {}", self.name, labels, sample.sum).unwrap();
+                    writeln!(out, "{}_count{{{}}} {}", self.name, labels, sample.count).unwrap();
+                }
+            }
+        }
+    }
+}
+
+/// Request counts and durations per method, route and status class.
+pub struct RequestSeries {
+    /// Whether `/metrics` serves them.
+    exposed: bool,
+    max_series: usize,
+    families: Mutex<Vec<Family>>,
+}
+
+impl RequestSeries {
+    pub fn new(exposed: bool, max_series: usize) -> Self {
+        Self {
+            exposed,
+            max_series: max_series.max(2),
+            families: Mutex::new(vec![
+                Family::new(
+                    "http_requests_total",
+                    "HTTP requests by method, route pattern and status class.",
+                    Kind::Counter,
+                    &[METHOD, ROUTE, STATUS],
+                ),
+                Family::new(
+                    "http_request_duration_seconds",
+                    "HTTP request durations by method and route pattern.",
+                    Kind::Summary,
+                    &[METHOD, ROUTE],
+                ),
+            ]),
+        }
+    }
+
+    /// Served at `/metrics` when `METRICS_ENABLED=true`, with at most
+    /// `METRICS_MAX_SERIES` series per family (default 2000).
+    pub fn from_env() -> Self {
+        Self::new(env::METRICS_ENABLED.flag(), env::METRICS_MAX_SERIES.get())
+    }
+
+    pub fn exposed(&self) -> bool {
+        self.exposed
+    }
+
+    /// Record a finished request. `route` is the pattern it matched, if any.
+    pub fn record(&self, method: &str, route: Option<&str>, status: u16, elapsed: Duration) {
+        let route = route.unwrap_or(UNMATCHED);
+        let class = match status {
+            100..=199 => "1xx",
+            200..=299 => "2xx",
+            300..=399 => "3xx",
+            400..=499 => "4xx",
+            500..=599 => "5xx",
+            _ => OTHER,
+        };
+        let mut families = self.families.lock().unwrap();
+        families[0].observe(&[method, route, class], 1.0, self.max_series);
+        families[1].observe(&[method, route], elapsed.as_secs_f64(), self.max_series);
+    }
+
+    /// Every family in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for family in self.families.lock().unwrap().iter() {
+            family.render(&mut out);
+        }
+        out
+    }
+
+    /// Series counts per family, to check cardinality stays bounded.
+    #[cfg(feature = "auth")]
+    pub fn report(&self) -> MetricsReportResponse {
+        let families = self.families.lock().unwrap();
+        MetricsReportResponse {
+            exposed: self.exposed,
+            max_series_per_family: self.max_series,
+            total_series: families.iter().map(|f| f.series.len()).sum(),
+            families: families
+                .iter()
+                .map(|family| MetricFamilyResponse {
+                    name: family.name.to_string(),
+                    kind: family.kind.as_str().to_string(),
+                    labels: family.labels.iter().map(|l| l.name.to_string()).collect(),
+                    series: family.series.len(),
+                    overflowed: family.overflowed,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Escape a label value for the text format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,120 @@
+//! Route matrix: every route with the access it requires.
+//!
+//! Generated at build time from the route registrations and handler
+//! signatures (see `build.rs`), so it cannot drift from what is served.
+
+/// Access a route requires, from its handler's extractors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// No extractor for the caller.
+    Public,
+    /// `OptionalIdentity`: works signed in or not.
+    #[cfg(feature = "graphql")]
+    Optional,
+    /// `Identity`: any signed-in user.
+    Authenticated,
+    /// `Admin`: the admin role.
+    Admin,
+}
+
+impl Access {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Access::Public => "public",
+            #[cfg(feature = "graphql")]
+            Access::Optional => "optional",
+            Access::Authenticated => "authenticated",
+            Access::Admin => "admin",
+        }
+    }
+}
+
+/// A route and what it takes to call it.
+#[derive(Debug)]
+pub struct RouteSpec {
+    pub method: &'static str,
+    pub path: &'static str,
+    /// Handler function, relative to `handlers`.
+    pub handler: &'static str,
+    pub access: Access,
+    /// Middleware wrapping the route, innermost first.
+    pub middleware: &'static [&'static str],
+}
+
+/// Routes of this build.
+pub static ROUTES: &[RouteSpec] = &include!(concat!(env!("OUT_DIR"), "/routes.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::versioning::unversioned;
+
+    /// Routes meant to be callable without signing in, in every API
+    /// version. Anything else reachable anonymously is a mistake.
+    const PUBLIC: &[(&str, &str)] = &[
+        ("GET", "/healthz"),
+        ("GET", "/readyz"),
+        ("GET", "/api/features"),
+        ("POST", "/api/auth/register"),
+        ("POST", "/api/auth/login"),
+        // Verified by its Stripe signature instead
+        ("POST", "/api/billing/stripe/webhook"),
+        // Verified by its link signature instead
+        ("GET", "/api/files/{key:.*}"),
+        // Field guards check the caller per field
+        ("POST", "/api/graphql"),
+        // Published posts only
+        ("GET", "/sitemap.xml"),
+        ("GET", "/feed.xml"),
+        ("GET", "/robots.txt"),
+        ("GET", "/.well-known/{name}"),
+        // Off unless METRICS_ENABLED, and only aggregate counters
+        ("GET", "/metrics"),
+    ];
+
+    #[test]
+    fn test_no_route_is_unintentionally_public() {
+        let anonymous: Vec<_> = ROUTES
+            .iter()
+            .filter(|route| !matches!(route.access, Access::Authenticated | Access::Admin))
+            .filter(|route| !PUBLIC.contains(&(route.method, &*unversioned(route.path))))
+            .map(|route| format!("{} {} ({})", route.method, route.path, route.handler))
+            .collect();
+        assert!(
+            anonymous.is_empty(),
+            "routes without authentication: {:?}",
+            anonymous
+        );
+    }
+
+    #[test]
+    fn test_admin_routes_require_the_admin_role() {
+        let admin: Vec<_> = ROUTES
+            .iter()
+            .filter(|route| unversioned(route.path).starts_with("/api/admin"))
+            .collect();
+        assert!(!admin.is_empty());
+        for route in admin {
+            assert_eq!(
+                route.access,
+                Access::Admin,
+                "{} {}",
+                route.method,
+                route.path
+            );
+        }
+    }
+
+    #[test]
+    fn test_routes_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for route in ROUTES {
+            assert!(
+                seen.insert((route.method, route.path)),
+                "{} {} registered twice",
+                route.method,
+                route.path
+            );
+        }
+    }
+}
@@ -0,0 +1,572 @@
+//! Application state - shared across all handlers.
+//!
+//! The cache and the repositories handlers use directly sit behind
+//! [`HotSwap`], so they can be replaced without restarting the server. Call
+//! `load()` for the current implementation.
+//!
+//! Ports only account routes use are part of the state with the `auth`
+//! feature alone, so a handler reaching for one in a build without it fails
+//! to compile instead of running against a stub.
+
+mod stubs;
+
+use std::sync::Arc;
+
+use apex_core::ports::{
+    Cache, LegalHoldRepository, PlanRepository, SubscriptionRepository, UsageRepository,
+    WebhookDeliveryRepository,
+};
+use apex_infra::cache::{InMemoryCache, RedisCache, RedisConfig};
+use apex_infra::database::{DatabaseConfig, DatabaseConnections};
+use apex_infra::health::DependencyEvent;
+use apex_infra::{EntitlementResolver, HotSwap, SubscriptionService, UsageMeter, WellKnown};
+
+#[cfg(feature = "auth")]
+use apex_core::domain::PasswordPolicy;
+#[cfg(feature = "auth")]
+use apex_core::ports::{
+    AnnouncementRepository, CommentRepository, ConsentRepository, CustomDomainRepository,
+    DnsResolver, EventDispatcher, InvitationRepository, MembershipRepository, NoopEventDispatcher,
+    OAuthClientRepository, OrganizationRepository, PasswordHistoryRepository, PasswordService,
+    PendingOperationRepository, PostRepository, SettingsRepository, StorageUsageRepository,
+    TagRepository, TokenService, UserRepository,
+};
+#[cfg(all(feature = "auth", feature = "storage"))]
+use apex_core::ports::{AttachmentRepository, StorageService};
+#[cfg(all(feature = "auth", feature = "storage"))]
+use apex_core::services::AttachmentService;
+#[cfg(feature = "auth")]
+use apex_core::services::{AuthService, CommentService, PostService, TagService, UserService};
+#[cfg(feature = "auth")]
+use apex_infra::api_quota::api_quotas_from_env;
+#[cfg(feature = "auth")]
+use apex_infra::consent::policy_versions_from_env;
+#[cfg(all(feature = "auth", feature = "storage"))]
+use apex_infra::database::InMemoryAttachmentRepository;
+#[cfg(feature = "auth")]
+use apex_infra::database::{
+    InMemoryCommentRepository, InMemoryPasswordHistoryRepository, InMemoryPostRepository,
+    InMemoryTagRepository, InMemoryUserRepository,
+};
+#[cfg(feature = "auth")]
+use apex_infra::feeds::FeedConfig;
+#[cfg(feature = "auth")]
+use apex_infra::storage_quota::storage_quotas_from_env;
+#[cfg(feature = "auth")]
+use apex_infra::{
+    AdminApprovals, AnnouncementBoard, ApiQuotas, ConsentService, PostViews, PublicFeeds,
+    SettingsStore, StorageQuotas, TenantDomains, password_policy_from_env,
+};
+
+#[cfg(feature = "postgres")]
+use apex_infra::database::{
+    PostgresLegalHoldRepository, PostgresPlanRepository, PostgresSubscriptionRepository,
+    PostgresUsageRepository, PostgresWebhookDeliveryRepository,
+};
+
+#[cfg(all(feature = "postgres", feature = "auth"))]
+use apex_infra::database::{
+    PostgresAnnouncementRepository, PostgresCommentRepository, PostgresConsentRepository,
+    PostgresCustomDomainRepository, PostgresInvitationRepository, PostgresMembershipRepository,
+    PostgresOAuthClientRepository, PostgresOrganizationRepository,
+    PostgresPasswordHistoryRepository, PostgresPendingOperationRepository, PostgresPostRepository,
+    PostgresSettingsRepository, PostgresStorageUsageRepository, PostgresTagRepository,
+    PostgresUserRepository,
+};
+
+#[cfg(all(feature = "postgres", feature = "auth", feature = "storage"))]
+use apex_infra::database::PostgresAttachmentRepository;
+
+use stubs::*;
+
+/// Shared application state.
+#[derive(Clone)]
+pub struct AppState {
+    pub cache: HotSwap<dyn Cache>,
+    #[cfg(feature = "auth")]
+    pub users: HotSwap<dyn UserRepository>,
+    #[cfg(feature = "auth")]
+    pub password_history: HotSwap<dyn PasswordHistoryRepository>,
+    /// Rules for new passwords, from `PASSWORD_MIN_LENGTH` and
+    /// `PASSWORD_HISTORY`.
+    #[cfg(feature = "auth")]
+    pub password_policy: PasswordPolicy,
+    #[cfg(feature = "auth")]
+    pub posts: HotSwap<dyn PostRepository>,
+    #[cfg(feature = "auth")]
+    pub comments: HotSwap<dyn CommentRepository>,
+    #[cfg(feature = "auth")]
+    pub tags: HotSwap<dyn TagRepository>,
+    /// Metadata of uploaded files; the bytes are in file storage.
+    #[cfg(all(feature = "auth", feature = "storage"))]
+    pub attachments: HotSwap<dyn AttachmentRepository>,
+    #[cfg(feature = "auth")]
+    pub post_views: Arc<PostViews>,
+    #[cfg(feature = "auth")]
+    pub feeds: Arc<PublicFeeds>,
+    /// Where services announce their changes. Events are dropped until
+    /// pub/sub and the job queue are up.
+    #[cfg(feature = "auth")]
+    pub events: HotSwap<dyn EventDispatcher>,
+    pub deliveries: HotSwap<dyn WebhookDeliveryRepository>,
+    /// Users whose data must not be deleted or anonymized.
+    pub legal_holds: HotSwap<dyn LegalHoldRepository>,
+    #[cfg(feature = "auth")]
+    pub organizations: HotSwap<dyn OrganizationRepository>,
+    #[cfg(feature = "auth")]
+    pub memberships: HotSwap<dyn MembershipRepository>,
+    #[cfg(feature = "auth")]
+    pub invitations: HotSwap<dyn InvitationRepository>,
+    #[cfg(feature = "auth")]
+    pub oauth_clients: HotSwap<dyn OAuthClientRepository>,
+    #[cfg(feature = "auth")]
+    pub settings: Arc<SettingsStore>,
+    pub usage: Arc<UsageMeter>,
+    #[cfg(feature = "auth")]
+    pub storage: Arc<StorageQuotas>,
+    #[cfg(feature = "auth")]
+    pub api_quotas: Arc<ApiQuotas>,
+    #[cfg(feature = "auth")]
+    pub entitlements: Arc<EntitlementResolver>,
+    pub subscriptions: Arc<SubscriptionService>,
+    #[cfg(feature = "auth")]
+    pub announcements: Arc<AnnouncementBoard>,
+    #[cfg(feature = "auth")]
+    pub consent: Arc<ConsentService>,
+    #[cfg(feature = "auth")]
+    pub domains: Arc<TenantDomains>,
+    /// Destructive admin actions waiting for a second admin.
+    #[cfg(feature = "auth")]
+    pub approvals: Arc<AdminApprovals>,
+    /// `robots.txt` and documents under `/.well-known/`.
+    pub well_known: Arc<WellKnown>,
+    pub db: Option<Arc<DatabaseConnections>>,
+}
+
+/// Database handle plus the repositories built on top of it.
+struct Repositories {
+    db: Option<Arc<DatabaseConnections>>,
+    #[cfg(feature = "auth")]
+    users: Arc<dyn UserRepository>,
+    #[cfg(feature = "auth")]
+    password_history: Arc<dyn PasswordHistoryRepository>,
+    #[cfg(feature = "auth")]
+    posts: Arc<dyn PostRepository>,
+    #[cfg(feature = "auth")]
+    comments: Arc<dyn CommentRepository>,
+    #[cfg(feature = "auth")]
+    tags: Arc<dyn TagRepository>,
+    #[cfg(all(feature = "auth", feature = "storage"))]
+    attachments: Arc<dyn AttachmentRepository>,
+    deliveries: Arc<dyn WebhookDeliveryRepository>,
+    legal_holds: Arc<dyn LegalHoldRepository>,
+    #[cfg(feature = "auth")]
+    organizations: Arc<dyn OrganizationRepository>,
+    #[cfg(feature = "auth")]
+    memberships: Arc<dyn MembershipRepository>,
+    #[cfg(feature = "auth")]
+    invitations: Arc<dyn InvitationRepository>,
+    #[cfg(feature = "auth")]
+    oauth_clients: Arc<dyn OAuthClientRepository>,
+    #[cfg(feature = "auth")]
+    settings: Arc<dyn SettingsRepository>,
+    usage: Arc<dyn UsageRepository>,
+    #[cfg(feature = "auth")]
+    storage: Arc<dyn StorageUsageRepository>,
+    plans: Arc<dyn PlanRepository>,
+    subscriptions: Arc<dyn SubscriptionRepository>,
+    #[cfg(feature = "auth")]
+    announcements: Arc<dyn AnnouncementRepository>,
+    #[cfg(feature = "auth")]
+    consents: Arc<dyn ConsentRepository>,
+    #[cfg(feature = "auth")]
+    custom_domains: Arc<dyn CustomDomainRepository>,
+    #[cfg(feature = "auth")]
+    pending_operations: Arc<dyn PendingOperationRepository>,
+}
+
+impl Repositories {
+    /// Repositories used when no database is available: users, their
+    /// password history, posts, comments, tags and attachments in memory,
+    /// stubs for the rest.
+    fn stub() -> Self {
+        tracing::warn!(
+            "No database: users, password history, posts, comments, tags and attachments are \
+             kept in memory until restart, \
+             other repositories are stubs that find nothing and drop writes, so webhook \
+             deliveries, usage, subscriptions and legal holds are not persisted"
+        );
+        Self {
+            db: None,
+            #[cfg(feature = "auth")]
+            users: Arc::new(InMemoryUserRepository::new()),
+            #[cfg(feature = "auth")]
+            password_history: Arc::new(InMemoryPasswordHistoryRepository::new()),
+            #[cfg(feature = "auth")]
+            posts: Arc::new(InMemoryPostRepository::new()),
+            #[cfg(feature = "auth")]
+            comments: Arc::new(InMemoryCommentRepository::new()),
+            #[cfg(feature = "auth")]
+            tags: Arc::new(InMemoryTagRepository::new()),
+            #[cfg(all(feature = "auth", feature = "storage"))]
+            attachments: Arc::new(InMemoryAttachmentRepository::new()),
+            deliveries: Arc::new(StubWebhookDeliveryRepository),
+            legal_holds: Arc::new(StubLegalHoldRepository),
+            #[cfg(feature = "auth")]
+            organizations: Arc::new(StubOrganizationRepository),
+            #[cfg(feature = "auth")]
+            memberships: Arc::new(StubMembershipRepository),
+            #[cfg(feature = "auth")]
+            invitations: Arc::new(StubInvitationRepository),
+            #[cfg(feature = "auth")]
+            oauth_clients: Arc::new(StubOAuthClientRepository),
+            #[cfg(feature = "auth")]
+            settings: Arc::new(StubSettingsRepository),
+            usage: Arc::new(StubUsageRepository),
+            #[cfg(feature = "auth")]
+            storage: Arc::new(StubStorageUsageRepository),
+            plans: Arc::new(StubPlanRepository),
+            subscriptions: Arc::new(StubSubscriptionRepository),
+            #[cfg(feature = "auth")]
+            announcements: Arc::new(StubAnnouncementRepository),
+            #[cfg(feature = "auth")]
+            consents: Arc::new(StubConsentRepository),
+            #[cfg(feature = "auth")]
+            custom_domains: Arc::new(StubCustomDomainRepository),
+            #[cfg(feature = "auth")]
+            pending_operations: Arc::new(StubPendingOperationRepository),
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    fn postgres(conn: Arc<DatabaseConnections>) -> Self {
+        Self {
+            #[cfg(feature = "auth")]
+            users: Arc::new(PostgresUserRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            password_history: Arc::new(PostgresPasswordHistoryRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            posts: tenant_posts(&conn),
+            #[cfg(feature = "auth")]
+            comments: Arc::new(PostgresCommentRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            tags: Arc::new(PostgresTagRepository::new(conn.main.clone())),
+            #[cfg(all(feature = "auth", feature = "storage"))]
+            attachments: Arc::new(PostgresAttachmentRepository::new(conn.main.clone())),
+            deliveries: Arc::new(PostgresWebhookDeliveryRepository::new(conn.main.clone())),
+            legal_holds: Arc::new(PostgresLegalHoldRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            organizations: Arc::new(PostgresOrganizationRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            memberships: Arc::new(PostgresMembershipRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            invitations: Arc::new(PostgresInvitationRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            oauth_clients: Arc::new(PostgresOAuthClientRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            settings: Arc::new(PostgresSettingsRepository::new(conn.main.clone())),
+            usage: Arc::new(PostgresUsageRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            storage: Arc::new(PostgresStorageUsageRepository::new(conn.main.clone())),
+            plans: Arc::new(PostgresPlanRepository::new(conn.main.clone())),
+            subscriptions: Arc::new(PostgresSubscriptionRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            announcements: Arc::new(PostgresAnnouncementRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            consents: Arc::new(PostgresConsentRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            custom_domains: Arc::new(PostgresCustomDomainRepository::new(conn.main.clone())),
+            #[cfg(feature = "auth")]
+            pending_operations: Arc::new(PostgresPendingOperationRepository::new(
+                conn.main.clone(),
+            )),
+            db: Some(conn),
+        }
+    }
+}
+
+impl AppState {
+    /// Build the application state with appropriate implementations.
+    pub async fn new(db_config: Option<&DatabaseConfig>) -> Self {
+        // In memory until Redis answers, see `fail_over_cache`. Services
+        // get the swappable cache itself, so they follow.
+        let cache: HotSwap<dyn Cache> = HotSwap::new(Arc::new(InMemoryCache::new()));
+        let shared_cache: Arc<dyn Cache> = Arc::new(cache.clone());
+
+        // Initialize database connections if configured
+        #[cfg(feature = "postgres")]
+        let repos = {
+            if let Some(config) = db_config {
+                match DatabaseConnections::init_with_retry(config).await {
+                    Ok(connections) => Repositories::postgres(Arc::new(connections)),
+                    // Queries fail until the database is back, then the pools
+                    // connect by themselves and the watchdog reports it recovered
+                    Err(e) => match DatabaseConnections::init_lazy(config).await {
+                        Ok(connections) => {
+                            tracing::error!(
+                                "Failed to connect to database after {} attempts: {}. \
+                                 Connecting when it becomes reachable.",
+                                config.connect_attempts,
+                                e
+                            );
+                            Repositories::postgres(Arc::new(connections))
+                        }
+                        Err(_) => {
+                            tracing::error!(
+                                "Failed to connect to database: {}. Using stub fallback.",
+                                e
+                            );
+                            Repositories::stub()
+                        }
+                    },
+                }
+            } else {
+                tracing::warn!("DATABASE_URL not set. Running without a database.");
+                Repositories::stub()
+            }
+        };
+
+        #[cfg(not(feature = "postgres"))]
+        let repos = {
+            if db_config.is_some() {
+                tracing::warn!("DATABASE_URL is ignored: built without the postgres feature");
+            }
+            Repositories::stub()
+        };
+
+        tracing::info!("Application state initialized");
+
+        let usage = Arc::new(UsageMeter::new(repos.usage));
+        let entitlements = Arc::new(EntitlementResolver::new(repos.plans, shared_cache.clone()));
+        let subscriptions = Arc::new(SubscriptionService::new(
+            repos.subscriptions,
+            entitlements.clone(),
+        ));
+
+        let well_known = WellKnown::from_env();
+        #[cfg(feature = "auth")]
+        well_known.add_sitemap("/sitemap.xml");
+
+        // A stub would accept operations and never find them again
+        #[cfg(feature = "auth")]
+        let approvals = match AdminApprovals::from_env(repos.pending_operations) {
+            approvals if approvals.required() && repos.db.is_none() => {
+                tracing::warn!(
+                    "No database: admin approvals cannot be stored, \
+                     so destructive admin actions run without a second admin"
+                );
+                approvals.with_required(false)
+            }
+            approvals => approvals,
+        };
+
+        Self {
+            #[cfg(feature = "auth")]
+            users: HotSwap::new(repos.users),
+            #[cfg(feature = "auth")]
+            password_history: HotSwap::new(repos.password_history),
+            #[cfg(feature = "auth")]
+            password_policy: password_policy_from_env(),
+            #[cfg(feature = "auth")]
+            post_views: Arc::new(PostViews::new(shared_cache.clone(), repos.posts.clone())),
+            #[cfg(feature = "auth")]
+            feeds: Arc::new(PublicFeeds::new(
+                repos.posts.clone(),
+                shared_cache.clone(),
+                FeedConfig::from_env(),
+            )),
+            #[cfg(feature = "auth")]
+            events: HotSwap::new(Arc::new(NoopEventDispatcher)),
+            #[cfg(feature = "auth")]
+            posts: HotSwap::new(repos.posts),
+            #[cfg(feature = "auth")]
+            comments: HotSwap::new(repos.comments),
+            #[cfg(feature = "auth")]
+            tags: HotSwap::new(repos.tags),
+            #[cfg(all(feature = "auth", feature = "storage"))]
+            attachments: HotSwap::new(repos.attachments),
+            deliveries: HotSwap::new(repos.deliveries),
+            legal_holds: HotSwap::new(repos.legal_holds),
+            #[cfg(feature = "auth")]
+            organizations: HotSwap::new(repos.organizations),
+            #[cfg(feature = "auth")]
+            memberships: HotSwap::new(repos.memberships),
+            #[cfg(feature = "auth")]
+            invitations: HotSwap::new(repos.invitations),
+            #[cfg(feature = "auth")]
+            oauth_clients: HotSwap::new(repos.oauth_clients),
+            #[cfg(feature = "auth")]
+            settings: Arc::new(SettingsStore::new(repos.settings, shared_cache.clone())),
+            #[cfg(feature = "auth")]
+            storage: Arc::new(StorageQuotas::new(
+                repos.storage,
+                entitlements.clone(),
+                usage.clone(),
+                storage_quotas_from_env(),
+            )),
+            #[cfg(feature = "auth")]
+            api_quotas: Arc::new(ApiQuotas::new(
+                shared_cache.clone(),
+                entitlements.clone(),
+                api_quotas_from_env(),
+            )),
+            usage,
+            #[cfg(feature = "auth")]
+            entitlements,
+            subscriptions,
+            #[cfg(feature = "auth")]
+            announcements: Arc::new(AnnouncementBoard::new(
+                repos.announcements,
+                shared_cache.clone(),
+            )),
+            #[cfg(feature = "auth")]
+            consent: Arc::new(ConsentService::new(
+                repos.consents,
+                shared_cache.clone(),
+                policy_versions_from_env(),
+            )),
+            #[cfg(feature = "auth")]
+            domains: Arc::new(TenantDomains::new(
+                repos.custom_domains,
+                shared_cache.clone(),
+                dns_resolver(),
+            )),
+            #[cfg(feature = "auth")]
+            approvals: Arc::new(approvals),
+            well_known: Arc::new(well_known),
+            cache,
+            db: repos.db,
+        }
+    }
+
+    /// Sign-up, login and password changes over the current user
+    /// repository.
+    #[cfg(feature = "auth")]
+    pub fn auth_service(
+        &self,
+        tokens: Arc<dyn TokenService>,
+        passwords: Arc<dyn PasswordService>,
+    ) -> AuthService {
+        AuthService::new(
+            self.users.load(),
+            self.subscriptions.repository(),
+            self.password_history.load(),
+            passwords,
+            tokens,
+            self.events.load(),
+        )
+        .with_policy(self.password_policy)
+    }
+
+    /// User accounts and roles over the current user repository.
+    #[cfg(feature = "auth")]
+    pub fn user_service(&self) -> UserService {
+        UserService::new(self.users.load())
+    }
+
+    /// Post reads and publishing over the current post repository.
+    #[cfg(feature = "auth")]
+    pub fn post_service(&self) -> PostService {
+        PostService::new(self.posts.load(), self.events.load())
+    }
+
+    /// Comments on posts over the current post and comment repositories.
+    #[cfg(feature = "auth")]
+    pub fn comment_service(&self) -> CommentService {
+        CommentService::new(self.posts.load(), self.comments.load())
+    }
+
+    /// Post tags over the current post and tag repositories.
+    #[cfg(feature = "auth")]
+    pub fn tag_service(&self) -> TagService {
+        TagService::new(self.posts.load(), self.tags.load())
+    }
+
+    /// Uploaded files over the current attachment repository, their bytes
+    /// in `storage`.
+    #[cfg(all(feature = "auth", feature = "storage"))]
+    pub fn attachment_service(&self, storage: Arc<dyn StorageService>) -> AttachmentService {
+        AttachmentService::new(self.posts.load(), self.attachments.load(), storage)
+    }
+
+    /// Move the cache to Redis when `REDIS_URL` is set, then follow the
+    /// watchdog: back to a fresh in-memory cache while Redis is degraded
+    /// (unless `REDIS_FALLBACK_TO_MEMORY=false`), to Redis again once it
+    /// recovers. Cached values and counters do not carry over.
+    pub fn fail_over_cache(
+        &self,
+        watchdog: &apex_infra::DependencyWatchdog,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        apex_infra::env::REDIS_URL.raw()?;
+        let config = RedisConfig::from_env();
+        let cache = self.cache.clone();
+        let mut events = watchdog.subscribe();
+
+        Some(tokio::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+
+            match RedisCache::new(config.clone()).await {
+                Ok(redis) => {
+                    cache.swap(Arc::new(redis));
+                }
+                Err(e) => tracing::warn!(
+                    error = %e,
+                    "Redis unreachable, caching in memory until it answers"
+                ),
+            }
+
+            loop {
+                match events.recv().await {
+                    Ok(DependencyEvent::Degraded { dependency, .. })
+                        if dependency == "redis" && config.fallback_to_memory =>
+                    {
+                        cache.swap(Arc::new(InMemoryCache::new()));
+                        tracing::warn!("Redis degraded, cache failed over to memory");
+                    }
+                    Ok(DependencyEvent::Recovered { dependency, .. }) if dependency == "redis" => {
+                        match RedisCache::new(config.clone()).await {
+                            Ok(redis) => {
+                                cache.swap(Arc::new(redis));
+                                tracing::info!("Redis recovered, cache moved back to Redis");
+                            }
+                            Err(e) => {
+                                tracing::warn!(error = %e, "Redis recovered but connecting failed")
+                            }
+                        }
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }))
+    }
+}
+
+/// Posts scoped to the request's tenant as `TENANCY_MODE` says.
+#[cfg(all(feature = "postgres", feature = "auth"))]
+fn tenant_posts(conn: &Arc<DatabaseConnections>) -> Arc<dyn PostRepository> {
+    use apex_infra::database::TenancyMode;
+    use apex_infra::tenancy::TenantScopedPostRepository;
+
+    match conn.tenancy() {
+        TenancyMode::None => Arc::new(PostgresPostRepository::new(conn.main.clone())),
+        TenancyMode::Column => Arc::new(TenantScopedPostRepository::new(Arc::new(
+            PostgresPostRepository::new(conn.main.clone()),
+        ))),
+    }
+}
+
+/// Resolver for custom domain TXT challenges: DNS over HTTPS when the HTTP
+/// client is built in, otherwise one that finds no records.
+#[cfg(feature = "auth")]
+fn dns_resolver() -> Arc<dyn DnsResolver> {
+    #[cfg(feature = "webhooks")]
+    {
+        Arc::new(apex_infra::domains::DohResolver::from_env())
+    }
+    #[cfg(not(feature = "webhooks"))]
+    {
+        tracing::warn!("Built without an HTTP client: custom domains cannot be verified");
+        Arc::new(apex_infra::domains::StaticDnsResolver::default())
+    }
+}
@@ -0,0 +1,589 @@
+//! Repositories used when there is no database.
+//!
+//! Reads find nothing and writes are accepted and dropped, so nothing
+//! persists. Users and posts are kept in memory instead, by
+//! `apex_infra::database`'s in-memory repositories. Startup warns when they are in use; the ones behind account
+//! routes are only wired in with the `auth` feature.
+
+#[cfg(feature = "auth")]
+use apex_core::ports::{
+    AnnouncementRepository, ConsentRepository, CustomDomainRepository, InvitationRepository,
+    MembershipRepository, OAuthClientRepository, OrganizationRepository,
+    PendingOperationRepository, SettingsRepository, StorageUsageRepository,
+};
+use apex_core::ports::{
+    LegalHoldRepository, PlanRepository, SubscriptionRepository, UsageRepository,
+    WebhookDeliveryRepository,
+};
+
+/// Webhook delivery log (Stub) - deliveries are not recorded without a database
+pub struct StubWebhookDeliveryRepository;
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::WebhookDelivery, uuid::Uuid>
+    for StubWebhookDeliveryRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::WebhookDelivery>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        d: apex_core::domain::WebhookDelivery,
+    ) -> Result<apex_core::domain::WebhookDelivery, apex_core::error::RepoError> {
+        Ok(d)
+    }
+    async fn insert(
+        &self,
+        d: apex_core::domain::WebhookDelivery,
+    ) -> Result<apex_core::domain::WebhookDelivery, apex_core::error::RepoError> {
+        Ok(d)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<
+        apex_core::domain::Page<apex_core::domain::WebhookDelivery>,
+        apex_core::error::RepoError,
+    > {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[async_trait::async_trait]
+impl WebhookDeliveryRepository for StubWebhookDeliveryRepository {
+    async fn list_recent(
+        &self,
+        _failed_only: bool,
+        _limit: u64,
+    ) -> Result<Vec<apex_core::domain::WebhookDelivery>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+    async fn latest_attempt(
+        &self,
+        _chain_id: uuid::Uuid,
+    ) -> Result<Option<u32>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+}
+
+/// Organization repository (Stub)
+#[cfg(feature = "auth")]
+pub struct StubOrganizationRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::Organization, uuid::Uuid>
+    for StubOrganizationRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::Organization>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        o: apex_core::domain::Organization,
+    ) -> Result<apex_core::domain::Organization, apex_core::error::RepoError> {
+        Ok(o)
+    }
+    async fn insert(
+        &self,
+        o: apex_core::domain::Organization,
+    ) -> Result<apex_core::domain::Organization, apex_core::error::RepoError> {
+        Ok(o)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<apex_core::domain::Page<apex_core::domain::Organization>, apex_core::error::RepoError>
+    {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl OrganizationRepository for StubOrganizationRepository {
+    async fn find_by_slug(
+        &self,
+        _slug: &str,
+    ) -> Result<Option<apex_core::domain::Organization>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn list_for_user(
+        &self,
+        _user_id: uuid::Uuid,
+    ) -> Result<Vec<apex_core::domain::Organization>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Membership repository (Stub)
+#[cfg(feature = "auth")]
+pub struct StubMembershipRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::Membership, uuid::Uuid>
+    for StubMembershipRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::Membership>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        m: apex_core::domain::Membership,
+    ) -> Result<apex_core::domain::Membership, apex_core::error::RepoError> {
+        Ok(m)
+    }
+    async fn insert(
+        &self,
+        m: apex_core::domain::Membership,
+    ) -> Result<apex_core::domain::Membership, apex_core::error::RepoError> {
+        Ok(m)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<apex_core::domain::Page<apex_core::domain::Membership>, apex_core::error::RepoError>
+    {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl MembershipRepository for StubMembershipRepository {
+    async fn find_membership(
+        &self,
+        _organization_id: uuid::Uuid,
+        _user_id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::Membership>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn list_by_organization(
+        &self,
+        _organization_id: uuid::Uuid,
+    ) -> Result<Vec<apex_core::domain::Membership>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Invitation repository (Stub)
+#[cfg(feature = "auth")]
+pub struct StubInvitationRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::Invitation, uuid::Uuid>
+    for StubInvitationRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::Invitation>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        i: apex_core::domain::Invitation,
+    ) -> Result<apex_core::domain::Invitation, apex_core::error::RepoError> {
+        Ok(i)
+    }
+    async fn insert(
+        &self,
+        i: apex_core::domain::Invitation,
+    ) -> Result<apex_core::domain::Invitation, apex_core::error::RepoError> {
+        Ok(i)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<apex_core::domain::Page<apex_core::domain::Invitation>, apex_core::error::RepoError>
+    {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl InvitationRepository for StubInvitationRepository {
+    async fn find_by_token(
+        &self,
+        _token: &str,
+    ) -> Result<Option<apex_core::domain::Invitation>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+}
+
+/// OAuth client repository (Stub) - clients are not persisted without a database
+#[cfg(feature = "auth")]
+pub struct StubOAuthClientRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::OAuthClient, uuid::Uuid>
+    for StubOAuthClientRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::OAuthClient>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        c: apex_core::domain::OAuthClient,
+    ) -> Result<apex_core::domain::OAuthClient, apex_core::error::RepoError> {
+        Ok(c)
+    }
+    async fn insert(
+        &self,
+        c: apex_core::domain::OAuthClient,
+    ) -> Result<apex_core::domain::OAuthClient, apex_core::error::RepoError> {
+        Ok(c)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<apex_core::domain::Page<apex_core::domain::OAuthClient>, apex_core::error::RepoError>
+    {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl OAuthClientRepository for StubOAuthClientRepository {
+    async fn find_by_client_id(
+        &self,
+        _client_id: &str,
+    ) -> Result<Option<apex_core::domain::OAuthClient>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn list_by_owner(
+        &self,
+        _owner_id: uuid::Uuid,
+    ) -> Result<Vec<apex_core::domain::OAuthClient>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Announcement repository (Stub) - nothing is announced without a database
+#[cfg(feature = "auth")]
+pub struct StubAnnouncementRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::Announcement, uuid::Uuid>
+    for StubAnnouncementRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::Announcement>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        a: apex_core::domain::Announcement,
+    ) -> Result<apex_core::domain::Announcement, apex_core::error::RepoError> {
+        Ok(a)
+    }
+    async fn insert(
+        &self,
+        a: apex_core::domain::Announcement,
+    ) -> Result<apex_core::domain::Announcement, apex_core::error::RepoError> {
+        Ok(a)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<apex_core::domain::Page<apex_core::domain::Announcement>, apex_core::error::RepoError>
+    {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl AnnouncementRepository for StubAnnouncementRepository {
+    async fn list_current(
+        &self,
+        _now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<apex_core::domain::Announcement>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+    async fn list_recent(
+        &self,
+        _limit: u64,
+    ) -> Result<Vec<apex_core::domain::Announcement>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Settings repository (Stub) - every scope reads as defaults
+#[cfg(feature = "auth")]
+pub struct StubSettingsRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl SettingsRepository for StubSettingsRepository {
+    async fn get(
+        &self,
+        _scope: apex_core::domain::SettingsScope,
+    ) -> Result<Option<serde_json::Value>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn put(
+        &self,
+        _scope: apex_core::domain::SettingsScope,
+        _values: serde_json::Value,
+    ) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+}
+
+/// Usage repository (Stub) - usage is not persisted without a database
+pub struct StubUsageRepository;
+#[async_trait::async_trait]
+impl UsageRepository for StubUsageRepository {
+    async fn increment(
+        &self,
+        _increments: Vec<apex_core::domain::UsageTotal>,
+    ) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn totals(
+        &self,
+        _account_id: uuid::Uuid,
+        _period_start: chrono::NaiveDate,
+    ) -> Result<Vec<apex_core::domain::UsageTotal>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Storage usage repository (Stub) - no account stores anything without a database
+#[cfg(feature = "auth")]
+pub struct StubStorageUsageRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl StorageUsageRepository for StubStorageUsageRepository {
+    async fn bytes_stored(
+        &self,
+        _account_id: uuid::Uuid,
+    ) -> Result<i64, apex_core::error::RepoError> {
+        Ok(0)
+    }
+    async fn add_bytes(
+        &self,
+        _account_id: uuid::Uuid,
+        _delta: i64,
+    ) -> Result<i64, apex_core::error::RepoError> {
+        Ok(0)
+    }
+}
+
+/// Plan repository (Stub) - every account is on the free plan without a database
+pub struct StubPlanRepository;
+#[async_trait::async_trait]
+impl PlanRepository for StubPlanRepository {
+    async fn get_plan(
+        &self,
+        _account_id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::Plan>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn set_plan(
+        &self,
+        _account_id: uuid::Uuid,
+        _plan: apex_core::domain::Plan,
+    ) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+}
+
+/// Subscription repository (Stub) - no account has a subscription without a database
+pub struct StubSubscriptionRepository;
+#[async_trait::async_trait]
+impl SubscriptionRepository for StubSubscriptionRepository {
+    async fn find_by_account(
+        &self,
+        _account_id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::Subscription>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        s: apex_core::domain::Subscription,
+    ) -> Result<apex_core::domain::Subscription, apex_core::error::RepoError> {
+        Ok(s)
+    }
+    async fn list_trials_ending_before(
+        &self,
+        _at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<apex_core::domain::Subscription>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Consent repository (Stub) - acceptances are not persisted without a database
+#[cfg(feature = "auth")]
+pub struct StubConsentRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl ConsentRepository for StubConsentRepository {
+    async fn record(
+        &self,
+        acceptance: apex_core::domain::PolicyAcceptance,
+    ) -> Result<apex_core::domain::PolicyAcceptance, apex_core::error::RepoError> {
+        Ok(acceptance)
+    }
+    async fn list_for_user(
+        &self,
+        _user_id: uuid::Uuid,
+    ) -> Result<Vec<apex_core::domain::PolicyAcceptance>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Legal hold repository (Stub) - holds are not persisted without a database
+pub struct StubLegalHoldRepository;
+#[async_trait::async_trait]
+impl LegalHoldRepository for StubLegalHoldRepository {
+    async fn record(
+        &self,
+        change: apex_core::domain::LegalHoldChange,
+    ) -> Result<apex_core::domain::LegalHoldChange, apex_core::error::RepoError> {
+        Ok(change)
+    }
+    async fn history(
+        &self,
+        _user_id: uuid::Uuid,
+    ) -> Result<Vec<apex_core::domain::LegalHoldChange>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
+
+/// Pending admin operation repository (Stub) - operations are not persisted without a database
+#[cfg(feature = "auth")]
+pub struct StubPendingOperationRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::PendingOperation, uuid::Uuid>
+    for StubPendingOperationRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::PendingOperation>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        op: apex_core::domain::PendingOperation,
+    ) -> Result<apex_core::domain::PendingOperation, apex_core::error::RepoError> {
+        Ok(op)
+    }
+    async fn insert(
+        &self,
+        op: apex_core::domain::PendingOperation,
+    ) -> Result<apex_core::domain::PendingOperation, apex_core::error::RepoError> {
+        Ok(op)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<
+        apex_core::domain::Page<apex_core::domain::PendingOperation>,
+        apex_core::error::RepoError,
+    > {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl PendingOperationRepository for StubPendingOperationRepository {
+    async fn list(
+        &self,
+        _status: Option<apex_core::domain::ApprovalStatus>,
+        _limit: u64,
+    ) -> Result<Vec<apex_core::domain::PendingOperation>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+    async fn decide(
+        &self,
+        _operation: &apex_core::domain::PendingOperation,
+    ) -> Result<bool, apex_core::error::RepoError> {
+        Ok(false)
+    }
+}
+
+/// Custom domain repository (Stub) - no host maps to an organization without a database
+#[cfg(feature = "auth")]
+pub struct StubCustomDomainRepository;
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl apex_core::ports::BaseRepository<apex_core::domain::CustomDomain, uuid::Uuid>
+    for StubCustomDomainRepository
+{
+    async fn find_by_id(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<apex_core::domain::CustomDomain>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn save(
+        &self,
+        d: apex_core::domain::CustomDomain,
+    ) -> Result<apex_core::domain::CustomDomain, apex_core::error::RepoError> {
+        Ok(d)
+    }
+    async fn insert(
+        &self,
+        d: apex_core::domain::CustomDomain,
+    ) -> Result<apex_core::domain::CustomDomain, apex_core::error::RepoError> {
+        Ok(d)
+    }
+    async fn delete(&self, _id: uuid::Uuid) -> Result<(), apex_core::error::RepoError> {
+        Ok(())
+    }
+    async fn find_page(
+        &self,
+        request: apex_core::domain::PageRequest,
+    ) -> Result<apex_core::domain::Page<apex_core::domain::CustomDomain>, apex_core::error::RepoError>
+    {
+        Ok(apex_core::domain::Page::empty(&request))
+    }
+}
+#[cfg(feature = "auth")]
+#[async_trait::async_trait]
+impl CustomDomainRepository for StubCustomDomainRepository {
+    async fn find_by_hostname(
+        &self,
+        _hostname: &str,
+    ) -> Result<Option<apex_core::domain::CustomDomain>, apex_core::error::RepoError> {
+        Ok(None)
+    }
+    async fn list_by_organization(
+        &self,
+        _organization_id: uuid::Uuid,
+    ) -> Result<Vec<apex_core::domain::CustomDomain>, apex_core::error::RepoError> {
+        Ok(vec![])
+    }
+}
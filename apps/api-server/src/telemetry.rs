@@ -1,8 +1,16 @@
 //! Telemetry initialization - tracing and alerting setup.
 
+use std::sync::Arc;
+
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::observability::AlertLayer;
+use apex_core::ports::WebhookSender;
+
+use crate::env;
+use crate::observability::{
+    AlertConfig, AlertDispatcher, AlertLayer, AlertSender, ConsoleAlertSender, PiiFields, PiiJson,
+    PiiJsonFields, WebhookAlertSender,
+};
 
 /// Telemetry configuration.
 #[derive(Debug, Clone)]
@@ -32,48 +40,45 @@ impl TelemetryConfig {
     /// Load configuration from environment variables.
     pub fn from_env() -> Self {
         Self {
-            json_logs: std::env::var("LOG_FORMAT")
-                .map(|v| v.to_lowercase() == "json")
-                .unwrap_or(false),
-            service_name: std::env::var("OTEL_SERVICE_NAME")
-                .unwrap_or_else(|_| "apex-api".to_string()),
-            alerts_enabled: std::env::var("ALERTS_ENABLED")
-                .map(|v| v != "false" && v != "0")
-                .unwrap_or(true),
-            alert_webhook_url: std::env::var("ALERT_WEBHOOK_URL").ok(),
+            json_logs: env::LOG_FORMAT.string().eq_ignore_ascii_case("json"),
+            service_name: env::OTEL_SERVICE_NAME.string(),
+            alerts_enabled: env::ALERTS_ENABLED.flag(),
+            alert_webhook_url: env::ALERT_WEBHOOK_URL.raw(),
         }
     }
 }
 
 /// Initialize telemetry (tracing and alerting).
-pub fn init_telemetry(config: &TelemetryConfig) {
+///
+/// Returns the alert dispatcher when alerting is enabled; start it with
+/// [`alert_sender`] once the outbound services are available.
+pub fn init_telemetry(config: &TelemetryConfig) -> Option<AlertDispatcher> {
     let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,api_server=debug,apex_infra=debug"));
+        .unwrap_or_else(|_| EnvFilter::new(env::RUST_LOG.default.unwrap_or_default()));
 
     // Create alert layer if enabled
-    let alert_layer = if config.alerts_enabled {
-        let layer = if let Some(webhook_url) = &config.alert_webhook_url {
-            tracing::info!("Alert webhook configured");
-            AlertLayer::webhook(webhook_url.clone())
-        } else {
-            AlertLayer::console()
-        };
-        Some(layer)
+    let (alert_layer, alert_dispatcher) = if config.alerts_enabled {
+        let (layer, dispatcher) = AlertLayer::new(AlertConfig::default());
+        (Some(layer), Some(dispatcher))
     } else {
-        None
+        (None, None)
     };
 
-    // Build and init subscriber based on log format
+    // Build and init subscriber based on log format; both mask sensitive fields
     if config.json_logs {
         tracing_subscriber::registry()
             .with(env_filter)
-            .with(tracing_subscriber::fmt::layer().json())
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .event_format(PiiJson)
+                    .fmt_fields(PiiJsonFields),
+            )
             .with(alert_layer)
             .init();
     } else {
         tracing_subscriber::registry()
             .with(env_filter)
-            .with(tracing_subscriber::fmt::layer().pretty())
+            .with(tracing_subscriber::fmt::layer().fmt_fields(PiiFields))
             .with(alert_layer)
             .init();
     }
@@ -84,4 +89,23 @@ pub fn init_telemetry(config: &TelemetryConfig) {
         alerts_enabled = config.alerts_enabled,
         "Telemetry initialized"
     );
+
+    alert_dispatcher
+}
+
+/// Build the alert sender for the configured channel.
+///
+/// Webhook alerts are delivered through `webhooks`, so sandbox mode and
+/// delivery auditing apply to them too.
+pub fn alert_sender(
+    config: &TelemetryConfig,
+    webhooks: Arc<dyn WebhookSender>,
+) -> Arc<dyn AlertSender> {
+    match &config.alert_webhook_url {
+        Some(webhook_url) => {
+            tracing::info!("Alert webhook configured");
+            Arc::new(WebhookAlertSender::new(webhook_url.clone(), webhooks))
+        }
+        None => Arc::new(ConsoleAlertSender),
+    }
 }
@@ -0,0 +1,151 @@
+//! WebSocket connection quality metrics.
+//!
+//! Per namespace: open connections, connects and reconnects, and the round
+//! trip times of the server's heartbeats, so realtime health can be watched
+//! per deployment (`GET /api/admin/realtime`).
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use apex_infra::env::EnvVar;
+
+use crate::env;
+
+/// Round trip samples kept per namespace for percentiles.
+const MAX_SAMPLES: usize = 1_000;
+
+/// Connection counters of every namespace.
+#[derive(Default)]
+pub struct ConnectionMetrics {
+    namespaces: Mutex<BTreeMap<String, Namespace>>,
+}
+
+#[derive(Default)]
+struct Namespace {
+    connected: u64,
+    connects: u64,
+    reconnects: u64,
+    disconnects: u64,
+    heartbeats: u64,
+    missed_heartbeats: u64,
+    /// Latest round trips, oldest first.
+    rtts: VecDeque<Duration>,
+}
+
+/// A namespace's counters at one point in time.
+#[cfg(feature = "auth")]
+#[derive(Debug, Clone, Default)]
+pub struct NamespaceSnapshot {
+    pub namespace: String,
+    /// Open connections.
+    pub connected: u64,
+    pub connects: u64,
+    /// Connects of clients that said they had been connected before.
+    pub reconnects: u64,
+    pub disconnects: u64,
+    /// Heartbeats acknowledged.
+    pub heartbeats: u64,
+    /// Heartbeats not acknowledged in time.
+    pub missed_heartbeats: u64,
+    pub rtt_p50: Option<Duration>,
+    pub rtt_p99: Option<Duration>,
+    pub rtt_max: Option<Duration>,
+}
+
+impl ConnectionMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connected(&self, namespace: &str, reconnect: bool) {
+        self.update(namespace, |ns| {
+            ns.connected += 1;
+            ns.connects += 1;
+            if reconnect {
+                ns.reconnects += 1;
+            }
+        });
+    }
+
+    pub fn disconnected(&self, namespace: &str) {
+        self.update(namespace, |ns| {
+            ns.connected = ns.connected.saturating_sub(1);
+            ns.disconnects += 1;
+        });
+    }
+
+    /// Record an acknowledged heartbeat and how long the ack took.
+    pub fn heartbeat(&self, namespace: &str, rtt: Duration) {
+        self.update(namespace, |ns| {
+            ns.heartbeats += 1;
+            if ns.rtts.len() == MAX_SAMPLES {
+                ns.rtts.pop_front();
+            }
+            ns.rtts.push_back(rtt);
+        });
+    }
+
+    pub fn missed_heartbeat(&self, namespace: &str) {
+        self.update(namespace, |ns| ns.missed_heartbeats += 1);
+    }
+
+    /// Every namespace's counters, by name.
+    #[cfg(feature = "auth")]
+    pub fn snapshot(&self) -> Vec<NamespaceSnapshot> {
+        let namespaces = self.namespaces.lock().unwrap();
+        namespaces
+            .iter()
+            .map(|(name, ns)| {
+                let mut rtts: Vec<Duration> = ns.rtts.iter().copied().collect();
+                rtts.sort_unstable();
+                let percentile =
+                    |p: usize| (!rtts.is_empty()).then(|| rtts[(rtts.len() * p).div_ceil(100) - 1]);
+                NamespaceSnapshot {
+                    namespace: name.clone(),
+                    connected: ns.connected,
+                    connects: ns.connects,
+                    reconnects: ns.reconnects,
+                    disconnects: ns.disconnects,
+                    heartbeats: ns.heartbeats,
+                    missed_heartbeats: ns.missed_heartbeats,
+                    rtt_p50: percentile(50),
+                    rtt_p99: percentile(99),
+                    rtt_max: rtts.last().copied(),
+                }
+            })
+            .collect()
+    }
+
+    fn update(&self, namespace: &str, f: impl FnOnce(&mut Namespace)) {
+        let mut namespaces = self.namespaces.lock().unwrap();
+        f(namespaces.entry(namespace.to_string()).or_default());
+    }
+}
+
+/// How often the server heartbeats each client and how long it waits for
+/// the ack.
+#[derive(Debug, Clone, Copy)]
+pub struct Heartbeat {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl Heartbeat {
+    /// `WS_HEARTBEAT_SECS` (25 by default) and `WS_HEARTBEAT_TIMEOUT_SECS`
+    /// (10 by default).
+    pub fn from_env() -> Self {
+        // Zero would never ping, or drop every client
+        let secs = |var: EnvVar| {
+            var.parse::<u64>()
+                .filter(|&secs| secs > 0)
+                .or_else(|| var.default?.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or_default()
+        };
+        Self {
+            interval: secs(env::WS_HEARTBEAT_SECS),
+            timeout: secs(env::WS_HEARTBEAT_TIMEOUT_SECS),
+        }
+    }
+}
@@ -1,25 +1,52 @@
 //! WebSocket handlers using socketioxide.
 
+mod metrics;
+
+pub use metrics::{ConnectionMetrics, Heartbeat};
+
+use serde::Deserialize;
 use socketioxide::{
     SocketIo,
-    extract::{Data, SocketRef},
+    extract::{Data, SocketRef, TryData},
 };
 use std::sync::Arc;
+use std::time::Instant;
 
-use apex_infra::InMemoryPubSub;
+use apex_core::ports::Envelope;
+use apex_infra::announcements::ANNOUNCEMENTS_CHANNEL;
+use apex_infra::{InMemoryPubSub, TypedPubSub};
+use apex_shared::dto::AnnouncementResponse;
 
 /// Shared state for WebSocket handlers.
 #[derive(Clone)]
 pub struct WsState {
-    pub pubsub: Arc<InMemoryPubSub>,
+    pub pubsub: Arc<TypedPubSub<InMemoryPubSub>>,
+    pub metrics: Arc<ConnectionMetrics>,
+    pub heartbeat: Heartbeat,
+}
+
+/// Auth payload clients may send when connecting.
+#[derive(Debug, Deserialize)]
+struct ConnectAuth {
+    /// Set by clients reconnecting after losing their connection.
+    #[serde(default)]
+    reconnect: bool,
 }
 
 /// Configure WebSocket handlers.
-pub fn configure_socket_handlers(io: SocketIo, _state: WsState) {
-    io.ns("/", move |socket: SocketRef| {
+pub fn configure_socket_handlers(io: SocketIo, state: WsState) {
+    forward_announcements(io.clone(), state.pubsub.clone());
+
+    let metrics = state.metrics.clone();
+    let heartbeat = state.heartbeat;
+    io.ns("/", move |socket: SocketRef, TryData(auth): TryData<ConnectAuth>| {
+        let metrics = metrics.clone();
         async move {
             let socket_id = socket.id.to_string();
-            tracing::info!(socket_id = %socket_id, "Client connected");
+            let reconnect = auth.is_ok_and(|auth| auth.reconnect);
+            tracing::info!(socket_id = %socket_id, reconnect, "Client connected");
+            metrics.connected(socket.ns(), reconnect);
+            tokio::spawn(send_heartbeats(socket.clone(), metrics.clone(), heartbeat));
 
             // Handle join room
             socket.on("join", |socket: SocketRef, Data::<String>(room)| async move {
@@ -54,13 +81,81 @@ pub fn configure_socket_handlers(io: SocketIo, _state: WsState) {
             });
 
             // Handle disconnect
-            socket.on_disconnect(|socket: SocketRef| async move {
+            socket.on_disconnect(move |socket: SocketRef| async move {
                 tracing::info!(socket_id = %socket.id, "Client disconnected");
+                metrics.disconnected(socket.ns());
             });
         }
     });
 }
 
+/// Send `heartbeat` events to a client until it disconnects, recording
+/// how long its acks take and the heartbeats it does not ack in time.
+async fn send_heartbeats(socket: SocketRef, metrics: Arc<ConnectionMetrics>, heartbeat: Heartbeat) {
+    let mut ticks = tokio::time::interval(heartbeat.interval);
+    // The first tick is immediate; the client just connected
+    ticks.tick().await;
+    let (mut acked, mut missed) = (0u64, 0u64);
+    loop {
+        ticks.tick().await;
+        if !socket.connected() {
+            break;
+        }
+        let sent = Instant::now();
+        let ack = socket
+            .timeout(heartbeat.timeout)
+            .emit_with_ack::<_, serde_json::Value>("heartbeat", chrono::Utc::now().to_rfc3339());
+        let acked_in_time = match ack {
+            Ok(ack) => ack.await.is_ok(),
+            Err(_) => false,
+        };
+        if acked_in_time {
+            acked += 1;
+            metrics.heartbeat(socket.ns(), sent.elapsed());
+        } else if socket.connected() {
+            missed += 1;
+            metrics.missed_heartbeat(socket.ns());
+            tracing::debug!(socket_id = %socket.id, "Heartbeat not acknowledged");
+        }
+    }
+    tracing::debug!(socket_id = %socket.id, acked, missed, "Heartbeats stopped");
+}
+
+/// Push published announcements to clients as `announcement` events.
+///
+/// Announcements for everyone go to all clients; targeted ones go to the room
+/// named after the audience (e.g. `plan:pro`, `org:<id>`), which clients join.
+fn forward_announcements(io: SocketIo, pubsub: Arc<TypedPubSub<InMemoryPubSub>>) {
+    tokio::spawn(async move {
+        let result = pubsub
+            .subscribe_json(
+                ANNOUNCEMENTS_CHANNEL,
+                move |envelope: Envelope<AnnouncementResponse>| {
+                    let io = io.clone();
+                    async move {
+                        let announcement = envelope.data;
+                        let sent = if announcement.audience == "everyone" {
+                            io.emit("announcement", &announcement)
+                        } else {
+                            io.to(announcement.audience.clone())
+                                .emit("announcement", &announcement)
+                        };
+                        if let Err(e) = sent {
+                            tracing::warn!(error = %e, "Failed to push announcement");
+                        }
+                    }
+                },
+            )
+            .await;
+
+        // Forwarding runs for the life of the server
+        match result {
+            Ok(subscription) => subscription.detach(),
+            Err(e) => tracing::error!(error = %e, "Failed to subscribe to announcements"),
+        }
+    });
+}
+
 /// Create SocketIO layer for integration.
 pub fn create_socketio_layer(state: WsState) -> (socketioxide::layer::SocketIoLayer, SocketIo) {
     let (layer, io) = SocketIo::new_layer();
@@ -0,0 +1,64 @@
+//! Create webhook deliveries (outbound audit log) table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDeliveries::Table)
+                    .if_not_exists()
+                    .col(pk_uuid(WebhookDeliveries::Id))
+                    .col(text(WebhookDeliveries::Url))
+                    .col(json_binary(WebhookDeliveries::Payload))
+                    .col(integer_null(WebhookDeliveries::ResponseStatus))
+                    .col(text_null(WebhookDeliveries::ResponseBody))
+                    .col(text_null(WebhookDeliveries::Error))
+                    .col(big_integer(WebhookDeliveries::LatencyMs))
+                    .col(integer(WebhookDeliveries::Attempt))
+                    .col(uuid_null(WebhookDeliveries::ReplayOf))
+                    .col(timestamp_with_time_zone(WebhookDeliveries::CreatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // The admin console lists newest deliveries first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_deliveries_created_at")
+                    .table(WebhookDeliveries::Table)
+                    .col(WebhookDeliveries::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDeliveries::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WebhookDeliveries {
+    Table,
+    Id,
+    Url,
+    Payload,
+    ResponseStatus,
+    ResponseBody,
+    Error,
+    LatencyMs,
+    Attempt,
+    ReplayOf,
+    CreatedAt,
+}
@@ -0,0 +1,94 @@
+//! Create organization memberships table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Memberships::Table)
+                    .if_not_exists()
+                    .col(pk_uuid(Memberships::Id))
+                    .col(uuid(Memberships::OrganizationId))
+                    .col(uuid(Memberships::UserId))
+                    .col(string(Memberships::Role))
+                    .col(timestamp_with_time_zone(Memberships::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-memberships-organization_id")
+                            .from(Memberships::Table, Memberships::OrganizationId)
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-memberships-user_id")
+                            .from(Memberships::Table, Memberships::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A user belongs to an organization at most once
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memberships_organization_user")
+                    .table(Memberships::Table)
+                    .col(Memberships::OrganizationId)
+                    .col(Memberships::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memberships_user_id")
+                    .table(Memberships::Table)
+                    .col(Memberships::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Memberships::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Memberships {
+    Table,
+    Id,
+    OrganizationId,
+    UserId,
+    Role,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Organizations {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
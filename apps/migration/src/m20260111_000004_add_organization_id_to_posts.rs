@@ -0,0 +1,100 @@
+//! Scope posts to organizations.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == sea_orm::DatabaseBackend::Sqlite {
+            // SQLite cannot add a constraint to an existing table, only a
+            // column carrying one
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Posts::Table)
+                        .add_column(uuid_null(Posts::OrganizationId).extra(
+                            r#"REFERENCES "organizations" ("id") ON DELETE CASCADE ON UPDATE CASCADE"#,
+                        ))
+                        .to_owned(),
+                )
+                .await?;
+        } else {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Posts::Table)
+                        .add_column(uuid_null(Posts::OrganizationId))
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name("fk-posts-organization_id")
+                                .from_tbl(Posts::Table)
+                                .from_col(Posts::OrganizationId)
+                                .to_tbl(Organizations::Table)
+                                .to_col(Organizations::Id)
+                                .on_delete(ForeignKeyAction::Cascade)
+                                .on_update(ForeignKeyAction::Cascade),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_posts_organization_id")
+                    .table(Posts::Table)
+                    .col(Posts::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == sea_orm::DatabaseBackend::Sqlite {
+            manager
+                .drop_index(
+                    Index::drop()
+                        .name("idx_posts_organization_id")
+                        .table(Posts::Table)
+                        .to_owned(),
+                )
+                .await?;
+            return manager
+                .alter_table(
+                    Table::alter()
+                        .table(Posts::Table)
+                        .drop_column(Posts::OrganizationId)
+                        .to_owned(),
+                )
+                .await;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posts::Table)
+                    .drop_foreign_key(Alias::new("fk-posts-organization_id"))
+                    .drop_column(Posts::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    OrganizationId,
+}
+
+#[derive(DeriveIden)]
+enum Organizations {
+    Table,
+    Id,
+}
@@ -0,0 +1,44 @@
+//! Create settings (per-scope JSON documents) table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Settings::Table)
+                    .if_not_exists()
+                    .col(string(Settings::ScopeKind))
+                    .col(uuid(Settings::ScopeId))
+                    .col(json_binary(Settings::Values))
+                    .col(timestamp_with_time_zone(Settings::UpdatedAt))
+                    .primary_key(
+                        Index::create()
+                            .col(Settings::ScopeKind)
+                            .col(Settings::ScopeId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Settings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Settings {
+    Table,
+    ScopeKind,
+    ScopeId,
+    Values,
+    UpdatedAt,
+}
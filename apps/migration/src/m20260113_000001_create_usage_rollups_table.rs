@@ -0,0 +1,47 @@
+//! Create monthly usage rollups table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UsageRollups::Table)
+                    .if_not_exists()
+                    .col(uuid(UsageRollups::AccountId))
+                    .col(string(UsageRollups::Metric))
+                    .col(date(UsageRollups::PeriodStart))
+                    .col(big_integer(UsageRollups::Quantity))
+                    .col(timestamp_with_time_zone(UsageRollups::UpdatedAt))
+                    .primary_key(
+                        Index::create()
+                            .col(UsageRollups::AccountId)
+                            .col(UsageRollups::Metric)
+                            .col(UsageRollups::PeriodStart),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UsageRollups::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UsageRollups {
+    Table,
+    AccountId,
+    Metric,
+    PeriodStart,
+    Quantity,
+    UpdatedAt,
+}
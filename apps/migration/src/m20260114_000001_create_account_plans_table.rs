@@ -0,0 +1,37 @@
+//! Create account plans table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountPlans::Table)
+                    .if_not_exists()
+                    .col(uuid(AccountPlans::AccountId).primary_key())
+                    .col(string(AccountPlans::Plan))
+                    .col(timestamp_with_time_zone(AccountPlans::UpdatedAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccountPlans::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AccountPlans {
+    Table,
+    AccountId,
+    Plan,
+    UpdatedAt,
+}
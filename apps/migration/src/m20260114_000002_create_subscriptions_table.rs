@@ -0,0 +1,61 @@
+//! Create subscriptions table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Subscriptions::Table)
+                    .if_not_exists()
+                    .col(uuid(Subscriptions::AccountId).primary_key())
+                    .col(string(Subscriptions::Plan))
+                    .col(string(Subscriptions::Status))
+                    .col(timestamp_with_time_zone_null(Subscriptions::TrialEndsAt))
+                    .col(timestamp_with_time_zone_null(
+                        Subscriptions::CurrentPeriodEnd,
+                    ))
+                    .col(string_null(Subscriptions::ExternalId))
+                    .col(timestamp_with_time_zone(Subscriptions::CreatedAt))
+                    .col(timestamp_with_time_zone(Subscriptions::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // The trial expiry job scans trialing subscriptions by end date
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_subscriptions_status_trial_ends_at")
+                    .table(Subscriptions::Table)
+                    .col(Subscriptions::Status)
+                    .col(Subscriptions::TrialEndsAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Subscriptions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Subscriptions {
+    Table,
+    AccountId,
+    Plan,
+    Status,
+    TrialEndsAt,
+    CurrentPeriodEnd,
+    ExternalId,
+    CreatedAt,
+    UpdatedAt,
+}
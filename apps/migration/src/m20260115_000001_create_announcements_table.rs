@@ -0,0 +1,57 @@
+//! Create announcements table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Announcements::Table)
+                    .if_not_exists()
+                    .col(uuid(Announcements::Id).primary_key())
+                    .col(string(Announcements::Title))
+                    .col(text(Announcements::Body))
+                    .col(string(Announcements::Audience))
+                    .col(timestamp_with_time_zone(Announcements::StartsAt))
+                    .col(timestamp_with_time_zone_null(Announcements::EndsAt))
+                    .col(timestamp_with_time_zone(Announcements::CreatedAt))
+                    .col(timestamp_with_time_zone(Announcements::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_announcements_ends_at")
+                    .table(Announcements::Table)
+                    .col(Announcements::EndsAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Announcements::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Announcements {
+    Table,
+    Id,
+    Title,
+    Body,
+    Audience,
+    StartsAt,
+    EndsAt,
+    CreatedAt,
+    UpdatedAt,
+}
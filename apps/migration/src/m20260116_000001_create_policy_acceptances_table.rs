@@ -0,0 +1,70 @@
+//! Create policy acceptances table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PolicyAcceptances::Table)
+                    .if_not_exists()
+                    .col(uuid(PolicyAcceptances::Id).primary_key())
+                    .col(uuid(PolicyAcceptances::UserId))
+                    .col(string(PolicyAcceptances::Policy))
+                    .col(string(PolicyAcceptances::Version))
+                    .col(timestamp_with_time_zone(PolicyAcceptances::AcceptedAt))
+                    .col(string_null(PolicyAcceptances::IpAddress))
+                    .col(string_null(PolicyAcceptances::UserAgent))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-policy_acceptances-user_id")
+                            .from(PolicyAcceptances::Table, PolicyAcceptances::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Consent checks read a user's acceptances on every request (cache misses)
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_policy_acceptances_user_id")
+                    .table(PolicyAcceptances::Table)
+                    .col(PolicyAcceptances::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PolicyAcceptances::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PolicyAcceptances {
+    Table,
+    Id,
+    UserId,
+    Policy,
+    Version,
+    AcceptedAt,
+    IpAddress,
+    UserAgent,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,73 @@
+//! Create OAuth clients table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OauthClients::Table)
+                    .if_not_exists()
+                    .col(uuid(OauthClients::Id).primary_key())
+                    .col(uuid(OauthClients::OwnerId))
+                    .col(string(OauthClients::Name))
+                    .col(string_uniq(OauthClients::ClientId))
+                    .col(string(OauthClients::ClientSecretHash))
+                    .col(json_binary(OauthClients::RedirectUris))
+                    .col(json_binary(OauthClients::Scopes))
+                    .col(timestamp_with_time_zone(OauthClients::CreatedAt))
+                    .col(timestamp_with_time_zone(OauthClients::UpdatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-oauth_clients-owner_id")
+                            .from(OauthClients::Table, OauthClients::OwnerId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_oauth_clients_owner_id")
+                    .table(OauthClients::Table)
+                    .col(OauthClients::OwnerId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OauthClients::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OauthClients {
+    Table,
+    Id,
+    OwnerId,
+    Name,
+    ClientId,
+    ClientSecretHash,
+    RedirectUris,
+    Scopes,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
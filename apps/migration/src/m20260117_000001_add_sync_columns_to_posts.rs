@@ -0,0 +1,82 @@
+//! Version posts and keep deleted ones as tombstones, for offline sync.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One column per statement, for SQLite
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posts::Table)
+                    .add_column(big_integer(Posts::Version).default(1))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posts::Table)
+                    .add_column(timestamp_with_time_zone_null(Posts::DeletedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Change feed: a user's posts by update time
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_posts_user_id_updated_at")
+                    .table(Posts::Table)
+                    .col(Posts::UserId)
+                    .col(Posts::UpdatedAt)
+                    .col(Posts::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_posts_user_id_updated_at")
+                    .table(Posts::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posts::Table)
+                    .drop_column(Posts::Version)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posts::Table)
+                    .drop_column(Posts::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    Id,
+    UserId,
+    UpdatedAt,
+    Version,
+    DeletedAt,
+}
@@ -0,0 +1,37 @@
+//! Create per-account storage usage table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StorageUsage::Table)
+                    .if_not_exists()
+                    .col(uuid(StorageUsage::AccountId).primary_key())
+                    .col(big_integer(StorageUsage::Bytes).default(0))
+                    .col(timestamp_with_time_zone(StorageUsage::UpdatedAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StorageUsage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StorageUsage {
+    Table,
+    AccountId,
+    Bytes,
+    UpdatedAt,
+}
@@ -0,0 +1,67 @@
+//! Create custom domains table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CustomDomains::Table)
+                    .if_not_exists()
+                    .col(uuid(CustomDomains::Id).primary_key())
+                    .col(uuid(CustomDomains::OrganizationId))
+                    .col(string_uniq(CustomDomains::Hostname))
+                    .col(string(CustomDomains::VerificationToken))
+                    .col(timestamp_with_time_zone_null(CustomDomains::VerifiedAt))
+                    .col(timestamp_with_time_zone(CustomDomains::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-custom_domains-organization_id")
+                            .from(CustomDomains::Table, CustomDomains::OrganizationId)
+                            .to(Organizations::Table, Organizations::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_custom_domains_organization_id")
+                    .table(CustomDomains::Table)
+                    .col(CustomDomains::OrganizationId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CustomDomains::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CustomDomains {
+    Table,
+    Id,
+    OrganizationId,
+    Hostname,
+    VerificationToken,
+    VerifiedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Organizations {
+    Table,
+    Id,
+}
@@ -0,0 +1,56 @@
+//! Create legal hold changes table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // No foreign key to users: the trail has to outlive the user it is about
+        manager
+            .create_table(
+                Table::create()
+                    .table(LegalHoldChanges::Table)
+                    .if_not_exists()
+                    .col(uuid(LegalHoldChanges::Id).primary_key())
+                    .col(uuid(LegalHoldChanges::UserId))
+                    .col(boolean(LegalHoldChanges::Held))
+                    .col(text(LegalHoldChanges::Reason))
+                    .col(uuid(LegalHoldChanges::ChangedBy))
+                    .col(timestamp_with_time_zone(LegalHoldChanges::ChangedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Erasure jobs look up a user's latest change before running
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_legal_hold_changes_user_id_changed_at")
+                    .table(LegalHoldChanges::Table)
+                    .col(LegalHoldChanges::UserId)
+                    .col(LegalHoldChanges::ChangedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LegalHoldChanges::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LegalHoldChanges {
+    Table,
+    Id,
+    UserId,
+    Held,
+    Reason,
+    ChangedBy,
+    ChangedAt,
+}
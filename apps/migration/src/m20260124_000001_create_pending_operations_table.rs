@@ -0,0 +1,62 @@
+//! Create pending operations table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // No foreign keys to users: the record has to outlive the admins on it
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingOperations::Table)
+                    .if_not_exists()
+                    .col(uuid(PendingOperations::Id).primary_key())
+                    .col(json_binary(PendingOperations::Action))
+                    .col(string_len(PendingOperations::Status, 16))
+                    .col(uuid(PendingOperations::RequestedBy))
+                    .col(timestamp_with_time_zone(PendingOperations::RequestedAt))
+                    .col(timestamp_with_time_zone(PendingOperations::ExpiresAt))
+                    .col(uuid_null(PendingOperations::DecidedBy))
+                    .col(timestamp_with_time_zone_null(PendingOperations::DecidedAt))
+                    .col(text_null(PendingOperations::Outcome))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Admins list what is waiting for them, newest first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pending_operations_status_requested_at")
+                    .table(PendingOperations::Table)
+                    .col(PendingOperations::Status)
+                    .col(PendingOperations::RequestedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PendingOperations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PendingOperations {
+    Table,
+    Id,
+    Action,
+    Status,
+    RequestedBy,
+    RequestedAt,
+    ExpiresAt,
+    DecidedBy,
+    DecidedAt,
+    Outcome,
+}
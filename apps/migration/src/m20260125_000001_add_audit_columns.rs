@@ -0,0 +1,60 @@
+//! Who created and last changed each user and post.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Plain columns rather than foreign keys: the record outlives the
+        // account that made the change. One column per statement, for SQLite
+        for statement in [
+            add(Users::Table, Users::CreatedBy),
+            add(Users::Table, Users::UpdatedBy),
+            add(Posts::Table, Posts::CreatedBy),
+            add(Posts::Table, Posts::UpdatedBy),
+        ] {
+            manager.alter_table(statement).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for statement in [
+            drop(Posts::Table, Posts::UpdatedBy),
+            drop(Posts::Table, Posts::CreatedBy),
+            drop(Users::Table, Users::UpdatedBy),
+            drop(Users::Table, Users::CreatedBy),
+        ] {
+            manager.alter_table(statement).await?;
+        }
+        Ok(())
+    }
+}
+
+fn add(table: impl IntoIden + 'static, column: impl IntoIden + 'static) -> TableAlterStatement {
+    Table::alter()
+        .table(table)
+        .add_column(uuid_null(column))
+        .to_owned()
+}
+
+fn drop(table: impl IntoIden + 'static, column: impl IntoIden + 'static) -> TableAlterStatement {
+    Table::alter().table(table).drop_column(column).to_owned()
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    CreatedBy,
+    UpdatedBy,
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    CreatedBy,
+    UpdatedBy,
+}
@@ -0,0 +1,44 @@
+//! Index for paging a user's posts by cursor, newest first.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Seeks straight to the cursor's row, where an offset would read
+        // and discard every post before it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_posts_user_id_created_at")
+                    .table(Posts::Table)
+                    .col(Posts::UserId)
+                    .col(Posts::CreatedAt)
+                    .col(Posts::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_posts_user_id_created_at")
+                    .table(Posts::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    UserId,
+    CreatedAt,
+    Id,
+}
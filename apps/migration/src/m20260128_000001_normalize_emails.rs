@@ -0,0 +1,51 @@
+//! Emails of users and invitations, lowercased to match how they are now
+//! parsed, so lookups by the normalized address find older rows.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Fails on the users' unique index if an address was registered
+        // twice in different letter cases; those accounts need merging first
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Users::Table)
+                    .value(Users::Email, Func::lower(Expr::col(Users::Email)))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Invitations::Table)
+                    .value(
+                        Invitations::Email,
+                        Func::lower(Expr::col(Invitations::Email)),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // The original casing is gone, and lowercase addresses are still valid
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Email,
+}
+
+#[derive(DeriveIden)]
+enum Invitations {
+    Table,
+    Email,
+}
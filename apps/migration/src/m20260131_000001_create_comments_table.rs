@@ -0,0 +1,83 @@
+//! Create comments table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Comments::Table)
+                    .if_not_exists()
+                    .col(pk_uuid(Comments::Id))
+                    .col(uuid(Comments::PostId))
+                    .col(uuid(Comments::UserId))
+                    .col(text(Comments::Body))
+                    .col(timestamp_with_time_zone(Comments::CreatedAt))
+                    .col(timestamp_with_time_zone(Comments::UpdatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comments-post_id")
+                            .from(Comments::Table, Comments::PostId)
+                            .to(Posts::Table, Posts::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comments-user_id")
+                            .from(Comments::Table, Comments::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A post's comments are listed oldest first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_comments_post_id_created_at")
+                    .table(Comments::Table)
+                    .col(Comments::PostId)
+                    .col(Comments::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Comments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Comments {
+    Table,
+    Id,
+    PostId,
+    UserId,
+    Body,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,75 @@
+//! Create post tags table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PostTags::Table)
+                    .if_not_exists()
+                    .col(uuid(PostTags::PostId))
+                    .col(uuid(PostTags::TagId))
+                    .primary_key(Index::create().col(PostTags::PostId).col(PostTags::TagId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-post_tags-post_id")
+                            .from(PostTags::Table, PostTags::PostId)
+                            .to(Posts::Table, Posts::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-post_tags-tag_id")
+                            .from(PostTags::Table, PostTags::TagId)
+                            .to(Tags::Table, Tags::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Filtering posts by tag looks links up by tag
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_post_tags_tag_id")
+                    .table(PostTags::Table)
+                    .col(PostTags::TagId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PostTags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PostTags {
+    Table,
+    PostId,
+    TagId,
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Tags {
+    Table,
+    Id,
+}
@@ -0,0 +1,88 @@
+//! Create attachments table migration.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attachments::Table)
+                    .if_not_exists()
+                    .col(pk_uuid(Attachments::Id))
+                    .col(uuid(Attachments::UserId))
+                    .col(uuid_null(Attachments::OrganizationId))
+                    .col(uuid_null(Attachments::PostId))
+                    .col(string_uniq(Attachments::Key))
+                    .col(string(Attachments::Filename))
+                    .col(string(Attachments::ContentType))
+                    .col(big_integer(Attachments::SizeBytes))
+                    .col(timestamp_with_time_zone(Attachments::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachments-user_id")
+                            .from(Attachments::Table, Attachments::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    // The file outlives the post it was attached to
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachments-post_id")
+                            .from(Attachments::Table, Attachments::PostId)
+                            .to(Posts::Table, Posts::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_attachments_post_id")
+                    .table(Attachments::Table)
+                    .col(Attachments::PostId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attachments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Attachments {
+    Table,
+    Id,
+    UserId,
+    OrganizationId,
+    PostId,
+    Key,
+    Filename,
+    ContentType,
+    SizeBytes,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    Id,
+}
@@ -5,10 +5,7 @@ use sea_orm_migration::prelude::*;
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().ok();
-    
-    tracing_subscriber::fmt()
-        .with_env_filter("info")
-        .init();
 
+    // The CLI sets up its own log output (`-v` for SQL statements)
     cli::run_cli(migration::Migrator).await;
 }
@@ -0,0 +1,82 @@
+//! Request context: who a unit of work is for and how long it may take.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Per-request values that services read without taking them as arguments.
+///
+/// Set once per request by the server and carried into jobs the request
+/// enqueues, so logs, audit records and background work can be traced back
+/// to the request that caused them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestContext {
+    pub request_id: String,
+    /// Authenticated caller, once known.
+    pub user_id: Option<Uuid>,
+    /// Organization the caller acts for.
+    pub tenant_id: Option<Uuid>,
+    /// Preferred language, e.g. `de-CH`.
+    pub locale: Option<String>,
+    /// When the caller stops waiting. Not enforced; work that can give up
+    /// early should check [`RequestContext::remaining`].
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl RequestContext {
+    pub fn new(request_id: impl Into<String>) -> Self {
+        Self {
+            request_id: request_id.into(),
+            user_id: None,
+            tenant_id: None,
+            locale: None,
+            deadline: None,
+        }
+    }
+
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Set the deadline `timeout` from now.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = chrono::Duration::from_std(timeout)
+            .ok()
+            .map(|timeout| Utc::now() + timeout);
+        self
+    }
+
+    /// Time left until the deadline; zero once it has passed and `None`
+    /// without one.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| (deadline - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_counts_down_to_zero() {
+        let context = RequestContext::new("req-1");
+        assert_eq!(context.remaining(), None);
+        assert!(!context.is_expired());
+
+        let context = context.with_timeout(Duration::from_secs(60));
+        assert!(context.remaining().unwrap() > Duration::from_secs(59));
+
+        let context = RequestContext {
+            deadline: Some(Utc::now() - chrono::Duration::seconds(1)),
+            ..context
+        };
+        assert!(context.is_expired());
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Plan;
+use crate::error::DomainError;
+
+/// Who an announcement is shown to.
+///
+/// Encoded as `everyone`, `role:<role>`, `plan:<plan>` or `org:<id>`; the
+/// encoding doubles as the WebSocket room announcements are pushed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Audience {
+    Everyone,
+    /// Users with a global role, e.g. `admin`.
+    Role(String),
+    /// Accounts on exactly this plan.
+    Plan(Plan),
+    /// Members of one organization.
+    Organization(Uuid),
+}
+
+/// What is known about the viewer when matching an audience.
+#[derive(Debug, Clone, Copy)]
+pub struct Viewer<'a> {
+    pub roles: &'a [String],
+    pub plan: Plan,
+    pub organization_id: Option<Uuid>,
+}
+
+impl Audience {
+    pub fn includes(&self, viewer: &Viewer<'_>) -> bool {
+        match self {
+            Audience::Everyone => true,
+            Audience::Role(role) => viewer.roles.iter().any(|r| r == role),
+            Audience::Plan(plan) => viewer.plan == *plan,
+            Audience::Organization(id) => viewer.organization_id == Some(*id),
+        }
+    }
+}
+
+impl std::fmt::Display for Audience {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Audience::Everyone => f.write_str("everyone"),
+            Audience::Role(role) => write!(f, "role:{}", role),
+            Audience::Plan(plan) => write!(f, "plan:{}", plan),
+            Audience::Organization(id) => write!(f, "org:{}", id),
+        }
+    }
+}
+
+impl std::str::FromStr for Audience {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DomainError::Validation(format!("Unknown audience: {}", s));
+        match s.split_once(':') {
+            None if s == "everyone" => Ok(Audience::Everyone),
+            Some(("role", role)) if !role.is_empty() => Ok(Audience::Role(role.to_string())),
+            Some(("plan", plan)) => Ok(Audience::Plan(plan.parse()?)),
+            Some(("org", id)) => id
+                .parse()
+                .map(Audience::Organization)
+                .map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Serialize for Audience {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Audience {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Admin-managed banner shown to an audience during a time window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Announcement {
+    pub id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub audience: Audience,
+    pub starts_at: DateTime<Utc>,
+    /// Open-ended when `None`.
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Announcement {
+    pub fn new(
+        title: String,
+        body: String,
+        audience: Audience,
+        starts_at: DateTime<Utc>,
+        ends_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, DomainError> {
+        let now = Utc::now();
+        let mut announcement = Self {
+            id: Uuid::new_v4(),
+            title: String::new(),
+            body: String::new(),
+            audience: Audience::Everyone,
+            starts_at,
+            ends_at,
+            created_at: now,
+            updated_at: now,
+        };
+        announcement.revise(title, body, audience, starts_at, ends_at)?;
+        Ok(announcement)
+    }
+
+    /// Replace the content and schedule, keeping the identity.
+    pub fn revise(
+        &mut self,
+        title: String,
+        body: String,
+        audience: Audience,
+        starts_at: DateTime<Utc>,
+        ends_at: Option<DateTime<Utc>>,
+    ) -> Result<(), DomainError> {
+        if title.trim().is_empty() {
+            return Err(DomainError::Validation(
+                "Announcement title is required".to_string(),
+            ));
+        }
+        if ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+            return Err(DomainError::Validation(
+                "Announcement must end after it starts".to_string(),
+            ));
+        }
+
+        self.title = title;
+        self.body = body;
+        self.audience = audience;
+        self.starts_at = starts_at;
+        self.ends_at = ends_at;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Whether the announcement is showing at `now`.
+    pub fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && self.ends_at.is_none_or(|ends_at| now < ends_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_audience_round_trip_and_matching() {
+        let org = Uuid::new_v4();
+        for audience in [
+            Audience::Everyone,
+            Audience::Role("admin".to_string()),
+            Audience::Plan(Plan::Pro),
+            Audience::Organization(org),
+        ] {
+            assert_eq!(audience.to_string().parse::<Audience>().unwrap(), audience);
+        }
+        assert!("plan:gold".parse::<Audience>().is_err());
+
+        let roles = vec!["user".to_string()];
+        let viewer = Viewer {
+            roles: &roles,
+            plan: Plan::Free,
+            organization_id: Some(org),
+        };
+        assert!(Audience::Organization(org).includes(&viewer));
+        assert!(Audience::Plan(Plan::Free).includes(&viewer));
+        assert!(!Audience::Role("admin".to_string()).includes(&viewer));
+    }
+
+    #[test]
+    fn test_schedule() {
+        let now = Utc::now();
+        let announcement = Announcement::new(
+            "Maintenance".to_string(),
+            "Tonight".to_string(),
+            Audience::Everyone,
+            now,
+            Some(now + Duration::hours(1)),
+        )
+        .unwrap();
+
+        assert!(announcement.is_live(now));
+        assert!(!announcement.is_live(now - Duration::seconds(1)));
+        assert!(!announcement.is_live(now + Duration::hours(1)));
+
+        let backwards = Announcement::new(
+            "Maintenance".to_string(),
+            String::new(),
+            Audience::Everyone,
+            now,
+            Some(now),
+        );
+        assert!(backwards.is_err());
+    }
+}
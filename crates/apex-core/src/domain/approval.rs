@@ -0,0 +1,230 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// A destructive or privileged admin operation, recorded so it can run once
+/// a second admin approves it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminAction {
+    /// Drop every job waiting in the queue.
+    PurgePendingJobs,
+    /// Release a legal hold, letting the user's data be erased again.
+    ReleaseLegalHold { user_id: Uuid, reason: String },
+    /// Grant a privileged role, e.g. `admin`.
+    GrantRole { user_id: Uuid, role: String },
+    /// An action this build does not know, staged by a newer one. It
+    /// cannot run here.
+    #[serde(other)]
+    Unknown,
+}
+
+impl AdminAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AdminAction::PurgePendingJobs => "purge_pending_jobs",
+            AdminAction::ReleaseLegalHold { .. } => "release_legal_hold",
+            AdminAction::GrantRole { .. } => "grant_role",
+            AdminAction::Unknown => "unknown",
+        }
+    }
+}
+
+/// Where a staged operation stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    /// Waiting for a second admin.
+    Pending,
+    /// Approved and running.
+    Approved,
+    /// Ran to completion.
+    Executed,
+    /// Approved, but running it failed.
+    Failed,
+    /// Turned down, or withdrawn by the admin who staged it.
+    Rejected,
+}
+
+impl ApprovalStatus {
+    pub const ALL: [ApprovalStatus; 5] = [
+        ApprovalStatus::Pending,
+        ApprovalStatus::Approved,
+        ApprovalStatus::Executed,
+        ApprovalStatus::Failed,
+        ApprovalStatus::Rejected,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "pending",
+            ApprovalStatus::Approved => "approved",
+            ApprovalStatus::Executed => "executed",
+            ApprovalStatus::Failed => "failed",
+            ApprovalStatus::Rejected => "rejected",
+        }
+    }
+}
+
+impl std::fmt::Display for ApprovalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ApprovalStatus {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ApprovalStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| DomainError::Validation(format!("Unknown approval status: {}", s)))
+    }
+}
+
+/// An admin action staged for a second admin's approval, and what became
+/// of it. Nothing is deleted, so operations double as their audit trail.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingOperation {
+    pub id: Uuid,
+    pub action: AdminAction,
+    pub status: ApprovalStatus,
+    /// Admin who staged it.
+    pub requested_by: Uuid,
+    pub requested_at: DateTime<Utc>,
+    /// Pending operations cannot be approved after this.
+    pub expires_at: DateTime<Utc>,
+    /// Admin who approved or rejected it.
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    /// What running it did, or why it failed.
+    pub outcome: Option<String>,
+}
+
+impl PendingOperation {
+    /// Stage `action`, approvable for `ttl`.
+    pub fn new(action: AdminAction, requested_by: Uuid, ttl: Duration) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            action,
+            status: ApprovalStatus::Pending,
+            requested_by,
+            requested_at: now,
+            expires_at: now + ttl,
+            decided_by: None,
+            decided_at: None,
+            outcome: None,
+        }
+    }
+
+    /// Pending past its expiry.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.status == ApprovalStatus::Pending && now >= self.expires_at
+    }
+
+    /// Approve on behalf of `approver`, who must not be the admin who staged
+    /// it.
+    pub fn approve(&mut self, approver: Uuid) -> Result<(), DomainError> {
+        self.check_pending()?;
+        if approver == self.requested_by {
+            return Err(DomainError::Unauthorized);
+        }
+        self.decide(ApprovalStatus::Approved, approver);
+        Ok(())
+    }
+
+    /// Reject, or withdraw when `admin` staged it.
+    pub fn reject(&mut self, admin: Uuid) -> Result<(), DomainError> {
+        self.check_pending()?;
+        self.decide(ApprovalStatus::Rejected, admin);
+        Ok(())
+    }
+
+    /// Record how running an approved operation went.
+    pub fn finish(&mut self, result: Result<String, String>) {
+        let (status, outcome) = match result {
+            Ok(outcome) => (ApprovalStatus::Executed, outcome),
+            Err(error) => (ApprovalStatus::Failed, error),
+        };
+        self.status = status;
+        self.outcome = Some(outcome);
+    }
+
+    fn check_pending(&self) -> Result<(), DomainError> {
+        if self.status != ApprovalStatus::Pending {
+            return Err(DomainError::Validation(format!(
+                "Operation is already {}",
+                self.status
+            )));
+        }
+        if self.is_expired(Utc::now()) {
+            return Err(DomainError::Validation("Operation has expired".to_string()));
+        }
+        Ok(())
+    }
+
+    fn decide(&mut self, status: ApprovalStatus, admin: Uuid) {
+        self.status = status;
+        self.decided_by = Some(admin);
+        self.decided_at = Some(Utc::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approval_takes_a_second_admin() {
+        let requester = Uuid::new_v4();
+        let mut operation =
+            PendingOperation::new(AdminAction::PurgePendingJobs, requester, Duration::hours(1));
+
+        assert!(matches!(
+            operation.approve(requester),
+            Err(DomainError::Unauthorized)
+        ));
+        let approver = Uuid::new_v4();
+        operation.approve(approver).unwrap();
+        assert_eq!(operation.status, ApprovalStatus::Approved);
+        assert_eq!(operation.decided_by, Some(approver));
+
+        // Decided once only
+        assert!(operation.reject(approver).is_err());
+        operation.finish(Ok("Purged 3 jobs".to_string()));
+        assert_eq!(operation.status, ApprovalStatus::Executed);
+    }
+
+    #[test]
+    fn test_expired_operations_cannot_be_approved() {
+        let mut operation = PendingOperation::new(
+            AdminAction::PurgePendingJobs,
+            Uuid::new_v4(),
+            Duration::zero(),
+        );
+        assert!(operation.is_expired(Utc::now()));
+        assert!(operation.approve(Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn test_unknown_actions_still_load() {
+        let action: AdminAction =
+            serde_json::from_value(serde_json::json!({"type": "drop_everything"})).unwrap();
+        assert_eq!(action, AdminAction::Unknown);
+
+        let release = AdminAction::ReleaseLegalHold {
+            user_id: Uuid::nil(),
+            reason: "Case closed".to_string(),
+        };
+        let json = serde_json::to_value(&release).unwrap();
+        assert_eq!(json["type"], "release_legal_hold");
+        assert_eq!(
+            serde_json::from_value::<AdminAction>(json).unwrap(),
+            release
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// An uploaded file: its metadata, with the bytes kept in object storage
+/// under [`key`](Self::key).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: Uuid,
+    /// User who uploaded it.
+    pub user_id: Uuid,
+    /// Organization it was uploaded for, whose storage quota it counts
+    /// against.
+    pub organization_id: Option<Uuid>,
+    /// Post it is attached to, if any.
+    pub post_id: Option<Uuid>,
+    /// Storage key of the bytes.
+    pub key: String,
+    /// Sanitized name, as offered on download.
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Attachment {
+    /// Prefix of every attachment's storage key.
+    pub const KEY_PREFIX: &'static str = "attachments";
+
+    /// Metadata for a file `user_id` uploads. The name is sanitized and the
+    /// key derived from the new id, so uploads never overwrite each other.
+    pub fn new(
+        user_id: Uuid,
+        filename: &str,
+        content_type: &str,
+        size_bytes: i64,
+    ) -> Result<Self, DomainError> {
+        Self::pending(user_id, filename, content_type)?.with_size(size_bytes)
+    }
+
+    /// Metadata for a file whose bytes are still arriving. Its size is set
+    /// with [`with_size`](Self::with_size) once they are stored.
+    pub fn pending(user_id: Uuid, filename: &str, content_type: &str) -> Result<Self, DomainError> {
+        let content_type = content_type.trim().to_ascii_lowercase();
+        if !content_type.contains('/') {
+            return Err(DomainError::Validation(format!(
+                "Invalid content type: {}",
+                content_type
+            )));
+        }
+        let id = Uuid::new_v4();
+        let filename = sanitize_filename(filename);
+        Ok(Self {
+            id,
+            user_id,
+            organization_id: None,
+            post_id: None,
+            key: format!("{}/{}/{}", Self::KEY_PREFIX, id, filename),
+            filename,
+            content_type,
+            size_bytes: 0,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Set the size of the stored bytes, of which there must be some.
+    pub fn with_size(mut self, size_bytes: i64) -> Result<Self, DomainError> {
+        if size_bytes <= 0 {
+            return Err(DomainError::Validation("File is empty".to_string()));
+        }
+        self.size_bytes = size_bytes;
+        Ok(self)
+    }
+
+    /// Count the attachment against an organization's quota.
+    pub fn in_organization(mut self, organization_id: Uuid) -> Self {
+        self.organization_id = Some(organization_id);
+        self
+    }
+
+    /// Attach it to a post.
+    pub fn on_post(mut self, post_id: Uuid) -> Self {
+        self.post_id = Some(post_id);
+        self
+    }
+
+    /// Account whose storage quota the bytes count against: the
+    /// organization, or else the uploader.
+    pub fn account_id(&self) -> Uuid {
+        self.organization_id.unwrap_or(self.user_id)
+    }
+}
+
+/// The last path segment of `filename`, with anything but letters, digits,
+/// `.`, `_` and `-` replaced by `_`, at most 100 characters. Never empty and
+/// never starting with a dot.
+fn sanitize_filename(filename: &str) -> String {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') => c,
+            _ => '_',
+        })
+        .take(100)
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() {
+        "file".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filenames_are_sanitized_into_the_key() {
+        let user = Uuid::new_v4();
+        let attachment = Attachment::new(user, "../My Photo (1).PNG", "Image/PNG", 3).unwrap();
+        assert_eq!(attachment.filename, "My_Photo__1_.PNG");
+        assert_eq!(attachment.content_type, "image/png");
+        assert_eq!(
+            attachment.key,
+            format!("attachments/{}/My_Photo__1_.PNG", attachment.id)
+        );
+        assert_eq!(attachment.account_id(), user);
+
+        assert_eq!(sanitize_filename("C:\\tmp\\.env"), "env");
+        assert_eq!(sanitize_filename(".."), "file");
+        assert!(Attachment::new(user, "a.txt", "text/plain", 0).is_err());
+        assert!(Attachment::new(user, "a.txt", "text", 1).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+//! IDs generated by clients for the resources they create.
+
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// Validate an ID a client generated for a resource it is creating.
+///
+/// Clients that render optimistically need the ID before the server has
+/// answered, and send the same one again when retrying so the server can
+/// recognise the duplicate. Only random (v4) and time-ordered (v7) UUIDs are
+/// accepted; hand-picked values such as the nil UUID are bound to collide.
+pub fn parse_client_id(raw: &str) -> Result<Uuid, DomainError> {
+    let invalid = || DomainError::Validation(format!("Invalid client-generated id: {}", raw));
+    let id = Uuid::parse_str(raw).map_err(|_| invalid())?;
+    match id.get_version_num() {
+        4 | 7 => Ok(id),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_only_random_and_time_ordered_uuids() {
+        let id = Uuid::new_v4();
+        assert_eq!(parse_client_id(&id.to_string()).unwrap(), id);
+        assert!(parse_client_id("0190b2d2-7a3c-7cc1-8b7e-2f6a4c1d9e01").is_ok());
+
+        assert!(parse_client_id(&Uuid::nil().to_string()).is_err());
+        assert!(parse_client_id("6ba7b810-9dad-11d1-80b4-00c04fd430c8").is_err());
+        assert!(parse_client_id("not-a-uuid").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// A reader's comment on a post.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    /// User who wrote the comment.
+    pub user_id: Uuid,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Comment {
+    /// Longest body a comment may have, in characters.
+    pub const MAX_BODY_LENGTH: usize = 5_000;
+
+    /// A new comment by `user_id` on `post_id`, its body trimmed.
+    pub fn new(post_id: Uuid, user_id: Uuid, body: &str) -> Result<Self, DomainError> {
+        let body = body.trim();
+        if body.is_empty() {
+            return Err(DomainError::Validation(
+                "Comment body is required".to_string(),
+            ));
+        }
+        if body.chars().count() > Self::MAX_BODY_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "Comment body is longer than {} characters",
+                Self::MAX_BODY_LENGTH
+            )));
+        }
+        let now = Utc::now();
+        Ok(Self {
+            id: Uuid::new_v4(),
+            post_id,
+            user_id,
+            body: body.to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_is_trimmed_and_bounded() {
+        let (post, user) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(Comment::new(post, user, "  Nice  ").unwrap().body, "Nice");
+        assert!(Comment::new(post, user, " \n ").is_err());
+
+        let longest = "x".repeat(Comment::MAX_BODY_LENGTH);
+        assert!(Comment::new(post, user, &longest).is_ok());
+        assert!(Comment::new(post, user, &format!("{longest}x")).is_err());
+    }
+}
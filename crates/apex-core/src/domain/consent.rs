@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// Legal document users have to accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyDocument {
+    Terms,
+    Privacy,
+}
+
+impl PolicyDocument {
+    pub const ALL: [PolicyDocument; 2] = [PolicyDocument::Terms, PolicyDocument::Privacy];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyDocument::Terms => "terms",
+            PolicyDocument::Privacy => "privacy",
+        }
+    }
+}
+
+impl std::fmt::Display for PolicyDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PolicyDocument {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PolicyDocument::ALL
+            .into_iter()
+            .find(|p| p.as_str() == s)
+            .ok_or_else(|| DomainError::Validation(format!("Unknown policy: {}", s)))
+    }
+}
+
+/// A user accepting a version of a policy. Acceptances are never updated or
+/// deleted, so they double as the consent audit trail.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyAcceptance {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub policy: PolicyDocument,
+    pub version: String,
+    pub accepted_at: DateTime<Utc>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl PolicyAcceptance {
+    pub fn new(user_id: Uuid, policy: PolicyDocument, version: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            policy,
+            version,
+            accepted_at: Utc::now(),
+            ip_address: None,
+            user_agent: None,
+        }
+    }
+
+    /// Record where the acceptance came from.
+    pub fn with_origin(mut self, ip_address: Option<String>, user_agent: Option<String>) -> Self {
+        self.ip_address = ip_address;
+        self.user_agent = user_agent;
+        self
+    }
+}
+
+/// Policy versions users currently have to accept. Policies without a
+/// version are not required.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyVersions(BTreeMap<PolicyDocument, String>);
+
+impl PolicyVersions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn require(mut self, policy: PolicyDocument, version: impl Into<String>) -> Self {
+        self.0.insert(policy, version.into());
+        self
+    }
+
+    pub fn current(&self, policy: PolicyDocument) -> Option<&str> {
+        self.0.get(&policy).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (PolicyDocument, &str)> {
+        self.0
+            .iter()
+            .map(|(policy, version)| (*policy, version.as_str()))
+    }
+
+    /// Required policies whose current version is not among `accepted`.
+    pub fn outstanding(&self, accepted: &[PolicyAcceptance]) -> Vec<PolicyDocument> {
+        self.iter()
+            .filter(|(policy, version)| {
+                !accepted
+                    .iter()
+                    .any(|a| a.policy == *policy && a.version == *version)
+            })
+            .map(|(policy, _)| policy)
+            .collect()
+    }
+
+    /// Check that `version` is the one users are asked to accept.
+    pub fn validate(&self, policy: PolicyDocument, version: &str) -> Result<(), DomainError> {
+        match self.current(policy) {
+            Some(current) if current == version => Ok(()),
+            Some(current) => Err(DomainError::Validation(format!(
+                "Version {} of the {} policy is not current (current is {})",
+                version, policy, current
+            ))),
+            None => Err(DomainError::Validation(format!(
+                "The {} policy does not need to be accepted",
+                policy
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_version_requires_reconsent() {
+        let user_id = Uuid::new_v4();
+        let accepted = vec![
+            PolicyAcceptance::new(user_id, PolicyDocument::Terms, "2026-01".to_string()),
+            PolicyAcceptance::new(user_id, PolicyDocument::Privacy, "2026-01".to_string()),
+        ];
+
+        let versions = PolicyVersions::new()
+            .require(PolicyDocument::Terms, "2026-01")
+            .require(PolicyDocument::Privacy, "2026-01");
+        assert!(versions.outstanding(&accepted).is_empty());
+
+        let versions = versions.require(PolicyDocument::Terms, "2026-06");
+        assert_eq!(versions.outstanding(&accepted), vec![PolicyDocument::Terms]);
+        assert!(versions.validate(PolicyDocument::Terms, "2026-01").is_err());
+        assert!(versions.validate(PolicyDocument::Terms, "2026-06").is_ok());
+    }
+
+    #[test]
+    fn test_unversioned_policies_are_not_required() {
+        let versions = PolicyVersions::new();
+        assert!(versions.outstanding(&[]).is_empty());
+        assert!(versions.validate(PolicyDocument::Privacy, "1").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// Hostname an organization serves the API under, e.g. `api.acme.com`.
+///
+/// Requests for a domain are routed to its organization once ownership is
+/// proven: a TXT record at [`challenge_name`](Self::challenge_name) holding
+/// [`challenge_value`](Self::challenge_value).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomDomain {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    /// Lowercase, without a trailing dot.
+    pub hostname: String,
+    /// Secret the TXT record has to contain.
+    pub verification_token: String,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CustomDomain {
+    /// Label the TXT challenge is published under, below the hostname.
+    pub const CHALLENGE_LABEL: &'static str = "_apex-challenge";
+
+    /// A new, unverified domain for an organization.
+    pub fn new(organization_id: Uuid, hostname: &str) -> Result<Self, DomainError> {
+        Ok(Self {
+            id: Uuid::new_v4(),
+            organization_id,
+            hostname: normalize_hostname(hostname)?,
+            verification_token: Uuid::new_v4().simple().to_string(),
+            verified_at: None,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Name of the TXT record proving ownership.
+    pub fn challenge_name(&self) -> String {
+        format!("{}.{}", Self::CHALLENGE_LABEL, self.hostname)
+    }
+
+    /// Value the TXT record has to hold.
+    pub fn challenge_value(&self) -> String {
+        format!("apex-domain-verification={}", self.verification_token)
+    }
+
+    /// Whether one of `records` (TXT values at the challenge name) is the
+    /// expected one.
+    pub fn challenge_met(&self, records: &[String]) -> bool {
+        let expected = self.challenge_value();
+        records
+            .iter()
+            .any(|record| record.trim().trim_matches('"') == expected)
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+}
+
+/// Lowercase `hostname` and check it is a fully qualified domain name: at
+/// least two dot-separated labels of letters, digits and inner dashes.
+pub fn normalize_hostname(hostname: &str) -> Result<String, DomainError> {
+    let hostname = hostname.trim().trim_end_matches('.').to_ascii_lowercase();
+    let labels: Vec<&str> = hostname.split('.').collect();
+    let valid = hostname.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+        // A top-level domain is never all digits, so IP addresses are refused
+        && !labels.last().is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()));
+    if !valid {
+        return Err(DomainError::Validation(format!(
+            "Invalid hostname: {}",
+            hostname
+        )));
+    }
+    Ok(hostname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hostnames_are_normalized_and_validated() {
+        assert_eq!(
+            normalize_hostname(" API.Acme.com. ").unwrap(),
+            "api.acme.com"
+        );
+        for invalid in [
+            "localhost",
+            "acme..com",
+            "-acme.com",
+            "acme_x.com",
+            "10.0.0.1",
+            "",
+        ] {
+            assert!(normalize_hostname(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_challenge() {
+        let domain = CustomDomain::new(Uuid::new_v4(), "api.acme.com").unwrap();
+        assert_eq!(domain.challenge_name(), "_apex-challenge.api.acme.com");
+        assert!(!domain.is_verified());
+
+        let quoted = format!("\"{}\"", domain.challenge_value());
+        assert!(domain.challenge_met(&["v=spf1 -all".to_string(), quoted]));
+        assert!(!domain.challenge_met(&["apex-domain-verification=other".to_string()]));
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::DomainError;
+
+/// Longest address SMTP can deliver to (RFC 5321 path limit minus the brackets).
+const MAX_LEN: usize = 254;
+const MAX_LOCAL_LEN: usize = 64;
+
+/// A syntactically valid, normalized email address.
+///
+/// Addresses are trimmed and lowercased when parsed, so two spellings of the
+/// same mailbox compare (and are stored) as equal. Serializes as a plain
+/// string; deserializing validates it again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Email(String);
+
+impl Email {
+    /// Validate and normalize user input.
+    pub fn parse(raw: &str) -> Result<Self, DomainError> {
+        let email = raw.trim().to_lowercase();
+        if is_valid(&email) {
+            Ok(Self(email))
+        } else {
+            Err(DomainError::Validation("Invalid email address".to_string()))
+        }
+    }
+
+    /// An address read back from storage. It was validated when written, so
+    /// it is only normalized here.
+    pub fn from_stored(stored: String) -> Self {
+        Self(stored.trim().to_lowercase())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The part after the `@`.
+    pub fn domain(&self) -> &str {
+        self.0.rsplit_once('@').map_or("", |(_, domain)| domain)
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+fn is_valid(email: &str) -> bool {
+    if email.len() > MAX_LEN || email.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    let local_ok = !local.is_empty()
+        && local.len() <= MAX_LOCAL_LEN
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && !local.contains(['"', '(', ')', ',', ':', ';', '<', '>', '[', '\\', ']']);
+    let labels: Vec<&str> = domain.split('.').collect();
+    let domain_ok = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+    local_ok && domain_ok
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Email {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Email {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Email {
+    type Error = DomainError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Email> for String {
+    fn from(email: Email) -> Self {
+        email.0
+    }
+}
+
+impl PartialEq<str> for Email {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Email {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_normalizes() {
+        let email = Email::parse("  Jane.Doe@Example.COM ").unwrap();
+        assert_eq!(email, "jane.doe@example.com");
+        assert_eq!(email.domain(), "example.com");
+        assert_eq!(email, Email::parse("jane.doe@example.com").unwrap());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_addresses() {
+        for raw in [
+            "",
+            "jane",
+            "jane@",
+            "@example.com",
+            "jane@example",
+            "jane@@example.com",
+            "jane doe@example.com",
+            ".jane@example.com",
+            "ja..ne@example.com",
+            "jane@-example.com",
+            "jane@example..com",
+            "<jane>@example.com",
+        ] {
+            assert!(Email::parse(raw).is_err(), "{raw:?} should be rejected");
+        }
+        assert!(Email::parse("a@b.c").is_ok());
+        assert!(Email::parse("jane+tag@mail.example.co.uk").is_ok());
+    }
+
+    #[test]
+    fn test_serde_is_a_plain_string() {
+        let email = Email::parse("Jane@Example.com").unwrap();
+        assert_eq!(
+            serde_json::to_string(&email).unwrap(),
+            "\"jane@example.com\""
+        );
+
+        let parsed: Email = serde_json::from_str("\"JANE@example.com\"").unwrap();
+        assert_eq!(parsed, email);
+        assert!(serde_json::from_str::<Email>("\"not an email\"").is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Email;
+
+/// Something that happened in the domain, raised by the services after the
+/// change is stored. Side effects (emails, cache invalidation, realtime
+/// pushes) subscribe to these instead of running inline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    UserRegistered {
+        user_id: Uuid,
+        email: Email,
+    },
+    PostCreated {
+        post_id: Uuid,
+        user_id: Uuid,
+    },
+    /// Title or content changed.
+    PostUpdated {
+        post_id: Uuid,
+        user_id: Uuid,
+        /// Whether the post is public, so its change shows in the feeds.
+        published: bool,
+    },
+    PostPublished {
+        post_id: Uuid,
+        user_id: Uuid,
+        published_at: DateTime<Utc>,
+    },
+    PostUnpublished {
+        post_id: Uuid,
+        user_id: Uuid,
+    },
+    PostDeleted {
+        post_id: Uuid,
+        user_id: Uuid,
+        /// Whether the post was public until now.
+        published: bool,
+    },
+}
+
+impl DomainEvent {
+    /// Name the event is published under, e.g. `post.published`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UserRegistered { .. } => "user.registered",
+            Self::PostCreated { .. } => "post.created",
+            Self::PostUpdated { .. } => "post.updated",
+            Self::PostPublished { .. } => "post.published",
+            Self::PostUnpublished { .. } => "post.unpublished",
+            Self::PostDeleted { .. } => "post.deleted",
+        }
+    }
+
+    /// The user the event is about, or who caused it.
+    pub fn user_id(&self) -> Uuid {
+        match self {
+            Self::UserRegistered { user_id, .. }
+            | Self::PostCreated { user_id, .. }
+            | Self::PostUpdated { user_id, .. }
+            | Self::PostPublished { user_id, .. }
+            | Self::PostUnpublished { user_id, .. }
+            | Self::PostDeleted { user_id, .. } => *user_id,
+        }
+    }
+
+    /// Whether the public sitemap and feed change with it.
+    pub fn changes_public_posts(&self) -> bool {
+        match self {
+            Self::PostPublished { .. } | Self::PostUnpublished { .. } => true,
+            Self::PostUpdated { published, .. } | Self::PostDeleted { published, .. } => *published,
+            Self::UserRegistered { .. } | Self::PostCreated { .. } => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_serialize_with_their_type() {
+        let post_id = Uuid::new_v4();
+        let event = DomainEvent::PostDeleted {
+            post_id,
+            user_id: Uuid::new_v4(),
+            published: true,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "post_deleted");
+        assert_eq!(json["post_id"], post_id.to_string());
+        assert_eq!(serde_json::from_value::<DomainEvent>(json).unwrap(), event);
+        assert_eq!(event.name(), "post.deleted");
+        assert!(event.changes_public_posts());
+    }
+}
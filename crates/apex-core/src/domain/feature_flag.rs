@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use crate::error::DomainError;
+
+/// Runtime feature flags, for shipping behavior dark and turning it on
+/// without a deploy. Flags that are not set are off.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlags(BTreeMap<String, bool>);
+
+impl FeatureFlags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(mut self, flag: impl Into<String>, enabled: bool) -> Self {
+        self.0.insert(flag.into(), enabled);
+        self
+    }
+
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.0.get(flag).copied().unwrap_or(false)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.0
+            .iter()
+            .map(|(flag, enabled)| (flag.as_str(), *enabled))
+    }
+
+    /// Parse a comma-separated list of flags, each either a bare name (on) or
+    /// `name=on|off` (also `true|false`, `1|0`), e.g. `new_checkout,beta_search=off`.
+    pub fn parse(spec: &str) -> Result<Self, DomainError> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .try_fold(Self::new(), |flags, entry| {
+                let (flag, enabled) = match entry.split_once('=') {
+                    Some((flag, value)) => (flag.trim(), parse_state(value.trim())?),
+                    None => (entry, true),
+                };
+                if !is_valid_name(flag) {
+                    return Err(DomainError::Validation(format!(
+                        "Invalid feature flag name: {}",
+                        flag
+                    )));
+                }
+                Ok(flags.set(flag, enabled))
+            })
+    }
+
+    /// These flags with `overrides` applied on top.
+    pub fn overridden(&self, overrides: &FeatureFlags) -> Self {
+        let mut flags = self.clone();
+        flags.0.extend(overrides.0.clone());
+        flags
+    }
+}
+
+fn parse_state(value: &str) -> Result<bool, DomainError> {
+    match value {
+        "on" | "true" | "1" => Ok(true),
+        "off" | "false" | "0" => Ok(false),
+        _ => Err(DomainError::Validation(format!(
+            "Invalid feature flag state: {} (expected on or off)",
+            value
+        ))),
+    }
+}
+
+fn is_valid_name(flag: &str) -> bool {
+    !flag.is_empty()
+        && flag.len() <= 64
+        && flag
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flags() {
+        let flags = FeatureFlags::parse("new_checkout, beta_search=off ,dark-mode=1").unwrap();
+        assert!(flags.is_enabled("new_checkout"));
+        assert!(!flags.is_enabled("beta_search"));
+        assert!(flags.is_enabled("dark-mode"));
+        assert!(!flags.is_enabled("unknown"));
+
+        assert!(FeatureFlags::parse("").unwrap().iter().next().is_none());
+        assert!(FeatureFlags::parse("new_checkout=maybe").is_err());
+        assert!(FeatureFlags::parse("New Checkout").is_err());
+    }
+
+    #[test]
+    fn test_overrides_win_over_defaults() {
+        let defaults = FeatureFlags::new()
+            .set("new_checkout", false)
+            .set("beta_search", true);
+        let flags = defaults
+            .overridden(&FeatureFlags::parse("new_checkout,beta_search=off,v2_api").unwrap());
+
+        assert!(flags.is_enabled("new_checkout"));
+        assert!(!flags.is_enabled("beta_search"));
+        assert!(flags.is_enabled("v2_api"));
+        // The defaults themselves are untouched
+        assert!(!defaults.is_enabled("new_checkout"));
+    }
+}
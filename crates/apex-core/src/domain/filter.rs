@@ -0,0 +1,211 @@
+//! Conditions on repository listings.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Condition the items of a listing have to meet, on their stored fields.
+///
+/// Built from comparisons and composed with [`and`](Self::and) and
+/// [`or`](Self::or); repositories translate it to their query language, so
+/// a new combination needs no new repository method:
+/// ```
+/// use apex_core::domain::Filter;
+/// use uuid::Uuid;
+///
+/// let drafts_of = |author: Uuid| Filter::eq("user_id", author).and(Filter::is_null("published_at"));
+/// ```
+/// Like SQL, comparisons never match an unset field; use
+/// [`is_null`](Self::is_null) for those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// `field op value`
+    Compare {
+        field: String,
+        op: CompareOp,
+        value: FilterValue,
+    },
+    /// `field` equals one of the values; none at all never match.
+    In {
+        field: String,
+        values: Vec<FilterValue>,
+    },
+    /// `field` is unset (`null: true`) or set (`null: false`).
+    Null { field: String, null: bool },
+    /// Every condition holds; none at all always do.
+    And(Vec<Filter>),
+    /// One of the conditions holds; none at all never do.
+    Or(Vec<Filter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+/// A value to compare a field with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterValue {
+    Uuid(Uuid),
+    Text(String),
+    Number(i64),
+    Time(DateTime<Utc>),
+}
+
+impl Filter {
+    pub fn compare(field: impl Into<String>, op: CompareOp, value: impl Into<FilterValue>) -> Self {
+        Filter::Compare {
+            field: field.into(),
+            op,
+            value: value.into(),
+        }
+    }
+
+    pub fn eq(field: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(field, CompareOp::Eq, value)
+    }
+
+    pub fn ne(field: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(field, CompareOp::Ne, value)
+    }
+
+    pub fn lt(field: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(field, CompareOp::Lt, value)
+    }
+
+    pub fn lte(field: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(field, CompareOp::Lte, value)
+    }
+
+    pub fn gt(field: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(field, CompareOp::Gt, value)
+    }
+
+    pub fn gte(field: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::compare(field, CompareOp::Gte, value)
+    }
+
+    pub fn is_in<V: Into<FilterValue>>(
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Filter::In {
+            field: field.into(),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn is_null(field: impl Into<String>) -> Self {
+        Filter::Null {
+            field: field.into(),
+            null: true,
+        }
+    }
+
+    pub fn is_not_null(field: impl Into<String>) -> Self {
+        Filter::Null {
+            field: field.into(),
+            null: false,
+        }
+    }
+
+    /// Both this and `other` hold.
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::And(mut all) => {
+                all.push(other);
+                Filter::And(all)
+            }
+            this => Filter::And(vec![this, other]),
+        }
+    }
+
+    /// This or `other` holds.
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Or(mut any) => {
+                any.push(other);
+                Filter::Or(any)
+            }
+            this => Filter::Or(vec![this, other]),
+        }
+    }
+
+    /// Every field the filter looks at, for checking them against the ones a
+    /// repository knows.
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            Filter::Compare { field, .. }
+            | Filter::In { field, .. }
+            | Filter::Null { field, .. } => {
+                vec![field]
+            }
+            Filter::And(filters) | Filter::Or(filters) => {
+                filters.iter().flat_map(Filter::fields).collect()
+            }
+        }
+    }
+}
+
+impl From<Uuid> for FilterValue {
+    fn from(value: Uuid) -> Self {
+        FilterValue::Uuid(value)
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        FilterValue::Text(value)
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        FilterValue::Text(value.to_string())
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(value: i64) -> Self {
+        FilterValue::Number(value)
+    }
+}
+
+impl From<DateTime<Utc>> for FilterValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        FilterValue::Time(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composition_flattens_and_lists_fields() {
+        let author = Uuid::new_v4();
+        let filter = Filter::eq("user_id", author)
+            .and(Filter::is_null("deleted_at"))
+            .and(Filter::is_not_null("published_at").or(Filter::gt("view_count", 10)));
+
+        let Filter::And(all) = &filter else {
+            panic!("expected a conjunction, got {filter:?}");
+        };
+        assert_eq!(all.len(), 3);
+        assert_eq!(
+            all[2],
+            Filter::Or(vec![
+                Filter::is_not_null("published_at"),
+                Filter::compare("view_count", CompareOp::Gt, FilterValue::Number(10)),
+            ])
+        );
+        assert_eq!(
+            filter.fields(),
+            ["user_id", "deleted_at", "published_at", "view_count"]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// An admin placing or releasing a legal hold on a user's data.
+///
+/// While held, nothing may delete or anonymize the user's data. Changes are
+/// never updated or deleted: the latest one is the user's hold state and
+/// the rest are its audit trail.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegalHoldChange {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Whether the user is held after this change.
+    pub held: bool,
+    /// Why, e.g. the case or request it is for.
+    pub reason: String,
+    /// Admin who made the change.
+    pub changed_by: Uuid,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl LegalHoldChange {
+    /// Hold `user_id`'s data, or release it when `held` is false. Every
+    /// change needs a reason for the audit trail.
+    pub fn new(
+        user_id: Uuid,
+        held: bool,
+        reason: String,
+        changed_by: Uuid,
+    ) -> Result<Self, DomainError> {
+        let reason = reason.trim().to_string();
+        if reason.is_empty() {
+            return Err(DomainError::Validation(
+                "A legal hold change needs a reason".to_string(),
+            ));
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            user_id,
+            held,
+            reason,
+            changed_by,
+            changed_at: Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_requires_a_reason() {
+        let user_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+
+        let change = LegalHoldChange::new(user_id, true, " Case 42 ".into(), admin_id).unwrap();
+        assert!(change.held);
+        assert_eq!(change.reason, "Case 42");
+        assert!(LegalHoldChange::new(user_id, false, "  ".into(), admin_id).is_err());
+    }
+}
@@ -1,8 +1,82 @@
 //! Domain entities - the core business objects.
 
+mod announcement;
+
+mod approval;
+
+mod attachment;
+
+mod client_id;
+
+mod comment;
+
+mod consent;
+
+mod custom_domain;
+
+mod email;
+
+mod event;
+
+mod feature_flag;
+
+mod filter;
+
+mod legal_hold;
+
 mod user;
 
 mod post;
 
-pub use post::Post;
+mod oauth_client;
+
+mod organization;
+
+mod page;
+
+mod password;
+
+mod patch;
+
+mod plan;
+
+mod settings;
+
+mod subscription;
+
+mod sync;
+
+mod tag;
+
+mod usage;
+
+mod webhook_delivery;
+
+pub use announcement::{Announcement, Audience, Viewer};
+pub use approval::{AdminAction, ApprovalStatus, PendingOperation};
+pub use attachment::Attachment;
+pub use client_id::parse_client_id;
+pub use comment::Comment;
+pub use consent::{PolicyAcceptance, PolicyDocument, PolicyVersions};
+pub use custom_domain::{CustomDomain, normalize_hostname};
+pub use email::Email;
+pub use event::DomainEvent;
+pub use feature_flag::FeatureFlags;
+pub use filter::{CompareOp, Filter, FilterValue};
+pub use legal_hold::LegalHoldChange;
+pub use oauth_client::OAuthClient;
+pub use organization::{Invitation, Membership, OrgRole, Organization};
+pub use page::{Page, PageCursor, PageRequest, Sort, SortDirection};
+pub use password::PasswordPolicy;
+pub use patch::{Patch, PatchOperation, apply_patch};
+pub use plan::{Entitlement, Plan};
+pub use post::{Post, PostStatus};
+pub use settings::{OrgSettings, SettingsSchema, SettingsScope, UserSettings};
+pub use subscription::{Subscription, SubscriptionEvent, SubscriptionStatus};
+pub use sync::{PostMutation, SyncCursor, SyncOutcome};
+pub use tag::{Tag, normalize_tag};
+pub use usage::{
+    ApiQuota, QuotaPeriod, QuotaUsage, StorageUsage, UsageMetric, UsageTotal, billing_period,
+};
 pub use user::User;
+pub use webhook_delivery::WebhookDelivery;
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// Application a user registered to call the API on behalf of users.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuthClient {
+    pub id: Uuid,
+    /// User who registered the client.
+    pub owner_id: Uuid,
+    pub name: String,
+    /// Public identifier presented in authorization requests.
+    pub client_id: String,
+    /// Hash of the client secret; the secret itself is only shown once.
+    pub client_secret_hash: String,
+    /// Exact URIs authorization responses may be sent to.
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OAuthClient {
+    /// Scopes a client can be granted.
+    pub const SCOPES: [&'static str; 5] = ["openid", "profile", "email", "offline_access", "api"];
+
+    /// Most redirect URIs a client can register.
+    pub const MAX_REDIRECT_URIS: usize = 10;
+
+    pub fn new(
+        owner_id: Uuid,
+        name: String,
+        redirect_uris: Vec<String>,
+        scopes: Vec<String>,
+        client_secret_hash: String,
+    ) -> Result<Self, DomainError> {
+        let now = Utc::now();
+        let mut client = Self {
+            id: Uuid::new_v4(),
+            owner_id,
+            name: String::new(),
+            client_id: format!("apex_{}", Uuid::new_v4().simple()),
+            client_secret_hash,
+            redirect_uris: vec![],
+            scopes: vec![],
+            created_at: now,
+            updated_at: now,
+        };
+        client.revise(name, redirect_uris, scopes)?;
+        Ok(client)
+    }
+
+    /// A new random client secret, to be hashed before storing.
+    pub fn generate_secret() -> String {
+        format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+    }
+
+    /// Replace the name, redirect URIs and scopes, keeping the credentials.
+    pub fn revise(
+        &mut self,
+        name: String,
+        redirect_uris: Vec<String>,
+        mut scopes: Vec<String>,
+    ) -> Result<(), DomainError> {
+        if name.trim().is_empty() {
+            return Err(DomainError::Validation(
+                "Client name is required".to_string(),
+            ));
+        }
+        if redirect_uris.is_empty() || redirect_uris.len() > Self::MAX_REDIRECT_URIS {
+            return Err(DomainError::Validation(format!(
+                "Between 1 and {} redirect URIs are required",
+                Self::MAX_REDIRECT_URIS
+            )));
+        }
+        if let Some(uri) = redirect_uris.iter().find(|uri| !is_valid_redirect_uri(uri)) {
+            return Err(DomainError::Validation(format!(
+                "Invalid redirect URI: {} (https, or http on localhost, without a fragment)",
+                uri
+            )));
+        }
+        if let Some(scope) = scopes
+            .iter()
+            .find(|scope| !Self::SCOPES.contains(&scope.as_str()))
+        {
+            return Err(DomainError::Validation(format!("Unknown scope: {}", scope)));
+        }
+        scopes.sort();
+        scopes.dedup();
+
+        self.name = name;
+        self.redirect_uris = redirect_uris;
+        self.scopes = scopes;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Replace the secret, invalidating the previous one.
+    pub fn rotate_secret(&mut self, client_secret_hash: String) {
+        self.client_secret_hash = client_secret_hash;
+        self.updated_at = Utc::now();
+    }
+
+    /// Whether authorization responses may be sent to `uri`. Only exact
+    /// matches count.
+    pub fn allows_redirect(&self, uri: &str) -> bool {
+        self.redirect_uris.iter().any(|allowed| allowed == uri)
+    }
+}
+
+fn is_valid_redirect_uri(uri: &str) -> bool {
+    let rest = if let Some(rest) = uri.strip_prefix("https://") {
+        rest
+    } else if let Some(rest) = uri.strip_prefix("http://") {
+        let host = rest.split(['/', ':']).next().unwrap_or_default();
+        if host != "localhost" && host != "127.0.0.1" {
+            return false;
+        }
+        rest
+    } else {
+        return false;
+    };
+
+    !rest.is_empty() && !rest.starts_with('/') && !uri.contains('#') && !uri.contains(' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(redirect_uris: &[&str], scopes: &[&str]) -> Result<OAuthClient, DomainError> {
+        OAuthClient::new(
+            Uuid::new_v4(),
+            "My app".to_string(),
+            redirect_uris.iter().map(|s| s.to_string()).collect(),
+            scopes.iter().map(|s| s.to_string()).collect(),
+            "hash".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_redirect_uris_are_validated_and_matched_exactly() {
+        let app = client(
+            &[
+                "https://app.example.com/callback",
+                "http://localhost:3000/cb",
+            ],
+            &[],
+        )
+        .unwrap();
+        assert!(app.allows_redirect("https://app.example.com/callback"));
+        assert!(!app.allows_redirect("https://app.example.com/callback/other"));
+
+        assert!(client(&["http://app.example.com/callback"], &[]).is_err());
+        assert!(client(&["https://app.example.com/cb#frag"], &[]).is_err());
+        assert!(client(&["myapp://callback"], &[]).is_err());
+        assert!(client(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_scopes_are_validated_and_deduplicated() {
+        let app = client(
+            &["https://example.com/cb"],
+            &["profile", "openid", "profile"],
+        )
+        .unwrap();
+        assert_eq!(app.scopes, vec!["openid", "profile"]);
+        assert!(client(&["https://example.com/cb"], &["admin"]).is_err());
+    }
+}
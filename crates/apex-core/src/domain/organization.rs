@@ -0,0 +1,201 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Email;
+use crate::error::DomainError;
+
+/// Organization entity - a tenant that owns resources and has members.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    /// URL-safe unique identifier.
+    pub slug: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Organization {
+    /// Create a new organization, validating the slug.
+    pub fn new(name: String, slug: String) -> Result<Self, DomainError> {
+        if name.trim().is_empty() {
+            return Err(DomainError::Validation(
+                "Organization name is required".to_string(),
+            ));
+        }
+        let valid_slug = !slug.is_empty()
+            && slug.len() <= 64
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid_slug {
+            return Err(DomainError::Validation(
+                "Slug must be 1-64 lowercase letters, digits or dashes".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        Ok(Self {
+            id: Uuid::new_v4(),
+            name,
+            slug,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+/// Role of a user within an organization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgRole {
+    Member,
+    Admin,
+    Owner,
+}
+
+impl OrgRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrgRole::Member => "member",
+            OrgRole::Admin => "admin",
+            OrgRole::Owner => "owner",
+        }
+    }
+
+    /// Whether this role may invite and remove members.
+    pub fn can_manage_members(&self) -> bool {
+        *self >= OrgRole::Admin
+    }
+}
+
+impl std::fmt::Display for OrgRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for OrgRole {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "member" => Ok(OrgRole::Member),
+            "admin" => Ok(OrgRole::Admin),
+            "owner" => Ok(OrgRole::Owner),
+            other => Err(DomainError::Validation(format!(
+                "Unknown organization role: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Membership of a user in an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Membership {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: OrgRole,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Membership {
+    pub fn new(organization_id: Uuid, user_id: Uuid, role: OrgRole) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            organization_id,
+            user_id,
+            role,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Pending invitation for an email address to join an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub email: Email,
+    pub role: OrgRole,
+    /// Secret token presented when accepting the invitation.
+    pub token: String,
+    pub invited_by: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    /// How long an invitation stays valid.
+    pub const VALIDITY_DAYS: i64 = 7;
+
+    pub fn new(organization_id: Uuid, email: Email, role: OrgRole, invited_by: Uuid) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            organization_id,
+            email,
+            role,
+            token: Uuid::new_v4().simple().to_string(),
+            invited_by,
+            expires_at: now + Duration::days(Self::VALIDITY_DAYS),
+            accepted_at: None,
+            created_at: now,
+        }
+    }
+
+    /// Whether the invitation can still be accepted.
+    pub fn is_pending(&self) -> bool {
+        self.accepted_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_organization_rejects_invalid_slug() {
+        assert!(Organization::new("Acme".to_string(), "acme-inc".to_string()).is_ok());
+        assert!(Organization::new("Acme".to_string(), "Acme Inc".to_string()).is_err());
+        assert!(Organization::new("Acme".to_string(), String::new()).is_err());
+        assert!(Organization::new(" ".to_string(), "acme".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_org_role_round_trip() {
+        for role in [OrgRole::Member, OrgRole::Admin, OrgRole::Owner] {
+            assert_eq!(role.as_str().parse::<OrgRole>().unwrap(), role);
+        }
+        assert!("superuser".parse::<OrgRole>().is_err());
+    }
+
+    #[test]
+    fn test_org_role_permissions() {
+        assert!(OrgRole::Owner.can_manage_members());
+        assert!(OrgRole::Admin.can_manage_members());
+        assert!(!OrgRole::Member.can_manage_members());
+    }
+
+    #[test]
+    fn test_invitation_pending_until_accepted_or_expired() {
+        let mut invitation = Invitation::new(
+            Uuid::new_v4(),
+            Email::parse("new@example.com").unwrap(),
+            OrgRole::Member,
+            Uuid::new_v4(),
+        );
+        assert!(invitation.is_pending());
+
+        invitation.accepted_at = Some(Utc::now());
+        assert!(!invitation.is_pending());
+
+        invitation.accepted_at = None;
+        invitation.expires_at = Utc::now() - Duration::seconds(1);
+        assert!(!invitation.is_pending());
+    }
+}
@@ -0,0 +1,249 @@
+//! Offset and keyset pagination for repository listings.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::Filter;
+use crate::error::DomainError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Order of a listing, by one field of the entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sort {
+    /// Field name as stored, e.g. `created_at`.
+    pub field: String,
+    pub direction: SortDirection,
+}
+
+impl Sort {
+    pub fn asc(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            direction: SortDirection::Asc,
+        }
+    }
+
+    pub fn desc(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            direction: SortDirection::Desc,
+        }
+    }
+}
+
+/// `created_at` sorts ascending, `-created_at` descending, as in `?sort=`.
+impl FromStr for Sort {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, direction) = match s.strip_prefix('-') {
+            Some(field) => (field, SortDirection::Desc),
+            None => (s, SortDirection::Asc),
+        };
+        let valid = !field.is_empty()
+            && field
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            return Err(DomainError::Validation(format!("Invalid sort: {}", s)));
+        }
+        Ok(Self {
+            field: field.to_string(),
+            direction,
+        })
+    }
+}
+
+/// Which page of a listing to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRequest {
+    /// Items to skip.
+    pub offset: u64,
+    /// Items per page, at most [`PageRequest::MAX_LIMIT`].
+    pub limit: u64,
+    /// Without one, the repository's default order (by id), which is
+    /// stable but otherwise meaningless.
+    pub sort: Option<Sort>,
+    /// Only items meeting it are listed and counted.
+    pub filter: Option<Filter>,
+}
+
+impl PageRequest {
+    pub const DEFAULT_LIMIT: u64 = 20;
+    pub const MAX_LIMIT: u64 = 100;
+
+    /// Page at `offset`, with `limit` clamped to 1..=[`Self::MAX_LIMIT`].
+    pub fn new(offset: u64, limit: u64) -> Self {
+        Self {
+            offset,
+            limit: limit.clamp(1, Self::MAX_LIMIT),
+            sort: None,
+            filter: None,
+        }
+    }
+
+    pub fn sorted_by(mut self, sort: Sort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// List only items meeting `filter`, as well as any filter already set.
+    pub fn filtered_by(mut self, filter: Filter) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(existing) => existing.and(filter),
+            None => filter,
+        });
+        self
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self::new(0, Self::DEFAULT_LIMIT)
+    }
+}
+
+/// One page of a listing and the size of the whole listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Items in the listing across all pages.
+    pub total: u64,
+    pub offset: u64,
+    pub limit: u64,
+}
+
+impl<T> Page<T> {
+    /// No items, e.g. from a repository without storage.
+    pub fn empty(request: &PageRequest) -> Self {
+        Self {
+            items: Vec::new(),
+            total: 0,
+            offset: request.offset,
+            limit: request.limit,
+        }
+    }
+
+    /// Offset of the next page, if there is one.
+    pub fn next_offset(&self) -> Option<u64> {
+        let next = self.offset + self.items.len() as u64;
+        (!self.items.is_empty() && next < self.total).then_some(next)
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            offset: self.offset,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Position in a listing ordered newest first, just past the last item a
+/// caller has seen: items created earlier, or at the same time with a
+/// smaller id, come next.
+///
+/// Unlike an offset, every page costs the same to load, and rows added
+/// since the last page neither repeat nor skip items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl PageCursor {
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    /// Opaque form handed to clients.
+    pub fn encode(&self) -> String {
+        format!(
+            "{}.{}",
+            self.created_at.timestamp_micros(),
+            self.id.simple()
+        )
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, DomainError> {
+        let invalid = || DomainError::Validation("Invalid page cursor".to_string());
+        let (micros, id) = cursor.split_once('.').ok_or_else(invalid)?;
+        let micros: i64 = micros.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            created_at: DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?,
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+
+    /// Whether an item at `(created_at, id)` comes after the cursor.
+    pub fn precedes(&self, created_at: DateTime<Utc>, id: Uuid) -> bool {
+        (created_at, id) < (self.created_at, self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_parses_direction_prefix() {
+        assert_eq!(
+            "created_at".parse::<Sort>().unwrap(),
+            Sort::asc("created_at")
+        );
+        assert_eq!("-title".parse::<Sort>().unwrap(), Sort::desc("title"));
+        assert!("".parse::<Sort>().is_err());
+        assert!("-".parse::<Sort>().is_err());
+        assert!("title; drop table posts".parse::<Sort>().is_err());
+    }
+
+    #[test]
+    fn test_next_offset() {
+        let request = PageRequest::new(20, 500);
+        assert_eq!(request.limit, PageRequest::MAX_LIMIT);
+
+        let page = Page {
+            items: vec![1, 2, 3],
+            total: 25,
+            offset: 20,
+            limit: 3,
+        };
+        assert_eq!(page.next_offset(), Some(23));
+        assert_eq!(page.map(|n| n * 2).items, [2, 4, 6]);
+
+        let last = Page {
+            items: vec![1, 2],
+            total: 25,
+            offset: 23,
+            limit: 3,
+        };
+        assert_eq!(last.next_offset(), None);
+        assert_eq!(
+            Page::<u8>::empty(&PageRequest::default()).next_offset(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_page_cursor_round_trips_and_orders_newest_first() {
+        let now = DateTime::from_timestamp_micros(1_767_225_600_123_456).unwrap();
+        let cursor = PageCursor::new(now, Uuid::from_u128(5));
+        assert_eq!(PageCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(PageCursor::decode("yesterday").is_err());
+
+        let earlier = now - chrono::Duration::seconds(1);
+        assert!(cursor.precedes(earlier, Uuid::from_u128(9)));
+        assert!(cursor.precedes(now, Uuid::from_u128(4)));
+        assert!(!cursor.precedes(now, Uuid::from_u128(5)));
+        assert!(!cursor.precedes(now + chrono::Duration::seconds(1), Uuid::nil()));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::error::DomainError;
+
+/// Rules a new password has to follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    /// Shortest password accepted, in bytes.
+    pub min_length: usize,
+    /// Recent passwords of the account, the current one included, that a
+    /// new one may not repeat. 0 allows any.
+    pub history: usize,
+}
+
+impl PasswordPolicy {
+    pub const DEFAULT_MIN_LENGTH: usize = 8;
+    pub const DEFAULT_HISTORY: usize = 5;
+
+    /// Check the length of a new password. Reuse is checked against the
+    /// stored hashes by the service changing it.
+    pub fn check(&self, password: &str) -> Result<(), DomainError> {
+        if password.len() < self.min_length {
+            return Err(DomainError::Validation(format!(
+                "Password must be at least {} characters",
+                self.min_length
+            )));
+        }
+        Ok(())
+    }
+
+    /// Earlier passwords to keep besides the current one.
+    pub fn previous(&self) -> usize {
+        self.history.saturating_sub(1)
+    }
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: Self::DEFAULT_MIN_LENGTH,
+            history: Self::DEFAULT_HISTORY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_enforces_the_minimum_length() {
+        let policy = PasswordPolicy {
+            min_length: 12,
+            history: 0,
+        };
+        assert!(policy.check("eleven char").is_err());
+        assert!(policy.check("twelve chars").is_ok());
+        assert_eq!(policy.previous(), 0);
+        assert_eq!(PasswordPolicy::default().previous(), 4);
+    }
+}
@@ -0,0 +1,290 @@
+//! Partial updates: JSON Merge Patch (RFC 7396) and JSON Patch (RFC 6902).
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::DomainError;
+
+/// A partial update to a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Patch {
+    /// RFC 7396: objects merge recursively, `null` removes a member and
+    /// anything else replaces what is there.
+    Merge(Value),
+    /// RFC 6902: operations applied in order, all or nothing.
+    Json(Vec<PatchOperation>),
+}
+
+/// A single RFC 6902 operation. Paths are JSON Pointers (RFC 6901).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add {
+        path: String,
+        value: Value,
+    },
+    Remove {
+        path: String,
+    },
+    Replace {
+        path: String,
+        value: Value,
+    },
+    Move {
+        from: String,
+        path: String,
+    },
+    Copy {
+        from: String,
+        path: String,
+    },
+    /// Fail the whole patch unless the value at `path` equals `value`.
+    Test {
+        path: String,
+        value: Value,
+    },
+}
+
+impl Patch {
+    /// Apply the patch to `target`. On error `target` is left untouched.
+    pub fn apply(&self, target: &mut Value) -> Result<(), DomainError> {
+        match self {
+            Patch::Merge(patch) => {
+                merge_patch(target, patch);
+                Ok(())
+            }
+            Patch::Json(operations) => {
+                let mut patched = target.clone();
+                for operation in operations {
+                    apply_operation(&mut patched, operation)?;
+                }
+                *target = patched;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Apply `patch` to the JSON form of `value` and decode the result.
+///
+/// Fails with `Validation` if the patch does not apply or the patched
+/// document is not a valid `T`; checks serde cannot express are up to the
+/// caller.
+pub fn apply_patch<T>(value: &T, patch: &Patch) -> Result<T, DomainError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let mut document = serde_json::to_value(value)
+        .map_err(|e| DomainError::Internal(format!("Failed to encode value: {}", e)))?;
+    patch.apply(&mut document)?;
+    serde_json::from_value(document)
+        .map_err(|e| DomainError::Validation(format!("Invalid patched value: {}", e)))
+}
+
+/// RFC 7396 JSON merge patch.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let target = target.as_object_mut().expect("target is an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn apply_operation(document: &mut Value, operation: &PatchOperation) -> Result<(), DomainError> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, path, value.clone()),
+        PatchOperation::Remove { path } => remove(document, path).map(drop),
+        PatchOperation::Replace { path, value } => {
+            *pointer_mut(document, path)? = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{}/", from)) {
+                return Err(invalid(format!("Cannot move {} into itself", from)));
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = pointer(document, from)?.clone();
+            add(document, path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            if pointer(document, path)? != value {
+                return Err(invalid(format!("Test failed at {}", path)));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), DomainError> {
+    let Some((parent, key)) = split_pointer(path)? else {
+        *document = value;
+        return Ok(());
+    };
+    match pointer_mut(document, &parent)? {
+        Value::Object(map) => {
+            map.insert(key, value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let index = if key == "-" {
+                items.len()
+            } else {
+                array_index(&key, items.len() + 1, path)?
+            };
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err(invalid(format!("Cannot add to a scalar at {}", path))),
+    }
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, DomainError> {
+    let Some((parent, key)) = split_pointer(path)? else {
+        return Err(invalid("Cannot remove the whole document".to_string()));
+    };
+    match pointer_mut(document, &parent)? {
+        Value::Object(map) => map
+            .remove(&key)
+            .ok_or_else(|| invalid(format!("Nothing to remove at {}", path))),
+        Value::Array(items) => {
+            let index = array_index(&key, items.len(), path)?;
+            Ok(items.remove(index))
+        }
+        _ => Err(invalid(format!("Nothing to remove at {}", path))),
+    }
+}
+
+fn pointer<'a>(document: &'a Value, path: &str) -> Result<&'a Value, DomainError> {
+    check_pointer(path)?;
+    document
+        .pointer(path)
+        .ok_or_else(|| invalid(format!("No value at {}", path)))
+}
+
+fn pointer_mut<'a>(document: &'a mut Value, path: &str) -> Result<&'a mut Value, DomainError> {
+    check_pointer(path)?;
+    document
+        .pointer_mut(path)
+        .ok_or_else(|| invalid(format!("No value at {}", path)))
+}
+
+/// Split a pointer into its parent pointer and unescaped last token;
+/// `None` for the whole document.
+fn split_pointer(path: &str) -> Result<Option<(String, String)>, DomainError> {
+    check_pointer(path)?;
+    Ok(path.rsplit_once('/').map(|(parent, token)| {
+        (
+            parent.to_string(),
+            token.replace("~1", "/").replace("~0", "~"),
+        )
+    }))
+}
+
+fn check_pointer(path: &str) -> Result<(), DomainError> {
+    if !path.is_empty() && !path.starts_with('/') {
+        return Err(invalid(format!("Invalid JSON pointer: {}", path)));
+    }
+    Ok(())
+}
+
+/// Parse an array index below `bound`, rejecting leading zeros.
+fn array_index(token: &str, bound: usize, path: &str) -> Result<usize, DomainError> {
+    let canonical = token == "0" || !token.starts_with('0');
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|index| canonical && *index < bound)
+        .ok_or_else(|| invalid(format!("Invalid array index at {}", path)))
+}
+
+fn invalid(message: String) -> DomainError {
+    DomainError::Validation(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn operations(ops: Value) -> Patch {
+        Patch::Json(serde_json::from_value(ops).unwrap())
+    }
+
+    #[test]
+    fn test_merge_patch_follows_rfc_7396() {
+        let mut document = json!({"a": "b", "c": {"d": "e", "f": "g"}});
+        Patch::Merge(json!({"a": "z", "c": {"f": null}, "x": [1]}))
+            .apply(&mut document)
+            .unwrap();
+        assert_eq!(document, json!({"a": "z", "c": {"d": "e"}, "x": [1]}));
+    }
+
+    #[test]
+    fn test_json_patch_applies_operations_in_order() {
+        let mut document = json!({"name": "a", "tags": ["x", "y"], "meta": {"n": 1}});
+        operations(json!([
+            {"op": "test", "path": "/name", "value": "a"},
+            {"op": "replace", "path": "/name", "value": "b"},
+            {"op": "add", "path": "/tags/1", "value": "new"},
+            {"op": "add", "path": "/tags/-", "value": "last"},
+            {"op": "remove", "path": "/tags/0"},
+            {"op": "copy", "from": "/meta/n", "path": "/count"},
+            {"op": "move", "from": "/meta", "path": "/info"},
+        ]))
+        .apply(&mut document)
+        .unwrap();
+
+        assert_eq!(
+            document,
+            json!({"name": "b", "tags": ["new", "y", "last"], "count": 1, "info": {"n": 1}})
+        );
+    }
+
+    #[test]
+    fn test_failed_json_patch_leaves_document_untouched() {
+        let original = json!({"name": "a", "tags": []});
+        for ops in [
+            json!([{"op": "replace", "path": "/name", "value": "b"}, {"op": "test", "path": "/name", "value": "a"}]),
+            json!([{"op": "remove", "path": "/missing"}]),
+            json!([{"op": "add", "path": "/tags/01", "value": 1}]),
+            json!([{"op": "move", "from": "/tags", "path": "/tags/0"}]),
+            json!([{"op": "replace", "path": "name", "value": "b"}]),
+        ] {
+            let mut document = original.clone();
+            assert!(operations(ops).apply(&mut document).is_err());
+            assert_eq!(document, original);
+        }
+    }
+
+    #[test]
+    fn test_apply_patch_decodes_the_result() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Item {
+            name: String,
+            count: u32,
+        }
+        let item = Item {
+            name: "a".to_string(),
+            count: 1,
+        };
+
+        let patched = apply_patch(&item, &Patch::Merge(json!({"count": 2}))).unwrap();
+        assert_eq!(patched.count, 2);
+        assert!(apply_patch(&item, &Patch::Merge(json!({"count": "x"}))).is_err());
+        assert!(apply_patch(&item, &Patch::Merge(json!({"name": null}))).is_err());
+    }
+}
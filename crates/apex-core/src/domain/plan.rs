@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::DomainError;
+
+/// Capability that is unlocked by a plan rather than by a role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Entitlement {
+    /// Realtime updates over WebSocket.
+    Websocket,
+    /// Bulk data exports.
+    Exports,
+    /// Outbound webhooks to customer endpoints.
+    Webhooks,
+}
+
+impl Entitlement {
+    pub const ALL: [Entitlement; 3] = [
+        Entitlement::Websocket,
+        Entitlement::Exports,
+        Entitlement::Webhooks,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Entitlement::Websocket => "websocket",
+            Entitlement::Exports => "exports",
+            Entitlement::Webhooks => "webhooks",
+        }
+    }
+}
+
+impl std::fmt::Display for Entitlement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Billing plan of an account. Ordered from cheapest to most expensive.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Plan {
+    #[default]
+    Free,
+    Pro,
+    Enterprise,
+}
+
+impl Plan {
+    pub const ALL: [Plan; 3] = [Plan::Free, Plan::Pro, Plan::Enterprise];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Plan::Free => "free",
+            Plan::Pro => "pro",
+            Plan::Enterprise => "enterprise",
+        }
+    }
+
+    /// Entitlements included in the plan.
+    pub fn entitlements(&self) -> &'static [Entitlement] {
+        match self {
+            Plan::Free => &[],
+            Plan::Pro => &[Entitlement::Websocket, Entitlement::Exports],
+            Plan::Enterprise => &Entitlement::ALL,
+        }
+    }
+
+    pub fn includes(&self, entitlement: Entitlement) -> bool {
+        self.entitlements().contains(&entitlement)
+    }
+
+    /// Cheapest plan that includes the entitlement, used as the upgrade hint.
+    pub fn cheapest_with(entitlement: Entitlement) -> Option<Plan> {
+        Plan::ALL
+            .into_iter()
+            .find(|plan| plan.includes(entitlement))
+    }
+}
+
+impl std::fmt::Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Plan {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Plan::ALL
+            .into_iter()
+            .find(|p| p.as_str() == s)
+            .ok_or_else(|| DomainError::Validation(format!("Unknown plan: {}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_higher_plans_include_lower_plan_entitlements() {
+        for pair in Plan::ALL.windows(2) {
+            for entitlement in pair[0].entitlements() {
+                assert!(pair[1].includes(*entitlement));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cheapest_with() {
+        assert_eq!(Plan::cheapest_with(Entitlement::Exports), Some(Plan::Pro));
+        assert_eq!(
+            Plan::cheapest_with(Entitlement::Webhooks),
+            Some(Plan::Enterprise)
+        );
+        assert!(!Plan::Free.includes(Entitlement::Websocket));
+    }
+}
@@ -1,16 +1,49 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::Filter;
+use crate::error::DomainError;
+
 /// Post entity - represents a blog post or article.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Post {
     pub id: Uuid,
     pub user_id: Uuid,
+    /// Owning organization, if the post is org-scoped.
+    #[serde(default)]
+    pub organization_id: Option<Uuid>,
     pub title: String,
     pub content: String,
+    /// Bumped on every change, so writers can detect concurrent edits.
+    #[serde(default = "first_version")]
+    pub version: i64,
+    /// Set when the post is deleted. The row stays behind as a tombstone so
+    /// synced clients learn about the deletion.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Views flushed to storage. Counted apart from the post and never
+    /// written back with it, so saving a post doesn't reset the count.
+    #[serde(default)]
+    pub view_count: i64,
+    /// When the post went public; it shows in the sitemap and feed from then.
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// User who created the post. Repositories fill it in from the request
+    /// on the first save.
+    #[serde(default)]
+    pub created_by: Option<Uuid>,
+    /// User who last saved the post, stamped from the request.
+    #[serde(default)]
+    pub updated_by: Option<Uuid>,
+}
+
+fn first_version() -> i64 {
+    1
 }
 
 impl Post {
@@ -20,10 +53,165 @@ impl Post {
         Self {
             id: Uuid::new_v4(),
             user_id,
+            organization_id: None,
             title,
             content,
+            version: first_version(),
+            deleted_at: None,
+            view_count: 0,
+            published_at: None,
             created_at: now,
             updated_at: now,
+            created_by: None,
+            updated_by: None,
+        }
+    }
+
+    /// Check a title before creating or revising a post with it.
+    pub fn validate_title(title: &str) -> Result<(), DomainError> {
+        if title.trim().is_empty() {
+            return Err(DomainError::Validation(
+                "Post title is required".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Scope the post to an organization.
+    pub fn in_organization(mut self, organization_id: Uuid) -> Self {
+        self.organization_id = Some(organization_id);
+        self
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Whether the post is public at `now`.
+    pub fn is_published(&self, now: DateTime<Utc>) -> bool {
+        !self.is_deleted() && self.published_at.is_some_and(|at| at <= now)
+    }
+
+    /// Whether `user_id`, acting for `org_id` if any, may read the post at
+    /// `now`: its author and organization always, anyone once it is public.
+    pub fn readable_by(&self, user_id: Uuid, org_id: Option<Uuid>, now: DateTime<Utc>) -> bool {
+        let in_org = self.organization_id.is_some() && self.organization_id == org_id;
+        !self.is_deleted() && (self.user_id == user_id || in_org || self.is_published(now))
+    }
+
+    /// Make the post public at `at`, which may lie ahead. Returns `false`,
+    /// changing nothing, if it already is public at that time.
+    pub fn publish(&mut self, at: DateTime<Utc>) -> bool {
+        if self.published_at.is_some_and(|published| published <= at) {
+            return false;
+        }
+        self.published_at = Some(at);
+        self.touch();
+        true
+    }
+
+    /// Take the post out of public view. Returns `false` if it was not
+    /// published.
+    pub fn unpublish(&mut self) -> bool {
+        if self.published_at.take().is_none() {
+            return false;
+        }
+        self.touch();
+        true
+    }
+
+    /// Replace the title and content.
+    pub fn revise(&mut self, title: String, content: String) {
+        self.title = title;
+        self.content = content;
+        self.touch();
+    }
+
+    /// Turn the post into a tombstone.
+    pub fn mark_deleted(&mut self) {
+        self.touch();
+        self.deleted_at = Some(self.updated_at);
+    }
+
+    fn touch(&mut self) {
+        self.version += 1;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Where a post stands in publishing, as of some point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostStatus {
+    /// Not published.
+    Draft,
+    /// Published from a time still ahead.
+    Scheduled,
+    /// Public.
+    Published,
+}
+
+impl PostStatus {
+    /// The posts in this status at `now`, as [`Post::is_published`] decides
+    /// it for published ones.
+    pub fn filter(self, now: DateTime<Utc>) -> Filter {
+        match self {
+            PostStatus::Draft => Filter::is_null("published_at"),
+            PostStatus::Scheduled => Filter::gt("published_at", now),
+            PostStatus::Published => Filter::lte("published_at", now),
+        }
+    }
+}
+
+impl FromStr for PostStatus {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(PostStatus::Draft),
+            "scheduled" => Ok(PostStatus::Scheduled),
+            "published" => Ok(PostStatus::Published),
+            _ => Err(DomainError::Validation(format!(
+                "Unknown post status: {}",
+                s
+            ))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_publishing() {
+        let mut post = Post::new(Uuid::new_v4(), "Title".to_string(), "Content".to_string());
+        let now = Utc::now();
+        assert!(!post.is_published(now));
+
+        // Scheduled ahead, then brought forward; publishing later changes nothing
+        assert!(post.publish(now + Duration::hours(1)));
+        assert!(!post.is_published(now));
+        assert!(post.publish(now));
+        assert!(post.is_published(now));
+        assert!(!post.publish(now + Duration::hours(2)));
+        assert_eq!(post.version, 3);
+
+        post.mark_deleted();
+        assert!(!post.is_published(now));
+        assert!(post.unpublish());
+        assert!(!post.unpublish());
+    }
+
+    #[test]
+    fn test_status_parses_to_a_filter() {
+        let now = Utc::now();
+        let status: PostStatus = "scheduled".parse().unwrap();
+        assert_eq!(status.filter(now), Filter::gt("published_at", now));
+        assert_eq!(
+            "draft".parse::<PostStatus>().unwrap().filter(now),
+            Filter::is_null("published_at")
+        );
+        assert!("Published".parse::<PostStatus>().is_err());
+    }
+}
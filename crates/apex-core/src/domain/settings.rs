@@ -0,0 +1,218 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{OrgRole, Patch, apply_patch};
+use crate::error::DomainError;
+
+/// Owner of a settings document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingsScope {
+    Organization(Uuid),
+    User(Uuid),
+}
+
+impl SettingsScope {
+    /// Scope kind as stored alongside the settings.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SettingsScope::Organization(_) => "organization",
+            SettingsScope::User(_) => "user",
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        match self {
+            SettingsScope::Organization(id) | SettingsScope::User(id) => *id,
+        }
+    }
+}
+
+/// Typed view over a stored settings document.
+///
+/// Fields missing from storage take their `Default` value, so new settings
+/// can be added without migrating existing rows.
+pub trait SettingsSchema: Serialize + DeserializeOwned + Default + Send + Sync {
+    /// Check invariants that serde cannot express.
+    fn validate(&self) -> Result<(), DomainError> {
+        Ok(())
+    }
+
+    /// Decode stored values, falling back to defaults for anything missing.
+    fn from_stored(values: Option<serde_json::Value>) -> Result<Self, DomainError> {
+        match values {
+            Some(values) => serde_json::from_value(values)
+                .map_err(|e| DomainError::Validation(format!("Invalid stored settings: {}", e))),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Apply a patch and validate the result.
+    ///
+    /// In a merge patch, `null` resets a field to its default.
+    fn patched(&self, patch: &Patch) -> Result<Self, DomainError> {
+        let updated = apply_patch(self, patch)?;
+        updated.validate()?;
+        Ok(updated)
+    }
+}
+
+/// Organization-wide product settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OrgSettings {
+    /// Let plain members invite others (as members).
+    pub allow_member_invites: bool,
+    /// Role granted when an invitation does not specify one, up to the
+    /// inviter's own.
+    pub default_invite_role: OrgRole,
+    /// Upper bound on memberships, if any.
+    pub max_members: Option<u32>,
+}
+
+impl Default for OrgSettings {
+    fn default() -> Self {
+        Self {
+            allow_member_invites: false,
+            default_invite_role: OrgRole::Member,
+            max_members: None,
+        }
+    }
+}
+
+impl OrgSettings {
+    /// Role of an invitation that does not specify one, sent by a member
+    /// with `inviter_role`: the default, but never above their own.
+    pub fn invite_role(&self, inviter_role: OrgRole) -> OrgRole {
+        self.default_invite_role.min(inviter_role)
+    }
+}
+
+impl SettingsSchema for OrgSettings {
+    fn validate(&self) -> Result<(), DomainError> {
+        if self.default_invite_role == OrgRole::Owner {
+            return Err(DomainError::Validation(
+                "default_invite_role cannot be owner".to_string(),
+            ));
+        }
+        if self.max_members == Some(0) {
+            return Err(DomainError::Validation(
+                "max_members must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Per-user preferences.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserSettings {
+    /// BCP 47 language tag, e.g. "en" or "pt-BR".
+    pub locale: String,
+    /// IANA time zone name, e.g. "Europe/Berlin".
+    pub timezone: String,
+    pub email_notifications: bool,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            locale: "en".to_string(),
+            timezone: "UTC".to_string(),
+            email_notifications: true,
+        }
+    }
+}
+
+impl SettingsSchema for UserSettings {
+    fn validate(&self) -> Result<(), DomainError> {
+        let valid_locale = !self.locale.is_empty()
+            && self.locale.len() <= 35
+            && self
+                .locale
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !valid_locale {
+            return Err(DomainError::Validation("Invalid locale".to_string()));
+        }
+        if self.timezone.is_empty() || self.timezone.len() > 64 {
+            return Err(DomainError::Validation("Invalid timezone".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings = OrgSettings::from_stored(Some(json!({"max_members": 10}))).unwrap();
+        assert_eq!(settings.max_members, Some(10));
+        assert!(!settings.allow_member_invites);
+
+        assert_eq!(
+            UserSettings::from_stored(None).unwrap(),
+            UserSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_patch_merges_and_resets_with_null() {
+        let settings = OrgSettings {
+            max_members: Some(5),
+            ..Default::default()
+        };
+
+        let patched = settings
+            .patched(&Patch::Merge(
+                json!({"allow_member_invites": true, "max_members": null}),
+            ))
+            .unwrap();
+
+        assert!(patched.allow_member_invites);
+        assert_eq!(patched.max_members, None);
+    }
+
+    #[test]
+    fn test_default_invite_role_is_capped_at_the_inviters() {
+        let settings = OrgSettings {
+            allow_member_invites: true,
+            default_invite_role: OrgRole::Admin,
+            ..Default::default()
+        };
+
+        assert_eq!(settings.invite_role(OrgRole::Member), OrgRole::Member);
+        assert_eq!(settings.invite_role(OrgRole::Admin), OrgRole::Admin);
+        assert_eq!(settings.invite_role(OrgRole::Owner), OrgRole::Admin);
+    }
+
+    #[test]
+    fn test_patch_rejects_invalid_values() {
+        let settings = OrgSettings::default();
+
+        assert!(
+            settings
+                .patched(&Patch::Merge(json!({"max_members": 0})))
+                .is_err()
+        );
+        assert!(
+            settings
+                .patched(&Patch::Merge(json!({"default_invite_role": "owner"})))
+                .is_err()
+        );
+        assert!(
+            settings
+                .patched(&Patch::Merge(json!({"unknown": true})))
+                .is_err()
+        );
+        assert!(
+            UserSettings::default()
+                .patched(&Patch::Merge(json!({"locale": "en US"})))
+                .is_err()
+        );
+    }
+}
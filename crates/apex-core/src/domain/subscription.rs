@@ -0,0 +1,213 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Plan;
+use crate::error::DomainError;
+
+/// Lifecycle state of an account's subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    /// Free trial of the plan, no payment taken yet.
+    Trialing,
+    /// Paid up.
+    Active,
+    /// Payment is due or failed; access continues while it is retried.
+    PastDue,
+    /// Ended. The account falls back to the free plan.
+    Canceled,
+}
+
+impl SubscriptionStatus {
+    pub const ALL: [SubscriptionStatus; 4] = [
+        SubscriptionStatus::Trialing,
+        SubscriptionStatus::Active,
+        SubscriptionStatus::PastDue,
+        SubscriptionStatus::Canceled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionStatus::Trialing => "trialing",
+            SubscriptionStatus::Active => "active",
+            SubscriptionStatus::PastDue => "past_due",
+            SubscriptionStatus::Canceled => "canceled",
+        }
+    }
+
+    /// Whether the subscribed plan's entitlements apply.
+    pub fn grants_access(&self) -> bool {
+        !matches!(self, SubscriptionStatus::Canceled)
+    }
+}
+
+impl std::fmt::Display for SubscriptionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for SubscriptionStatus {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SubscriptionStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| DomainError::Validation(format!("Unknown subscription status: {}", s)))
+    }
+}
+
+/// Something that happened to a subscription, from the billing provider or
+/// the trial expiry job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionEvent {
+    /// A payment went through, covering the period ending at `period_end`.
+    PaymentSucceeded {
+        period_end: DateTime<Utc>,
+    },
+    PaymentFailed,
+    /// The trial ended without a payment.
+    TrialExpired,
+    PlanChanged(Plan),
+    Canceled,
+}
+
+/// An account's (organization or user) subscription to a plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscription {
+    pub account_id: Uuid,
+    pub plan: Plan,
+    pub status: SubscriptionStatus,
+    pub trial_ends_at: Option<DateTime<Utc>>,
+    pub current_period_end: Option<DateTime<Utc>>,
+    /// Subscription id at the billing provider, once there is one.
+    pub external_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Subscription {
+    /// Default trial length.
+    pub const TRIAL_DAYS: i64 = 14;
+
+    pub fn start_trial(account_id: Uuid, plan: Plan, trial_days: i64) -> Self {
+        let now = Utc::now();
+        Self {
+            account_id,
+            plan,
+            status: SubscriptionStatus::Trialing,
+            trial_ends_at: Some(now + Duration::days(trial_days)),
+            current_period_end: None,
+            external_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Subscription first seen at the billing provider, without a trial.
+    ///
+    /// Starts past due; the provider's payment events move it on from there.
+    pub fn external(account_id: Uuid, plan: Plan, external_id: String) -> Self {
+        let now = Utc::now();
+        Self {
+            account_id,
+            plan,
+            status: SubscriptionStatus::PastDue,
+            trial_ends_at: None,
+            current_period_end: None,
+            external_id: Some(external_id),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Apply an event, moving the subscription to its next state.
+    ///
+    /// Canceled subscriptions are final; events that do not apply to the
+    /// current state are rejected so out-of-order deliveries are visible.
+    pub fn apply(&mut self, event: SubscriptionEvent) -> Result<(), DomainError> {
+        use SubscriptionStatus::*;
+
+        let status = match (self.status, &event) {
+            (Canceled, _) => {
+                return Err(DomainError::Validation(
+                    "Subscription is canceled".to_string(),
+                ));
+            }
+            (_, SubscriptionEvent::PaymentSucceeded { period_end }) => {
+                self.current_period_end = Some(*period_end);
+                Active
+            }
+            (_, SubscriptionEvent::PaymentFailed) => PastDue,
+            (Trialing, SubscriptionEvent::TrialExpired) => PastDue,
+            (_, SubscriptionEvent::PlanChanged(plan)) => {
+                self.plan = *plan;
+                self.status
+            }
+            (_, SubscriptionEvent::Canceled) => Canceled,
+            (current, SubscriptionEvent::TrialExpired) => {
+                return Err(DomainError::Validation(format!(
+                    "Cannot expire the trial of a {} subscription",
+                    current
+                )));
+            }
+        };
+
+        self.status = status;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Whether the trial has run out as of `now`.
+    pub fn trial_expired(&self, now: DateTime<Utc>) -> bool {
+        self.status == SubscriptionStatus::Trialing
+            && self.trial_ends_at.is_some_and(|ends_at| ends_at <= now)
+    }
+
+    /// Plan whose entitlements the account currently has.
+    pub fn effective_plan(&self) -> Plan {
+        if self.status.grants_access() {
+            self.plan
+        } else {
+            Plan::Free
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trial_to_active_to_canceled() {
+        let mut sub = Subscription::start_trial(Uuid::new_v4(), Plan::Pro, 14);
+        assert!(!sub.trial_expired(Utc::now()));
+
+        let period_end = Utc::now() + Duration::days(30);
+        sub.apply(SubscriptionEvent::PaymentSucceeded { period_end })
+            .unwrap();
+        assert_eq!(sub.status, SubscriptionStatus::Active);
+        assert_eq!(sub.current_period_end, Some(period_end));
+
+        sub.apply(SubscriptionEvent::PaymentFailed).unwrap();
+        assert_eq!(sub.status, SubscriptionStatus::PastDue);
+        assert_eq!(sub.effective_plan(), Plan::Pro);
+
+        sub.apply(SubscriptionEvent::Canceled).unwrap();
+        assert_eq!(sub.effective_plan(), Plan::Free);
+        assert!(sub.apply(SubscriptionEvent::PaymentFailed).is_err());
+    }
+
+    #[test]
+    fn test_trial_expiry_only_applies_to_trials() {
+        let mut sub = Subscription::start_trial(Uuid::new_v4(), Plan::Pro, 0);
+        assert!(sub.trial_expired(Utc::now()));
+
+        sub.apply(SubscriptionEvent::TrialExpired).unwrap();
+        assert_eq!(sub.status, SubscriptionStatus::PastDue);
+        assert!(!sub.trial_expired(Utc::now()));
+        assert!(sub.apply(SubscriptionEvent::TrialExpired).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Offline sync: change cursors and version-checked client mutations.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::Post;
+use crate::error::DomainError;
+
+/// Position in a user's change feed, just past the last change a client has
+/// seen. Changes are ordered by update time, then id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncCursor {
+    pub updated_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl SyncCursor {
+    /// Cursor just past `post`.
+    pub fn after(post: &Post) -> Self {
+        Self {
+            updated_at: post.updated_at,
+            id: post.id,
+        }
+    }
+
+    /// Opaque form handed to clients.
+    pub fn encode(&self) -> String {
+        format!(
+            "{}.{}",
+            self.updated_at.timestamp_micros(),
+            self.id.simple()
+        )
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, DomainError> {
+        let invalid = || DomainError::Validation("Invalid sync cursor".to_string());
+        let (micros, id) = cursor.split_once('.').ok_or_else(invalid)?;
+        let micros: i64 = micros.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            updated_at: DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?,
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// A change to a post made by a client, possibly while offline.
+///
+/// `base_version` is the version the client last saw; the change only
+/// applies if the post has not moved on since.
+#[derive(Debug, Clone)]
+pub enum PostMutation {
+    /// Create the post (no base version) or replace its title and content.
+    Upsert {
+        id: Uuid,
+        base_version: Option<i64>,
+        title: String,
+        content: String,
+    },
+    Delete {
+        id: Uuid,
+        base_version: i64,
+    },
+}
+
+/// What pushing a mutation amounts to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncOutcome {
+    /// Store this post.
+    Apply(Post),
+    /// The server already has the change, e.g. a push retried after a lost
+    /// response. Carries the server copy, if there is one.
+    Unchanged(Option<Post>),
+    /// The post changed on the server since `base_version`. Carries the
+    /// server copy, `None` if the post no longer exists, for the client to
+    /// merge and push again.
+    Conflict(Option<Post>),
+}
+
+impl PostMutation {
+    pub fn id(&self) -> Uuid {
+        match self {
+            PostMutation::Upsert { id, .. } | PostMutation::Delete { id, .. } => *id,
+        }
+    }
+
+    /// Resolve the mutation by `user_id` against the server copy `current`.
+    ///
+    /// Fails with `Unauthorized` for someone else's post.
+    pub fn resolve(self, user_id: Uuid, current: Option<Post>) -> Result<SyncOutcome, DomainError> {
+        if current.as_ref().is_some_and(|post| post.user_id != user_id) {
+            return Err(DomainError::Unauthorized);
+        }
+
+        match (self, current) {
+            (
+                PostMutation::Upsert {
+                    id,
+                    base_version: None,
+                    title,
+                    content,
+                },
+                None,
+            ) => {
+                Post::validate_title(&title)?;
+                let mut post = Post::new(user_id, title, content);
+                post.id = id;
+                Ok(SyncOutcome::Apply(post))
+            }
+            (
+                PostMutation::Upsert {
+                    base_version: None,
+                    title,
+                    content,
+                    ..
+                },
+                Some(post),
+            ) => {
+                if !post.is_deleted() && post.title == title && post.content == content {
+                    Ok(SyncOutcome::Unchanged(Some(post)))
+                } else {
+                    Ok(SyncOutcome::Conflict(Some(post)))
+                }
+            }
+            (
+                PostMutation::Upsert {
+                    base_version: Some(_),
+                    ..
+                },
+                None,
+            ) => Ok(SyncOutcome::Conflict(None)),
+            (
+                PostMutation::Upsert {
+                    base_version: Some(base_version),
+                    title,
+                    content,
+                    ..
+                },
+                Some(mut post),
+            ) => {
+                if post.is_deleted() || post.version != base_version {
+                    return Ok(SyncOutcome::Conflict(Some(post)));
+                }
+                Post::validate_title(&title)?;
+                post.revise(title, content);
+                Ok(SyncOutcome::Apply(post))
+            }
+            (PostMutation::Delete { .. }, None) => Ok(SyncOutcome::Unchanged(None)),
+            (PostMutation::Delete { base_version, .. }, Some(mut post)) => {
+                if post.is_deleted() {
+                    Ok(SyncOutcome::Unchanged(Some(post)))
+                } else if post.version != base_version {
+                    Ok(SyncOutcome::Conflict(Some(post)))
+                } else {
+                    post.mark_deleted();
+                    Ok(SyncOutcome::Apply(post))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upsert(id: Uuid, base_version: Option<i64>, title: &str) -> PostMutation {
+        PostMutation::Upsert {
+            id,
+            base_version,
+            title: title.to_string(),
+            content: "Content".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trips() {
+        let post = Post::new(Uuid::new_v4(), "Title".to_string(), "Content".to_string());
+        let cursor = SyncCursor::after(&post);
+        let decoded = SyncCursor::decode(&cursor.encode()).unwrap();
+
+        assert_eq!(decoded.id, post.id);
+        assert_eq!(
+            decoded.updated_at.timestamp_micros(),
+            post.updated_at.timestamp_micros()
+        );
+        assert!(SyncCursor::decode("garbage").is_err());
+    }
+
+    #[test]
+    fn test_mutations_apply_only_on_the_base_version() {
+        let user_id = Uuid::new_v4();
+        let id = Uuid::new_v4();
+
+        let SyncOutcome::Apply(created) = upsert(id, None, "Draft").resolve(user_id, None).unwrap()
+        else {
+            panic!("expected the post to be created");
+        };
+        assert_eq!((created.id, created.version), (id, 1));
+
+        // A retried create is a no-op, a different one conflicts
+        assert_eq!(
+            upsert(id, None, "Draft")
+                .resolve(user_id, Some(created.clone()))
+                .unwrap(),
+            SyncOutcome::Unchanged(Some(created.clone()))
+        );
+        assert!(matches!(
+            upsert(id, None, "Other").resolve(user_id, Some(created.clone())),
+            Ok(SyncOutcome::Conflict(Some(_)))
+        ));
+
+        let SyncOutcome::Apply(edited) = upsert(id, Some(1), "Final")
+            .resolve(user_id, Some(created.clone()))
+            .unwrap()
+        else {
+            panic!("expected the edit to apply");
+        };
+        assert_eq!((edited.title.as_str(), edited.version), ("Final", 2));
+
+        // A stale edit or delete conflicts with the newer server copy
+        assert_eq!(
+            upsert(id, Some(1), "Stale")
+                .resolve(user_id, Some(edited.clone()))
+                .unwrap(),
+            SyncOutcome::Conflict(Some(edited.clone()))
+        );
+        let delete = PostMutation::Delete {
+            id,
+            base_version: 1,
+        };
+        assert!(matches!(
+            delete.resolve(user_id, Some(edited.clone())),
+            Ok(SyncOutcome::Conflict(_))
+        ));
+
+        let SyncOutcome::Apply(deleted) = PostMutation::Delete {
+            id,
+            base_version: 2,
+        }
+        .resolve(user_id, Some(edited.clone()))
+        .unwrap() else {
+            panic!("expected the delete to apply");
+        };
+        assert!(deleted.is_deleted());
+        assert_eq!(deleted.version, 3);
+    }
+
+    #[test]
+    fn test_mutations_on_other_users_posts_are_rejected() {
+        let post = Post::new(Uuid::new_v4(), "Title".to_string(), "Content".to_string());
+        let mutation = PostMutation::Delete {
+            id: post.id,
+            base_version: 1,
+        };
+        assert!(matches!(
+            mutation.resolve(Uuid::new_v4(), Some(post)),
+            Err(DomainError::Unauthorized)
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::DomainError;
+
+/// A label posts are grouped by. Names are unique, so a tag is shared by
+/// every post carrying it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: Uuid,
+    /// Normalized, see [`normalize_tag`].
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Tag {
+    /// Most tags one post may carry.
+    pub const MAX_PER_POST: usize = 10;
+
+    pub fn new(name: &str) -> Result<Self, DomainError> {
+        Ok(Self {
+            id: Uuid::new_v4(),
+            name: normalize_tag(name)?,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+/// Lowercase `name` and check it is 1 to 32 letters, digits and inner
+/// dashes, so `Rust` and `rust ` name the same tag.
+pub fn normalize_tag(name: &str) -> Result<String, DomainError> {
+    let name = name.trim().to_ascii_lowercase();
+    let valid = (1..=32).contains(&name.len())
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid {
+        return Err(DomainError::Validation(format!("Invalid tag: {}", name)));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_names_are_normalized_and_validated() {
+        assert_eq!(normalize_tag(" Rust-Lang ").unwrap(), "rust-lang");
+        assert_eq!(Tag::new("2026").unwrap().name, "2026");
+        for invalid in ["", "-rust", "rust-", "two words", "c++", &"x".repeat(33)] {
+            assert!(normalize_tag(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
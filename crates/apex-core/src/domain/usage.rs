@@ -0,0 +1,229 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Plan;
+use crate::error::DomainError;
+
+/// Billable quantity tracked per account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageMetric {
+    /// Authenticated API requests.
+    ApiCalls,
+    /// Net bytes stored (negative quantities record deletions).
+    StorageBytes,
+    /// Background jobs run on the account's behalf.
+    JobsExecuted,
+}
+
+impl UsageMetric {
+    pub const ALL: [UsageMetric; 3] = [
+        UsageMetric::ApiCalls,
+        UsageMetric::StorageBytes,
+        UsageMetric::JobsExecuted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageMetric::ApiCalls => "api_calls",
+            UsageMetric::StorageBytes => "storage_bytes",
+            UsageMetric::JobsExecuted => "jobs_executed",
+        }
+    }
+}
+
+impl std::fmt::Display for UsageMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for UsageMetric {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        UsageMetric::ALL
+            .into_iter()
+            .find(|m| m.as_str() == s)
+            .ok_or_else(|| DomainError::Validation(format!("Unknown usage metric: {}", s)))
+    }
+}
+
+/// Monthly rollup of one metric for one account.
+///
+/// The account is the organization for org-scoped activity, otherwise the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageTotal {
+    pub account_id: Uuid,
+    pub metric: UsageMetric,
+    /// First day of the billing month.
+    pub period_start: NaiveDate,
+    pub quantity: i64,
+}
+
+/// Bytes an account stores, against its plan's storage quota.
+///
+/// Unlike the monthly `StorageBytes` rollups this is a running total.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub account_id: Uuid,
+    pub plan: Plan,
+    pub used_bytes: i64,
+    /// `None` when the plan has no quota.
+    pub quota_bytes: Option<i64>,
+}
+
+impl StorageUsage {
+    /// Whether storing `bytes` more stays within the quota.
+    pub fn fits(&self, bytes: i64) -> bool {
+        self.quota_bytes
+            .is_none_or(|quota| self.used_bytes.saturating_add(bytes) <= quota)
+    }
+}
+
+/// Calendar window (UTC) a long-horizon API quota counts requests in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaPeriod {
+    Day,
+    Month,
+}
+
+impl QuotaPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaPeriod::Day => "day",
+            QuotaPeriod::Month => "month",
+        }
+    }
+
+    /// Start of the window containing `at`.
+    pub fn window_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let day = match self {
+            QuotaPeriod::Day => at.date_naive(),
+            QuotaPeriod::Month => billing_period(at),
+        };
+        day.and_time(NaiveTime::MIN).and_utc()
+    }
+
+    /// Start of the window after the one containing `at`, when its count
+    /// resets.
+    pub fn window_end(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.window_start(at);
+        match self {
+            QuotaPeriod::Day => start + Days::new(1),
+            QuotaPeriod::Month => start + Months::new(1),
+        }
+    }
+}
+
+impl std::fmt::Display for QuotaPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for QuotaPeriod {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "d" | "day" | "daily" => Ok(QuotaPeriod::Day),
+            "mo" | "month" | "monthly" => Ok(QuotaPeriod::Month),
+            _ => Err(DomainError::Validation(format!(
+                "Unknown quota period: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Most API requests an account may make per calendar window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiQuota {
+    pub period: QuotaPeriod,
+    pub limit: u64,
+}
+
+/// Requests an account made in a quota's current window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub period: QuotaPeriod,
+    pub limit: u64,
+    pub used: u64,
+    /// When the window ends and the count starts over.
+    pub resets_at: DateTime<Utc>,
+}
+
+impl QuotaUsage {
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.used)
+    }
+}
+
+/// First day of the month containing `at`.
+pub fn billing_period(at: DateTime<Utc>) -> NaiveDate {
+    at.date_naive()
+        .with_day(1)
+        .expect("day 1 exists in every month")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_billing_period_is_month_start() {
+        let at = "2026-03-31T23:59:59Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            billing_period(at),
+            NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_quota_windows_follow_the_calendar() {
+        let at = "2026-01-31T18:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let day = QuotaPeriod::Day;
+        assert_eq!(
+            day.window_start(at).to_rfc3339(),
+            "2026-01-31T00:00:00+00:00"
+        );
+        assert_eq!(day.window_end(at).to_rfc3339(), "2026-02-01T00:00:00+00:00");
+        let month = QuotaPeriod::Month;
+        assert_eq!(
+            month.window_start(at).to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            month.window_end(at).to_rfc3339(),
+            "2026-02-01T00:00:00+00:00"
+        );
+        assert_eq!("mo".parse::<QuotaPeriod>().unwrap(), month);
+        assert!("week".parse::<QuotaPeriod>().is_err());
+    }
+
+    #[test]
+    fn test_metric_round_trip() {
+        for metric in UsageMetric::ALL {
+            assert_eq!(metric.as_str().parse::<UsageMetric>().unwrap(), metric);
+        }
+        assert!("seats".parse::<UsageMetric>().is_err());
+    }
+
+    #[test]
+    fn test_storage_fits_quota() {
+        let mut usage = StorageUsage {
+            account_id: Uuid::new_v4(),
+            plan: Plan::Free,
+            used_bytes: 900,
+            quota_bytes: Some(1000),
+        };
+        assert!(usage.fits(100));
+        assert!(!usage.fits(101));
+
+        usage.quota_bytes = None;
+        assert!(usage.fits(i64::MAX));
+    }
+}
@@ -2,26 +2,109 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::Email;
+use crate::error::DomainError;
+
+/// Longest role name accepted.
+const MAX_ROLE_LENGTH: usize = 32;
+
 /// User entity - represents a user in the system.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: Uuid,
-    pub email: String,
+    pub email: Email,
     pub password_hash: String,
+    /// Roles granted to the user, copied into every token issued to them.
+    #[serde(default = "User::default_roles")]
+    pub roles: Vec<String>,
+    /// Phone number, e.g. for SMS verification. Encrypted at rest.
+    #[serde(default)]
+    pub phone_number: Option<String>,
+    /// Seed of the user's authenticator app, set while two-factor sign-in
+    /// is on. Encrypted at rest.
+    #[serde(default)]
+    pub two_factor_secret: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// User who created the account, e.g. an admin; `None` for sign-ups.
+    /// Repositories fill it in from the request on the first save.
+    #[serde(default)]
+    pub created_by: Option<Uuid>,
+    /// User who last saved the account, stamped from the request.
+    #[serde(default)]
+    pub updated_by: Option<Uuid>,
 }
 
 impl User {
+    /// Role every account starts with.
+    pub const DEFAULT_ROLE: &'static str = "user";
+
+    /// Role granting access to the admin endpoints.
+    pub const ADMIN_ROLE: &'static str = "admin";
+
     /// Create a new user with generated ID and timestamps.
-    pub fn new(email: String, password_hash: String) -> Self {
+    pub fn new(email: Email, password_hash: String) -> Self {
         let now = Utc::now();
         Self {
             id: Uuid::new_v4(),
             email,
             password_hash,
+            roles: Self::default_roles(),
+            phone_number: None,
+            two_factor_secret: None,
             created_at: now,
             updated_at: now,
+            created_by: None,
+            updated_by: None,
+        }
+    }
+
+    /// Roles of a new account, and of stored ones that predate roles.
+    pub fn default_roles() -> Vec<String> {
+        vec![Self::DEFAULT_ROLE.to_string()]
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    /// Grant `role`. Returns whether the user did not have it yet.
+    pub fn grant_role(&mut self, role: &str) -> Result<bool, DomainError> {
+        Self::validate_role(role)?;
+        if self.has_role(role) {
+            return Ok(false);
+        }
+        self.roles.push(role.to_string());
+        Ok(true)
+    }
+
+    /// Revoke `role`. Returns whether the user had it.
+    pub fn revoke_role(&mut self, role: &str) -> bool {
+        let before = self.roles.len();
+        self.roles.retain(|r| r != role);
+        self.roles.len() != before
+    }
+
+    /// Store a new password hash, returning the one it replaces.
+    pub fn replace_password_hash(&mut self, password_hash: String) -> String {
+        self.updated_at = Utc::now();
+        std::mem::replace(&mut self.password_hash, password_hash)
+    }
+
+    /// Role names are short lowercase identifiers, e.g. `admin` or
+    /// `support_agent`.
+    pub fn validate_role(role: &str) -> Result<(), DomainError> {
+        let valid = !role.is_empty()
+            && role.len() <= MAX_ROLE_LENGTH
+            && role
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if valid {
+            Ok(())
+        } else {
+            Err(DomainError::Validation(format!(
+                "Role names are 1-{MAX_ROLE_LENGTH} lowercase letters, digits, '_' or '-'"
+            )))
         }
     }
 }
@@ -32,7 +115,7 @@ mod tests {
 
     #[test]
     fn test_user_new_creates_valid_user() {
-        let email = "test@example.com".to_string();
+        let email = Email::parse("test@example.com").unwrap();
         let password_hash = "hashed_password".to_string();
 
         let user = User::new(email.clone(), password_hash.clone());
@@ -41,19 +124,47 @@ mod tests {
         assert_eq!(user.password_hash, password_hash);
         assert_ne!(user.id, Uuid::nil());
         assert_eq!(user.created_at, user.updated_at);
+        assert_eq!(user.roles, vec!["user".to_string()]);
+    }
+
+    #[test]
+    fn test_roles_are_granted_and_revoked_once() {
+        let mut user = User::new(
+            Email::parse("test@example.com").unwrap(),
+            "hash".to_string(),
+        );
+
+        assert!(user.grant_role("admin").unwrap());
+        assert!(!user.grant_role("admin").unwrap());
+        assert_eq!(user.roles, vec!["user".to_string(), "admin".to_string()]);
+        assert!(user.has_role("admin"));
+
+        assert!(user.revoke_role("admin"));
+        assert!(!user.revoke_role("admin"));
+        assert!(!user.has_role("admin"));
+
+        for role in ["", "Admin", "super admin", &"x".repeat(33)] {
+            assert!(
+                user.grant_role(role).is_err(),
+                "{role:?} should be rejected"
+            );
+        }
     }
 
     #[test]
     fn test_user_new_generates_unique_ids() {
-        let user1 = User::new("user1@test.com".to_string(), "hash1".to_string());
-        let user2 = User::new("user2@test.com".to_string(), "hash2".to_string());
+        let user1 = User::new(Email::parse("user1@test.com").unwrap(), "hash1".to_string());
+        let user2 = User::new(Email::parse("user2@test.com").unwrap(), "hash2".to_string());
 
         assert_ne!(user1.id, user2.id);
     }
 
     #[test]
     fn test_user_serialization() {
-        let user = User::new("test@example.com".to_string(), "hash".to_string());
+        let user = User::new(
+            Email::parse("test@example.com").unwrap(),
+            "hash".to_string(),
+        );
 
         let json = serde_json::to_string(&user).expect("Should serialize");
         assert!(json.contains("test@example.com"));
@@ -61,5 +172,19 @@ mod tests {
         let deserialized: User = serde_json::from_str(&json).expect("Should deserialize");
         assert_eq!(deserialized.id, user.id);
         assert_eq!(deserialized.email, user.email);
+        assert_eq!(deserialized.roles, user.roles);
+    }
+
+    #[test]
+    fn test_users_stored_without_roles_get_the_default() {
+        let user = User::new(
+            Email::parse("test@example.com").unwrap(),
+            "hash".to_string(),
+        );
+        let mut json = serde_json::to_value(&user).unwrap();
+        json.as_object_mut().unwrap().remove("roles");
+
+        let deserialized: User = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.roles, User::default_roles());
     }
 }
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Webhook delivery - an audit record of one outbound delivery attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub url: String,
+    pub payload: serde_json::Value,
+    /// HTTP status returned by the endpoint, if a response was received.
+    pub response_status: Option<u16>,
+    pub response_body: Option<String>,
+    /// Transport error, if no response was received.
+    pub error: Option<String>,
+    pub latency_ms: u64,
+    /// Attempt number, starting at 1.
+    pub attempt: u32,
+    /// For a replay, the first delivery of the chain it belongs to.
+    pub replay_of: Option<Uuid>,
+    /// Request during which the attempt was made, if any.
+    #[serde(default)]
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookDelivery {
+    /// The first delivery of this one's chain: itself, unless it is a replay.
+    pub fn chain_id(&self) -> Uuid {
+        self.replay_of.unwrap_or(self.id)
+    }
+
+    /// Whether the endpoint accepted the delivery (2xx status).
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+            && self
+                .response_status
+                .map(|s| (200..300).contains(&s))
+                .unwrap_or(false)
+    }
+}
@@ -36,4 +36,19 @@ pub enum RepoError {
 
     #[error("Constraint violation: {0}")]
     Constraint(String),
+
+    /// A versioned write found the stored entity changed since it was read.
+    #[error("Conflict: {0}")]
+    Conflict(String),
+}
+
+impl RepoError {
+    /// Conflict for a write expecting entity `id` at `expected_version`, or
+    /// with `None`, expecting it not to exist yet.
+    pub fn stale_version(id: impl std::fmt::Display, expected_version: Option<i64>) -> Self {
+        RepoError::Conflict(match expected_version {
+            Some(version) => format!("{} changed since version {}", id, version),
+            None => format!("{} already exists", id),
+        })
+    }
 }
@@ -3,8 +3,12 @@
 //! The domain layer of the Apex boilerplate.
 //! This crate contains pure business logic with zero infrastructure dependencies.
 
+pub mod context;
 pub mod domain;
 pub mod error;
+pub mod pii;
 pub mod ports;
+pub mod services;
 
+pub use context::RequestContext;
 pub use error::DomainError;
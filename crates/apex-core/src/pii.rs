@@ -0,0 +1,201 @@
+//! Personal data in logs.
+//!
+//! Log fields are classified by name: sensitive ones (emails, passwords,
+//! tokens, phone numbers) are masked wherever the server writes logs,
+//! internal ones (ids, IP addresses) are logged but kept out of alerts that
+//! leave the system, and everything else is public.
+//!
+//! Field names are a safety net. Code logging personal data under a neutral
+//! name wraps it in [`Sensitive`], which masks it whatever the name and
+//! whichever subscriber records it:
+//!
+//! ```
+//! use apex_core::pii::Sensitive;
+//!
+//! let recipient = "jane@example.com";
+//! assert_eq!(Sensitive(recipient).to_string(), "j***@example.com");
+//! ```
+
+use std::fmt;
+
+/// How freely a log field may be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FieldClass {
+    Public,
+    /// Fine in our own logs, not in alerts sent to chat or paging tools.
+    Internal,
+    /// Masked everywhere.
+    Sensitive,
+}
+
+/// Name segments marking a field as sensitive, e.g. `user_email` or
+/// `access_token`.
+const SENSITIVE_SEGMENTS: [&str; 13] = [
+    "email",
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "cookie",
+    "authorization",
+    "phone",
+    "ssn",
+    "card",
+    "iban",
+    "signature",
+    "otp",
+];
+
+/// Names containing these are sensitive too.
+const SENSITIVE_NAMES: [&str; 3] = ["api_key", "apikey", "private_key"];
+
+/// Name segments marking a field as internal, e.g. `account_id` or
+/// `client_ip`.
+const INTERNAL_SEGMENTS: [&str; 5] = ["id", "ids", "ip", "addr", "agent"];
+
+/// Class of the log field `name`, from the words it is made of.
+pub fn classify(name: &str) -> FieldClass {
+    let name = name.to_ascii_lowercase();
+    let segments = || name.split(['_', '.', '-']);
+    if segments().any(|segment| SENSITIVE_SEGMENTS.contains(&segment))
+        || SENSITIVE_NAMES
+            .iter()
+            .any(|sensitive| name.contains(sensitive))
+    {
+        FieldClass::Sensitive
+    } else if segments().any(|segment| INTERNAL_SEGMENTS.contains(&segment)) {
+        FieldClass::Internal
+    } else {
+        FieldClass::Public
+    }
+}
+
+/// `j***@example.com` for `jane@example.com`: the domain stays readable for
+/// debugging deliverability, the mailbox does not.
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => match local.chars().next() {
+            Some(first) if local.chars().count() > 1 => format!("{}***@{}", first, domain),
+            _ => format!("***@{}", domain),
+        },
+        None => "***".to_string(),
+    }
+}
+
+/// A sensitive value as it may be logged: emails masked by
+/// [`mask_email`], anything else replaced entirely.
+pub fn mask(value: &str) -> String {
+    match value.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => mask_email(value),
+        _ => "***".to_string(),
+    }
+}
+
+/// `value` with the values of sensitive keys masked, at any depth; for
+/// logging payloads and request bodies.
+pub fn redact(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| {
+                    let value = match (classify(key), value) {
+                        (FieldClass::Sensitive, Value::String(s)) => Value::String(mask(s)),
+                        (FieldClass::Sensitive, Value::Null) => Value::Null,
+                        (FieldClass::Sensitive, _) => Value::String("***".to_string()),
+                        _ => redact(value),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// A value that is logged masked, e.g.
+/// `tracing::info!(recipient = %Sensitive(&to), "Sending email")`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Sensitive<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&mask(&self.0.to_string()))
+    }
+}
+
+impl<T: fmt::Display> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_are_classified_by_name() {
+        for name in [
+            "email",
+            "user_email",
+            "password",
+            "access_token",
+            "Authorization",
+            "api_key",
+            "stripe_apikey",
+            "phone_number",
+        ] {
+            assert_eq!(classify(name), FieldClass::Sensitive, "{}", name);
+        }
+        for name in [
+            "user_id",
+            "account_id",
+            "client_ip",
+            "remote_addr",
+            "user_agent",
+        ] {
+            assert_eq!(classify(name), FieldClass::Internal, "{}", name);
+        }
+        for name in [
+            "job_type",
+            "recipient",
+            "status_code",
+            "description",
+            "message",
+        ] {
+            assert_eq!(classify(name), FieldClass::Public, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_masking() {
+        assert_eq!(mask_email("jane@example.com"), "j***@example.com");
+        assert_eq!(mask_email("j@example.com"), "***@example.com");
+        assert_eq!(mask_email("élodie@example.com"), "é***@example.com");
+        assert_eq!(mask_email("not an email"), "***");
+        assert_eq!(mask("hunter2"), "***");
+        assert_eq!(mask("@handle"), "***");
+        assert_eq!(
+            format!("{:?}", Sensitive("jane@example.com")),
+            "j***@example.com"
+        );
+        assert_eq!(Sensitive("sk_live_123").to_string(), "***");
+
+        let payload = serde_json::json!({
+            "template": "welcome",
+            "to_email": "jane@example.com",
+            "users": [{"id": 1, "password": "hunter2", "otp": 123456}],
+        });
+        assert_eq!(
+            redact(&payload),
+            serde_json::json!({
+                "template": "welcome",
+                "to_email": "j***@example.com",
+                "users": [{"id": 1, "password": "***", "otp": "***"}],
+            })
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Admin approval storage port.
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{ApprovalStatus, PendingOperation};
+use crate::error::{DomainError, RepoError};
+use crate::ports::BaseRepository;
+
+/// Admin operations staged for approval.
+#[async_trait]
+pub trait PendingOperationRepository: BaseRepository<PendingOperation, Uuid> {
+    /// Operations newest first, only those in `status` when given.
+    async fn list(
+        &self,
+        status: Option<ApprovalStatus>,
+        limit: u64,
+    ) -> Result<Vec<PendingOperation>, RepoError>;
+
+    /// Save an approval or rejection, unless the operation is no longer
+    /// pending. Returns false when another admin decided it first, so an
+    /// operation never runs twice.
+    async fn decide(&self, operation: &PendingOperation) -> Result<bool, RepoError>;
+}
+
+/// Admin approval errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ApprovalError {
+    #[error("Operation {0} not found")]
+    NotFound(Uuid),
+
+    /// The approver staged the operation themselves.
+    #[error("Operations need a second admin's approval")]
+    SelfApproval,
+
+    #[error("Operation is already {0}")]
+    AlreadyDecided(ApprovalStatus),
+
+    /// The operation expired, or cannot run in this build.
+    #[error(transparent)]
+    Invalid(#[from] DomainError),
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+}
@@ -3,15 +3,47 @@
 use async_trait::async_trait;
 use uuid::Uuid;
 
+use crate::domain::{OrgRole, Plan, Subscription, SubscriptionStatus};
+
 /// Claims stored in JWT tokens.
 #[derive(Debug, Clone)]
 pub struct TokenClaims {
     pub user_id: Uuid,
     pub email: String,
     pub roles: Vec<String>,
+    /// Active organization, set when the user has switched into one.
+    pub org: Option<OrgClaim>,
+    /// Subscription of the token's account (the organization, or the user)
+    /// when the token was issued.
+    pub subscription: Option<SubscriptionClaim>,
     pub exp: i64,
 }
 
+/// Organization context carried in a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrgClaim {
+    pub id: Uuid,
+    pub role: OrgRole,
+}
+
+/// Subscription state carried in a token, for gating without a lookup.
+///
+/// Only as fresh as the token; re-issue tokens after billing changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionClaim {
+    pub plan: Plan,
+    pub status: SubscriptionStatus,
+}
+
+impl From<Subscription> for SubscriptionClaim {
+    fn from(subscription: Subscription) -> Self {
+        Self {
+            plan: subscription.plan,
+            status: subscription.status,
+        }
+    }
+}
+
 /// Token service trait for JWT operations.
 #[async_trait]
 pub trait TokenService: Send + Sync {
@@ -21,6 +53,17 @@ pub trait TokenService: Send + Sync {
         user_id: Uuid,
         email: &str,
         roles: Vec<String>,
+        subscription: Option<SubscriptionClaim>,
+    ) -> Result<String, AuthError>;
+
+    /// Generate access token scoped to one of the user's organizations.
+    fn generate_org_token(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        roles: Vec<String>,
+        org: OrgClaim,
+        subscription: Option<SubscriptionClaim>,
     ) -> Result<String, AuthError>;
 
     /// Validate and decode a token.
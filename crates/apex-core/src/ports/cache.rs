@@ -15,6 +15,21 @@ pub trait Cache: Send + Sync {
 
     /// Check if a key exists.
     async fn exists(&self, key: &str) -> bool;
+
+    /// Add `delta` to the integer at `key` (0 if missing), returning the new
+    /// value. Counters have no TTL.
+    async fn incr(&self, key: &str, delta: i64) -> Result<i64, CacheError>;
+
+    /// Like [`incr`](Self::incr), but a counter this call creates expires
+    /// after `ttl`. Later increments keep that expiry.
+    async fn incr_expiring(&self, key: &str, delta: i64, ttl: Duration) -> Result<i64, CacheError>;
+
+    /// Remove `key` and return its value, atomically, so no concurrent
+    /// write between reading and deleting is lost.
+    async fn take(&self, key: &str) -> Result<Option<String>, CacheError>;
+
+    /// Keys starting with `prefix`.
+    async fn keys(&self, prefix: &str) -> Result<Vec<String>, CacheError>;
 }
 
 /// Cache operation errors.
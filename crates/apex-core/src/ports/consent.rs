@@ -0,0 +1,27 @@
+//! Policy consent storage port.
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::PolicyAcceptance;
+use crate::error::{DomainError, RepoError};
+
+/// Append-only log of policy acceptances.
+#[async_trait]
+pub trait ConsentRepository: Send + Sync {
+    async fn record(&self, acceptance: PolicyAcceptance) -> Result<PolicyAcceptance, RepoError>;
+
+    /// The user's acceptances, newest first.
+    async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<PolicyAcceptance>, RepoError>;
+}
+
+/// Policy consent errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ConsentError {
+    /// The policy or version is not the one users are asked to accept.
+    #[error(transparent)]
+    Invalid(#[from] DomainError),
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+}
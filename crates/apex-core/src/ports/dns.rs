@@ -0,0 +1,40 @@
+//! DNS lookup port.
+
+use async_trait::async_trait;
+
+/// Looks up DNS records, e.g. to check domain ownership challenges.
+#[async_trait]
+pub trait DnsResolver: Send + Sync {
+    /// TXT record values at `name`; none when the name does not exist.
+    async fn txt_records(&self, name: &str) -> Result<Vec<String>, DnsError>;
+}
+
+/// DNS lookup errors.
+#[derive(Debug, thiserror::Error)]
+pub enum DnsError {
+    #[error("DNS lookup failed: {0}")]
+    Lookup(String),
+}
+
+/// Custom domain management errors.
+#[derive(Debug, thiserror::Error)]
+pub enum CustomDomainError {
+    #[error(transparent)]
+    Invalid(#[from] crate::error::DomainError),
+
+    #[error("Domain {0} is already verified by another organization")]
+    Taken(String),
+
+    #[error("Domain not found")]
+    NotFound,
+
+    /// The TXT record is missing or holds another value.
+    #[error("TXT record {name} does not contain {value}")]
+    ChallengeFailed { name: String, value: String },
+
+    #[error(transparent)]
+    Dns(#[from] DnsError),
+
+    #[error(transparent)]
+    Repo(#[from] crate::error::RepoError),
+}
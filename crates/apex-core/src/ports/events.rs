@@ -0,0 +1,22 @@
+//! Domain event port - where services announce what they changed.
+
+use async_trait::async_trait;
+
+use crate::domain::DomainEvent;
+
+/// Delivers domain events to whatever reacts to them.
+#[async_trait]
+pub trait EventDispatcher: Send + Sync {
+    /// Deliver `event`. The change it reports is already stored, so
+    /// delivery is best effort: implementations log what they fail to
+    /// deliver rather than fail the caller.
+    async fn dispatch(&self, event: DomainEvent);
+}
+
+/// Drops every event, for code paths with nothing subscribed.
+pub struct NoopEventDispatcher;
+
+#[async_trait]
+impl EventDispatcher for NoopEventDispatcher {
+    async fn dispatch(&self, _event: DomainEvent) {}
+}
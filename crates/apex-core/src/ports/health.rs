@@ -0,0 +1,21 @@
+//! Dependency health port.
+
+use async_trait::async_trait;
+
+/// Checks that an external dependency, such as the database or Redis,
+/// answers, and re-establishes the connection to it when it does not.
+#[async_trait]
+pub trait DependencyProbe: Send + Sync {
+    /// Name the dependency is reported under, e.g. `database`.
+    fn name(&self) -> &str;
+
+    /// Whether the dependency answers, and why not.
+    async fn check(&self) -> Result<(), String>;
+
+    /// Rebuild the connection after a failed check, and check it again.
+    /// Probes over connections that heal themselves keep the default, which
+    /// only checks again.
+    async fn reconnect(&self) -> Result<(), String> {
+        self.check().await
+    }
+}
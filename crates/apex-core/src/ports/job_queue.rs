@@ -4,6 +4,8 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// A job that can be queued and processed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +24,17 @@ pub struct Job {
     pub created_at: chrono::DateTime<chrono::Utc>,
     /// When to execute the job (for delayed jobs).
     pub scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Deduplication key. While a job with the same key is pending or
+    /// processing, enqueueing another one is a no-op.
+    #[serde(default)]
+    pub unique_key: Option<String>,
+    /// Account (organization or user) the job runs for, used for usage metering.
+    #[serde(default)]
+    pub account_id: Option<uuid::Uuid>,
+    /// Context of the request that enqueued the job; current again while
+    /// the job runs.
+    #[serde(default)]
+    pub context: Option<crate::RequestContext>,
 }
 
 impl Job {
@@ -34,6 +47,9 @@ impl Job {
             max_attempts: 3,
             created_at: chrono::Utc::now(),
             scheduled_at: None,
+            unique_key: None,
+            account_id: None,
+            context: None,
         }
     }
 
@@ -42,6 +58,18 @@ impl Job {
         self
     }
 
+    /// Deduplicate against other jobs enqueued with the same key.
+    pub fn with_unique_key(mut self, key: impl Into<String>) -> Self {
+        self.unique_key = Some(key.into());
+        self
+    }
+
+    /// Attribute the job to an account for usage metering.
+    pub fn for_account(mut self, account_id: uuid::Uuid) -> Self {
+        self.account_id = Some(account_id);
+        self
+    }
+
     pub fn delayed(mut self, delay: chrono::Duration) -> Self {
         self.scheduled_at = Some(chrono::Utc::now() + delay);
         self
@@ -53,20 +81,88 @@ impl Job {
 pub enum JobResult {
     /// Job completed successfully.
     Success,
+    /// Job completed successfully with a result for `JobQueue::get_status` callers.
+    SuccessWith(serde_json::Value),
     /// Job failed, should be retried.
     Retry(String),
     /// Job failed permanently, should not be retried.
     Failed(String),
 }
 
-/// Job handler function type.
+/// A job that failed permanently, kept in the dead letter queue for
+/// inspection and manual retry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadJob {
+    pub job: Job,
+    pub error: String,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl DeadJob {
+    pub fn new(job: Job, error: impl Into<String>) -> Self {
+        Self {
+            job,
+            error: error.into(),
+            failed_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// Processing state of a job, as reported by `JobQueue::get_status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for a worker, including between retries.
+    Pending,
+    Processing,
+    Completed {
+        /// Value returned via `JobResult::SuccessWith`, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<serde_json::Value>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+impl JobStatus {
+    /// Whether the job has reached a final state.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, JobStatus::Completed { .. } | JobStatus::Failed { .. })
+    }
+}
 
 /// Job queue trait - abstraction over job queue backends.
 #[async_trait]
 pub trait JobQueue: Send + Sync {
     /// Enqueue a job for processing.
+    ///
+    /// Jobs with a `unique_key` that is already pending or processing are
+    /// silently dropped.
     async fn enqueue(&self, job: Job) -> Result<(), JobQueueError>;
 
+    /// Enqueue many jobs at once.
+    ///
+    /// Backends override this with a bulk path; the default enqueues one by one.
+    async fn enqueue_batch(&self, jobs: Vec<Job>) -> Result<(), JobQueueError> {
+        for job in jobs {
+            self.enqueue(job).await?;
+        }
+        Ok(())
+    }
+
+    /// Enqueue a copy of `job` at every occurrence of the cron expression
+    /// `schedule` (seconds optional) until the queue shuts down.
+    ///
+    /// Each copy goes through the queue like any other job, so it gets
+    /// retries, stats and dead-lettering. Copies are deduplicated per
+    /// occurrence, so instances sharing a queue enqueue each one once.
+    async fn enqueue_recurring(
+        self: Arc<Self>,
+        job: Job,
+        schedule: &str,
+    ) -> Result<(), JobQueueError>;
+
     /// Start processing jobs with the given handler.
     async fn start_worker<F>(&self, handler: F) -> Result<(), JobQueueError>
     where
@@ -74,6 +170,62 @@ pub trait JobQueue: Send + Sync {
 
     /// Get queue statistics.
     async fn stats(&self) -> Result<QueueStats, JobQueueError>;
+
+    /// Current status of a job, so callers can poll the outcome of work they
+    /// enqueued.
+    ///
+    /// Returns `None` for unknown jobs, including jobs skipped as duplicates
+    /// and finished jobs whose status has outlived the backend's result TTL.
+    async fn get_status(&self, job_id: &str) -> Result<Option<JobStatus>, JobQueueError>;
+
+    /// Jobs waiting for a worker, oldest first, at most `limit`. With a
+    /// `job_type`, only jobs of that type.
+    async fn list_pending(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError>;
+
+    /// Jobs workers are running, across every instance sharing the queue
+    /// where the backend can see them, at most `limit`.
+    async fn list_processing(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError>;
+
+    /// Jobs in the dead letter queue, newest first, at most `limit`.
+    async fn list_dead(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DeadJob>, JobQueueError>;
+
+    /// Move a dead job back onto the queue with its attempts reset.
+    ///
+    /// Returns `false` if no dead job has the id.
+    async fn retry_dead(&self, job_id: &str) -> Result<bool, JobQueueError>;
+
+    /// Drop a pending job before a worker picks it up.
+    ///
+    /// Returns `false` if no pending job has the id.
+    async fn delete_pending(&self, job_id: &str) -> Result<bool, JobQueueError>;
+
+    /// Drop a job from the dead letter queue.
+    ///
+    /// Returns `false` if no dead job has the id.
+    async fn delete_dead(&self, job_id: &str) -> Result<bool, JobQueueError>;
+
+    /// Drop every pending job, returning how many were dropped. Jobs already
+    /// processing and dead jobs are kept.
+    async fn purge(&self) -> Result<usize, JobQueueError>;
+
+    /// Stop the workers and wait up to `timeout` for in-flight jobs to finish.
+    ///
+    /// New jobs are rejected with [`JobQueueError::ShuttingDown`] from the
+    /// moment this is called. Jobs still running when the timeout elapses are
+    /// cancelled and put back on the queue if the backend can persist them.
+    async fn shutdown(&self, timeout: Duration) -> Result<(), JobQueueError>;
 }
 
 /// Queue statistics.
@@ -83,6 +235,8 @@ pub struct QueueStats {
     pub processing: usize,
     pub completed: usize,
     pub failed: usize,
+    /// Jobs currently in the dead letter queue.
+    pub dead: usize,
 }
 
 /// Job queue errors.
@@ -94,6 +248,12 @@ pub enum JobQueueError {
     #[error("Queue is full")]
     QueueFull,
 
+    #[error("Queue is shutting down")]
+    ShuttingDown,
+
     #[error("Backend error: {0}")]
     Backend(String),
+
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(String),
 }
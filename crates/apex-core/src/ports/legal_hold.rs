@@ -0,0 +1,29 @@
+//! Legal hold storage port.
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::LegalHoldChange;
+use crate::error::RepoError;
+
+/// Append-only log of legal hold changes.
+#[async_trait]
+pub trait LegalHoldRepository: Send + Sync {
+    async fn record(&self, change: LegalHoldChange) -> Result<LegalHoldChange, RepoError>;
+
+    /// The user's changes, newest first.
+    async fn history(&self, user_id: Uuid) -> Result<Vec<LegalHoldChange>, RepoError>;
+
+    /// The user's latest change, which says whether they are held.
+    async fn latest(&self, user_id: Uuid) -> Result<Option<LegalHoldChange>, RepoError> {
+        Ok(self.history(user_id).await?.into_iter().next())
+    }
+
+    /// Whether the user's data must not be deleted or anonymized.
+    async fn is_held(&self, user_id: Uuid) -> Result<bool, RepoError> {
+        Ok(self
+            .latest(user_id)
+            .await?
+            .is_some_and(|change| change.held))
+    }
+}
@@ -0,0 +1,38 @@
+//! Traffic mirroring port - replays production requests against a shadow deployment.
+
+use async_trait::async_trait;
+
+/// A copy of an incoming request, to be replayed against the shadow.
+#[derive(Debug, Clone)]
+pub struct MirrorRequest {
+    pub method: String,
+    /// Path and query string, e.g. `/api/plan?verbose=1`.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// What the shadow answered.
+#[derive(Debug, Clone)]
+pub struct MirrorResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Sends mirrored requests to the shadow deployment.
+#[async_trait]
+pub trait TrafficMirror: Send + Sync {
+    /// Replay a request. Non-2xx responses are returned as `Ok`; only
+    /// transport failures are errors.
+    async fn send(&self, request: &MirrorRequest) -> Result<MirrorResponse, MirrorError>;
+}
+
+/// Traffic mirroring errors.
+#[derive(Debug, thiserror::Error)]
+pub enum MirrorError {
+    #[error("Mirroring failed: {0}")]
+    Transport(String),
+
+    #[error("Shadow timed out")]
+    Timeout,
+}
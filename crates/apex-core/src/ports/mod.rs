@@ -1,16 +1,57 @@
 //! Ports - trait definitions for external dependencies.
 //! These are the "interfaces" that infrastructure must implement.
 
+mod approval;
 mod auth;
 mod cache;
+mod consent;
+mod dns;
+mod events;
+mod health;
 mod job_queue;
+mod legal_hold;
+mod mirror;
+mod password_history;
+mod plan;
 mod pubsub;
 mod rate_limit;
 mod repository;
+mod secrets;
+mod settings;
+mod storage;
+mod subscription;
+mod tag;
+mod usage;
+mod webhook;
 
-pub use auth::{AuthError, PasswordService, TokenClaims, TokenService};
+pub use approval::{ApprovalError, PendingOperationRepository};
+pub use auth::{
+    AuthError, OrgClaim, PasswordService, SubscriptionClaim, TokenClaims, TokenService,
+};
 pub use cache::{Cache, CacheError};
-pub use job_queue::{Job, JobQueue, JobQueueError, JobResult, QueueStats};
-pub use pubsub::{PubSub, PubSubError, PubSubMessage};
-pub use rate_limit::{RateLimitError, RateLimitResult, RateLimiter};
-pub use repository::{BaseRepository, PostRepository, UserRepository};
+pub use consent::{ConsentError, ConsentRepository};
+pub use dns::{CustomDomainError, DnsError, DnsResolver};
+pub use events::{EventDispatcher, NoopEventDispatcher};
+pub use health::DependencyProbe;
+pub use job_queue::{DeadJob, Job, JobQueue, JobQueueError, JobResult, JobStatus, QueueStats};
+pub use legal_hold::LegalHoldRepository;
+pub use mirror::{MirrorError, MirrorRequest, MirrorResponse, TrafficMirror};
+pub use password_history::PasswordHistoryRepository;
+pub use plan::{EntitlementError, PlanRepository};
+pub use pubsub::{
+    Envelope, PubSub, PubSubError, PubSubMessage, PubSubStats, RpcReply, RpcRequest,
+    SubscriptionHandle,
+};
+pub use rate_limit::{RateLimitError, RateLimitResult, RateLimitTier, RateLimiter};
+pub use repository::{
+    AnnouncementRepository, AttachmentRepository, BaseRepository, CommentRepository,
+    CustomDomainRepository, InvitationRepository, MembershipRepository, OAuthClientRepository,
+    OrganizationRepository, PostRepository, UserRepository, WebhookDeliveryRepository,
+};
+pub use secrets::{SecretsError, SecretsProvider};
+pub use settings::{SettingsError, SettingsRepository};
+pub use storage::{ByteStream, StorageError, StorageService, StoredObject};
+pub use subscription::{SubscriptionError, SubscriptionRepository};
+pub use tag::TagRepository;
+pub use usage::{ApiQuotaError, StorageQuotaError, StorageUsageRepository, UsageRepository};
+pub use webhook::{WebhookError, WebhookRequest, WebhookResponse, WebhookSender};
@@ -0,0 +1,22 @@
+//! Password history storage port.
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::error::RepoError;
+
+/// Hashes of the passwords users had before their current one.
+#[async_trait]
+pub trait PasswordHistoryRepository: Send + Sync {
+    /// Up to `limit` of the user's earlier hashes, newest first.
+    async fn recent(&self, user_id: Uuid, limit: usize) -> Result<Vec<String>, RepoError>;
+
+    /// Remember a hash the user just replaced, keeping only their `keep`
+    /// newest ones.
+    async fn record(
+        &self,
+        user_id: Uuid,
+        password_hash: String,
+        keep: usize,
+    ) -> Result<(), RepoError>;
+}
@@ -0,0 +1,30 @@
+//! Account plan storage port.
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{Entitlement, Plan};
+use crate::error::RepoError;
+
+/// Plan assignments per account (organization or user).
+#[async_trait]
+pub trait PlanRepository: Send + Sync {
+    /// The account's plan, or `None` if it was never assigned one.
+    async fn get_plan(&self, account_id: Uuid) -> Result<Option<Plan>, RepoError>;
+
+    async fn set_plan(&self, account_id: Uuid, plan: Plan) -> Result<(), RepoError>;
+}
+
+/// Entitlement check errors.
+#[derive(Debug, thiserror::Error)]
+pub enum EntitlementError {
+    /// The account's plan does not include the entitlement.
+    #[error("The {plan} plan does not include {entitlement}")]
+    Missing {
+        entitlement: Entitlement,
+        plan: Plan,
+    },
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+}
@@ -1,8 +1,12 @@
 //! Pub/Sub port - abstraction over pub/sub backends.
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::pin::Pin;
+use std::time::Duration;
+use uuid::Uuid;
 
 /// Message received from a channel.
 #[derive(Debug, Clone)]
@@ -11,7 +15,150 @@ pub struct PubSubMessage {
     pub payload: String,
 }
 
-/// Handler for incoming messages.
+/// Typed message with metadata, as published on a channel in JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    pub id: Uuid,
+    /// What happened, e.g. `announcement.published`.
+    pub event: String,
+    pub timestamp: DateTime<Utc>,
+    /// Instance that published the message.
+    pub producer: String,
+    pub data: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(event: impl Into<String>, producer: impl Into<String>, data: T) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event: event.into(),
+            timestamp: Utc::now(),
+            producer: producer.into(),
+            data,
+        }
+    }
+}
+
+/// A request sent with [`PubSub::request`], as published on the request
+/// channel in JSON. Responders answer with [`PubSub::reply`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub correlation_id: Uuid,
+    /// Temporary channel the requester listens on for the reply.
+    pub reply_to: String,
+    pub payload: String,
+}
+
+impl RpcRequest {
+    /// A request on `channel` with a fresh correlation id and reply channel.
+    pub fn new(channel: &str, payload: impl Into<String>) -> Self {
+        let correlation_id = Uuid::new_v4();
+        Self {
+            correlation_id,
+            reply_to: format!("{}.reply.{}", channel, correlation_id.simple()),
+            payload: payload.into(),
+        }
+    }
+}
+
+/// The answer to an [`RpcRequest`], as published on its `reply_to` channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcReply {
+    pub correlation_id: Uuid,
+    pub payload: String,
+}
+
+/// A live subscription. Dropping or cancelling the handle unsubscribes its
+/// handler only; other handlers on the channel keep receiving.
+#[must_use = "dropping the handle unsubscribes the handler"]
+pub struct SubscriptionHandle {
+    id: Uuid,
+    channel: String,
+    cancel: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl SubscriptionHandle {
+    /// A handle for subscription `id` to `channel`, running `cancel` once
+    /// when cancelled or dropped.
+    pub fn new(
+        id: Uuid,
+        channel: impl Into<String>,
+        cancel: impl FnOnce() + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id,
+            channel: channel.into(),
+            cancel: Some(Box::new(cancel)),
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// Stop this handler.
+    pub fn cancel(mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+
+    /// Keep the handler running after the handle is dropped, until the
+    /// whole channel is unsubscribed.
+    pub fn detach(mut self) {
+        self.cancel = None;
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+impl std::fmt::Debug for SubscriptionHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionHandle")
+            .field("id", &self.id)
+            .field("channel", &self.channel)
+            .field("detached", &self.cancel.is_none())
+            .finish()
+    }
+}
+
+/// Pub/sub counters since the backend was created.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PubSubStats {
+    pub published: u64,
+    /// Messages handed to a subscription's handler.
+    pub delivered: u64,
+    /// Messages a subscriber missed because it fell too far behind, i.e.
+    /// dropped by the channel's overflow policy.
+    pub lagged: u64,
+    /// Messages delivered again after going unacknowledged.
+    pub redelivered: u64,
+    /// Subscribers cut off for falling behind.
+    pub disconnected: u64,
+    /// Active subscriptions.
+    pub subscriptions: usize,
+    /// Time spent in handlers, over all deliveries.
+    pub handler_time: Duration,
+    /// Slowest single handler run.
+    pub max_handler_time: Duration,
+}
+
+impl PubSubStats {
+    /// Average handler run time, `None` before the first delivery.
+    pub fn mean_handler_time(&self) -> Option<Duration> {
+        (self.delivered > 0).then(|| self.handler_time / self.delivered as u32)
+    }
+}
 
 /// Pub/Sub trait - abstraction over pub/sub backends.
 #[async_trait]
@@ -19,13 +166,41 @@ pub trait PubSub: Send + Sync {
     /// Publish a message to a channel.
     async fn publish(&self, channel: &str, message: &str) -> Result<(), PubSubError>;
 
-    /// Subscribe to a channel with a handler.
-    async fn subscribe<F>(&self, channel: &str, handler: F) -> Result<(), PubSubError>
+    /// Subscribe a handler to a channel. A channel can have any number of
+    /// handlers; the subscription lasts as long as the returned handle.
+    async fn subscribe<F>(
+        &self,
+        channel: &str,
+        handler: F,
+    ) -> Result<SubscriptionHandle, PubSubError>
     where
         F: Fn(PubSubMessage) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static;
 
-    /// Unsubscribe from a channel.
+    /// Unsubscribe every handler from a channel.
     async fn unsubscribe(&self, channel: &str) -> Result<(), PubSubError>;
+
+    /// Publish an [`RpcRequest`] carrying `payload` on `channel` and wait up
+    /// to `timeout` for the reply on its temporary reply channel.
+    async fn request(
+        &self,
+        channel: &str,
+        payload: &str,
+        timeout: Duration,
+    ) -> Result<String, PubSubError>;
+
+    /// Counters since the backend was created.
+    fn stats(&self) -> PubSubStats;
+
+    /// Answer `request` with `payload`.
+    async fn reply(&self, request: &RpcRequest, payload: &str) -> Result<(), PubSubError> {
+        let reply = RpcReply {
+            correlation_id: request.correlation_id,
+            payload: payload.to_string(),
+        };
+        let message = serde_json::to_string(&reply)
+            .map_err(|e| PubSubError::InvalidMessage(e.to_string()))?;
+        self.publish(&request.reply_to, &message).await
+    }
 }
 
 /// Pub/Sub errors.
@@ -39,4 +214,13 @@ pub enum PubSubError {
 
     #[error("Connection error: {0}")]
     Connection(String),
+
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+
+    #[error("No reply within {0:?}")]
+    Timeout(Duration),
+
+    #[error("Not supported: {0}")]
+    Unsupported(String),
 }
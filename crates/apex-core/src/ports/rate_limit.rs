@@ -9,13 +9,76 @@ pub trait RateLimiter: Send + Sync {
     /// Check if a request is allowed and update the counter.
     /// Returns Ok(true) if allowed, Ok(false) if rate limited.
     async fn check(&self, key: &str) -> Result<RateLimitResult, RateLimitError>;
+
+    /// Check against the budget of the caller's tier. Limiters without
+    /// per-tier budgets treat every tier alike.
+    async fn check_tier(
+        &self,
+        key: &str,
+        tier: RateLimitTier,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        let _ = tier;
+        self.check(key).await
+    }
+}
+
+/// Class of caller a budget applies to, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RateLimitTier {
+    /// No valid credentials.
+    Anonymous,
+    /// Signed in, without a paid subscription.
+    User,
+    /// Signed in with a paid plan whose subscription grants access.
+    Premium,
+    /// Holds the admin role.
+    Admin,
+}
+
+impl RateLimitTier {
+    pub const ALL: [RateLimitTier; 4] = [
+        RateLimitTier::Anonymous,
+        RateLimitTier::User,
+        RateLimitTier::Premium,
+        RateLimitTier::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitTier::Anonymous => "anonymous",
+            RateLimitTier::User => "user",
+            RateLimitTier::Premium => "premium",
+            RateLimitTier::Admin => "admin",
+        }
+    }
+}
+
+impl std::fmt::Display for RateLimitTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RateLimitTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RateLimitTier::ALL
+            .into_iter()
+            .find(|tier| tier.as_str() == s)
+            .ok_or_else(|| format!("Unknown rate limit tier: {}", s))
+    }
 }
 
 /// Result of a rate limit check.
 #[derive(Debug, Clone)]
 pub struct RateLimitResult {
     pub allowed: bool,
+    /// Requests allowed per window.
+    pub limit: u32,
     pub remaining: u32,
+    /// Until another request is allowed when denied; until the full budget
+    /// is back when allowed.
     pub reset_after: Duration,
 }
 
@@ -1,7 +1,11 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::domain::{Post, User};
+use crate::domain::{
+    Announcement, Attachment, Comment, CustomDomain, Email, Invitation, Membership, OAuthClient,
+    Organization, Page, PageCursor, PageRequest, Post, SyncCursor, User, WebhookDelivery,
+};
 use crate::error::RepoError;
 
 /// Generic repository trait defining standard CRUD operations.
@@ -13,15 +17,56 @@ pub trait BaseRepository<T, ID>: Send + Sync {
     /// Save an entity (create or update).
     async fn save(&self, entity: T) -> Result<T, RepoError>;
 
+    /// Create an entity, failing with `Constraint` if its ID is taken.
+    ///
+    /// Use this rather than `save` when the ID came from a client, so a
+    /// colliding ID cannot overwrite someone else's entity.
+    async fn insert(&self, entity: T) -> Result<T, RepoError>;
+
     /// Delete an entity by its ID.
     async fn delete(&self, id: ID) -> Result<(), RepoError>;
+
+    /// Save many entities (create or update), for seeders and imports.
+    ///
+    /// Saves them one at a time by default; database repositories write
+    /// them in batches.
+    async fn save_many(&self, entities: Vec<T>) -> Result<(), RepoError>
+    where
+        T: Send + 'async_trait,
+    {
+        for entity in entities {
+            self.save(entity).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete the entities with these IDs, returning how many there were.
+    /// IDs of missing entities are skipped.
+    async fn delete_many(&self, ids: Vec<ID>) -> Result<u64, RepoError>
+    where
+        ID: Send + 'async_trait,
+    {
+        let mut deleted = 0;
+        for id in ids {
+            match self.delete(id).await {
+                Ok(()) => deleted += 1,
+                Err(RepoError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// One page of all entities, with their total count. Sorting by a field
+    /// the entity does not have fails with `Query`.
+    async fn find_page(&self, request: PageRequest) -> Result<Page<T>, RepoError>;
 }
 
 /// User repository with domain-specific methods.
 #[async_trait]
 pub trait UserRepository: BaseRepository<User, Uuid> {
     /// Find a user by their email address.
-    async fn find_by_email(&self, email: &str) -> Result<Option<User>, RepoError>;
+    async fn find_by_email(&self, email: &Email) -> Result<Option<User>, RepoError>;
 }
 
 /// Post repository.
@@ -29,4 +74,165 @@ pub trait UserRepository: BaseRepository<User, Uuid> {
 pub trait PostRepository: BaseRepository<Post, Uuid> {
     // Add specific methods here if needed (e.g., find_by_user_id)
     async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<Post>, RepoError>;
+
+    /// One page of a user's posts, deleted ones excluded.
+    async fn find_page_by_user_id(
+        &self,
+        user_id: Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError>;
+
+    /// Up to `limit` of a user's posts, deleted ones excluded, newest first,
+    /// starting past `after` or at the newest. For listings too large to
+    /// page by offset: no total is counted.
+    async fn find_by_user_id_after(
+        &self,
+        user_id: Uuid,
+        after: Option<PageCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError>;
+
+    /// Find all posts owned by an organization.
+    async fn find_by_organization_id(&self, organization_id: Uuid) -> Result<Vec<Post>, RepoError>;
+
+    /// One page of an organization's posts, deleted ones excluded; only
+    /// `user_id`'s if given.
+    async fn find_page_by_organization_id(
+        &self,
+        organization_id: Uuid,
+        user_id: Option<Uuid>,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError>;
+
+    /// A user's posts changed after `after`, deleted ones included, oldest
+    /// change first.
+    async fn list_changes(
+        &self,
+        user_id: Uuid,
+        after: Option<SyncCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError>;
+
+    /// Store `post` if the stored copy is still at `expected_version`, or, with
+    /// `None`, if there is none yet. Fails with `Conflict` when another write
+    /// got there first.
+    ///
+    /// `post` carries its new version, bumped by the change that produced it.
+    /// Updates go through here rather than `save`, which overwrites whatever
+    /// is stored.
+    async fn save_if_version(
+        &self,
+        post: Post,
+        expected_version: Option<i64>,
+    ) -> Result<(), RepoError>;
+
+    /// Add views to posts' counts, in one write. Unknown posts are skipped.
+    async fn add_views(&self, views: Vec<(Uuid, i64)>) -> Result<(), RepoError>;
+
+    /// Posts published by `now` and not deleted, most recently published
+    /// first.
+    async fn list_published(&self, now: DateTime<Utc>, limit: u64) -> Result<Vec<Post>, RepoError>;
+}
+
+/// Metadata of uploaded files; the bytes are in a [`StorageService`].
+///
+/// [`StorageService`]: crate::ports::StorageService
+#[async_trait]
+pub trait AttachmentRepository: BaseRepository<Attachment, Uuid> {
+    /// A post's attachments, oldest first.
+    async fn list_by_post(&self, post_id: Uuid) -> Result<Vec<Attachment>, RepoError>;
+}
+
+/// Comments on posts.
+#[async_trait]
+pub trait CommentRepository: BaseRepository<Comment, Uuid> {
+    /// One page of a post's comments.
+    async fn find_page_by_post_id(
+        &self,
+        post_id: Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Comment>, RepoError>;
+}
+
+/// Organization repository.
+#[async_trait]
+pub trait OrganizationRepository: BaseRepository<Organization, Uuid> {
+    /// Find an organization by its slug.
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<Organization>, RepoError>;
+
+    /// List the organizations a user is a member of.
+    async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<Organization>, RepoError>;
+}
+
+/// Organization membership repository.
+#[async_trait]
+pub trait MembershipRepository: BaseRepository<Membership, Uuid> {
+    /// Find a user's membership in an organization.
+    async fn find_membership(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<Membership>, RepoError>;
+
+    /// List all members of an organization.
+    async fn list_by_organization(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<Membership>, RepoError>;
+}
+
+/// Organization invitation repository.
+#[async_trait]
+pub trait InvitationRepository: BaseRepository<Invitation, Uuid> {
+    /// Find an invitation by its secret token.
+    async fn find_by_token(&self, token: &str) -> Result<Option<Invitation>, RepoError>;
+}
+
+/// Webhook delivery audit log.
+#[async_trait]
+pub trait WebhookDeliveryRepository: BaseRepository<WebhookDelivery, Uuid> {
+    /// List the most recent deliveries, newest first.
+    async fn list_recent(
+        &self,
+        failed_only: bool,
+        limit: u64,
+    ) -> Result<Vec<WebhookDelivery>, RepoError>;
+
+    /// Highest attempt number recorded in the chain started by `chain_id`:
+    /// that delivery and its replays.
+    async fn latest_attempt(&self, chain_id: Uuid) -> Result<Option<u32>, RepoError>;
+}
+
+/// Announcements managed by admins.
+#[async_trait]
+pub trait AnnouncementRepository: BaseRepository<Announcement, Uuid> {
+    /// Announcements that have not ended as of `now`, including scheduled
+    /// ones, ordered by start time.
+    async fn list_current(&self, now: DateTime<Utc>) -> Result<Vec<Announcement>, RepoError>;
+
+    /// All announcements, newest first.
+    async fn list_recent(&self, limit: u64) -> Result<Vec<Announcement>, RepoError>;
+}
+
+/// OAuth clients registered by users.
+#[async_trait]
+pub trait OAuthClientRepository: BaseRepository<OAuthClient, Uuid> {
+    /// Find a client by its public client id.
+    async fn find_by_client_id(&self, client_id: &str) -> Result<Option<OAuthClient>, RepoError>;
+
+    /// Clients registered by a user, oldest first.
+    async fn list_by_owner(&self, owner_id: Uuid) -> Result<Vec<OAuthClient>, RepoError>;
+}
+
+/// Custom domains of organizations.
+#[async_trait]
+pub trait CustomDomainRepository: BaseRepository<CustomDomain, Uuid> {
+    /// Find a domain by its (normalized) hostname.
+    async fn find_by_hostname(&self, hostname: &str) -> Result<Option<CustomDomain>, RepoError>;
+
+    /// An organization's domains, oldest first.
+    async fn list_by_organization(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<CustomDomain>, RepoError>;
 }
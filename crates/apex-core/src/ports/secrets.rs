@@ -0,0 +1,17 @@
+//! Secrets port - where keys and credentials come from.
+
+use async_trait::async_trait;
+
+/// Looks up secrets by name, e.g. from the environment or a secrets manager.
+#[async_trait]
+pub trait SecretsProvider: Send + Sync {
+    /// The secret called `name`; `None` when it is not set.
+    async fn secret(&self, name: &str) -> Result<Option<String>, SecretsError>;
+}
+
+/// Secret lookup errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SecretsError {
+    #[error("Failed to read secret {name}: {reason}")]
+    Unavailable { name: String, reason: String },
+}
@@ -0,0 +1,29 @@
+//! Settings storage port.
+
+use async_trait::async_trait;
+
+use crate::domain::SettingsScope;
+use crate::error::RepoError;
+
+/// Raw settings storage - one JSON document per scope.
+///
+/// Typed access and validation happen above this layer, so the stored
+/// document may lag behind the current schema.
+#[async_trait]
+pub trait SettingsRepository: Send + Sync {
+    /// Load the stored document for a scope, if any.
+    async fn get(&self, scope: SettingsScope) -> Result<Option<serde_json::Value>, RepoError>;
+
+    /// Replace the stored document for a scope.
+    async fn put(&self, scope: SettingsScope, values: serde_json::Value) -> Result<(), RepoError>;
+}
+
+/// Settings access errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error(transparent)]
+    Invalid(#[from] crate::error::DomainError),
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+}
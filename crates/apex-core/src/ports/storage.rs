@@ -0,0 +1,74 @@
+//! Object storage port.
+
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// A stored object and its media type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    pub bytes: Vec<u8>,
+    pub content_type: String,
+}
+
+/// Bytes arriving in chunks, such as an upload still being received.
+#[async_trait]
+pub trait ByteStream: Send {
+    /// The next chunk, or `None` once every byte was read.
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, StorageError>;
+}
+
+/// Storage trait - abstraction over object stores (local disk, S3-compatible).
+///
+/// Keys are `/`-separated paths, e.g. `reports/signups/2026-01-20.csv`.
+#[async_trait]
+pub trait StorageService: Send + Sync {
+    /// Store an object, replacing any object under the same key.
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), StorageError>;
+
+    /// Store an object read from `bytes` chunk by chunk, returning its size.
+    /// Nothing is stored if reading fails. Backends that can write chunks
+    /// as they arrive override this; the default collects them for
+    /// [`put`](Self::put).
+    async fn put_stream(
+        &self,
+        key: &str,
+        bytes: &mut dyn ByteStream,
+        content_type: &str,
+    ) -> Result<u64, StorageError> {
+        let mut object = Vec::new();
+        while let Some(chunk) = bytes.next_chunk().await? {
+            object.extend_from_slice(&chunk);
+        }
+        let size = object.len() as u64;
+        self.put(key, object, content_type).await?;
+        Ok(size)
+    }
+
+    /// The object under `key`, if any.
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, StorageError>;
+
+    /// Delete an object. Deleting a missing object succeeds.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// A URL anyone holding it can download the object from, until
+    /// `expires_in` has passed.
+    fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String, StorageError>;
+}
+
+/// Storage operation errors.
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("Invalid storage key: {0}")]
+    InvalidKey(String),
+
+    #[error("Storage backend failed: {0}")]
+    Backend(String),
+
+    /// A streamed object went over the size its reader allows.
+    #[error("Object is larger than {0} bytes")]
+    TooLarge(u64),
+
+    /// The bytes being streamed could not be read, e.g. the client went away.
+    #[error("Reading the object failed: {0}")]
+    Source(String),
+}
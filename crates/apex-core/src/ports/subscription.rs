@@ -0,0 +1,34 @@
+//! Subscription storage port.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::Subscription;
+use crate::error::{DomainError, RepoError};
+
+/// Subscriptions, one per account.
+#[async_trait]
+pub trait SubscriptionRepository: Send + Sync {
+    async fn find_by_account(&self, account_id: Uuid) -> Result<Option<Subscription>, RepoError>;
+
+    /// Insert or replace the account's subscription.
+    async fn save(&self, subscription: Subscription) -> Result<Subscription, RepoError>;
+
+    /// Trialing subscriptions whose trial ends at or before `at`.
+    async fn list_trials_ending_before(
+        &self,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<Subscription>, RepoError>;
+}
+
+/// Subscription lifecycle errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SubscriptionError {
+    /// The event does not apply to the subscription's current state.
+    #[error(transparent)]
+    Invalid(#[from] DomainError),
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+}
@@ -0,0 +1,22 @@
+//! Tag storage port.
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::Tag;
+use crate::error::RepoError;
+
+/// Tags and the posts they are attached to.
+#[async_trait]
+pub trait TagRepository: Send + Sync {
+    /// The tags on a post, by name.
+    async fn tags_of(&self, post_id: Uuid) -> Result<Vec<Tag>, RepoError>;
+
+    /// Make `tags` the post's tags, in one write. Tags are matched by name:
+    /// existing ones are reused, the others stored. Returns the post's tags
+    /// as stored, by name.
+    async fn set_tags(&self, post_id: Uuid, tags: Vec<Tag>) -> Result<Vec<Tag>, RepoError>;
+
+    /// Ids of the posts carrying the tag named `name`.
+    async fn post_ids_tagged(&self, name: &str) -> Result<Vec<Uuid>, RepoError>;
+}
@@ -0,0 +1,63 @@
+//! Usage metering storage port.
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::domain::{Plan, QuotaUsage, UsageTotal};
+use crate::error::RepoError;
+
+/// Persistent monthly usage rollups.
+#[async_trait]
+pub trait UsageRepository: Send + Sync {
+    /// Add each increment's quantity to the matching monthly rollup.
+    async fn increment(&self, increments: Vec<UsageTotal>) -> Result<(), RepoError>;
+
+    /// All rollups for an account in a billing period.
+    async fn totals(
+        &self,
+        account_id: Uuid,
+        period_start: NaiveDate,
+    ) -> Result<Vec<UsageTotal>, RepoError>;
+}
+
+/// Running totals of the bytes each account stores.
+#[async_trait]
+pub trait StorageUsageRepository: Send + Sync {
+    /// Bytes the account stores, 0 if it never stored anything.
+    async fn bytes_stored(&self, account_id: Uuid) -> Result<i64, RepoError>;
+
+    /// Add `delta` bytes (negative for deletions) and return the new total.
+    async fn add_bytes(&self, account_id: Uuid, delta: i64) -> Result<i64, RepoError>;
+}
+
+/// Storage quota errors.
+#[derive(Debug, thiserror::Error)]
+pub enum StorageQuotaError {
+    /// Storing `requested` more bytes would go over the plan's quota.
+    #[error(
+        "Storing {requested} more bytes would exceed the {quota} byte quota of the {plan} plan"
+    )]
+    Exceeded {
+        plan: Plan,
+        used: i64,
+        quota: i64,
+        requested: i64,
+        /// Cheapest plan with room for it, if any.
+        upgrade: Option<Plan>,
+    },
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+}
+
+/// API quota errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiQuotaError {
+    /// The account used up a quota; requests are refused until it resets.
+    #[error("The {} quota of {} requests is used up", .0.period, .0.limit)]
+    Exceeded(QuotaUsage),
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+}
@@ -0,0 +1,56 @@
+//! Outbound webhook port - abstraction over HTTP delivery of webhooks.
+
+use async_trait::async_trait;
+
+/// A webhook to be delivered to an external endpoint.
+#[derive(Debug, Clone)]
+pub struct WebhookRequest {
+    /// Target URL.
+    pub url: String,
+    /// JSON body sent with the request.
+    pub payload: serde_json::Value,
+}
+
+impl WebhookRequest {
+    pub fn new(url: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            url: url.into(),
+            payload,
+        }
+    }
+}
+
+/// Response returned by the receiving endpoint.
+#[derive(Debug, Clone)]
+pub struct WebhookResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response body (possibly truncated by the implementation).
+    pub body: String,
+}
+
+impl WebhookResponse {
+    /// Whether the endpoint accepted the delivery (2xx status).
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Webhook sender trait - abstraction over delivery backends (HTTP, recording fake).
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    /// Deliver a webhook and return the endpoint's response.
+    ///
+    /// Non-2xx responses are returned as `Ok`; only transport failures are errors.
+    async fn send(&self, request: &WebhookRequest) -> Result<WebhookResponse, WebhookError>;
+}
+
+/// Webhook delivery errors.
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error("Delivery failed: {0}")]
+    Delivery(String),
+
+    #[error("Delivery timed out")]
+    Timeout,
+}
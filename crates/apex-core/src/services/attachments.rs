@@ -0,0 +1,373 @@
+//! Uploaded files: metadata in a repository, bytes in object storage.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::ServiceError;
+use crate::domain::{Attachment, Post};
+use crate::error::DomainError;
+use crate::ports::{AttachmentRepository, ByteStream, PostRepository, StorageService};
+
+/// Files users upload, on their own or attached to one of their posts.
+/// Whoever can read the post can read its attachments; only the uploader
+/// deletes them.
+pub struct AttachmentService {
+    posts: Arc<dyn PostRepository>,
+    attachments: Arc<dyn AttachmentRepository>,
+    storage: Arc<dyn StorageService>,
+}
+
+impl AttachmentService {
+    pub fn new(
+        posts: Arc<dyn PostRepository>,
+        attachments: Arc<dyn AttachmentRepository>,
+        storage: Arc<dyn StorageService>,
+    ) -> Self {
+        Self {
+            posts,
+            attachments,
+            storage,
+        }
+    }
+
+    /// Store a file for `user_id`, in the organization they act for if
+    /// any, and attached to `post_id`, which must be theirs.
+    pub async fn upload(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        post_id: Option<Uuid>,
+        filename: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<Attachment, ServiceError> {
+        let attachment = Attachment::new(user_id, filename, content_type, bytes.len() as i64)?;
+        let attachment = self.place(attachment, org_id, post_id).await?;
+
+        self.storage
+            .put(&attachment.key, bytes, &attachment.content_type)
+            .await?;
+        self.insert(attachment).await
+    }
+
+    /// Like [`upload`](Self::upload), with the bytes read chunk by chunk
+    /// into storage as they arrive rather than held in memory first.
+    pub async fn upload_stream(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        post_id: Option<Uuid>,
+        filename: &str,
+        content_type: &str,
+        bytes: &mut dyn ByteStream,
+    ) -> Result<Attachment, ServiceError> {
+        let attachment = Attachment::pending(user_id, filename, content_type)?;
+        let attachment = self.place(attachment, org_id, post_id).await?;
+
+        let size = self
+            .storage
+            .put_stream(&attachment.key, bytes, &attachment.content_type)
+            .await?;
+        match attachment.clone().with_size(size as i64) {
+            Ok(attachment) => self.insert(attachment).await,
+            Err(e) => {
+                let _ = self.storage.delete(&attachment.key).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Put a new attachment in `org_id` and on `post_id`, which must be
+    /// its uploader's.
+    async fn place(
+        &self,
+        mut attachment: Attachment,
+        org_id: Option<Uuid>,
+        post_id: Option<Uuid>,
+    ) -> Result<Attachment, ServiceError> {
+        if let Some(org_id) = org_id {
+            attachment = attachment.in_organization(org_id);
+        }
+        if let Some(post_id) = post_id {
+            self.find_post(post_id)
+                .await?
+                .filter(|post| post.user_id == attachment.user_id)
+                .ok_or_else(|| not_found("Post", post_id))?;
+            attachment = attachment.on_post(post_id);
+        }
+        Ok(attachment)
+    }
+
+    /// Record the metadata of bytes already stored.
+    async fn insert(&self, attachment: Attachment) -> Result<Attachment, ServiceError> {
+        match self.attachments.insert(attachment.clone()).await {
+            Ok(attachment) => Ok(attachment),
+            Err(e) => {
+                // Nothing refers to the bytes without their metadata
+                let _ = self.storage.delete(&attachment.key).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// An attachment `user_id` may read: their own, their organization's,
+    /// or one on a post they can read. Others fail with `NotFound`.
+    pub async fn get(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        id: Uuid,
+    ) -> Result<Attachment, ServiceError> {
+        let attachment = self
+            .attachments
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| not_found("Attachment", id))?;
+        let in_org = attachment.organization_id.is_some() && attachment.organization_id == org_id;
+        if attachment.user_id == user_id || in_org {
+            return Ok(attachment);
+        }
+        if let Some(post_id) = attachment.post_id
+            && self.readable_post(user_id, org_id, post_id).await.is_ok()
+        {
+            return Ok(attachment);
+        }
+        Err(not_found("Attachment", id))
+    }
+
+    /// The attachments on a post `user_id` can read, oldest first.
+    pub async fn list_for_post(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        post_id: Uuid,
+    ) -> Result<Vec<Attachment>, ServiceError> {
+        self.readable_post(user_id, org_id, post_id).await?;
+        Ok(self.attachments.list_by_post(post_id).await?)
+    }
+
+    /// A link to download the attachment's bytes, valid for `expires_in`.
+    pub fn download_url(
+        &self,
+        attachment: &Attachment,
+        expires_in: Duration,
+    ) -> Result<String, ServiceError> {
+        Ok(self.storage.signed_url(&attachment.key, expires_in)?)
+    }
+
+    /// Delete one of `user_id`'s attachments and its bytes, returning it.
+    pub async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<Attachment, ServiceError> {
+        let attachment = self
+            .attachments
+            .find_by_id(id)
+            .await?
+            .filter(|attachment| attachment.user_id == user_id)
+            .ok_or_else(|| not_found("Attachment", id))?;
+        // Metadata first: bytes left behind are unreachable, metadata left
+        // behind would point at nothing
+        self.attachments.delete(id).await?;
+        self.storage.delete(&attachment.key).await?;
+        Ok(attachment)
+    }
+
+    async fn readable_post(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        id: Uuid,
+    ) -> Result<Post, ServiceError> {
+        self.find_post(id)
+            .await?
+            .filter(|post| post.readable_by(user_id, org_id, Utc::now()))
+            .ok_or_else(|| not_found("Post", id))
+    }
+
+    async fn find_post(&self, id: Uuid) -> Result<Option<Post>, ServiceError> {
+        Ok(self
+            .posts
+            .find_by_id(id)
+            .await?
+            .filter(|post| !post.is_deleted()))
+    }
+}
+
+fn not_found(entity_type: &'static str, id: Uuid) -> ServiceError {
+    DomainError::NotFound { entity_type, id }.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Page, PageRequest};
+    use crate::error::RepoError;
+    use crate::ports::{BaseRepository, StorageError, StoredObject};
+    use crate::services::posts::tests::MemoryPosts;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryAttachments(Mutex<HashMap<Uuid, Attachment>>);
+
+    #[async_trait]
+    impl BaseRepository<Attachment, Uuid> for MemoryAttachments {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<Attachment>, RepoError> {
+            Ok(self.0.lock().unwrap().get(&id).cloned())
+        }
+        async fn save(&self, attachment: Attachment) -> Result<Attachment, RepoError> {
+            let mut attachments = self.0.lock().unwrap();
+            attachments.insert(attachment.id, attachment.clone());
+            Ok(attachment)
+        }
+        async fn insert(&self, attachment: Attachment) -> Result<Attachment, RepoError> {
+            self.save(attachment).await
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.0.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        async fn find_page(&self, request: PageRequest) -> Result<Page<Attachment>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl AttachmentRepository for MemoryAttachments {
+        async fn list_by_post(&self, post_id: Uuid) -> Result<Vec<Attachment>, RepoError> {
+            let attachments = self.0.lock().unwrap();
+            Ok(attachments
+                .values()
+                .filter(|attachment| attachment.post_id == Some(post_id))
+                .cloned()
+                .collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryStorage(Mutex<HashMap<String, StoredObject>>);
+
+    #[async_trait]
+    impl StorageService for MemoryStorage {
+        async fn put(
+            &self,
+            key: &str,
+            bytes: Vec<u8>,
+            content_type: &str,
+        ) -> Result<(), StorageError> {
+            let object = StoredObject {
+                bytes,
+                content_type: content_type.to_string(),
+            };
+            self.0.lock().unwrap().insert(key.to_string(), object);
+            Ok(())
+        }
+        async fn get(&self, key: &str) -> Result<Option<StoredObject>, StorageError> {
+            Ok(self.0.lock().unwrap().get(key).cloned())
+        }
+        async fn delete(&self, key: &str) -> Result<(), StorageError> {
+            self.0.lock().unwrap().remove(key);
+            Ok(())
+        }
+        fn signed_url(&self, key: &str, _expires_in: Duration) -> Result<String, StorageError> {
+            Ok(format!("https://files.test/{key}"))
+        }
+    }
+
+    fn is_not_found(result: Result<impl std::fmt::Debug, ServiceError>) -> bool {
+        matches!(
+            result,
+            Err(ServiceError::Invalid(DomainError::NotFound { .. }))
+        )
+    }
+
+    #[tokio::test]
+    async fn test_attachments_keep_bytes_in_storage_and_follow_their_post() {
+        let posts = Arc::new(MemoryPosts::default());
+        let storage = Arc::new(MemoryStorage::default());
+        let service = AttachmentService::new(
+            posts.clone(),
+            Arc::new(MemoryAttachments::default()),
+            storage.clone(),
+        );
+        let (author, reader) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut post = Post::new(author, "Title".into(), "".into());
+        posts.save(post.clone()).await.unwrap();
+
+        let upload = |user, post_id| {
+            service.upload(
+                user,
+                None,
+                Some(post_id),
+                "a.txt",
+                "text/plain",
+                b"hi".to_vec(),
+            )
+        };
+        assert!(is_not_found(upload(reader, post.id).await));
+        let attachment = upload(author, post.id).await.unwrap();
+        assert_eq!(attachment.size_bytes, 2);
+        let stored = storage.get(&attachment.key).await.unwrap().unwrap();
+        assert_eq!(stored.bytes, b"hi");
+
+        // Readable with the post: not while it is a draft, once published
+        assert!(is_not_found(service.get(reader, None, attachment.id).await));
+        post.publish(Utc::now());
+        posts.save(post.clone()).await.unwrap();
+        assert_eq!(
+            service.get(reader, None, attachment.id).await.unwrap(),
+            attachment
+        );
+        let listed = service.list_for_post(reader, None, post.id).await.unwrap();
+        assert_eq!(listed.len(), 1);
+
+        // Only the uploader deletes, bytes included
+        assert!(is_not_found(service.delete(reader, attachment.id).await));
+        service.delete(author, attachment.id).await.unwrap();
+        assert!(storage.get(&attachment.key).await.unwrap().is_none());
+        assert!(is_not_found(service.get(author, None, attachment.id).await));
+    }
+
+    struct Chunks(Vec<Vec<u8>>);
+
+    #[async_trait]
+    impl ByteStream for Chunks {
+        async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, StorageError> {
+            Ok((!self.0.is_empty()).then(|| self.0.remove(0)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_upload_is_sized_by_its_bytes_and_must_have_some() {
+        let storage = Arc::new(MemoryStorage::default());
+        let attachments = Arc::new(MemoryAttachments::default());
+        let service = AttachmentService::new(
+            Arc::new(MemoryPosts::default()),
+            attachments.clone(),
+            storage.clone(),
+        );
+        let user = Uuid::new_v4();
+
+        let mut chunks = Chunks(vec![b"he".to_vec(), b"llo".to_vec()]);
+        let attachment = service
+            .upload_stream(user, None, None, "a.txt", "text/plain", &mut chunks)
+            .await
+            .unwrap();
+        assert_eq!(attachment.size_bytes, 5);
+        let stored = storage.get(&attachment.key).await.unwrap().unwrap();
+        assert_eq!(stored.bytes, b"hello");
+
+        // An empty file leaves neither bytes nor metadata behind
+        let result = service
+            .upload_stream(user, None, None, "b.txt", "text/plain", &mut Chunks(vec![]))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ServiceError::Invalid(DomainError::Validation(_)))
+        ));
+        assert_eq!(storage.0.lock().unwrap().len(), 1);
+        assert_eq!(attachments.0.lock().unwrap().len(), 1);
+    }
+}
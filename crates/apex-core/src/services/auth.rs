@@ -0,0 +1,454 @@
+//! Sign-up, login and password changes.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::ServiceError;
+use crate::domain::{DomainEvent, Email, PasswordPolicy, User};
+use crate::error::DomainError;
+use crate::ports::{
+    EventDispatcher, PasswordHistoryRepository, PasswordService, SubscriptionClaim,
+    SubscriptionRepository, TokenService, UserRepository,
+};
+
+/// An access token and how long it lasts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuedToken {
+    pub access_token: String,
+    pub expires_in: u64,
+}
+
+/// Registers users and logs them in, issuing access tokens that carry the
+/// account's subscription. Passwords follow a [`PasswordPolicy`], the
+/// default one unless [`with_policy`](Self::with_policy) sets another.
+pub struct AuthService {
+    users: Arc<dyn UserRepository>,
+    subscriptions: Arc<dyn SubscriptionRepository>,
+    password_history: Arc<dyn PasswordHistoryRepository>,
+    passwords: Arc<dyn PasswordService>,
+    tokens: Arc<dyn TokenService>,
+    events: Arc<dyn EventDispatcher>,
+    policy: PasswordPolicy,
+}
+
+impl AuthService {
+    pub fn new(
+        users: Arc<dyn UserRepository>,
+        subscriptions: Arc<dyn SubscriptionRepository>,
+        password_history: Arc<dyn PasswordHistoryRepository>,
+        passwords: Arc<dyn PasswordService>,
+        tokens: Arc<dyn TokenService>,
+        events: Arc<dyn EventDispatcher>,
+    ) -> Self {
+        Self {
+            users,
+            subscriptions,
+            password_history,
+            passwords,
+            tokens,
+            events,
+            policy: PasswordPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: PasswordPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Create a user, announced with `UserRegistered`. Fails with
+    /// `Validation` for a malformed email or a password the policy rejects
+    /// and `Duplicate` for a registered email, in any letter case.
+    pub async fn register(&self, email: &str, password: &str) -> Result<IssuedToken, ServiceError> {
+        let email = Email::parse(email)?;
+        self.policy.check(password)?;
+
+        if self.users.find_by_email(&email).await?.is_some() {
+            return Err(DomainError::Duplicate("Email already registered".to_string()).into());
+        }
+
+        let password_hash = self.passwords.hash(password)?;
+        let user = self.users.save(User::new(email, password_hash)).await?;
+        self.events
+            .dispatch(DomainEvent::UserRegistered {
+                user_id: user.id,
+                email: user.email.clone(),
+            })
+            .await;
+        self.issue(&user).await
+    }
+
+    /// Log a user in. An unknown email and a wrong password both fail with
+    /// `Unauthorized`, so callers can't tell which emails are registered.
+    pub async fn login(&self, email: &str, password: &str) -> Result<IssuedToken, ServiceError> {
+        let email = Email::parse(email).map_err(|_| DomainError::Unauthorized)?;
+        let user = self
+            .users
+            .find_by_email(&email)
+            .await?
+            .ok_or(DomainError::Unauthorized)?;
+        if !self.passwords.verify(password, &user.password_hash)? {
+            return Err(DomainError::Unauthorized.into());
+        }
+        self.issue(&user).await
+    }
+
+    /// Replace a user's password after checking their current one. Fails
+    /// with `Validation` for a wrong current password and for a new one the
+    /// policy rejects, including one of the user's last `history` passwords.
+    /// The caller is signed in, so a wrong password is no reason to answer
+    /// `Unauthorized`.
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        current: &str,
+        new: &str,
+    ) -> Result<(), ServiceError> {
+        let mut user = self
+            .users
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::Unauthorized)?;
+        if !self.passwords.verify(current, &user.password_hash)? {
+            return Err(
+                DomainError::Validation("Current password is incorrect".to_string()).into(),
+            );
+        }
+        self.policy.check(new)?;
+        if self.policy.history > 0 {
+            let previous = self
+                .password_history
+                .recent(user_id, self.policy.previous())
+                .await?;
+            for hash in std::iter::once(&user.password_hash).chain(&previous) {
+                if self.passwords.verify(new, hash)? {
+                    return Err(DomainError::Validation(format!(
+                        "Password must differ from your last {} passwords",
+                        self.policy.history
+                    ))
+                    .into());
+                }
+            }
+        }
+
+        let replaced = user.replace_password_hash(self.passwords.hash(new)?);
+        self.users.save(user).await?;
+        self.password_history
+            .record(user_id, replaced, self.policy.previous())
+            .await?;
+        Ok(())
+    }
+
+    async fn issue(&self, user: &User) -> Result<IssuedToken, ServiceError> {
+        let subscription = self
+            .subscriptions
+            .find_by_account(user.id)
+            .await?
+            .map(SubscriptionClaim::from);
+        let access_token = self.tokens.generate_token(
+            user.id,
+            user.email.as_str(),
+            user.roles.clone(),
+            subscription,
+        )?;
+        Ok(IssuedToken {
+            access_token,
+            expires_in: self.tokens.expiration_seconds() as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Page, PageRequest, Plan, Subscription};
+    use crate::error::RepoError;
+    use crate::ports::{AuthError, BaseRepository, OrgClaim, TokenClaims};
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct MemoryUsers {
+        users: Mutex<HashMap<Uuid, User>>,
+    }
+
+    #[async_trait]
+    impl BaseRepository<User, Uuid> for MemoryUsers {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, RepoError> {
+            Ok(self.users.lock().unwrap().get(&id).cloned())
+        }
+        async fn save(&self, user: User) -> Result<User, RepoError> {
+            self.users.lock().unwrap().insert(user.id, user.clone());
+            Ok(user)
+        }
+        async fn insert(&self, user: User) -> Result<User, RepoError> {
+            self.save(user).await
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.users.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        async fn find_page(&self, request: PageRequest) -> Result<Page<User>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl UserRepository for MemoryUsers {
+        async fn find_by_email(&self, email: &Email) -> Result<Option<User>, RepoError> {
+            let users = self.users.lock().unwrap();
+            Ok(users.values().find(|user| user.email == *email).cloned())
+        }
+    }
+
+    /// Every account is on a Pro trial.
+    struct ProTrials;
+
+    #[async_trait]
+    impl SubscriptionRepository for ProTrials {
+        async fn find_by_account(
+            &self,
+            account_id: Uuid,
+        ) -> Result<Option<Subscription>, RepoError> {
+            Ok(Some(Subscription::start_trial(account_id, Plan::Pro, 14)))
+        }
+        async fn save(&self, subscription: Subscription) -> Result<Subscription, RepoError> {
+            Ok(subscription)
+        }
+        async fn list_trials_ending_before(
+            &self,
+            _at: DateTime<Utc>,
+        ) -> Result<Vec<Subscription>, RepoError> {
+            Ok(vec![])
+        }
+    }
+
+    struct PlainPasswords;
+
+    impl PasswordService for PlainPasswords {
+        fn hash(&self, password: &str) -> Result<String, AuthError> {
+            Ok(format!("hashed:{password}"))
+        }
+        fn verify(&self, password: &str, hash: &str) -> Result<bool, AuthError> {
+            Ok(hash == format!("hashed:{password}"))
+        }
+    }
+
+    /// Tokens spelling out the user, plan and roles they were issued for.
+    struct PlainTokens;
+
+    impl TokenService for PlainTokens {
+        fn generate_token(
+            &self,
+            user_id: Uuid,
+            _email: &str,
+            roles: Vec<String>,
+            subscription: Option<SubscriptionClaim>,
+        ) -> Result<String, AuthError> {
+            let plan = subscription.map(|claim| claim.plan.to_string());
+            Ok(format!(
+                "{user_id}:{}:{}",
+                plan.unwrap_or_default(),
+                roles.join(",")
+            ))
+        }
+        fn generate_org_token(
+            &self,
+            _user_id: Uuid,
+            _email: &str,
+            _roles: Vec<String>,
+            _org: OrgClaim,
+            _subscription: Option<SubscriptionClaim>,
+        ) -> Result<String, AuthError> {
+            unimplemented!()
+        }
+        fn validate_token(&self, _token: &str) -> Result<TokenClaims, AuthError> {
+            unimplemented!()
+        }
+        fn expiration_seconds(&self) -> i64 {
+            3600
+        }
+    }
+
+    /// Events in the order they were dispatched.
+    #[derive(Default)]
+    struct RecordedEvents(Mutex<Vec<DomainEvent>>);
+
+    #[async_trait]
+    impl EventDispatcher for RecordedEvents {
+        async fn dispatch(&self, event: DomainEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    /// Earlier hashes per user, newest first.
+    #[derive(Default)]
+    struct MemoryHistory(Mutex<HashMap<Uuid, Vec<String>>>);
+
+    #[async_trait]
+    impl PasswordHistoryRepository for MemoryHistory {
+        async fn recent(&self, user_id: Uuid, limit: usize) -> Result<Vec<String>, RepoError> {
+            let history = self.0.lock().unwrap();
+            let hashes = history.get(&user_id).map(Vec::as_slice).unwrap_or_default();
+            Ok(hashes.iter().take(limit).cloned().collect())
+        }
+        async fn record(
+            &self,
+            user_id: Uuid,
+            password_hash: String,
+            keep: usize,
+        ) -> Result<(), RepoError> {
+            let mut history = self.0.lock().unwrap();
+            let hashes = history.entry(user_id).or_default();
+            hashes.insert(0, password_hash);
+            hashes.truncate(keep);
+            Ok(())
+        }
+    }
+
+    fn service(users: Arc<MemoryUsers>, events: Arc<RecordedEvents>) -> AuthService {
+        AuthService::new(
+            users,
+            Arc::new(ProTrials),
+            Arc::new(MemoryHistory::default()),
+            Arc::new(PlainPasswords),
+            Arc::new(PlainTokens),
+            events,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_register_then_login() {
+        let users = Arc::new(MemoryUsers::default());
+        let events = Arc::new(RecordedEvents::default());
+        let auth = service(users.clone(), events.clone());
+
+        let registered = auth
+            .register("dev@example.com", "correct horse")
+            .await
+            .unwrap();
+        let user = users
+            .find_by_email(&Email::parse("dev@example.com").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.password_hash, "hashed:correct horse");
+        assert_eq!(registered.access_token, format!("{}:pro:user", user.id));
+        assert_eq!(registered.expires_in, 3600);
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            vec![DomainEvent::UserRegistered {
+                user_id: user.id,
+                email: user.email.clone(),
+            }]
+        );
+
+        let logged_in = auth.login(" Dev@Example.com", "correct horse").await;
+        assert_eq!(logged_in.unwrap(), registered);
+    }
+
+    #[tokio::test]
+    async fn test_tokens_carry_the_stored_roles() {
+        let users = Arc::new(MemoryUsers::default());
+        let auth = service(users.clone(), Arc::default());
+        auth.register("dev@example.com", "long enough")
+            .await
+            .unwrap();
+
+        let email = Email::parse("dev@example.com").unwrap();
+        let mut user = users.find_by_email(&email).await.unwrap().unwrap();
+        user.grant_role("admin").unwrap();
+        users.save(user.clone()).await.unwrap();
+
+        let token = auth.login("dev@example.com", "long enough").await.unwrap();
+        assert_eq!(token.access_token, format!("{}:pro:user,admin", user.id));
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_bad_input_and_taken_emails() {
+        let auth = service(Arc::new(MemoryUsers::default()), Arc::default());
+        auth.register("dev@example.com", "long enough")
+            .await
+            .unwrap();
+
+        for (email, password) in [("dev.example.com", "long enough"), ("a@b.c", "short")] {
+            assert!(matches!(
+                auth.register(email, password).await,
+                Err(ServiceError::Invalid(DomainError::Validation(_)))
+            ));
+        }
+        assert!(matches!(
+            auth.register("DEV@example.com", "long enough").await,
+            Err(ServiceError::Invalid(DomainError::Duplicate(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_login_fails_alike_for_unknown_email_and_wrong_password() {
+        let auth = service(Arc::new(MemoryUsers::default()), Arc::default());
+        auth.register("dev@example.com", "long enough")
+            .await
+            .unwrap();
+
+        for (email, password) in [
+            ("nobody@example.com", "long enough"),
+            ("not an email", "long enough"),
+            ("dev@example.com", "wrong password"),
+        ] {
+            assert!(matches!(
+                auth.login(email, password).await,
+                Err(ServiceError::Invalid(DomainError::Unauthorized))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_password_changes_skip_the_recent_passwords() {
+        let users = Arc::new(MemoryUsers::default());
+        let auth = service(users.clone(), Arc::default()).with_policy(PasswordPolicy {
+            min_length: 8,
+            history: 3,
+        });
+        auth.register("dev@example.com", "password 1")
+            .await
+            .unwrap();
+        let email = Email::parse("dev@example.com").unwrap();
+        let id = users.find_by_email(&email).await.unwrap().unwrap().id;
+
+        assert!(matches!(
+            auth.change_password(id, "wrong password", "password 2")
+                .await,
+            Err(ServiceError::Invalid(DomainError::Validation(_)))
+        ));
+        assert!(matches!(
+            auth.change_password(id, "password 1", "short").await,
+            Err(ServiceError::Invalid(DomainError::Validation(_)))
+        ));
+        auth.change_password(id, "password 1", "password 2")
+            .await
+            .unwrap();
+        auth.change_password(id, "password 2", "password 3")
+            .await
+            .unwrap();
+        auth.login("dev@example.com", "password 3").await.unwrap();
+
+        // The current password and the two before it are taken
+        for reused in ["password 3", "password 2", "password 1"] {
+            assert!(matches!(
+                auth.change_password(id, "password 3", reused).await,
+                Err(ServiceError::Invalid(DomainError::Validation(_)))
+            ));
+        }
+        auth.change_password(id, "password 3", "password 4")
+            .await
+            .unwrap();
+        // Three changes ago, so free again
+        auth.change_password(id, "password 4", "password 1")
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! Comments on posts.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::ServiceError;
+use crate::domain::{Comment, Page, PageRequest, Post};
+use crate::error::DomainError;
+use crate::ports::{CommentRepository, PostRepository};
+
+/// Comments on the posts a user can read: their own, their organization's
+/// and published ones.
+pub struct CommentService {
+    posts: Arc<dyn PostRepository>,
+    comments: Arc<dyn CommentRepository>,
+}
+
+impl CommentService {
+    pub fn new(posts: Arc<dyn PostRepository>, comments: Arc<dyn CommentRepository>) -> Self {
+        Self { posts, comments }
+    }
+
+    /// One page of a post's comments.
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        post_id: Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Comment>, ServiceError> {
+        self.readable_post(user_id, org_id, post_id).await?;
+        Ok(self.comments.find_page_by_post_id(post_id, request).await?)
+    }
+
+    /// Comment on a post as `user_id`.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        post_id: Uuid,
+        body: &str,
+    ) -> Result<Comment, ServiceError> {
+        self.readable_post(user_id, org_id, post_id).await?;
+        let comment = Comment::new(post_id, user_id, body)?;
+        Ok(self.comments.insert(comment).await?)
+    }
+
+    /// Delete a comment, returning it. Its writer and the post's author may;
+    /// anyone else gets `NotFound`.
+    pub async fn delete(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        post_id: Uuid,
+        id: Uuid,
+    ) -> Result<Comment, ServiceError> {
+        let post = self.readable_post(user_id, org_id, post_id).await?;
+        let comment = self
+            .comments
+            .find_by_id(id)
+            .await?
+            .filter(|comment| comment.post_id == post_id)
+            .filter(|comment| comment.user_id == user_id || post.user_id == user_id)
+            .ok_or(DomainError::NotFound {
+                entity_type: "Comment",
+                id,
+            })?;
+        self.comments.delete(id).await?;
+        Ok(comment)
+    }
+
+    async fn readable_post(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        id: Uuid,
+    ) -> Result<Post, ServiceError> {
+        self.posts
+            .find_by_id(id)
+            .await?
+            .filter(|post| post.readable_by(user_id, org_id, Utc::now()))
+            .ok_or_else(|| {
+                DomainError::NotFound {
+                    entity_type: "Post",
+                    id,
+                }
+                .into()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RepoError;
+    use crate::ports::BaseRepository;
+    use crate::services::posts::tests::MemoryPosts;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryComments(Mutex<HashMap<Uuid, Comment>>);
+
+    #[async_trait]
+    impl BaseRepository<Comment, Uuid> for MemoryComments {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>, RepoError> {
+            Ok(self.0.lock().unwrap().get(&id).cloned())
+        }
+        async fn save(&self, comment: Comment) -> Result<Comment, RepoError> {
+            self.0.lock().unwrap().insert(comment.id, comment.clone());
+            Ok(comment)
+        }
+        async fn insert(&self, comment: Comment) -> Result<Comment, RepoError> {
+            self.save(comment).await
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.0.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        async fn find_page(&self, request: PageRequest) -> Result<Page<Comment>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl CommentRepository for MemoryComments {
+        async fn find_page_by_post_id(
+            &self,
+            post_id: Uuid,
+            request: PageRequest,
+        ) -> Result<Page<Comment>, RepoError> {
+            let items: Vec<Comment> = self
+                .0
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|comment| comment.post_id == post_id)
+                .cloned()
+                .collect();
+            Ok(Page {
+                total: items.len() as u64,
+                items,
+                offset: request.offset,
+                limit: request.limit,
+            })
+        }
+    }
+
+    fn is_not_found(result: Result<impl std::fmt::Debug, ServiceError>) -> bool {
+        matches!(
+            result,
+            Err(ServiceError::Invalid(DomainError::NotFound { .. }))
+        )
+    }
+
+    #[tokio::test]
+    async fn test_comments_follow_who_can_read_the_post() {
+        let posts = Arc::new(MemoryPosts::default());
+        let service = CommentService::new(posts.clone(), Arc::new(MemoryComments::default()));
+        let (author, reader) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut post = Post::new(author, "Title".into(), "".into());
+        posts.save(post.clone()).await.unwrap();
+        let post_id = post.id;
+        let comment = |user| service.create(user, None, post_id, "First");
+
+        // A draft is the author's alone
+        assert!(is_not_found(comment(reader).await));
+        let own = comment(author).await.unwrap();
+
+        post.publish(Utc::now());
+        posts.save(post.clone()).await.unwrap();
+        let theirs = comment(reader).await.unwrap();
+        let page = service
+            .list(reader, None, post.id, PageRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+
+        // Readers delete their own comments, the author any on the post
+        assert!(is_not_found(
+            service.delete(reader, None, post.id, own.id).await
+        ));
+        assert!(is_not_found(
+            service
+                .delete(author, None, Uuid::new_v4(), theirs.id)
+                .await
+        ));
+        assert_eq!(
+            service
+                .delete(author, None, post.id, theirs.id)
+                .await
+                .unwrap(),
+            theirs
+        );
+        assert!(service.delete(author, None, post.id, own.id).await.is_ok());
+    }
+}
@@ -0,0 +1,43 @@
+//! Application services - business flows over the ports.
+//!
+//! Each service runs one use case end to end (validation, repository calls,
+//! token issuance) so it can be tested with in-memory ports, and the server's
+//! handlers only translate between HTTP and these calls.
+
+mod attachments;
+mod auth;
+mod comments;
+mod posts;
+mod tags;
+mod users;
+
+pub use attachments::AttachmentService;
+pub use auth::{AuthService, IssuedToken};
+pub use comments::CommentService;
+pub use posts::PostService;
+pub use tags::TagService;
+pub use users::UserService;
+
+use crate::error::{DomainError, RepoError};
+use crate::ports::{AuthError, StorageError};
+
+/// Application service errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceError {
+    #[error(transparent)]
+    Invalid(#[from] DomainError),
+
+    #[error(transparent)]
+    Repo(#[from] RepoError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    /// The change is refused in the current state, e.g. an admin revoking
+    /// their own admin role.
+    #[error("{0}")]
+    Conflict(String),
+}
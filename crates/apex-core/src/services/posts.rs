@@ -0,0 +1,522 @@
+//! Reading and publishing posts.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::ServiceError;
+use crate::domain::{DomainEvent, Filter, Page, PageCursor, PageRequest, Post};
+use crate::error::{DomainError, RepoError};
+use crate::ports::{EventDispatcher, PostRepository};
+
+/// Posts as their author and organization see them. Every change is
+/// announced as a [`DomainEvent`].
+pub struct PostService {
+    posts: Arc<dyn PostRepository>,
+    events: Arc<dyn EventDispatcher>,
+}
+
+impl PostService {
+    pub fn new(posts: Arc<dyn PostRepository>, events: Arc<dyn EventDispatcher>) -> Self {
+        Self { posts, events }
+    }
+
+    /// Write a post for `user_id`, in the organization they act for if any.
+    /// It starts out unpublished.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        title: String,
+        content: String,
+    ) -> Result<Post, ServiceError> {
+        Post::validate_title(&title)?;
+        let mut post = Post::new(user_id, title, content);
+        post.organization_id = org_id;
+        let post = self.posts.insert(post).await?;
+        self.events
+            .dispatch(DomainEvent::PostCreated {
+                post_id: post.id,
+                user_id,
+            })
+            .await;
+        Ok(post)
+    }
+
+    /// One page of `user_id`'s own posts, newest first, and the cursor of
+    /// the next page if there is one.
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        after: Option<PageCursor>,
+        limit: u64,
+    ) -> Result<(Vec<Post>, Option<PageCursor>), ServiceError> {
+        // One extra row tells whether there is another page
+        let mut posts = self
+            .posts
+            .find_by_user_id_after(user_id, after, limit + 1)
+            .await?;
+        let next = if posts.len() as u64 > limit {
+            posts.truncate(limit as usize);
+            posts
+                .last()
+                .map(|post| PageCursor::new(post.created_at, post.id))
+        } else {
+            None
+        };
+        Ok((posts, next))
+    }
+
+    /// One page of every author's posts that are not deleted, narrowed by
+    /// the request's filter.
+    pub async fn search(&self, request: PageRequest) -> Result<Page<Post>, ServiceError> {
+        let request = request.filtered_by(Filter::is_null("deleted_at"));
+        Ok(self.posts.find_page(request).await?)
+    }
+
+    /// Replace the title and content of one of `user_id`'s posts. With a
+    /// `version`, fails with `Conflict` unless the post is still at it.
+    pub async fn update(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        title: String,
+        content: String,
+        version: Option<i64>,
+    ) -> Result<Post, ServiceError> {
+        Post::validate_title(&title)?;
+        let mut post = self.find_owned(user_id, id).await?;
+        if version.is_some_and(|version| version != post.version) {
+            return Err(RepoError::stale_version(id, version).into());
+        }
+
+        let current = post.version;
+        post.revise(title, content);
+        self.posts
+            .save_if_version(post.clone(), Some(current))
+            .await?;
+        self.events
+            .dispatch(DomainEvent::PostUpdated {
+                post_id: id,
+                user_id,
+                published: post.is_published(Utc::now()),
+            })
+            .await;
+        Ok(post)
+    }
+
+    /// Delete one of `user_id`'s posts, returning it. The row stays behind
+    /// as a tombstone for synced clients.
+    pub async fn delete(&self, user_id: Uuid, id: Uuid) -> Result<Post, ServiceError> {
+        let mut post = self.find_owned(user_id, id).await?;
+        let (current, published) = (post.version, post.is_published(Utc::now()));
+        post.mark_deleted();
+        self.posts
+            .save_if_version(post.clone(), Some(current))
+            .await?;
+        self.events
+            .dispatch(DomainEvent::PostDeleted {
+                post_id: id,
+                user_id,
+                published,
+            })
+            .await;
+        Ok(post)
+    }
+
+    /// A post of `user_id`, or of the organization they act for. Other
+    /// posts fail with `NotFound`, as if they did not exist.
+    pub async fn get(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        id: Uuid,
+    ) -> Result<Post, ServiceError> {
+        let post = self.find(id).await?;
+        let in_org = post.organization_id.is_some() && post.organization_id == org_id;
+        if post.user_id != user_id && !in_org {
+            return Err(not_found(id));
+        }
+        Ok(post)
+    }
+
+    /// Make one of `user_id`'s posts public, returning it and whether it
+    /// changed: publishing a published post does nothing.
+    pub async fn publish(&self, user_id: Uuid, id: Uuid) -> Result<(Post, bool), ServiceError> {
+        self.set_published(user_id, id, true).await
+    }
+
+    /// Take one of `user_id`'s posts out of public view, returning it and
+    /// whether it changed.
+    pub async fn unpublish(&self, user_id: Uuid, id: Uuid) -> Result<(Post, bool), ServiceError> {
+        self.set_published(user_id, id, false).await
+    }
+
+    async fn set_published(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        published: bool,
+    ) -> Result<(Post, bool), ServiceError> {
+        let mut post = self.find_owned(user_id, id).await?;
+        let version = post.version;
+        let changed = if published {
+            post.publish(Utc::now())
+        } else {
+            post.unpublish()
+        };
+        if changed {
+            // A concurrent edit fails this one with `Conflict` instead of
+            // being overwritten
+            self.posts
+                .save_if_version(post.clone(), Some(version))
+                .await?;
+            let event = match post.published_at {
+                Some(published_at) => DomainEvent::PostPublished {
+                    post_id: id,
+                    user_id,
+                    published_at,
+                },
+                None => DomainEvent::PostUnpublished {
+                    post_id: id,
+                    user_id,
+                },
+            };
+            self.events.dispatch(event).await;
+        }
+        Ok((post, changed))
+    }
+
+    /// A post only its author may change. Colleagues who can read it get
+    /// `NotFound` too.
+    async fn find_owned(&self, user_id: Uuid, id: Uuid) -> Result<Post, ServiceError> {
+        let post = self.find(id).await?;
+        if post.user_id != user_id {
+            return Err(not_found(id));
+        }
+        Ok(post)
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Post, ServiceError> {
+        self.posts
+            .find_by_id(id)
+            .await?
+            .filter(|post| !post.is_deleted())
+            .ok_or_else(|| not_found(id))
+    }
+}
+
+fn not_found(id: Uuid) -> ServiceError {
+    DomainError::NotFound {
+        entity_type: "Post",
+        id,
+    }
+    .into()
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::domain::SyncCursor;
+    use crate::ports::BaseRepository;
+    use async_trait::async_trait;
+    use chrono::DateTime;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    pub(in crate::services) struct MemoryPosts {
+        posts: Mutex<HashMap<Uuid, Post>>,
+    }
+
+    #[async_trait]
+    impl BaseRepository<Post, Uuid> for MemoryPosts {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<Post>, RepoError> {
+            Ok(self.posts.lock().unwrap().get(&id).cloned())
+        }
+        async fn save(&self, post: Post) -> Result<Post, RepoError> {
+            self.posts.lock().unwrap().insert(post.id, post.clone());
+            Ok(post)
+        }
+        async fn insert(&self, post: Post) -> Result<Post, RepoError> {
+            self.save(post).await
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.posts.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        async fn find_page(&self, request: PageRequest) -> Result<Page<Post>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl PostRepository for MemoryPosts {
+        async fn find_by_user_id(&self, _user_id: Uuid) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn find_page_by_user_id(
+            &self,
+            _user_id: Uuid,
+            request: PageRequest,
+        ) -> Result<Page<Post>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+        async fn find_by_user_id_after(
+            &self,
+            user_id: Uuid,
+            after: Option<PageCursor>,
+            limit: u64,
+        ) -> Result<Vec<Post>, RepoError> {
+            let mut posts: Vec<Post> = self
+                .posts
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|post| post.user_id == user_id && !post.is_deleted())
+                .filter(|post| after.is_none_or(|c| c.precedes(post.created_at, post.id)))
+                .cloned()
+                .collect();
+            posts.sort_by_key(|post| std::cmp::Reverse((post.created_at, post.id)));
+            posts.truncate(limit as usize);
+            Ok(posts)
+        }
+        async fn find_by_organization_id(&self, _org: Uuid) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn find_page_by_organization_id(
+            &self,
+            _org: Uuid,
+            _user_id: Option<Uuid>,
+            request: PageRequest,
+        ) -> Result<Page<Post>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+        async fn list_changes(
+            &self,
+            _user_id: Uuid,
+            _after: Option<SyncCursor>,
+            _limit: u64,
+        ) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn save_if_version(&self, post: Post, v: Option<i64>) -> Result<(), RepoError> {
+            let mut posts = self.posts.lock().unwrap();
+            if posts.get(&post.id).map(|stored| stored.version) != v {
+                return Err(RepoError::stale_version(post.id, v));
+            }
+            posts.insert(post.id, post);
+            Ok(())
+        }
+        async fn add_views(&self, _views: Vec<(Uuid, i64)>) -> Result<(), RepoError> {
+            Ok(())
+        }
+        async fn list_published(
+            &self,
+            _now: DateTime<Utc>,
+            _limit: u64,
+        ) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+    }
+
+    /// Events in the order they were dispatched.
+    #[derive(Default)]
+    struct RecordedEvents(Mutex<Vec<DomainEvent>>);
+
+    #[async_trait]
+    impl EventDispatcher for RecordedEvents {
+        async fn dispatch(&self, event: DomainEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    fn service(repo: Arc<MemoryPosts>) -> PostService {
+        PostService::new(repo, Arc::new(RecordedEvents::default()))
+    }
+
+    fn is_not_found(result: Result<impl std::fmt::Debug, ServiceError>) -> bool {
+        matches!(
+            result,
+            Err(ServiceError::Invalid(DomainError::NotFound { .. }))
+        )
+    }
+
+    #[tokio::test]
+    async fn test_posts_are_read_by_their_author_and_organization() {
+        let repo = Arc::new(MemoryPosts::default());
+        let service = service(repo.clone());
+        let (author, colleague, org) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let post = Post::new(author, "Title".into(), "".into()).in_organization(org);
+        repo.save(post.clone()).await.unwrap();
+
+        assert_eq!(service.get(author, None, post.id).await.unwrap(), post);
+        assert_eq!(
+            service.get(colleague, Some(org), post.id).await.unwrap(),
+            post
+        );
+        assert!(is_not_found(service.get(colleague, None, post.id).await));
+        assert!(is_not_found(
+            service.get(colleague, Some(Uuid::new_v4()), post.id).await
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_only_the_author_publishes() {
+        let repo = Arc::new(MemoryPosts::default());
+        let service = service(repo.clone());
+        let author = Uuid::new_v4();
+
+        let post = Post::new(author, "Title".into(), "".into());
+        repo.save(post.clone()).await.unwrap();
+
+        assert!(is_not_found(service.publish(Uuid::new_v4(), post.id).await));
+
+        let (published, changed) = service.publish(author, post.id).await.unwrap();
+        assert!(changed && published.published_at.is_some());
+        assert_eq!(repo.find_by_id(post.id).await.unwrap(), Some(published));
+
+        // Already public: nothing to write
+        let (_, changed) = service.publish(author, post.id).await.unwrap();
+        assert!(!changed);
+
+        let (unpublished, changed) = service.unpublish(author, post.id).await.unwrap();
+        assert!(changed && unpublished.published_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_only_the_author_edits_and_deletes() {
+        let repo = Arc::new(MemoryPosts::default());
+        let service = service(repo.clone());
+        let (author, colleague, org) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let post = service
+            .create(author, Some(org), "Draft".into(), "".into())
+            .await
+            .unwrap();
+        assert_eq!(post.organization_id, Some(org));
+        assert!(post.published_at.is_none());
+
+        // Readable by the organization, but only the author changes it
+        assert!(service.get(colleague, Some(org), post.id).await.is_ok());
+        let edit = |user| service.update(user, post.id, "Final".into(), "Body".into(), None);
+        assert!(is_not_found(edit(colleague).await));
+        assert!(is_not_found(service.delete(colleague, post.id).await));
+
+        let edited = edit(author).await.unwrap();
+        assert_eq!((edited.title.as_str(), edited.version), ("Final", 2));
+
+        // An edit based on an old version is refused
+        assert!(matches!(
+            service
+                .update(author, post.id, "Late".into(), "".into(), Some(1))
+                .await,
+            Err(ServiceError::Repo(RepoError::Conflict(_)))
+        ));
+
+        service.delete(author, post.id).await.unwrap();
+        assert!(
+            repo.find_by_id(post.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .is_deleted()
+        );
+        assert!(is_not_found(service.get(author, None, post.id).await));
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_announced() {
+        let events = Arc::new(RecordedEvents::default());
+        let service = PostService::new(Arc::new(MemoryPosts::default()), events.clone());
+        let author = Uuid::new_v4();
+
+        let post = service
+            .create(author, None, "Title".into(), "".into())
+            .await
+            .unwrap();
+        let (published, _) = service.publish(author, post.id).await.unwrap();
+        // Publishing again changes nothing, so announces nothing
+        service.publish(author, post.id).await.unwrap();
+        service
+            .update(author, post.id, "Edited".into(), "".into(), None)
+            .await
+            .unwrap();
+        service.delete(author, post.id).await.unwrap();
+
+        let post_id = post.id;
+        let user_id = author;
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            vec![
+                DomainEvent::PostCreated { post_id, user_id },
+                DomainEvent::PostPublished {
+                    post_id,
+                    user_id,
+                    published_at: published.published_at.unwrap(),
+                },
+                DomainEvent::PostUpdated {
+                    post_id,
+                    user_id,
+                    published: true,
+                },
+                DomainEvent::PostDeleted {
+                    post_id,
+                    user_id,
+                    published: true,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_titles_are_required() {
+        let service = service(Arc::new(MemoryPosts::default()));
+        assert!(matches!(
+            service
+                .create(Uuid::new_v4(), None, " ".into(), "".into())
+                .await,
+            Err(ServiceError::Invalid(DomainError::Validation(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_pages_through_the_authors_posts() {
+        let repo = Arc::new(MemoryPosts::default());
+        let service = service(repo.clone());
+        let author = Uuid::new_v4();
+        for n in 0..3 {
+            let mut post = Post::new(author, format!("Post {n}"), "".into());
+            post.created_at += chrono::Duration::minutes(n);
+            repo.save(post).await.unwrap();
+        }
+        repo.save(Post::new(Uuid::new_v4(), "Other".into(), "".into()))
+            .await
+            .unwrap();
+
+        let (first, next) = service.list(author, None, 2).await.unwrap();
+        let titles: Vec<_> = first.iter().map(|post| post.title.as_str()).collect();
+        assert_eq!(titles, ["Post 2", "Post 1"]);
+
+        let (last, next) = service.list(author, next, 2).await.unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].title, "Post 0");
+        assert!(next.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_deleted_posts_are_not_found() {
+        let repo = Arc::new(MemoryPosts::default());
+        let service = service(repo.clone());
+        let author = Uuid::new_v4();
+
+        let mut post = Post::new(author, "Title".into(), "".into());
+        post.mark_deleted();
+        repo.save(post.clone()).await.unwrap();
+
+        assert!(is_not_found(service.get(author, None, post.id).await));
+        assert!(is_not_found(service.publish(author, post.id).await));
+    }
+}
@@ -0,0 +1,163 @@
+//! Tagging posts.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::ServiceError;
+use crate::domain::{Post, Tag, normalize_tag};
+use crate::error::DomainError;
+use crate::ports::{PostRepository, TagRepository};
+
+/// Tags on posts. Whoever can read a post sees its tags; only its author
+/// changes them.
+pub struct TagService {
+    posts: Arc<dyn PostRepository>,
+    tags: Arc<dyn TagRepository>,
+}
+
+impl TagService {
+    pub fn new(posts: Arc<dyn PostRepository>, tags: Arc<dyn TagRepository>) -> Self {
+        Self { posts, tags }
+    }
+
+    /// The tags on a post, by name.
+    pub async fn tags_of(
+        &self,
+        user_id: Uuid,
+        org_id: Option<Uuid>,
+        post_id: Uuid,
+    ) -> Result<Vec<Tag>, ServiceError> {
+        self.find(post_id)
+            .await?
+            .filter(|post| post.readable_by(user_id, org_id, Utc::now()))
+            .ok_or_else(|| not_found(post_id))?;
+        Ok(self.tags.tags_of(post_id).await?)
+    }
+
+    /// Replace the tags on one of `user_id`'s posts with `names`, returning
+    /// them as stored. Names are normalized; repeats count once.
+    pub async fn set_tags(
+        &self,
+        user_id: Uuid,
+        post_id: Uuid,
+        names: &[String],
+    ) -> Result<Vec<Tag>, ServiceError> {
+        let mut tags: Vec<Tag> = Vec::new();
+        for name in names {
+            let tag = Tag::new(name)?;
+            if !tags.iter().any(|other| other.name == tag.name) {
+                tags.push(tag);
+            }
+        }
+        if tags.len() > Tag::MAX_PER_POST {
+            return Err(DomainError::Validation(format!(
+                "A post has at most {} tags",
+                Tag::MAX_PER_POST
+            ))
+            .into());
+        }
+
+        self.find(post_id)
+            .await?
+            .filter(|post| post.user_id == user_id)
+            .ok_or_else(|| not_found(post_id))?;
+        Ok(self.tags.set_tags(post_id, tags).await?)
+    }
+
+    /// Ids of the posts tagged `name`, deleted ones included.
+    pub async fn tagged(&self, name: &str) -> Result<Vec<Uuid>, ServiceError> {
+        Ok(self.tags.post_ids_tagged(&normalize_tag(name)?).await?)
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Post>, ServiceError> {
+        Ok(self
+            .posts
+            .find_by_id(id)
+            .await?
+            .filter(|post| !post.is_deleted()))
+    }
+}
+
+fn not_found(id: Uuid) -> ServiceError {
+    DomainError::NotFound {
+        entity_type: "Post",
+        id,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RepoError;
+    use crate::ports::BaseRepository;
+    use crate::services::posts::tests::MemoryPosts;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryTags(Mutex<HashMap<Uuid, Vec<Tag>>>);
+
+    #[async_trait]
+    impl TagRepository for MemoryTags {
+        async fn tags_of(&self, post_id: Uuid) -> Result<Vec<Tag>, RepoError> {
+            Ok(self
+                .0
+                .lock()
+                .unwrap()
+                .get(&post_id)
+                .cloned()
+                .unwrap_or_default())
+        }
+        async fn set_tags(&self, post_id: Uuid, tags: Vec<Tag>) -> Result<Vec<Tag>, RepoError> {
+            self.0.lock().unwrap().insert(post_id, tags.clone());
+            Ok(tags)
+        }
+        async fn post_ids_tagged(&self, name: &str) -> Result<Vec<Uuid>, RepoError> {
+            let posts = self.0.lock().unwrap();
+            Ok(posts
+                .iter()
+                .filter(|(_, tags)| tags.iter().any(|tag| tag.name == name))
+                .map(|(id, _)| *id)
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authors_tag_their_posts() {
+        let posts = Arc::new(MemoryPosts::default());
+        let service = TagService::new(posts.clone(), Arc::new(MemoryTags::default()));
+        let author = Uuid::new_v4();
+        let post = Post::new(author, "Title".into(), "".into());
+        posts.save(post.clone()).await.unwrap();
+
+        let names = ["Rust".to_string(), " rust".to_string(), "web".to_string()];
+        let tags = service.set_tags(author, post.id, &names).await.unwrap();
+        let names: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, ["rust", "web"]);
+        assert_eq!(service.tagged("RUST").await.unwrap(), [post.id]);
+
+        // Someone else's post, a bad name and too many tags are refused
+        assert!(matches!(
+            service.set_tags(Uuid::new_v4(), post.id, &[]).await,
+            Err(ServiceError::Invalid(DomainError::NotFound { .. }))
+        ));
+        assert!(
+            service
+                .set_tags(author, post.id, &["c++".into()])
+                .await
+                .is_err()
+        );
+        let many: Vec<String> = (0..=Tag::MAX_PER_POST).map(|n| format!("t{n}")).collect();
+        assert!(service.set_tags(author, post.id, &many).await.is_err());
+        assert!(
+            service
+                .tags_of(Uuid::new_v4(), None, post.id)
+                .await
+                .is_err()
+        );
+    }
+}
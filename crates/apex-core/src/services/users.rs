@@ -0,0 +1,165 @@
+//! User accounts and their roles.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::ServiceError;
+use crate::domain::User;
+use crate::error::DomainError;
+use crate::ports::UserRepository;
+
+/// Reads user accounts and changes their roles. Roles are copied into tokens
+/// when they are issued, so a change applies from the user's next login.
+pub struct UserService {
+    users: Arc<dyn UserRepository>,
+}
+
+impl UserService {
+    pub fn new(users: Arc<dyn UserRepository>) -> Self {
+        Self { users }
+    }
+
+    /// The user with `id`, failing with `NotFound` if there is none.
+    pub async fn get(&self, id: Uuid) -> Result<User, ServiceError> {
+        self.users.find_by_id(id).await?.ok_or_else(|| {
+            DomainError::NotFound {
+                entity_type: "User",
+                id,
+            }
+            .into()
+        })
+    }
+
+    /// Grant `role` to a user, returning them and whether it changed:
+    /// granting a role they have does nothing.
+    pub async fn grant_role(&self, id: Uuid, role: &str) -> Result<(User, bool), ServiceError> {
+        let mut user = self.get(id).await?;
+        let changed = user.grant_role(role)?;
+        if changed {
+            user = self.users.save(user).await?;
+        }
+        Ok((user, changed))
+    }
+
+    /// Revoke `role` from a user on behalf of `actor_id`, returning them and
+    /// whether it changed. Fails with `Conflict` when an admin revokes their
+    /// own admin role, so the last admin cannot lock everyone out.
+    pub async fn revoke_role(
+        &self,
+        actor_id: Uuid,
+        id: Uuid,
+        role: &str,
+    ) -> Result<(User, bool), ServiceError> {
+        if actor_id == id && role == User::ADMIN_ROLE {
+            return Err(ServiceError::Conflict(
+                "Admins cannot revoke their own admin role".to_string(),
+            ));
+        }
+        let mut user = self.get(id).await?;
+        let changed = user.revoke_role(role);
+        if changed {
+            user = self.users.save(user).await?;
+        }
+        Ok((user, changed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Email, Page, PageRequest};
+    use crate::error::RepoError;
+    use crate::ports::BaseRepository;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Users, counting the saves.
+    #[derive(Default)]
+    struct MemoryUsers {
+        users: Mutex<HashMap<Uuid, User>>,
+        saves: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl BaseRepository<User, Uuid> for MemoryUsers {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, RepoError> {
+            Ok(self.users.lock().unwrap().get(&id).cloned())
+        }
+        async fn save(&self, user: User) -> Result<User, RepoError> {
+            *self.saves.lock().unwrap() += 1;
+            self.users.lock().unwrap().insert(user.id, user.clone());
+            Ok(user)
+        }
+        async fn insert(&self, user: User) -> Result<User, RepoError> {
+            self.save(user).await
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.users.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        async fn find_page(&self, request: PageRequest) -> Result<Page<User>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl UserRepository for MemoryUsers {
+        async fn find_by_email(&self, email: &Email) -> Result<Option<User>, RepoError> {
+            let users = self.users.lock().unwrap();
+            Ok(users.values().find(|user| user.email == *email).cloned())
+        }
+    }
+
+    async fn with_user() -> (Arc<MemoryUsers>, UserService, User) {
+        let users = Arc::new(MemoryUsers::default());
+        let email = Email::parse("dev@example.com").unwrap();
+        let user = users
+            .insert(User::new(email, "hash".to_string()))
+            .await
+            .unwrap();
+        (users.clone(), UserService::new(users), user)
+    }
+
+    #[tokio::test]
+    async fn test_grant_saves_only_changes() {
+        let (users, service, user) = with_user().await;
+
+        let (granted, changed) = service.grant_role(user.id, "editor").await.unwrap();
+        assert!(changed);
+        assert!(granted.has_role("editor"));
+        assert!(!service.grant_role(user.id, "editor").await.unwrap().1);
+        assert_eq!(*users.saves.lock().unwrap(), 2);
+
+        assert!(matches!(
+            service.grant_role(user.id, "Not A Role").await,
+            Err(ServiceError::Invalid(DomainError::Validation(_)))
+        ));
+        assert!(matches!(
+            service.grant_role(Uuid::new_v4(), "editor").await,
+            Err(ServiceError::Invalid(DomainError::NotFound { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_admins_keep_their_own_admin_role() {
+        let (_, service, user) = with_user().await;
+        service.grant_role(user.id, User::ADMIN_ROLE).await.unwrap();
+
+        assert!(matches!(
+            service
+                .revoke_role(user.id, user.id, User::ADMIN_ROLE)
+                .await,
+            Err(ServiceError::Conflict(_))
+        ));
+        let other_admin = Uuid::new_v4();
+        let (revoked, changed) = service
+            .revoke_role(other_admin, user.id, User::ADMIN_ROLE)
+            .await
+            .unwrap();
+        assert!(changed);
+        assert_eq!(revoked.roles, User::default_roles());
+        assert_eq!(service.get(user.id).await.unwrap().roles, revoked.roles);
+    }
+}
@@ -0,0 +1,216 @@
+//! Cache-backed announcement board.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use apex_core::domain::{Announcement, Viewer};
+use apex_core::error::RepoError;
+use apex_core::ports::{AnnouncementRepository, Cache};
+
+/// Pub/sub channel new announcements are published on, for realtime push.
+pub const ANNOUNCEMENTS_CHANNEL: &str = "announcements";
+
+/// Event of an announcement going live.
+pub const ANNOUNCEMENT_PUBLISHED: &str = "announcement.published";
+
+const CACHE_KEY: &str = "announcements:current";
+
+/// Serves announcements to viewers and keeps the cached list fresh on
+/// every admin change.
+///
+/// The cache holds every announcement that has not ended, scheduled ones
+/// included, so announcements start and end on time without invalidation.
+pub struct AnnouncementBoard {
+    repo: Arc<dyn AnnouncementRepository>,
+    cache: Arc<dyn Cache>,
+    ttl: Duration,
+}
+
+impl AnnouncementBoard {
+    pub fn new(repo: Arc<dyn AnnouncementRepository>, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            repo,
+            cache,
+            ttl: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Announcements showing to `viewer` at `now`, in start order.
+    pub async fn live_for(
+        &self,
+        viewer: &Viewer<'_>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Announcement>, RepoError> {
+        Ok(self
+            .current(now)
+            .await?
+            .into_iter()
+            .filter(|a| a.is_live(now) && a.audience.includes(viewer))
+            .collect())
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<Announcement>, RepoError> {
+        self.repo.find_by_id(id).await
+    }
+
+    pub async fn list_recent(&self, limit: u64) -> Result<Vec<Announcement>, RepoError> {
+        self.repo.list_recent(limit).await
+    }
+
+    /// Create an announcement, failing if its ID is taken.
+    pub async fn create(&self, announcement: Announcement) -> Result<Announcement, RepoError> {
+        let created = self.repo.insert(announcement).await?;
+        self.invalidate().await;
+        Ok(created)
+    }
+
+    /// Create or update an announcement.
+    pub async fn save(&self, announcement: Announcement) -> Result<Announcement, RepoError> {
+        let saved = self.repo.save(announcement).await?;
+        self.invalidate().await;
+        Ok(saved)
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        self.repo.delete(id).await?;
+        self.invalidate().await;
+        Ok(())
+    }
+
+    async fn current(&self, now: DateTime<Utc>) -> Result<Vec<Announcement>, RepoError> {
+        if let Some(cached) = self.cache.get(CACHE_KEY).await {
+            match serde_json::from_str(&cached) {
+                Ok(announcements) => return Ok(announcements),
+                Err(e) => tracing::warn!(error = %e, "Discarding unreadable cached announcements"),
+            }
+        }
+
+        let announcements = self.repo.list_current(now).await?;
+        if let Ok(json) = serde_json::to_string(&announcements)
+            && let Err(e) = self.cache.set(CACHE_KEY, &json, Some(self.ttl)).await
+        {
+            tracing::warn!(error = %e, "Failed to cache announcements");
+        }
+        Ok(announcements)
+    }
+
+    async fn invalidate(&self) {
+        if let Err(e) = self.cache.delete(CACHE_KEY).await {
+            tracing::warn!(error = %e, "Failed to invalidate cached announcements");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use apex_core::domain::{Audience, Page, PageRequest, Plan};
+    use apex_core::ports::BaseRepository;
+    use async_trait::async_trait;
+    use chrono::Duration as ChronoDuration;
+    use std::collections::HashMap;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryAnnouncements(Mutex<HashMap<Uuid, Announcement>>);
+
+    #[async_trait]
+    impl BaseRepository<Announcement, Uuid> for MemoryAnnouncements {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<Announcement>, RepoError> {
+            Ok(self.0.lock().await.get(&id).cloned())
+        }
+
+        async fn save(&self, entity: Announcement) -> Result<Announcement, RepoError> {
+            self.0.lock().await.insert(entity.id, entity.clone());
+            Ok(entity)
+        }
+
+        async fn insert(&self, entity: Announcement) -> Result<Announcement, RepoError> {
+            let mut announcements = self.0.lock().await;
+            if announcements.contains_key(&entity.id) {
+                return Err(RepoError::Constraint("Entity already exists".to_string()));
+            }
+            announcements.insert(entity.id, entity.clone());
+            Ok(entity)
+        }
+
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.0.lock().await.remove(&id);
+            Ok(())
+        }
+
+        async fn find_page(&self, request: PageRequest) -> Result<Page<Announcement>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl AnnouncementRepository for MemoryAnnouncements {
+        async fn list_current(&self, now: DateTime<Utc>) -> Result<Vec<Announcement>, RepoError> {
+            Ok(self
+                .0
+                .lock()
+                .await
+                .values()
+                .filter(|a| a.ends_at.is_none_or(|ends_at| now < ends_at))
+                .cloned()
+                .collect())
+        }
+
+        async fn list_recent(&self, _limit: u64) -> Result<Vec<Announcement>, RepoError> {
+            Ok(self.0.lock().await.values().cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_live_for_filters_audience_and_sees_new_announcements() {
+        let board = AnnouncementBoard::new(
+            Arc::new(MemoryAnnouncements::default()),
+            Arc::new(InMemoryCache::new()),
+        );
+        let now = Utc::now();
+        let roles = vec!["user".to_string()];
+        let viewer = Viewer {
+            roles: &roles,
+            plan: Plan::Free,
+            organization_id: None,
+        };
+
+        assert!(board.live_for(&viewer, now).await.unwrap().is_empty());
+
+        let upgrade = Announcement::new(
+            "Try Pro".to_string(),
+            String::new(),
+            Audience::Plan(Plan::Free),
+            now - ChronoDuration::minutes(1),
+            None,
+        )
+        .unwrap();
+        let admins_only = Announcement::new(
+            "Deploy freeze".to_string(),
+            String::new(),
+            Audience::Role("admin".to_string()),
+            now - ChronoDuration::minutes(1),
+            None,
+        )
+        .unwrap();
+        board.save(upgrade.clone()).await.unwrap();
+        board.save(admins_only).await.unwrap();
+
+        let live = board.live_for(&viewer, now).await.unwrap();
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].id, upgrade.id);
+
+        board.delete(upgrade.id).await.unwrap();
+        assert!(board.live_for(&viewer, now).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+//! API quotas - requests per account per day or month, limited by plan.
+//!
+//! Counters live in the cache under a key per calendar window and expire
+//! when the window ends, so nothing has to reset them. Like storage quotas
+//! they are soft: concurrent requests can take an account slightly over a
+//! limit, and the next request after that is refused.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use apex_core::domain::{ApiQuota, Plan, QuotaPeriod, QuotaUsage};
+use apex_core::error::RepoError;
+use apex_core::ports::{ApiQuotaError, Cache};
+
+use crate::entitlements::EntitlementResolver;
+use crate::env;
+
+const KEY_PREFIX: &str = "api_quota:";
+
+/// Counts each account's requests and enforces its plan's quotas.
+pub struct ApiQuotas {
+    cache: Arc<dyn Cache>,
+    entitlements: Arc<EntitlementResolver>,
+    /// Quotas per plan; plans missing here are unlimited.
+    quotas: HashMap<Plan, Vec<ApiQuota>>,
+}
+
+impl ApiQuotas {
+    pub fn new(
+        cache: Arc<dyn Cache>,
+        entitlements: Arc<EntitlementResolver>,
+        quotas: HashMap<Plan, Vec<ApiQuota>>,
+    ) -> Self {
+        Self {
+            cache,
+            entitlements,
+            quotas,
+        }
+    }
+
+    /// Whether any plan has a quota; if not there is nothing to count.
+    pub fn is_empty(&self) -> bool {
+        self.quotas.values().all(Vec::is_empty)
+    }
+
+    /// The account's usage of each of its plan's quotas, daily first.
+    pub async fn usage(&self, account_id: Uuid) -> Result<Vec<QuotaUsage>, RepoError> {
+        let now = Utc::now();
+        let mut usage = Vec::new();
+        for quota in self.quotas_for(account_id).await? {
+            let used = self
+                .cache
+                .get(&counter_key(account_id, quota.period, now))
+                .await
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(0);
+            usage.push(QuotaUsage {
+                period: quota.period,
+                limit: quota.limit,
+                used,
+                resets_at: quota.period.window_end(now),
+            });
+        }
+        Ok(usage)
+    }
+
+    /// Count a request against the account's quotas, or refuse it with the
+    /// first quota that is used up. Refused requests are not counted.
+    ///
+    /// Cache errors let the request through: quotas are not worth an outage.
+    pub async fn consume(&self, account_id: Uuid) -> Result<Vec<QuotaUsage>, ApiQuotaError> {
+        let mut usage = self.usage(account_id).await?;
+        if let Some(exceeded) = usage.iter().find(|usage| usage.remaining() == 0) {
+            return Err(ApiQuotaError::Exceeded(*exceeded));
+        }
+
+        let now = Utc::now();
+        for quota in &mut usage {
+            let key = counter_key(account_id, quota.period, now);
+            let ttl = (quota.resets_at - now).to_std().unwrap_or_default();
+            match self.cache.incr_expiring(&key, 1, ttl).await {
+                Ok(used) => quota.used = used.max(0) as u64,
+                Err(e) => {
+                    tracing::error!(
+                        account_id = %account_id,
+                        error = %e,
+                        "Failed to count API quota usage"
+                    );
+                    quota.used += 1;
+                }
+            }
+        }
+        Ok(usage)
+    }
+
+    async fn quotas_for(&self, account_id: Uuid) -> Result<Vec<ApiQuota>, RepoError> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let plan = self.entitlements.plan_for(account_id).await?;
+        let mut quotas = self.quotas.get(&plan).cloned().unwrap_or_default();
+        quotas.sort_by_key(|quota| quota.period);
+        Ok(quotas)
+    }
+}
+
+/// Counter of an account's requests in the window containing `at`. The
+/// window start is part of the key, so a new window starts from zero even
+/// if the previous counter has not expired yet.
+fn counter_key(account_id: Uuid, period: QuotaPeriod, at: DateTime<Utc>) -> String {
+    format!(
+        "{}{}:{}:{}",
+        KEY_PREFIX,
+        period,
+        period.window_start(at).format("%Y-%m-%d"),
+        account_id
+    )
+}
+
+/// Quotas per plan from `API_QUOTAS`, e.g.
+/// `free=1000/day,free=20000/month,pro=1000000/month`. Unset means no
+/// quotas; plans not listed are unlimited.
+pub fn api_quotas_from_env() -> HashMap<Plan, Vec<ApiQuota>> {
+    parse_api_quotas(&env::API_QUOTAS.string())
+}
+
+/// Parse `plan=limit/period` entries; periods are `day` or `month`. A plan
+/// listed twice for a period keeps the last limit. Malformed entries are
+/// skipped with a warning.
+pub fn parse_api_quotas(spec: &str) -> HashMap<Plan, Vec<ApiQuota>> {
+    let mut quotas: HashMap<Plan, Vec<ApiQuota>> = HashMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once('=').and_then(|(plan, quota)| {
+            let (limit, period) = quota.split_once('/')?;
+            let quota = ApiQuota {
+                period: period.trim().parse().ok()?,
+                limit: limit.trim().parse().ok()?,
+            };
+            Some((plan.trim().parse::<Plan>().ok()?, quota))
+        });
+        match parsed {
+            Some((plan, quota)) => {
+                let plan_quotas = quotas.entry(plan).or_default();
+                plan_quotas.retain(|existing| existing.period != quota.period);
+                plan_quotas.push(quota);
+            }
+            None => tracing::warn!(entry = %entry, "Ignoring malformed API quota"),
+        }
+    }
+    quotas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use apex_core::ports::PlanRepository;
+    use async_trait::async_trait;
+
+    struct FreePlans;
+
+    #[async_trait]
+    impl PlanRepository for FreePlans {
+        async fn get_plan(&self, _account_id: Uuid) -> Result<Option<Plan>, RepoError> {
+            Ok(None)
+        }
+        async fn set_plan(&self, _account_id: Uuid, _plan: Plan) -> Result<(), RepoError> {
+            Ok(())
+        }
+    }
+
+    fn api_quotas(spec: &str) -> ApiQuotas {
+        let cache = Arc::new(InMemoryCache::new());
+        ApiQuotas::new(
+            cache.clone(),
+            Arc::new(EntitlementResolver::new(Arc::new(FreePlans), cache)),
+            parse_api_quotas(spec),
+        )
+    }
+
+    #[test]
+    fn test_parse_api_quotas() {
+        let parsed = parse_api_quotas(
+            "free=10/day, free=100/monthly,free=20/d,pro=5000/month,gold=1/day,pro=x/day,pro=1/week",
+        );
+        assert_eq!(parsed.len(), 2);
+        let mut free = parsed[&Plan::Free].clone();
+        free.sort_by_key(|quota| quota.period);
+        assert_eq!(
+            free,
+            [
+                ApiQuota {
+                    period: QuotaPeriod::Day,
+                    limit: 20
+                },
+                ApiQuota {
+                    period: QuotaPeriod::Month,
+                    limit: 100
+                },
+            ]
+        );
+        assert_eq!(parsed[&Plan::Pro].len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_a_quota_are_refused() {
+        let quotas = api_quotas("free=2/day,free=10/month");
+        let account = Uuid::new_v4();
+
+        quotas.consume(account).await.unwrap();
+        let usage = quotas.consume(account).await.unwrap();
+        assert_eq!(usage.iter().map(|u| u.used).collect::<Vec<_>>(), [2, 2]);
+
+        match quotas.consume(account).await {
+            Err(ApiQuotaError::Exceeded(usage)) => {
+                assert_eq!(usage.period, QuotaPeriod::Day);
+                assert_eq!((usage.used, usage.limit), (2, 2));
+                assert!(usage.resets_at > Utc::now());
+            }
+            other => panic!("expected quota error, got {:?}", other),
+        }
+        // Refused requests don't count
+        assert_eq!(quotas.usage(account).await.unwrap()[1].used, 2);
+
+        // Other accounts and unlimited plans are unaffected
+        quotas.consume(Uuid::new_v4()).await.unwrap();
+        let unlimited = api_quotas("pro=1/day");
+        assert!(unlimited.consume(account).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+//! Two-person approval for destructive admin actions.
+
+use std::sync::Arc;
+
+use chrono::Duration;
+use uuid::Uuid;
+
+use apex_core::domain::{AdminAction, ApprovalStatus, PendingOperation};
+use apex_core::error::{DomainError, RepoError};
+use apex_core::ports::{ApprovalError, PendingOperationRepository};
+
+use crate::env;
+
+/// Stages destructive admin actions until a second admin approves them.
+///
+/// Staged actions are stored as data, so whichever instance serves the
+/// approval replays them. Running an action is left to the caller, which
+/// owns the queue or repository it acts on: [`approve`](Self::approve)
+/// hands back the claimed operation and [`finish`](Self::finish) records
+/// what running it did.
+pub struct AdminApprovals {
+    repo: Arc<dyn PendingOperationRepository>,
+    required: bool,
+    ttl: Duration,
+}
+
+impl AdminApprovals {
+    /// Require approval, within a day of staging.
+    pub fn new(repo: Arc<dyn PendingOperationRepository>) -> Self {
+        Self {
+            repo,
+            required: true,
+            ttl: Duration::hours(24),
+        }
+    }
+
+    /// Let single admins run actions directly instead, e.g. on a one-admin
+    /// deployment.
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// How long a staged action waits for approval.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Required unless `ADMIN_APPROVALS_REQUIRED=false`, within
+    /// `ADMIN_APPROVAL_TTL_HOURS` (default 24).
+    pub fn from_env(repo: Arc<dyn PendingOperationRepository>) -> Self {
+        let mut approvals = Self::new(repo).with_required(env::ADMIN_APPROVALS_REQUIRED.flag());
+        if let Some(hours) = env::ADMIN_APPROVAL_TTL_HOURS
+            .parse::<i64>()
+            .filter(|hours| *hours > 0)
+        {
+            approvals = approvals.with_ttl(Duration::hours(hours));
+        }
+        approvals
+    }
+
+    /// Whether destructive actions wait for a second admin.
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    /// Stage `action` on behalf of `requested_by`.
+    pub async fn stage(
+        &self,
+        action: AdminAction,
+        requested_by: Uuid,
+    ) -> Result<PendingOperation, ApprovalError> {
+        let operation = PendingOperation::new(action, requested_by, self.ttl);
+        let operation = self.repo.insert(operation).await?;
+        tracing::warn!(
+            operation_id = %operation.id,
+            action = operation.action.name(),
+            admin_id = %requested_by,
+            "Admin action staged for approval"
+        );
+        Ok(operation)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<PendingOperation, ApprovalError> {
+        self.repo
+            .find_by_id(id)
+            .await?
+            .ok_or(ApprovalError::NotFound(id))
+    }
+
+    /// Operations newest first, only those in `status` when given.
+    pub async fn list(
+        &self,
+        status: Option<ApprovalStatus>,
+        limit: u64,
+    ) -> Result<Vec<PendingOperation>, RepoError> {
+        self.repo.list(status, limit).await
+    }
+
+    /// Approve on behalf of `approver` and claim the operation to run.
+    ///
+    /// Of two admins approving at once, only one gets the operation back.
+    pub async fn approve(
+        &self,
+        id: Uuid,
+        approver: Uuid,
+    ) -> Result<PendingOperation, ApprovalError> {
+        let mut operation = self.get(id).await?;
+        if operation.status != ApprovalStatus::Pending {
+            return Err(ApprovalError::AlreadyDecided(operation.status));
+        }
+        if operation.action == AdminAction::Unknown {
+            return Err(DomainError::Validation(
+                "Operation was staged by a newer version and cannot run here".to_string(),
+            )
+            .into());
+        }
+        operation.approve(approver).map_err(|e| match e {
+            DomainError::Unauthorized => ApprovalError::SelfApproval,
+            e => e.into(),
+        })?;
+        self.claim(&operation).await?;
+
+        tracing::warn!(
+            operation_id = %operation.id,
+            action = operation.action.name(),
+            admin_id = %approver,
+            requested_by = %operation.requested_by,
+            "Admin action approved"
+        );
+        Ok(operation)
+    }
+
+    /// Reject on behalf of `admin`, or withdraw when they staged it.
+    pub async fn reject(&self, id: Uuid, admin: Uuid) -> Result<PendingOperation, ApprovalError> {
+        let mut operation = self.get(id).await?;
+        if operation.status != ApprovalStatus::Pending {
+            return Err(ApprovalError::AlreadyDecided(operation.status));
+        }
+        operation.reject(admin)?;
+        self.claim(&operation).await?;
+
+        tracing::info!(
+            operation_id = %operation.id,
+            action = operation.action.name(),
+            admin_id = %admin,
+            "Admin action rejected"
+        );
+        Ok(operation)
+    }
+
+    /// Record how running an approved operation went.
+    pub async fn finish(
+        &self,
+        mut operation: PendingOperation,
+        result: Result<String, String>,
+    ) -> Result<PendingOperation, RepoError> {
+        if let Err(error) = &result {
+            tracing::error!(operation_id = %operation.id, error = %error, "Approved admin action failed");
+        }
+        operation.finish(result);
+        self.repo.save(operation).await
+    }
+
+    async fn claim(&self, operation: &PendingOperation) -> Result<(), ApprovalError> {
+        if self.repo.decide(operation).await? {
+            return Ok(());
+        }
+        // Lost the race: report what the other admin decided
+        let current = self.get(operation.id).await?;
+        Err(ApprovalError::AlreadyDecided(current.status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apex_core::domain::{Page, PageRequest};
+    use apex_core::ports::BaseRepository;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryOperations(Mutex<HashMap<Uuid, PendingOperation>>);
+
+    #[async_trait]
+    impl BaseRepository<PendingOperation, Uuid> for MemoryOperations {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<PendingOperation>, RepoError> {
+            Ok(self.0.lock().await.get(&id).cloned())
+        }
+        async fn save(&self, entity: PendingOperation) -> Result<PendingOperation, RepoError> {
+            self.0.lock().await.insert(entity.id, entity.clone());
+            Ok(entity)
+        }
+        async fn insert(&self, entity: PendingOperation) -> Result<PendingOperation, RepoError> {
+            self.save(entity).await
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.0.lock().await.remove(&id);
+            Ok(())
+        }
+        async fn find_page(
+            &self,
+            request: PageRequest,
+        ) -> Result<Page<PendingOperation>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl PendingOperationRepository for MemoryOperations {
+        async fn list(
+            &self,
+            status: Option<ApprovalStatus>,
+            limit: u64,
+        ) -> Result<Vec<PendingOperation>, RepoError> {
+            let operations = self.0.lock().await;
+            Ok(operations
+                .values()
+                .filter(|op| status.is_none_or(|status| op.status == status))
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn decide(&self, operation: &PendingOperation) -> Result<bool, RepoError> {
+            let mut operations = self.0.lock().await;
+            match operations.get_mut(&operation.id) {
+                Some(stored) if stored.status == ApprovalStatus::Pending => {
+                    *stored = operation.clone();
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_operations_run_once_a_second_admin_approves() {
+        let approvals = AdminApprovals::new(Arc::new(MemoryOperations::default()));
+        let requester = Uuid::new_v4();
+        let staged = approvals
+            .stage(AdminAction::PurgePendingJobs, requester)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            approvals.approve(staged.id, requester).await,
+            Err(ApprovalError::SelfApproval)
+        ));
+        let approved = approvals.approve(staged.id, Uuid::new_v4()).await.unwrap();
+        assert!(matches!(
+            approvals.approve(staged.id, Uuid::new_v4()).await,
+            Err(ApprovalError::AlreadyDecided(ApprovalStatus::Approved))
+        ));
+
+        approvals
+            .finish(approved, Ok("Purged 2 jobs".to_string()))
+            .await
+            .unwrap();
+        let executed = approvals.get(staged.id).await.unwrap();
+        assert_eq!(executed.status, ApprovalStatus::Executed);
+        assert_eq!(executed.outcome.as_deref(), Some("Purged 2 jobs"));
+    }
+
+    #[tokio::test]
+    async fn test_admins_cannot_approve_their_own_role_grant() {
+        let approvals = AdminApprovals::new(Arc::new(MemoryOperations::default()));
+        let requester = Uuid::new_v4();
+        let action = AdminAction::GrantRole {
+            user_id: requester,
+            role: "admin".to_string(),
+        };
+        let staged = approvals.stage(action, requester).await.unwrap();
+
+        assert!(matches!(
+            approvals.approve(staged.id, requester).await,
+            Err(ApprovalError::SelfApproval)
+        ));
+        assert_eq!(
+            approvals.get(staged.id).await.unwrap().status,
+            ApprovalStatus::Pending
+        );
+        let approved = approvals.approve(staged.id, Uuid::new_v4()).await.unwrap();
+        assert_eq!(approved.action.name(), "grant_role");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_operations_cannot_be_approved() {
+        let approvals = AdminApprovals::new(Arc::new(MemoryOperations::default()));
+        let requester = Uuid::new_v4();
+        let staged = approvals
+            .stage(AdminAction::PurgePendingJobs, requester)
+            .await
+            .unwrap();
+
+        // The requester may withdraw their own operation
+        approvals.reject(staged.id, requester).await.unwrap();
+        assert!(matches!(
+            approvals.approve(staged.id, Uuid::new_v4()).await,
+            Err(ApprovalError::AlreadyDecided(ApprovalStatus::Rejected))
+        ));
+        assert_eq!(
+            approvals
+                .list(Some(ApprovalStatus::Pending), 10)
+                .await
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+}
@@ -5,7 +5,9 @@ use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode}
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use apex_core::ports::{AuthError, TokenClaims, TokenService};
+use apex_core::ports::{AuthError, OrgClaim, SubscriptionClaim, TokenClaims, TokenService};
+
+use crate::env;
 
 /// JWT token service configuration.
 #[derive(Debug, Clone)]
@@ -31,6 +33,14 @@ struct Claims {
     sub: String, // user_id
     email: String,
     roles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    org_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    org_role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    plan: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    subscription_status: Option<String>,
     exp: i64,    // expiration timestamp
     iat: i64,    // issued at
     iss: String, // issuer
@@ -56,16 +66,11 @@ impl JwtTokenService {
     }
 
     pub fn from_env() -> Self {
-        let secret =
-            std::env::var("JWT_SECRET").unwrap_or_else(|_| "change-me-in-production".to_string());
+        let secret = env::JWT_SECRET.string();
 
         // Warn if using default secret in production
-        if secret == "change-me-in-production" {
-            let is_production = std::env::var("RUST_ENV")
-                .map(|v| v == "production" || v == "prod")
-                .unwrap_or(false);
-
-            if is_production {
+        if !env::JWT_SECRET.is_set() {
+            if env::is_production() {
                 tracing::error!(
                     "SECURITY: Using default JWT secret in production! Set JWT_SECRET environment variable."
                 );
@@ -76,22 +81,19 @@ impl JwtTokenService {
 
         let config = JwtConfig {
             secret,
-            expiration_hours: std::env::var("JWT_EXPIRATION_HOURS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(24),
-            issuer: std::env::var("JWT_ISSUER").unwrap_or_else(|_| "apex-api".to_string()),
+            expiration_hours: env::JWT_EXPIRATION_HOURS.get(),
+            issuer: env::JWT_ISSUER.string(),
         };
         Self::new(config)
     }
-}
 
-impl TokenService for JwtTokenService {
-    fn generate_token(
+    fn encode_claims(
         &self,
         user_id: Uuid,
         email: &str,
         roles: Vec<String>,
+        org: Option<OrgClaim>,
+        subscription: Option<SubscriptionClaim>,
     ) -> Result<String, AuthError> {
         let now = Utc::now();
         let exp = now + TimeDelta::hours(self.config.expiration_hours);
@@ -100,6 +102,10 @@ impl TokenService for JwtTokenService {
             sub: user_id.to_string(),
             email: email.to_string(),
             roles,
+            org_id: org.as_ref().map(|o| o.id.to_string()),
+            org_role: org.as_ref().map(|o| o.role.to_string()),
+            plan: subscription.as_ref().map(|s| s.plan.to_string()),
+            subscription_status: subscription.as_ref().map(|s| s.status.to_string()),
             exp: exp.timestamp(),
             iat: now.timestamp(),
             iss: self.config.issuer.clone(),
@@ -108,6 +114,29 @@ impl TokenService for JwtTokenService {
         encode(&Header::default(), &claims, &self.encoding_key)
             .map_err(|e| AuthError::InvalidToken(e.to_string()))
     }
+}
+
+impl TokenService for JwtTokenService {
+    fn generate_token(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        roles: Vec<String>,
+        subscription: Option<SubscriptionClaim>,
+    ) -> Result<String, AuthError> {
+        self.encode_claims(user_id, email, roles, None, subscription)
+    }
+
+    fn generate_org_token(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        roles: Vec<String>,
+        org: OrgClaim,
+        subscription: Option<SubscriptionClaim>,
+    ) -> Result<String, AuthError> {
+        self.encode_claims(user_id, email, roles, Some(org), subscription)
+    }
 
     fn validate_token(&self, token: &str) -> Result<TokenClaims, AuthError> {
         let mut validation = Validation::default();
@@ -120,14 +149,39 @@ impl TokenService for JwtTokenService {
             }
         })?;
 
-        let user_id = Uuid::parse_str(&token_data.claims.sub)
-            .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+        let claims = token_data.claims;
+        let user_id =
+            Uuid::parse_str(&claims.sub).map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+
+        let org = match (claims.org_id, claims.org_role) {
+            (Some(id), Some(role)) => Some(OrgClaim {
+                id: Uuid::parse_str(&id).map_err(|e| AuthError::InvalidToken(e.to_string()))?,
+                role: role.parse().map_err(|e: apex_core::error::DomainError| {
+                    AuthError::InvalidToken(e.to_string())
+                })?,
+            }),
+            _ => None,
+        };
+
+        let subscription = match (claims.plan, claims.subscription_status) {
+            (Some(plan), Some(status)) => Some(SubscriptionClaim {
+                plan: plan.parse().map_err(|e: apex_core::error::DomainError| {
+                    AuthError::InvalidToken(e.to_string())
+                })?,
+                status: status.parse().map_err(|e: apex_core::error::DomainError| {
+                    AuthError::InvalidToken(e.to_string())
+                })?,
+            }),
+            _ => None,
+        };
 
         Ok(TokenClaims {
             user_id,
-            email: token_data.claims.email,
-            roles: token_data.claims.roles,
-            exp: token_data.claims.exp,
+            email: claims.email,
+            roles: claims.roles,
+            org,
+            subscription,
+            exp: claims.exp,
         })
     }
 
@@ -153,7 +207,8 @@ mod tests {
         let service = JwtTokenService::new(test_config());
         let user_id = Uuid::new_v4();
 
-        let result = service.generate_token(user_id, "test@example.com", vec!["user".to_string()]);
+        let result =
+            service.generate_token(user_id, "test@example.com", vec!["user".to_string()], None);
 
         assert!(result.is_ok());
         let token = result.unwrap();
@@ -167,7 +222,7 @@ mod tests {
         let email = "test@example.com";
 
         let token = service
-            .generate_token(user_id, email, vec!["admin".to_string()])
+            .generate_token(user_id, email, vec!["admin".to_string()], None)
             .unwrap();
 
         let claims = service.validate_token(&token).unwrap();
@@ -177,6 +232,55 @@ mod tests {
         assert_eq!(claims.roles, vec!["admin".to_string()]);
     }
 
+    #[test]
+    fn test_org_token_round_trip() {
+        let service = JwtTokenService::new(test_config());
+        let org = OrgClaim {
+            id: Uuid::new_v4(),
+            role: apex_core::domain::OrgRole::Admin,
+        };
+
+        let token = service
+            .generate_org_token(
+                Uuid::new_v4(),
+                "test@example.com",
+                vec![],
+                org.clone(),
+                None,
+            )
+            .unwrap();
+        let claims = service.validate_token(&token).unwrap();
+        assert_eq!(claims.org, Some(org));
+
+        let token = service
+            .generate_token(Uuid::new_v4(), "test@example.com", vec![], None)
+            .unwrap();
+        assert_eq!(service.validate_token(&token).unwrap().org, None);
+    }
+
+    #[test]
+    fn test_subscription_claim_round_trip() {
+        let service = JwtTokenService::new(test_config());
+        let subscription = SubscriptionClaim {
+            plan: apex_core::domain::Plan::Pro,
+            status: apex_core::domain::SubscriptionStatus::PastDue,
+        };
+
+        let token = service
+            .generate_token(
+                Uuid::new_v4(),
+                "test@example.com",
+                vec![],
+                Some(subscription.clone()),
+            )
+            .unwrap();
+
+        assert_eq!(
+            service.validate_token(&token).unwrap().subscription,
+            Some(subscription)
+        );
+    }
+
     #[test]
     fn test_validate_invalid_token() {
         let service = JwtTokenService::new(test_config());
@@ -201,7 +305,7 @@ mod tests {
         });
 
         let token = service1
-            .generate_token(Uuid::new_v4(), "test@test.com", vec![])
+            .generate_token(Uuid::new_v4(), "test@test.com", vec![], None)
             .unwrap();
 
         let result = service2.validate_token(&token);
@@ -4,4 +4,4 @@ mod jwt;
 mod password;
 
 pub use jwt::JwtTokenService;
-pub use password::Argon2PasswordService;
+pub use password::{Argon2PasswordService, password_policy_from_env};
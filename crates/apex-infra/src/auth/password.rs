@@ -5,8 +5,19 @@ use argon2::{
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
 
+use apex_core::domain::PasswordPolicy;
 use apex_core::ports::{AuthError, PasswordService};
 
+use crate::env;
+
+/// The password policy set by `PASSWORD_MIN_LENGTH` and `PASSWORD_HISTORY`.
+pub fn password_policy_from_env() -> PasswordPolicy {
+    PasswordPolicy {
+        min_length: env::PASSWORD_MIN_LENGTH.get(),
+        history: env::PASSWORD_HISTORY.get(),
+    }
+}
+
 /// Argon2-based password service.
 pub struct Argon2PasswordService {
     argon2: Argon2<'static>,
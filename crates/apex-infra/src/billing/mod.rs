@@ -0,0 +1,267 @@
+//! Subscription lifecycle and billing provider integration.
+//!
+//! `SubscriptionService` owns the subscription state machine and keeps the
+//! account's plan in sync with it. With the `billing` feature, Stripe webhook
+//! events are verified and translated into subscription events.
+
+#[cfg(feature = "billing")]
+pub mod stripe;
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use apex_core::domain::{Plan, Subscription, SubscriptionEvent};
+use apex_core::error::{DomainError, RepoError};
+use apex_core::ports::{SubscriptionClaim, SubscriptionError, SubscriptionRepository};
+
+use crate::entitlements::EntitlementResolver;
+
+/// Drives subscriptions through their lifecycle.
+///
+/// Every transition updates the account's plan through the entitlement
+/// resolver, so plan-gated routes follow the subscription state.
+pub struct SubscriptionService {
+    repo: Arc<dyn SubscriptionRepository>,
+    entitlements: Arc<EntitlementResolver>,
+    trial_days: i64,
+}
+
+impl SubscriptionService {
+    pub fn new(
+        repo: Arc<dyn SubscriptionRepository>,
+        entitlements: Arc<EntitlementResolver>,
+    ) -> Self {
+        Self {
+            repo,
+            entitlements,
+            trial_days: Subscription::TRIAL_DAYS,
+        }
+    }
+
+    pub fn with_trial_days(mut self, trial_days: i64) -> Self {
+        self.trial_days = trial_days;
+        self
+    }
+
+    /// The subscriptions, for services that only read them.
+    pub fn repository(&self) -> Arc<dyn SubscriptionRepository> {
+        self.repo.clone()
+    }
+
+    pub async fn current(&self, account_id: Uuid) -> Result<Option<Subscription>, RepoError> {
+        self.repo.find_by_account(account_id).await
+    }
+
+    /// Subscription state to embed in tokens issued for the account.
+    pub async fn claim_for(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Option<SubscriptionClaim>, RepoError> {
+        Ok(self
+            .repo
+            .find_by_account(account_id)
+            .await?
+            .map(SubscriptionClaim::from))
+    }
+
+    /// Start a trial of `plan`. Only accounts without a live subscription
+    /// can start one.
+    pub async fn start_trial(
+        &self,
+        account_id: Uuid,
+        plan: Plan,
+    ) -> Result<Subscription, SubscriptionError> {
+        if let Some(existing) = self.repo.find_by_account(account_id).await?
+            && existing.status.grants_access()
+        {
+            return Err(DomainError::Duplicate(format!(
+                "Account already has a {} subscription",
+                existing.status
+            ))
+            .into());
+        }
+
+        let sub = Subscription::start_trial(account_id, plan, self.trial_days);
+        self.save(sub).await
+    }
+
+    /// Apply an event to the account's subscription.
+    pub async fn apply(
+        &self,
+        account_id: Uuid,
+        event: SubscriptionEvent,
+    ) -> Result<Subscription, SubscriptionError> {
+        let mut sub =
+            self.repo
+                .find_by_account(account_id)
+                .await?
+                .ok_or(DomainError::NotFound {
+                    entity_type: "Subscription",
+                    id: account_id,
+                })?;
+
+        sub.apply(event)?;
+        self.save(sub).await
+    }
+
+    /// Apply events reported by the billing provider, creating the
+    /// subscription if the provider is the first to know about it.
+    pub async fn apply_external(
+        &self,
+        account_id: Uuid,
+        external_id: &str,
+        plan: Option<Plan>,
+        events: Vec<SubscriptionEvent>,
+    ) -> Result<Subscription, SubscriptionError> {
+        let mut sub = match self.repo.find_by_account(account_id).await? {
+            Some(sub) => sub,
+            None => Subscription::external(
+                account_id,
+                plan.unwrap_or_default(),
+                external_id.to_string(),
+            ),
+        };
+        sub.external_id = Some(external_id.to_string());
+
+        for event in events {
+            sub.apply(event)?;
+        }
+        self.save(sub).await
+    }
+
+    /// Move trials that ended at or before `now` to past due. Returns how
+    /// many were expired.
+    pub async fn expire_trials(&self, now: DateTime<Utc>) -> Result<usize, SubscriptionError> {
+        let mut expired = 0;
+        for mut sub in self.repo.list_trials_ending_before(now).await? {
+            if !sub.trial_expired(now) {
+                continue;
+            }
+            let account_id = sub.account_id;
+            sub.apply(SubscriptionEvent::TrialExpired)?;
+            match self.save(sub).await {
+                Ok(_) => expired += 1,
+                Err(e) => {
+                    tracing::warn!(error = %e, account_id = %account_id, "Failed to expire trial")
+                }
+            }
+        }
+        Ok(expired)
+    }
+
+    async fn save(&self, sub: Subscription) -> Result<Subscription, SubscriptionError> {
+        let sub = self.repo.save(sub).await?;
+        self.entitlements
+            .set_plan(sub.account_id, sub.effective_plan())
+            .await?;
+        Ok(sub)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use apex_core::domain::SubscriptionStatus;
+    use apex_core::ports::PlanRepository;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemorySubscriptions(Mutex<HashMap<Uuid, Subscription>>);
+
+    #[async_trait]
+    impl SubscriptionRepository for MemorySubscriptions {
+        async fn find_by_account(
+            &self,
+            account_id: Uuid,
+        ) -> Result<Option<Subscription>, RepoError> {
+            Ok(self.0.lock().await.get(&account_id).cloned())
+        }
+
+        async fn save(&self, subscription: Subscription) -> Result<Subscription, RepoError> {
+            self.0
+                .lock()
+                .await
+                .insert(subscription.account_id, subscription.clone());
+            Ok(subscription)
+        }
+
+        async fn list_trials_ending_before(
+            &self,
+            at: DateTime<Utc>,
+        ) -> Result<Vec<Subscription>, RepoError> {
+            Ok(self
+                .0
+                .lock()
+                .await
+                .values()
+                .filter(|sub| sub.trial_expired(at))
+                .cloned()
+                .collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryPlans(Mutex<HashMap<Uuid, Plan>>);
+
+    #[async_trait]
+    impl PlanRepository for MemoryPlans {
+        async fn get_plan(&self, account_id: Uuid) -> Result<Option<Plan>, RepoError> {
+            Ok(self.0.lock().await.get(&account_id).copied())
+        }
+
+        async fn set_plan(&self, account_id: Uuid, plan: Plan) -> Result<(), RepoError> {
+            self.0.lock().await.insert(account_id, plan);
+            Ok(())
+        }
+    }
+
+    fn service(trial_days: i64) -> (SubscriptionService, Arc<EntitlementResolver>) {
+        let entitlements = Arc::new(EntitlementResolver::new(
+            Arc::new(MemoryPlans::default()),
+            Arc::new(InMemoryCache::new()),
+        ));
+        let service = SubscriptionService::new(
+            Arc::new(MemorySubscriptions::default()),
+            entitlements.clone(),
+        )
+        .with_trial_days(trial_days);
+        (service, entitlements)
+    }
+
+    #[tokio::test]
+    async fn test_trial_grants_plan_until_canceled() {
+        let (service, entitlements) = service(14);
+        let account = Uuid::new_v4();
+
+        service.start_trial(account, Plan::Pro).await.unwrap();
+        assert_eq!(entitlements.plan_for(account).await.unwrap(), Plan::Pro);
+        assert!(matches!(
+            service.start_trial(account, Plan::Pro).await,
+            Err(SubscriptionError::Invalid(DomainError::Duplicate(_)))
+        ));
+
+        service
+            .apply(account, SubscriptionEvent::Canceled)
+            .await
+            .unwrap();
+        assert_eq!(entitlements.plan_for(account).await.unwrap(), Plan::Free);
+    }
+
+    #[tokio::test]
+    async fn test_expire_trials() {
+        let (service, _) = service(0);
+        let account = Uuid::new_v4();
+        service.start_trial(account, Plan::Pro).await.unwrap();
+
+        assert_eq!(service.expire_trials(Utc::now()).await.unwrap(), 1);
+        assert_eq!(service.expire_trials(Utc::now()).await.unwrap(), 0);
+
+        let claim = service.claim_for(account).await.unwrap().unwrap();
+        assert_eq!(claim.status, SubscriptionStatus::PastDue);
+    }
+}
@@ -0,0 +1,257 @@
+//! Stripe webhook verification and subscription event mapping.
+//!
+//! Subscriptions are linked to accounts through Stripe metadata: checkout
+//! sessions must set `account_id` (and `plan`) on the subscription.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use apex_core::domain::{Plan, SubscriptionEvent};
+
+use crate::env;
+
+/// Stripe webhook errors.
+#[derive(Debug, thiserror::Error)]
+pub enum StripeError {
+    #[error("Invalid Stripe signature")]
+    InvalidSignature,
+
+    #[error("Stripe signature timestamp is outside the tolerance")]
+    Expired,
+
+    #[error("Malformed Stripe event: {0}")]
+    Malformed(String),
+}
+
+/// Verifies the `Stripe-Signature` header of webhook requests.
+pub struct StripeWebhookVerifier {
+    secret: String,
+    tolerance_secs: i64,
+}
+
+impl StripeWebhookVerifier {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            tolerance_secs: 300,
+        }
+    }
+
+    /// Verifier for `STRIPE_WEBHOOK_SECRET`, if it is set.
+    pub fn from_env() -> Option<Self> {
+        env::STRIPE_WEBHOOK_SECRET.raw().map(Self::new)
+    }
+
+    pub fn with_tolerance(mut self, tolerance_secs: i64) -> Self {
+        self.tolerance_secs = tolerance_secs;
+        self
+    }
+
+    /// Check the header's `v1` signatures against the payload, rejecting
+    /// timestamps further than the tolerance from `now` to limit replays.
+    pub fn verify(
+        &self,
+        payload: &[u8],
+        header: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), StripeError> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+                Some(("v1", sig)) => signatures.extend(hex::decode(sig).ok()),
+                _ => {}
+            }
+        }
+
+        let timestamp = timestamp.ok_or(StripeError::InvalidSignature)?;
+        if (now.timestamp() - timestamp).abs() > self.tolerance_secs {
+            return Err(StripeError::Expired);
+        }
+
+        let valid = signatures.iter().any(|sig| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(timestamp.to_string().as_bytes());
+            mac.update(b".");
+            mac.update(payload);
+            mac.verify_slice(sig).is_ok()
+        });
+
+        if valid {
+            Ok(())
+        } else {
+            Err(StripeError::InvalidSignature)
+        }
+    }
+}
+
+/// Subscription change reported by Stripe, as domain events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StripeSubscriptionUpdate {
+    pub account_id: Uuid,
+    /// Stripe subscription id.
+    pub external_id: String,
+    pub plan: Option<Plan>,
+    pub events: Vec<SubscriptionEvent>,
+}
+
+#[derive(Deserialize)]
+struct StripeEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    data: StripeEventData,
+}
+
+#[derive(Deserialize)]
+struct StripeEventData {
+    object: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct StripeSubscription {
+    id: String,
+    status: String,
+    current_period_end: Option<i64>,
+    #[serde(default)]
+    metadata: std::collections::HashMap<String, String>,
+}
+
+/// Translate a webhook payload into a subscription update.
+///
+/// Returns `None` for event types that do not concern subscriptions.
+pub fn parse_event(payload: &[u8]) -> Result<Option<StripeSubscriptionUpdate>, StripeError> {
+    let event: StripeEvent =
+        serde_json::from_slice(payload).map_err(|e| StripeError::Malformed(e.to_string()))?;
+
+    let deleted = match event.kind.as_str() {
+        "customer.subscription.created" | "customer.subscription.updated" => false,
+        "customer.subscription.deleted" => true,
+        _ => return Ok(None),
+    };
+
+    let subscription: StripeSubscription = serde_json::from_value(event.data.object)
+        .map_err(|e| StripeError::Malformed(e.to_string()))?;
+
+    let account_id = subscription
+        .metadata
+        .get("account_id")
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| StripeError::Malformed("Missing metadata.account_id".to_string()))?;
+    let plan = match subscription.metadata.get("plan") {
+        Some(plan) => Some(
+            plan.parse::<Plan>()
+                .map_err(|e| StripeError::Malformed(e.to_string()))?,
+        ),
+        None => None,
+    };
+
+    let mut events: Vec<SubscriptionEvent> = plan
+        .map(SubscriptionEvent::PlanChanged)
+        .into_iter()
+        .collect();
+    let status = if deleted {
+        "canceled"
+    } else {
+        subscription.status.as_str()
+    };
+    match status {
+        "active" => {
+            let period_end = subscription
+                .current_period_end
+                .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                .ok_or_else(|| StripeError::Malformed("Missing current_period_end".to_string()))?;
+            events.push(SubscriptionEvent::PaymentSucceeded { period_end });
+        }
+        "past_due" | "unpaid" => events.push(SubscriptionEvent::PaymentFailed),
+        "canceled" | "incomplete_expired" => events.push(SubscriptionEvent::Canceled),
+        // Trialing and incomplete subscriptions only carry plan changes
+        _ => {}
+    }
+
+    Ok(Some(StripeSubscriptionUpdate {
+        account_id,
+        external_id: subscription.id,
+        plan,
+        events,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, timestamp: i64, payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(payload);
+        format!(
+            "t={},v1={}",
+            timestamp,
+            hex::encode(mac.finalize().into_bytes())
+        )
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let verifier = StripeWebhookVerifier::new("whsec_test");
+        let now = Utc::now();
+        let payload = br#"{"type":"ping"}"#;
+
+        let header = sign("whsec_test", now.timestamp(), payload);
+        assert!(verifier.verify(payload, &header, now).is_ok());
+
+        let forged = sign("other", now.timestamp(), payload);
+        assert!(matches!(
+            verifier.verify(payload, &forged, now),
+            Err(StripeError::InvalidSignature)
+        ));
+
+        let stale = sign("whsec_test", now.timestamp() - 600, payload);
+        assert!(matches!(
+            verifier.verify(payload, &stale, now),
+            Err(StripeError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_parse_subscription_events() {
+        let account = Uuid::new_v4();
+        let payload = serde_json::json!({
+            "type": "customer.subscription.updated",
+            "data": {"object": {
+                "id": "sub_123",
+                "status": "active",
+                "current_period_end": 1_800_000_000,
+                "metadata": {"account_id": account.to_string(), "plan": "pro"}
+            }}
+        });
+
+        let update = parse_event(payload.to_string().as_bytes())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(update.account_id, account);
+        assert_eq!(update.external_id, "sub_123");
+        assert_eq!(
+            update.events,
+            vec![
+                SubscriptionEvent::PlanChanged(Plan::Pro),
+                SubscriptionEvent::PaymentSucceeded {
+                    period_end: DateTime::from_timestamp(1_800_000_000, 0).unwrap()
+                },
+            ]
+        );
+
+        let ignored = serde_json::json!({"type": "invoice.paid", "data": {"object": {}}});
+        assert!(
+            parse_event(ignored.to_string().as_bytes())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -34,6 +34,37 @@ impl InMemoryCache {
             .map(|exp| Instant::now() > exp)
             .unwrap_or(false)
     }
+
+    /// Increment, starting a missing or expired counter with `ttl`.
+    async fn incr_with(
+        &self,
+        key: &str,
+        delta: i64,
+        ttl: Option<Duration>,
+    ) -> Result<i64, CacheError> {
+        let mut store = self.store.write().await;
+        let expires_at = ttl.map(|ttl| Instant::now() + ttl);
+        let entry = store
+            .entry(key.to_string())
+            .and_modify(|entry| {
+                if Self::is_expired(entry) {
+                    entry.value = "0".to_string();
+                    entry.expires_at = expires_at;
+                }
+            })
+            .or_insert_with(|| CacheEntry {
+                value: "0".to_string(),
+                expires_at,
+            });
+
+        let value = entry
+            .value
+            .parse::<i64>()
+            .map_err(|_| CacheError::Operation(format!("{} is not an integer", key)))?
+            + delta;
+        entry.value = value.to_string();
+        Ok(value)
+    }
 }
 
 impl Default for InMemoryCache {
@@ -86,6 +117,31 @@ impl Cache for InMemoryCache {
     async fn exists(&self, key: &str) -> bool {
         self.get(key).await.is_some()
     }
+
+    async fn incr(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.incr_with(key, delta, None).await
+    }
+
+    async fn incr_expiring(&self, key: &str, delta: i64, ttl: Duration) -> Result<i64, CacheError> {
+        self.incr_with(key, delta, Some(ttl)).await
+    }
+
+    async fn take(&self, key: &str) -> Result<Option<String>, CacheError> {
+        let mut store = self.store.write().await;
+        Ok(store
+            .remove(key)
+            .filter(|entry| !Self::is_expired(entry))
+            .map(|entry| entry.value))
+    }
+
+    async fn keys(&self, prefix: &str) -> Result<Vec<String>, CacheError> {
+        let store = self.store.read().await;
+        Ok(store
+            .iter()
+            .filter(|(key, entry)| key.starts_with(prefix) && !Self::is_expired(entry))
+            .map(|(key, _)| key.clone())
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -99,6 +155,34 @@ mod tests {
         assert_eq!(cache.get("key1").await, Some("value1".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_counters() {
+        let cache = InMemoryCache::new();
+        assert_eq!(cache.incr("views:a", 1).await.unwrap(), 1);
+        assert_eq!(cache.incr("views:a", 2).await.unwrap(), 3);
+        cache.incr("views:b", 1).await.unwrap();
+        cache.set("other", "x", None).await.unwrap();
+        assert!(cache.incr("other", 1).await.is_err());
+
+        let mut keys = cache.keys("views:").await.unwrap();
+        keys.sort();
+        assert_eq!(keys, ["views:a", "views:b"]);
+
+        assert_eq!(cache.take("views:a").await.unwrap().as_deref(), Some("3"));
+        assert_eq!(cache.take("views:a").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_expiring_counters() {
+        let cache = InMemoryCache::new();
+        let ttl = Duration::from_millis(50);
+        assert_eq!(cache.incr_expiring("quota:a", 1, ttl).await.unwrap(), 1);
+        assert_eq!(cache.incr_expiring("quota:a", 1, ttl).await.unwrap(), 2);
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(!cache.exists("quota:a").await);
+        assert_eq!(cache.incr_expiring("quota:a", 1, ttl).await.unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn test_delete() {
         let cache = InMemoryCache::new();
@@ -4,10 +4,21 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use redis::aio::ConnectionManager;
-use redis::{AsyncCommands, Client};
+use redis::{AsyncCommands, Client, Script};
 
 use apex_core::ports::{Cache, CacheError};
 
+use crate::env;
+
+/// INCRBY, then PEXPIRE if the counter has no expiry yet.
+const INCR_EXPIRING: &str = r#"
+    local value = redis.call('INCRBY', KEYS[1], ARGV[1])
+    if redis.call('PTTL', KEYS[1]) == -1 then
+        redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    end
+    return value
+"#;
+
 /// Redis connection configuration.
 #[derive(Debug, Clone)]
 pub struct RedisConfig {
@@ -33,17 +44,9 @@ impl RedisConfig {
     /// Load configuration from environment variables.
     pub fn from_env() -> Self {
         Self {
-            url: std::env::var("REDIS_URL")
-                .unwrap_or_else(|_| "redis://localhost:6379".to_string()),
-            connect_timeout: Duration::from_secs(
-                std::env::var("REDIS_CONNECT_TIMEOUT_SECS")
-                    .ok()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(5),
-            ),
-            fallback_to_memory: std::env::var("REDIS_FALLBACK_TO_MEMORY")
-                .map(|v| v == "true" || v == "1")
-                .unwrap_or(true),
+            url: env::REDIS_URL.string(),
+            connect_timeout: env::REDIS_CONNECT_TIMEOUT_SECS.secs(),
+            fallback_to_memory: env::REDIS_FALLBACK_TO_MEMORY.flag(),
         }
     }
 }
@@ -124,6 +127,46 @@ impl Cache for RedisCache {
         let mut conn = self.conn.clone();
         conn.exists::<_, bool>(key).await.unwrap_or(false)
     }
+
+    async fn incr(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        let mut conn = self.conn.clone();
+        conn.incr(key, delta)
+            .await
+            .map_err(|e| CacheError::Operation(e.to_string()))
+    }
+
+    async fn incr_expiring(&self, key: &str, delta: i64, ttl: Duration) -> Result<i64, CacheError> {
+        // One script, so a counter is never left without its expiry
+        let mut conn = self.conn.clone();
+        Script::new(INCR_EXPIRING)
+            .key(key)
+            .arg(delta)
+            .arg(ttl.as_millis().max(1) as u64)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| CacheError::Operation(e.to_string()))
+    }
+
+    async fn take(&self, key: &str) -> Result<Option<String>, CacheError> {
+        let mut conn = self.conn.clone();
+        conn.get_del(key)
+            .await
+            .map_err(|e| CacheError::Operation(e.to_string()))
+    }
+
+    async fn keys(&self, prefix: &str) -> Result<Vec<String>, CacheError> {
+        // SCAN rather than KEYS, so a large keyspace doesn't block the server
+        let mut conn = self.conn.clone();
+        let mut keys = Vec::new();
+        let mut iter: redis::AsyncIter<String> = conn
+            .scan_match(format!("{}*", prefix))
+            .await
+            .map_err(|e| CacheError::Operation(e.to_string()))?;
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
 }
 
 #[cfg(test)]
@@ -133,8 +176,9 @@ mod tests {
 
     async fn get_test_cache() -> Option<RedisCache> {
         let config = RedisConfig {
-            url: std::env::var("REDIS_URL")
-                .unwrap_or_else(|_| "redis://localhost:6389".to_string()),
+            url: env::REDIS_URL
+                .raw()
+                .unwrap_or_else(|| "redis://localhost:6389".to_string()),
             connect_timeout: Duration::from_secs(1),
             fallback_to_memory: false,
         };
@@ -0,0 +1,177 @@
+//! Policy consent tracking.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use apex_core::domain::{PolicyAcceptance, PolicyDocument, PolicyVersions};
+use apex_core::error::RepoError;
+use apex_core::ports::{Cache, ConsentError, ConsentRepository};
+
+use crate::env;
+
+/// Tracks which policy versions users accepted and which they still have to.
+///
+/// A user's acceptances are cached, since consent is checked on every
+/// authenticated request.
+pub struct ConsentService {
+    repo: Arc<dyn ConsentRepository>,
+    cache: Arc<dyn Cache>,
+    versions: PolicyVersions,
+    ttl: Duration,
+}
+
+impl ConsentService {
+    pub fn new(
+        repo: Arc<dyn ConsentRepository>,
+        cache: Arc<dyn Cache>,
+        versions: PolicyVersions,
+    ) -> Self {
+        Self {
+            repo,
+            cache,
+            versions,
+            ttl: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Policy versions users currently have to accept.
+    pub fn versions(&self) -> &PolicyVersions {
+        &self.versions
+    }
+
+    /// The user's acceptances, newest first.
+    pub async fn history(&self, user_id: Uuid) -> Result<Vec<PolicyAcceptance>, RepoError> {
+        let key = cache_key(user_id);
+        if let Some(cached) = self.cache.get(&key).await {
+            match serde_json::from_str(&cached) {
+                Ok(accepted) => return Ok(accepted),
+                Err(e) => tracing::warn!(error = %e, "Discarding unreadable cached consent"),
+            }
+        }
+
+        let accepted = self.repo.list_for_user(user_id).await?;
+        if let Ok(json) = serde_json::to_string(&accepted)
+            && let Err(e) = self.cache.set(&key, &json, Some(self.ttl)).await
+        {
+            tracing::warn!(error = %e, key = %key, "Failed to cache consent");
+        }
+        Ok(accepted)
+    }
+
+    /// Required policies the user has not accepted the current version of.
+    pub async fn outstanding(&self, user_id: Uuid) -> Result<Vec<PolicyDocument>, RepoError> {
+        if self.versions.iter().next().is_none() {
+            return Ok(vec![]);
+        }
+        Ok(self.versions.outstanding(&self.history(user_id).await?))
+    }
+
+    /// Record the user accepting the current version of a policy.
+    pub async fn accept(
+        &self,
+        acceptance: PolicyAcceptance,
+    ) -> Result<PolicyAcceptance, ConsentError> {
+        self.versions
+            .validate(acceptance.policy, &acceptance.version)?;
+
+        let recorded = self.repo.record(acceptance).await?;
+        if let Err(e) = self.cache.delete(&cache_key(recorded.user_id)).await {
+            tracing::warn!(error = %e, "Failed to invalidate cached consent");
+        }
+
+        tracing::info!(
+            user_id = %recorded.user_id,
+            policy = %recorded.policy,
+            version = %recorded.version,
+            ip_address = recorded.ip_address.as_deref().unwrap_or("-"),
+            "Policy accepted"
+        );
+        Ok(recorded)
+    }
+}
+
+/// Policy versions from `TERMS_VERSION` and `PRIVACY_POLICY_VERSION`.
+/// Unset policies are not required.
+pub fn policy_versions_from_env() -> PolicyVersions {
+    [
+        (PolicyDocument::Terms, env::TERMS_VERSION),
+        (PolicyDocument::Privacy, env::PRIVACY_POLICY_VERSION),
+    ]
+    .into_iter()
+    .fold(PolicyVersions::new(), |versions, (policy, var)| {
+        match var.raw() {
+            Some(version) => versions.require(policy, version),
+            None => versions,
+        }
+    })
+}
+
+fn cache_key(user_id: Uuid) -> String {
+    format!("consent:{}", user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryConsents(Mutex<Vec<PolicyAcceptance>>);
+
+    #[async_trait]
+    impl ConsentRepository for MemoryConsents {
+        async fn record(
+            &self,
+            acceptance: PolicyAcceptance,
+        ) -> Result<PolicyAcceptance, RepoError> {
+            self.0.lock().await.insert(0, acceptance.clone());
+            Ok(acceptance)
+        }
+
+        async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<PolicyAcceptance>, RepoError> {
+            Ok(self
+                .0
+                .lock()
+                .await
+                .iter()
+                .filter(|a| a.user_id == user_id)
+                .cloned()
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accepting_clears_outstanding_policy() {
+        let service = ConsentService::new(
+            Arc::new(MemoryConsents::default()),
+            Arc::new(InMemoryCache::new()),
+            PolicyVersions::new().require(PolicyDocument::Terms, "2026-01"),
+        );
+        let user_id = Uuid::new_v4();
+
+        // Cached before accepting, so accepting has to invalidate it
+        assert_eq!(
+            service.outstanding(user_id).await.unwrap(),
+            vec![PolicyDocument::Terms]
+        );
+
+        let stale = PolicyAcceptance::new(user_id, PolicyDocument::Terms, "2025-01".to_string());
+        assert!(matches!(
+            service.accept(stale).await,
+            Err(ConsentError::Invalid(_))
+        ));
+
+        let current = PolicyAcceptance::new(user_id, PolicyDocument::Terms, "2026-01".to_string());
+        service.accept(current).await.unwrap();
+        assert!(service.outstanding(user_id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+//! The current [`RequestContext`], kept in a tokio task-local.
+//!
+//! The server enters a scope per request, and anything running inside it
+//! (repositories, services, the webhook audit log) can read the context with
+//! [`current`]. Enqueued jobs carry it along and run with it again on the
+//! worker.
+//!
+//! Code running outside a scope (startup, spawned tasks) sees `None`:
+//! `tokio::spawn` does not carry task-locals over, so wrap spawned work in
+//! [`scope`] to keep the context.
+
+use std::cell::RefCell;
+use std::future::Future;
+
+use apex_core::RequestContext;
+use apex_core::ports::Job;
+use uuid::Uuid;
+
+tokio::task_local! {
+    static CURRENT: RefCell<RequestContext>;
+}
+
+/// Run `future` with `context` as the current context.
+pub async fn scope<F: Future>(context: RequestContext, future: F) -> F::Output {
+    CURRENT.scope(RefCell::new(context), future).await
+}
+
+/// Run `f` with `context` as the current context.
+pub fn sync_scope<R>(context: RequestContext, f: impl FnOnce() -> R) -> R {
+    CURRENT.sync_scope(RefCell::new(context), f)
+}
+
+/// A copy of the current context, if inside a scope.
+pub fn current() -> Option<RequestContext> {
+    CURRENT.try_with(|context| context.borrow().clone()).ok()
+}
+
+/// Change the current context, e.g. once the caller is authenticated.
+/// Returns `false` outside a scope.
+pub fn update(f: impl FnOnce(&mut RequestContext)) -> bool {
+    CURRENT
+        .try_with(|context| f(&mut context.borrow_mut()))
+        .is_ok()
+}
+
+/// The user the current request acts as, once authenticated.
+pub fn actor() -> Option<Uuid> {
+    current().and_then(|context| context.user_id)
+}
+
+/// `(created_by, updated_by)` of an entity being saved: a recorded creator
+/// stays, and the current actor creates a new entity and updates any.
+/// Outside a request (startup, jobs enqueued without one) both stay as
+/// they are.
+pub fn audit_stamp(
+    created_by: Option<Uuid>,
+    updated_by: Option<Uuid>,
+) -> (Option<Uuid>, Option<Uuid>) {
+    let actor = actor();
+    (created_by.or(actor), actor.or(updated_by))
+}
+
+/// Attach the current context to a job being enqueued, unless it has one.
+/// The request's deadline stays behind: the job runs on its own time.
+pub(crate) fn attach(job: &mut Job) {
+    if job.context.is_none() {
+        job.context = current().map(|context| RequestContext {
+            deadline: None,
+            ..context
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_context_is_visible_inside_the_scope_only() {
+        assert!(current().is_none());
+
+        let user_id = Uuid::new_v4();
+        let context = RequestContext::new("req-1").with_timeout(std::time::Duration::ZERO);
+        let seen = scope(context, async move {
+            tokio::task::yield_now().await;
+            assert!(update(|context| context.user_id = Some(user_id)));
+
+            let mut job = Job::new("email", serde_json::json!({}));
+            attach(&mut job);
+            (current(), job.context)
+        })
+        .await;
+
+        let (context, job_context) = seen;
+        assert_eq!(context.as_ref().unwrap().request_id, "req-1");
+        assert_eq!(context.unwrap().user_id, Some(user_id));
+        let job_context = job_context.unwrap();
+        assert_eq!(job_context.user_id, Some(user_id));
+        assert_eq!(job_context.deadline, None);
+        assert!(current().is_none());
+        assert!(!update(|_| {}));
+    }
+
+    #[tokio::test]
+    async fn test_audit_stamp_records_the_actor() {
+        let (creator, editor) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(audit_stamp(None, None), (None, None));
+        assert_eq!(
+            audit_stamp(Some(creator), Some(creator)),
+            (Some(creator), Some(creator))
+        );
+
+        let mut context = RequestContext::new("req-1");
+        context.user_id = Some(editor);
+        let stamps = scope(context, async move {
+            (audit_stamp(None, None), audit_stamp(Some(creator), None))
+        })
+        .await;
+        assert_eq!(stamps.0, (Some(editor), Some(editor)));
+        assert_eq!(stamps.1, (Some(creator), Some(editor)));
+    }
+}
@@ -1,5 +1,10 @@
 use std::time::Duration;
 
+#[cfg(feature = "postgres")]
+use std::sync::Arc;
+
+#[cfg(feature = "postgres")]
+use super::metrics::QueryMetrics;
 #[cfg(feature = "postgres")]
 use sea_orm::{ConnectOptions, Database, DbConn, DbErr};
 
@@ -10,6 +15,36 @@ pub struct DatabaseConfig {
     pub main_max_connections: u32,
     pub main_min_connections: u32,
     pub secondary_databases: Vec<SecondaryDbConfig>,
+    /// Connection attempts at startup before giving up.
+    pub connect_attempts: u32,
+    /// Wait after the first failed attempt, doubled after each one.
+    pub connect_backoff: Duration,
+    /// How tenants' data is kept apart.
+    pub tenancy: TenancyMode,
+    /// Queries taking this long or longer are logged at WARN; zero logs none.
+    pub slow_query_threshold: Duration,
+}
+
+/// How tenants' data is kept apart. A tenant is an organization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TenancyMode {
+    /// Not at all: repositories see every row.
+    #[default]
+    None,
+    /// Shared tables. Tenant-owned rows carry the tenant in
+    /// `organization_id`, and tenant-scoped repositories filter on it.
+    Column,
+}
+
+impl TenancyMode {
+    /// Parse `none` or `column`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" | "" => Some(Self::None),
+            "column" => Some(Self::Column),
+            _ => None,
+        }
+    }
 }
 
 /// Configuration for a secondary database.
@@ -49,9 +84,12 @@ pub struct NamedConnection {
 #[cfg(feature = "postgres")]
 pub struct DatabaseConnections {
     /// Primary database - used for most operations (100+ pool).
-    pub main: DbConn,
+    pub main: Arc<DbConn>,
     /// Secondary databases - for specific use cases (<20 pool each).
     pub secondary: Vec<NamedConnection>,
+    /// Timings of the queries run on every pool, tenants' included.
+    pub queries: Arc<QueryMetrics>,
+    tenancy: TenancyMode,
 }
 
 #[cfg(not(feature = "postgres"))]
@@ -63,6 +101,40 @@ pub struct DatabaseConnections {
 impl DatabaseConnections {
     /// Initialize all database connections from configuration.
     pub async fn init(config: &DatabaseConfig) -> Result<Self, DbErr> {
+        Self::connect(config, false).await
+    }
+
+    /// [`Self::init`], retried up to `connect_attempts` times with
+    /// exponential backoff, for a database that is still starting up.
+    pub async fn init_with_retry(config: &DatabaseConfig) -> Result<Self, DbErr> {
+        let mut attempt = 1;
+        loop {
+            match Self::init(config).await {
+                Ok(connections) => return Ok(connections),
+                Err(e) if attempt >= config.connect_attempts => return Err(e),
+                Err(e) => {
+                    let delay = backoff(config.connect_backoff, attempt);
+                    tracing::warn!(
+                        attempt,
+                        retry_in_ms = delay.as_millis() as u64,
+                        error = %e,
+                        "Database connection failed, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Set up the pools without connecting, for a database that is not
+    /// reachable yet. Queries fail until it is, then the pools connect on
+    /// demand. Fails only on a malformed URL.
+    pub async fn init_lazy(config: &DatabaseConfig) -> Result<Self, DbErr> {
+        Self::connect(config, true).await
+    }
+
+    async fn connect(config: &DatabaseConfig, lazy: bool) -> Result<Self, DbErr> {
         tracing::info!("Initializing database connections...");
 
         // Main DB: High connection pool for primary operations
@@ -72,11 +144,17 @@ impl DatabaseConnections {
             .connect_timeout(Duration::from_secs(10))
             .idle_timeout(Duration::from_secs(300))
             .sqlx_logging(true)
+            .connect_lazy(lazy)
             .to_owned();
 
-        let main = Database::connect(main_opts).await?;
+        let state = if lazy { "pool ready" } else { "connected" };
+        let queries = Arc::new(QueryMetrics::new(config.slow_query_threshold));
+
+        let mut main = Database::connect(main_opts).await?;
+        queries.instrument(&mut main, "main");
         tracing::info!(
-            "Main database connected (pool: {})",
+            "Main database {} (pool: {})",
+            state,
             config.main_max_connections
         );
 
@@ -88,12 +166,15 @@ impl DatabaseConnections {
                 .min_connections(2)
                 .connect_timeout(Duration::from_secs(10))
                 .idle_timeout(Duration::from_secs(300))
+                .connect_lazy(lazy)
                 .to_owned();
 
-            let conn = Database::connect(opts).await?;
+            let mut conn = Database::connect(opts).await?;
+            queries.instrument(&mut conn, &db_config.name);
             tracing::info!(
-                "Secondary database '{}' connected (pool: {})",
+                "Secondary database '{}' {} (pool: {})",
                 db_config.name,
+                state,
                 db_config.max_connections
             );
 
@@ -103,7 +184,17 @@ impl DatabaseConnections {
             });
         }
 
-        Ok(Self { main, secondary })
+        Ok(Self {
+            main: Arc::new(main),
+            secondary,
+            queries,
+            tenancy: config.tenancy,
+        })
+    }
+
+    /// How tenants' data is kept apart.
+    pub fn tenancy(&self) -> TenancyMode {
+        self.tenancy
     }
 
     /// Get a secondary database connection by name.
@@ -118,4 +209,48 @@ impl DatabaseConnections {
     pub fn secondary_names(&self) -> Vec<&str> {
         self.secondary.iter().map(|c| c.name.as_str()).collect()
     }
+
+    /// Ping every database, main first, timing each round trip.
+    pub async fn ping(&self) -> Vec<(&str, Result<Duration, DbErr>)> {
+        let connections = std::iter::once(("main", self.main.as_ref()))
+            .chain(self.secondary.iter().map(|c| (c.name.as_str(), &c.conn)));
+        let mut results = Vec::new();
+        for (name, conn) in connections {
+            let started = std::time::Instant::now();
+            results.push((name, conn.ping().await.map(|()| started.elapsed())));
+        }
+        results
+    }
+}
+
+/// Longest wait between two connection attempts.
+#[cfg(feature = "postgres")]
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Wait after failed attempt number `attempt`, counting from 1.
+#[cfg(feature = "postgres")]
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(all(test, feature = "postgres"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenancy_mode_parses() {
+        assert_eq!(TenancyMode::parse("Column"), Some(TenancyMode::Column));
+        assert_eq!(TenancyMode::parse(""), Some(TenancyMode::None));
+        assert_eq!(TenancyMode::parse("row"), None);
+        assert_eq!(TenancyMode::parse("schema"), None);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let base = Duration::from_millis(500);
+        assert_eq!(backoff(base, 1), base);
+        assert_eq!(backoff(base, 3), Duration::from_secs(2));
+        assert_eq!(backoff(base, 40), MAX_BACKOFF);
+    }
 }
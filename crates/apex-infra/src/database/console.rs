@@ -0,0 +1,240 @@
+//! Read-only SQL console over the secondary databases, for support
+//! investigations without direct database access.
+//!
+//! Queries run in a read-only transaction with a statement timeout, wrapped
+//! in a subquery that caps the rows returned, and the transaction is always
+//! rolled back. The statement check in front of that is a first line of
+//! defence only; the database enforces read-only access.
+//!
+//! Only Postgres databases can be queried: the statement timeout and the
+//! `$n` placeholders are Postgres'.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sea_orm::{
+    AccessMode, ConnectionTrait, DbBackend, FromQueryResult, JsonValue, Statement,
+    TransactionTrait, Value,
+};
+
+use super::DatabaseConnections;
+use crate::env;
+
+/// SQL console configuration.
+#[derive(Debug, Clone)]
+pub struct SqlConsoleConfig {
+    /// The console is off unless explicitly enabled.
+    pub enabled: bool,
+    /// Most rows a query returns.
+    pub row_limit: u64,
+    /// Queries running longer are cancelled by the database.
+    pub statement_timeout: Duration,
+}
+
+impl Default for SqlConsoleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            row_limit: 500,
+            statement_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl SqlConsoleConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: env::SQL_CONSOLE_ENABLED.flag(),
+            row_limit: env::SQL_CONSOLE_ROW_LIMIT.get(),
+            statement_timeout: env::SQL_CONSOLE_TIMEOUT_MS.millis(),
+        }
+    }
+}
+
+/// Rows returned by a console query, each in `columns` order.
+#[derive(Debug, Clone)]
+pub struct ConsoleRows {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// More rows matched than were returned.
+    pub truncated: bool,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SqlConsoleError {
+    #[error("Unknown database: {0}")]
+    UnknownDatabase(String),
+
+    #[error("The SQL console only supports Postgres, not {0}")]
+    UnsupportedBackend(String),
+
+    #[error("Only a single SELECT or WITH query is allowed")]
+    NotReadOnly,
+
+    #[error("Query failed: {0}")]
+    Query(String),
+}
+
+/// Runs read-only, parameterized queries against named secondary databases.
+pub struct SqlConsole {
+    connections: Arc<DatabaseConnections>,
+    config: SqlConsoleConfig,
+}
+
+impl SqlConsole {
+    pub fn new(connections: Arc<DatabaseConnections>, config: SqlConsoleConfig) -> Self {
+        Self {
+            connections,
+            config,
+        }
+    }
+
+    /// Names of the databases queries can run against.
+    pub fn databases(&self) -> Vec<&str> {
+        self.connections
+            .secondary_names()
+            .into_iter()
+            .filter(|name| {
+                self.connections
+                    .get(name)
+                    .is_some_and(|conn| conn.get_database_backend() == DbBackend::Postgres)
+            })
+            .collect()
+    }
+
+    /// Run `sql` with `$1`, `$2`, ... bound to `params`, returning at most
+    /// `limit` rows (capped at the configured row limit).
+    pub async fn query(
+        &self,
+        database: &str,
+        sql: &str,
+        params: &[serde_json::Value],
+        limit: Option<u64>,
+    ) -> Result<ConsoleRows, SqlConsoleError> {
+        let conn = self
+            .connections
+            .get(database)
+            .ok_or_else(|| SqlConsoleError::UnknownDatabase(database.to_string()))?;
+        let backend = conn.get_database_backend();
+        if backend != DbBackend::Postgres {
+            return Err(SqlConsoleError::UnsupportedBackend(format!(
+                "{:?}",
+                backend
+            )));
+        }
+        let sql = read_only_statement(sql)?;
+        let limit = limit
+            .unwrap_or(self.config.row_limit)
+            .clamp(1, self.config.row_limit);
+
+        let started = Instant::now();
+        let txn = conn
+            .begin_with_config(None, Some(AccessMode::ReadOnly))
+            .await
+            .map_err(|e| SqlConsoleError::Query(e.to_string()))?;
+        txn.execute_unprepared(&format!(
+            "SET LOCAL statement_timeout = {}",
+            self.config.statement_timeout.as_millis()
+        ))
+        .await
+        .map_err(|e| SqlConsoleError::Query(e.to_string()))?;
+
+        // One extra row tells whether the result was cut off
+        let statement = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!("SELECT * FROM ({}) AS console LIMIT {}", sql, limit + 1),
+            params.iter().map(to_value),
+        );
+        let result = txn.query_all(statement).await;
+        if let Err(e) = txn.rollback().await {
+            tracing::warn!(error = %e, "Failed to roll back SQL console transaction");
+        }
+        let mut results = result.map_err(|e| SqlConsoleError::Query(e.to_string()))?;
+
+        let truncated = results.len() as u64 > limit;
+        results.truncate(limit as usize);
+        let columns = results
+            .first()
+            .map(|row| row.column_names())
+            .unwrap_or_default();
+        let rows = results
+            .iter()
+            .map(|row| {
+                let mut values = match JsonValue::from_query_result(row, "") {
+                    Ok(serde_json::Value::Object(values)) => values,
+                    _ => serde_json::Map::new(),
+                };
+                columns
+                    .iter()
+                    .map(|column| values.remove(column).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        Ok(ConsoleRows {
+            columns,
+            rows,
+            truncated,
+            elapsed: started.elapsed(),
+        })
+    }
+}
+
+/// The statement without its trailing semicolon, if it is a single SELECT or
+/// WITH query without comments.
+fn read_only_statement(sql: &str) -> Result<&str, SqlConsoleError> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    let keyword = sql
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let single = !sql.contains(';') && !sql.contains("--") && !sql.contains("/*");
+    if single && (keyword == "select" || keyword == "with") {
+        Ok(sql)
+    } else {
+        Err(SqlConsoleError::NotReadOnly)
+    }
+}
+
+fn to_value(param: &serde_json::Value) -> Value {
+    match param {
+        serde_json::Value::Null => Value::String(None),
+        serde_json::Value::Bool(b) => Value::Bool(Some(*b)),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::BigInt(Some(i)),
+            None => Value::Double(n.as_f64()),
+        },
+        serde_json::Value::String(s) => Value::String(Some(Box::new(s.clone()))),
+        other => Value::Json(Some(Box::new(other.clone()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_single_read_queries_are_allowed() {
+        assert_eq!(
+            read_only_statement("  SELECT id FROM users WHERE id = $1; ").unwrap(),
+            "SELECT id FROM users WHERE id = $1"
+        );
+        assert!(read_only_statement("with t as (select 1) select * from t").is_ok());
+
+        for sql in [
+            "DELETE FROM users",
+            "select 1; drop table users",
+            "select 1 -- ; drop table users",
+            "/* */ update users set email = ''",
+            "",
+        ] {
+            assert!(
+                matches!(read_only_statement(sql), Err(SqlConsoleError::NotReadOnly)),
+                "{sql}"
+            );
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Column-level encryption of sensitive fields.
+//!
+//! [`EncryptedString`] holds plaintext in memory and is sealed with
+//! AES-256-GCM on its way into the database, so the column, its replicas
+//! and every backup only hold ciphertext. The key is process-wide: a
+//! [`ColumnCipher`] [installed](ColumnCipher::install) at startup, since
+//! SeaORM and serde convert values without any context to carry it in.
+//!
+//! Values written before a key was configured are stored as they are and
+//! read back unchanged; they are sealed the next time the row is saved.
+//! Sealed values are not bound to their column or row: anyone able to
+//! write the table can move a sealed value between them, but not read it.
+
+use std::sync::OnceLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use apex_core::ports::{SecretsError, SecretsProvider};
+
+/// Secret holding the key new values are sealed with: 32 bytes, base64.
+pub const KEY_SECRET: &str = "COLUMN_ENCRYPTION_KEY";
+/// Secret holding retired keys (comma separated), still tried when opening
+/// so rows sealed before a rotation stay readable.
+pub const PREVIOUS_KEYS_SECRET: &str = "COLUMN_ENCRYPTION_PREVIOUS_KEYS";
+
+/// Prefix of a sealed value, telling it apart from plaintext.
+const SEALED_PREFIX: &str = "enc:v1:";
+
+static CIPHER: OnceLock<ColumnCipher> = OnceLock::new();
+
+/// Column encryption errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ColumnEncryptionError {
+    #[error("Invalid column encryption key: {0}")]
+    InvalidKey(String),
+
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+
+    #[error("A column encryption key is already installed")]
+    AlreadyInstalled,
+
+    #[error("Failed to seal column value")]
+    Seal,
+
+    /// Tampered with, or sealed with a key that is no longer configured.
+    #[error("Failed to open column value")]
+    Open,
+}
+
+/// Seals and opens column values.
+pub struct ColumnCipher {
+    /// The first key seals; all of them are tried when opening.
+    keys: Vec<LessSafeKey>,
+    rng: SystemRandom,
+}
+
+impl ColumnCipher {
+    /// Encrypt with `key`, and open values sealed with it or with one of
+    /// the `previous` keys. Keys are 32 bytes.
+    pub fn new(key: &[u8], previous: &[Vec<u8>]) -> Result<Self, ColumnEncryptionError> {
+        let keys = std::iter::once(key)
+            .chain(previous.iter().map(Vec::as_slice))
+            .map(|key| {
+                UnboundKey::new(&AES_256_GCM, key)
+                    .map(LessSafeKey::new)
+                    .map_err(|_| {
+                        ColumnEncryptionError::InvalidKey(format!(
+                            "expected 32 bytes, got {}",
+                            key.len()
+                        ))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            keys,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Keys from [`KEY_SECRET`] and [`PREVIOUS_KEYS_SECRET`]; `None` when no
+    /// key is configured.
+    pub async fn from_secrets(
+        secrets: &dyn SecretsProvider,
+    ) -> Result<Option<Self>, ColumnEncryptionError> {
+        let Some(key) = secrets.secret(KEY_SECRET).await? else {
+            return Ok(None);
+        };
+        let previous = secrets
+            .secret(PREVIOUS_KEYS_SECRET)
+            .await?
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(decode_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(&decode_key(&key)?, &previous).map(Some)
+    }
+
+    /// Make this the cipher every [`EncryptedString`] is sealed with. Once
+    /// per process, before the first row is read or written.
+    pub fn install(self) -> Result<(), ColumnEncryptionError> {
+        CIPHER
+            .set(self)
+            .map_err(|_| ColumnEncryptionError::AlreadyInstalled)
+    }
+
+    /// Whether a cipher is installed, i.e. values are sealed when written.
+    pub fn is_installed() -> bool {
+        CIPHER.get().is_some()
+    }
+
+    /// Whether `stored` is a sealed value rather than plaintext.
+    pub fn is_sealed(stored: &str) -> bool {
+        stored.starts_with(SEALED_PREFIX)
+    }
+
+    /// `plaintext` sealed under a fresh nonce, as stored in the column.
+    pub fn seal(&self, plaintext: &str) -> Result<String, ColumnEncryptionError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| ColumnEncryptionError::Seal)?;
+        let mut sealed = plaintext.as_bytes().to_vec();
+        self.keys[0]
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .map_err(|_| ColumnEncryptionError::Seal)?;
+
+        let mut stored = nonce.to_vec();
+        stored.extend_from_slice(&sealed);
+        Ok(format!("{}{}", SEALED_PREFIX, BASE64.encode(stored)))
+    }
+
+    /// Open a value [`seal`](Self::seal)ed with one of the keys.
+    pub fn open(&self, stored: &str) -> Result<String, ColumnEncryptionError> {
+        let stored = stored
+            .strip_prefix(SEALED_PREFIX)
+            .and_then(|stored| BASE64.decode(stored).ok())
+            .filter(|stored| stored.len() > NONCE_LEN)
+            .ok_or(ColumnEncryptionError::Open)?;
+        let (nonce, sealed) = stored.split_at(NONCE_LEN);
+
+        for key in &self.keys {
+            let mut buffer = sealed.to_vec();
+            let nonce =
+                Nonce::try_assume_unique_for_key(nonce).map_err(|_| ColumnEncryptionError::Open)?;
+            if let Ok(opened) = key.open_in_place(nonce, Aad::empty(), &mut buffer) {
+                return String::from_utf8(opened.to_vec()).map_err(|_| ColumnEncryptionError::Open);
+            }
+        }
+        Err(ColumnEncryptionError::Open)
+    }
+}
+
+fn decode_key(key: &str) -> Result<Vec<u8>, ColumnEncryptionError> {
+    BASE64
+        .decode(key.trim())
+        .map_err(|e| ColumnEncryptionError::InvalidKey(e.to_string()))
+}
+
+/// A string column sealed at rest with the installed [`ColumnCipher`].
+///
+/// Without one, values are written as plaintext. Its `Debug` output never
+/// shows the value.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptedString(pub String);
+
+impl EncryptedString {
+    pub fn new(plaintext: impl Into<String>) -> Self {
+        Self(plaintext.into())
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+
+    /// The value as written to the column.
+    pub fn seal(&self) -> Result<String, ColumnEncryptionError> {
+        match CIPHER.get() {
+            Some(cipher) => cipher.seal(&self.0),
+            None => Ok(self.0.clone()),
+        }
+    }
+
+    /// The value read from a column: sealed, or plaintext written before a
+    /// key was configured.
+    pub fn open(stored: &str) -> Result<Self, ColumnEncryptionError> {
+        if !ColumnCipher::is_sealed(stored) {
+            return Ok(Self(stored.to_string()));
+        }
+        CIPHER
+            .get()
+            .ok_or(ColumnEncryptionError::Open)?
+            .open(stored)
+            .map(Self)
+    }
+}
+
+impl std::fmt::Debug for EncryptedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptedString(***)")
+    }
+}
+
+impl From<String> for EncryptedString {
+    fn from(plaintext: String) -> Self {
+        Self(plaintext)
+    }
+}
+
+/// Serialized sealed, for document stores.
+impl Serialize for EncryptedString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let stored = self.seal().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&stored)
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptedString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stored = String::deserialize(deserializer)?;
+        Self::open(&stored).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "postgres")]
+mod sea_orm_value {
+    use sea_orm::sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr};
+    use sea_orm::{ColIdx, DbErr, QueryResult, TryGetError, TryGetable, Value};
+
+    use super::EncryptedString;
+
+    impl From<EncryptedString> for Value {
+        fn from(value: EncryptedString) -> Self {
+            // Sealing only fails when the system's random source does
+            let stored = value.seal().expect("Failed to seal column value");
+            Value::String(Some(Box::new(stored)))
+        }
+    }
+
+    impl Nullable for EncryptedString {
+        fn null() -> Value {
+            Value::String(None)
+        }
+    }
+
+    impl ValueType for EncryptedString {
+        fn try_from(value: Value) -> Result<Self, ValueTypeErr> {
+            match value {
+                Value::String(Some(stored)) => Self::open(&stored).map_err(|_| ValueTypeErr),
+                _ => Err(ValueTypeErr),
+            }
+        }
+
+        fn type_name() -> String {
+            "EncryptedString".to_string()
+        }
+
+        fn array_type() -> ArrayType {
+            ArrayType::String
+        }
+
+        fn column_type() -> ColumnType {
+            ColumnType::Text
+        }
+    }
+
+    impl TryGetable for EncryptedString {
+        fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+            let stored = String::try_get_by(res, index)?;
+            Self::open(&stored).map_err(|e| TryGetError::DbErr(DbErr::Type(e.to_string())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The cipher is process-wide: every test installs the same one.
+    fn install_test_cipher() {
+        let _ = CIPHER.set(ColumnCipher::new(&[9; 32], &[]).unwrap());
+    }
+
+    #[test]
+    fn test_sealed_values_open_with_current_and_previous_keys() {
+        let old = ColumnCipher::new(&[1; 32], &[]).unwrap();
+        let sealed = old.seal("+44 20 7946 0958").unwrap();
+        assert!(ColumnCipher::is_sealed(&sealed));
+        assert!(!sealed.contains("7946"));
+        assert_eq!(old.open(&sealed).unwrap(), "+44 20 7946 0958");
+        // A fresh nonce every time
+        assert_ne!(old.seal("+44 20 7946 0958").unwrap(), sealed);
+
+        let rotated = ColumnCipher::new(&[2; 32], &[vec![1; 32]]).unwrap();
+        assert_eq!(rotated.open(&sealed).unwrap(), "+44 20 7946 0958");
+        let dropped = ColumnCipher::new(&[2; 32], &[]).unwrap();
+        assert!(dropped.open(&sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        tampered.replace_range(sealed.len() - 4.., "AAAA");
+        assert!(old.open(&tampered).is_err());
+
+        assert!(ColumnCipher::new(&[1; 16], &[]).is_err());
+    }
+
+    #[test]
+    fn test_values_round_trip_through_the_installed_cipher() {
+        install_test_cipher();
+        let phone = EncryptedString::new("+1 555 0100");
+
+        let stored = serde_json::to_value(&phone).unwrap();
+        assert!(ColumnCipher::is_sealed(stored.as_str().unwrap()));
+        assert_eq!(
+            serde_json::from_value::<EncryptedString>(stored).unwrap(),
+            phone
+        );
+
+        // Rows written before the key was configured read as they are
+        assert_eq!(EncryptedString::open("+1 555 0100").unwrap(), phone);
+        assert_eq!(format!("{phone:?}"), "EncryptedString(***)");
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_sea_orm_values_are_sealed() {
+        use sea_orm::Value;
+        use sea_orm::sea_query::ValueType;
+
+        install_test_cipher();
+        let secret = EncryptedString::new("JBSWY3DPEHPK3PXP");
+        let value = Value::from(secret.clone());
+        let Value::String(Some(stored)) = &value else {
+            panic!("expected a string value, got {value:?}");
+        };
+        assert!(!stored.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(
+            <EncryptedString as ValueType>::try_from(value).unwrap(),
+            secret
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Announcement entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "announcements")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub title: String,
+    #[sea_orm(column_type = "Text")]
+    pub body: String,
+    pub audience: String,
+    pub starts_at: DateTimeWithTimeZone,
+    pub ends_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain Announcement.
+///
+/// An unreadable audience narrows to admins rather than showing to everyone.
+impl From<Model> for apex_core::domain::Announcement {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            title: model.title,
+            body: model.body,
+            audience: model
+                .audience
+                .parse()
+                .unwrap_or_else(|_| apex_core::domain::Audience::Role("admin".to_string())),
+            starts_at: model.starts_at.into(),
+            ends_at: model.ends_at.map(Into::into),
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain Announcement to SeaORM ActiveModel.
+impl From<apex_core::domain::Announcement> for ActiveModel {
+    fn from(announcement: apex_core::domain::Announcement) -> Self {
+        Self {
+            id: Set(announcement.id),
+            title: Set(announcement.title),
+            body: Set(announcement.body),
+            audience: Set(announcement.audience.to_string()),
+            starts_at: Set(announcement.starts_at.into()),
+            ends_at: Set(announcement.ends_at.map(Into::into)),
+            created_at: Set(announcement.created_at.into()),
+            updated_at: Set(announcement.updated_at.into()),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Attachment entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+/// Metadata of an uploaded file; the bytes are in object storage.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "attachments")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub organization_id: Option<Uuid>,
+    pub post_id: Option<Uuid>,
+    #[sea_orm(unique)]
+    pub key: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::post::Entity",
+        from = "Column::PostId",
+        to = "super::post::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Post,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::post::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Post.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain Attachment.
+impl From<Model> for apex_core::domain::Attachment {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            user_id: model.user_id,
+            organization_id: model.organization_id,
+            post_id: model.post_id,
+            key: model.key,
+            filename: model.filename,
+            content_type: model.content_type,
+            size_bytes: model.size_bytes,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain Attachment to SeaORM ActiveModel.
+impl From<apex_core::domain::Attachment> for ActiveModel {
+    fn from(attachment: apex_core::domain::Attachment) -> Self {
+        Self {
+            id: Set(attachment.id),
+            user_id: Set(attachment.user_id),
+            organization_id: Set(attachment.organization_id),
+            post_id: Set(attachment.post_id),
+            key: Set(attachment.key),
+            filename: Set(attachment.filename),
+            content_type: Set(attachment.content_type),
+            size_bytes: Set(attachment.size_bytes),
+            created_at: Set(attachment.created_at.into()),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Custom domain entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "custom_domains")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    #[sea_orm(unique)]
+    pub hostname: String,
+    pub verification_token: String,
+    pub verified_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain CustomDomain.
+impl From<Model> for apex_core::domain::CustomDomain {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            organization_id: model.organization_id,
+            hostname: model.hostname,
+            verification_token: model.verification_token,
+            verified_at: model.verified_at.map(Into::into),
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain CustomDomain to SeaORM ActiveModel.
+impl From<apex_core::domain::CustomDomain> for ActiveModel {
+    fn from(domain: apex_core::domain::CustomDomain) -> Self {
+        Self {
+            id: Set(domain.id),
+            organization_id: Set(domain.organization_id),
+            hostname: Set(domain.hostname),
+            verification_token: Set(domain.verification_token),
+            verified_at: Set(domain.verified_at.map(Into::into)),
+            created_at: Set(domain.created_at.into()),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Organization invitation entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "invitations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub email: String,
+    pub role: String,
+    #[sea_orm(unique)]
+    pub token: String,
+    pub invited_by: Uuid,
+    pub expires_at: DateTimeWithTimeZone,
+    pub accepted_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Organization,
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain Invitation.
+impl From<Model> for apex_core::domain::Invitation {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            organization_id: model.organization_id,
+            email: apex_core::domain::Email::from_stored(model.email),
+            role: model
+                .role
+                .parse()
+                .unwrap_or(apex_core::domain::OrgRole::Member),
+            token: model.token,
+            invited_by: model.invited_by,
+            expires_at: model.expires_at.into(),
+            accepted_at: model.accepted_at.map(Into::into),
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain Invitation to SeaORM ActiveModel.
+impl From<apex_core::domain::Invitation> for ActiveModel {
+    fn from(invitation: apex_core::domain::Invitation) -> Self {
+        Self {
+            id: Set(invitation.id),
+            organization_id: Set(invitation.organization_id),
+            email: Set(invitation.email.into()),
+            role: Set(invitation.role.to_string()),
+            token: Set(invitation.token),
+            invited_by: Set(invitation.invited_by),
+            expires_at: Set(invitation.expires_at.into()),
+            accepted_at: Set(invitation.accepted_at.map(Into::into)),
+            created_at: Set(invitation.created_at.into()),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Legal hold change entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "legal_hold_changes")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub held: bool,
+    pub reason: String,
+    pub changed_by: Uuid,
+    pub changed_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain LegalHoldChange.
+impl From<Model> for apex_core::domain::LegalHoldChange {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            user_id: model.user_id,
+            held: model.held,
+            reason: model.reason,
+            changed_by: model.changed_by,
+            changed_at: model.changed_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain LegalHoldChange to SeaORM ActiveModel.
+impl From<apex_core::domain::LegalHoldChange> for ActiveModel {
+    fn from(change: apex_core::domain::LegalHoldChange) -> Self {
+        Self {
+            id: Set(change.id),
+            user_id: Set(change.user_id),
+            held: Set(change.held),
+            reason: Set(change.reason),
+            changed_by: Set(change.changed_by),
+            changed_at: Set(change.changed_at.into()),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Organization membership entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "memberships")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Organization,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain Membership.
+///
+/// Unknown roles fall back to the least privileged one.
+impl From<Model> for apex_core::domain::Membership {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            organization_id: model.organization_id,
+            user_id: model.user_id,
+            role: model
+                .role
+                .parse()
+                .unwrap_or(apex_core::domain::OrgRole::Member),
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain Membership to SeaORM ActiveModel.
+impl From<apex_core::domain::Membership> for ActiveModel {
+    fn from(membership: apex_core::domain::Membership) -> Self {
+        Self {
+            id: Set(membership.id),
+            organization_id: Set(membership.organization_id),
+            user_id: Set(membership.user_id),
+            role: Set(membership.role.to_string()),
+            created_at: Set(membership.created_at.into()),
+        }
+    }
+}
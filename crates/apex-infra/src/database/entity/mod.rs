@@ -3,8 +3,48 @@
 //! These are auto-generated by `sea-orm-cli generate entity` but
 //! we maintain them manually for better control.
 
+pub mod account_plan;
+pub mod announcement;
+pub mod attachment;
+pub mod comment;
+pub mod custom_domain;
+pub mod invitation;
+pub mod legal_hold_change;
+pub mod membership;
+pub mod oauth_client;
+pub mod organization;
+pub mod password_history;
+pub mod pending_operation;
+pub mod policy_acceptance;
 pub mod post;
+pub mod post_tag;
+pub mod setting;
+pub mod storage_usage;
+pub mod subscription;
+pub mod tag;
+pub mod usage_rollup;
 pub mod user;
+pub mod webhook_delivery;
 
+pub use account_plan::Entity as AccountPlan;
+pub use announcement::Entity as Announcement;
+pub use attachment::Entity as Attachment;
+pub use comment::Entity as Comment;
+pub use custom_domain::Entity as CustomDomain;
+pub use invitation::Entity as Invitation;
+pub use legal_hold_change::Entity as LegalHoldChange;
+pub use membership::Entity as Membership;
+pub use oauth_client::Entity as OAuthClient;
+pub use organization::Entity as Organization;
+pub use password_history::Entity as PasswordHistory;
+pub use pending_operation::Entity as PendingOperation;
+pub use policy_acceptance::Entity as PolicyAcceptance;
 pub use post::Entity as Post;
+pub use post_tag::Entity as PostTag;
+pub use setting::Entity as Setting;
+pub use storage_usage::Entity as StorageUsage;
+pub use subscription::Entity as Subscription;
+pub use tag::Entity as Tag;
+pub use usage_rollup::Entity as UsageRollup;
 pub use user::Entity as User;
+pub use webhook_delivery::Entity as WebhookDelivery;
@@ -0,0 +1,64 @@
+//! OAuth client entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "oauth_clients")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub name: String,
+    #[sea_orm(unique)]
+    pub client_id: String,
+    pub client_secret_hash: String,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub redirect_uris: Json,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub scopes: Json,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain OAuthClient.
+///
+/// Unreadable redirect URIs or scopes read as none, so the client cannot be
+/// used until it is fixed.
+impl From<Model> for apex_core::domain::OAuthClient {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            owner_id: model.owner_id,
+            name: model.name,
+            client_id: model.client_id,
+            client_secret_hash: model.client_secret_hash,
+            redirect_uris: serde_json::from_value(model.redirect_uris).unwrap_or_default(),
+            scopes: serde_json::from_value(model.scopes).unwrap_or_default(),
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain OAuthClient to SeaORM ActiveModel.
+impl From<apex_core::domain::OAuthClient> for ActiveModel {
+    fn from(client: apex_core::domain::OAuthClient) -> Self {
+        Self {
+            id: Set(client.id),
+            owner_id: Set(client.owner_id),
+            name: Set(client.name),
+            client_id: Set(client.client_id),
+            client_secret_hash: Set(client.client_secret_hash),
+            redirect_uris: Set(serde_json::json!(client.redirect_uris)),
+            scopes: Set(serde_json::json!(client.scopes)),
+            created_at: Set(client.created_at.into()),
+            updated_at: Set(client.updated_at.into()),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Organization entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "organizations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub name: String,
+    #[sea_orm(unique)]
+    pub slug: String,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::membership::Entity")]
+    Membership,
+}
+
+impl Related<super::membership::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Membership.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain Organization.
+impl From<Model> for apex_core::domain::Organization {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            slug: model.slug,
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain Organization to SeaORM ActiveModel.
+impl From<apex_core::domain::Organization> for ActiveModel {
+    fn from(org: apex_core::domain::Organization) -> Self {
+        Self {
+            id: Set(org.id),
+            name: Set(org.name),
+            slug: Set(org.slug),
+            created_at: Set(org.created_at.into()),
+            updated_at: Set(org.updated_at.into()),
+        }
+    }
+}
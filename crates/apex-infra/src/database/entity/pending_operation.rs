@@ -0,0 +1,65 @@
+//! Pending admin operation entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+use apex_core::domain::{AdminAction, ApprovalStatus};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "pending_operations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub action: Json,
+    pub status: String,
+    pub requested_by: Uuid,
+    pub requested_at: DateTimeWithTimeZone,
+    pub expires_at: DateTimeWithTimeZone,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub outcome: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain PendingOperation.
+///
+/// An unreadable action cannot run and an unreadable status reads as
+/// rejected, so neither can be approved.
+impl From<Model> for apex_core::domain::PendingOperation {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            action: serde_json::from_value(model.action).unwrap_or(AdminAction::Unknown),
+            status: model.status.parse().unwrap_or(ApprovalStatus::Rejected),
+            requested_by: model.requested_by,
+            requested_at: model.requested_at.into(),
+            expires_at: model.expires_at.into(),
+            decided_by: model.decided_by,
+            decided_at: model.decided_at.map(Into::into),
+            outcome: model.outcome,
+        }
+    }
+}
+
+/// Conversion from Domain PendingOperation to SeaORM ActiveModel.
+impl From<apex_core::domain::PendingOperation> for ActiveModel {
+    fn from(operation: apex_core::domain::PendingOperation) -> Self {
+        Self {
+            id: Set(operation.id),
+            action: Set(serde_json::to_value(&operation.action).unwrap_or_default()),
+            status: Set(operation.status.as_str().to_string()),
+            requested_by: Set(operation.requested_by),
+            requested_at: Set(operation.requested_at.into()),
+            expires_at: Set(operation.expires_at.into()),
+            decided_by: Set(operation.decided_by),
+            decided_at: Set(operation.decided_at.map(Into::into)),
+            outcome: Set(operation.outcome),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Policy acceptance entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "policy_acceptances")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub policy: String,
+    pub version: String,
+    pub accepted_at: DateTimeWithTimeZone,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain PolicyAcceptance.
+///
+/// Rows for policies this build does not know are skipped by the caller.
+impl TryFrom<Model> for apex_core::domain::PolicyAcceptance {
+    type Error = apex_core::error::DomainError;
+
+    fn try_from(model: Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: model.id,
+            user_id: model.user_id,
+            policy: model.policy.parse()?,
+            version: model.version,
+            accepted_at: model.accepted_at.into(),
+            ip_address: model.ip_address,
+            user_agent: model.user_agent,
+        })
+    }
+}
+
+/// Conversion from Domain PolicyAcceptance to SeaORM ActiveModel.
+impl From<apex_core::domain::PolicyAcceptance> for ActiveModel {
+    fn from(acceptance: apex_core::domain::PolicyAcceptance) -> Self {
+        Self {
+            id: Set(acceptance.id),
+            user_id: Set(acceptance.user_id),
+            policy: Set(acceptance.policy.to_string()),
+            version: Set(acceptance.version),
+            accepted_at: Set(acceptance.accepted_at.into()),
+            ip_address: Set(acceptance.ip_address),
+            user_agent: Set(acceptance.user_agent),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 //! Post entity for SeaORM.
 
-use sea_orm::Set;
 use sea_orm::entity::prelude::*;
+use sea_orm::{NotSet, Set};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
 #[sea_orm(table_name = "posts")]
@@ -9,11 +9,18 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
     pub user_id: Uuid,
+    pub organization_id: Option<Uuid>,
     pub title: String,
     #[sea_orm(column_type = "Text")]
     pub content: String,
+    pub version: i64,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub view_count: i64,
+    pub published_at: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub created_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -42,10 +49,17 @@ impl From<Model> for apex_core::domain::Post {
         Self {
             id: model.id,
             user_id: model.user_id,
+            organization_id: model.organization_id,
             title: model.title,
             content: model.content,
+            version: model.version,
+            deleted_at: model.deleted_at.map(Into::into),
+            view_count: model.view_count,
+            published_at: model.published_at.map(Into::into),
             created_at: model.created_at.into(),
             updated_at: model.updated_at.into(),
+            created_by: model.created_by,
+            updated_by: model.updated_by,
         }
     }
 }
@@ -53,13 +67,23 @@ impl From<Model> for apex_core::domain::Post {
 /// Conversion from Domain Post to SeaORM ActiveModel.
 impl From<apex_core::domain::Post> for ActiveModel {
     fn from(post: apex_core::domain::Post) -> Self {
+        let (created_by, updated_by) =
+            crate::context::audit_stamp(post.created_by, post.updated_by);
         Self {
             id: Set(post.id),
             user_id: Set(post.user_id),
+            organization_id: Set(post.organization_id),
             title: Set(post.title),
             content: Set(post.content),
+            version: Set(post.version),
+            deleted_at: Set(post.deleted_at.map(Into::into)),
+            // Only ever incremented, by `PostRepository::add_views`
+            view_count: NotSet,
+            published_at: Set(post.published_at.map(Into::into)),
             created_at: Set(post.created_at.into()),
             updated_at: Set(post.updated_at.into()),
+            created_by: Set(created_by),
+            updated_by: Set(updated_by),
         }
     }
 }
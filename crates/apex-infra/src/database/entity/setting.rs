@@ -0,0 +1,20 @@
+//! Settings document entity for SeaORM.
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "settings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub scope_kind: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub scope_id: Uuid,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub values: Json,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,60 @@
+//! Subscription entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "subscriptions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub account_id: Uuid,
+    pub plan: String,
+    pub status: String,
+    pub trial_ends_at: Option<DateTimeWithTimeZone>,
+    pub current_period_end: Option<DateTimeWithTimeZone>,
+    pub external_id: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain Subscription.
+///
+/// Unknown values fall back to the least privileged ones.
+impl From<Model> for apex_core::domain::Subscription {
+    fn from(model: Model) -> Self {
+        Self {
+            account_id: model.account_id,
+            plan: model.plan.parse().unwrap_or_default(),
+            status: model
+                .status
+                .parse()
+                .unwrap_or(apex_core::domain::SubscriptionStatus::Canceled),
+            trial_ends_at: model.trial_ends_at.map(Into::into),
+            current_period_end: model.current_period_end.map(Into::into),
+            external_id: model.external_id,
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain Subscription to SeaORM ActiveModel.
+impl From<apex_core::domain::Subscription> for ActiveModel {
+    fn from(subscription: apex_core::domain::Subscription) -> Self {
+        Self {
+            account_id: Set(subscription.account_id),
+            plan: Set(subscription.plan.to_string()),
+            status: Set(subscription.status.to_string()),
+            trial_ends_at: Set(subscription.trial_ends_at.map(Into::into)),
+            current_period_end: Set(subscription.current_period_end.map(Into::into)),
+            external_id: Set(subscription.external_id),
+            created_at: Set(subscription.created_at.into()),
+            updated_at: Set(subscription.updated_at.into()),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Tag entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub name: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::post_tag::Entity")]
+    PostTag,
+}
+
+impl Related<super::post_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PostTag.def()
+    }
+}
+
+impl Related<super::post::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::post_tag::Relation::Post.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::post_tag::Relation::Tag.def().rev())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain Tag.
+impl From<Model> for apex_core::domain::Tag {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain Tag to SeaORM ActiveModel.
+impl From<apex_core::domain::Tag> for ActiveModel {
+    fn from(tag: apex_core::domain::Tag) -> Self {
+        Self {
+            id: Set(tag.id),
+            name: Set(tag.name),
+            created_at: Set(tag.created_at.into()),
+        }
+    }
+}
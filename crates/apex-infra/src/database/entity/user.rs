@@ -3,6 +3,8 @@
 use sea_orm::Set;
 use sea_orm::entity::prelude::*;
 
+use crate::database::EncryptedString;
+
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
 #[sea_orm(table_name = "users")]
 pub struct Model {
@@ -11,8 +13,17 @@ pub struct Model {
     #[sea_orm(unique)]
     pub email: String,
     pub password_hash: String,
+    /// JSON array of role names; `NULL` for accounts that predate roles.
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub roles: Option<Json>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub phone_number: Option<EncryptedString>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub two_factor_secret: Option<EncryptedString>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub created_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -21,14 +32,24 @@ pub enum Relation {}
 impl ActiveModelBehavior for ActiveModel {}
 
 /// Conversion from SeaORM Model to Domain User.
+///
+/// Missing or unreadable roles read as the default ones.
 impl From<Model> for apex_core::domain::User {
     fn from(model: Model) -> Self {
         Self {
             id: model.id,
-            email: model.email,
+            email: apex_core::domain::Email::from_stored(model.email),
             password_hash: model.password_hash,
+            roles: model
+                .roles
+                .and_then(|roles| serde_json::from_value(roles).ok())
+                .unwrap_or_else(apex_core::domain::User::default_roles),
+            phone_number: model.phone_number.map(EncryptedString::into_inner),
+            two_factor_secret: model.two_factor_secret.map(EncryptedString::into_inner),
             created_at: model.created_at.into(),
             updated_at: model.updated_at.into(),
+            created_by: model.created_by,
+            updated_by: model.updated_by,
         }
     }
 }
@@ -36,12 +57,19 @@ impl From<Model> for apex_core::domain::User {
 /// Conversion from Domain User to SeaORM ActiveModel.
 impl From<apex_core::domain::User> for ActiveModel {
     fn from(user: apex_core::domain::User) -> Self {
+        let (created_by, updated_by) =
+            crate::context::audit_stamp(user.created_by, user.updated_by);
         Self {
             id: Set(user.id),
-            email: Set(user.email),
+            email: Set(user.email.into()),
             password_hash: Set(user.password_hash),
+            roles: Set(Some(serde_json::json!(user.roles))),
+            phone_number: Set(user.phone_number.map(EncryptedString::from)),
+            two_factor_secret: Set(user.two_factor_secret.map(EncryptedString::from)),
             created_at: Set(user.created_at.into()),
             updated_at: Set(user.updated_at.into()),
+            created_by: Set(created_by),
+            updated_by: Set(updated_by),
         }
     }
 }
@@ -0,0 +1,68 @@
+//! Webhook delivery entity for SeaORM.
+
+use sea_orm::Set;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "webhook_deliveries")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_type = "Text")]
+    pub url: String,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub payload: Json,
+    pub response_status: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub response_body: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub latency_ms: i64,
+    pub attempt: i32,
+    pub replay_of: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Conversion from SeaORM Model to Domain WebhookDelivery.
+impl From<Model> for apex_core::domain::WebhookDelivery {
+    fn from(model: Model) -> Self {
+        Self {
+            id: model.id,
+            url: model.url,
+            payload: model.payload,
+            response_status: model.response_status.map(|s| s as u16),
+            response_body: model.response_body,
+            error: model.error,
+            latency_ms: model.latency_ms as u64,
+            attempt: model.attempt as u32,
+            replay_of: model.replay_of,
+            request_id: model.request_id,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// Conversion from Domain WebhookDelivery to SeaORM ActiveModel.
+impl From<apex_core::domain::WebhookDelivery> for ActiveModel {
+    fn from(delivery: apex_core::domain::WebhookDelivery) -> Self {
+        Self {
+            id: Set(delivery.id),
+            url: Set(delivery.url),
+            payload: Set(delivery.payload),
+            response_status: Set(delivery.response_status.map(i32::from)),
+            response_body: Set(delivery.response_body),
+            error: Set(delivery.error),
+            latency_ms: Set(delivery.latency_ms as i64),
+            attempt: Set(delivery.attempt as i32),
+            replay_of: Set(delivery.replay_of),
+            request_id: Set(delivery.request_id),
+            created_at: Set(delivery.created_at.into()),
+        }
+    }
+}
@@ -0,0 +1,1023 @@
+//! In-memory user, password history, post, comment, tag and attachment
+//! repositories, used when there is no database.
+//!
+//! They behave like the Postgres ones, constraints included, so sign-up,
+//! login, password changes, posting, commenting, tagging and attaching files
+//! work end to end without Postgres. Data is lost on process restart.
+
+use std::cmp::Ordering;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use uuid::Uuid;
+
+use apex_core::domain::{
+    Attachment, Comment, CompareOp, Email, Filter, FilterValue, Page, PageCursor, PageRequest,
+    Post, SortDirection, SyncCursor, Tag, User,
+};
+use apex_core::error::RepoError;
+use apex_core::ports::{
+    AttachmentRepository, BaseRepository, CommentRepository, PasswordHistoryRepository,
+    PostRepository, TagRepository, UserRepository,
+};
+
+use crate::context;
+
+/// A field value to sort or filter by. Missing timestamps sort last
+/// ascending and first descending, as NULLs do in Postgres.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum SortValue {
+    Id(Option<Uuid>),
+    Text(String),
+    Number(i64),
+    Time(Option<DateTime<Utc>>),
+}
+
+impl SortValue {
+    fn is_null(&self) -> bool {
+        matches!(self, SortValue::Id(None) | SortValue::Time(None))
+    }
+
+    fn cmp_nulls_last(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (SortValue::Time(None), SortValue::Time(Some(_))) => Ordering::Greater,
+            (SortValue::Time(Some(_)), SortValue::Time(None)) => Ordering::Less,
+            _ => self.cmp(other),
+        }
+    }
+}
+
+fn user_sort_value(user: &User, field: &str) -> Option<SortValue> {
+    Some(match field {
+        "id" => SortValue::Id(Some(user.id)),
+        "email" => SortValue::Text(user.email.to_string()),
+        "password_hash" => SortValue::Text(user.password_hash.clone()),
+        "created_at" => SortValue::Time(Some(user.created_at)),
+        "updated_at" => SortValue::Time(Some(user.updated_at)),
+        _ => return None,
+    })
+}
+
+fn post_sort_value(post: &Post, field: &str) -> Option<SortValue> {
+    Some(match field {
+        "id" => SortValue::Id(Some(post.id)),
+        "user_id" => SortValue::Id(Some(post.user_id)),
+        "organization_id" => SortValue::Id(post.organization_id),
+        "title" => SortValue::Text(post.title.clone()),
+        "content" => SortValue::Text(post.content.clone()),
+        "version" => SortValue::Number(post.version),
+        "view_count" => SortValue::Number(post.view_count),
+        "deleted_at" => SortValue::Time(post.deleted_at),
+        "published_at" => SortValue::Time(post.published_at),
+        "created_at" => SortValue::Time(Some(post.created_at)),
+        "updated_at" => SortValue::Time(Some(post.updated_at)),
+        _ => return None,
+    })
+}
+
+fn comment_sort_value(comment: &Comment, field: &str) -> Option<SortValue> {
+    Some(match field {
+        "id" => SortValue::Id(Some(comment.id)),
+        "post_id" => SortValue::Id(Some(comment.post_id)),
+        "user_id" => SortValue::Id(Some(comment.user_id)),
+        "body" => SortValue::Text(comment.body.clone()),
+        "created_at" => SortValue::Time(Some(comment.created_at)),
+        "updated_at" => SortValue::Time(Some(comment.updated_at)),
+        _ => return None,
+    })
+}
+
+fn attachment_sort_value(attachment: &Attachment, field: &str) -> Option<SortValue> {
+    Some(match field {
+        "id" => SortValue::Id(Some(attachment.id)),
+        "user_id" => SortValue::Id(Some(attachment.user_id)),
+        "organization_id" => SortValue::Id(attachment.organization_id),
+        "post_id" => SortValue::Id(attachment.post_id),
+        "key" => SortValue::Text(attachment.key.clone()),
+        "filename" => SortValue::Text(attachment.filename.clone()),
+        "content_type" => SortValue::Text(attachment.content_type.clone()),
+        "size_bytes" => SortValue::Number(attachment.size_bytes),
+        "created_at" => SortValue::Time(Some(attachment.created_at)),
+        _ => return None,
+    })
+}
+
+/// The page of `items` that `request` asks for. Items are ordered by id
+/// before the requested sort, so equal values keep their order from one page
+/// to the next.
+fn page_of<T>(
+    mut items: Vec<T>,
+    request: &PageRequest,
+    id: fn(&T) -> Uuid,
+    value: fn(&T, &str) -> Option<SortValue>,
+) -> Result<Page<T>, RepoError> {
+    if let Some(filter) = &request.filter {
+        let mut kept = Vec::with_capacity(items.len());
+        for item in items {
+            if meets(filter, &item, value)? {
+                kept.push(item);
+            }
+        }
+        items = kept;
+    }
+    items.sort_by_key(id);
+    if let Some(sort) = &request.sort {
+        let mut keyed = items
+            .into_iter()
+            .map(|item| match value(&item, &sort.field) {
+                Some(key) => Ok((key, item)),
+                None => Err(RepoError::Query(format!(
+                    "Unknown sort field: {}",
+                    sort.field
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        keyed.sort_by(|(a, _), (b, _)| match sort.direction {
+            SortDirection::Asc => a.cmp_nulls_last(b),
+            SortDirection::Desc => b.cmp_nulls_last(a),
+        });
+        items = keyed.into_iter().map(|(_, item)| item).collect();
+    }
+
+    let total = items.len() as u64;
+    Ok(Page {
+        items: items
+            .into_iter()
+            .skip(request.offset as usize)
+            .take(request.limit as usize)
+            .collect(),
+        total,
+        offset: request.offset,
+        limit: request.limit,
+    })
+}
+
+fn sort_value(value: &FilterValue) -> SortValue {
+    match value {
+        FilterValue::Uuid(id) => SortValue::Id(Some(*id)),
+        FilterValue::Text(text) => SortValue::Text(text.clone()),
+        FilterValue::Number(number) => SortValue::Number(*number),
+        FilterValue::Time(time) => SortValue::Time(Some(*time)),
+    }
+}
+
+fn check_kind(field: &str, actual: &SortValue, expected: &SortValue) -> Result<(), RepoError> {
+    if std::mem::discriminant(actual) != std::mem::discriminant(expected) {
+        return Err(RepoError::Query(format!(
+            "Invalid value for filter field: {}",
+            field
+        )));
+    }
+    Ok(())
+}
+
+/// Whether `item` meets `filter`, with the semantics of its SQL translation:
+/// comparisons never match an unset field.
+fn meets<T>(
+    filter: &Filter,
+    item: &T,
+    value: fn(&T, &str) -> Option<SortValue>,
+) -> Result<bool, RepoError> {
+    let field_value = |field: &str| {
+        value(item, field)
+            .ok_or_else(|| RepoError::Query(format!("Unknown filter field: {}", field)))
+    };
+    Ok(match filter {
+        Filter::Compare {
+            field,
+            op,
+            value: expected,
+        } => {
+            let expected = sort_value(expected);
+            let actual = field_value(field)?;
+            if actual.is_null() {
+                return Ok(false);
+            }
+            check_kind(field, &actual, &expected)?;
+            let ordering = actual.cmp(&expected);
+            match op {
+                CompareOp::Eq => ordering.is_eq(),
+                CompareOp::Ne => ordering.is_ne(),
+                CompareOp::Lt => ordering.is_lt(),
+                CompareOp::Lte => ordering.is_le(),
+                CompareOp::Gt => ordering.is_gt(),
+                CompareOp::Gte => ordering.is_ge(),
+            }
+        }
+        Filter::In { field, values } => {
+            let actual = field_value(field)?;
+            if actual.is_null() {
+                return Ok(false);
+            }
+            let mut found = false;
+            for expected in values.iter().map(sort_value) {
+                check_kind(field, &actual, &expected)?;
+                found |= actual == expected;
+            }
+            found
+        }
+        Filter::Null { field, null } => field_value(field)?.is_null() == *null,
+        Filter::And(filters) => {
+            for filter in filters {
+                if !meets(filter, item, value)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        Filter::Or(filters) => {
+            for filter in filters {
+                if meets(filter, item, value)? {
+                    return Ok(true);
+                }
+            }
+            false
+        }
+    })
+}
+
+/// Users in a `DashMap` keyed by id, with an email index keeping emails
+/// unique.
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    users: DashMap<Uuid, User>,
+    emails: DashMap<Email, Uuid>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Point `email` at `id`, unless another user has it.
+    fn claim_email(&self, email: &Email, id: Uuid) -> Result<(), RepoError> {
+        match self.emails.entry(email.clone()) {
+            Entry::Occupied(owner) if *owner.get() != id => Err(RepoError::Constraint(
+                "Email is already registered".to_string(),
+            )),
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(slot) => {
+                slot.insert(id);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BaseRepository<User, Uuid> for InMemoryUserRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, RepoError> {
+        Ok(self.users.get(&id).map(|user| user.clone()))
+    }
+
+    async fn save(&self, mut entity: User) -> Result<User, RepoError> {
+        // Users before emails, everywhere, so two writers can't deadlock
+        let slot = self.users.entry(entity.id);
+        let created_by = match &slot {
+            Entry::Occupied(stored) => stored.get().created_by,
+            Entry::Vacant(_) => entity.created_by,
+        };
+        (entity.created_by, entity.updated_by) =
+            context::audit_stamp(created_by, entity.updated_by);
+        self.claim_email(&entity.email, entity.id)?;
+        if let Entry::Occupied(stored) = &slot
+            && stored.get().email != entity.email
+        {
+            self.emails
+                .remove_if(&stored.get().email, |_, id| *id == entity.id);
+        }
+        slot.insert(entity.clone());
+        Ok(entity)
+    }
+
+    async fn insert(&self, mut entity: User) -> Result<User, RepoError> {
+        let Entry::Vacant(slot) = self.users.entry(entity.id) else {
+            return Err(RepoError::Constraint("User already exists".to_string()));
+        };
+        (entity.created_by, entity.updated_by) =
+            context::audit_stamp(entity.created_by, entity.updated_by);
+        self.claim_email(&entity.email, entity.id)?;
+        slot.insert(entity.clone());
+        Ok(entity)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        let (_, user) = self.users.remove(&id).ok_or(RepoError::NotFound)?;
+        self.emails.remove_if(&user.email, |_, owner| *owner == id);
+        Ok(())
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<User>, RepoError> {
+        let users = self.users.iter().map(|user| user.clone()).collect();
+        page_of(users, &request, |user| user.id, user_sort_value)
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn find_by_email(&self, email: &Email) -> Result<Option<User>, RepoError> {
+        let Some(id) = self.emails.get(email).map(|id| *id) else {
+            return Ok(None);
+        };
+        self.find_by_id(id).await
+    }
+}
+
+/// Replaced password hashes per user, newest first.
+#[derive(Default)]
+pub struct InMemoryPasswordHistoryRepository {
+    hashes: DashMap<Uuid, Vec<String>>,
+}
+
+impl InMemoryPasswordHistoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PasswordHistoryRepository for InMemoryPasswordHistoryRepository {
+    async fn recent(&self, user_id: Uuid, limit: usize) -> Result<Vec<String>, RepoError> {
+        Ok(self
+            .hashes
+            .get(&user_id)
+            .map(|hashes| hashes.iter().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn record(
+        &self,
+        user_id: Uuid,
+        password_hash: String,
+        keep: usize,
+    ) -> Result<(), RepoError> {
+        let mut hashes = self.hashes.entry(user_id).or_default();
+        hashes.insert(0, password_hash);
+        hashes.truncate(keep);
+        Ok(())
+    }
+}
+
+/// Posts in a `DashMap` keyed by id.
+#[derive(Default)]
+pub struct InMemoryPostRepository {
+    posts: DashMap<Uuid, Post>,
+}
+
+impl InMemoryPostRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Posts matching `filter`, oldest first.
+    fn matching(&self, filter: impl Fn(&Post) -> bool) -> Vec<Post> {
+        let mut posts: Vec<Post> = self
+            .posts
+            .iter()
+            .filter(|post| filter(post))
+            .map(|post| post.clone())
+            .collect();
+        posts.sort_by_key(|post| (post.created_at, post.id));
+        posts
+    }
+}
+
+/// `post` as stored over `stored`: saving a post never changes its view
+/// count, which starts at zero, nor its creator. Stamped with the current
+/// actor.
+fn as_stored(mut post: Post, stored: Option<&Post>) -> Post {
+    post.view_count = stored.map_or(0, |stored| stored.view_count);
+    let created_by = stored.map_or(post.created_by, |stored| stored.created_by);
+    (post.created_by, post.updated_by) = context::audit_stamp(created_by, post.updated_by);
+    post
+}
+
+#[async_trait]
+impl BaseRepository<Post, Uuid> for InMemoryPostRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Post>, RepoError> {
+        Ok(self.posts.get(&id).map(|post| post.clone()))
+    }
+
+    async fn save(&self, entity: Post) -> Result<Post, RepoError> {
+        let post = match self.posts.entry(entity.id) {
+            Entry::Occupied(mut stored) => {
+                let post = as_stored(entity, Some(stored.get()));
+                stored.insert(post.clone());
+                post
+            }
+            Entry::Vacant(slot) => slot.insert(as_stored(entity, None)).clone(),
+        };
+        Ok(post)
+    }
+
+    async fn insert(&self, entity: Post) -> Result<Post, RepoError> {
+        match self.posts.entry(entity.id) {
+            Entry::Occupied(_) => Err(RepoError::Constraint("Post already exists".to_string())),
+            Entry::Vacant(slot) => Ok(slot.insert(as_stored(entity, None)).clone()),
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        self.posts
+            .remove(&id)
+            .map(|_| ())
+            .ok_or(RepoError::NotFound)
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<Post>, RepoError> {
+        let posts = self.posts.iter().map(|post| post.clone()).collect();
+        page_of(posts, &request, |post| post.id, post_sort_value)
+    }
+}
+
+#[async_trait]
+impl PostRepository for InMemoryPostRepository {
+    async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<Post>, RepoError> {
+        Ok(self.matching(|post| post.user_id == user_id && post.deleted_at.is_none()))
+    }
+
+    async fn find_page_by_user_id(
+        &self,
+        user_id: Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        let posts = self.matching(|post| post.user_id == user_id && post.deleted_at.is_none());
+        page_of(posts, &request, |post| post.id, post_sort_value)
+    }
+
+    async fn find_by_user_id_after(
+        &self,
+        user_id: Uuid,
+        after: Option<PageCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let mut posts = self.matching(|post| {
+            post.user_id == user_id
+                && post.deleted_at.is_none()
+                && after.is_none_or(|after| after.precedes(post.created_at, post.id))
+        });
+        posts.reverse();
+        posts.truncate(limit as usize);
+        Ok(posts)
+    }
+
+    async fn find_by_organization_id(&self, organization_id: Uuid) -> Result<Vec<Post>, RepoError> {
+        Ok(self.matching(|post| {
+            post.organization_id == Some(organization_id) && post.deleted_at.is_none()
+        }))
+    }
+
+    async fn find_page_by_organization_id(
+        &self,
+        organization_id: Uuid,
+        user_id: Option<Uuid>,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        let posts = self.matching(|post| {
+            post.organization_id == Some(organization_id)
+                && user_id.is_none_or(|user_id| post.user_id == user_id)
+                && post.deleted_at.is_none()
+        });
+        page_of(posts, &request, |post| post.id, post_sort_value)
+    }
+
+    async fn list_changes(
+        &self,
+        user_id: Uuid,
+        after: Option<SyncCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let after = after.map(|cursor| (cursor.updated_at, cursor.id));
+        let mut posts = self.matching(|post| {
+            post.user_id == user_id && after.is_none_or(|after| (post.updated_at, post.id) > after)
+        });
+        posts.sort_by_key(|post| (post.updated_at, post.id));
+        posts.truncate(limit as usize);
+        Ok(posts)
+    }
+
+    async fn save_if_version(
+        &self,
+        post: Post,
+        expected_version: Option<i64>,
+    ) -> Result<(), RepoError> {
+        let id = post.id;
+        match (self.posts.entry(id), expected_version) {
+            (Entry::Vacant(slot), None) => {
+                slot.insert(as_stored(post, None));
+                Ok(())
+            }
+            (Entry::Occupied(mut stored), Some(version)) if stored.get().version == version => {
+                let post = as_stored(post, Some(stored.get()));
+                stored.insert(post);
+                Ok(())
+            }
+            _ => Err(RepoError::stale_version(id, expected_version)),
+        }
+    }
+
+    async fn add_views(&self, views: Vec<(Uuid, i64)>) -> Result<(), RepoError> {
+        for (id, count) in views {
+            if let Some(mut post) = self.posts.get_mut(&id) {
+                post.view_count += count;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_published(&self, now: DateTime<Utc>, limit: u64) -> Result<Vec<Post>, RepoError> {
+        let mut posts = self.matching(|post| {
+            post.deleted_at.is_none() && post.published_at.is_some_and(|at| at <= now)
+        });
+        posts.sort_by_key(|post| std::cmp::Reverse((post.published_at, post.id)));
+        posts.truncate(limit as usize);
+        Ok(posts)
+    }
+}
+
+/// Comments in a `DashMap` keyed by id.
+#[derive(Default)]
+pub struct InMemoryCommentRepository {
+    comments: DashMap<Uuid, Comment>,
+}
+
+impl InMemoryCommentRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BaseRepository<Comment, Uuid> for InMemoryCommentRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Comment>, RepoError> {
+        Ok(self.comments.get(&id).map(|comment| comment.clone()))
+    }
+
+    async fn save(&self, entity: Comment) -> Result<Comment, RepoError> {
+        self.comments.insert(entity.id, entity.clone());
+        Ok(entity)
+    }
+
+    async fn insert(&self, entity: Comment) -> Result<Comment, RepoError> {
+        match self.comments.entry(entity.id) {
+            Entry::Occupied(_) => Err(RepoError::Constraint("Comment already exists".to_string())),
+            Entry::Vacant(slot) => Ok(slot.insert(entity).clone()),
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        self.comments
+            .remove(&id)
+            .map(|_| ())
+            .ok_or(RepoError::NotFound)
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<Comment>, RepoError> {
+        let comments = self
+            .comments
+            .iter()
+            .map(|comment| comment.clone())
+            .collect();
+        page_of(comments, &request, |comment| comment.id, comment_sort_value)
+    }
+}
+
+#[async_trait]
+impl CommentRepository for InMemoryCommentRepository {
+    async fn find_page_by_post_id(
+        &self,
+        post_id: Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Comment>, RepoError> {
+        let comments = self
+            .comments
+            .iter()
+            .filter(|comment| comment.post_id == post_id)
+            .map(|comment| comment.clone())
+            .collect();
+        page_of(comments, &request, |comment| comment.id, comment_sort_value)
+    }
+}
+
+/// Attachment metadata in a `DashMap` keyed by id.
+#[derive(Default)]
+pub struct InMemoryAttachmentRepository {
+    attachments: DashMap<Uuid, Attachment>,
+}
+
+impl InMemoryAttachmentRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BaseRepository<Attachment, Uuid> for InMemoryAttachmentRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Attachment>, RepoError> {
+        Ok(self
+            .attachments
+            .get(&id)
+            .map(|attachment| attachment.clone()))
+    }
+
+    async fn save(&self, entity: Attachment) -> Result<Attachment, RepoError> {
+        self.attachments.insert(entity.id, entity.clone());
+        Ok(entity)
+    }
+
+    async fn insert(&self, entity: Attachment) -> Result<Attachment, RepoError> {
+        match self.attachments.entry(entity.id) {
+            Entry::Occupied(_) => Err(RepoError::Constraint(
+                "Attachment already exists".to_string(),
+            )),
+            Entry::Vacant(slot) => Ok(slot.insert(entity).clone()),
+        }
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        self.attachments
+            .remove(&id)
+            .map(|_| ())
+            .ok_or(RepoError::NotFound)
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<Attachment>, RepoError> {
+        let attachments = self
+            .attachments
+            .iter()
+            .map(|attachment| attachment.clone())
+            .collect();
+        page_of(
+            attachments,
+            &request,
+            |attachment| attachment.id,
+            attachment_sort_value,
+        )
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for InMemoryAttachmentRepository {
+    async fn list_by_post(&self, post_id: Uuid) -> Result<Vec<Attachment>, RepoError> {
+        let mut attachments: Vec<Attachment> = self
+            .attachments
+            .iter()
+            .filter(|attachment| attachment.post_id == Some(post_id))
+            .map(|attachment| attachment.clone())
+            .collect();
+        attachments.sort_by_key(|attachment| (attachment.created_at, attachment.id));
+        Ok(attachments)
+    }
+}
+
+/// Tags by name, and the names on each post.
+#[derive(Default)]
+pub struct InMemoryTagRepository {
+    tags: DashMap<String, Tag>,
+    post_tags: DashMap<Uuid, Vec<String>>,
+}
+
+impl InMemoryTagRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TagRepository for InMemoryTagRepository {
+    async fn tags_of(&self, post_id: Uuid) -> Result<Vec<Tag>, RepoError> {
+        let names = self
+            .post_tags
+            .get(&post_id)
+            .map(|names| names.clone())
+            .unwrap_or_default();
+        Ok(names
+            .iter()
+            .filter_map(|name| self.tags.get(name).map(|tag| tag.clone()))
+            .collect())
+    }
+
+    async fn set_tags(&self, post_id: Uuid, tags: Vec<Tag>) -> Result<Vec<Tag>, RepoError> {
+        let mut stored: Vec<Tag> = tags
+            .into_iter()
+            .map(|tag| self.tags.entry(tag.name.clone()).or_insert(tag).clone())
+            .collect();
+        stored.sort_by(|a, b| a.name.cmp(&b.name));
+        stored.dedup_by(|a, b| a.name == b.name);
+        let names = stored.iter().map(|tag| tag.name.clone()).collect();
+        self.post_tags.insert(post_id, names);
+        Ok(stored)
+    }
+
+    async fn post_ids_tagged(&self, name: &str) -> Result<Vec<Uuid>, RepoError> {
+        Ok(self
+            .post_tags
+            .iter()
+            .filter(|entry| entry.value().iter().any(|tagged| tagged == name))
+            .map(|entry| *entry.key())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apex_core::domain::Sort;
+
+    fn email(address: &str) -> Email {
+        Email::parse(address).unwrap()
+    }
+
+    fn user(address: &str) -> User {
+        User::new(email(address), "hash".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_emails_stay_unique_and_indexed() {
+        let repo = InMemoryUserRepository::new();
+        let alice = repo.insert(user("alice@example.com")).await.unwrap();
+
+        assert!(matches!(
+            repo.insert(user("alice@example.com")).await,
+            Err(RepoError::Constraint(_))
+        ));
+        assert!(matches!(
+            repo.insert(alice.clone()).await,
+            Err(RepoError::Constraint(_))
+        ));
+
+        let mut renamed = alice.clone();
+        renamed.email = email("alice@example.org");
+        repo.save(renamed).await.unwrap();
+        assert!(
+            repo.find_by_email(&email("alice@example.com"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            repo.find_by_email(&email("alice@example.org"))
+                .await
+                .unwrap()
+                .map(|u| u.id),
+            Some(alice.id)
+        );
+
+        // The old address is free again
+        repo.insert(user("alice@example.com")).await.unwrap();
+        repo.delete(alice.id).await.unwrap();
+        assert!(matches!(
+            repo.delete(alice.id).await,
+            Err(RepoError::NotFound)
+        ));
+        assert!(
+            repo.find_by_email(&email("alice@example.org"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_users_page_and_sort_like_postgres() {
+        let repo = InMemoryUserRepository::new();
+        for email in ["c@example.com", "a@example.com", "b@example.com"] {
+            repo.insert(user(email)).await.unwrap();
+        }
+
+        let request = PageRequest::new(1, 1).sorted_by(Sort::desc("email"));
+        let page = repo.find_page(request).await.unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items[0].email, "b@example.com");
+
+        let unknown = PageRequest::default().sorted_by(Sort::asc("password"));
+        assert!(matches!(
+            repo.find_page(unknown).await,
+            Err(RepoError::Query(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_posts_filter_like_sql() {
+        let repo = InMemoryPostRepository::new();
+        let author = Uuid::new_v4();
+        let mut published = Post::new(author, "Published".to_string(), "Body".to_string());
+        published.publish(Utc::now());
+        let draft = Post::new(author, "Draft".to_string(), "Body".to_string());
+        let other = Post::new(Uuid::new_v4(), "Other".to_string(), "Body".to_string());
+        for post in [published, draft, other] {
+            repo.insert(post).await.unwrap();
+        }
+
+        let titles = |filter: Filter| {
+            let request = PageRequest::default()
+                .sorted_by(Sort::asc("title"))
+                .filtered_by(filter);
+            let repo = &repo;
+            async move {
+                let page = repo.find_page(request).await.unwrap();
+                assert_eq!(page.total, page.items.len() as u64);
+                page.items
+                    .into_iter()
+                    .map(|post| post.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            titles(Filter::eq("user_id", author)).await,
+            ["Draft", "Published"]
+        );
+        assert_eq!(
+            titles(Filter::eq("user_id", author).and(Filter::is_null("published_at"))).await,
+            ["Draft"]
+        );
+        // An unset field matches no comparison, not even `ne`
+        assert_eq!(
+            titles(Filter::ne("published_at", Utc::now())).await,
+            ["Published"]
+        );
+        assert_eq!(
+            titles(Filter::is_in("title", ["Other", "Missing"])).await,
+            ["Other"]
+        );
+        assert!(
+            titles(Filter::is_in("id", Vec::<Uuid>::new()))
+                .await
+                .is_empty()
+        );
+        assert!(titles(Filter::Or(Vec::new())).await.is_empty());
+
+        let mismatched = PageRequest::default().filtered_by(Filter::eq("title", 1));
+        assert!(matches!(
+            repo.find_page(mismatched).await,
+            Err(RepoError::Query(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_saving_a_post_keeps_its_views_and_checks_versions() {
+        let repo = InMemoryPostRepository::new();
+        let post = Post::new(Uuid::new_v4(), "Title".to_string(), "Body".to_string());
+
+        repo.save_if_version(post.clone(), None).await.unwrap();
+        assert!(matches!(
+            repo.save_if_version(post.clone(), None).await,
+            Err(RepoError::Conflict(_))
+        ));
+        repo.add_views(vec![(post.id, 5), (Uuid::new_v4(), 1)])
+            .await
+            .unwrap();
+
+        let mut edited = post.clone();
+        edited.title = "Edited".to_string();
+        edited.version = post.version + 1;
+        assert!(matches!(
+            repo.save_if_version(edited.clone(), Some(post.version + 7))
+                .await,
+            Err(RepoError::Conflict(_))
+        ));
+        repo.save_if_version(edited, Some(post.version))
+            .await
+            .unwrap();
+
+        let stored = repo.save(post.clone()).await.unwrap();
+        assert_eq!(stored.view_count, 5);
+        assert_eq!(
+            repo.find_by_id(post.id).await.unwrap().unwrap().view_count,
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keyset_pages_run_newest_first_without_overlap() {
+        let repo = InMemoryPostRepository::new();
+        let (author, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+        let mut posts = Vec::new();
+        // Two posts in the same instant: the id breaks the tie
+        for (minutes, user_id) in [
+            (0, author),
+            (1, author),
+            (1, author),
+            (2, other),
+            (3, author),
+        ] {
+            let mut post = Post::new(user_id, "Title".to_string(), "Body".to_string());
+            post.created_at = now + chrono::Duration::minutes(minutes);
+            posts.push(repo.save(post).await.unwrap());
+        }
+        let mut deleted = posts[4].clone();
+        deleted.mark_deleted();
+        repo.save(deleted).await.unwrap();
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = repo.find_by_user_id_after(author, cursor, 2).await.unwrap();
+            let Some(last) = page.last() else { break };
+            cursor = Some(PageCursor::new(last.created_at, last.id));
+            seen.extend(page.iter().map(|post| (post.created_at, post.id)));
+        }
+
+        let mut expected: Vec<_> = posts[..3]
+            .iter()
+            .map(|post| (post.created_at, post.id))
+            .collect();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test]
+    async fn test_saves_record_who_created_and_changed_a_post() {
+        let repo = InMemoryPostRepository::new();
+        let (author, editor) = (Uuid::new_v4(), Uuid::new_v4());
+        let acting_as = |user_id| {
+            let mut context = apex_core::RequestContext::new("req");
+            context.user_id = Some(user_id);
+            context
+        };
+
+        let post = Post::new(author, "Title".to_string(), "Body".to_string());
+        let created = context::scope(acting_as(author), repo.save(post.clone()))
+            .await
+            .unwrap();
+        assert_eq!(
+            (created.created_by, created.updated_by),
+            (Some(author), Some(author))
+        );
+
+        // A copy that never learned its creator doesn't erase it
+        let edited = context::scope(acting_as(editor), repo.save(post))
+            .await
+            .unwrap();
+        assert_eq!(
+            (edited.created_by, edited.updated_by),
+            (Some(author), Some(editor))
+        );
+
+        // Outside a request, the last editor stays
+        let saved = repo.save(edited).await.unwrap();
+        assert_eq!(saved.updated_by, Some(editor));
+    }
+
+    #[tokio::test]
+    async fn test_changes_resume_after_the_cursor() {
+        let repo = InMemoryPostRepository::new();
+        let user_id = Uuid::new_v4();
+        let mut deleted = Post::new(user_id, "Gone".to_string(), String::new());
+        deleted.deleted_at = Some(Utc::now());
+        let kept = Post::new(user_id, "Kept".to_string(), String::new());
+        repo.save(deleted.clone()).await.unwrap();
+        repo.save(kept.clone()).await.unwrap();
+        repo.save(Post::new(
+            Uuid::new_v4(),
+            "Other".to_string(),
+            String::new(),
+        ))
+        .await
+        .unwrap();
+
+        // Deleted posts are changes too, but not listed
+        let changes = repo.list_changes(user_id, None, 10).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(repo.find_by_user_id(user_id).await.unwrap().len(), 1);
+
+        let rest = repo
+            .list_changes(user_id, Some(SyncCursor::after(&changes[0])), 10)
+            .await
+            .unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].id, changes[1].id);
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_shared_by_name() {
+        let repo = InMemoryTagRepository::new();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let tag = |name| Tag::new(name).unwrap();
+
+        let stored = repo
+            .set_tags(first, vec![tag("web"), tag("rust")])
+            .await
+            .unwrap();
+        let rust = stored[0].clone();
+        assert_eq!(rust.name, "rust");
+        // A new tag of the same name is the stored one
+        assert_eq!(
+            repo.set_tags(second, vec![tag("rust")]).await.unwrap(),
+            [rust]
+        );
+
+        let mut tagged = repo.post_ids_tagged("rust").await.unwrap();
+        tagged.sort();
+        let mut both = vec![first, second];
+        both.sort();
+        assert_eq!(tagged, both);
+
+        repo.set_tags(first, Vec::new()).await.unwrap();
+        assert!(repo.tags_of(first).await.unwrap().is_empty());
+        assert_eq!(repo.post_ids_tagged("rust").await.unwrap(), [second]);
+    }
+}
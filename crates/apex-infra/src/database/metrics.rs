@@ -0,0 +1,288 @@
+//! Query timings, from SeaORM's metric callback.
+//!
+//! Every statement run on a pool of [`super::DatabaseConnections`] is
+//! counted by database, operation and table, its duration added to a
+//! histogram, and statements slower than the threshold logged at WARN with
+//! their SQL. Statements carry placeholders, never the values bound to them,
+//! so the log holds no personal data.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sea_orm::DbConn;
+
+/// Upper bounds of the duration histogram's buckets, in seconds.
+const BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Characters of a slow statement's SQL kept in the log.
+const SUMMARY_LENGTH: usize = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct QueryKey {
+    database: String,
+    operation: &'static str,
+    table: String,
+}
+
+#[derive(Default)]
+struct QuerySeries {
+    succeeded: u64,
+    failed: u64,
+    /// Observations per bucket, not cumulative; the last counts those over
+    /// every bound.
+    buckets: [u64; BUCKETS.len() + 1],
+    sum: f64,
+}
+
+/// Query counts and latencies, rendered for `/metrics`.
+pub struct QueryMetrics {
+    slow_threshold: Duration,
+    series: Mutex<BTreeMap<QueryKey, QuerySeries>>,
+}
+
+impl QueryMetrics {
+    /// Log statements taking `slow_threshold` or longer; zero logs none.
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self {
+            slow_threshold,
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Report every statement `conn` runs as on `database`.
+    pub(crate) fn instrument(self: &Arc<Self>, conn: &mut DbConn, database: &str) {
+        let metrics = self.clone();
+        let database = database.to_string();
+        conn.set_metric_callback(move |info| {
+            metrics.record(&database, &info.statement.sql, info.elapsed, info.failed)
+        });
+    }
+
+    /// Count a statement that took `elapsed`.
+    pub fn record(&self, database: &str, sql: &str, elapsed: Duration, failed: bool) {
+        let (operation, table) = classify(sql);
+        if !self.slow_threshold.is_zero() && elapsed >= self.slow_threshold {
+            tracing::warn!(
+                database,
+                operation,
+                table = %table,
+                elapsed_ms = elapsed.as_millis() as u64,
+                failed,
+                statement = %summarize(sql),
+                "Slow query"
+            );
+        }
+
+        let key = QueryKey {
+            database: database.to_string(),
+            operation,
+            table,
+        };
+        let seconds = elapsed.as_secs_f64();
+        let bucket = BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(BUCKETS.len());
+
+        let mut series = self.series.lock().unwrap();
+        let series = series.entry(key).or_default();
+        if failed {
+            series.failed += 1;
+        } else {
+            series.succeeded += 1;
+        }
+        series.buckets[bucket] += 1;
+        series.sum += seconds;
+    }
+
+    /// The query families in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str(
+            "# HELP db_queries_total Queries by database, operation, table and outcome.\n",
+        );
+        out.push_str("# TYPE db_queries_total counter\n");
+        for (key, sample) in series.iter() {
+            for (outcome, count) in [("ok", sample.succeeded), ("error", sample.failed)] {
+                if count > 0 {
+                    writeln!(
+                        out,
+                        "db_queries_total{{{},outcome=\"{}\"}} {}",
+                        labels(key),
+                        outcome,
+                        count
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        out.push_str(
+            "# HELP db_query_duration_seconds Query durations by database, operation and table.\n",
+        );
+        out.push_str("# TYPE db_query_duration_seconds histogram\n");
+        for (key, sample) in series.iter() {
+            let labels = labels(key);
+            let mut cumulative = 0;
+            for (bound, count) in BUCKETS.iter().zip(&sample.buckets) {
+                cumulative += count;
+                writeln!(
+                    out,
+                    "db_query_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, cumulative
+                )
+                .unwrap();
+            }
+            let total = sample.succeeded + sample.failed;
+            writeln!(
+                out,
+                "db_query_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, total
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "db_query_duration_seconds_sum{{{}}} {}",
+                labels, sample.sum
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "db_query_duration_seconds_count{{{}}} {}",
+                labels, total
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+fn labels(key: &QueryKey) -> String {
+    format!(
+        "database=\"{}\",operation=\"{}\",table=\"{}\"",
+        key.database, key.operation, key.table
+    )
+}
+
+/// The statement's operation and the first table it names, `other` when it
+/// names none. Both come from the schema and the query builder, never from
+/// request data, so they are safe labels.
+fn classify(sql: &str) -> (&'static str, String) {
+    let mut words = sql.split_whitespace();
+    let operation = match words.next().map(str::to_ascii_uppercase).as_deref() {
+        Some("SELECT") => "select",
+        Some("INSERT") => "insert",
+        Some("UPDATE") => "update",
+        Some("DELETE") => "delete",
+        _ => "other",
+    };
+
+    let mut table = None;
+    let mut words = sql.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        let names_table = ["FROM", "INTO", "UPDATE"]
+            .iter()
+            .any(|keyword| word.eq_ignore_ascii_case(keyword));
+        if let Some(next) = words.peek().filter(|_| names_table)
+            && !next.starts_with('(')
+        {
+            table = Some(identifier(next));
+            break;
+        }
+    }
+    (operation, table.unwrap_or_else(|| "other".to_string()))
+}
+
+/// `"public"."posts"` as `posts`.
+fn identifier(word: &str) -> String {
+    let name = word.rsplit('.').next().unwrap_or(word);
+    name.trim_matches(|c: char| c == '"' || c == '`' || c == '(' || c == ')' || c == ',')
+        .to_string()
+}
+
+/// The statement on one line, cut to [`SUMMARY_LENGTH`] characters.
+fn summarize(sql: &str) -> String {
+    let line = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(SUMMARY_LENGTH) {
+        Some((cut, _)) => format!("{}...", &line[..cut]),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statements_are_classified_by_operation_and_table() {
+        let cases = [
+            (
+                r#"SELECT "posts"."id" FROM "posts" WHERE "posts"."id" = $1"#,
+                ("select", "posts"),
+            ),
+            (
+                r#"SELECT COUNT(*) AS num_items FROM (SELECT "id" FROM "users") AS "sub""#,
+                ("select", "users"),
+            ),
+            (
+                r#"INSERT INTO "tenant_1"."posts" ("id") VALUES ($1)"#,
+                ("insert", "posts"),
+            ),
+            (r#"UPDATE `posts` SET `title` = ?"#, ("update", "posts")),
+            (
+                r#"delete from "posts" where "id" in ($1, $2)"#,
+                ("delete", "posts"),
+            ),
+            ("SET search_path TO tenant_1", ("other", "other")),
+        ];
+        for (sql, (operation, table)) in cases {
+            assert_eq!(classify(sql), (operation, table.to_string()), "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_render_counts_outcomes_and_fills_buckets() {
+        let metrics = QueryMetrics::new(Duration::ZERO);
+        let sql = r#"SELECT "id" FROM "posts""#;
+        metrics.record("main", sql, Duration::from_millis(3), false);
+        metrics.record("main", sql, Duration::from_millis(30), false);
+        metrics.record("main", sql, Duration::from_secs(20), true);
+
+        let out = metrics.render();
+        let labels = r#"database="main",operation="select",table="posts""#;
+        assert!(out.contains(&format!("db_queries_total{{{labels},outcome=\"ok\"}} 2")));
+        assert!(out.contains(&format!("db_queries_total{{{labels},outcome=\"error\"}} 1")));
+        assert!(out.contains(&format!(
+            "db_query_duration_seconds_bucket{{{labels},le=\"0.001\"}} 0"
+        )));
+        assert!(out.contains(&format!(
+            "db_query_duration_seconds_bucket{{{labels},le=\"0.005\"}} 1"
+        )));
+        assert!(out.contains(&format!(
+            "db_query_duration_seconds_bucket{{{labels},le=\"0.05\"}} 2"
+        )));
+        assert!(out.contains(&format!(
+            "db_query_duration_seconds_bucket{{{labels},le=\"10\"}} 2"
+        )));
+        assert!(out.contains(&format!(
+            "db_query_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 3"
+        )));
+        assert!(out.contains(&format!("db_query_duration_seconds_count{{{labels}}} 3")));
+    }
+
+    #[test]
+    fn test_summary_is_one_bounded_line() {
+        assert_eq!(
+            summarize("SELECT *\n  FROM \"posts\""),
+            "SELECT * FROM \"posts\""
+        );
+        let long = format!("SELECT {}", "\"column\", ".repeat(100));
+        assert_eq!(summarize(&long).chars().count(), SUMMARY_LENGTH + 3);
+    }
+}
@@ -1,6 +1,13 @@
 //! Database connection management.
 
 mod connections;
+#[cfg(feature = "postgres")]
+mod console;
+#[cfg(any(feature = "postgres", feature = "mongo"))]
+pub(crate) mod encrypted;
+mod memory;
+#[cfg(feature = "postgres")]
+mod metrics;
 
 #[cfg(feature = "postgres")]
 mod postgres_base;
@@ -9,10 +16,31 @@ pub mod postgres_repo;
 #[cfg(feature = "postgres")]
 pub mod entity;
 
-pub use connections::{DatabaseConfig, DatabaseConnections, NamedConnection, SecondaryDbConfig};
+pub use connections::{
+    DatabaseConfig, DatabaseConnections, NamedConnection, SecondaryDbConfig, TenancyMode,
+};
+#[cfg(feature = "postgres")]
+pub use console::{ConsoleRows, SqlConsole, SqlConsoleConfig, SqlConsoleError};
+#[cfg(any(feature = "postgres", feature = "mongo"))]
+pub use encrypted::{ColumnCipher, ColumnEncryptionError, EncryptedString};
+pub use memory::{
+    InMemoryAttachmentRepository, InMemoryCommentRepository, InMemoryPasswordHistoryRepository,
+    InMemoryPostRepository, InMemoryTagRepository, InMemoryUserRepository,
+};
+#[cfg(feature = "postgres")]
+pub use metrics::QueryMetrics;
 
 #[cfg(feature = "postgres")]
-pub use postgres_repo::{PostgresPostRepository, PostgresUserRepository};
+pub use postgres_repo::{
+    PostgresAnnouncementRepository, PostgresAttachmentRepository, PostgresCommentRepository,
+    PostgresConsentRepository, PostgresCustomDomainRepository, PostgresInvitationRepository,
+    PostgresLegalHoldRepository, PostgresMembershipRepository, PostgresOAuthClientRepository,
+    PostgresOrganizationRepository, PostgresPasswordHistoryRepository,
+    PostgresPendingOperationRepository, PostgresPlanRepository, PostgresPostRepository,
+    PostgresSettingsRepository, PostgresStorageUsageRepository, PostgresSubscriptionRepository,
+    PostgresTagRepository, PostgresUsageRepository, PostgresUserRepository,
+    PostgresWebhookDeliveryRepository,
+};
 
 #[cfg(feature = "postgres")]
 #[cfg(test)]
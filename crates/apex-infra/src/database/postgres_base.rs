@@ -1,19 +1,28 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use sea_orm::sea_query::{Alias, Expr, Func, IntoIden, OnConflict, SimpleExpr};
 use sea_orm::{
-    ActiveModelTrait, DbConn, EntityTrait, IntoActiveModel, PrimaryKeyTrait, TryIntoModel,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbBackend, DbConn, EntityTrait,
+    IdenStatic, IntoActiveModel, Iterable, Order, PaginatorTrait, PrimaryKeyToColumn,
+    PrimaryKeyTrait, QueryFilter, QueryOrder, QuerySelect, Select, TryIntoModel,
 };
 
+use apex_core::domain::{CompareOp, Filter, FilterValue, Page, PageRequest, SortDirection};
 use apex_core::error::RepoError;
 use apex_core::ports::BaseRepository;
 
+/// Bind parameters a bulk statement may use, under the limits of Postgres
+/// and MySQL (65535) and SQLite (32766).
+const MAX_BULK_PARAMS: usize = 30_000;
+
 /// Generic PostgreSQL repository implementation.
 pub struct PostgresBaseRepository<E>
 where
     E: EntityTrait,
 {
-    pub(crate) db: DbConn,
+    pub(crate) db: Arc<DbConn>,
     _entity: PhantomData<E>,
 }
 
@@ -21,7 +30,7 @@ impl<E> PostgresBaseRepository<E>
 where
     E: EntityTrait,
 {
-    pub fn new(db: DbConn) -> Self {
+    pub fn new(db: Arc<DbConn>) -> Self {
         Self {
             db,
             _entity: PhantomData,
@@ -41,7 +50,7 @@ where
 {
     async fn find_by_id(&self, id: ID) -> Result<Option<T>, RepoError> {
         let result = E::find_by_id(id)
-            .one(&self.db)
+            .one(self.db.as_ref())
             .await
             .map_err(|e| RepoError::Query(e.to_string()))?;
 
@@ -49,32 +58,33 @@ where
     }
 
     async fn save(&self, entity: T) -> Result<T, RepoError> {
-        // but for Postgres we can use OnConflict.
+        // IDs are generated in the domain, so the primary key is always set and
+        // `ActiveModel::save` would only ever UPDATE. Upsert on the primary key instead.
+        let active_model: E::ActiveModel = entity.into();
+        let on_conflict = upsert_on_primary_key(&active_model);
+
+        let model = E::insert(active_model)
+            .on_conflict(on_conflict)
+            .exec_with_returning(self.db.as_ref())
+            .await
+            .map_err(map_db_error)?;
+
+        Ok(model.into())
+    }
 
+    async fn insert(&self, entity: T) -> Result<T, RepoError> {
         let active_model: E::ActiveModel = entity.into();
-        let result = active_model.save(&self.db).await.map_err(|e| {
-            let err_str = e.to_string();
-            if err_str.contains("duplicate") || err_str.contains("unique") {
-                RepoError::Constraint("Entity already exists".to_string())
-            } else {
-                RepoError::Query(err_str)
-            }
-        })?;
-
-        // We need to convert back to ActiveModel to get the Model to get T
-        // Wait, save returns ActiveModel.
-        // And we need to convert ActiveModel back to Model?
-        // SeaORM ActiveModel has try_into_model()?
-
-        let model = result
-            .try_into_model()
-            .map_err(|e| RepoError::Query(e.to_string()))?;
+        let model = E::insert(active_model)
+            .exec_with_returning(self.db.as_ref())
+            .await
+            .map_err(map_db_error)?;
+
         Ok(model.into())
     }
 
     async fn delete(&self, id: ID) -> Result<(), RepoError> {
         let result = E::delete_by_id(id)
-            .exec(&self.db)
+            .exec(self.db.as_ref())
             .await
             .map_err(|e| RepoError::Query(e.to_string()))?;
 
@@ -84,4 +94,201 @@ where
 
         Ok(())
     }
+
+    async fn save_many(&self, entities: Vec<T>) -> Result<(), RepoError> {
+        let models: Vec<E::ActiveModel> = entities.into_iter().map(Into::into).collect();
+        // Every model comes from the same conversion, so the first sets the
+        // same columns as the rest
+        let Some(first) = models.first() else {
+            return Ok(());
+        };
+        let on_conflict = upsert_on_primary_key(first);
+        let batch_size = (MAX_BULK_PARAMS / E::Column::iter().count()).max(1);
+
+        let mut models = models.into_iter().peekable();
+        while models.peek().is_some() {
+            let batch: Vec<E::ActiveModel> = models.by_ref().take(batch_size).collect();
+            E::insert_many(batch)
+                .on_conflict(on_conflict.clone())
+                .exec_without_returning(self.db.as_ref())
+                .await
+                .map_err(map_db_error)?;
+        }
+        Ok(())
+    }
+
+    async fn delete_many(&self, ids: Vec<ID>) -> Result<u64, RepoError> {
+        let Some(key) = E::PrimaryKey::iter().next() else {
+            return Ok(0);
+        };
+        let mut deleted = 0;
+        for batch in ids.chunks(MAX_BULK_PARAMS) {
+            let result = E::delete_many()
+                .filter(key.into_column().is_in(batch.iter().copied()))
+                .exec(self.db.as_ref())
+                .await
+                .map_err(|e| RepoError::Query(e.to_string()))?;
+            deleted += result.rows_affected;
+        }
+        Ok(deleted)
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<T>, RepoError> {
+        fetch_page(self.db.as_ref(), E::find(), &request).await
+    }
+}
+
+/// Upsert of `active_model` on its primary key. Columns the conversion
+/// leaves unset (e.g. counters written elsewhere) keep their stored value,
+/// and so does `created_by`: an entity is created once.
+fn upsert_on_primary_key<A>(active_model: &A) -> OnConflict
+where
+    A: ActiveModelTrait,
+{
+    let pk_columns: Vec<<A::Entity as EntityTrait>::Column> =
+        <A::Entity as EntityTrait>::PrimaryKey::iter()
+            .map(|key| key.into_column())
+            .collect();
+    let update_columns = <A::Entity as EntityTrait>::Column::iter()
+        .filter(|column| !pk_columns.iter().any(|pk| pk.as_str() == column.as_str()))
+        .filter(|column| column.as_str() != "created_by")
+        .filter(|column| active_model.get(*column).is_set());
+    OnConflict::columns(pk_columns.clone())
+        .update_columns(update_columns)
+        .to_owned()
+}
+
+/// The page of `select` that `request` asks for, counting the rows of the
+/// whole selection for the total.
+pub(crate) async fn fetch_page<E, T>(
+    db: &DbConn,
+    select: Select<E>,
+    request: &PageRequest,
+) -> Result<Page<T>, RepoError>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    T: From<E::Model>,
+{
+    let order = match &request.sort {
+        Some(sort) => {
+            let column: E::Column = sort
+                .field
+                .parse()
+                .map_err(|_| RepoError::Query(format!("Unknown sort field: {}", sort.field)))?;
+            let order = match sort.direction {
+                SortDirection::Asc => Order::Asc,
+                SortDirection::Desc => Order::Desc,
+            };
+            Some((column, order))
+        }
+        None => None,
+    };
+
+    let select = match &request.filter {
+        Some(filter) => select.filter(condition::<E>(filter)?),
+        None => select,
+    };
+
+    let total = select
+        .clone()
+        .count(db)
+        .await
+        .map_err(|e| RepoError::Query(e.to_string()))?;
+
+    let mut select = select;
+    if let Some((column, order)) = order {
+        select = select.order_by(column, order);
+    }
+    // Primary key last, so rows with equal sort values keep their order
+    // from one page to the next
+    for key in E::PrimaryKey::iter() {
+        select = select.order_by(key.into_column(), Order::Asc);
+    }
+
+    let items = select
+        .offset(request.offset)
+        .limit(request.limit)
+        .all(db)
+        .await
+        .map_err(|e| RepoError::Query(e.to_string()))?;
+
+    Ok(Page {
+        items: items.into_iter().map(Into::into).collect(),
+        total,
+        offset: request.offset,
+        limit: request.limit,
+    })
+}
+
+/// `filter` as a condition on the columns of `E`, which it must name by
+/// their stored names.
+pub(crate) fn condition<E: EntityTrait>(filter: &Filter) -> Result<Condition, RepoError> {
+    let column = |field: &str| -> Result<E::Column, RepoError> {
+        field
+            .parse()
+            .map_err(|_| RepoError::Query(format!("Unknown filter field: {}", field)))
+    };
+    Ok(match filter {
+        Filter::Compare { field, op, value } => {
+            let column = column(field)?;
+            let value = sql_value(value);
+            Condition::all().add(match op {
+                CompareOp::Eq => column.eq(value),
+                CompareOp::Ne => column.ne(value),
+                CompareOp::Lt => column.lt(value),
+                CompareOp::Lte => column.lte(value),
+                CompareOp::Gt => column.gt(value),
+                CompareOp::Gte => column.gte(value),
+            })
+        }
+        // An empty list renders as FALSE, like an empty OR
+        Filter::In { values, .. } if values.is_empty() => Condition::any(),
+        Filter::In { field, values } => {
+            Condition::all().add(column(field)?.is_in(values.iter().map(sql_value)))
+        }
+        Filter::Null { field, null: true } => Condition::all().add(column(field)?.is_null()),
+        Filter::Null { field, null: false } => Condition::all().add(column(field)?.is_not_null()),
+        Filter::And(filters) => filters
+            .iter()
+            .try_fold(Condition::all(), |all, f| Ok(all.add(condition::<E>(f)?)))?,
+        Filter::Or(filters) => filters
+            .iter()
+            .try_fold(Condition::any(), |any, f| Ok(any.add(condition::<E>(f)?)))?,
+    })
+}
+
+fn sql_value(value: &FilterValue) -> sea_orm::Value {
+    match value {
+        FilterValue::Uuid(id) => sea_orm::Value::from(*id),
+        FilterValue::Text(text) => sea_orm::Value::from(text.clone()),
+        FilterValue::Number(number) => sea_orm::Value::from(*number),
+        FilterValue::Time(time) => {
+            sea_orm::Value::from(chrono::DateTime::<chrono::FixedOffset>::from(*time))
+        }
+    }
+}
+
+/// The value a conflicting insert proposed for `column`, for the update of
+/// an upsert: `excluded.column`, or `VALUES(column)` on MySQL.
+pub(crate) fn excluded(db: &DbConn, column: impl IntoIden + 'static) -> SimpleExpr {
+    match db.get_database_backend() {
+        DbBackend::MySql => Func::cust(Alias::new("VALUES"))
+            .arg(Expr::col(column))
+            .into(),
+        _ => Expr::col((Alias::new("excluded"), column)).into(),
+    }
+}
+
+/// Repository error for a failed write, telling unique constraint
+/// violations apart: Postgres reports a "duplicate key", MySQL and MariaDB
+/// a "Duplicate entry" and SQLite a "UNIQUE constraint failed".
+pub(crate) fn map_db_error(e: sea_orm::DbErr) -> RepoError {
+    let err_str = e.to_string();
+    let lowercase = err_str.to_lowercase();
+    if lowercase.contains("duplicate") || lowercase.contains("unique") {
+        RepoError::Constraint("Entity already exists".to_string())
+    } else {
+        RepoError::Query(err_str)
+    }
 }
@@ -1,15 +1,54 @@
 //! PostgreSQL repository implementations.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::sea_query::{Expr, OnConflict, Query};
+use sea_orm::{
+    ColumnTrait, Condition, DbConn, EntityTrait, NotSet, QueryFilter, QueryOrder, QuerySelect, Set,
+    TransactionTrait,
+};
 
-use apex_core::domain::{Post, User};
+use apex_core::domain::{
+    Announcement, ApprovalStatus, Attachment, Comment, CustomDomain, Email, Invitation,
+    LegalHoldChange, Membership, OAuthClient, Organization, Page, PageCursor, PageRequest,
+    PendingOperation, Plan, PolicyAcceptance, Post, SettingsScope, Subscription,
+    SubscriptionStatus, SyncCursor, Tag, UsageTotal, User, WebhookDelivery,
+};
 use apex_core::error::RepoError;
-use apex_core::ports::{PostRepository, UserRepository};
+use apex_core::pii::Sensitive;
+use apex_core::ports::{
+    AnnouncementRepository, AttachmentRepository, CommentRepository, ConsentRepository,
+    CustomDomainRepository, InvitationRepository, LegalHoldRepository, MembershipRepository,
+    OAuthClientRepository, OrganizationRepository, PasswordHistoryRepository,
+    PendingOperationRepository, PlanRepository, PostRepository, SettingsRepository,
+    StorageUsageRepository, SubscriptionRepository, TagRepository, UsageRepository, UserRepository,
+    WebhookDeliveryRepository,
+};
 
+use super::entity::account_plan::{self, Entity as AccountPlanEntity};
+use super::entity::announcement::{self, Entity as AnnouncementEntity};
+use super::entity::attachment::{self, Entity as AttachmentEntity};
+use super::entity::comment::{self, Entity as CommentEntity};
+use super::entity::custom_domain::{self, Entity as CustomDomainEntity};
+use super::entity::invitation::{self, Entity as InvitationEntity};
+use super::entity::legal_hold_change::{self, Entity as LegalHoldChangeEntity};
+use super::entity::membership::{self, Entity as MembershipEntity};
+use super::entity::oauth_client::{self, Entity as OAuthClientEntity};
+use super::entity::organization::{self, Entity as OrganizationEntity};
+use super::entity::password_history::{self, Entity as PasswordHistoryEntity};
+use super::entity::pending_operation::{self, Entity as PendingOperationEntity};
+use super::entity::policy_acceptance::{self, Entity as PolicyAcceptanceEntity};
 use super::entity::post::{self, Entity as PostEntity};
+use super::entity::post_tag::{self, Entity as PostTagEntity};
+use super::entity::setting::{self, Entity as SettingEntity};
+use super::entity::storage_usage::{self, Entity as StorageUsageEntity};
+use super::entity::subscription::{self, Entity as SubscriptionEntity};
+use super::entity::tag::{self, Entity as TagEntity};
+use super::entity::usage_rollup::{self, Entity as UsageRollupEntity};
 use super::entity::user::{self, Entity as UserEntity};
-use super::postgres_base::PostgresBaseRepository;
+use super::entity::webhook_delivery::{self, Entity as WebhookDeliveryEntity};
+use super::postgres_base::{PostgresBaseRepository, excluded, fetch_page};
 
 /// PostgreSQL user repository.
 pub type PostgresUserRepository = PostgresBaseRepository<UserEntity>;
@@ -17,26 +56,44 @@ pub type PostgresUserRepository = PostgresBaseRepository<UserEntity>;
 /// PostgreSQL post repository.
 pub type PostgresPostRepository = PostgresBaseRepository<PostEntity>;
 
+/// PostgreSQL attachment metadata repository.
+pub type PostgresAttachmentRepository = PostgresBaseRepository<AttachmentEntity>;
+
+/// PostgreSQL comment repository.
+pub type PostgresCommentRepository = PostgresBaseRepository<CommentEntity>;
+
+/// PostgreSQL organization repository.
+pub type PostgresOrganizationRepository = PostgresBaseRepository<OrganizationEntity>;
+
+/// PostgreSQL membership repository.
+pub type PostgresMembershipRepository = PostgresBaseRepository<MembershipEntity>;
+
+/// PostgreSQL invitation repository.
+pub type PostgresInvitationRepository = PostgresBaseRepository<InvitationEntity>;
+
+/// PostgreSQL webhook delivery repository.
+pub type PostgresWebhookDeliveryRepository = PostgresBaseRepository<WebhookDeliveryEntity>;
+
+/// PostgreSQL announcement repository.
+pub type PostgresAnnouncementRepository = PostgresBaseRepository<AnnouncementEntity>;
+
+/// PostgreSQL OAuth client repository.
+pub type PostgresOAuthClientRepository = PostgresBaseRepository<OAuthClientEntity>;
+
+/// PostgreSQL custom domain repository.
+pub type PostgresCustomDomainRepository = PostgresBaseRepository<CustomDomainEntity>;
+
+/// PostgreSQL pending admin operation repository.
+pub type PostgresPendingOperationRepository = PostgresBaseRepository<PendingOperationEntity>;
+
 #[async_trait]
 impl UserRepository for PostgresUserRepository {
-    async fn find_by_email(&self, email: &str) -> Result<Option<User>, RepoError> {
-        // Mask email for logging to avoid PII in logs
-        let masked = if let Some(at_pos) = email.find('@') {
-            let (local, domain) = email.split_at(at_pos);
-            let masked_local = if local.len() > 1 {
-                format!("{}***", &local[..1])
-            } else {
-                "***".to_string()
-            };
-            format!("{}{}", masked_local, domain)
-        } else {
-            "***".to_string()
-        };
-        tracing::debug!(user_email = %masked, "Finding user by email");
+    async fn find_by_email(&self, email: &Email) -> Result<Option<User>, RepoError> {
+        tracing::debug!(user_email = %Sensitive(email), "Finding user by email");
 
         let result = UserEntity::find()
-            .filter(user::Column::Email.eq(email))
-            .one(&self.db)
+            .filter(user::Column::Email.eq(email.as_str()))
+            .one(self.db.as_ref())
             .await
             .map_err(|e| RepoError::Query(e.to_string()))?;
 
@@ -49,10 +106,983 @@ impl PostRepository for PostgresPostRepository {
     async fn find_by_user_id(&self, user_id: uuid::Uuid) -> Result<Vec<Post>, RepoError> {
         let result = PostEntity::find()
             .filter(post::Column::UserId.eq(user_id))
-            .all(&self.db)
+            .filter(post::Column::DeletedAt.is_null())
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn find_page_by_user_id(
+        &self,
+        user_id: uuid::Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        let select = PostEntity::find()
+            .filter(post::Column::UserId.eq(user_id))
+            .filter(post::Column::DeletedAt.is_null());
+        fetch_page(self.db.as_ref(), select, &request).await
+    }
+
+    async fn find_by_organization_id(
+        &self,
+        organization_id: uuid::Uuid,
+    ) -> Result<Vec<Post>, RepoError> {
+        let result = PostEntity::find()
+            .filter(post::Column::OrganizationId.eq(organization_id))
+            .filter(post::Column::DeletedAt.is_null())
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn find_page_by_organization_id(
+        &self,
+        organization_id: uuid::Uuid,
+        user_id: Option<uuid::Uuid>,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        let mut select = PostEntity::find()
+            .filter(post::Column::OrganizationId.eq(organization_id))
+            .filter(post::Column::DeletedAt.is_null());
+        if let Some(user_id) = user_id {
+            select = select.filter(post::Column::UserId.eq(user_id));
+        }
+        fetch_page(self.db.as_ref(), select, &request).await
+    }
+
+    async fn find_by_user_id_after(
+        &self,
+        user_id: uuid::Uuid,
+        after: Option<PageCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let mut query = PostEntity::find()
+            .filter(post::Column::UserId.eq(user_id))
+            .filter(post::Column::DeletedAt.is_null());
+        if let Some(after) = after {
+            let created_at: chrono::DateTime<chrono::FixedOffset> = after.created_at.into();
+            query = query.filter(
+                Condition::any()
+                    .add(post::Column::CreatedAt.lt(created_at))
+                    .add(
+                        Condition::all()
+                            .add(post::Column::CreatedAt.eq(created_at))
+                            .add(post::Column::Id.lt(after.id)),
+                    ),
+            );
+        }
+
+        // Walks idx_posts_user_id_created_at backwards from the cursor
+        let result = query
+            .order_by_desc(post::Column::CreatedAt)
+            .order_by_desc(post::Column::Id)
+            .limit(limit)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn list_changes(
+        &self,
+        user_id: uuid::Uuid,
+        after: Option<SyncCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let mut query = PostEntity::find().filter(post::Column::UserId.eq(user_id));
+        if let Some(after) = after {
+            let updated_at: chrono::DateTime<chrono::FixedOffset> = after.updated_at.into();
+            query = query.filter(
+                Condition::any()
+                    .add(post::Column::UpdatedAt.gt(updated_at))
+                    .add(
+                        Condition::all()
+                            .add(post::Column::UpdatedAt.eq(updated_at))
+                            .add(post::Column::Id.gt(after.id)),
+                    ),
+            );
+        }
+
+        let result = query
+            .order_by_asc(post::Column::UpdatedAt)
+            .order_by_asc(post::Column::Id)
+            .limit(limit)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn save_if_version(
+        &self,
+        post: Post,
+        expected_version: Option<i64>,
+    ) -> Result<(), RepoError> {
+        let id = post.id;
+        let mut model: post::ActiveModel = post.into();
+
+        let rows_affected = match expected_version {
+            None => PostEntity::insert(model)
+                .on_conflict(OnConflict::column(post::Column::Id).do_nothing().to_owned())
+                .exec_without_returning(self.db.as_ref())
+                .await
+                .map_err(|e| RepoError::Query(e.to_string()))?,
+            Some(version) => {
+                // The creator is written once, by the insert
+                model.created_by = NotSet;
+                PostEntity::update_many()
+                    .set(model)
+                    .filter(post::Column::Id.eq(id))
+                    .filter(post::Column::Version.eq(version))
+                    .exec(self.db.as_ref())
+                    .await
+                    .map_err(|e| RepoError::Query(e.to_string()))?
+                    .rows_affected
+            }
+        };
+
+        if rows_affected != 1 {
+            return Err(RepoError::stale_version(id, expected_version));
+        }
+        Ok(())
+    }
+
+    async fn add_views(&self, views: Vec<(uuid::Uuid, i64)>) -> Result<(), RepoError> {
+        let Some(((first_id, first_views), rest)) = views.split_first() else {
+            return Ok(());
+        };
+
+        // One UPDATE for the batch: view_count + CASE id WHEN .. THEN .. END
+        let increment = rest
+            .iter()
+            .fold(
+                Expr::case(post::Column::Id.eq(*first_id), *first_views),
+                |case, (id, views)| case.case(post::Column::Id.eq(*id), *views),
+            )
+            .finally(0i64);
+
+        PostEntity::update_many()
+            .col_expr(
+                post::Column::ViewCount,
+                Expr::col(post::Column::ViewCount).add(increment),
+            )
+            .filter(post::Column::Id.is_in(views.iter().map(|(id, _)| *id)))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_published(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let now: chrono::DateTime<chrono::FixedOffset> = now.into();
+        let result = PostEntity::find()
+            .filter(post::Column::PublishedAt.lte(now))
+            .filter(post::Column::DeletedAt.is_null())
+            .order_by_desc(post::Column::PublishedAt)
+            .order_by_desc(post::Column::Id)
+            .limit(limit)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for PostgresAttachmentRepository {
+    async fn list_by_post(&self, post_id: uuid::Uuid) -> Result<Vec<Attachment>, RepoError> {
+        let result = AttachmentEntity::find()
+            .filter(attachment::Column::PostId.eq(post_id))
+            .order_by_asc(attachment::Column::CreatedAt)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait]
+impl CommentRepository for PostgresCommentRepository {
+    async fn find_page_by_post_id(
+        &self,
+        post_id: uuid::Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Comment>, RepoError> {
+        let select = CommentEntity::find().filter(comment::Column::PostId.eq(post_id));
+        fetch_page(self.db.as_ref(), select, &request).await
+    }
+}
+
+#[async_trait]
+impl OrganizationRepository for PostgresOrganizationRepository {
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<Organization>, RepoError> {
+        let result = OrganizationEntity::find()
+            .filter(organization::Column::Slug.eq(slug))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.map(Into::into))
+    }
+
+    async fn list_for_user(&self, user_id: uuid::Uuid) -> Result<Vec<Organization>, RepoError> {
+        let member_of = Query::select()
+            .column(membership::Column::OrganizationId)
+            .from(MembershipEntity)
+            .and_where(membership::Column::UserId.eq(user_id))
+            .to_owned();
+
+        let result = OrganizationEntity::find()
+            .filter(organization::Column::Id.in_subquery(member_of))
+            .order_by_asc(organization::Column::Name)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait]
+impl MembershipRepository for PostgresMembershipRepository {
+    async fn find_membership(
+        &self,
+        organization_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<Membership>, RepoError> {
+        let result = MembershipEntity::find()
+            .filter(membership::Column::OrganizationId.eq(organization_id))
+            .filter(membership::Column::UserId.eq(user_id))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.map(Into::into))
+    }
+
+    async fn list_by_organization(
+        &self,
+        organization_id: uuid::Uuid,
+    ) -> Result<Vec<Membership>, RepoError> {
+        let result = MembershipEntity::find()
+            .filter(membership::Column::OrganizationId.eq(organization_id))
+            .order_by_asc(membership::Column::CreatedAt)
+            .all(self.db.as_ref())
             .await
             .map_err(|e| RepoError::Query(e.to_string()))?;
 
         Ok(result.into_iter().map(Into::into).collect())
     }
 }
+
+#[async_trait]
+impl InvitationRepository for PostgresInvitationRepository {
+    async fn find_by_token(&self, token: &str) -> Result<Option<Invitation>, RepoError> {
+        let result = InvitationEntity::find()
+            .filter(invitation::Column::Token.eq(token))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.map(Into::into))
+    }
+}
+
+#[async_trait]
+impl WebhookDeliveryRepository for PostgresWebhookDeliveryRepository {
+    async fn list_recent(
+        &self,
+        failed_only: bool,
+        limit: u64,
+    ) -> Result<Vec<WebhookDelivery>, RepoError> {
+        let mut query = WebhookDeliveryEntity::find();
+        if failed_only {
+            // Anything without a 2xx response counts as failed
+            query = query.filter(
+                webhook_delivery::Column::Error
+                    .is_not_null()
+                    .or(webhook_delivery::Column::ResponseStatus.is_null())
+                    .or(webhook_delivery::Column::ResponseStatus.lt(200))
+                    .or(webhook_delivery::Column::ResponseStatus.gte(300)),
+            );
+        }
+
+        let result = query
+            .order_by_desc(webhook_delivery::Column::CreatedAt)
+            .limit(limit)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn latest_attempt(&self, chain_id: uuid::Uuid) -> Result<Option<u32>, RepoError> {
+        let latest = WebhookDeliveryEntity::find()
+            .filter(
+                Condition::any()
+                    .add(webhook_delivery::Column::Id.eq(chain_id))
+                    .add(webhook_delivery::Column::ReplayOf.eq(chain_id)),
+            )
+            .order_by_desc(webhook_delivery::Column::Attempt)
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(latest.map(|model| WebhookDelivery::from(model).attempt))
+    }
+}
+
+#[async_trait]
+impl AnnouncementRepository for PostgresAnnouncementRepository {
+    async fn list_current(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<Announcement>, RepoError> {
+        let result = AnnouncementEntity::find()
+            .filter(
+                announcement::Column::EndsAt
+                    .is_null()
+                    .or(announcement::Column::EndsAt.gt(now)),
+            )
+            .order_by_asc(announcement::Column::StartsAt)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn list_recent(&self, limit: u64) -> Result<Vec<Announcement>, RepoError> {
+        let result = AnnouncementEntity::find()
+            .order_by_desc(announcement::Column::CreatedAt)
+            .limit(limit)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait]
+impl OAuthClientRepository for PostgresOAuthClientRepository {
+    async fn find_by_client_id(&self, client_id: &str) -> Result<Option<OAuthClient>, RepoError> {
+        let result = OAuthClientEntity::find()
+            .filter(oauth_client::Column::ClientId.eq(client_id))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.map(Into::into))
+    }
+
+    async fn list_by_owner(&self, owner_id: uuid::Uuid) -> Result<Vec<OAuthClient>, RepoError> {
+        let result = OAuthClientEntity::find()
+            .filter(oauth_client::Column::OwnerId.eq(owner_id))
+            .order_by_asc(oauth_client::Column::CreatedAt)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait]
+impl CustomDomainRepository for PostgresCustomDomainRepository {
+    async fn find_by_hostname(&self, hostname: &str) -> Result<Option<CustomDomain>, RepoError> {
+        let result = CustomDomainEntity::find()
+            .filter(custom_domain::Column::Hostname.eq(hostname))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.map(Into::into))
+    }
+
+    async fn list_by_organization(
+        &self,
+        organization_id: uuid::Uuid,
+    ) -> Result<Vec<CustomDomain>, RepoError> {
+        let result = CustomDomainEntity::find()
+            .filter(custom_domain::Column::OrganizationId.eq(organization_id))
+            .order_by_asc(custom_domain::Column::CreatedAt)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait]
+impl PendingOperationRepository for PostgresPendingOperationRepository {
+    async fn list(
+        &self,
+        status: Option<ApprovalStatus>,
+        limit: u64,
+    ) -> Result<Vec<PendingOperation>, RepoError> {
+        let mut query = PendingOperationEntity::find();
+        if let Some(status) = status {
+            query = query.filter(pending_operation::Column::Status.eq(status.as_str()));
+        }
+
+        let result = query
+            .order_by_desc(pending_operation::Column::RequestedAt)
+            .limit(limit)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.into_iter().map(Into::into).collect())
+    }
+
+    async fn decide(&self, operation: &PendingOperation) -> Result<bool, RepoError> {
+        let model: pending_operation::ActiveModel = operation.clone().into();
+
+        let result = PendingOperationEntity::update_many()
+            .set(model)
+            .filter(pending_operation::Column::Id.eq(operation.id))
+            .filter(pending_operation::Column::Status.eq(ApprovalStatus::Pending.as_str()))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.rows_affected == 1)
+    }
+}
+
+/// PostgreSQL settings repository, keyed by (scope kind, scope id).
+pub struct PostgresSettingsRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresSettingsRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl SettingsRepository for PostgresSettingsRepository {
+    async fn get(&self, scope: SettingsScope) -> Result<Option<serde_json::Value>, RepoError> {
+        let result = SettingEntity::find_by_id((scope.kind().to_string(), scope.id()))
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.map(|model| model.values))
+    }
+
+    async fn put(&self, scope: SettingsScope, values: serde_json::Value) -> Result<(), RepoError> {
+        let model = setting::ActiveModel {
+            scope_kind: Set(scope.kind().to_string()),
+            scope_id: Set(scope.id()),
+            values: Set(values),
+            updated_at: Set(chrono::Utc::now().into()),
+        };
+
+        SettingEntity::insert(model)
+            .on_conflict(
+                OnConflict::columns([setting::Column::ScopeKind, setting::Column::ScopeId])
+                    .update_columns([setting::Column::Values, setting::Column::UpdatedAt])
+                    .to_owned(),
+            )
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// PostgreSQL plan repository, one row per account with an assigned plan.
+pub struct PostgresPlanRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresPlanRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PlanRepository for PostgresPlanRepository {
+    async fn get_plan(&self, account_id: uuid::Uuid) -> Result<Option<Plan>, RepoError> {
+        let result = AccountPlanEntity::find_by_id(account_id)
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        result
+            .map(|model| {
+                model
+                    .plan
+                    .parse()
+                    .map_err(|_| RepoError::Query(format!("Unknown plan: {}", model.plan)))
+            })
+            .transpose()
+    }
+
+    async fn set_plan(&self, account_id: uuid::Uuid, plan: Plan) -> Result<(), RepoError> {
+        let model = account_plan::ActiveModel {
+            account_id: Set(account_id),
+            plan: Set(plan.to_string()),
+            updated_at: Set(chrono::Utc::now().into()),
+        };
+
+        AccountPlanEntity::insert(model)
+            .on_conflict(
+                OnConflict::column(account_plan::Column::AccountId)
+                    .update_columns([account_plan::Column::Plan, account_plan::Column::UpdatedAt])
+                    .to_owned(),
+            )
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// PostgreSQL subscription repository, one row per account.
+pub struct PostgresSubscriptionRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresSubscriptionRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl SubscriptionRepository for PostgresSubscriptionRepository {
+    async fn find_by_account(
+        &self,
+        account_id: uuid::Uuid,
+    ) -> Result<Option<Subscription>, RepoError> {
+        let result = SubscriptionEntity::find_by_id(account_id)
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(result.map(Into::into))
+    }
+
+    async fn save(&self, subscription: Subscription) -> Result<Subscription, RepoError> {
+        let model: subscription::ActiveModel = subscription.clone().into();
+
+        SubscriptionEntity::insert(model)
+            .on_conflict(
+                OnConflict::column(subscription::Column::AccountId)
+                    .update_columns([
+                        subscription::Column::Plan,
+                        subscription::Column::Status,
+                        subscription::Column::TrialEndsAt,
+                        subscription::Column::CurrentPeriodEnd,
+                        subscription::Column::ExternalId,
+                        subscription::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(subscription)
+    }
+
+    async fn list_trials_ending_before(
+        &self,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<Subscription>, RepoError> {
+        let rows = SubscriptionEntity::find()
+            .filter(subscription::Column::Status.eq(SubscriptionStatus::Trialing.as_str()))
+            .filter(subscription::Column::TrialEndsAt.lte(at))
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+}
+
+/// PostgreSQL consent repository, one row per acceptance.
+pub struct PostgresConsentRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresConsentRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ConsentRepository for PostgresConsentRepository {
+    async fn record(&self, acceptance: PolicyAcceptance) -> Result<PolicyAcceptance, RepoError> {
+        let model: policy_acceptance::ActiveModel = acceptance.clone().into();
+
+        PolicyAcceptanceEntity::insert(model)
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(acceptance)
+    }
+
+    async fn list_for_user(&self, user_id: uuid::Uuid) -> Result<Vec<PolicyAcceptance>, RepoError> {
+        let rows = PolicyAcceptanceEntity::find()
+            .filter(policy_acceptance::Column::UserId.eq(user_id))
+            .order_by_desc(policy_acceptance::Column::AcceptedAt)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| PolicyAcceptance::try_from(row).ok())
+            .collect())
+    }
+}
+
+/// PostgreSQL legal hold repository, one row per change.
+pub struct PostgresLegalHoldRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresLegalHoldRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl LegalHoldRepository for PostgresLegalHoldRepository {
+    async fn record(&self, change: LegalHoldChange) -> Result<LegalHoldChange, RepoError> {
+        let model: legal_hold_change::ActiveModel = change.clone().into();
+
+        LegalHoldChangeEntity::insert(model)
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(change)
+    }
+
+    async fn history(&self, user_id: uuid::Uuid) -> Result<Vec<LegalHoldChange>, RepoError> {
+        let rows = LegalHoldChangeEntity::find()
+            .filter(legal_hold_change::Column::UserId.eq(user_id))
+            .order_by_desc(legal_hold_change::Column::ChangedAt)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn latest(&self, user_id: uuid::Uuid) -> Result<Option<LegalHoldChange>, RepoError> {
+        let row = LegalHoldChangeEntity::find()
+            .filter(legal_hold_change::Column::UserId.eq(user_id))
+            .order_by_desc(legal_hold_change::Column::ChangedAt)
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(row.map(Into::into))
+    }
+}
+
+/// PostgreSQL password history repository, one row per replaced hash.
+pub struct PostgresPasswordHistoryRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresPasswordHistoryRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PasswordHistoryRepository for PostgresPasswordHistoryRepository {
+    async fn recent(&self, user_id: uuid::Uuid, limit: usize) -> Result<Vec<String>, RepoError> {
+        let rows = PasswordHistoryEntity::find()
+            .filter(password_history::Column::UserId.eq(user_id))
+            .order_by_desc(password_history::Column::CreatedAt)
+            .limit(limit as u64)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|row| row.password_hash).collect())
+    }
+
+    async fn record(
+        &self,
+        user_id: uuid::Uuid,
+        password_hash: String,
+        keep: usize,
+    ) -> Result<(), RepoError> {
+        if keep > 0 {
+            let model = password_history::ActiveModel {
+                id: Set(uuid::Uuid::new_v4()),
+                user_id: Set(user_id),
+                password_hash: Set(password_hash),
+                created_at: Set(chrono::Utc::now().into()),
+            };
+            PasswordHistoryEntity::insert(model)
+                .exec(self.db.as_ref())
+                .await
+                .map_err(|e| RepoError::Query(e.to_string()))?;
+        }
+
+        let kept: Vec<uuid::Uuid> = PasswordHistoryEntity::find()
+            .select_only()
+            .column(password_history::Column::Id)
+            .filter(password_history::Column::UserId.eq(user_id))
+            .order_by_desc(password_history::Column::CreatedAt)
+            .limit(keep as u64)
+            .into_tuple()
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+        PasswordHistoryEntity::delete_many()
+            .filter(password_history::Column::UserId.eq(user_id))
+            .filter(password_history::Column::Id.is_not_in(kept))
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// PostgreSQL tag repository: tags by unique name, and one `post_tags` row
+/// per tag on a post.
+pub struct PostgresTagRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresTagRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl TagRepository for PostgresTagRepository {
+    async fn tags_of(&self, post_id: uuid::Uuid) -> Result<Vec<Tag>, RepoError> {
+        let rows = TagEntity::find()
+            .inner_join(PostTagEntity)
+            .filter(post_tag::Column::PostId.eq(post_id))
+            .order_by_asc(tag::Column::Name)
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn set_tags(&self, post_id: uuid::Uuid, tags: Vec<Tag>) -> Result<Vec<Tag>, RepoError> {
+        let txn = self
+            .db
+            .begin()
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        let names: Vec<String> = tags.iter().map(|tag| tag.name.clone()).collect();
+        if !tags.is_empty() {
+            // Names already taken keep their stored tag
+            TagEntity::insert_many(tags.into_iter().map(tag::ActiveModel::from))
+                .on_conflict(
+                    OnConflict::column(tag::Column::Name)
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await
+                .map_err(|e| RepoError::Query(e.to_string()))?;
+        }
+        let stored = TagEntity::find()
+            .filter(tag::Column::Name.is_in(names))
+            .order_by_asc(tag::Column::Name)
+            .all(&txn)
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        PostTagEntity::delete_many()
+            .filter(post_tag::Column::PostId.eq(post_id))
+            .exec(&txn)
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+        if !stored.is_empty() {
+            let links = stored.iter().map(|tag| post_tag::ActiveModel {
+                post_id: Set(post_id),
+                tag_id: Set(tag.id),
+            });
+            PostTagEntity::insert_many(links)
+                .exec_without_returning(&txn)
+                .await
+                .map_err(|e| RepoError::Query(e.to_string()))?;
+        }
+
+        txn.commit()
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+        Ok(stored.into_iter().map(Into::into).collect())
+    }
+
+    async fn post_ids_tagged(&self, name: &str) -> Result<Vec<uuid::Uuid>, RepoError> {
+        PostTagEntity::find()
+            .select_only()
+            .column(post_tag::Column::PostId)
+            .inner_join(TagEntity)
+            .filter(tag::Column::Name.eq(name))
+            .into_tuple()
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))
+    }
+}
+
+/// PostgreSQL usage repository, one row per (account, metric, month).
+pub struct PostgresUsageRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresUsageRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl UsageRepository for PostgresUsageRepository {
+    async fn increment(&self, increments: Vec<UsageTotal>) -> Result<(), RepoError> {
+        if increments.is_empty() {
+            return Ok(());
+        }
+
+        let now = chrono::Utc::now();
+        let models = increments
+            .into_iter()
+            .map(|total| usage_rollup::ActiveModel {
+                account_id: Set(total.account_id),
+                metric: Set(total.metric.to_string()),
+                period_start: Set(total.period_start),
+                quantity: Set(total.quantity),
+                updated_at: Set(now.into()),
+            });
+
+        // Add to the existing rollup rather than overwriting it
+        let quantity = Expr::col((UsageRollupEntity, usage_rollup::Column::Quantity))
+            .add(excluded(&self.db, usage_rollup::Column::Quantity));
+
+        UsageRollupEntity::insert_many(models)
+            .on_conflict(
+                OnConflict::columns([
+                    usage_rollup::Column::AccountId,
+                    usage_rollup::Column::Metric,
+                    usage_rollup::Column::PeriodStart,
+                ])
+                .value(usage_rollup::Column::Quantity, quantity)
+                .update_column(usage_rollup::Column::UpdatedAt)
+                .to_owned(),
+            )
+            .exec(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn totals(
+        &self,
+        account_id: uuid::Uuid,
+        period_start: chrono::NaiveDate,
+    ) -> Result<Vec<UsageTotal>, RepoError> {
+        let rows = UsageRollupEntity::find()
+            .filter(usage_rollup::Column::AccountId.eq(account_id))
+            .filter(usage_rollup::Column::PeriodStart.eq(period_start))
+            .all(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(UsageTotal {
+                    account_id: row.account_id,
+                    metric: row.metric.parse().ok()?,
+                    period_start: row.period_start,
+                    quantity: row.quantity,
+                })
+            })
+            .collect())
+    }
+}
+
+/// PostgreSQL storage usage repository, one running total per account.
+pub struct PostgresStorageUsageRepository {
+    db: Arc<DbConn>,
+}
+
+impl PostgresStorageUsageRepository {
+    pub fn new(db: Arc<DbConn>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl StorageUsageRepository for PostgresStorageUsageRepository {
+    async fn bytes_stored(&self, account_id: uuid::Uuid) -> Result<i64, RepoError> {
+        let row = StorageUsageEntity::find_by_id(account_id)
+            .one(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+        Ok(row.map(|row| row.bytes).unwrap_or(0))
+    }
+
+    async fn add_bytes(&self, account_id: uuid::Uuid, delta: i64) -> Result<i64, RepoError> {
+        let model = storage_usage::ActiveModel {
+            account_id: Set(account_id),
+            bytes: Set(delta),
+            updated_at: Set(chrono::Utc::now().into()),
+        };
+
+        // Add to the running total in one statement, so concurrent writes
+        // don't lose each other's bytes
+        let bytes = Expr::col((StorageUsageEntity, storage_usage::Column::Bytes))
+            .add(excluded(&self.db, storage_usage::Column::Bytes));
+
+        let row = StorageUsageEntity::insert(model)
+            .on_conflict(
+                OnConflict::column(storage_usage::Column::AccountId)
+                    .value(storage_usage::Column::Bytes, bytes)
+                    .update_column(storage_usage::Column::UpdatedAt)
+                    .to_owned(),
+            )
+            .exec_with_returning(self.db.as_ref())
+            .await
+            .map_err(|e| RepoError::Query(e.to_string()))?;
+
+        Ok(row.bytes)
+    }
+}
@@ -1,37 +1,816 @@
-#[cfg(test)]
-mod tests {
-    use crate::database::entity::post;
-    use crate::database::postgres_repo::PostgresPostRepository;
-    use apex_core::domain::Post;
-    use apex_core::ports::BaseRepository;
-    use sea_orm::{DatabaseBackend, MockDatabase};
-
-    #[tokio::test]
-    async fn test_find_post_by_id() {
-        // Create mock database with expected query results
-        let post_id = uuid::Uuid::new_v4();
-        let user_id = uuid::Uuid::new_v4();
-        let now = chrono::Utc::now();
-
-        // Mock the query expectation
-        let db = MockDatabase::new(DatabaseBackend::Postgres)
-            .append_query_results(vec![vec![post::Model {
-                id: post_id,
-                user_id,
-                title: "Test Post".to_owned(),
-                content: "Content".to_owned(),
-                created_at: now.into(),
-                updated_at: now.into(),
-            }]])
-            .into_connection();
-
-        let repo = PostgresPostRepository::new(db);
-
-        let result: Option<Post> = repo.find_by_id(post_id).await.unwrap();
-
-        assert!(result.is_some());
-        let post = result.unwrap();
-        assert_eq!(post.title, "Test Post");
-        assert_eq!(post.id, post_id);
+use crate::database::entity::post;
+use crate::database::postgres_repo::PostgresPostRepository;
+use apex_core::domain::Post;
+use apex_core::ports::BaseRepository;
+use sea_orm::{DatabaseBackend, MockDatabase};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_find_post_by_id() {
+    // Create mock database with expected query results
+    let post_id = uuid::Uuid::new_v4();
+    let user_id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now();
+
+    // Mock the query expectation
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![post::Model {
+            id: post_id,
+            user_id,
+            organization_id: None,
+            title: "Test Post".to_owned(),
+            content: "Content".to_owned(),
+            version: 1,
+            deleted_at: None,
+            view_count: 0,
+            published_at: None,
+            created_at: now.into(),
+            updated_at: now.into(),
+            created_by: None,
+            updated_by: None,
+        }]])
+        .into_connection();
+
+    let repo = PostgresPostRepository::new(Arc::new(db));
+
+    let result: Option<Post> = repo.find_by_id(post_id).await.unwrap();
+
+    assert!(result.is_some());
+    let post = result.unwrap();
+    assert_eq!(post.title, "Test Post");
+    assert_eq!(post.id, post_id);
+}
+
+#[tokio::test]
+async fn test_save_upserts_on_primary_key() {
+    let now = chrono::Utc::now();
+    let post = Post {
+        id: uuid::Uuid::new_v4(),
+        user_id: uuid::Uuid::new_v4(),
+        organization_id: None,
+        title: "New Post".to_owned(),
+        content: "Content".to_owned(),
+        version: 1,
+        deleted_at: None,
+        view_count: 0,
+        published_at: None,
+        created_at: now,
+        updated_at: now,
+        created_by: None,
+        updated_by: None,
+    };
+    let actor = uuid::Uuid::new_v4();
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![post::Model {
+            id: post.id,
+            user_id: post.user_id,
+            organization_id: None,
+            title: post.title.clone(),
+            content: post.content.clone(),
+            version: 1,
+            deleted_at: None,
+            view_count: 0,
+            published_at: None,
+            created_at: now.into(),
+            updated_at: now.into(),
+            created_by: None,
+            updated_by: None,
+        }]])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresPostRepository::new(db.clone());
+    let mut context = apex_core::RequestContext::new("req-1");
+    context.user_id = Some(actor);
+    let saved = crate::context::scope(context, repo.save(post.clone()))
+        .await
+        .unwrap();
+    assert_eq!(saved.id, post.id);
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    assert_eq!(log.len(), 1);
+    let sql = format!("{:?}", log[0]);
+    assert!(sql.contains("INSERT INTO"));
+    assert!(sql.contains("ON CONFLICT (\\\"id\\\") DO UPDATE"));
+    // Views are only ever added to, never overwritten by a save
+    assert!(!sql.contains("\\\"view_count\\\" = \\\"excluded\\\""));
+    // The request's user is recorded; a creator is never overwritten
+    assert!(sql.contains(&actor.to_string()));
+    assert!(sql.contains("\\\"updated_by\\\" = \\\"excluded\\\""));
+    assert!(!sql.contains("\\\"created_by\\\" = \\\"excluded\\\""));
+}
+
+#[tokio::test]
+async fn test_add_views_updates_the_batch_in_one_statement() {
+    use apex_core::ports::PostRepository;
+    use sea_orm::MockExecResult;
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_exec_results(vec![MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 2,
+        }])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresPostRepository::new(db.clone());
+    repo.add_views(vec![(uuid::Uuid::new_v4(), 3), (uuid::Uuid::new_v4(), 1)])
+        .await
+        .unwrap();
+    repo.add_views(Vec::new()).await.unwrap();
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    assert_eq!(log.len(), 1);
+    let sql = format!("{:?}", log[0]);
+    assert!(sql.contains("\\\"view_count\\\" = \\\"view_count\\\" + (CASE WHEN"));
+}
+
+#[tokio::test]
+async fn test_bulk_writes_are_one_statement_per_batch() {
+    use sea_orm::MockExecResult;
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_exec_results(vec![
+            MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 3,
+            },
+            MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 2,
+            },
+        ])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresPostRepository::new(db.clone());
+    let author = uuid::Uuid::new_v4();
+    let posts: Vec<Post> = (0..3)
+        .map(|i| Post::new(author, format!("Post {i}"), "Content".to_owned()))
+        .collect();
+    let ids = vec![posts[0].id, posts[1].id];
+    repo.save_many(posts).await.unwrap();
+    let deleted = BaseRepository::<Post, _>::delete_many(&repo, ids).await;
+    assert_eq!(deleted.unwrap(), 2);
+    // Nothing to write, no statement
+    repo.save_many(Vec::<Post>::new()).await.unwrap();
+    let deleted = BaseRepository::<Post, _>::delete_many(&repo, Vec::new()).await;
+    assert_eq!(deleted.unwrap(), 0);
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    assert_eq!(log.len(), 2);
+    let insert = format!("{:?}", log[0]);
+    assert!(insert.contains("INSERT INTO"));
+    assert!(insert.contains("ON CONFLICT (\\\"id\\\") DO UPDATE"));
+    assert!(!insert.contains("\\\"view_count\\\" = \\\"excluded\\\""));
+    let delete = format!("{:?}", log[1]);
+    assert!(delete.contains("DELETE FROM"));
+    assert!(delete.contains("\\\"id\\\" IN ("));
+}
+
+#[tokio::test]
+async fn test_usage_increment_adds_to_existing_rollup() {
+    use crate::database::postgres_repo::PostgresUsageRepository;
+    use apex_core::domain::{UsageMetric, UsageTotal};
+    use apex_core::ports::UsageRepository;
+    use sea_orm::MockExecResult;
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_exec_results(vec![MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresUsageRepository::new(db.clone());
+    repo.increment(vec![UsageTotal {
+        account_id: uuid::Uuid::new_v4(),
+        metric: UsageMetric::ApiCalls,
+        period_start: chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        quantity: 5,
+    }])
+    .await
+    .unwrap();
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    let sql = format!("{:?}", log[0]);
+    assert!(
+        sql.contains("\\\"usage_rollups\\\".\\\"quantity\\\" + \\\"excluded\\\".\\\"quantity\\\"")
+    );
+}
+
+#[tokio::test]
+async fn test_add_bytes_returns_the_running_total() {
+    use crate::database::entity::storage_usage;
+    use crate::database::postgres_repo::PostgresStorageUsageRepository;
+    use apex_core::ports::StorageUsageRepository;
+
+    let account_id = uuid::Uuid::new_v4();
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![storage_usage::Model {
+            account_id,
+            bytes: 1500,
+            updated_at: chrono::Utc::now().into(),
+        }]])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresStorageUsageRepository::new(db.clone());
+    assert_eq!(repo.add_bytes(account_id, 500).await.unwrap(), 1500);
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    let sql = format!("{:?}", log[0]);
+    assert!(sql.contains("\\\"storage_usage\\\".\\\"bytes\\\" + \\\"excluded\\\".\\\"bytes\\\""));
+    assert!(sql.contains("RETURNING"));
+}
+
+#[tokio::test]
+async fn test_add_bytes_upserts_on_mysql() {
+    use crate::database::entity::storage_usage;
+    use crate::database::postgres_repo::PostgresStorageUsageRepository;
+    use apex_core::ports::StorageUsageRepository;
+    use sea_orm::MockExecResult;
+
+    let account_id = uuid::Uuid::new_v4();
+    let db = MockDatabase::new(DatabaseBackend::MySql)
+        .append_exec_results(vec![MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 2,
+        }])
+        .append_query_results(vec![vec![storage_usage::Model {
+            account_id,
+            bytes: 1500,
+            updated_at: chrono::Utc::now().into(),
+        }]])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresStorageUsageRepository::new(db.clone());
+    assert_eq!(repo.add_bytes(account_id, 500).await.unwrap(), 1500);
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    let sql = format!("{:?}", log[0]);
+    assert!(sql.contains("ON DUPLICATE KEY UPDATE"));
+    assert!(sql.contains("`storage_usage`.`bytes` + VALUES(`bytes`)"));
+}
+
+#[test]
+fn test_unique_violations_are_recognised_on_every_backend() {
+    use super::postgres_base::map_db_error;
+    use apex_core::error::RepoError;
+    use sea_orm::{DbErr, RuntimeErr};
+
+    for message in [
+        "duplicate key value violates unique constraint \"users_email_key\"",
+        "Duplicate entry 'jane@example.com' for key 'users.email'",
+        "UNIQUE constraint failed: users.email",
+    ] {
+        let error = DbErr::Exec(RuntimeErr::Internal(message.to_string()));
+        assert!(
+            matches!(map_db_error(error), RepoError::Constraint(_)),
+            "{}",
+            message
+        );
+    }
+    let other = DbErr::Exec(RuntimeErr::Internal("connection reset".to_string()));
+    assert!(matches!(map_db_error(other), RepoError::Query(_)));
+}
+
+#[tokio::test]
+async fn test_save_if_version_reports_a_lost_race() {
+    use apex_core::error::RepoError;
+    use apex_core::ports::PostRepository;
+    use sea_orm::MockExecResult;
+
+    let mut post = Post::new(uuid::Uuid::new_v4(), "Title".into(), "Content".into());
+    post.revise("Edited".into(), "Content".into());
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_exec_results(vec![MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 0,
+        }])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresPostRepository::new(db.clone());
+    assert!(matches!(
+        repo.save_if_version(post, Some(1)).await,
+        Err(RepoError::Conflict(_))
+    ));
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    let sql = format!("{:?}", log[0]);
+    assert!(sql.contains("UPDATE \\\"posts\\\""));
+    assert!(sql.contains("\\\"version\\\" = $"));
+}
+
+#[tokio::test]
+async fn test_decide_only_updates_pending_operations() {
+    use crate::database::postgres_repo::PostgresPendingOperationRepository;
+    use apex_core::domain::{AdminAction, PendingOperation};
+    use apex_core::ports::PendingOperationRepository;
+    use sea_orm::MockExecResult;
+
+    let mut operation = PendingOperation::new(
+        AdminAction::PurgePendingJobs,
+        uuid::Uuid::new_v4(),
+        chrono::Duration::hours(1),
+    );
+    operation.approve(uuid::Uuid::new_v4()).unwrap();
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_exec_results(vec![MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 0,
+        }])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresPendingOperationRepository::new(db.clone());
+    assert!(!repo.decide(&operation).await.unwrap());
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    let sql = format!("{:?}", log[0]);
+    assert!(sql.contains("UPDATE \\\"pending_operations\\\""));
+    assert!(sql.contains("\\\"status\\\" = $"));
+    assert!(sql.contains("String(Some(\"pending\"))"));
+}
+
+#[tokio::test]
+async fn test_find_page_by_user_id_counts_and_orders() {
+    use apex_core::domain::{PageRequest, Sort};
+    use apex_core::ports::PostRepository;
+    use std::collections::BTreeMap;
+
+    let user_id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now();
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![vec![BTreeMap::from([(
+            "num_items",
+            sea_orm::Value::BigInt(Some(21)),
+        )])]])
+        .append_query_results(vec![vec![post::Model {
+            id: uuid::Uuid::new_v4(),
+            user_id,
+            organization_id: None,
+            title: "Last Post".to_owned(),
+            content: "Content".to_owned(),
+            version: 1,
+            deleted_at: None,
+            view_count: 0,
+            published_at: None,
+            created_at: now.into(),
+            updated_at: now.into(),
+            created_by: None,
+            updated_by: None,
+        }]])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresPostRepository::new(db.clone());
+    let request = PageRequest::new(20, 20).sorted_by(Sort::desc("created_at"));
+    let page = repo.find_page_by_user_id(user_id, request).await.unwrap();
+    assert_eq!(page.total, 21);
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.next_offset(), None);
+
+    let unknown = PageRequest::default().sorted_by(Sort::asc("password"));
+    assert!(repo.find_page_by_user_id(user_id, unknown).await.is_err());
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    assert_eq!(log.len(), 2);
+    let sql = format!("{:?}", log[1]);
+    assert!(sql.contains("\\\"deleted_at\\\" IS NULL"));
+    assert!(
+        sql.contains(
+            "ORDER BY \\\"posts\\\".\\\"created_at\\\" DESC, \\\"posts\\\".\\\"id\\\" ASC"
+        )
+    );
+    assert!(sql.contains("LIMIT $"));
+}
+
+#[tokio::test]
+async fn test_find_by_user_id_after_seeks_past_the_cursor() {
+    use apex_core::domain::PageCursor;
+    use apex_core::ports::PostRepository;
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results(vec![Vec::<post::Model>::new()])
+        .into_connection();
+    let db = Arc::new(db);
+
+    let repo = PostgresPostRepository::new(db.clone());
+    let cursor = PageCursor::new(chrono::Utc::now(), uuid::Uuid::new_v4());
+    let posts = repo
+        .find_by_user_id_after(uuid::Uuid::new_v4(), Some(cursor), 20)
+        .await
+        .unwrap();
+    assert!(posts.is_empty());
+
+    drop(repo);
+    let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+    let sql = format!("{:?}", log[0]);
+    // No OFFSET and no COUNT: the cursor's row is found through the index
+    assert!(sql.contains(
+        "\\\"posts\\\".\\\"created_at\\\" < $2 OR (\\\"posts\\\".\\\"created_at\\\" = $3 AND \\\"posts\\\".\\\"id\\\" < $4)"
+    ));
+    assert!(
+        sql.contains(
+            "ORDER BY \\\"posts\\\".\\\"created_at\\\" DESC, \\\"posts\\\".\\\"id\\\" DESC"
+        )
+    );
+    assert!(!sql.contains("OFFSET"));
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_repositories_run_on_sqlite() {
+    use crate::database::entity::user;
+    use crate::database::{
+        DatabaseConfig, DatabaseConnections, PostgresUserRepository, TenancyMode,
+    };
+    use apex_core::domain::{Email, User};
+    use apex_core::ports::UserRepository;
+    use sea_orm::{ConnectionTrait, Schema};
+
+    // One connection: each would get its own in-memory database
+    let connections = DatabaseConnections::init(&DatabaseConfig {
+        main_url: "sqlite::memory:".to_string(),
+        main_max_connections: 1,
+        main_min_connections: 1,
+        secondary_databases: Vec::new(),
+        connect_attempts: 1,
+        connect_backoff: std::time::Duration::ZERO,
+        tenancy: TenancyMode::None,
+        slow_query_threshold: std::time::Duration::ZERO,
+    })
+    .await
+    .unwrap();
+    let db = connections.main.clone();
+    let backend = db.get_database_backend();
+    db.execute(backend.build(&Schema::new(backend).create_table_from_entity(user::Entity)))
+        .await
+        .unwrap();
+
+    let repo = PostgresUserRepository::new(db);
+    let user = User::new(Email::parse("dev@example.com").unwrap(), "hash".to_string());
+    repo.save(user.clone()).await.unwrap();
+    // Saved again: the upsert works on SQLite too
+    repo.save(user.clone()).await.unwrap();
+
+    let others: Vec<User> = (0..3)
+        .map(|i| {
+            let email = Email::parse(&format!("dev{i}@example.com")).unwrap();
+            User::new(email, "hash".to_string())
+        })
+        .collect();
+    let ids: Vec<_> = others.iter().map(|other| other.id).collect();
+    repo.save_many(others.clone()).await.unwrap();
+    repo.save_many(others).await.unwrap();
+    let deleted = BaseRepository::<User, _>::delete_many(&repo, ids).await;
+    assert_eq!(deleted.unwrap(), 3);
+
+    let found = repo
+        .find_by_email(&Email::parse("Dev@Example.com").unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, user.id);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_find_page_filters_on_sqlite() {
+    use crate::database::entity::user;
+    use crate::database::{
+        DatabaseConfig, DatabaseConnections, PostgresUserRepository, TenancyMode,
+    };
+    use apex_core::domain::{Email, Filter, Page, PageRequest, Sort, User};
+    use sea_orm::{ConnectionTrait, EntityTrait, Schema};
+
+    let connections = DatabaseConnections::init(&DatabaseConfig {
+        main_url: "sqlite::memory:".to_string(),
+        main_max_connections: 1,
+        main_min_connections: 1,
+        secondary_databases: Vec::new(),
+        connect_attempts: 1,
+        connect_backoff: std::time::Duration::ZERO,
+        tenancy: TenancyMode::None,
+        slow_query_threshold: std::time::Duration::ZERO,
+    })
+    .await
+    .unwrap();
+    let db = connections.main.clone();
+    let backend = db.get_database_backend();
+    for table in [
+        Schema::new(backend).create_table_from_entity(user::Entity),
+        Schema::new(backend).create_table_from_entity(post::Entity),
+    ] {
+        db.execute(backend.build(&table)).await.unwrap();
+    }
+    let users: Vec<User> = ["author", "other"]
+        .into_iter()
+        .map(|name| {
+            let email = Email::parse(&format!("{name}@example.com")).unwrap();
+            User::new(email, "hash".to_string())
+        })
+        .collect();
+    let (author, someone_else) = (users[0].id, users[1].id);
+    PostgresUserRepository::new(db.clone())
+        .save_many(users)
+        .await
+        .unwrap();
+
+    let now = chrono::Utc::now();
+    let mut published = Post::new(author, "Published".to_string(), "Content".to_string());
+    published.publish(now - chrono::Duration::hours(1));
+    let draft = Post::new(author, "Draft".to_string(), "Content".to_string());
+    let other = Post::new(someone_else, "Other".to_string(), "Content".to_string());
+    // The migrations default view_count, the entity-built table does not
+    let rows = [published, draft, other.clone()].map(|post| post::ActiveModel {
+        view_count: sea_orm::Set(0),
+        ..post.into()
+    });
+    post::Entity::insert_many(rows).exec(&*db).await.unwrap();
+    let repo = PostgresPostRepository::new(db);
+
+    let titles = |filter: Filter| {
+        let request = PageRequest::default()
+            .sorted_by(Sort::asc("title"))
+            .filtered_by(filter);
+        let repo = &repo;
+        async move {
+            let page: Page<Post> = repo.find_page(request).await.unwrap();
+            assert_eq!(page.total, page.items.len() as u64);
+            page.items
+                .into_iter()
+                .map(|post| post.title)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        titles(Filter::eq("user_id", author)).await,
+        ["Draft", "Published"]
+    );
+    assert_eq!(
+        titles(Filter::eq("user_id", author).and(Filter::is_null("published_at"))).await,
+        ["Draft"]
+    );
+    assert_eq!(
+        titles(Filter::lte("published_at", now).or(Filter::eq("id", other.id))).await,
+        ["Other", "Published"]
+    );
+    assert_eq!(
+        titles(Filter::ne("title", "Draft")).await,
+        ["Other", "Published"]
+    );
+    assert_eq!(
+        titles(Filter::is_in(
+            "user_id",
+            [someone_else, uuid::Uuid::new_v4()]
+        ))
+        .await,
+        ["Other"]
+    );
+    assert!(
+        titles(Filter::is_in("id", Vec::<uuid::Uuid>::new()))
+            .await
+            .is_empty()
+    );
+    assert!(titles(Filter::Or(Vec::new())).await.is_empty());
+    assert_eq!(titles(Filter::And(Vec::new())).await.len(), 3);
+
+    let unknown = PageRequest::default().filtered_by(Filter::eq("password", "x"));
+    let page: Result<Page<Post>, _> = repo.find_page(unknown).await;
+    assert!(page.is_err());
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_password_history_keeps_the_newest_hashes_on_sqlite() {
+    use crate::database::entity::{password_history, user};
+    use crate::database::{
+        DatabaseConfig, DatabaseConnections, PostgresPasswordHistoryRepository,
+        PostgresUserRepository, TenancyMode,
+    };
+    use apex_core::domain::{Email, User};
+    use apex_core::ports::PasswordHistoryRepository;
+    use sea_orm::{ConnectionTrait, Schema};
+
+    let connections = DatabaseConnections::init(&DatabaseConfig {
+        main_url: "sqlite::memory:".to_string(),
+        main_max_connections: 1,
+        main_min_connections: 1,
+        secondary_databases: Vec::new(),
+        connect_attempts: 1,
+        connect_backoff: std::time::Duration::ZERO,
+        tenancy: TenancyMode::None,
+        slow_query_threshold: std::time::Duration::ZERO,
+    })
+    .await
+    .unwrap();
+    let db = connections.main.clone();
+    let backend = db.get_database_backend();
+    for table in [
+        Schema::new(backend).create_table_from_entity(user::Entity),
+        Schema::new(backend).create_table_from_entity(password_history::Entity),
+    ] {
+        db.execute(backend.build(&table)).await.unwrap();
+    }
+    let user = User::new(Email::parse("dev@example.com").unwrap(), "hash".to_string());
+    PostgresUserRepository::new(db.clone())
+        .save(user.clone())
+        .await
+        .unwrap();
+
+    let history = PostgresPasswordHistoryRepository::new(db);
+    for n in 1..=4 {
+        history
+            .record(user.id, format!("hash {n}"), 2)
+            .await
+            .unwrap();
+        // Rows a microsecond apart could tie on created_at
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    assert_eq!(
+        history.recent(user.id, 10).await.unwrap(),
+        ["hash 4", "hash 3"]
+    );
+    assert_eq!(history.recent(user.id, 1).await.unwrap(), ["hash 4"]);
+
+    history
+        .record(user.id, "hash 5".to_string(), 0)
+        .await
+        .unwrap();
+    assert!(history.recent(user.id, 10).await.unwrap().is_empty());
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_comments_and_tags_on_sqlite() {
+    use crate::database::entity::{comment, post_tag, tag, user};
+    use crate::database::{
+        DatabaseConfig, DatabaseConnections, PostgresCommentRepository, PostgresTagRepository,
+        PostgresUserRepository, TenancyMode,
+    };
+    use apex_core::domain::{Comment, Email, Page, PageRequest, Sort, Tag, User};
+    use apex_core::ports::{CommentRepository, TagRepository};
+    use sea_orm::{ConnectionTrait, EntityTrait, Schema};
+
+    let connections = DatabaseConnections::init(&DatabaseConfig {
+        main_url: "sqlite::memory:".to_string(),
+        main_max_connections: 1,
+        main_min_connections: 1,
+        secondary_databases: Vec::new(),
+        connect_attempts: 1,
+        connect_backoff: std::time::Duration::ZERO,
+        tenancy: TenancyMode::None,
+        slow_query_threshold: std::time::Duration::ZERO,
+    })
+    .await
+    .unwrap();
+    let db = connections.main.clone();
+    let backend = db.get_database_backend();
+    for table in [
+        Schema::new(backend).create_table_from_entity(user::Entity),
+        Schema::new(backend).create_table_from_entity(post::Entity),
+        Schema::new(backend).create_table_from_entity(comment::Entity),
+        Schema::new(backend).create_table_from_entity(tag::Entity),
+        Schema::new(backend).create_table_from_entity(post_tag::Entity),
+    ] {
+        db.execute(backend.build(&table)).await.unwrap();
+    }
+    let user = User::new(Email::parse("dev@example.com").unwrap(), "hash".to_string());
+    PostgresUserRepository::new(db.clone())
+        .save(user.clone())
+        .await
+        .unwrap();
+    let posts: Vec<Post> = ["First", "Second"]
+        .into_iter()
+        .map(|title| Post::new(user.id, title.to_string(), "Content".to_string()))
+        .collect();
+    let (first, second) = (posts[0].id, posts[1].id);
+    // The migrations default view_count, the entity-built table does not
+    let rows = posts.into_iter().map(|post| post::ActiveModel {
+        view_count: sea_orm::Set(0),
+        ..post.into()
+    });
+    post::Entity::insert_many(rows).exec(&*db).await.unwrap();
+
+    let comments = PostgresCommentRepository::new(db.clone());
+    for body in ["One", "Two", "Three"] {
+        let comment = Comment::new(first, user.id, body).unwrap();
+        comments.insert(comment).await.unwrap();
+    }
+    let request = PageRequest::new(0, 2).sorted_by(Sort::asc("body"));
+    let page = comments.find_page_by_post_id(first, request).await.unwrap();
+    let bodies: Vec<&str> = page.items.iter().map(|c| c.body.as_str()).collect();
+    assert_eq!((bodies, page.total), (vec!["One", "Three"], 3));
+    let none: Page<Comment> = comments
+        .find_page_by_post_id(second, PageRequest::default())
+        .await
+        .unwrap();
+    assert_eq!(none.total, 0);
+
+    let tags = PostgresTagRepository::new(db);
+    let tag = |name| Tag::new(name).unwrap();
+    let stored = tags
+        .set_tags(first, vec![tag("web"), tag("rust")])
+        .await
+        .unwrap();
+    let rust = stored[0].clone();
+    assert_eq!(rust.name, "rust");
+    // The name is taken, so the stored tag is reused
+    assert_eq!(
+        tags.set_tags(second, vec![tag("rust")]).await.unwrap(),
+        [rust]
+    );
+    assert_eq!(tags.tags_of(first).await.unwrap(), stored);
+    assert_eq!(tags.post_ids_tagged("web").await.unwrap(), [first]);
+
+    tags.set_tags(first, vec![tag("web")]).await.unwrap();
+    assert_eq!(tags.post_ids_tagged("rust").await.unwrap(), [second]);
+    tags.set_tags(first, Vec::new()).await.unwrap();
+    assert!(tags.tags_of(first).await.unwrap().is_empty());
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_attachments_list_by_post_on_sqlite() {
+    use crate::database::entity::{attachment, user};
+    use crate::database::{
+        DatabaseConfig, DatabaseConnections, PostgresAttachmentRepository, PostgresUserRepository,
+        TenancyMode,
+    };
+    use apex_core::domain::{Attachment, Email, User};
+    use apex_core::ports::AttachmentRepository;
+    use sea_orm::{ConnectionTrait, EntityTrait, Schema};
+
+    let connections = DatabaseConnections::init(&DatabaseConfig {
+        main_url: "sqlite::memory:".to_string(),
+        main_max_connections: 1,
+        main_min_connections: 1,
+        secondary_databases: Vec::new(),
+        connect_attempts: 1,
+        connect_backoff: std::time::Duration::ZERO,
+        tenancy: TenancyMode::None,
+        slow_query_threshold: std::time::Duration::ZERO,
+    })
+    .await
+    .unwrap();
+    let db = connections.main.clone();
+    let backend = db.get_database_backend();
+    for table in [
+        Schema::new(backend).create_table_from_entity(user::Entity),
+        Schema::new(backend).create_table_from_entity(post::Entity),
+        Schema::new(backend).create_table_from_entity(attachment::Entity),
+    ] {
+        db.execute(backend.build(&table)).await.unwrap();
     }
+    let user = User::new(Email::parse("dev@example.com").unwrap(), "hash".to_string());
+    PostgresUserRepository::new(db.clone())
+        .save(user.clone())
+        .await
+        .unwrap();
+    let post = Post::new(user.id, "Title".to_string(), "Content".to_string());
+    // The migrations default view_count, the entity-built table does not
+    let row = post::ActiveModel {
+        view_count: sea_orm::Set(0),
+        ..post.clone().into()
+    };
+    post::Entity::insert(row).exec(&*db).await.unwrap();
+
+    let repo = PostgresAttachmentRepository::new(db);
+    let file = |name| Attachment::new(user.id, name, "text/plain", 1).unwrap();
+    let first = repo
+        .insert(file("first.txt").on_post(post.id))
+        .await
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let second = repo
+        .insert(file("second.txt").on_post(post.id))
+        .await
+        .unwrap();
+    repo.insert(file("loose.txt")).await.unwrap();
+
+    assert_eq!(repo.list_by_post(post.id).await.unwrap(), [first, second]);
+    assert!(
+        repo.list_by_post(uuid::Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_empty()
+    );
 }
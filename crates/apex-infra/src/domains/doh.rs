@@ -0,0 +1,157 @@
+//! DNS over HTTPS, with the JSON API of Cloudflare and Google public DNS.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use apex_core::ports::{DnsError, DnsResolver};
+
+use crate::env;
+
+/// DNS record type of TXT records.
+const TXT: u16 = 16;
+
+/// Response code of a name that does not exist.
+const NXDOMAIN: u32 = 3;
+
+/// DNS-over-HTTPS configuration.
+#[derive(Debug, Clone)]
+pub struct DohConfig {
+    /// JSON API endpoint.
+    pub url: String,
+    pub timeout: Duration,
+}
+
+impl Default for DohConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://cloudflare-dns.com/dns-query".to_string(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl DohConfig {
+    /// `DNS_OVER_HTTPS_URL`, Cloudflare's resolver when unset.
+    pub fn from_env() -> Self {
+        Self {
+            url: env::DNS_OVER_HTTPS_URL.string(),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+/// Resolves through a DNS-over-HTTPS JSON API, so lookups need no resolver
+/// library or access to port 53.
+pub struct DohResolver {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl DohResolver {
+    pub fn new(config: DohConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            url: config.url,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(DohConfig::from_env())
+    }
+}
+
+#[async_trait]
+impl DnsResolver for DohResolver {
+    async fn txt_records(&self, name: &str) -> Result<Vec<String>, DnsError> {
+        let response: DohResponse = self
+            .client
+            .get(&self.url)
+            .query(&[("name", name), ("type", "TXT")])
+            .header("accept", "application/dns-json")
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| DnsError::Lookup(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| DnsError::Lookup(e.to_string()))?;
+
+        match response.status {
+            0 => Ok(response
+                .answer
+                .iter()
+                .filter(|answer| answer.record_type == TXT)
+                .map(|answer| txt_value(&answer.data))
+                .collect()),
+            NXDOMAIN => Ok(Vec::new()),
+            status => Err(DnsError::Lookup(format!("DNS response code {}", status))),
+        }
+    }
+}
+
+/// The value of a TXT record given as its quoted character strings, e.g.
+/// `"part one" "part two"`, which are concatenated.
+fn txt_value(data: &str) -> String {
+    if !data.starts_with('"') {
+        return data.to_string();
+    }
+    let mut value = String::new();
+    let mut chars = data.chars();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => value.extend(chars.next()),
+            c if quoted => value.push(c),
+            _ => {}
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_txt_value() {
+        assert_eq!(txt_value("\"apex=1\""), "apex=1");
+        assert_eq!(txt_value("\"part \" \"two\""), "part two");
+        assert_eq!(txt_value("\"say \\\"hi\\\"\""), "say \"hi\"");
+        assert_eq!(txt_value("unquoted"), "unquoted");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response: DohResponse = serde_json::from_str(
+            r#"{"Status": 0, "Answer": [
+                {"name": "x.", "type": 5, "TTL": 60, "data": "y."},
+                {"name": "y.", "type": 16, "TTL": 60, "data": "\"apex=1\""}]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.answer.len(), 2);
+        let nx: DohResponse = serde_json::from_str(r#"{"Status": 3}"#).unwrap();
+        assert_eq!((nx.status, nx.answer.len()), (NXDOMAIN, 0));
+    }
+}
@@ -0,0 +1,285 @@
+//! Custom domains - host names mapped to the organizations serving under them.
+//!
+//! Organizations add a domain, publish the TXT challenge it comes with and
+//! have it verified; from then on requests for that host resolve to the
+//! organization. Lookups are cached, including misses, since every request
+//! makes one.
+
+mod resolver;
+
+#[cfg(feature = "webhooks")]
+mod doh;
+
+pub use resolver::StaticDnsResolver;
+
+#[cfg(feature = "webhooks")]
+pub use doh::{DohConfig, DohResolver};
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use apex_core::domain::{CustomDomain, normalize_hostname};
+use apex_core::error::RepoError;
+use apex_core::ports::{Cache, CustomDomainError, CustomDomainRepository, DnsResolver};
+
+const KEY_PREFIX: &str = "tenant_domain:";
+
+/// Cached value for hosts that are not a verified custom domain.
+const NO_TENANT: &str = "-";
+
+/// Manages custom domains and resolves request hosts to organizations.
+pub struct TenantDomains {
+    repo: Arc<dyn CustomDomainRepository>,
+    cache: Arc<dyn Cache>,
+    resolver: Arc<dyn DnsResolver>,
+    ttl: Duration,
+}
+
+impl TenantDomains {
+    pub fn new(
+        repo: Arc<dyn CustomDomainRepository>,
+        cache: Arc<dyn Cache>,
+        resolver: Arc<dyn DnsResolver>,
+    ) -> Self {
+        Self {
+            repo,
+            cache,
+            resolver,
+            ttl: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Organization serving under `host` (without a port), if it is one's
+    /// verified domain.
+    pub async fn resolve(&self, host: &str) -> Result<Option<Uuid>, RepoError> {
+        let Ok(hostname) = normalize_hostname(host) else {
+            return Ok(None);
+        };
+        let key = format!("{}{}", KEY_PREFIX, hostname);
+        if let Some(cached) = self.cache.get(&key).await {
+            return Ok(cached.parse().ok());
+        }
+
+        let organization_id = self
+            .repo
+            .find_by_hostname(&hostname)
+            .await?
+            .filter(CustomDomain::is_verified)
+            .map(|domain| domain.organization_id);
+        let value = organization_id.map_or_else(|| NO_TENANT.to_string(), |id| id.to_string());
+        if let Err(e) = self.cache.set(&key, &value, Some(self.ttl)).await {
+            tracing::warn!(host = %hostname, error = %e, "Failed to cache tenant domain");
+        }
+        Ok(organization_id)
+    }
+
+    /// An organization's domains, oldest first.
+    pub async fn list(&self, organization_id: Uuid) -> Result<Vec<CustomDomain>, RepoError> {
+        self.repo.list_by_organization(organization_id).await
+    }
+
+    /// Add a domain to an organization, unverified. Adding one it already
+    /// has returns it; a domain only another organization verified is taken.
+    pub async fn add(
+        &self,
+        organization_id: Uuid,
+        hostname: &str,
+    ) -> Result<CustomDomain, CustomDomainError> {
+        let domain = CustomDomain::new(organization_id, hostname)?;
+        if let Some(existing) = self.repo.find_by_hostname(&domain.hostname).await? {
+            if existing.organization_id == organization_id {
+                return Ok(existing);
+            }
+            if existing.is_verified() {
+                return Err(CustomDomainError::Taken(existing.hostname));
+            }
+            // Claiming a domain without proving it does not keep others out
+            self.repo.delete(existing.id).await?;
+        }
+        Ok(self.repo.insert(domain).await?)
+    }
+
+    /// Check the domain's TXT challenge and mark it verified if it is met.
+    pub async fn verify(
+        &self,
+        organization_id: Uuid,
+        domain_id: Uuid,
+    ) -> Result<CustomDomain, CustomDomainError> {
+        let mut domain = self.find(organization_id, domain_id).await?;
+        if domain.is_verified() {
+            return Ok(domain);
+        }
+
+        let name = domain.challenge_name();
+        let records = self.resolver.txt_records(&name).await?;
+        if !domain.challenge_met(&records) {
+            return Err(CustomDomainError::ChallengeFailed {
+                name,
+                value: domain.challenge_value(),
+            });
+        }
+
+        domain.verified_at = Some(Utc::now());
+        let domain = self.repo.save(domain).await?;
+        self.invalidate(&domain.hostname).await;
+        tracing::info!(
+            org_id = %organization_id,
+            host = %domain.hostname,
+            "Custom domain verified"
+        );
+        Ok(domain)
+    }
+
+    /// Remove a domain; requests for it stop resolving to the organization.
+    pub async fn remove(
+        &self,
+        organization_id: Uuid,
+        domain_id: Uuid,
+    ) -> Result<(), CustomDomainError> {
+        let domain = self.find(organization_id, domain_id).await?;
+        self.repo.delete(domain.id).await?;
+        self.invalidate(&domain.hostname).await;
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        organization_id: Uuid,
+        domain_id: Uuid,
+    ) -> Result<CustomDomain, CustomDomainError> {
+        self.repo
+            .find_by_id(domain_id)
+            .await?
+            .filter(|domain| domain.organization_id == organization_id)
+            .ok_or(CustomDomainError::NotFound)
+    }
+
+    async fn invalidate(&self, hostname: &str) {
+        let key = format!("{}{}", KEY_PREFIX, hostname);
+        if let Err(e) = self.cache.delete(&key).await {
+            tracing::warn!(host = %hostname, error = %e, "Failed to invalidate tenant domain");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use apex_core::domain::{Page, PageRequest};
+    use apex_core::ports::BaseRepository;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryDomains {
+        domains: Mutex<HashMap<Uuid, CustomDomain>>,
+        lookups: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BaseRepository<CustomDomain, Uuid> for MemoryDomains {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<CustomDomain>, RepoError> {
+            Ok(self.domains.lock().await.get(&id).cloned())
+        }
+        async fn save(&self, domain: CustomDomain) -> Result<CustomDomain, RepoError> {
+            self.domains.lock().await.insert(domain.id, domain.clone());
+            Ok(domain)
+        }
+        async fn insert(&self, domain: CustomDomain) -> Result<CustomDomain, RepoError> {
+            self.save(domain).await
+        }
+        async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+            self.domains.lock().await.remove(&id);
+            Ok(())
+        }
+        async fn find_page(&self, request: PageRequest) -> Result<Page<CustomDomain>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl CustomDomainRepository for MemoryDomains {
+        async fn find_by_hostname(&self, host: &str) -> Result<Option<CustomDomain>, RepoError> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            let domains = self.domains.lock().await;
+            Ok(domains.values().find(|d| d.hostname == host).cloned())
+        }
+        async fn list_by_organization(&self, org: Uuid) -> Result<Vec<CustomDomain>, RepoError> {
+            let domains = self.domains.lock().await;
+            Ok(domains
+                .values()
+                .filter(|d| d.organization_id == org)
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn domains(dns: StaticDnsResolver) -> (TenantDomains, Arc<MemoryDomains>) {
+        let repo = Arc::new(MemoryDomains::default());
+        let domains =
+            TenantDomains::new(repo.clone(), Arc::new(InMemoryCache::new()), Arc::new(dns));
+        (domains, repo)
+    }
+
+    #[tokio::test]
+    async fn test_domains_resolve_once_verified() {
+        let dns = StaticDnsResolver::default();
+        let (domains, repo) = domains(dns.clone());
+        let org = Uuid::new_v4();
+
+        let domain = domains.add(org, "API.acme.com").await.unwrap();
+        assert_eq!(domains.resolve("api.acme.com").await.unwrap(), None);
+
+        match domains.verify(org, domain.id).await {
+            Err(CustomDomainError::ChallengeFailed { name, .. }) => {
+                assert_eq!(name, "_apex-challenge.api.acme.com")
+            }
+            other => panic!("expected a failed challenge, got {:?}", other),
+        }
+
+        dns.set_txt(&domain.challenge_name(), vec![domain.challenge_value()]);
+        assert!(domains.verify(org, domain.id).await.unwrap().is_verified());
+
+        // Verifying drops the cached miss
+        assert_eq!(domains.resolve("api.acme.com").await.unwrap(), Some(org));
+        let lookups = repo.lookups.load(Ordering::SeqCst);
+        assert_eq!(domains.resolve("api.acme.com").await.unwrap(), Some(org));
+        assert_eq!(repo.lookups.load(Ordering::SeqCst), lookups);
+
+        domains.remove(org, domain.id).await.unwrap();
+        assert_eq!(domains.resolve("api.acme.com").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_only_verified_domains_are_taken() {
+        let dns = StaticDnsResolver::default();
+        let (domains, _) = domains(dns.clone());
+        let (squatter, owner, late) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        domains.add(squatter, "acme.com").await.unwrap();
+        let domain = domains.add(owner, "acme.com").await.unwrap();
+        assert_eq!(domains.list(squatter).await.unwrap().len(), 0);
+
+        dns.set_txt(&domain.challenge_name(), vec![domain.challenge_value()]);
+        domains.verify(owner, domain.id).await.unwrap();
+        assert!(matches!(
+            domains.add(late, "acme.com").await,
+            Err(CustomDomainError::Taken(_))
+        ));
+        assert!(matches!(
+            domains.remove(late, domain.id).await,
+            Err(CustomDomainError::NotFound)
+        ));
+    }
+}
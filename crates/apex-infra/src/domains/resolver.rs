@@ -0,0 +1,36 @@
+//! Fixed DNS answers.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use apex_core::ports::{DnsError, DnsResolver};
+
+/// Answers TXT lookups from records set in process; for tests and setups
+/// without DNS access. Clones share their records.
+#[derive(Clone, Default)]
+pub struct StaticDnsResolver {
+    txt: Arc<Mutex<HashMap<String, Vec<String>>>>,
+}
+
+impl StaticDnsResolver {
+    /// Answer lookups of `name` with `values`.
+    pub fn set_txt(&self, name: &str, values: Vec<String>) {
+        self.txt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_ascii_lowercase(), values);
+    }
+}
+
+#[async_trait]
+impl DnsResolver for StaticDnsResolver {
+    async fn txt_records(&self, name: &str) -> Result<Vec<String>, DnsError> {
+        let txt = self.txt.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(txt
+            .get(&name.to_ascii_lowercase())
+            .cloned()
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,139 @@
+//! Plan-based entitlement checks.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use apex_core::domain::{Entitlement, Plan};
+use apex_core::error::RepoError;
+use apex_core::ports::{Cache, EntitlementError, PlanRepository};
+
+/// Resolves an account's plan, caching it, and checks entitlements against it.
+///
+/// Accounts without an assigned plan are on the free plan.
+pub struct EntitlementResolver {
+    repo: Arc<dyn PlanRepository>,
+    cache: Arc<dyn Cache>,
+    ttl: Duration,
+}
+
+impl EntitlementResolver {
+    pub fn new(repo: Arc<dyn PlanRepository>, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            repo,
+            cache,
+            ttl: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The account's current plan.
+    pub async fn plan_for(&self, account_id: Uuid) -> Result<Plan, RepoError> {
+        let key = cache_key(account_id);
+        if let Some(cached) = self.cache.get(&key).await
+            && let Ok(plan) = cached.parse()
+        {
+            return Ok(plan);
+        }
+
+        let plan = self.repo.get_plan(account_id).await?.unwrap_or_default();
+        self.cache_plan(&key, plan).await;
+        Ok(plan)
+    }
+
+    /// Assign a plan to an account.
+    pub async fn set_plan(&self, account_id: Uuid, plan: Plan) -> Result<(), RepoError> {
+        self.repo.set_plan(account_id, plan).await?;
+        self.cache_plan(&cache_key(account_id), plan).await;
+        Ok(())
+    }
+
+    /// Succeeds if the account's plan includes the entitlement.
+    pub async fn check(
+        &self,
+        account_id: Uuid,
+        entitlement: Entitlement,
+    ) -> Result<(), EntitlementError> {
+        let plan = self.plan_for(account_id).await?;
+        if plan.includes(entitlement) {
+            Ok(())
+        } else {
+            Err(EntitlementError::Missing { entitlement, plan })
+        }
+    }
+
+    async fn cache_plan(&self, key: &str, plan: Plan) {
+        if let Err(e) = self.cache.set(key, plan.as_str(), Some(self.ttl)).await {
+            tracing::warn!(error = %e, key = %key, "Failed to cache plan");
+        }
+    }
+}
+
+fn cache_key(account_id: Uuid) -> String {
+    format!("plan:{}", account_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryPlans {
+        plans: Mutex<HashMap<Uuid, Plan>>,
+        reads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl PlanRepository for MemoryPlans {
+        async fn get_plan(&self, account_id: Uuid) -> Result<Option<Plan>, RepoError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(self.plans.lock().await.get(&account_id).copied())
+        }
+
+        async fn set_plan(&self, account_id: Uuid, plan: Plan) -> Result<(), RepoError> {
+            self.plans.lock().await.insert(account_id, plan);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unassigned_account_is_on_free_plan() {
+        let repo = Arc::new(MemoryPlans::default());
+        let resolver = EntitlementResolver::new(repo.clone(), Arc::new(InMemoryCache::new()));
+        let account = Uuid::new_v4();
+
+        let result = resolver.check(account, Entitlement::Exports).await;
+
+        assert!(matches!(
+            result,
+            Err(EntitlementError::Missing {
+                entitlement: Entitlement::Exports,
+                plan: Plan::Free
+            })
+        ));
+        resolver.plan_for(account).await.unwrap();
+        assert_eq!(repo.reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_plan_takes_effect_immediately() {
+        let repo = Arc::new(MemoryPlans::default());
+        let resolver = EntitlementResolver::new(repo, Arc::new(InMemoryCache::new()));
+        let account = Uuid::new_v4();
+
+        assert_eq!(resolver.plan_for(account).await.unwrap(), Plan::Free);
+        resolver.set_plan(account, Plan::Pro).await.unwrap();
+
+        assert!(resolver.check(account, Entitlement::Exports).await.is_ok());
+    }
+}
@@ -0,0 +1,1077 @@
+//! Environment variable catalog.
+//!
+//! Every variable the infrastructure reads is declared here once, as an
+//! [`EnvVar`]: its name, type, default and the feature that reads it.
+//! Modules read through the typed accessors on the declaration, so a
+//! default is written in one place. The app declares its own variables the
+//! same way and puts both lists in a [`Catalog`], which checks the whole
+//! environment at startup and renders the Markdown reference printed by
+//! `api-server --env-reference`.
+//!
+//! An empty variable counts as unset. A value that does not parse falls
+//! back to the default when read; [`Catalog::validate`] is what reports it.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use EnvKind::{Decimal, Flag, Integer, List, OneOf, Text};
+
+/// What a variable holds, checked by [`Catalog::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvKind {
+    /// Free text, including the small formats parsed by the module.
+    Text,
+    /// `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off`.
+    Flag,
+    /// Non-negative whole number.
+    Integer,
+    /// Decimal number.
+    Decimal,
+    /// Comma separated values.
+    List,
+    /// One of the given values, ignoring case.
+    OneOf(&'static [&'static str]),
+}
+
+impl EnvKind {
+    fn check(&self, value: &str) -> Result<(), String> {
+        let ok = match self {
+            Self::Text | Self::List => true,
+            Self::Flag => parse_flag(value).is_some(),
+            Self::Integer => value.trim().parse::<u64>().is_ok(),
+            Self::Decimal => value.trim().parse::<f64>().is_ok(),
+            Self::OneOf(options) => options
+                .iter()
+                .any(|option| option.eq_ignore_ascii_case(value.trim())),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("expected {}", self))
+        }
+    }
+}
+
+impl fmt::Display for EnvKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Flag => write!(f, "flag"),
+            Self::Integer => write!(f, "integer"),
+            Self::Decimal => write!(f, "decimal"),
+            Self::List => write!(f, "list"),
+            Self::OneOf(options) => write!(f, "one of {}", options.join(", ")),
+        }
+    }
+}
+
+/// Whether a variable must be set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    Optional,
+    Always,
+    /// Only when [`RUST_ENV`] is `production` or `prod`.
+    InProduction,
+}
+
+/// A declared environment variable.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvVar {
+    pub name: &'static str,
+    pub kind: EnvKind,
+    pub default: Option<&'static str>,
+    /// Cargo feature whose code reads the variable, if any.
+    pub feature: Option<&'static str>,
+    pub requirement: Requirement,
+    pub description: &'static str,
+}
+
+impl EnvVar {
+    pub const fn new(name: &'static str, kind: EnvKind, description: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            default: None,
+            feature: None,
+            requirement: Requirement::Optional,
+            description,
+        }
+    }
+
+    pub const fn with_default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    pub const fn for_feature(mut self, feature: &'static str) -> Self {
+        self.feature = Some(feature);
+        self
+    }
+
+    pub const fn required(mut self) -> Self {
+        self.requirement = Requirement::Always;
+        self
+    }
+
+    pub const fn required_in_production(mut self) -> Self {
+        self.requirement = Requirement::InProduction;
+        self
+    }
+
+    /// The value as set, ignoring the default.
+    pub fn raw(&self) -> Option<String> {
+        std::env::var(self.name).ok().filter(|v| !v.is_empty())
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.raw().is_some()
+    }
+
+    /// The value as set, or the default.
+    pub fn value(&self) -> Option<String> {
+        self.raw().or_else(|| self.default.map(str::to_string))
+    }
+
+    /// The value or the default, empty when there is neither.
+    pub fn string(&self) -> String {
+        self.value().unwrap_or_default()
+    }
+
+    /// The value parsed, or the default parsed when unset or malformed.
+    pub fn parse<T: FromStr>(&self) -> Option<T> {
+        self.raw()
+            .and_then(|v| v.trim().parse().ok())
+            .or_else(|| self.default.and_then(|v| v.parse().ok()))
+    }
+
+    /// [`parse`](Self::parse), for variables with a default.
+    pub fn get<T: FromStr + Default>(&self) -> T {
+        self.parse().unwrap_or_default()
+    }
+
+    /// The value as a [`EnvKind::Flag`]: false when unset without a default.
+    pub fn flag(&self) -> bool {
+        self.raw()
+            .and_then(|v| parse_flag(&v))
+            .or_else(|| self.default.and_then(parse_flag))
+            .unwrap_or(false)
+    }
+
+    /// The value as a [`EnvKind::List`], trimmed and without empty entries.
+    pub fn list(&self) -> Vec<String> {
+        self.string()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// [`get`](Self::get) as a number of seconds.
+    pub fn secs(&self) -> Duration {
+        Duration::from_secs(self.get())
+    }
+
+    /// [`get`](Self::get) as a number of milliseconds.
+    pub fn millis(&self) -> Duration {
+        Duration::from_millis(self.get())
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether `RUST_ENV` names production.
+pub fn is_production() -> bool {
+    RUST_ENV
+        .raw()
+        .is_some_and(|env| env == "production" || env == "prod")
+}
+
+/// A variable that is missing or malformed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EnvError {
+    #[error("{name} is required")]
+    Missing { name: &'static str },
+    #[error("{name}={value:?}: {reason}")]
+    Invalid {
+        name: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+/// The variables an app reads, with the features it was built with.
+#[derive(Default)]
+pub struct Catalog {
+    vars: Vec<(EnvVar, bool)>,
+}
+
+impl Catalog {
+    /// The infrastructure's variables.
+    pub fn infra() -> Self {
+        Self::default().with(VARS, feature_enabled)
+    }
+
+    /// Add `vars`, read when `enabled` says their feature is built.
+    pub fn with(mut self, vars: &[EnvVar], enabled: fn(&str) -> bool) -> Self {
+        self.vars.extend(
+            vars.iter()
+                .map(|var| (*var, var.feature.is_none_or(enabled))),
+        );
+        self
+    }
+
+    /// Check every variable of the features built.
+    pub fn validate(&self) -> Result<(), Vec<EnvError>> {
+        self.validate_with(|name| std::env::var(name).ok())
+    }
+
+    fn validate_with(&self, lookup: impl Fn(&str) -> Option<String>) -> Result<(), Vec<EnvError>> {
+        let lookup = |name: &str| lookup(name).filter(|v| !v.is_empty());
+        let production =
+            lookup(RUST_ENV.name).is_some_and(|env| env == "production" || env == "prod");
+
+        let errors: Vec<EnvError> = self
+            .vars
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .filter_map(|(var, _)| match lookup(var.name) {
+                Some(value) => var
+                    .kind
+                    .check(&value)
+                    .err()
+                    .map(|reason| EnvError::Invalid {
+                        name: var.name,
+                        value,
+                        reason,
+                    }),
+                None => match var.requirement {
+                    Requirement::Always => Some(EnvError::Missing { name: var.name }),
+                    Requirement::InProduction if production => {
+                        Some(EnvError::Missing { name: var.name })
+                    }
+                    _ => None,
+                },
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Markdown reference of every variable, built or not.
+    pub fn reference(&self) -> String {
+        let mut vars: Vec<&EnvVar> = self.vars.iter().map(|(var, _)| var).collect();
+        vars.sort_by_key(|var| var.name);
+
+        let mut out = String::from(
+            "# Environment variables\n\n\
+             Generated by `api-server --env-reference`.\n\n\
+             | Variable | Type | Default | Required | Feature | Description |\n\
+             |---|---|---|---|---|---|\n",
+        );
+        for var in vars {
+            let required = match var.requirement {
+                Requirement::Optional => "",
+                Requirement::Always => "yes",
+                Requirement::InProduction => "in production",
+            };
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} | {} |\n",
+                var.name,
+                var.kind,
+                var.default.map(|d| format!("`{}`", d)).unwrap_or_default(),
+                required,
+                var.feature.map(|f| format!("`{}`", f)).unwrap_or_default(),
+                var.description.replace('|', "\\|"),
+            ));
+        }
+        out
+    }
+}
+
+/// Compile-time features of this build of the crate.
+const FEATURES: &[(&str, bool)] = &[
+    ("postgres", cfg!(feature = "postgres")),
+    ("auth", cfg!(feature = "auth")),
+    ("rate-limit", cfg!(feature = "rate-limit")),
+    ("redis", cfg!(feature = "redis")),
+    ("kafka", cfg!(feature = "kafka")),
+    ("mongo", cfg!(feature = "mongo")),
+    ("webhooks", cfg!(feature = "webhooks")),
+    ("billing", cfg!(feature = "billing")),
+    ("storage", cfg!(feature = "storage")),
+    ("encryption", cfg!(feature = "encryption")),
+];
+
+fn feature_enabled(feature: &str) -> bool {
+    FEATURES.contains(&(feature, true))
+}
+
+// General
+
+pub const RUST_ENV: EnvVar = EnvVar::new(
+    "RUST_ENV",
+    Text,
+    "Deployment environment; `production` or `prod` turns on production checks.",
+);
+pub const PUBLIC_URL: EnvVar = EnvVar::new(
+    "PUBLIC_URL",
+    Text,
+    "Public base URL, used in feeds, download links and robots.txt.",
+)
+.with_default("http://localhost:8080");
+
+// Redis
+
+pub const REDIS_URL: EnvVar = EnvVar::new(
+    "REDIS_URL",
+    Text,
+    "Redis server; the cache, job queue and pub/sub use Redis when set.",
+)
+.with_default("redis://localhost:6379")
+.for_feature("redis");
+pub const REDIS_CONNECT_TIMEOUT_SECS: EnvVar = EnvVar::new(
+    "REDIS_CONNECT_TIMEOUT_SECS",
+    Integer,
+    "Redis connect timeout.",
+)
+.with_default("5")
+.for_feature("redis");
+pub const REDIS_FALLBACK_TO_MEMORY: EnvVar = EnvVar::new(
+    "REDIS_FALLBACK_TO_MEMORY",
+    Flag,
+    "Use in-memory implementations while Redis is unreachable.",
+)
+.with_default("true")
+.for_feature("redis");
+
+// Authentication
+
+pub const JWT_SECRET: EnvVar =
+    EnvVar::new("JWT_SECRET", Text, "Secret access tokens are signed with.")
+        .with_default("change-me-in-production")
+        .for_feature("auth")
+        .required_in_production();
+pub const JWT_EXPIRATION_HOURS: EnvVar =
+    EnvVar::new("JWT_EXPIRATION_HOURS", Integer, "Access token lifetime.")
+        .with_default("24")
+        .for_feature("auth");
+pub const JWT_ISSUER: EnvVar = EnvVar::new("JWT_ISSUER", Text, "Issuer claim of access tokens.")
+    .with_default("apex-api")
+    .for_feature("auth");
+pub const PASSWORD_MIN_LENGTH: EnvVar = EnvVar::new(
+    "PASSWORD_MIN_LENGTH",
+    Integer,
+    "Shortest password accepted.",
+)
+.with_default("8")
+.for_feature("auth");
+pub const PASSWORD_HISTORY: EnvVar = EnvVar::new(
+    "PASSWORD_HISTORY",
+    Integer,
+    "Recent passwords, the current one included, a new password may not repeat; 0 turns the check off.",
+)
+.with_default("5")
+.for_feature("auth");
+pub const ADMIN_APPROVALS_REQUIRED: EnvVar = EnvVar::new(
+    "ADMIN_APPROVALS_REQUIRED",
+    Flag,
+    "Destructive admin actions wait for a second admin.",
+)
+.with_default("true");
+pub const ADMIN_APPROVAL_TTL_HOURS: EnvVar = EnvVar::new(
+    "ADMIN_APPROVAL_TTL_HOURS",
+    Integer,
+    "How long a pending admin action can be approved.",
+)
+.with_default("24");
+
+// Rate limiting
+
+pub const RATE_LIMIT_MAX_REQUESTS: EnvVar = EnvVar::new(
+    "RATE_LIMIT_MAX_REQUESTS",
+    Integer,
+    "Requests allowed per client per window.",
+)
+.with_default("100")
+.for_feature("rate-limit");
+pub const RATE_LIMIT_WINDOW_SECS: EnvVar =
+    EnvVar::new("RATE_LIMIT_WINDOW_SECS", Integer, "Rate limit window.")
+        .with_default("60")
+        .for_feature("rate-limit");
+pub const RATE_LIMIT_ALGORITHM: EnvVar = EnvVar::new(
+    "RATE_LIMIT_ALGORITHM",
+    OneOf(&["gcra", "fixed-window", "sliding-log", "token-bucket"]),
+    "Rate limiting algorithm.",
+)
+.with_default("gcra")
+.for_feature("rate-limit");
+pub const RATE_LIMIT_KEY_PREFIX: EnvVar = EnvVar::new(
+    "RATE_LIMIT_KEY_PREFIX",
+    Text,
+    "Prefix of the Redis rate limit keys.",
+)
+.with_default("ratelimit")
+.for_feature("rate-limit");
+pub const RATE_LIMIT_ROUTES: EnvVar = EnvVar::new(
+    "RATE_LIMIT_ROUTES",
+    Text,
+    "Per-route limits, e.g. `/api/auth/*=10/m`.",
+)
+.with_default("/api/auth/*=10/m")
+.for_feature("rate-limit");
+pub const RATE_LIMIT_TIERS: EnvVar = EnvVar::new(
+    "RATE_LIMIT_TIERS",
+    Text,
+    "Limits per tier, e.g. `anonymous=30/m,user=100/m`.",
+)
+.for_feature("rate-limit");
+pub const RATE_LIMIT_ALLOWLIST: EnvVar = EnvVar::new(
+    "RATE_LIMIT_ALLOWLIST",
+    List,
+    "IPs, CIDR blocks or `user:<id>` never limited.",
+)
+.for_feature("rate-limit");
+pub const RATE_LIMIT_DENYLIST: EnvVar = EnvVar::new(
+    "RATE_LIMIT_DENYLIST",
+    List,
+    "IPs, CIDR blocks or `user:<id>` always refused.",
+)
+.for_feature("rate-limit");
+pub const TRUSTED_PROXIES: EnvVar = EnvVar::new(
+    "TRUSTED_PROXIES",
+    List,
+    "Proxy IPs or CIDR blocks whose X-Forwarded-For/Forwarded headers are believed.",
+)
+.for_feature("rate-limit");
+pub const API_QUOTAS: EnvVar = EnvVar::new(
+    "API_QUOTAS",
+    Text,
+    "Request quotas per plan, e.g. `free=1000/day,pro=1000000/month`.",
+);
+
+// Jobs
+
+pub const JOB_QUEUE_NAME: EnvVar = EnvVar::new("JOB_QUEUE_NAME", Text, "Redis job queue name.")
+    .with_default("jobs")
+    .for_feature("redis");
+pub const JOB_STREAM_GROUP: EnvVar = EnvVar::new(
+    "JOB_STREAM_GROUP",
+    Text,
+    "Consumer group of the Redis stream job queue.",
+)
+.with_default("workers")
+.for_feature("redis");
+pub const JOB_STREAM_CONSUMER: EnvVar = EnvVar::new(
+    "JOB_STREAM_CONSUMER",
+    Text,
+    "Consumer name in the Redis stream job queue; random when unset.",
+)
+.for_feature("redis");
+pub const JOB_QUEUE_WORKERS: EnvVar =
+    EnvVar::new("JOB_QUEUE_WORKERS", Integer, "Concurrent job workers.").with_default("4");
+pub const JOB_QUEUE_POP_TIMEOUT: EnvVar = EnvVar::new(
+    "JOB_QUEUE_POP_TIMEOUT",
+    Integer,
+    "Seconds a Redis worker blocks waiting for a job.",
+)
+.with_default("5")
+.for_feature("redis");
+pub const JOB_QUEUE_VISIBILITY_TIMEOUT: EnvVar = EnvVar::new(
+    "JOB_QUEUE_VISIBILITY_TIMEOUT",
+    Integer,
+    "Seconds before a Redis job held by a dead worker is retried.",
+)
+.with_default("60")
+.for_feature("redis");
+pub const JOB_QUEUE_UNIQUE_TTL: EnvVar = EnvVar::new(
+    "JOB_QUEUE_UNIQUE_TTL",
+    Integer,
+    "Seconds a unique job key is held in Redis.",
+)
+.with_default("3600")
+.for_feature("redis");
+pub const JOB_QUEUE_RESULT_TTL: EnvVar = EnvVar::new(
+    "JOB_QUEUE_RESULT_TTL",
+    Integer,
+    "Seconds job results are kept.",
+)
+.with_default("3600");
+pub const JOB_QUEUE_DEAD_LETTER_LIMIT: EnvVar = EnvVar::new(
+    "JOB_QUEUE_DEAD_LETTER_LIMIT",
+    Integer,
+    "Failed jobs kept in the dead letter queue.",
+)
+.with_default("1000");
+pub const JOB_QUEUE_MAX_SIZE: EnvVar = EnvVar::new(
+    "JOB_QUEUE_MAX_SIZE",
+    Integer,
+    "Jobs the in-memory queue holds before refusing more.",
+)
+.with_default("10000");
+pub const JOB_TYPE_CONCURRENCY: EnvVar = EnvVar::new(
+    "JOB_TYPE_CONCURRENCY",
+    Text,
+    "Concurrent runs per job type, e.g. `report=2`.",
+);
+pub const JOB_TYPE_RATE_LIMITS: EnvVar = EnvVar::new(
+    "JOB_TYPE_RATE_LIMITS",
+    Text,
+    "Start rate per job type, e.g. `email=10/s`.",
+);
+pub const LEGAL_HOLD_JOB_TYPES: EnvVar = EnvVar::new(
+    "LEGAL_HOLD_JOB_TYPES",
+    List,
+    "Job types refused for users under legal hold; the erasure jobs when unset.",
+);
+pub const JOB_ENCRYPTION_KEY: EnvVar = EnvVar::new(
+    "JOB_ENCRYPTION_KEY",
+    Text,
+    "Base64 32-byte key job payloads are sealed with (or `JOB_ENCRYPTION_KEY_FILE`).",
+)
+.for_feature("encryption");
+pub const JOB_ENCRYPTION_PREVIOUS_KEYS: EnvVar = EnvVar::new(
+    "JOB_ENCRYPTION_PREVIOUS_KEYS",
+    List,
+    "Retired job payload keys, still used to open (or `JOB_ENCRYPTION_PREVIOUS_KEYS_FILE`).",
+)
+.for_feature("encryption");
+
+// Pub/sub
+
+pub const PUBSUB_BUFFER_SIZE: EnvVar = EnvVar::new(
+    "PUBSUB_BUFFER_SIZE",
+    Integer,
+    "Messages buffered per in-memory subscriber.",
+)
+.with_default("100");
+pub const PUBSUB_OVERFLOW: EnvVar = EnvVar::new(
+    "PUBSUB_OVERFLOW",
+    Text,
+    "When a subscriber's buffer is full: `drop-oldest`, `drop-newest`, `disconnect` or `block:<ms>`.",
+)
+.with_default("drop-oldest");
+pub const PUBSUB_CHANNEL_OVERFLOW: EnvVar = EnvVar::new(
+    "PUBSUB_CHANNEL_OVERFLOW",
+    Text,
+    "Overflow policy per channel, e.g. `billing=block:500`.",
+);
+pub const PUBSUB_DURABLE_CHANNELS: EnvVar = EnvVar::new(
+    "PUBSUB_DURABLE_CHANNELS",
+    List,
+    "Channels delivered through Redis streams, at least once.",
+)
+.for_feature("redis");
+pub const PUBSUB_GROUP: EnvVar = EnvVar::new(
+    "PUBSUB_GROUP",
+    Text,
+    "Consumer group of the durable channels.",
+)
+.with_default("pubsub")
+.for_feature("redis");
+pub const PUBSUB_CONSUMER: EnvVar = EnvVar::new(
+    "PUBSUB_CONSUMER",
+    Text,
+    "Consumer name in the durable channels' group; random when unset.",
+)
+.for_feature("redis");
+pub const PUBSUB_BLOCK_TIMEOUT_MS: EnvVar = EnvVar::new(
+    "PUBSUB_BLOCK_TIMEOUT_MS",
+    Integer,
+    "How long a durable channel read blocks.",
+)
+.with_default("5000")
+.for_feature("redis");
+pub const PUBSUB_CLAIM_IDLE_MS: EnvVar = EnvVar::new(
+    "PUBSUB_CLAIM_IDLE_MS",
+    Integer,
+    "Idle time before a dead consumer's messages are claimed.",
+)
+.with_default("30000")
+.for_feature("redis");
+pub const PUBSUB_MAX_DELIVERIES: EnvVar = EnvVar::new(
+    "PUBSUB_MAX_DELIVERIES",
+    Integer,
+    "Deliveries before a durable message is dropped.",
+)
+.with_default("5")
+.for_feature("redis");
+pub const PUBSUB_STREAM_MAX_LEN: EnvVar = EnvVar::new(
+    "PUBSUB_STREAM_MAX_LEN",
+    Integer,
+    "Approximate length durable channel streams are trimmed to.",
+)
+.with_default("10000")
+.for_feature("redis");
+
+// Kafka
+
+pub const KAFKA_BROKERS: EnvVar = EnvVar::new("KAFKA_BROKERS", List, "Kafka bootstrap servers.")
+    .with_default("localhost:9092")
+    .for_feature("kafka");
+pub const KAFKA_CLIENT_ID: EnvVar = EnvVar::new("KAFKA_CLIENT_ID", Text, "Kafka client id.")
+    .with_default("apex")
+    .for_feature("kafka");
+pub const KAFKA_GROUP_ID: EnvVar =
+    EnvVar::new("KAFKA_GROUP_ID", Text, "Default Kafka consumer group.")
+        .with_default("apex")
+        .for_feature("kafka");
+pub const KAFKA_TOPIC_PREFIX: EnvVar = EnvVar::new(
+    "KAFKA_TOPIC_PREFIX",
+    Text,
+    "Prefix of the topic a channel maps to.",
+)
+.for_feature("kafka");
+pub const KAFKA_TOPICS: EnvVar = EnvVar::new(
+    "KAFKA_TOPICS",
+    Text,
+    "Topic per channel, e.g. `events=apex.events`.",
+)
+.for_feature("kafka");
+pub const KAFKA_GROUPS: EnvVar = EnvVar::new(
+    "KAFKA_GROUPS",
+    Text,
+    "Consumer group per channel, e.g. `events=analytics`.",
+)
+.for_feature("kafka");
+pub const KAFKA_COMMIT: EnvVar = EnvVar::new(
+    "KAFKA_COMMIT",
+    OneOf(&["interval", "message"]),
+    "Commit offsets periodically or after every message.",
+)
+.with_default("interval")
+.for_feature("kafka");
+pub const KAFKA_COMMIT_INTERVAL_MS: EnvVar = EnvVar::new(
+    "KAFKA_COMMIT_INTERVAL_MS",
+    Integer,
+    "Offset commit interval.",
+)
+.with_default("5000")
+.for_feature("kafka");
+pub const KAFKA_OFFSET_RESET: EnvVar = EnvVar::new(
+    "KAFKA_OFFSET_RESET",
+    Text,
+    "Where a new consumer group starts reading.",
+)
+.with_default("latest")
+.for_feature("kafka");
+pub const KAFKA_SEND_TIMEOUT_MS: EnvVar =
+    EnvVar::new("KAFKA_SEND_TIMEOUT_MS", Integer, "Kafka produce timeout.")
+        .with_default("5000")
+        .for_feature("kafka");
+
+// MongoDB
+
+pub const MONGODB_URI: EnvVar = EnvVar::new("MONGODB_URI", Text, "MongoDB connection string.")
+    .with_default("mongodb://localhost:27017")
+    .for_feature("mongo");
+pub const MONGODB_DATABASE: EnvVar = EnvVar::new("MONGODB_DATABASE", Text, "MongoDB database.")
+    .with_default("apex")
+    .for_feature("mongo");
+pub const MONGODB_CONNECT_TIMEOUT_SECS: EnvVar = EnvVar::new(
+    "MONGODB_CONNECT_TIMEOUT_SECS",
+    Integer,
+    "MongoDB connect timeout.",
+)
+.with_default("5")
+.for_feature("mongo");
+
+// Column encryption
+
+pub const COLUMN_ENCRYPTION_KEY: EnvVar = EnvVar::new(
+    "COLUMN_ENCRYPTION_KEY",
+    Text,
+    "Base64 32-byte key sensitive columns are sealed with (or `COLUMN_ENCRYPTION_KEY_FILE`).",
+)
+.for_feature("postgres");
+pub const COLUMN_ENCRYPTION_PREVIOUS_KEYS: EnvVar = EnvVar::new(
+    "COLUMN_ENCRYPTION_PREVIOUS_KEYS",
+    List,
+    "Retired column keys, still used to open (or `COLUMN_ENCRYPTION_PREVIOUS_KEYS_FILE`).",
+)
+.for_feature("postgres");
+
+// Database tools
+
+pub const SQL_CONSOLE_ENABLED: EnvVar = EnvVar::new(
+    "SQL_CONSOLE_ENABLED",
+    Flag,
+    "Admin read-only SQL console over the secondary databases.",
+)
+.with_default("false")
+.for_feature("postgres");
+pub const SQL_CONSOLE_ROW_LIMIT: EnvVar = EnvVar::new(
+    "SQL_CONSOLE_ROW_LIMIT",
+    Integer,
+    "Rows returned by a console query.",
+)
+.with_default("500")
+.for_feature("postgres");
+pub const SQL_CONSOLE_TIMEOUT_MS: EnvVar = EnvVar::new(
+    "SQL_CONSOLE_TIMEOUT_MS",
+    Integer,
+    "Console statement timeout.",
+)
+.with_default("5000")
+.for_feature("postgres");
+pub const REPORTS_FILE: EnvVar =
+    EnvVar::new("REPORTS_FILE", Text, "JSON file of the scheduled reports.").for_feature("storage");
+pub const REPORT_ROW_LIMIT: EnvVar =
+    EnvVar::new("REPORT_ROW_LIMIT", Integer, "Rows in a generated report.")
+        .with_default("100000")
+        .for_feature("storage");
+pub const REPORT_TIMEOUT_SECS: EnvVar =
+    EnvVar::new("REPORT_TIMEOUT_SECS", Integer, "Report statement timeout.")
+        .with_default("60")
+        .for_feature("storage");
+pub const REPORT_LINK_TTL_HOURS: EnvVar = EnvVar::new(
+    "REPORT_LINK_TTL_HOURS",
+    Integer,
+    "Lifetime of report download links.",
+)
+.with_default("168")
+.for_feature("storage");
+
+// Storage
+
+pub const STORAGE_DIR: EnvVar = EnvVar::new("STORAGE_DIR", Text, "Directory files are stored in.")
+    .with_default("data/storage")
+    .for_feature("storage");
+pub const STORAGE_SIGNING_SECRET: EnvVar = EnvVar::new(
+    "STORAGE_SIGNING_SECRET",
+    Text,
+    "Secret download links are signed with.",
+)
+.with_default("change-me-in-production")
+.for_feature("storage")
+.required_in_production();
+pub const STORAGE_QUOTAS: EnvVar = EnvVar::new(
+    "STORAGE_QUOTAS",
+    Text,
+    "Storage allowed per plan, e.g. `free=1GiB,pro=100GiB`.",
+)
+.with_default("free=1GiB,pro=100GiB");
+
+// Outbound
+
+pub const WEBHOOK_TIMEOUT_SECS: EnvVar = EnvVar::new(
+    "WEBHOOK_TIMEOUT_SECS",
+    Integer,
+    "Outbound webhook request timeout.",
+)
+.with_default("10")
+.for_feature("webhooks");
+pub const STRIPE_WEBHOOK_SECRET: EnvVar = EnvVar::new(
+    "STRIPE_WEBHOOK_SECRET",
+    Text,
+    "Stripe webhook signing secret; Stripe webhooks are refused when unset.",
+)
+.for_feature("billing");
+pub const NOTIFICATION_LOG_SIZE: EnvVar = EnvVar::new(
+    "NOTIFICATION_LOG_SIZE",
+    Integer,
+    "Notifications kept per user.",
+)
+.with_default("1000");
+pub const SSE_REPLAY_SIZE: EnvVar = EnvVar::new(
+    "SSE_REPLAY_SIZE",
+    Integer,
+    "Events kept per user for event streams resuming with `Last-Event-ID`.",
+)
+.with_default("100");
+pub const SSE_LINGER_SECS: EnvVar = EnvVar::new(
+    "SSE_LINGER_SECS",
+    Integer,
+    "How long a user's events are still kept after their last event stream closed.",
+)
+.with_default("300");
+pub const DNS_OVER_HTTPS_URL: EnvVar = EnvVar::new(
+    "DNS_OVER_HTTPS_URL",
+    Text,
+    "DNS-over-HTTPS resolver custom domains are verified with.",
+)
+.with_default("https://cloudflare-dns.com/dns-query");
+
+// Health
+
+pub const DEPENDENCY_CHECK_INTERVAL_SECS: EnvVar = EnvVar::new(
+    "DEPENDENCY_CHECK_INTERVAL_SECS",
+    Integer,
+    "How often the database and Redis are probed.",
+)
+.with_default("15");
+pub const DEPENDENCY_CHECK_TIMEOUT_SECS: EnvVar =
+    EnvVar::new("DEPENDENCY_CHECK_TIMEOUT_SECS", Integer, "Probe timeout.").with_default("5");
+pub const DEPENDENCY_FAILURE_THRESHOLD: EnvVar = EnvVar::new(
+    "DEPENDENCY_FAILURE_THRESHOLD",
+    Integer,
+    "Failed probes in a row before a dependency is degraded.",
+)
+.with_default("2");
+pub const DEPENDENCY_MAX_BACKOFF_SECS: EnvVar = EnvVar::new(
+    "DEPENDENCY_MAX_BACKOFF_SECS",
+    Integer,
+    "Longest wait between probes of a degraded dependency.",
+)
+.with_default("120");
+
+// Shadow traffic
+
+pub const SHADOW_URL: EnvVar = EnvVar::new(
+    "SHADOW_URL",
+    Text,
+    "Base URL requests are mirrored to; mirroring is off when unset.",
+);
+pub const SHADOW_SAMPLE_RATE: EnvVar = EnvVar::new(
+    "SHADOW_SAMPLE_RATE",
+    Decimal,
+    "Share of requests mirrored, from 0 to 1.",
+)
+.with_default("0.01");
+pub const SHADOW_METHODS: EnvVar =
+    EnvVar::new("SHADOW_METHODS", List, "HTTP methods mirrored.").with_default("GET,HEAD");
+pub const SHADOW_MAX_BODY_BYTES: EnvVar = EnvVar::new(
+    "SHADOW_MAX_BODY_BYTES",
+    Integer,
+    "Largest response body compared.",
+)
+.with_default("65536");
+pub const SHADOW_DIFF_LIMIT: EnvVar =
+    EnvVar::new("SHADOW_DIFF_LIMIT", Integer, "Differences kept.").with_default("500");
+pub const SHADOW_TIMEOUT_MS: EnvVar =
+    EnvVar::new("SHADOW_TIMEOUT_MS", Integer, "Mirrored request timeout.").with_default("5000");
+
+// Public content
+
+pub const FEED_TITLE: EnvVar =
+    EnvVar::new("FEED_TITLE", Text, "Title of the RSS feed.").with_default("Apex");
+pub const FEED_DESCRIPTION: EnvVar =
+    EnvVar::new("FEED_DESCRIPTION", Text, "Description of the RSS feed.")
+        .with_default("Latest posts");
+pub const FEED_MAX_ITEMS: EnvVar =
+    EnvVar::new("FEED_MAX_ITEMS", Integer, "Posts in the RSS feed.").with_default("50");
+pub const FEED_CACHE_SECS: EnvVar = EnvVar::new(
+    "FEED_CACHE_SECS",
+    Integer,
+    "How long the feed and sitemap are cached.",
+)
+.with_default("300");
+pub const TERMS_VERSION: EnvVar = EnvVar::new(
+    "TERMS_VERSION",
+    Text,
+    "Terms of service version users must accept.",
+);
+pub const PRIVACY_POLICY_VERSION: EnvVar = EnvVar::new(
+    "PRIVACY_POLICY_VERSION",
+    Text,
+    "Privacy policy version users must accept.",
+);
+pub const SECURITY_CONTACT: EnvVar = EnvVar::new(
+    "SECURITY_CONTACT",
+    List,
+    "security.txt contacts; security.txt is served when set.",
+);
+pub const SECURITY_TXT_EXPIRES: EnvVar = EnvVar::new(
+    "SECURITY_TXT_EXPIRES",
+    Text,
+    "RFC 3339 expiry of security.txt; a year from startup when unset.",
+);
+pub const SECURITY_POLICY_URL: EnvVar = EnvVar::new(
+    "SECURITY_POLICY_URL",
+    Text,
+    "Security policy linked from security.txt.",
+);
+pub const ROBOTS_TXT_PATH: EnvVar =
+    EnvVar::new("ROBOTS_TXT_PATH", Text, "File served as robots.txt.");
+pub const WELL_KNOWN_DIR: EnvVar = EnvVar::new(
+    "WELL_KNOWN_DIR",
+    Text,
+    "Directory of extra files served under /.well-known.",
+);
+
+/// Every variable read by this crate.
+pub static VARS: &[EnvVar] = &[
+    RUST_ENV,
+    PUBLIC_URL,
+    REDIS_URL,
+    REDIS_CONNECT_TIMEOUT_SECS,
+    REDIS_FALLBACK_TO_MEMORY,
+    JWT_SECRET,
+    JWT_EXPIRATION_HOURS,
+    JWT_ISSUER,
+    PASSWORD_MIN_LENGTH,
+    PASSWORD_HISTORY,
+    ADMIN_APPROVALS_REQUIRED,
+    ADMIN_APPROVAL_TTL_HOURS,
+    RATE_LIMIT_MAX_REQUESTS,
+    RATE_LIMIT_WINDOW_SECS,
+    RATE_LIMIT_ALGORITHM,
+    RATE_LIMIT_KEY_PREFIX,
+    RATE_LIMIT_ROUTES,
+    RATE_LIMIT_TIERS,
+    RATE_LIMIT_ALLOWLIST,
+    RATE_LIMIT_DENYLIST,
+    TRUSTED_PROXIES,
+    API_QUOTAS,
+    JOB_QUEUE_NAME,
+    JOB_STREAM_GROUP,
+    JOB_STREAM_CONSUMER,
+    JOB_QUEUE_WORKERS,
+    JOB_QUEUE_POP_TIMEOUT,
+    JOB_QUEUE_VISIBILITY_TIMEOUT,
+    JOB_QUEUE_UNIQUE_TTL,
+    JOB_QUEUE_RESULT_TTL,
+    JOB_QUEUE_DEAD_LETTER_LIMIT,
+    JOB_QUEUE_MAX_SIZE,
+    JOB_TYPE_CONCURRENCY,
+    JOB_TYPE_RATE_LIMITS,
+    LEGAL_HOLD_JOB_TYPES,
+    JOB_ENCRYPTION_KEY,
+    JOB_ENCRYPTION_PREVIOUS_KEYS,
+    PUBSUB_BUFFER_SIZE,
+    PUBSUB_OVERFLOW,
+    PUBSUB_CHANNEL_OVERFLOW,
+    PUBSUB_DURABLE_CHANNELS,
+    PUBSUB_GROUP,
+    PUBSUB_CONSUMER,
+    PUBSUB_BLOCK_TIMEOUT_MS,
+    PUBSUB_CLAIM_IDLE_MS,
+    PUBSUB_MAX_DELIVERIES,
+    PUBSUB_STREAM_MAX_LEN,
+    KAFKA_BROKERS,
+    KAFKA_CLIENT_ID,
+    KAFKA_GROUP_ID,
+    KAFKA_TOPIC_PREFIX,
+    KAFKA_TOPICS,
+    KAFKA_GROUPS,
+    KAFKA_COMMIT,
+    KAFKA_COMMIT_INTERVAL_MS,
+    KAFKA_OFFSET_RESET,
+    KAFKA_SEND_TIMEOUT_MS,
+    MONGODB_URI,
+    MONGODB_DATABASE,
+    MONGODB_CONNECT_TIMEOUT_SECS,
+    COLUMN_ENCRYPTION_KEY,
+    COLUMN_ENCRYPTION_PREVIOUS_KEYS,
+    SQL_CONSOLE_ENABLED,
+    SQL_CONSOLE_ROW_LIMIT,
+    SQL_CONSOLE_TIMEOUT_MS,
+    REPORTS_FILE,
+    REPORT_ROW_LIMIT,
+    REPORT_TIMEOUT_SECS,
+    REPORT_LINK_TTL_HOURS,
+    STORAGE_DIR,
+    STORAGE_SIGNING_SECRET,
+    STORAGE_QUOTAS,
+    WEBHOOK_TIMEOUT_SECS,
+    STRIPE_WEBHOOK_SECRET,
+    NOTIFICATION_LOG_SIZE,
+    SSE_REPLAY_SIZE,
+    SSE_LINGER_SECS,
+    DNS_OVER_HTTPS_URL,
+    DEPENDENCY_CHECK_INTERVAL_SECS,
+    DEPENDENCY_CHECK_TIMEOUT_SECS,
+    DEPENDENCY_FAILURE_THRESHOLD,
+    DEPENDENCY_MAX_BACKOFF_SECS,
+    SHADOW_URL,
+    SHADOW_SAMPLE_RATE,
+    SHADOW_METHODS,
+    SHADOW_MAX_BODY_BYTES,
+    SHADOW_DIFF_LIMIT,
+    SHADOW_TIMEOUT_MS,
+    FEED_TITLE,
+    FEED_DESCRIPTION,
+    FEED_MAX_ITEMS,
+    FEED_CACHE_SECS,
+    TERMS_VERSION,
+    PRIVACY_POLICY_VERSION,
+    SECURITY_CONTACT,
+    SECURITY_TXT_EXPIRES,
+    SECURITY_POLICY_URL,
+    ROBOTS_TXT_PATH,
+    WELL_KNOWN_DIR,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const PORT: EnvVar = EnvVar::new("TEST_PORT", Integer, "Port.").with_default("8080");
+    const MODE: EnvVar = EnvVar::new("TEST_MODE", OneOf(&["a", "b"]), "Mode.");
+    const SECRET: EnvVar = EnvVar::new("TEST_SECRET", Text, "Secret.").required_in_production();
+    const KEY: EnvVar = EnvVar::new("TEST_KEY", Text, "Key.")
+        .required()
+        .for_feature("not-built");
+
+    fn validate(vars: &[(&str, &str)]) -> Result<(), Vec<EnvError>> {
+        let env: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Catalog::default()
+            .with(&[PORT, MODE, SECRET, KEY], feature_enabled)
+            .validate_with(|name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn test_validate_reports_malformed_and_missing_variables() {
+        assert_eq!(validate(&[("TEST_PORT", "80"), ("TEST_MODE", "B")]), Ok(()));
+
+        let errors = validate(&[("TEST_PORT", "eighty"), ("TEST_MODE", "c")]).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[0],
+            EnvError::Invalid {
+                name: "TEST_PORT",
+                ..
+            }
+        ));
+
+        // Required in production only, and never for a feature not built
+        assert_eq!(
+            validate(&[("RUST_ENV", "production")]),
+            Err(vec![EnvError::Missing {
+                name: "TEST_SECRET"
+            }])
+        );
+        assert_eq!(
+            validate(&[("RUST_ENV", "production"), ("TEST_SECRET", "s")]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_kinds_check_values() {
+        assert!(Flag.check("On").is_ok());
+        assert!(Flag.check("maybe").is_err());
+        assert!(Integer.check("-1").is_err());
+        assert!(Decimal.check("0.5").is_ok());
+    }
+
+    #[test]
+    fn test_catalog_names_are_unique_and_documented() {
+        let mut names: Vec<&str> = VARS.iter().map(|var| var.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), VARS.len());
+
+        let reference = Catalog::infra().reference();
+        assert!(VARS.iter().all(|var| reference.contains(var.name)));
+        assert!(VARS.iter().all(|var| match (var.kind, var.default) {
+            (kind, Some(default)) => kind.check(default).is_ok(),
+            _ => true,
+        }));
+    }
+}
@@ -0,0 +1,153 @@
+//! Domain events fanned out to pub/sub and the job queue.
+//!
+//! Every event is published on [`DOMAIN_EVENTS_CHANNEL`] for in-process
+//! reactions that are cheap and may be lost, like dropping a cached feed,
+//! and on the [`user_channel`] of the user it is about, for their realtime
+//! clients.
+//! Work that must happen, like sending an email, is routed to a job type
+//! instead and gets the queue's retries and dead-lettering.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use uuid::Uuid;
+
+use apex_core::domain::DomainEvent;
+use apex_core::ports::{EventDispatcher, Job, JobQueue, PubSub};
+
+use crate::pubsub::TypedPubSub;
+
+/// Channel every domain event is published on, as an envelope named after
+/// the event.
+pub const DOMAIN_EVENTS_CHANNEL: &str = "domain_events";
+
+/// Channel of the events about one user, e.g. `user:<id>`.
+pub fn user_channel(user_id: Uuid) -> String {
+    format!("user:{}", user_id)
+}
+
+/// Publishes events and enqueues the jobs routed to them.
+pub struct EventFanOut<P, Q> {
+    pubsub: Arc<TypedPubSub<P>>,
+    jobs: Arc<Q>,
+    /// Job types enqueued for each event name.
+    routes: Vec<(&'static str, String)>,
+}
+
+impl<P: PubSub, Q: JobQueue> EventFanOut<P, Q> {
+    pub fn new(pubsub: Arc<TypedPubSub<P>>, jobs: Arc<Q>) -> Self {
+        Self {
+            pubsub,
+            jobs,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Enqueue a `job_type` job for every `event` (e.g. `user.registered`),
+    /// with the event as its payload.
+    pub fn with_job(mut self, event: &'static str, job_type: impl Into<String>) -> Self {
+        self.routes.push((event, job_type.into()));
+        self
+    }
+}
+
+#[async_trait]
+impl<P: PubSub + 'static, Q: JobQueue + 'static> EventDispatcher for EventFanOut<P, Q> {
+    async fn dispatch(&self, event: DomainEvent) {
+        let name = event.name();
+        if let Err(e) = self
+            .pubsub
+            .publish_json(DOMAIN_EVENTS_CHANNEL, name, &event)
+            .await
+        {
+            tracing::warn!(event = name, error = %e, "Failed to publish domain event");
+        }
+        if let Err(e) = self
+            .pubsub
+            .publish_json(&user_channel(event.user_id()), name, &event)
+            .await
+        {
+            tracing::warn!(event = name, error = %e, "Failed to publish user event");
+        }
+
+        for (_, job_type) in self.routes.iter().filter(|(routed, _)| *routed == name) {
+            let payload = match serde_json::to_value(&event) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::error!(event = name, error = %e, "Failed to serialize domain event");
+                    return;
+                }
+            };
+            let job = Job::new(job_type.as_str(), payload).for_account(event.user_id());
+            if let Err(e) = self.jobs.enqueue(job).await {
+                tracing::error!(event = name, job_type = %job_type, error = %e, "Failed to enqueue job for domain event");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::{InMemoryJobQueue, InMemoryJobQueueConfig};
+    use crate::pubsub::InMemoryPubSub;
+    use apex_core::ports::Envelope;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_events_are_published_and_routed_to_jobs() {
+        let pubsub = Arc::new(TypedPubSub::new(Arc::new(InMemoryPubSub::new(16)), "test"));
+        let queue = Arc::new(InMemoryJobQueue::new(InMemoryJobQueueConfig::default()));
+        let events =
+            EventFanOut::new(pubsub.clone(), queue.clone()).with_job("user.registered", "welcome");
+
+        let user_id = Uuid::new_v4();
+        let (tx, mut rx) = mpsc::channel(4);
+        let (user_tx, mut user_rx) = mpsc::channel(4);
+        let mut subscriptions = Vec::new();
+        for (channel, tx) in [
+            (DOMAIN_EVENTS_CHANNEL.to_string(), tx),
+            (user_channel(user_id), user_tx),
+        ] {
+            let subscription = pubsub
+                .subscribe_json(channel.as_str(), move |envelope: Envelope<DomainEvent>| {
+                    let tx = tx.clone();
+                    async move { tx.send(envelope).await.unwrap() }
+                })
+                .await
+                .unwrap();
+            subscriptions.push(subscription);
+        }
+
+        let registered = DomainEvent::UserRegistered {
+            user_id,
+            email: "jane@example.com".parse().unwrap(),
+        };
+        events.dispatch(registered.clone()).await;
+        events
+            .dispatch(DomainEvent::PostCreated {
+                post_id: Uuid::new_v4(),
+                user_id,
+            })
+            .await;
+
+        let envelope = rx.recv().await.unwrap();
+        assert_eq!(envelope.event, "user.registered");
+        assert_eq!(envelope.data, registered);
+        assert_eq!(rx.recv().await.unwrap().event, "post.created");
+        // Also on the user's own channel
+        assert_eq!(user_rx.recv().await.unwrap().data, registered);
+        assert_eq!(user_rx.recv().await.unwrap().event, "post.created");
+
+        // Only the routed event became a job
+        let pending = queue.list_pending(None, 10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].job_type, "welcome");
+        assert_eq!(pending[0].account_id, Some(user_id));
+        assert_eq!(
+            serde_json::from_value::<DomainEvent>(pending[0].payload.clone()).unwrap(),
+            registered
+        );
+    }
+}
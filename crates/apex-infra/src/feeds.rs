@@ -0,0 +1,335 @@
+//! Public sitemap and RSS feed of published posts.
+//!
+//! Both are rendered from the most recently published posts and cached for a
+//! few minutes, so crawlers polling them don't reach the database. Each
+//! rendering carries an entity tag and the time its newest post changed, for
+//! conditional GETs.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use apex_core::domain::{DomainEvent, Post};
+use apex_core::error::RepoError;
+use apex_core::ports::{Cache, Envelope, PostRepository, PubSub, PubSubError, SubscriptionHandle};
+
+use crate::env;
+use crate::events::DOMAIN_EVENTS_CHANNEL;
+use crate::pubsub::TypedPubSub;
+
+const KEY_PREFIX: &str = "feed:";
+
+/// Most URLs a sitemap file may list.
+const SITEMAP_MAX_URLS: u64 = 50_000;
+
+/// Characters of a post's content quoted as its feed item description.
+const SUMMARY_CHARS: usize = 280;
+
+/// Sitemap and feed configuration.
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    /// Site posts are linked under, as `{site_url}/posts/{id}`.
+    pub site_url: String,
+    pub title: String,
+    pub description: String,
+    /// Posts in the RSS feed.
+    pub max_items: u64,
+    /// How long a rendering is served from the cache.
+    pub ttl: Duration,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            site_url: "http://localhost:8080".to_string(),
+            title: "Apex".to_string(),
+            description: "Latest posts".to_string(),
+            max_items: 50,
+            ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+impl FeedConfig {
+    /// `PUBLIC_URL`, `FEED_TITLE`, `FEED_DESCRIPTION`, `FEED_MAX_ITEMS` and
+    /// `FEED_CACHE_SECS`.
+    pub fn from_env() -> Self {
+        Self {
+            site_url: env::PUBLIC_URL.string().trim_end_matches('/').to_string(),
+            title: env::FEED_TITLE.string(),
+            description: env::FEED_DESCRIPTION.string(),
+            max_items: env::FEED_MAX_ITEMS.get(),
+            ttl: env::FEED_CACHE_SECS.secs(),
+        }
+    }
+
+    fn post_url(&self, post: &Post) -> String {
+        format!("{}/posts/{}", self.site_url, post.id)
+    }
+}
+
+/// A rendered sitemap or feed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedFeed {
+    pub body: String,
+    /// Quoted entity tag of the body.
+    pub etag: String,
+    /// When the newest listed post last changed; `None` when none is listed.
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+impl RenderedFeed {
+    fn new(body: String, posts: &[Post]) -> Self {
+        Self {
+            etag: format!("\"{:016x}\"", fnv1a(body.as_bytes())),
+            last_modified: posts.iter().map(|post| post.updated_at).max(),
+            body,
+        }
+    }
+
+    /// Whether a client holding the rendering its conditional headers
+    /// describe already has this one. `If-None-Match` takes precedence over
+    /// `If-Modified-Since`, as in RFC 9110.
+    pub fn not_modified(
+        &self,
+        if_none_match: Option<&str>,
+        if_modified_since: Option<DateTime<Utc>>,
+    ) -> bool {
+        if let Some(tags) = if_none_match {
+            return tags
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == self.etag);
+        }
+        match (if_modified_since, self.last_modified) {
+            // HTTP dates have whole seconds
+            (Some(since), Some(modified)) => modified.timestamp() <= since.timestamp(),
+            _ => false,
+        }
+    }
+}
+
+/// Renders and caches the sitemap and feed.
+pub struct PublicFeeds {
+    posts: Arc<dyn PostRepository>,
+    cache: Arc<dyn Cache>,
+    config: FeedConfig,
+}
+
+impl PublicFeeds {
+    pub fn new(posts: Arc<dyn PostRepository>, cache: Arc<dyn Cache>, config: FeedConfig) -> Self {
+        Self {
+            posts,
+            cache,
+            config,
+        }
+    }
+
+    /// Sitemap of published posts.
+    pub async fn sitemap(&self) -> Result<RenderedFeed, RepoError> {
+        self.cached("sitemap", SITEMAP_MAX_URLS, |posts| {
+            render_sitemap(&self.config, posts)
+        })
+        .await
+    }
+
+    /// RSS 2.0 feed of the latest published posts.
+    pub async fn rss(&self) -> Result<RenderedFeed, RepoError> {
+        self.cached("rss", self.config.max_items, |posts| {
+            render_rss(&self.config, posts, Utc::now())
+        })
+        .await
+    }
+
+    /// Drop the cached renderings, e.g. after a post was published.
+    pub async fn invalidate(&self) {
+        for name in ["sitemap", "rss"] {
+            let key = format!("{}{}", KEY_PREFIX, name);
+            if let Err(e) = self.cache.delete(&key).await {
+                tracing::warn!(feed = %name, error = %e, "Failed to invalidate feed");
+            }
+        }
+    }
+
+    /// Invalidate on every domain event that changes the public posts.
+    pub async fn follow_events<P: PubSub + 'static>(
+        self: Arc<Self>,
+        pubsub: &TypedPubSub<P>,
+    ) -> Result<SubscriptionHandle, PubSubError> {
+        pubsub
+            .subscribe_json(
+                DOMAIN_EVENTS_CHANNEL,
+                move |envelope: Envelope<DomainEvent>| {
+                    let feeds = self.clone();
+                    async move {
+                        if envelope.data.changes_public_posts() {
+                            feeds.invalidate().await;
+                        }
+                    }
+                },
+            )
+            .await
+    }
+
+    async fn cached(
+        &self,
+        name: &str,
+        limit: u64,
+        render: impl FnOnce(&[Post]) -> String,
+    ) -> Result<RenderedFeed, RepoError> {
+        let key = format!("{}{}", KEY_PREFIX, name);
+        if let Some(feed) = self
+            .cache
+            .get(&key)
+            .await
+            .and_then(|json| serde_json::from_str(&json).ok())
+        {
+            return Ok(feed);
+        }
+
+        let posts = self.posts.list_published(Utc::now(), limit).await?;
+        let feed = RenderedFeed::new(render(&posts), &posts);
+        let json = serde_json::to_string(&feed).expect("feeds serialize");
+        if let Err(e) = self.cache.set(&key, &json, Some(self.config.ttl)).await {
+            tracing::warn!(feed = %name, error = %e, "Failed to cache feed");
+        }
+        Ok(feed)
+    }
+}
+
+/// Sitemap listing `posts`, each with when it last changed.
+pub fn render_sitemap(config: &FeedConfig, posts: &[Post]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for post in posts {
+        xml.push_str(&format!(
+            "  <url><loc>{}</loc><lastmod>{}</lastmod></url>\n",
+            escape(&config.post_url(post)),
+            post.updated_at.format("%Y-%m-%dT%H:%M:%SZ")
+        ));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+/// RSS 2.0 feed of `posts`, built at `now`.
+pub fn render_rss(config: &FeedConfig, posts: &[Post], now: DateTime<Utc>) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n\
+         <channel>\n\
+         \x20 <title>{}</title>\n\
+         \x20 <link>{}</link>\n\
+         \x20 <description>{}</description>\n\
+         \x20 <atom:link href=\"{}/feed.xml\" rel=\"self\" type=\"application/rss+xml\"/>\n\
+         \x20 <lastBuildDate>{}</lastBuildDate>\n",
+        escape(&config.title),
+        escape(&config.site_url),
+        escape(&config.description),
+        escape(&config.site_url),
+        now.to_rfc2822()
+    );
+    for post in posts {
+        let url = escape(&config.post_url(post));
+        xml.push_str(&format!(
+            "  <item>\n\
+             \x20   <title>{}</title>\n\
+             \x20   <link>{}</link>\n\
+             \x20   <guid isPermaLink=\"true\">{}</guid>\n\
+             \x20   <pubDate>{}</pubDate>\n\
+             \x20   <description>{}</description>\n\
+             \x20 </item>\n",
+            escape(&post.title),
+            url,
+            url,
+            post.published_at.unwrap_or(post.created_at).to_rfc2822(),
+            escape(&summary(&post.content))
+        ));
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// The start of `content`, cut at a character boundary.
+fn summary(content: &str) -> String {
+    match content.char_indices().nth(SUMMARY_CHARS) {
+        Some((end, _)) => format!("{}…", content[..end].trim_end()),
+        None => content.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 64-bit FNV-1a, stable across processes so instances agree on tags.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn post(title: &str, content: &str) -> Post {
+        let mut post = Post::new(Uuid::new_v4(), title.to_string(), content.to_string());
+        post.publish(post.created_at);
+        post
+    }
+
+    #[test]
+    fn test_render_escapes_and_links_posts() {
+        let config = FeedConfig {
+            site_url: "https://example.com".to_string(),
+            ..Default::default()
+        };
+        let posts = vec![post("Tips & <tricks>", &"a".repeat(300))];
+
+        let sitemap = render_sitemap(&config, &posts);
+        let url = format!("https://example.com/posts/{}", posts[0].id);
+        assert!(sitemap.contains(&format!("<loc>{}</loc>", url)));
+
+        let rss = render_rss(&config, &posts, Utc::now());
+        assert!(rss.contains("<title>Tips &amp; &lt;tricks&gt;</title>"));
+        assert!(rss.contains(&format!("<guid isPermaLink=\"true\">{}</guid>", url)));
+        assert!(rss.contains(&format!("{}…</description>", "a".repeat(SUMMARY_CHARS))));
+    }
+
+    #[test]
+    fn test_not_modified() {
+        let posts = vec![post("Title", "Content")];
+        let feed = RenderedFeed::new("<rss/>".to_string(), &posts);
+        let modified = feed.last_modified.unwrap();
+
+        assert!(feed.not_modified(Some(&feed.etag), None));
+        assert!(feed.not_modified(Some(&format!("\"x\", W/{}", feed.etag)), None));
+        assert!(!feed.not_modified(Some("\"x\""), Some(modified)));
+        assert!(feed.not_modified(None, Some(modified)));
+        assert!(!feed.not_modified(None, Some(modified - chrono::Duration::seconds(1))));
+        assert!(!feed.not_modified(None, None));
+
+        let empty = RenderedFeed::new("<rss/>".to_string(), &[]);
+        assert_eq!(empty.etag, feed.etag);
+        assert!(!empty.not_modified(None, Some(modified)));
+    }
+}
@@ -0,0 +1,71 @@
+//! Database probes.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use apex_core::ports::DependencyProbe;
+
+use crate::database::DatabaseConnections;
+
+/// Pings one database of [`DatabaseConnections`].
+///
+/// The pools drop connections that fail and open new ones on demand, so a
+/// ping after an outage is also the reconnect: nothing needs rebuilding.
+/// Pools opened lazily, while the database was unreachable, connect the
+/// same way.
+pub struct DatabaseProbe {
+    db: Arc<DatabaseConnections>,
+    /// Secondary database to ping; the main one when `None`.
+    secondary: Option<String>,
+    name: String,
+}
+
+impl DatabaseProbe {
+    /// Probe of the main database, reported as `database`.
+    pub fn main(db: Arc<DatabaseConnections>) -> Self {
+        Self {
+            db,
+            secondary: None,
+            name: "database".to_string(),
+        }
+    }
+
+    /// Probe of a secondary database, reported as `database:<name>`.
+    pub fn secondary(db: Arc<DatabaseConnections>, name: &str) -> Self {
+        Self {
+            db,
+            secondary: Some(name.to_string()),
+            name: format!("database:{}", name),
+        }
+    }
+
+    /// Probes of the main database and every secondary one.
+    pub fn all(db: &Arc<DatabaseConnections>) -> Vec<Self> {
+        std::iter::once(Self::main(db.clone()))
+            .chain(
+                db.secondary_names()
+                    .into_iter()
+                    .map(|name| Self::secondary(db.clone(), name)),
+            )
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DependencyProbe for DatabaseProbe {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let conn = match &self.secondary {
+            None => self.db.main.as_ref(),
+            Some(name) => self
+                .db
+                .get(name)
+                .ok_or_else(|| format!("No secondary database named {}", name))?,
+        };
+        conn.ping().await.map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,35 @@
+//! Job queue probe.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use apex_core::ports::{DependencyProbe, JobQueue};
+
+/// Asks a job queue for its stats, reported as `job_queue`. For queues
+/// backed by Redis this is a round trip to it; the in-memory queue always
+/// answers unless it is shutting down.
+pub struct JobQueueProbe<Q> {
+    queue: Arc<Q>,
+}
+
+impl<Q: JobQueue> JobQueueProbe<Q> {
+    pub fn new(queue: Arc<Q>) -> Self {
+        Self { queue }
+    }
+}
+
+#[async_trait]
+impl<Q: JobQueue + 'static> DependencyProbe for JobQueueProbe<Q> {
+    fn name(&self) -> &str {
+        "job_queue"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        self.queue
+            .stats()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
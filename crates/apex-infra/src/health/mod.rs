@@ -0,0 +1,459 @@
+//! Dependency health watchdog.
+//!
+//! Probes the database, Redis and any other [`DependencyProbe`] on an
+//! interval. A failed check triggers the probe's reconnect, so a broken pool
+//! or connection manager is rebuilt without restarting the process. A
+//! dependency still failing after `failure_threshold` consecutive rounds is
+//! flagged degraded; the first round it answers again, it is flagged
+//! healthy and a recovery event is emitted.
+//!
+//! A degraded dependency is not hammered: each failed round doubles the
+//! rounds it sits out before the next check, up to `max_backoff`.
+//!
+//! Readiness probes check the same dependencies on demand instead, through a
+//! [`HealthCheckRegistry`].
+
+#[cfg(feature = "postgres")]
+mod database;
+mod jobs;
+mod readiness;
+#[cfg(feature = "redis")]
+mod redis;
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+
+use apex_core::ports::DependencyProbe;
+
+use crate::env;
+
+#[cfg(feature = "postgres")]
+pub use database::DatabaseProbe;
+pub use jobs::JobQueueProbe;
+pub use readiness::{CheckResult, HealthCheckRegistry};
+#[cfg(feature = "redis")]
+pub use redis::RedisProbe;
+
+/// Watchdog configuration.
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    /// Time between two rounds of checks.
+    pub interval: Duration,
+    /// How long a check, or a reconnect, may take before it counts as failed.
+    pub timeout: Duration,
+    /// Consecutive failed rounds before a healthy dependency is flagged
+    /// degraded. A dependency that has never answered is degraded on its
+    /// first failure.
+    pub failure_threshold: u32,
+    /// Longest wait between two checks of a degraded dependency.
+    pub max_backoff: Duration,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            timeout: Duration::from_secs(5),
+            failure_threshold: 2,
+            max_backoff: Duration::from_secs(120),
+        }
+    }
+}
+
+impl WatchdogConfig {
+    /// Load configuration from environment variables.
+    pub fn from_env() -> Self {
+        Self {
+            interval: env::DEPENDENCY_CHECK_INTERVAL_SECS.secs(),
+            timeout: env::DEPENDENCY_CHECK_TIMEOUT_SECS.secs(),
+            failure_threshold: env::DEPENDENCY_FAILURE_THRESHOLD.get::<u32>().max(1),
+            max_backoff: env::DEPENDENCY_MAX_BACKOFF_SECS.secs(),
+        }
+    }
+
+    /// Rounds a degraded dependency sits out after failing `failures` times
+    /// in a row since it was degraded: 0, 1, 3, 7... up to `max_backoff`.
+    fn rounds_to_skip(&self, failures: u32) -> u32 {
+        let max = (self.max_backoff.as_secs_f64() / self.interval.as_secs_f64().max(1.0)) as u32;
+        2u32.saturating_pow(failures)
+            .saturating_sub(1)
+            .min(max.saturating_sub(1))
+    }
+}
+
+/// A dependency changing state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyEvent {
+    /// It stopped answering, and reconnecting did not help.
+    Degraded { dependency: String, error: String },
+    /// It answers again after being degraded for `down_for`.
+    Recovered {
+        dependency: String,
+        down_for: Duration,
+    },
+}
+
+/// Where a dependency stands, as of its last check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub degraded: bool,
+    pub consecutive_failures: u32,
+    /// Why the last failed check failed.
+    pub last_error: Option<String>,
+    pub last_checked: Option<DateTime<Utc>>,
+    /// When it was last flagged degraded or healthy.
+    pub since: DateTime<Utc>,
+}
+
+struct Tracked {
+    status: DependencyStatus,
+    ever_up: bool,
+    degraded_at: Option<Instant>,
+    /// Rounds left before the next check, while degraded.
+    skip: u32,
+}
+
+/// Probes dependencies on an interval, reconnecting and flagging them.
+pub struct DependencyWatchdog {
+    config: WatchdogConfig,
+    probes: Vec<Arc<dyn DependencyProbe>>,
+    tracked: RwLock<HashMap<String, Tracked>>,
+    events: broadcast::Sender<DependencyEvent>,
+}
+
+impl DependencyWatchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self {
+            config,
+            probes: Vec::new(),
+            tracked: RwLock::new(HashMap::new()),
+            events: broadcast::channel(64).0,
+        }
+    }
+
+    /// Watch another dependency.
+    pub fn with_probe(mut self, probe: Arc<dyn DependencyProbe>) -> Self {
+        let name = probe.name().to_string();
+        self.tracked.get_mut().unwrap().insert(
+            name.clone(),
+            Tracked {
+                status: DependencyStatus {
+                    name,
+                    degraded: false,
+                    consecutive_failures: 0,
+                    last_error: None,
+                    last_checked: None,
+                    since: Utc::now(),
+                },
+                ever_up: false,
+                degraded_at: None,
+                skip: 0,
+            },
+        );
+        self.probes.push(probe);
+        self
+    }
+
+    /// Degradation and recovery events from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<DependencyEvent> {
+        self.events.subscribe()
+    }
+
+    /// Whether `name` is flagged degraded. Unknown dependencies are not.
+    pub fn is_degraded(&self, name: &str) -> bool {
+        self.tracked
+            .read()
+            .unwrap()
+            .get(name)
+            .is_some_and(|tracked| tracked.status.degraded)
+    }
+
+    /// Every watched dependency, by name.
+    pub fn statuses(&self) -> Vec<DependencyStatus> {
+        let mut statuses: Vec<_> = self
+            .tracked
+            .read()
+            .unwrap()
+            .values()
+            .map(|tracked| tracked.status.clone())
+            .collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+
+    /// Check every dependency once, reconnecting those that fail. Degraded
+    /// dependencies backing off are skipped.
+    pub async fn check_all(&self) {
+        for probe in &self.probes {
+            if self.backing_off(probe.name()) {
+                continue;
+            }
+            let result = match self.bounded(probe.check()).await {
+                Ok(()) => Ok(()),
+                Err(error) => {
+                    tracing::debug!(
+                        dependency = %probe.name(),
+                        %error,
+                        "Check failed, reconnecting"
+                    );
+                    self.bounded(probe.reconnect()).await
+                }
+            };
+            self.record(probe.name(), result);
+        }
+    }
+
+    /// Whether `name` sits this round out, counting the round.
+    fn backing_off(&self, name: &str) -> bool {
+        let mut tracked = self.tracked.write().unwrap();
+        match tracked.get_mut(name) {
+            Some(tracked) if tracked.skip > 0 => {
+                tracked.skip -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    async fn bounded(&self, check: impl Future<Output = Result<(), String>>) -> Result<(), String> {
+        tokio::time::timeout(self.config.timeout, check)
+            .await
+            .unwrap_or_else(|_| Err("Timed out".to_string()))
+    }
+
+    fn record(&self, name: &str, result: Result<(), String>) {
+        let mut tracked = self.tracked.write().unwrap();
+        let Some(tracked) = tracked.get_mut(name) else {
+            return;
+        };
+        let now = Utc::now();
+        let status = &mut tracked.status;
+        status.last_checked = Some(now);
+
+        let event = match result {
+            Ok(()) => {
+                tracked.ever_up = true;
+                tracked.skip = 0;
+                status.consecutive_failures = 0;
+                if !status.degraded {
+                    return;
+                }
+                status.degraded = false;
+                status.since = now;
+                let down_for = tracked
+                    .degraded_at
+                    .take()
+                    .map(|at| at.elapsed())
+                    .unwrap_or_default();
+                tracing::info!(
+                    dependency = %name,
+                    down_secs = down_for.as_secs(),
+                    "Dependency recovered"
+                );
+                DependencyEvent::Recovered {
+                    dependency: name.to_string(),
+                    down_for,
+                }
+            }
+            Err(error) => {
+                status.consecutive_failures += 1;
+                status.last_error = Some(error.clone());
+                let threshold = if tracked.ever_up {
+                    self.config.failure_threshold
+                } else {
+                    1
+                };
+                if status.degraded {
+                    tracked.skip = self
+                        .config
+                        .rounds_to_skip(status.consecutive_failures.saturating_sub(threshold));
+                    return;
+                }
+                if status.consecutive_failures < threshold {
+                    return;
+                }
+                status.degraded = true;
+                status.since = now;
+                tracked.degraded_at = Some(Instant::now());
+                tracing::warn!(dependency = %name, %error, "Dependency degraded");
+                DependencyEvent::Degraded {
+                    dependency: name.to_string(),
+                    error,
+                }
+            }
+        };
+        // Nobody listening is fine
+        let _ = self.events.send(event);
+    }
+
+    /// Spawn the check loop, first round right away. Does nothing without
+    /// probes.
+    pub fn start(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        if self.probes.is_empty() {
+            return None;
+        }
+
+        tracing::info!(
+            dependencies = self.probes.len(),
+            interval_secs = self.config.interval.as_secs(),
+            "Dependency watchdog started"
+        );
+
+        let watchdog = self.clone();
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(watchdog.config.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                watchdog.check_all().await;
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Answers as told, counting reconnects.
+    struct Flaky {
+        up: Mutex<bool>,
+        heals_on_reconnect: bool,
+        reconnects: AtomicU32,
+    }
+
+    impl Flaky {
+        fn new(up: bool, heals_on_reconnect: bool) -> Arc<Self> {
+            Arc::new(Self {
+                up: Mutex::new(up),
+                heals_on_reconnect,
+                reconnects: AtomicU32::new(0),
+            })
+        }
+
+        fn set_up(&self, up: bool) {
+            *self.up.lock().unwrap() = up;
+        }
+    }
+
+    #[async_trait]
+    impl DependencyProbe for Flaky {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn check(&self) -> Result<(), String> {
+            if *self.up.lock().unwrap() {
+                Ok(())
+            } else {
+                Err("Connection refused".to_string())
+            }
+        }
+
+        async fn reconnect(&self) -> Result<(), String> {
+            self.reconnects.fetch_add(1, Ordering::SeqCst);
+            if self.heals_on_reconnect {
+                self.set_up(true);
+            }
+            self.check().await
+        }
+    }
+
+    fn watchdog(probe: Arc<Flaky>) -> DependencyWatchdog {
+        DependencyWatchdog::new(WatchdogConfig {
+            failure_threshold: 2,
+            ..WatchdogConfig::default()
+        })
+        .with_probe(probe)
+    }
+
+    #[tokio::test]
+    async fn test_degrades_after_threshold_and_recovers() {
+        let probe = Flaky::new(true, false);
+        let watchdog = watchdog(probe.clone());
+        let mut events = watchdog.subscribe();
+
+        watchdog.check_all().await;
+        probe.set_up(false);
+        watchdog.check_all().await;
+        // One failure of a dependency that was up is not enough
+        assert!(!watchdog.is_degraded("flaky"));
+        watchdog.check_all().await;
+        assert!(watchdog.is_degraded("flaky"));
+        assert_eq!(probe.reconnects.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DependencyEvent::Degraded { ref dependency, .. } if dependency == "flaky"
+        ));
+
+        probe.set_up(true);
+        watchdog.check_all().await;
+        assert!(!watchdog.is_degraded("flaky"));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DependencyEvent::Recovered { .. }
+        ));
+        let status = &watchdog.statuses()[0];
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.last_error.as_deref(), Some("Connection refused"));
+    }
+
+    #[tokio::test]
+    async fn test_degraded_dependency_is_checked_less_and_less_often() {
+        let probe = Flaky::new(false, false);
+        let watchdog = DependencyWatchdog::new(WatchdogConfig {
+            interval: Duration::from_secs(10),
+            max_backoff: Duration::from_secs(80),
+            ..WatchdogConfig::default()
+        })
+        .with_probe(probe.clone());
+
+        let mut checked = Vec::new();
+        for round in 1..=24 {
+            let before = probe.reconnects.load(Ordering::SeqCst);
+            watchdog.check_all().await;
+            if probe.reconnects.load(Ordering::SeqCst) > before {
+                checked.push(round);
+            }
+        }
+        // Every round until degraded, then waits of 1, 2, 4 and at most 8 rounds
+        assert_eq!(checked, vec![1, 2, 4, 8, 16, 24]);
+
+        // Back up: checked again after its wait, then every round
+        probe.set_up(true);
+        for _ in 0..8 {
+            watchdog.check_all().await;
+        }
+        assert!(!watchdog.is_degraded("flaky"));
+        assert_eq!(watchdog.tracked.read().unwrap()["flaky"].skip, 0);
+    }
+
+    #[tokio::test]
+    async fn test_never_reachable_dependency_degrades_at_once() {
+        let probe = Flaky::new(false, false);
+        let watchdog = watchdog(probe);
+
+        watchdog.check_all().await;
+        assert!(watchdog.is_degraded("flaky"));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_keeps_a_dependency_healthy() {
+        let probe = Flaky::new(false, true);
+        let watchdog = watchdog(probe.clone());
+        let mut events = watchdog.subscribe();
+
+        watchdog.check_all().await;
+        assert!(!watchdog.is_degraded("flaky"));
+        assert_eq!(probe.reconnects.load(Ordering::SeqCst), 1);
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Readiness checks.
+//!
+//! Where the watchdog probes in the background and remembers the outcome,
+//! a [`HealthCheckRegistry`] pings every registered dependency when asked,
+//! all at once, so a readiness probe reflects the moment it is asked. Any
+//! [`DependencyProbe`] can be registered; the watchdog's probes are usually
+//! shared, so a connection it rebuilds is the one checked here.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+
+use apex_core::ports::DependencyProbe;
+
+/// Outcome of one dependency's check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub name: String,
+    /// Why the check failed; `None` when the dependency answered.
+    pub error: Option<String>,
+    pub latency: Duration,
+}
+
+impl CheckResult {
+    pub fn is_up(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Dependencies a readiness probe checks.
+pub struct HealthCheckRegistry {
+    /// How long a check may take before it counts as failed.
+    timeout: Duration,
+    checks: Vec<Arc<dyn DependencyProbe>>,
+}
+
+impl HealthCheckRegistry {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Check another dependency.
+    pub fn with_check(mut self, check: Arc<dyn DependencyProbe>) -> Self {
+        self.checks.push(check);
+        self
+    }
+
+    /// Check every dependency now, concurrently, in registration order.
+    /// Nothing is reconnected: that is the watchdog's job.
+    pub async fn check_all(&self) -> Vec<CheckResult> {
+        join_all(self.checks.iter().map(|check| async move {
+            let started = Instant::now();
+            let result = tokio::time::timeout(self.timeout, check.check())
+                .await
+                .unwrap_or_else(|_| Err("Timed out".to_string()));
+            CheckResult {
+                name: check.name().to_string(),
+                error: result.err(),
+                latency: started.elapsed(),
+            }
+        }))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct Fixed {
+        name: &'static str,
+        result: Result<(), String>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl DependencyProbe for Fixed {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn check(&self) -> Result<(), String> {
+            tokio::time::sleep(self.delay).await;
+            self.result.clone()
+        }
+    }
+
+    fn fixed(name: &'static str, result: Result<(), String>, delay_ms: u64) -> Arc<Fixed> {
+        Arc::new(Fixed {
+            name,
+            result,
+            delay: Duration::from_millis(delay_ms),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_reports_every_check_with_failures_and_timeouts() {
+        let registry = HealthCheckRegistry::new(Duration::from_millis(50))
+            .with_check(fixed("database", Ok(()), 0))
+            .with_check(fixed("redis", Err("Connection refused".to_string()), 0))
+            .with_check(fixed("job_queue", Ok(()), 1_000));
+
+        let results = registry.check_all().await;
+
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["database", "redis", "job_queue"]);
+        assert!(results[0].is_up());
+        assert_eq!(results[1].error.as_deref(), Some("Connection refused"));
+        assert_eq!(results[2].error.as_deref(), Some("Timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_checks_run_concurrently() {
+        let registry = HealthCheckRegistry::new(Duration::from_secs(1))
+            .with_check(fixed("a", Ok(()), 100))
+            .with_check(fixed("b", Ok(()), 100))
+            .with_check(fixed("c", Ok(()), 100));
+
+        let started = Instant::now();
+        let results = registry.check_all().await;
+
+        assert!(results.iter().all(CheckResult::is_up));
+        assert!(started.elapsed() < Duration::from_millis(250));
+    }
+}
@@ -0,0 +1,62 @@
+//! Redis probe.
+
+use async_trait::async_trait;
+use redis::Client;
+use redis::aio::ConnectionManager;
+use tokio::sync::Mutex;
+
+use apex_core::ports::DependencyProbe;
+
+use crate::cache::RedisConfig;
+
+/// Pings Redis, reported as `redis`.
+///
+/// A connection manager that never connected, or stopped answering, is
+/// replaced with a new one on reconnect instead of waiting for it to
+/// recover by itself.
+pub struct RedisProbe {
+    config: RedisConfig,
+    conn: Mutex<Option<ConnectionManager>>,
+}
+
+impl RedisProbe {
+    /// Probe of the Redis at `config.url`. Connects on the first check.
+    pub fn new(config: RedisConfig) -> Self {
+        Self {
+            config,
+            conn: Mutex::new(None),
+        }
+    }
+
+    async fn connect(&self) -> Result<ConnectionManager, String> {
+        let client = Client::open(self.config.url.as_str()).map_err(|e| e.to_string())?;
+        tokio::time::timeout(self.config.connect_timeout, ConnectionManager::new(client))
+            .await
+            .map_err(|_| "Connection timed out".to_string())?
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl DependencyProbe for RedisProbe {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let Some(mut conn) = self.conn.lock().await.clone() else {
+            return Err("Not connected".to_string());
+        };
+        redis::cmd("PING")
+            .query_async::<String>(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn reconnect(&self) -> Result<(), String> {
+        let conn = self.connect().await?;
+        *self.conn.lock().await = Some(conn);
+        self.check().await
+    }
+}
@@ -0,0 +1,245 @@
+//! Job payload encryption at rest.
+//!
+//! Payloads often carry emails and tokens. With [`PayloadEncryption`]
+//! registered as middleware, a queue stores every payload sealed with
+//! AES-256-GCM and opens it again just before the handler runs, so the
+//! backend, the dead letter queue and the admin listings only hold
+//! ciphertext. Ids, types and the other job fields stay readable for
+//! routing and inspection.
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use apex_core::ports::{Job, JobQueueError, JobResult, SecretsError, SecretsProvider};
+
+use super::middleware::{JobMiddleware, Next};
+
+/// Secret holding the key new payloads are sealed with: 32 bytes, base64.
+pub const KEY_SECRET: &str = "JOB_ENCRYPTION_KEY";
+/// Secret holding retired keys (comma separated), still tried when opening
+/// so jobs enqueued before a rotation can run.
+pub const PREVIOUS_KEYS_SECRET: &str = "JOB_ENCRYPTION_PREVIOUS_KEYS";
+
+/// Field of the object a sealed payload is stored as.
+const SEALED_FIELD: &str = "$sealed";
+
+/// Payload encryption errors.
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadEncryptionError {
+    #[error("Invalid job encryption key: {0}")]
+    InvalidKey(String),
+
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+
+    #[error("Failed to seal job payload")]
+    Seal,
+
+    /// Tampered with, sealed for another job type, or sealed with a key
+    /// that is no longer configured.
+    #[error("Failed to open job payload")]
+    Open,
+}
+
+/// Seals payloads on enqueue and opens them before the handler runs.
+///
+/// Register it before middleware that reads payloads, so they see them
+/// opened. Payloads enqueued before encryption was turned on run as they
+/// are; sealed payloads that no configured key opens fail the job for good.
+pub struct PayloadEncryption {
+    /// The first key seals; all of them are tried when opening.
+    keys: Vec<LessSafeKey>,
+    rng: SystemRandom,
+}
+
+impl PayloadEncryption {
+    /// Encrypt with `key`, and open payloads sealed with it or with one of
+    /// the `previous` keys. Keys are 32 bytes.
+    pub fn new(key: &[u8], previous: &[Vec<u8>]) -> Result<Self, PayloadEncryptionError> {
+        let keys = std::iter::once(key)
+            .chain(previous.iter().map(Vec::as_slice))
+            .map(|key| {
+                UnboundKey::new(&AES_256_GCM, key)
+                    .map(LessSafeKey::new)
+                    .map_err(|_| {
+                        PayloadEncryptionError::InvalidKey(format!(
+                            "expected 32 bytes, got {}",
+                            key.len()
+                        ))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            keys,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Keys from [`KEY_SECRET`] and [`PREVIOUS_KEYS_SECRET`]; `None` when no
+    /// key is configured, which leaves payloads in plaintext.
+    pub async fn from_secrets(
+        secrets: &dyn SecretsProvider,
+    ) -> Result<Option<Self>, PayloadEncryptionError> {
+        let Some(key) = secrets.secret(KEY_SECRET).await? else {
+            return Ok(None);
+        };
+        let previous = secrets
+            .secret(PREVIOUS_KEYS_SECRET)
+            .await?
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(decode_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(&decode_key(&key)?, &previous).map(Some)
+    }
+
+    /// Whether `payload` is a sealed one.
+    pub fn is_sealed(payload: &serde_json::Value) -> bool {
+        payload
+            .as_object()
+            .is_some_and(|object| object.len() == 1 && object.contains_key(SEALED_FIELD))
+    }
+
+    /// Seal `payload` for jobs of `job_type`; it only opens for that type.
+    pub fn seal(
+        &self,
+        payload: &serde_json::Value,
+        job_type: &str,
+    ) -> Result<serde_json::Value, PayloadEncryptionError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| PayloadEncryptionError::Seal)?;
+        let mut sealed = serde_json::to_vec(payload).map_err(|_| PayloadEncryptionError::Seal)?;
+        self.keys[0]
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(job_type.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| PayloadEncryptionError::Seal)?;
+
+        let mut stored = nonce.to_vec();
+        stored.extend_from_slice(&sealed);
+        Ok(serde_json::json!({ SEALED_FIELD: BASE64.encode(stored) }))
+    }
+
+    /// Open a payload [`seal`](Self::seal)ed for `job_type`.
+    pub fn open(
+        &self,
+        payload: &serde_json::Value,
+        job_type: &str,
+    ) -> Result<serde_json::Value, PayloadEncryptionError> {
+        let stored = payload[SEALED_FIELD]
+            .as_str()
+            .and_then(|stored| BASE64.decode(stored).ok())
+            .filter(|stored| stored.len() > NONCE_LEN)
+            .ok_or(PayloadEncryptionError::Open)?;
+        let (nonce, sealed) = stored.split_at(NONCE_LEN);
+
+        for key in &self.keys {
+            let mut buffer = sealed.to_vec();
+            let nonce = Nonce::try_assume_unique_for_key(nonce)
+                .map_err(|_| PayloadEncryptionError::Open)?;
+            if let Ok(opened) =
+                key.open_in_place(nonce, Aad::from(job_type.as_bytes()), &mut buffer)
+            {
+                return serde_json::from_slice(opened).map_err(|_| PayloadEncryptionError::Open);
+            }
+        }
+        Err(PayloadEncryptionError::Open)
+    }
+}
+
+fn decode_key(key: &str) -> Result<Vec<u8>, PayloadEncryptionError> {
+    BASE64
+        .decode(key.trim())
+        .map_err(|e| PayloadEncryptionError::InvalidKey(e.to_string()))
+}
+
+#[async_trait]
+impl JobMiddleware for PayloadEncryption {
+    async fn enqueued(&self, job: &mut Job) -> Result<(), JobQueueError> {
+        // Re-enqueued dead jobs are sealed already
+        if !Self::is_sealed(&job.payload) {
+            job.payload = self
+                .seal(&job.payload, &job.job_type)
+                .map_err(|e| JobQueueError::EnqueueError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    async fn around(&self, mut job: Job, next: Next) -> JobResult {
+        if Self::is_sealed(&job.payload) {
+            match self.open(&job.payload, &job.job_type) {
+                Ok(payload) => job.payload = payload,
+                Err(e) => {
+                    tracing::error!(job_id = %job.id, job_type = %job.job_type, error = %e, "Job payload cannot be opened");
+                    return JobResult::Failed(e.to_string());
+                }
+            }
+        }
+        next.run(job).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::{InMemoryJobQueue, InMemoryJobQueueConfig};
+    use apex_core::ports::JobQueue;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_sealed_payloads_open_with_current_and_previous_keys() {
+        let payload = serde_json::json!({"email": "jane@example.com", "token": "abc"});
+        let old = PayloadEncryption::new(&[1; 32], &[]).unwrap();
+        let sealed = old.seal(&payload, "send_email").unwrap();
+        assert!(PayloadEncryption::is_sealed(&sealed));
+        assert!(!sealed.to_string().contains("jane@example.com"));
+        assert_eq!(old.open(&sealed, "send_email").unwrap(), payload);
+
+        // Only for the job type it was sealed for
+        assert!(old.open(&sealed, "export_data").is_err());
+
+        // After a rotation, old payloads open until the old key is dropped
+        let rotated = PayloadEncryption::new(&[2; 32], &[vec![1; 32]]).unwrap();
+        assert_eq!(rotated.open(&sealed, "send_email").unwrap(), payload);
+        let dropped = PayloadEncryption::new(&[2; 32], &[]).unwrap();
+        assert!(dropped.open(&sealed, "send_email").is_err());
+
+        assert!(PayloadEncryption::new(&[1; 16], &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queue_stores_sealed_payloads_and_handlers_see_plaintext() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig::default())
+            .with_middleware(PayloadEncryption::new(&[7; 32], &[]).unwrap());
+        let payload = serde_json::json!({"email": "jane@example.com"});
+        queue
+            .enqueue(Job::new("send_email", payload.clone()))
+            .await
+            .unwrap();
+
+        let pending = queue.list_pending(None, 10).await.unwrap();
+        assert!(PayloadEncryption::is_sealed(&pending[0].payload));
+
+        let (tx, mut rx) = mpsc::channel(1);
+        queue
+            .start_worker(move |job| {
+                let tx = tx.clone();
+                Box::pin(async move {
+                    tx.send(job.payload).await.unwrap();
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap(), payload);
+    }
+}
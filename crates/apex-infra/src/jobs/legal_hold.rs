@@ -0,0 +1,171 @@
+//! Legal holds on erasure jobs.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use apex_core::ports::{Job, JobQueueError, JobResult, LegalHoldRepository};
+
+use super::middleware::{JobMiddleware, Next};
+use crate::env;
+
+/// Job types that delete or anonymize a user's data, unless configured
+/// otherwise.
+pub const ERASURE_JOB_TYPES: [&str; 2] = ["user.delete", "user.anonymize"];
+
+/// Keeps erasure jobs away from users under legal hold.
+///
+/// Erasure jobs (account deletion, GDPR erasure, retention sweeps) name the
+/// user in their payload's `user_id`. They are rejected when enqueued for a
+/// held user, and fail into the dead letter queue when the hold was placed
+/// after they were enqueued, to be retried once it is released. Jobs that
+/// do not say whose data they erase cannot be checked and are refused too.
+pub struct LegalHoldGuard {
+    holds: Arc<dyn LegalHoldRepository>,
+    job_types: HashSet<String>,
+}
+
+impl LegalHoldGuard {
+    /// Guard the [`ERASURE_JOB_TYPES`].
+    pub fn new(holds: Arc<dyn LegalHoldRepository>) -> Self {
+        Self {
+            holds,
+            job_types: ERASURE_JOB_TYPES.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    /// Guard these job types instead.
+    pub fn with_job_types<I, S>(mut self, job_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.job_types = job_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Guard the job types in `LEGAL_HOLD_JOB_TYPES` (comma separated), or
+    /// the [`ERASURE_JOB_TYPES`] when unset.
+    pub fn from_env(holds: Arc<dyn LegalHoldRepository>) -> Self {
+        let guard = Self::new(holds);
+        if env::LEGAL_HOLD_JOB_TYPES.is_set() {
+            guard.with_job_types(env::LEGAL_HOLD_JOB_TYPES.list())
+        } else {
+            guard
+        }
+    }
+
+    /// Why the job must not run, if it must not.
+    async fn refusal(&self, job: &Job) -> Result<Option<String>, JobQueueError> {
+        if !self.job_types.contains(&job.job_type) {
+            return Ok(None);
+        }
+        let Some(user_id) = job
+            .payload
+            .get("user_id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| Uuid::parse_str(id).ok())
+        else {
+            return Ok(Some(format!(
+                "{} jobs need the user_id whose data they erase",
+                job.job_type
+            )));
+        };
+        let held = self
+            .holds
+            .is_held(user_id)
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        Ok(held.then(|| format!("User {} is under legal hold", user_id)))
+    }
+}
+
+#[async_trait]
+impl JobMiddleware for LegalHoldGuard {
+    async fn enqueued(&self, job: &mut Job) -> Result<(), JobQueueError> {
+        match self.refusal(job).await? {
+            Some(reason) => Err(JobQueueError::EnqueueError(reason)),
+            None => Ok(()),
+        }
+    }
+
+    async fn around(&self, job: Job, next: Next) -> JobResult {
+        match self.refusal(&job).await {
+            Ok(None) => next.run(job).await,
+            Ok(Some(reason)) => {
+                tracing::warn!(job_id = %job.id, job_type = %job.job_type, reason = %reason, "Erasure job blocked");
+                JobResult::Failed(reason)
+            }
+            // Erase nothing while unsure
+            Err(e) => JobResult::Retry(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::JobMiddlewareStack;
+    use apex_core::domain::LegalHoldChange;
+    use apex_core::error::RepoError;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryHolds(Mutex<Vec<LegalHoldChange>>);
+
+    #[async_trait]
+    impl LegalHoldRepository for MemoryHolds {
+        async fn record(&self, change: LegalHoldChange) -> Result<LegalHoldChange, RepoError> {
+            self.0.lock().await.insert(0, change.clone());
+            Ok(change)
+        }
+
+        async fn history(&self, user_id: Uuid) -> Result<Vec<LegalHoldChange>, RepoError> {
+            let changes = self.0.lock().await;
+            Ok(changes
+                .iter()
+                .filter(|c| c.user_id == user_id)
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn change(user_id: Uuid, held: bool) -> LegalHoldChange {
+        LegalHoldChange::new(user_id, held, "Case 42".into(), Uuid::new_v4()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_erasure_of_held_users_is_refused() {
+        let holds = Arc::new(MemoryHolds::default());
+        let guard = LegalHoldGuard::new(holds.clone());
+        let held = Uuid::new_v4();
+        holds.record(change(held, true)).await.unwrap();
+
+        let mut job = Job::new("user.delete", serde_json::json!({"user_id": held}));
+        assert!(guard.enqueued(&mut job).await.is_err());
+        let mut other = Job::new("send_email", serde_json::json!({"user_id": held}));
+        assert!(guard.enqueued(&mut other).await.is_ok());
+        let mut anonymous = Job::new("user.anonymize", serde_json::json!({}));
+        assert!(guard.enqueued(&mut anonymous).await.is_err());
+
+        holds.record(change(held, false)).await.unwrap();
+        assert!(guard.enqueued(&mut job).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_hold_placed_after_enqueue_fails_the_job() {
+        let holds = Arc::new(MemoryHolds::default());
+        let mut stack = JobMiddlewareStack::default();
+        stack.push(LegalHoldGuard::new(holds.clone()));
+        let handler = stack.wrap(|_job| Box::pin(async { JobResult::Success }));
+
+        let user_id = Uuid::new_v4();
+        let job = Job::new("user.delete", serde_json::json!({"user_id": user_id}));
+        assert!(matches!(handler(job.clone()).await, JobResult::Success));
+
+        holds.record(change(user_id, true)).await.unwrap();
+        assert!(matches!(handler(job).await, JobResult::Failed(_)));
+    }
+}
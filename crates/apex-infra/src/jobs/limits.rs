@@ -0,0 +1,97 @@
+//! Per-job-type concurrency limits.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::env;
+
+/// Delay before a job deferred by its type's limit is offered to workers again.
+pub(crate) const DEFERRAL_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Parse limits of the form `report=2,export=1`.
+///
+/// Malformed entries are skipped with a warning.
+pub fn parse_type_limits(spec: &str) -> HashMap<String, usize> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once('=')
+                .and_then(|(job_type, limit)| Some((job_type.trim(), limit.trim().parse().ok()?)))
+                .filter(|(job_type, limit)| !job_type.is_empty() && *limit > 0);
+            if parsed.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed job type concurrency limit");
+            }
+            parsed.map(|(job_type, limit)| (job_type.to_string(), limit))
+        })
+        .collect()
+}
+
+/// Read limits from `JOB_TYPE_CONCURRENCY`.
+pub fn type_limits_from_env() -> HashMap<String, usize> {
+    parse_type_limits(&env::JOB_TYPE_CONCURRENCY.string())
+}
+
+/// In-process concurrency limiter keyed by job type.
+pub(crate) struct JobTypeLimiter {
+    semaphores: HashMap<String, Arc<Semaphore>>,
+}
+
+/// Permission to run a job. Holding it occupies one slot of the job type's limit.
+pub(crate) struct JobPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl JobTypeLimiter {
+    pub(crate) fn new(limits: &HashMap<String, usize>) -> Self {
+        Self {
+            semaphores: limits
+                .iter()
+                .map(|(job_type, limit)| (job_type.clone(), Arc::new(Semaphore::new(*limit))))
+                .collect(),
+        }
+    }
+
+    /// Try to claim a slot for the job type. Returns `None` when the type is at its limit.
+    pub(crate) fn try_acquire(&self, job_type: &str) -> Option<JobPermit> {
+        match self.semaphores.get(job_type) {
+            Some(semaphore) => semaphore
+                .clone()
+                .try_acquire_owned()
+                .ok()
+                .map(|permit| JobPermit {
+                    _permit: Some(permit),
+                }),
+            None => Some(JobPermit { _permit: None }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_type_limits() {
+        let limits = parse_type_limits("report=2, export = 1,bogus,zero=0,=3");
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits["report"], 2);
+        assert_eq!(limits["export"], 1);
+    }
+
+    #[test]
+    fn test_limiter_caps_limited_types_only() {
+        let limiter = JobTypeLimiter::new(&parse_type_limits("report=1"));
+
+        let permit = limiter.try_acquire("report");
+        assert!(permit.is_some());
+        assert!(limiter.try_acquire("report").is_none());
+        assert!(limiter.try_acquire("email").is_some());
+
+        drop(permit);
+        assert!(limiter.try_acquire("report").is_some());
+    }
+}
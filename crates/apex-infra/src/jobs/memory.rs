@@ -4,15 +4,25 @@
 //! Jobs are stored in memory and processed by local workers.
 //! Note: Jobs are lost on server restart.
 
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::sync::{Mutex, mpsc};
 
-use apex_core::ports::{Job, JobQueue, JobQueueError, JobResult, QueueStats};
+use apex_core::ports::{DeadJob, Job, JobQueue, JobQueueError, JobResult, JobStatus, QueueStats};
+
+use super::limits::{DEFERRAL_DELAY, JobTypeLimiter, type_limits_from_env};
+use super::matches_type;
+use super::middleware::{JobMiddleware, JobMiddlewareStack};
+use super::recurring;
+use super::results::JobStatuses;
+use super::workers::Workers;
+use crate::env;
 
 /// In-memory job queue configuration.
 #[derive(Debug, Clone)]
@@ -21,6 +31,12 @@ pub struct InMemoryJobQueueConfig {
     pub max_size: usize,
     /// Number of worker tasks.
     pub workers: usize,
+    /// Maximum concurrent executions per job type. Types not listed are unlimited.
+    pub type_limits: HashMap<String, usize>,
+    /// How long statuses of finished jobs stay queryable (seconds).
+    pub result_ttl: u64,
+    /// Most failed jobs kept in the dead letter queue; the oldest are dropped.
+    pub dead_letter_limit: usize,
 }
 
 impl Default for InMemoryJobQueueConfig {
@@ -28,6 +44,9 @@ impl Default for InMemoryJobQueueConfig {
         Self {
             max_size: 10000,
             workers: 4,
+            type_limits: HashMap::new(),
+            result_ttl: 3600,
+            dead_letter_limit: 1000,
         }
     }
 }
@@ -38,6 +57,111 @@ pub struct InMemoryJobQueue {
     config: InMemoryJobQueueConfig,
     job_sender: mpsc::Sender<Job>,
     job_receiver: Arc<Mutex<mpsc::Receiver<Job>>>,
+    unique_keys: Arc<UniqueKeys>,
+    pending: Arc<PendingJobs>,
+    dead: Arc<DeadLetters>,
+    statuses: Arc<JobStatuses>,
+    workers: Workers,
+    middleware: JobMiddlewareStack,
+}
+
+/// Unique keys of jobs that are currently pending or processing.
+#[derive(Default)]
+struct UniqueKeys(std::sync::Mutex<HashSet<String>>);
+
+impl UniqueKeys {
+    /// Claim the job's key. Returns false if another job already holds it.
+    fn claim(&self, job: &Job) -> bool {
+        match &job.unique_key {
+            Some(key) => self.0.lock().unwrap().insert(key.clone()),
+            None => true,
+        }
+    }
+
+    fn release(&self, job: &Job) {
+        if let Some(key) = &job.unique_key {
+            self.0.lock().unwrap().remove(key);
+        }
+    }
+}
+
+/// Jobs waiting in the channel, so they can be listed and purged.
+///
+/// A job a worker receives that is no longer here was deleted, and is dropped.
+#[derive(Default)]
+struct PendingJobs(std::sync::Mutex<HashMap<String, Job>>);
+
+impl PendingJobs {
+    fn insert(&self, job: &Job) {
+        self.0.lock().unwrap().insert(job.id.clone(), job.clone());
+    }
+
+    /// Claim the job for processing. Returns false if it was purged.
+    fn take(&self, job_id: &str) -> bool {
+        self.remove(job_id).is_some()
+    }
+
+    fn remove(&self, job_id: &str) -> Option<Job> {
+        self.0.lock().unwrap().remove(job_id)
+    }
+
+    fn list(&self, job_type: Option<&str>, limit: usize) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .0
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|job| matches_type(job, job_type))
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        jobs.truncate(limit);
+        jobs
+    }
+
+    fn drain(&self) -> Vec<Job> {
+        self.0.lock().unwrap().drain().map(|(_, job)| job).collect()
+    }
+}
+
+/// Permanently failed jobs, newest first.
+struct DeadLetters {
+    limit: usize,
+    jobs: std::sync::Mutex<VecDeque<DeadJob>>,
+}
+
+impl DeadLetters {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            jobs: std::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn push(&self, job: Job, error: &str) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.push_front(DeadJob::new(job, error));
+        jobs.truncate(self.limit);
+    }
+
+    fn take(&self, job_id: &str) -> Option<DeadJob> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let index = jobs.iter().position(|dead| dead.job.id == job_id)?;
+        jobs.remove(index)
+    }
+
+    fn list(&self, job_type: Option<&str>, limit: usize) -> Vec<DeadJob> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.iter()
+            .filter(|dead| matches_type(&dead.job, job_type))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
 }
 
 struct JobStats {
@@ -50,6 +174,8 @@ struct JobStats {
 impl InMemoryJobQueue {
     pub fn new(config: InMemoryJobQueueConfig) -> Self {
         let (tx, rx) = mpsc::channel(config.max_size.max(100));
+        let statuses = JobStatuses::new(Duration::from_secs(config.result_ttl));
+        let dead = DeadLetters::new(config.dead_letter_limit);
 
         Self {
             stats: Arc::new(JobStats {
@@ -61,19 +187,32 @@ impl InMemoryJobQueue {
             config,
             job_sender: tx,
             job_receiver: Arc::new(Mutex::new(rx)),
+            unique_keys: Arc::new(UniqueKeys::default()),
+            pending: Arc::new(PendingJobs::default()),
+            dead: Arc::new(dead),
+            statuses: Arc::new(statuses),
+            workers: Workers::new(),
+            middleware: JobMiddlewareStack::default(),
         }
     }
 
+    pub fn config(&self) -> &InMemoryJobQueueConfig {
+        &self.config
+    }
+
+    /// Run every job through `middleware`, inside the middleware added before it.
+    pub fn with_middleware(mut self, middleware: impl JobMiddleware + 'static) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
     pub fn from_env() -> Self {
         let config = InMemoryJobQueueConfig {
-            max_size: std::env::var("JOB_QUEUE_MAX_SIZE")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(10000),
-            workers: std::env::var("JOB_QUEUE_WORKERS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(4),
+            max_size: env::JOB_QUEUE_MAX_SIZE.get(),
+            workers: env::JOB_QUEUE_WORKERS.get(),
+            type_limits: type_limits_from_env(),
+            result_ttl: env::JOB_QUEUE_RESULT_TTL.get(),
+            dead_letter_limit: env::JOB_QUEUE_DEAD_LETTER_LIMIT.get(),
         };
         Self::new(config)
     }
@@ -81,7 +220,13 @@ impl InMemoryJobQueue {
 
 #[async_trait]
 impl JobQueue for InMemoryJobQueue {
-    async fn enqueue(&self, job: Job) -> Result<(), JobQueueError> {
+    async fn enqueue(&self, mut job: Job) -> Result<(), JobQueueError> {
+        if self.workers.is_stopping() {
+            return Err(JobQueueError::ShuttingDown);
+        }
+        crate::context::attach(&mut job);
+        self.middleware.enqueue(&mut job).await?;
+
         // Check queue size
         if self.config.max_size > 0 {
             let current_size = self.stats.pending.load(Ordering::Relaxed);
@@ -90,12 +235,27 @@ impl JobQueue for InMemoryJobQueue {
             }
         }
 
+        if !self.unique_keys.claim(&job) {
+            tracing::debug!(
+                job_id = %job.id,
+                unique_key = ?job.unique_key,
+                "Duplicate job skipped"
+            );
+            return Ok(());
+        }
+
         self.stats.pending.fetch_add(1, Ordering::Relaxed);
+        self.statuses.set(&job.id, JobStatus::Pending);
+        self.pending.insert(&job);
 
-        self.job_sender
-            .send(job)
-            .await
-            .map_err(|e| JobQueueError::EnqueueError(e.to_string()))?;
+        if let Err(e) = self.job_sender.send(job).await {
+            if self.pending.take(&e.0.id) {
+                self.stats.pending.fetch_sub(1, Ordering::Relaxed);
+            }
+            self.unique_keys.release(&e.0);
+            self.statuses.remove(&e.0.id);
+            return Err(JobQueueError::EnqueueError(e.to_string()));
+        }
 
         tracing::debug!(
             "Job enqueued. Queue size: {}",
@@ -105,32 +265,127 @@ impl JobQueue for InMemoryJobQueue {
         Ok(())
     }
 
+    async fn enqueue_batch(&self, mut jobs: Vec<Job>) -> Result<(), JobQueueError> {
+        if self.workers.is_stopping() {
+            return Err(JobQueueError::ShuttingDown);
+        }
+        for job in &mut jobs {
+            crate::context::attach(job);
+            self.middleware.enqueue(job).await?;
+        }
+
+        let mut jobs: Vec<Job> = jobs
+            .into_iter()
+            .filter(|job| self.unique_keys.claim(job))
+            .collect();
+
+        if self.config.max_size > 0 {
+            let current_size = self.stats.pending.load(Ordering::Relaxed);
+            if current_size + jobs.len() > self.config.max_size {
+                jobs.iter().for_each(|job| self.unique_keys.release(job));
+                return Err(JobQueueError::QueueFull);
+            }
+        }
+
+        let count = jobs.len();
+        self.stats.pending.fetch_add(count, Ordering::Relaxed);
+        for job in &jobs {
+            self.statuses.set(&job.id, JobStatus::Pending);
+            self.pending.insert(job);
+        }
+
+        // Reserve channel slots in bulk, in chunks no larger than the channel
+        let chunk_size = self.job_sender.max_capacity();
+        while !jobs.is_empty() {
+            let rest = jobs.split_off(jobs.len().min(chunk_size));
+            let permits = match self.job_sender.reserve_many(jobs.len()).await {
+                Ok(permits) => permits,
+                Err(e) => {
+                    let mut dropped = 0;
+                    for job in jobs.iter().chain(&rest) {
+                        self.unique_keys.release(job);
+                        self.statuses.remove(&job.id);
+                        dropped += usize::from(self.pending.take(&job.id));
+                    }
+                    self.stats.pending.fetch_sub(dropped, Ordering::Relaxed);
+                    return Err(JobQueueError::EnqueueError(e.to_string()));
+                }
+            };
+            for (permit, job) in permits.zip(jobs) {
+                permit.send(job);
+            }
+            jobs = rest;
+        }
+
+        tracing::debug!(count = count, "Job batch enqueued");
+
+        Ok(())
+    }
+
+    async fn enqueue_recurring(
+        self: Arc<Self>,
+        job: Job,
+        schedule: &str,
+    ) -> Result<(), JobQueueError> {
+        recurring::register(&self, &self.workers, job, schedule)
+    }
+
     async fn start_worker<F>(&self, handler: F) -> Result<(), JobQueueError>
     where
         F: Fn(Job) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync + 'static,
     {
-        let handler = Arc::new(handler);
+        let handler = self.middleware.wrap(handler);
         let receiver = self.job_receiver.clone();
         let stats = self.stats.clone();
         let sender = self.job_sender.clone();
+        let unique_keys = self.unique_keys.clone();
+        let pending = self.pending.clone();
+        let dead = self.dead.clone();
+        let statuses = self.statuses.clone();
+        let limiter = Arc::new(JobTypeLimiter::new(&self.config.type_limits));
 
         for worker_id in 0..self.config.workers {
             let handler = handler.clone();
             let receiver = receiver.clone();
             let stats = stats.clone();
             let sender = sender.clone();
+            let unique_keys = unique_keys.clone();
+            let pending = pending.clone();
+            let dead = dead.clone();
+            let statuses = statuses.clone();
+            let limiter = limiter.clone();
+            let mut stop = self.workers.stop_signal();
+            let in_flight = self.workers.in_flight();
 
-            tokio::spawn(async move {
+            self.workers.spawn(async move {
                 tracing::info!("Job worker {} started", worker_id);
 
                 loop {
-                    let job = {
-                        let mut rx = receiver.lock().await;
-                        rx.recv().await
+                    let job = tokio::select! {
+                        _ = stop.wait_for(|stopping| *stopping) => None,
+                        job = async { receiver.lock().await.recv().await } => job,
                     };
 
                     match job {
                         Some(mut job) => {
+                            // Defer jobs whose type is at its concurrency limit
+                            let Some(_permit) = limiter.try_acquire(&job.job_type) else {
+                                let sender = sender.clone();
+                                let unique_keys = unique_keys.clone();
+                                tokio::spawn(async move {
+                                    tokio::time::sleep(DEFERRAL_DELAY).await;
+                                    if let Err(e) = sender.send(job).await {
+                                        unique_keys.release(&e.0);
+                                        tracing::error!("Failed to re-enqueue deferred job: {}", e);
+                                    }
+                                });
+                                continue;
+                            };
+
+                            // Deleted while it waited in the channel
+                            if !pending.take(&job.id) {
+                                continue;
+                            }
                             stats.pending.fetch_sub(1, Ordering::Relaxed);
                             stats.processing.fetch_add(1, Ordering::Relaxed);
 
@@ -141,13 +396,29 @@ impl JobQueue for InMemoryJobQueue {
                                 "Processing job"
                             );
 
+                            in_flight.start(&job);
+                            statuses.set(&job.id, JobStatus::Processing);
                             job.attempts += 1;
                             let result = handler(job.clone()).await;
+                            in_flight.finish(&job.id);
 
                             stats.processing.fetch_sub(1, Ordering::Relaxed);
 
                             match result {
                                 JobResult::Success => {
+                                    statuses.set(&job.id, JobStatus::Completed { result: None });
+                                    unique_keys.release(&job);
+                                    stats.completed.fetch_add(1, Ordering::Relaxed);
+                                    tracing::debug!(job_id = %job.id, "Job completed successfully");
+                                }
+                                JobResult::SuccessWith(value) => {
+                                    statuses.set(
+                                        &job.id,
+                                        JobStatus::Completed {
+                                            result: Some(value),
+                                        },
+                                    );
+                                    unique_keys.release(&job);
                                     stats.completed.fetch_add(1, Ordering::Relaxed);
                                     tracing::debug!(job_id = %job.id, "Job completed successfully");
                                 }
@@ -160,15 +431,19 @@ impl JobQueue for InMemoryJobQueue {
                                             reason = %reason,
                                             "Job failed, will retry"
                                         );
+                                        statuses.set(&job.id, JobStatus::Pending);
+                                        pending.insert(&job);
                                         // Actually re-enqueue the job for retry
                                         // Small delay before retry to prevent tight loops
                                         let sender = sender.clone();
+                                        let unique_keys = unique_keys.clone();
                                         tokio::spawn(async move {
                                             tokio::time::sleep(tokio::time::Duration::from_millis(
                                                 100 * job.attempts as u64,
                                             ))
                                             .await;
                                             if let Err(e) = sender.send(job).await {
+                                                unique_keys.release(&e.0);
                                                 tracing::error!(
                                                     "Failed to re-enqueue job for retry: {}",
                                                     e
@@ -177,17 +452,33 @@ impl JobQueue for InMemoryJobQueue {
                                         });
                                         stats.pending.fetch_add(1, Ordering::Relaxed);
                                     } else {
+                                        statuses.set(
+                                            &job.id,
+                                            JobStatus::Failed {
+                                                error: reason.clone(),
+                                            },
+                                        );
+                                        unique_keys.release(&job);
                                         stats.failed.fetch_add(1, Ordering::Relaxed);
                                         tracing::error!(
                                             job_id = %job.id,
                                             reason = %reason,
                                             "Job failed after max retries"
                                         );
+                                        dead.push(job, &reason);
                                     }
                                 }
                                 JobResult::Failed(reason) => {
+                                    statuses.set(
+                                        &job.id,
+                                        JobStatus::Failed {
+                                            error: reason.clone(),
+                                        },
+                                    );
+                                    unique_keys.release(&job);
                                     stats.failed.fetch_add(1, Ordering::Relaxed);
                                     tracing::error!(job_id = %job.id, reason = %reason, "Job failed permanently");
+                                    dead.push(job, &reason);
                                 }
                             }
                         }
@@ -209,6 +500,519 @@ impl JobQueue for InMemoryJobQueue {
             processing: self.stats.processing.load(Ordering::Relaxed),
             completed: self.stats.completed.load(Ordering::Relaxed),
             failed: self.stats.failed.load(Ordering::Relaxed),
+            dead: self.dead.len(),
         })
     }
+
+    async fn get_status(&self, job_id: &str) -> Result<Option<JobStatus>, JobQueueError> {
+        Ok(self.statuses.get(job_id))
+    }
+
+    async fn list_pending(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError> {
+        Ok(self.pending.list(job_type, limit))
+    }
+
+    async fn list_processing(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError> {
+        Ok(self.workers.in_flight().list(job_type, limit))
+    }
+
+    async fn list_dead(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DeadJob>, JobQueueError> {
+        Ok(self.dead.list(job_type, limit))
+    }
+
+    async fn retry_dead(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        let Some(dead) = self.dead.take(job_id) else {
+            return Ok(false);
+        };
+
+        let mut job = dead.job.clone();
+        job.attempts = 0;
+        if let Err(e) = self.enqueue(job).await {
+            self.dead.push(dead.job, &dead.error);
+            return Err(e);
+        }
+        tracing::info!(job_id = %job_id, "Dead job re-enqueued");
+        Ok(true)
+    }
+
+    async fn delete_pending(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        // The worker drops the job when it reaches it in the channel
+        let Some(job) = self.pending.remove(job_id) else {
+            return Ok(false);
+        };
+        self.unique_keys.release(&job);
+        self.statuses.remove(&job.id);
+        self.stats.pending.fetch_sub(1, Ordering::Relaxed);
+
+        tracing::info!(job_id = %job_id, "Pending job deleted");
+        Ok(true)
+    }
+
+    async fn delete_dead(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        Ok(self.dead.take(job_id).is_some())
+    }
+
+    async fn purge(&self) -> Result<usize, JobQueueError> {
+        // Workers skip purged jobs when they reach them in the channel
+        let purged = self.pending.drain();
+        for job in &purged {
+            self.unique_keys.release(job);
+            self.statuses.remove(&job.id);
+        }
+        self.stats
+            .pending
+            .fetch_sub(purged.len(), Ordering::Relaxed);
+
+        tracing::info!(purged = purged.len(), "Job queue purged");
+        Ok(purged.len())
+    }
+
+    async fn shutdown(&self, timeout: Duration) -> Result<(), JobQueueError> {
+        let interrupted = self.workers.shutdown(timeout).await;
+
+        // Nothing outlives the process, so interrupted jobs are dropped
+        // along with whatever is still pending
+        for job in &interrupted {
+            tracing::warn!(job_id = %job.id, job_type = %job.job_type, "Job interrupted by shutdown");
+        }
+        tracing::info!(
+            interrupted = interrupted.len(),
+            pending = self.stats.pending.load(Ordering::Relaxed),
+            "Job queue stopped"
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_duplicate_unique_job_is_skipped() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig::default());
+
+        for _ in 0..3 {
+            let job = Job::new("email_blast", serde_json::json!({})).with_unique_key("blast:42");
+            queue.enqueue(job).await.unwrap();
+        }
+        queue
+            .enqueue(Job::new("email_blast", serde_json::json!({})))
+            .await
+            .unwrap();
+
+        assert_eq!(queue.stats().await.unwrap().pending, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unique_key_released_after_completion() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            max_size: 100,
+            workers: 1,
+            ..Default::default()
+        });
+        let (tx, mut rx) = mpsc::channel(4);
+
+        queue
+            .start_worker(move |_job| {
+                let tx = tx.clone();
+                Box::pin(async move {
+                    tx.send(()).await.unwrap();
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+
+        let job = || Job::new("report", serde_json::json!({})).with_unique_key("report:daily");
+        queue.enqueue(job()).await.unwrap();
+        rx.recv().await.unwrap();
+
+        // Give the worker a moment to release the key after the handler returns
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        queue.enqueue(job()).await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .expect("second job should run once the key is released");
+    }
+
+    #[tokio::test]
+    async fn test_type_limit_caps_concurrent_executions() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            max_size: 100,
+            workers: 4,
+            type_limits: HashMap::from([("report".to_string(), 1)]),
+            ..Default::default()
+        });
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (tx, mut rx) = mpsc::channel(8);
+
+        let (r, p) = (running.clone(), peak.clone());
+        queue
+            .start_worker(move |_job| {
+                let (running, peak, tx) = (r.clone(), p.clone(), tx.clone());
+                Box::pin(async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    tx.send(()).await.unwrap();
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            queue
+                .enqueue(Job::new("report", serde_json::json!({})))
+                .await
+                .unwrap();
+        }
+        for _ in 0..3 {
+            tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_batch() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            max_size: 1000,
+            ..Default::default()
+        });
+
+        let mut jobs: Vec<Job> = (0..500)
+            .map(|i| Job::new("email", serde_json::json!({ "n": i })))
+            .collect();
+        jobs.push(Job::new("digest", serde_json::json!({})).with_unique_key("digest"));
+        jobs.push(Job::new("digest", serde_json::json!({})).with_unique_key("digest"));
+        queue.enqueue_batch(jobs).await.unwrap();
+
+        assert_eq!(queue.stats().await.unwrap().pending, 501);
+
+        let overflow: Vec<Job> = (0..500)
+            .map(|_| Job::new("email", serde_json::json!({})))
+            .collect();
+        assert!(matches!(
+            queue.enqueue_batch(overflow).await,
+            Err(JobQueueError::QueueFull)
+        ));
+        assert_eq!(queue.stats().await.unwrap().pending, 501);
+    }
+
+    #[tokio::test]
+    async fn test_batch_jobs_run_with_the_enqueuing_request_context() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig::default());
+        let (tx, mut rx) = mpsc::channel(4);
+        queue
+            .start_worker(move |_job| {
+                let tx = tx.clone();
+                Box::pin(async move {
+                    tx.send(crate::context::current()).await.unwrap();
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+
+        let user_id = uuid::Uuid::new_v4();
+        let mut context =
+            apex_core::RequestContext::new("req-1").with_timeout(std::time::Duration::ZERO);
+        context.user_id = Some(user_id);
+        crate::context::scope(
+            context,
+            queue.enqueue_batch(vec![Job::new("email", serde_json::json!({}))]),
+        )
+        .await
+        .unwrap();
+
+        let seen = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .flatten()
+            .expect("the job should run with the request's context");
+        assert_eq!(seen.request_id, "req-1");
+        assert_eq!(seen.user_id, Some(user_id));
+        assert_eq!(seen.deadline, None);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_in_flight_job() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            workers: 1,
+            ..Default::default()
+        });
+        let finished = Arc::new(AtomicUsize::new(0));
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let f = finished.clone();
+        queue
+            .start_worker(move |_job| {
+                let (finished, tx) = (f.clone(), tx.clone());
+                Box::pin(async move {
+                    tx.send(()).await.unwrap();
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    finished.fetch_add(1, Ordering::SeqCst);
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+
+        queue
+            .enqueue(Job::new("report", serde_json::json!({})))
+            .await
+            .unwrap();
+        rx.recv().await.unwrap();
+
+        queue.shutdown(Duration::from_secs(1)).await.unwrap();
+
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            queue
+                .enqueue(Job::new("report", serde_json::json!({})))
+                .await,
+            Err(JobQueueError::ShuttingDown)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_interrupts_jobs_after_timeout() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            workers: 1,
+            ..Default::default()
+        });
+        let (tx, mut rx) = mpsc::channel(1);
+
+        queue
+            .start_worker(move |_job| {
+                let tx = tx.clone();
+                Box::pin(async move {
+                    tx.send(()).await.unwrap();
+                    std::future::pending::<()>().await;
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+
+        queue
+            .enqueue(Job::new("stuck", serde_json::json!({})))
+            .await
+            .unwrap();
+        rx.recv().await.unwrap();
+
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            queue.shutdown(Duration::from_millis(20)),
+        )
+        .await
+        .expect("shutdown should not wait past its timeout")
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_job_result_is_queryable() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            workers: 1,
+            ..Default::default()
+        });
+        let (tx, mut rx) = mpsc::channel(1);
+
+        queue
+            .start_worker(move |job| {
+                let tx = tx.clone();
+                Box::pin(async move {
+                    tx.send(()).await.unwrap();
+                    JobResult::SuccessWith(serde_json::json!({ "rows": job.payload["n"] }))
+                })
+            })
+            .await
+            .unwrap();
+
+        let job = Job::new("export", serde_json::json!({ "n": 7 }));
+        let job_id = job.id.clone();
+        queue.enqueue(job).await.unwrap();
+        rx.recv().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(
+            queue.get_status(&job_id).await.unwrap(),
+            Some(JobStatus::Completed {
+                result: Some(serde_json::json!({ "rows": 7 }))
+            })
+        );
+        assert_eq!(queue.get_status("unknown").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_is_dead_lettered_and_retryable() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            workers: 1,
+            ..Default::default()
+        });
+        let runs = Arc::new(AtomicUsize::new(0));
+        let (tx, mut rx) = mpsc::channel(2);
+
+        let r = runs.clone();
+        queue
+            .start_worker(move |_job| {
+                let (runs, tx) = (r.clone(), tx.clone());
+                Box::pin(async move {
+                    let run = runs.fetch_add(1, Ordering::SeqCst);
+                    tx.send(()).await.unwrap();
+                    if run == 0 {
+                        JobResult::Failed("smtp down".to_string())
+                    } else {
+                        JobResult::Success
+                    }
+                })
+            })
+            .await
+            .unwrap();
+
+        let job = Job::new("email", serde_json::json!({}));
+        let job_id = job.id.clone();
+        queue.enqueue(job).await.unwrap();
+        rx.recv().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let dead = queue.list_dead(Some("email"), 10).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].error, "smtp down");
+        assert_eq!(queue.stats().await.unwrap().dead, 1);
+
+        assert!(queue.retry_dead(&job_id).await.unwrap());
+        assert!(!queue.retry_dead(&job_id).await.unwrap());
+        rx.recv().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert!(queue.list_dead(None, 10).await.unwrap().is_empty());
+        assert_eq!(
+            queue.get_status(&job_id).await.unwrap(),
+            Some(JobStatus::Completed { result: None })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_purge_drops_pending_jobs() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            workers: 1,
+            ..Default::default()
+        });
+        for i in 0..3 {
+            let job = Job::new("report", serde_json::json!({ "n": i }))
+                .with_unique_key(format!("r{}", i));
+            queue.enqueue(job).await.unwrap();
+        }
+        assert_eq!(queue.list_pending(None, 2).await.unwrap().len(), 2);
+
+        assert_eq!(queue.purge().await.unwrap(), 3);
+        assert_eq!(queue.stats().await.unwrap().pending, 0);
+        assert!(queue.list_pending(None, 10).await.unwrap().is_empty());
+
+        // Purged jobs reach the worker but are not run, and their keys are free again
+        let runs = Arc::new(AtomicUsize::new(0));
+        let r = runs.clone();
+        queue
+            .start_worker(move |_job| {
+                r.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { JobResult::Success })
+            })
+            .await
+            .unwrap();
+        queue
+            .enqueue(Job::new("report", serde_json::json!({})).with_unique_key("r0"))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pending_jobs_filter_by_type_and_delete() {
+        let queue = InMemoryJobQueue::new(InMemoryJobQueueConfig::default());
+        let report = Job::new("report", serde_json::json!({}));
+        let report_id = report.id.clone();
+        queue.enqueue(report).await.unwrap();
+        for _ in 0..2 {
+            queue
+                .enqueue(Job::new("email", serde_json::json!({})))
+                .await
+                .unwrap();
+        }
+
+        let reports = queue.list_pending(Some("report"), 10).await.unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].id, report_id);
+
+        assert!(queue.delete_pending(&report_id).await.unwrap());
+        assert!(!queue.delete_pending(&report_id).await.unwrap());
+        assert_eq!(queue.stats().await.unwrap().pending, 2);
+        assert!(queue.get_status(&report_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_recurring_job_is_enqueued_on_schedule() {
+        let queue = Arc::new(InMemoryJobQueue::new(InMemoryJobQueueConfig {
+            workers: 1,
+            ..Default::default()
+        }));
+        let (tx, mut rx) = mpsc::channel(4);
+
+        queue
+            .start_worker(move |job| {
+                let tx = tx.clone();
+                Box::pin(async move {
+                    tx.send(job).await.unwrap();
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            queue
+                .clone()
+                .enqueue_recurring(Job::new("tick", serde_json::json!({})), "not a schedule")
+                .await,
+            Err(JobQueueError::InvalidSchedule(_))
+        ));
+
+        queue
+            .clone()
+            .enqueue_recurring(
+                Job::new("tick", serde_json::json!({ "n": 1 })),
+                "* * * * * *",
+            )
+            .await
+            .unwrap();
+        let job = tokio::time::timeout(Duration::from_secs(3), rx.recv())
+            .await
+            .expect("recurring job should be enqueued within a second")
+            .unwrap();
+        assert_eq!(job.job_type, "tick");
+        assert_eq!(job.payload["n"], 1);
+        assert!(job.unique_key.unwrap().starts_with("tick@"));
+
+        queue.shutdown(Duration::from_secs(1)).await.unwrap();
+    }
 }
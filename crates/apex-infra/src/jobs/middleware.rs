@@ -0,0 +1,195 @@
+//! Job middleware: hooks around job execution.
+//!
+//! Middleware wraps the worker handler the way tower layers wrap a service,
+//! so cross-cutting concerns (tracing spans, metrics, tenant context, payload
+//! decryption) stay out of individual handlers. Every backend applies the
+//! middleware registered on it with `with_middleware`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::Instrument;
+
+use apex_core::ports::{Job, JobQueueError, JobResult};
+
+type JobFuture = Pin<Box<dyn Future<Output = JobResult> + Send>>;
+
+/// Job handler after middleware has been applied.
+pub type JobHandler = Arc<dyn Fn(Job) -> JobFuture + Send + Sync>;
+
+/// Hook around job execution.
+///
+/// Implement `before`/`after` for simple hooks, or override `around` to
+/// control the call itself (wrap it in a span, time it, skip it). The job a
+/// middleware passes on is what the handler sees; changes to it are not
+/// persisted, so retries start from the enqueued job again. Changes made in
+/// `enqueued` are what the backend stores, and an error there rejects the
+/// job. `enqueued` hooks run innermost first, so each middleware sees the
+/// job at enqueue time as it will see it when the job runs.
+#[async_trait]
+pub trait JobMiddleware: Send + Sync {
+    /// Runs when the job is enqueued, before the backend stores it.
+    async fn enqueued(&self, _job: &mut Job) -> Result<(), JobQueueError> {
+        Ok(())
+    }
+
+    /// Runs before the handler; may modify the job it gets.
+    async fn before(&self, _job: &mut Job) {}
+
+    /// Runs after the handler with its result.
+    async fn after(&self, _job: &Job, _result: &JobResult) {}
+
+    async fn around(&self, mut job: Job, next: Next) -> JobResult {
+        self.before(&mut job).await;
+        let seen = job.clone();
+        let result = next.run(job).await;
+        self.after(&seen, &result).await;
+        result
+    }
+}
+
+/// The rest of the middleware stack and the handler at its end.
+pub struct Next {
+    stack: Arc<[Arc<dyn JobMiddleware>]>,
+    index: usize,
+    handler: JobHandler,
+}
+
+impl Next {
+    pub async fn run(self, job: Job) -> JobResult {
+        match self.stack.get(self.index).cloned() {
+            Some(middleware) => {
+                let next = Next {
+                    index: self.index + 1,
+                    ..self
+                };
+                middleware.around(job, next).await
+            }
+            None => (self.handler)(job).await,
+        }
+    }
+}
+
+/// Middleware registered on a queue, outermost first.
+#[derive(Clone, Default)]
+pub struct JobMiddlewareStack(Vec<Arc<dyn JobMiddleware>>);
+
+impl JobMiddlewareStack {
+    /// Add a middleware inside the ones already registered.
+    pub fn push(&mut self, middleware: impl JobMiddleware + 'static) {
+        self.0.push(Arc::new(middleware));
+    }
+
+    /// Run the `enqueued` hooks on a job about to be stored, innermost first.
+    pub async fn enqueue(&self, job: &mut Job) -> Result<(), JobQueueError> {
+        for middleware in self.0.iter().rev() {
+            middleware.enqueued(job).await?;
+        }
+        Ok(())
+    }
+
+    /// Wrap a worker handler in the stack. Jobs carrying a request context
+    /// run with it as the current context.
+    pub fn wrap<F>(&self, handler: F) -> JobHandler
+    where
+        F: Fn(Job) -> JobFuture + Send + Sync + 'static,
+    {
+        let handler: JobHandler = Arc::new(handler);
+        let stack: Arc<[Arc<dyn JobMiddleware>]> = self.0.clone().into();
+        Arc::new(move |job| {
+            let context = job.context.clone();
+            let next = Next {
+                stack: stack.clone(),
+                index: 0,
+                handler: handler.clone(),
+            };
+            match context {
+                Some(context) => Box::pin(crate::context::scope(context, next.run(job))),
+                None => Box::pin(next.run(job)),
+            }
+        })
+    }
+}
+
+/// Runs each job inside a `job` span carrying its id, type and attempt, so
+/// everything the handler logs can be traced back to the job.
+pub struct TracingJobMiddleware;
+
+#[async_trait]
+impl JobMiddleware for TracingJobMiddleware {
+    async fn around(&self, job: Job, next: Next) -> JobResult {
+        let span = tracing::info_span!(
+            "job",
+            job_id = %job.id,
+            job_type = %job.job_type,
+            attempt = job.attempts,
+        );
+        next.run(job).instrument(span).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct Record(&'static str, Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl JobMiddleware for Record {
+        async fn enqueued(&self, _job: &mut Job) -> Result<(), JobQueueError> {
+            self.1.lock().unwrap().push(format!("{} enqueued", self.0));
+            Ok(())
+        }
+
+        async fn before(&self, job: &mut Job) {
+            self.1.lock().unwrap().push(format!("{} before", self.0));
+            job.payload[self.0] = serde_json::json!(true);
+        }
+
+        async fn after(&self, _job: &Job, result: &JobResult) {
+            self.1
+                .lock()
+                .unwrap()
+                .push(format!("{} after {:?}", self.0, result));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_runs_in_registration_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut stack = JobMiddlewareStack::default();
+        stack.push(Record("outer", log.clone()));
+        stack.push(Record("inner", log.clone()));
+
+        let handler_log = log.clone();
+        let handler = stack.wrap(move |job: Job| {
+            let log = handler_log.clone();
+            Box::pin(async move {
+                // Both middleware got to modify the job first
+                log.lock().unwrap().push(format!("handler {}", job.payload));
+                JobResult::Success
+            })
+        });
+
+        let mut job = Job::new("test", serde_json::json!({}));
+        stack.enqueue(&mut job).await.unwrap();
+        let result = handler(job).await;
+        assert!(matches!(result, JobResult::Success));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                // Innermost first, mirroring what the stack undoes in order
+                "inner enqueued",
+                "outer enqueued",
+                "outer before",
+                "inner before",
+                r#"handler {"inner":true,"outer":true}"#,
+                "inner after Success",
+                "outer after Success",
+            ]
+        );
+    }
+}
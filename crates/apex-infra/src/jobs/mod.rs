@@ -1,10 +1,37 @@
 //! Job queue implementations.
 
+mod legal_hold;
+mod limits;
 mod memory;
+mod middleware;
+mod recurring;
+mod results;
+mod throttle;
+mod workers;
 
-pub use memory::InMemoryJobQueue;
+pub use legal_hold::{ERASURE_JOB_TYPES, LegalHoldGuard};
+pub use limits::{parse_type_limits, type_limits_from_env};
+pub use memory::{InMemoryJobQueue, InMemoryJobQueueConfig};
+pub use middleware::{JobHandler, JobMiddleware, JobMiddlewareStack, Next, TracingJobMiddleware};
+#[cfg(feature = "rate-limit")]
+pub(crate) use throttle::parse_window;
+pub use throttle::{ThrottleJobMiddleware, parse_type_rates};
+
+#[cfg(feature = "encryption")]
+mod encryption;
+#[cfg(feature = "encryption")]
+pub use encryption::{PayloadEncryption, PayloadEncryptionError};
 
 #[cfg(feature = "redis")]
 mod redis;
 #[cfg(feature = "redis")]
+mod redis_stream;
+#[cfg(feature = "redis")]
 pub use self::redis::{RedisJobQueue, RedisJobQueueConfig};
+#[cfg(feature = "redis")]
+pub use self::redis_stream::{RedisStreamJobQueue, RedisStreamJobQueueConfig};
+
+/// Whether a job passes the optional job type filter of the listing methods.
+pub(crate) fn matches_type(job: &apex_core::ports::Job, job_type: Option<&str>) -> bool {
+    job_type.is_none_or(|job_type| job.job_type == job_type)
+}
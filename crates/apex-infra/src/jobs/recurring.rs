@@ -0,0 +1,77 @@
+//! Recurring jobs: copies of a template job enqueued on a cron schedule.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use tokio::sync::watch;
+
+use apex_core::ports::{Job, JobQueue, JobQueueError};
+
+use super::workers::Workers;
+
+/// Start enqueueing copies of `template` on `queue` at every occurrence of
+/// `schedule`. The schedule stops with the queue's workers.
+pub(crate) fn register<Q: JobQueue + 'static>(
+    queue: &Arc<Q>,
+    workers: &Workers,
+    template: Job,
+    schedule: &str,
+) -> Result<(), JobQueueError> {
+    let cron = parse_schedule(schedule)?;
+    tracing::info!(job_type = %template.job_type, schedule = %schedule, "Recurring job registered");
+    workers.spawn(run(queue.clone(), template, cron, workers.stop_signal()));
+    Ok(())
+}
+
+/// Parse a cron expression with optional seconds (`0 * * * *` or `0 0 * * * *`).
+pub(crate) fn parse_schedule(schedule: &str) -> Result<Cron, JobQueueError> {
+    Cron::new(schedule)
+        .with_seconds_optional()
+        .parse()
+        .map_err(|e| JobQueueError::InvalidSchedule(format!("{}: {}", schedule, e)))
+}
+
+/// The copy of `template` enqueued for the occurrence at `at`.
+///
+/// Its unique key names the occurrence, so instances sharing a queue that
+/// fire for the same occurrence enqueue it once.
+pub(crate) fn occurrence(template: &Job, at: DateTime<Utc>) -> Job {
+    let key = template.unique_key.as_deref().unwrap_or(&template.job_type);
+    Job {
+        id: uuid::Uuid::new_v4().to_string(),
+        attempts: 0,
+        created_at: Utc::now(),
+        scheduled_at: None,
+        unique_key: Some(format!("{}@{}", key, at.timestamp())),
+        context: None,
+        ..template.clone()
+    }
+}
+
+async fn run<Q: JobQueue>(
+    queue: Arc<Q>,
+    template: Job,
+    cron: Cron,
+    mut stop: watch::Receiver<bool>,
+) {
+    loop {
+        let next = match cron.find_next_occurrence(&Utc::now(), false) {
+            Ok(next) => next,
+            Err(e) => {
+                tracing::error!(error = %e, job_type = %template.job_type, "Recurring job has no next occurrence");
+                return;
+            }
+        };
+
+        let wait = (next - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = stop.wait_for(|stopping| *stopping) => return,
+            _ = tokio::time::sleep(wait) => {}
+        }
+
+        if let Err(e) = queue.enqueue(occurrence(&template, next)).await {
+            tracing::error!(error = %e, job_type = %template.job_type, "Failed to enqueue recurring job");
+        }
+    }
+}
@@ -1,19 +1,45 @@
 //! Redis job queue implementation using LIST operations.
+//!
+//! Workers move jobs from the pending list into their own processing list
+//! (BLMOVE) and remove them only once handled, so a crashed instance does not
+//! lose the jobs it was running. Each instance keeps a heartbeat key alive; a
+//! reaper on every instance re-enqueues the processing lists of instances
+//! whose heartbeat has expired.
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use redis::aio::ConnectionManager;
-use redis::{AsyncCommands, Client};
-use tokio::sync::RwLock;
+use redis::{AsyncCommands, Client, Direction, ExistenceCheck, Script, SetExpiry, SetOptions};
+use tokio::task::JoinHandle;
 
-use apex_core::ports::{Job, JobQueue, JobQueueError, JobResult, QueueStats};
+use apex_core::ports::{DeadJob, Job, JobQueue, JobQueueError, JobResult, JobStatus, QueueStats};
 
+use super::limits::{DEFERRAL_DELAY, type_limits_from_env};
+use super::matches_type;
+use super::middleware::{JobMiddleware, JobMiddlewareStack};
+use super::recurring;
+use super::workers::Workers;
 use crate::cache::RedisConfig;
+use crate::env;
+
+/// Expiry for per-type running counters, so slots leaked by a crashed
+/// worker are eventually reclaimed.
+pub(super) const RUNNING_COUNTER_TTL: i64 = 3600;
+
+/// Delete a unique key only while it holds the job's id: once it has
+/// expired and another job claimed it, that job's claim stays.
+const RELEASE_UNIQUE_KEY: &str = r#"
+    if redis.call('GET', KEYS[1]) == ARGV[1] then
+        return redis.call('DEL', KEYS[1])
+    end
+    return 0
+"#;
 
 /// Redis job queue configuration.
 #[derive(Debug, Clone)]
@@ -26,6 +52,20 @@ pub struct RedisJobQueueConfig {
     pub workers: usize,
     /// Timeout for blocking pop (seconds)
     pub pop_timeout: u64,
+    /// Expiry for unique job keys (seconds), so a crashed worker cannot
+    /// block a key forever
+    pub unique_ttl: u64,
+    /// Expiry for job statuses and results (seconds), refreshed on every
+    /// state change
+    pub result_ttl: u64,
+    /// Seconds without a heartbeat after which an instance is presumed dead
+    /// and the jobs it was processing are re-enqueued
+    pub visibility_timeout: u64,
+    /// Maximum concurrent executions per job type across all workers
+    /// sharing the queue. Types not listed are unlimited.
+    pub type_limits: HashMap<String, usize>,
+    /// Most failed jobs kept in the dead letter list; the oldest are dropped
+    pub dead_letter_limit: usize,
 }
 
 impl Default for RedisJobQueueConfig {
@@ -35,6 +75,11 @@ impl Default for RedisJobQueueConfig {
             queue_name: "jobs".to_string(),
             workers: 4,
             pop_timeout: 5,
+            unique_ttl: 3600,
+            result_ttl: 3600,
+            visibility_timeout: 60,
+            type_limits: HashMap::new(),
+            dead_letter_limit: 1000,
         }
     }
 }
@@ -43,15 +88,14 @@ impl RedisJobQueueConfig {
     pub fn from_env() -> Self {
         Self {
             redis: RedisConfig::from_env(),
-            queue_name: std::env::var("JOB_QUEUE_NAME").unwrap_or_else(|_| "jobs".to_string()),
-            workers: std::env::var("JOB_QUEUE_WORKERS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(4),
-            pop_timeout: std::env::var("JOB_QUEUE_POP_TIMEOUT")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(5),
+            queue_name: env::JOB_QUEUE_NAME.string(),
+            workers: env::JOB_QUEUE_WORKERS.get(),
+            pop_timeout: env::JOB_QUEUE_POP_TIMEOUT.get(),
+            unique_ttl: env::JOB_QUEUE_UNIQUE_TTL.get(),
+            result_ttl: env::JOB_QUEUE_RESULT_TTL.get(),
+            visibility_timeout: env::JOB_QUEUE_VISIBILITY_TIMEOUT.get(),
+            type_limits: type_limits_from_env(),
+            dead_letter_limit: env::JOB_QUEUE_DEAD_LETTER_LIMIT.get(),
         }
     }
 }
@@ -61,15 +105,35 @@ pub struct RedisJobQueue {
     conn: ConnectionManager,
     config: RedisJobQueueConfig,
     stats: Arc<JobStats>,
-    running: Arc<RwLock<bool>>,
+    workers: Workers,
+    /// Identifies this instance's heartbeat and processing lists.
+    instance_id: String,
+    /// Processing lists of this instance's workers.
+    processing: Arc<Mutex<Vec<String>>>,
+    next_worker: AtomicUsize,
+    /// Heartbeat and reaper task, stopped once the workers have drained.
+    maintenance: Mutex<Option<JoinHandle<()>>>,
+    middleware: JobMiddlewareStack,
 }
 
 #[derive(Debug, Default)]
-struct JobStats {
-    pending: AtomicUsize,
-    processing: AtomicUsize,
-    completed: AtomicUsize,
-    failed: AtomicUsize,
+pub(super) struct JobStats {
+    pub(super) pending: AtomicUsize,
+    pub(super) processing: AtomicUsize,
+    pub(super) completed: AtomicUsize,
+    pub(super) failed: AtomicUsize,
+}
+
+impl JobStats {
+    pub(super) fn snapshot(&self) -> QueueStats {
+        QueueStats {
+            pending: self.pending.load(Ordering::Relaxed),
+            processing: self.processing.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            dead: 0,
+        }
+    }
 }
 
 impl RedisJobQueue {
@@ -95,7 +159,12 @@ impl RedisJobQueue {
             conn,
             config,
             stats: Arc::new(JobStats::default()),
-            running: Arc::new(RwLock::new(false)),
+            workers: Workers::new(),
+            instance_id: uuid::Uuid::new_v4().to_string(),
+            processing: Arc::new(Mutex::new(Vec::new())),
+            next_worker: AtomicUsize::new(0),
+            maintenance: Mutex::new(None),
+            middleware: JobMiddlewareStack::default(),
         })
     }
 
@@ -104,21 +173,509 @@ impl RedisJobQueue {
         Self::new(RedisJobQueueConfig::from_env()).await
     }
 
+    /// Run every job through `middleware`, inside the middleware added before it.
+    pub fn with_middleware(mut self, middleware: impl JobMiddleware + 'static) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
     fn pending_key(&self) -> String {
         format!("{}:pending", self.config.queue_name)
     }
+
+    /// Keep this instance's heartbeat alive and reap dead instances' jobs.
+    fn start_maintenance(&self) {
+        let mut maintenance = self.maintenance.lock().unwrap();
+        if maintenance.is_some() {
+            return;
+        }
+
+        let mut conn = self.conn.clone();
+        let queue_name = self.config.queue_name.clone();
+        let instance_id = self.instance_id.clone();
+        let processing = self.processing.clone();
+        let stats = self.stats.clone();
+        let visibility_timeout = self.config.visibility_timeout.max(1);
+        let result_ttl = self.config.result_ttl;
+
+        *maintenance = Some(tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs((visibility_timeout / 3).max(1)));
+            loop {
+                interval.tick().await;
+
+                let own: Vec<String> = processing.lock().unwrap().clone();
+                if let Err(e) = heartbeat(
+                    &mut conn,
+                    &queue_name,
+                    &instance_id,
+                    &own,
+                    visibility_timeout,
+                )
+                .await
+                {
+                    tracing::warn!(error = %e, "Failed to refresh job queue heartbeat");
+                    continue;
+                }
+
+                match reap_dead_consumers(&mut conn, &queue_name, result_ttl).await {
+                    Ok(0) => {}
+                    Ok(requeued) => {
+                        stats.pending.fetch_add(requeued, Ordering::Relaxed);
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Failed to reap dead job workers"),
+                }
+            }
+        }));
+    }
+}
+
+fn processing_key(queue_name: &str, instance_id: &str, worker_id: usize) -> String {
+    format!("{}:processing:{}:{}", queue_name, instance_id, worker_id)
+}
+
+/// Hash of processing list -> owning instance.
+fn consumers_key(queue_name: &str) -> String {
+    format!("{}:consumers", queue_name)
+}
+
+fn heartbeat_key(queue_name: &str, instance_id: &str) -> String {
+    format!("{}:heartbeat:{}", queue_name, instance_id)
+}
+
+/// Mark the instance alive and (re-)register its processing lists.
+async fn heartbeat(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    instance_id: &str,
+    processing: &[String],
+    visibility_timeout: u64,
+) -> redis::RedisResult<()> {
+    let mut pipe = redis::pipe();
+    pipe.set_ex(
+        heartbeat_key(queue_name, instance_id),
+        1,
+        visibility_timeout,
+    )
+    .ignore();
+    if !processing.is_empty() {
+        let fields: Vec<(&str, &str)> = processing
+            .iter()
+            .map(|key| (key.as_str(), instance_id))
+            .collect();
+        pipe.hset_multiple(consumers_key(queue_name), &fields)
+            .ignore();
+    }
+    pipe.query_async(conn).await
+}
+
+/// Re-enqueue the jobs of instances whose heartbeat expired. Returns how
+/// many jobs were re-enqueued.
+///
+/// Type slots those jobs held are not released here: the worker may have
+/// died before claiming one, so leaked slots are left to the counter expiry.
+async fn reap_dead_consumers(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    result_ttl: u64,
+) -> redis::RedisResult<usize> {
+    let consumers: HashMap<String, String> = conn.hgetall(consumers_key(queue_name)).await?;
+    let pending_key = format!("{}:pending", queue_name);
+
+    let mut requeued = 0;
+    for (processing, instance_id) in consumers {
+        if conn
+            .exists::<_, bool>(heartbeat_key(queue_name, &instance_id))
+            .await?
+        {
+            continue;
+        }
+
+        let moved = requeue_processing(conn, &processing, &pending_key).await?;
+        mark_pending(conn, queue_name, &moved, result_ttl).await;
+        conn.hdel::<_, _, ()>(consumers_key(queue_name), &processing)
+            .await?;
+
+        if !moved.is_empty() {
+            tracing::warn!(
+                queue = %queue_name,
+                instance_id = %instance_id,
+                count = moved.len(),
+                "Re-enqueued jobs of a dead job worker"
+            );
+        }
+        requeued += moved.len();
+    }
+    Ok(requeued)
+}
+
+/// Move everything left in a processing list back to the head of the
+/// pending list, oldest first. Returns the moved payloads.
+async fn requeue_processing(
+    conn: &mut ConnectionManager,
+    processing_key: &str,
+    pending_key: &str,
+) -> redis::RedisResult<Vec<String>> {
+    let mut moved = Vec::new();
+    while let Some(json) = conn
+        .lmove::<_, _, Option<String>>(
+            processing_key,
+            pending_key,
+            Direction::Right,
+            Direction::Left,
+        )
+        .await?
+    {
+        moved.push(json);
+    }
+    Ok(moved)
+}
+
+async fn mark_pending(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    payloads: &[String],
+    result_ttl: u64,
+) {
+    for json in payloads {
+        if let Ok(job) = serde_json::from_str::<Job>(json) {
+            set_status(conn, queue_name, &job.id, &JobStatus::Pending, result_ttl).await;
+        }
+    }
+}
+
+/// Remove a handled job from the worker's processing list.
+async fn ack(conn: &mut ConnectionManager, processing_key: &str, taken: &str) {
+    if let Err(e) = conn.lrem::<_, _, ()>(processing_key, 1, taken).await {
+        tracing::warn!(error = %e, "Failed to remove job from processing list");
+    }
+}
+
+/// Atomically replace a taken job with `payload` on the pending list, at the
+/// head when `front` is set.
+async fn return_to_pending(
+    conn: &mut ConnectionManager,
+    processing_key: &str,
+    taken: &str,
+    pending_key: &str,
+    payload: &str,
+    front: bool,
+) -> redis::RedisResult<()> {
+    let mut pipe = redis::pipe();
+    pipe.atomic().lrem(processing_key, 1, taken).ignore();
+    if front {
+        pipe.lpush(pending_key, payload).ignore();
+    } else {
+        pipe.rpush(pending_key, payload).ignore();
+    }
+    pipe.query_async(conn).await
+}
+
+fn unique_key(queue_name: &str, key: &str) -> String {
+    format!("{}:unique:{}", queue_name, key)
+}
+
+/// Claim the job's unique key, if it has one. Returns false if another job
+/// holds it, in which case the job should be skipped.
+pub(super) async fn claim_unique_key(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    job: &Job,
+    ttl: u64,
+) -> Result<bool, JobQueueError> {
+    let Some(key) = &job.unique_key else {
+        return Ok(true);
+    };
+
+    let options = SetOptions::default()
+        .conditional_set(ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(ttl));
+    let claimed: Option<String> = conn
+        .set_options(unique_key(queue_name, key), &job.id, options)
+        .await
+        .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+
+    if claimed.is_none() {
+        tracing::debug!(job_id = %job.id, unique_key = %key, "Duplicate job skipped");
+    }
+    Ok(claimed.is_some())
+}
+
+/// Release the job's unique key once it reaches a terminal state, or could
+/// not be enqueued.
+pub(super) async fn release_unique_key(conn: &mut ConnectionManager, queue_name: &str, job: &Job) {
+    let Some(key) = &job.unique_key else {
+        return;
+    };
+    let released: redis::RedisResult<()> = Script::new(RELEASE_UNIQUE_KEY)
+        .key(unique_key(queue_name, key))
+        .arg(&job.id)
+        .invoke_async(conn)
+        .await;
+    if let Err(e) = released {
+        tracing::warn!(error = %e, unique_key = %key, "Failed to release unique job key");
+    }
+}
+
+/// Add the release of the job's unique key, if it has one, to `pipe`.
+fn pipe_release_unique_key(pipe: &mut redis::Pipeline, queue_name: &str, job: &Job) {
+    if let Some(key) = &job.unique_key {
+        pipe.cmd("EVAL")
+            .arg(RELEASE_UNIQUE_KEY)
+            .arg(1)
+            .arg(unique_key(queue_name, key))
+            .arg(&job.id)
+            .ignore();
+    }
+}
+
+fn status_key(queue_name: &str, job_id: &str) -> String {
+    format!("{}:status:{}", queue_name, job_id)
+}
+
+/// Record a job's status. Failures are logged; status is best-effort.
+pub(super) async fn set_status(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    job_id: &str,
+    status: &JobStatus,
+    ttl: u64,
+) {
+    let Ok(json) = serde_json::to_string(status) else {
+        return;
+    };
+    if let Err(e) = conn
+        .set_ex::<_, _, ()>(status_key(queue_name, job_id), json, ttl)
+        .await
+    {
+        tracing::warn!(error = %e, job_id = %job_id, "Failed to record job status");
+    }
+}
+
+pub(super) async fn get_status(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    job_id: &str,
+) -> Result<Option<JobStatus>, JobQueueError> {
+    let json: Option<String> = conn
+        .get(status_key(queue_name, job_id))
+        .await
+        .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+
+    json.map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| JobQueueError::Backend(e.to_string()))
+}
+
+/// Outcome status of a handled job. A retryable failure with attempts left
+/// goes back to pending.
+pub(super) fn status_for(result: &JobResult, job: &Job) -> JobStatus {
+    match result {
+        JobResult::Success => JobStatus::Completed { result: None },
+        JobResult::SuccessWith(value) => JobStatus::Completed {
+            result: Some(value.clone()),
+        },
+        JobResult::Retry(_) if job.attempts < job.max_attempts => JobStatus::Pending,
+        JobResult::Retry(reason) | JobResult::Failed(reason) => JobStatus::Failed {
+            error: reason.clone(),
+        },
+    }
+}
+
+fn dead_key(queue_name: &str) -> String {
+    format!("{}:dead", queue_name)
+}
+
+/// Add a permanently failed job to the dead letter list, dropping the oldest
+/// entries beyond `limit`. Failures are logged.
+pub(super) async fn bury(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    job: Job,
+    error: &str,
+    limit: usize,
+) {
+    let Ok(json) = serde_json::to_string(&DeadJob::new(job, error)) else {
+        return;
+    };
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .lpush(dead_key(queue_name), json)
+        .ignore()
+        .ltrim(dead_key(queue_name), 0, limit.max(1) as isize - 1)
+        .ignore();
+    if let Err(e) = pipe.query_async::<()>(conn).await {
+        tracing::warn!(error = %e, "Failed to add job to dead letter list");
+    }
+}
+
+pub(super) async fn dead_count(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+) -> Result<usize, JobQueueError> {
+    conn.llen(dead_key(queue_name))
+        .await
+        .map_err(|e| JobQueueError::Backend(e.to_string()))
+}
+
+/// Entries read per round trip when scanning a list or stream.
+pub(super) const SCAN_CHUNK: usize = 500;
+
+/// Entries of a list of JSON values from the head, at most `limit` that
+/// parse and satisfy `keep`. Unreadable entries are skipped.
+async fn scan_list<T: serde::de::DeserializeOwned>(
+    conn: &mut ConnectionManager,
+    key: &str,
+    limit: usize,
+    keep: impl Fn(&T) -> bool,
+) -> Result<Vec<(String, T)>, JobQueueError> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    while found.len() < limit {
+        let chunk: Vec<String> = conn
+            .lrange(key, start, start + SCAN_CHUNK as isize - 1)
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        let exhausted = chunk.len() < SCAN_CHUNK;
+        start += chunk.len() as isize;
+
+        found.extend(chunk.into_iter().filter_map(|json| {
+            let value = serde_json::from_str(&json).ok()?;
+            keep(&value).then_some((json, value))
+        }));
+        if exhausted {
+            break;
+        }
+    }
+    found.truncate(limit);
+    Ok(found)
+}
+
+/// Remove the first entry of a list satisfying `matches`. Returns `None` if
+/// none does, or another caller removed it first.
+async fn take_from_list<T: serde::de::DeserializeOwned>(
+    conn: &mut ConnectionManager,
+    key: &str,
+    matches: impl Fn(&T) -> bool,
+) -> Result<Option<T>, JobQueueError> {
+    let Some((json, value)) = scan_list(conn, key, 1, matches).await?.pop() else {
+        return Ok(None);
+    };
+    let removed: usize = conn
+        .lrem(key, 1, &json)
+        .await
+        .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+    Ok((removed > 0).then_some(value))
+}
+
+/// Dead jobs, newest first.
+pub(super) async fn list_dead(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    job_type: Option<&str>,
+    limit: usize,
+) -> Result<Vec<DeadJob>, JobQueueError> {
+    let found = scan_list(conn, &dead_key(queue_name), limit, |dead: &DeadJob| {
+        matches_type(&dead.job, job_type)
+    })
+    .await?;
+    Ok(found.into_iter().map(|(_, dead)| dead).collect())
+}
+
+/// Remove a dead job from the list. Returns `None` if no dead job has the id.
+pub(super) async fn take_dead(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    job_id: &str,
+) -> Result<Option<DeadJob>, JobQueueError> {
+    take_from_list(conn, &dead_key(queue_name), |dead: &DeadJob| {
+        dead.job.id == job_id
+    })
+    .await
+}
+
+/// Release the unique keys and drop the statuses of purged jobs.
+pub(super) async fn forget_jobs(
+    conn: &mut ConnectionManager,
+    queue_name: &str,
+    jobs: &[Job],
+) -> Result<(), JobQueueError> {
+    if jobs.is_empty() {
+        return Ok(());
+    }
+    let mut pipe = redis::pipe();
+    for job in jobs {
+        pipe.del(status_key(queue_name, &job.id)).ignore();
+        pipe_release_unique_key(&mut pipe, queue_name, job);
+    }
+    pipe.query_async(conn)
+        .await
+        .map_err(|e| JobQueueError::Backend(e.to_string()))
+}
+
+pub(super) fn running_key(queue_name: &str, job_type: &str) -> String {
+    format!("{}:running:{}", queue_name, job_type)
+}
+
+/// Claim one of the job type's slots. Returns false if the type is at its limit.
+pub(super) async fn acquire_type_slot(
+    conn: &mut ConnectionManager,
+    key: &str,
+    limit: usize,
+) -> redis::RedisResult<bool> {
+    let running: i64 = conn.incr(key, 1).await?;
+    conn.expire::<_, ()>(key, RUNNING_COUNTER_TTL).await?;
+
+    if running > limit as i64 {
+        conn.decr::<_, _, ()>(key, 1).await?;
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+pub(super) async fn release_type_slot(conn: &mut ConnectionManager, key: &str) {
+    if let Err(e) = conn.decr::<_, _, ()>(key, 1).await {
+        tracing::warn!(error = %e, key = %key, "Failed to release job type slot");
+    }
 }
 
 #[async_trait]
 impl JobQueue for RedisJobQueue {
-    async fn enqueue(&self, job: Job) -> Result<(), JobQueueError> {
+    async fn enqueue(&self, mut job: Job) -> Result<(), JobQueueError> {
+        if self.workers.is_stopping() {
+            return Err(JobQueueError::ShuttingDown);
+        }
+        crate::context::attach(&mut job);
+        self.middleware.enqueue(&mut job).await?;
+
         let mut conn = self.conn.clone();
         let job_json =
             serde_json::to_string(&job).map_err(|e| JobQueueError::EnqueueError(e.to_string()))?;
 
-        conn.rpush::<_, _, ()>(&self.pending_key(), &job_json)
-            .await
-            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        if !claim_unique_key(
+            &mut conn,
+            &self.config.queue_name,
+            &job,
+            self.config.unique_ttl,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        set_status(
+            &mut conn,
+            &self.config.queue_name,
+            &job.id,
+            &JobStatus::Pending,
+            self.config.result_ttl,
+        )
+        .await;
+        if let Err(e) = conn.rpush::<_, _, ()>(&self.pending_key(), &job_json).await {
+            // Or retries would be skipped as duplicates until the key expires
+            release_unique_key(&mut conn, &self.config.queue_name, &job).await;
+            return Err(JobQueueError::Backend(e.to_string()));
+        }
 
         self.stats.pending.fetch_add(1, Ordering::Relaxed);
         tracing::debug!(job_id = %job.id, job_type = %job.job_type, "Job enqueued");
@@ -126,23 +683,142 @@ impl JobQueue for RedisJobQueue {
         Ok(())
     }
 
+    async fn enqueue_batch(&self, mut jobs: Vec<Job>) -> Result<(), JobQueueError> {
+        if self.workers.is_stopping() {
+            return Err(JobQueueError::ShuttingDown);
+        }
+        for job in &mut jobs {
+            crate::context::attach(job);
+            self.middleware.enqueue(job).await?;
+        }
+
+        let payloads = jobs
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| JobQueueError::EnqueueError(e.to_string()))?;
+        let pending = serde_json::to_string(&JobStatus::Pending)
+            .map_err(|e| JobQueueError::EnqueueError(e.to_string()))?;
+        let mut conn = self.conn.clone();
+
+        // Claim all unique keys in one round trip
+        let unique: Vec<&Job> = jobs.iter().filter(|j| j.unique_key.is_some()).collect();
+        let mut skipped = std::collections::HashSet::new();
+        if !unique.is_empty() {
+            let mut pipe = redis::pipe();
+            for job in &unique {
+                let key = job.unique_key.as_deref().unwrap_or_default();
+                pipe.cmd("SET")
+                    .arg(unique_key(&self.config.queue_name, key))
+                    .arg(&job.id)
+                    .arg("NX")
+                    .arg("EX")
+                    .arg(self.config.unique_ttl);
+            }
+            let claimed: Vec<Option<String>> = pipe
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+            for (job, claimed) in unique.iter().zip(claimed) {
+                if claimed.is_none() {
+                    skipped.insert(job.id.clone());
+                }
+            }
+        }
+
+        let (queued, payloads): (Vec<&Job>, Vec<String>) = jobs
+            .iter()
+            .zip(payloads)
+            .filter(|(job, _)| !skipped.contains(&job.id))
+            .unzip();
+        if payloads.is_empty() {
+            return Ok(());
+        }
+
+        // Statuses and the jobs themselves in one round trip
+        let mut pipe = redis::pipe();
+        for job in &queued {
+            pipe.set_ex(
+                status_key(&self.config.queue_name, &job.id),
+                &pending,
+                self.config.result_ttl,
+            )
+            .ignore();
+        }
+        pipe.rpush(self.pending_key(), &payloads).ignore();
+        if let Err(e) = pipe.query_async::<()>(&mut conn).await {
+            // Or retries would be skipped as duplicates until the keys expire
+            let mut release = redis::pipe();
+            for job in &queued {
+                pipe_release_unique_key(&mut release, &self.config.queue_name, job);
+            }
+            if let Err(e) = release.query_async::<()>(&mut conn).await {
+                tracing::warn!(error = %e, "Failed to release unique job keys");
+            }
+            return Err(JobQueueError::Backend(e.to_string()));
+        }
+
+        self.stats
+            .pending
+            .fetch_add(payloads.len(), Ordering::Relaxed);
+        tracing::debug!(
+            count = payloads.len(),
+            skipped = skipped.len(),
+            "Job batch enqueued"
+        );
+
+        Ok(())
+    }
+
+    async fn enqueue_recurring(
+        self: Arc<Self>,
+        job: Job,
+        schedule: &str,
+    ) -> Result<(), JobQueueError> {
+        recurring::register(&self, &self.workers, job, schedule)
+    }
+
     async fn start_worker<F>(&self, handler: F) -> Result<(), JobQueueError>
     where
         F: Fn(Job) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync + 'static,
     {
-        *self.running.write().await = true;
-        let handler = Arc::new(handler);
+        let handler = self.middleware.wrap(handler);
+
+        // Register the processing lists before any job can land in them
+        let first_worker = self
+            .next_worker
+            .fetch_add(self.config.workers, Ordering::Relaxed);
+        let worker_ids = first_worker..first_worker + self.config.workers;
+        let keys: Vec<String> = worker_ids
+            .clone()
+            .map(|id| processing_key(&self.config.queue_name, &self.instance_id, id))
+            .collect();
+        self.processing.lock().unwrap().extend(keys.iter().cloned());
+        heartbeat(
+            &mut self.conn.clone(),
+            &self.config.queue_name,
+            &self.instance_id,
+            &keys,
+            self.config.visibility_timeout.max(1),
+        )
+        .await
+        .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        self.start_maintenance();
 
-        for worker_id in 0..self.config.workers {
+        for (worker_id, processing_key) in worker_ids.zip(keys) {
             let conn = self.conn.clone();
             let pending_key = self.pending_key();
             let stats = self.stats.clone();
-            let running = self.running.clone();
+            let stop = self.workers.stop_signal();
+            let in_flight = self.workers.in_flight();
             let handler = handler.clone();
             let pop_timeout = self.config.pop_timeout;
             let queue_name = self.config.queue_name.clone();
+            let type_limits = self.config.type_limits.clone();
+            let result_ttl = self.config.result_ttl;
+            let dead_letter_limit = self.config.dead_letter_limit;
 
-            tokio::spawn(async move {
+            self.workers.spawn(async move {
                 tracing::info!(
                     worker_id = worker_id,
                     queue = %queue_name,
@@ -152,20 +828,44 @@ impl JobQueue for RedisJobQueue {
                 let mut conn = conn;
 
                 loop {
-                    if !*running.read().await {
+                    if *stop.borrow() {
                         tracing::info!(worker_id = worker_id, "Worker stopping");
                         break;
                     }
 
-                    // Blocking pop with timeout
-                    let result: Result<Option<(String, String)>, _> =
-                        conn.blpop(&pending_key, pop_timeout as f64).await;
+                    // Blocking move into the processing list. Not raced against
+                    // the stop signal: dropping it mid-flight could strand a job.
+                    let result: Result<Option<String>, _> = conn
+                        .blmove(
+                            &pending_key,
+                            &processing_key,
+                            Direction::Left,
+                            Direction::Right,
+                            pop_timeout as f64,
+                        )
+                        .await;
 
                     let job_json = match result {
-                        Ok(Some((_, json))) => json,
+                        Ok(Some(json)) if *stop.borrow() => {
+                            // Shutdown began while we were waiting; hand the job back
+                            if let Err(e) = return_to_pending(
+                                &mut conn,
+                                &processing_key,
+                                &json,
+                                &pending_key,
+                                &json,
+                                true,
+                            )
+                            .await
+                            {
+                                tracing::error!(error = %e, "Failed to return job to queue on shutdown");
+                            }
+                            continue;
+                        }
+                        Ok(Some(json)) => json,
                         Ok(None) => continue, // Timeout, loop again
                         Err(e) => {
-                            tracing::error!(error = %e, "Redis BLPOP error");
+                            tracing::error!(error = %e, "Redis BLMOVE error");
                             tokio::time::sleep(Duration::from_secs(1)).await;
                             continue;
                         }
@@ -175,16 +875,58 @@ impl JobQueue for RedisJobQueue {
                         Ok(j) => j,
                         Err(e) => {
                             tracing::error!(error = %e, "Failed to deserialize job");
+                            ack(&mut conn, &processing_key, &job_json).await;
                             stats.failed.fetch_add(1, Ordering::Relaxed);
                             continue;
                         }
                     };
 
+                    // Defer jobs whose type is at its concurrency limit
+                    let slot_key = match type_limits.get(&job.job_type) {
+                        Some(&limit) => {
+                            let key = running_key(&queue_name, &job.job_type);
+                            let acquired = acquire_type_slot(&mut conn, &key, limit)
+                                .await
+                                .unwrap_or_else(|e| {
+                                    tracing::error!(error = %e, "Failed to claim job type slot");
+                                    false
+                                });
+                            if !acquired {
+                                if let Err(e) = return_to_pending(
+                                    &mut conn,
+                                    &processing_key,
+                                    &job_json,
+                                    &pending_key,
+                                    &job_json,
+                                    false,
+                                )
+                                .await
+                                {
+                                    tracing::error!(error = %e, job_id = %job.id, "Failed to re-enqueue deferred job");
+                                    stats.failed.fetch_add(1, Ordering::Relaxed);
+                                }
+                                tokio::time::sleep(DEFERRAL_DELAY).await;
+                                continue;
+                            }
+                            Some(key)
+                        }
+                        None => None,
+                    };
+
                     stats.pending.fetch_sub(1, Ordering::Relaxed);
                     stats.processing.fetch_add(1, Ordering::Relaxed);
 
+                    in_flight.start(&job);
                     job.attempts += 1;
                     let job_id = job.id.clone();
+                    set_status(
+                        &mut conn,
+                        &queue_name,
+                        &job_id,
+                        &JobStatus::Processing,
+                        result_ttl,
+                    )
+                    .await;
                     let job_type = job.job_type.clone();
 
                     tracing::debug!(
@@ -195,8 +937,18 @@ impl JobQueue for RedisJobQueue {
                         "Processing job"
                     );
 
-                    match handler(job.clone()).await {
-                        JobResult::Success => {
+                    let result = handler(job.clone()).await;
+                    in_flight.finish(&job_id);
+
+                    // Record the outcome before the unique key is released, so
+                    // a replacement job cannot be observed before it
+                    let status = status_for(&result, &job);
+                    set_status(&mut conn, &queue_name, &job_id, &status, result_ttl).await;
+
+                    match result {
+                        JobResult::Success | JobResult::SuccessWith(_) => {
+                            ack(&mut conn, &processing_key, &job_json).await;
+                            release_unique_key(&mut conn, &queue_name, &job).await;
                             stats.processing.fetch_sub(1, Ordering::Relaxed);
                             stats.completed.fetch_add(1, Ordering::Relaxed);
                             tracing::debug!(job_id = %job_id, "Job completed successfully");
@@ -205,11 +957,20 @@ impl JobQueue for RedisJobQueue {
                             stats.processing.fetch_sub(1, Ordering::Relaxed);
                             if job.attempts < job.max_attempts {
                                 // Re-enqueue for retry
-                                let job_json = serde_json::to_string(&job).unwrap();
-                                if let Err(e) =
-                                    conn.rpush::<_, _, ()>(&pending_key, &job_json).await
+                                let retry_json = serde_json::to_string(&job).unwrap();
+                                if let Err(e) = return_to_pending(
+                                    &mut conn,
+                                    &processing_key,
+                                    &job_json,
+                                    &pending_key,
+                                    &retry_json,
+                                    false,
+                                )
+                                .await
                                 {
                                     tracing::error!(error = %e, "Failed to re-enqueue job for retry");
+                                    ack(&mut conn, &processing_key, &job_json).await;
+                                    release_unique_key(&mut conn, &queue_name, &job).await;
                                     stats.failed.fetch_add(1, Ordering::Relaxed);
                                 } else {
                                     stats.pending.fetch_add(1, Ordering::Relaxed);
@@ -221,20 +982,31 @@ impl JobQueue for RedisJobQueue {
                                     );
                                 }
                             } else {
+                                ack(&mut conn, &processing_key, &job_json).await;
+                                release_unique_key(&mut conn, &queue_name, &job).await;
                                 stats.failed.fetch_add(1, Ordering::Relaxed);
                                 tracing::error!(
                                     job_id = %job_id,
                                     reason = %reason,
                                     "Job failed after max retries"
                                 );
+                                bury(&mut conn, &queue_name, job, &reason, dead_letter_limit)
+                                    .await;
                             }
                         }
                         JobResult::Failed(reason) => {
+                            ack(&mut conn, &processing_key, &job_json).await;
+                            release_unique_key(&mut conn, &queue_name, &job).await;
                             stats.processing.fetch_sub(1, Ordering::Relaxed);
                             stats.failed.fetch_add(1, Ordering::Relaxed);
                             tracing::error!(job_id = %job_id, reason = %reason, "Job failed");
+                            bury(&mut conn, &queue_name, job, &reason, dead_letter_limit).await;
                         }
                     }
+
+                    if let Some(key) = slot_key {
+                        release_type_slot(&mut conn, &key).await;
+                    }
                 }
             });
         }
@@ -243,13 +1015,199 @@ impl JobQueue for RedisJobQueue {
     }
 
     async fn stats(&self) -> Result<QueueStats, JobQueueError> {
+        let dead = dead_count(&mut self.conn.clone(), &self.config.queue_name).await?;
         Ok(QueueStats {
-            pending: self.stats.pending.load(Ordering::Relaxed),
-            processing: self.stats.processing.load(Ordering::Relaxed),
-            completed: self.stats.completed.load(Ordering::Relaxed),
-            failed: self.stats.failed.load(Ordering::Relaxed),
+            dead,
+            ..self.stats.snapshot()
         })
     }
+
+    async fn get_status(&self, job_id: &str) -> Result<Option<JobStatus>, JobQueueError> {
+        get_status(&mut self.conn.clone(), &self.config.queue_name, job_id).await
+    }
+
+    async fn list_pending(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError> {
+        let found = scan_list(&mut self.conn.clone(), &self.pending_key(), limit, |job| {
+            matches_type(job, job_type)
+        })
+        .await?;
+        Ok(found.into_iter().map(|(_, job)| job).collect())
+    }
+
+    async fn list_processing(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError> {
+        let mut conn = self.conn.clone();
+        let lists: Vec<String> = conn
+            .hkeys(consumers_key(&self.config.queue_name))
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+
+        let mut jobs = Vec::new();
+        for list in lists {
+            if jobs.len() >= limit {
+                break;
+            }
+            let found = scan_list(&mut conn, &list, limit - jobs.len(), |job| {
+                matches_type(job, job_type)
+            })
+            .await?;
+            jobs.extend(found.into_iter().map(|(_, job)| job));
+        }
+        jobs.sort_by_key(|job| job.created_at);
+        Ok(jobs)
+    }
+
+    async fn list_dead(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DeadJob>, JobQueueError> {
+        list_dead(
+            &mut self.conn.clone(),
+            &self.config.queue_name,
+            job_type,
+            limit,
+        )
+        .await
+    }
+
+    async fn retry_dead(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        let mut conn = self.conn.clone();
+        let Some(dead) = take_dead(&mut conn, &self.config.queue_name, job_id).await? else {
+            return Ok(false);
+        };
+
+        let mut job = dead.job.clone();
+        job.attempts = 0;
+        if let Err(e) = self.enqueue(job).await {
+            let limit = self.config.dead_letter_limit;
+            bury(
+                &mut conn,
+                &self.config.queue_name,
+                dead.job,
+                &dead.error,
+                limit,
+            )
+            .await;
+            return Err(e);
+        }
+        tracing::info!(job_id = %job_id, "Dead job re-enqueued");
+        Ok(true)
+    }
+
+    async fn delete_pending(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        let mut conn = self.conn.clone();
+        let Some(job) =
+            take_from_list(&mut conn, &self.pending_key(), |job: &Job| job.id == job_id).await?
+        else {
+            return Ok(false);
+        };
+        forget_jobs(&mut conn, &self.config.queue_name, &[job]).await?;
+
+        let _ = self
+            .stats
+            .pending
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        tracing::info!(job_id = %job_id, "Pending job deleted");
+        Ok(true)
+    }
+
+    async fn delete_dead(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        let taken = take_dead(&mut self.conn.clone(), &self.config.queue_name, job_id).await?;
+        Ok(taken.is_some())
+    }
+
+    async fn purge(&self) -> Result<usize, JobQueueError> {
+        let mut conn = self.conn.clone();
+        let (entries,): (Vec<String>,) = redis::pipe()
+            .atomic()
+            .lrange(self.pending_key(), 0, -1)
+            .del(self.pending_key())
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+
+        let purged: Vec<Job> = entries
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect();
+        forget_jobs(&mut conn, &self.config.queue_name, &purged).await?;
+
+        // The local count only covers jobs this instance enqueued, all of
+        // which are gone now
+        self.stats.pending.store(0, Ordering::Relaxed);
+        tracing::info!(queue = %self.config.queue_name, purged = entries.len(), "Job queue purged");
+        Ok(entries.len())
+    }
+
+    async fn shutdown(&self, timeout: Duration) -> Result<(), JobQueueError> {
+        let interrupted = self.workers.shutdown(timeout).await;
+        if let Some(maintenance) = self.maintenance.lock().unwrap().take() {
+            maintenance.abort();
+        }
+
+        // Whatever is left in our processing lists was interrupted; put it
+        // back at the head of the queue, with the attempt that was cut short
+        // not counted, and deregister before the heartbeat is dropped
+        let mut conn = self.conn.clone();
+        let processing = std::mem::take(&mut *self.processing.lock().unwrap());
+        let mut requeued = 0;
+        for key in &processing {
+            let moved = requeue_processing(&mut conn, key, &self.pending_key())
+                .await
+                .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+            mark_pending(
+                &mut conn,
+                &self.config.queue_name,
+                &moved,
+                self.config.result_ttl,
+            )
+            .await;
+            requeued += moved.len();
+        }
+        if !processing.is_empty() {
+            let mut pipe = redis::pipe();
+            pipe.hdel(consumers_key(&self.config.queue_name), &processing)
+                .ignore()
+                .del(heartbeat_key(&self.config.queue_name, &self.instance_id))
+                .ignore();
+            pipe.query_async::<()>(&mut conn)
+                .await
+                .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        }
+
+        if interrupted.is_empty() && requeued == 0 {
+            tracing::info!(queue = %self.config.queue_name, "Job queue stopped");
+            return Ok(());
+        }
+
+        for job in &interrupted {
+            if self.config.type_limits.contains_key(&job.job_type) {
+                let key = running_key(&self.config.queue_name, &job.job_type);
+                release_type_slot(&mut conn, &key).await;
+            }
+        }
+
+        self.stats
+            .processing
+            .fetch_sub(interrupted.len(), Ordering::Relaxed);
+        self.stats.pending.fetch_add(requeued, Ordering::Relaxed);
+        tracing::warn!(
+            queue = %self.config.queue_name,
+            count = requeued,
+            "Re-enqueued jobs interrupted by shutdown"
+        );
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -261,14 +1219,20 @@ mod tests {
     async fn get_test_job_queue() -> Option<RedisJobQueue> {
         let config = RedisJobQueueConfig {
             redis: RedisConfig {
-                url: std::env::var("REDIS_URL")
-                    .unwrap_or_else(|_| "redis://localhost:6389".to_string()),
+                url: env::REDIS_URL
+                    .raw()
+                    .unwrap_or_else(|| "redis://localhost:6389".to_string()),
                 connect_timeout: Duration::from_secs(1),
                 fallback_to_memory: false,
             },
             queue_name: "test_jobs".to_string(),
             workers: 1,
             pop_timeout: 1,
+            unique_ttl: 60,
+            result_ttl: 60,
+            visibility_timeout: 3,
+            dead_letter_limit: 10,
+            type_limits: HashMap::new(),
         };
 
         RedisJobQueue::new(config).await.ok()
@@ -307,6 +1271,69 @@ mod tests {
         let stats = queue.stats().await.unwrap();
         assert_eq!(stats.completed, 1);
 
-        *queue.running.write().await = false;
+        queue.shutdown(Duration::from_secs(5)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unique_key_is_released_only_by_its_holder() {
+        let queue = match get_test_job_queue().await {
+            Some(q) => q,
+            None => return,
+        };
+        let mut conn = queue.conn.clone();
+        let queue_name = &queue.config.queue_name;
+        let key = format!("release-{}", uuid::Uuid::new_v4());
+
+        let first = Job::new("test_job", serde_json::json!({})).with_unique_key(&key);
+        assert!(
+            claim_unique_key(&mut conn, queue_name, &first, 60)
+                .await
+                .unwrap()
+        );
+        // The first claim expired and a second job took the key
+        let second = Job::new("test_job", serde_json::json!({})).with_unique_key(&key);
+        conn.set::<_, _, ()>(unique_key(queue_name, &key), &second.id)
+            .await
+            .unwrap();
+
+        release_unique_key(&mut conn, queue_name, &first).await;
+        let holder: Option<String> = conn.get(unique_key(queue_name, &key)).await.unwrap();
+        assert_eq!(holder.as_deref(), Some(second.id.as_str()));
+
+        release_unique_key(&mut conn, queue_name, &second).await;
+        let holder: Option<String> = conn.get(unique_key(queue_name, &key)).await.unwrap();
+        assert_eq!(holder, None);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_of_dead_instance_are_requeued() {
+        let queue = match get_test_job_queue().await {
+            Some(q) => q,
+            None => return,
+        };
+        let mut conn = queue.conn.clone();
+        let queue_name = &queue.config.queue_name;
+
+        // A worker of another instance took a job, then the instance died
+        let job = Job::new("test_job", serde_json::json!({}));
+        let json = serde_json::to_string(&job).unwrap();
+        let dead = processing_key(queue_name, "dead-instance", 0);
+        conn.rpush::<_, _, ()>(&dead, &json).await.unwrap();
+        conn.hset::<_, _, _, ()>(consumers_key(queue_name), &dead, "dead-instance")
+            .await
+            .unwrap();
+
+        let requeued = reap_dead_consumers(&mut conn, queue_name, 60)
+            .await
+            .unwrap();
+        assert!(requeued >= 1);
+
+        let pending: Vec<String> = conn.lrange(queue.pending_key(), 0, -1).await.unwrap();
+        assert!(pending.contains(&json));
+        assert_eq!(conn.llen::<_, usize>(&dead).await.unwrap(), 0);
+
+        conn.lrem::<_, _, ()>(queue.pending_key(), 0, &json)
+            .await
+            .unwrap();
     }
 }
@@ -0,0 +1,875 @@
+//! Redis Streams job queue with consumer groups.
+//!
+//! Jobs are appended with XADD and read through a consumer group, so each job
+//! goes to one consumer across all instances sharing the group. Entries stay
+//! in the group's pending entries list until acknowledged; entries whose
+//! consumer stopped acknowledging them (crash, lost connection) are claimed by
+//! other workers with XAUTOCLAIM once idle for `claim_idle`. Delivery is
+//! at-least-once, so handlers must be idempotent.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::streams::{
+    StreamAutoClaimOptions, StreamAutoClaimReply, StreamId, StreamInfoGroupsReply,
+    StreamPendingCountReply, StreamRangeReply, StreamReadOptions, StreamReadReply,
+};
+use redis::{AsyncCommands, Client};
+
+use apex_core::ports::{DeadJob, Job, JobQueue, JobQueueError, JobResult, JobStatus, QueueStats};
+
+use super::limits::{DEFERRAL_DELAY, type_limits_from_env};
+use super::matches_type;
+use super::middleware::{JobMiddleware, JobMiddlewareStack};
+use super::recurring;
+use super::redis::{
+    JobStats, SCAN_CHUNK, acquire_type_slot, bury, claim_unique_key, dead_count, forget_jobs,
+    get_status, list_dead, release_type_slot, release_unique_key, running_key, set_status,
+    status_for, take_dead,
+};
+use super::workers::Workers;
+use crate::cache::RedisConfig;
+use crate::env;
+
+/// Stream entry field holding the serialized job.
+const JOB_FIELD: &str = "job";
+
+/// Redis Streams job queue configuration.
+#[derive(Debug, Clone)]
+pub struct RedisStreamJobQueueConfig {
+    /// Redis connection config
+    pub redis: RedisConfig,
+    /// Queue name/key prefix
+    pub queue_name: String,
+    /// Consumer group shared by every instance processing the queue
+    pub group: String,
+    /// Consumer name prefix, unique per instance. Worker consumers are named
+    /// `{consumer}-{worker}`.
+    pub consumer: String,
+    /// Number of worker consumers
+    pub workers: usize,
+    /// Timeout for blocking reads (seconds)
+    pub block_timeout: u64,
+    /// Seconds an entry may go unacknowledged before another worker claims it
+    pub claim_idle: u64,
+    /// Expiry for unique job keys (seconds)
+    pub unique_ttl: u64,
+    /// Expiry for job statuses and results (seconds)
+    pub result_ttl: u64,
+    /// Maximum concurrent executions per job type across all consumers.
+    /// Types not listed are unlimited.
+    pub type_limits: HashMap<String, usize>,
+    /// Most failed jobs kept in the dead letter list; the oldest are dropped
+    pub dead_letter_limit: usize,
+}
+
+impl Default for RedisStreamJobQueueConfig {
+    fn default() -> Self {
+        Self {
+            redis: RedisConfig::default(),
+            queue_name: "jobs".to_string(),
+            group: "workers".to_string(),
+            consumer: uuid::Uuid::new_v4().to_string(),
+            workers: 4,
+            block_timeout: 5,
+            claim_idle: 60,
+            unique_ttl: 3600,
+            result_ttl: 3600,
+            type_limits: HashMap::new(),
+            dead_letter_limit: 1000,
+        }
+    }
+}
+
+impl RedisStreamJobQueueConfig {
+    pub fn from_env() -> Self {
+        Self {
+            redis: RedisConfig::from_env(),
+            queue_name: env::JOB_QUEUE_NAME.string(),
+            group: env::JOB_STREAM_GROUP.string(),
+            consumer: env::JOB_STREAM_CONSUMER
+                .raw()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            workers: env::JOB_QUEUE_WORKERS.get(),
+            block_timeout: env::JOB_QUEUE_POP_TIMEOUT.get(),
+            claim_idle: env::JOB_QUEUE_VISIBILITY_TIMEOUT.get(),
+            unique_ttl: env::JOB_QUEUE_UNIQUE_TTL.get(),
+            result_ttl: env::JOB_QUEUE_RESULT_TTL.get(),
+            type_limits: type_limits_from_env(),
+            dead_letter_limit: env::JOB_QUEUE_DEAD_LETTER_LIMIT.get(),
+        }
+    }
+}
+
+/// Redis-backed job queue using streams and a consumer group.
+pub struct RedisStreamJobQueue {
+    conn: ConnectionManager,
+    config: RedisStreamJobQueueConfig,
+    stats: Arc<JobStats>,
+    workers: Workers,
+    middleware: JobMiddlewareStack,
+}
+
+impl RedisStreamJobQueue {
+    pub async fn new(config: RedisStreamJobQueueConfig) -> Result<Self, JobQueueError> {
+        let client = Client::open(config.redis.url.as_str())
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+
+        let conn_manager_fut = ConnectionManager::new(client);
+        let mut conn = tokio::time::timeout(config.redis.connect_timeout, conn_manager_fut)
+            .await
+            .map_err(|_| JobQueueError::Backend("Connection timed out".to_string()))?
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+
+        // Start the group at the beginning of the stream so jobs enqueued
+        // before the first worker came up are not skipped
+        let created: redis::RedisResult<()> = conn
+            .xgroup_create_mkstream(stream_key(&config.queue_name), &config.group, "0")
+            .await;
+        if let Err(e) = created
+            && e.code() != Some("BUSYGROUP")
+        {
+            return Err(JobQueueError::Backend(e.to_string()));
+        }
+
+        tracing::info!(
+            url = %config.redis.url,
+            queue = %config.queue_name,
+            group = %config.group,
+            consumer = %config.consumer,
+            workers = config.workers,
+            "Connected to Redis stream job queue"
+        );
+
+        Ok(Self {
+            conn,
+            config,
+            stats: Arc::new(JobStats::default()),
+            workers: Workers::new(),
+            middleware: JobMiddlewareStack::default(),
+        })
+    }
+
+    /// Create from environment configuration.
+    pub async fn from_env() -> Result<Self, JobQueueError> {
+        Self::new(RedisStreamJobQueueConfig::from_env()).await
+    }
+
+    /// Run every job through `middleware`, inside the middleware added before it.
+    pub fn with_middleware(mut self, middleware: impl JobMiddleware + 'static) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+}
+
+fn stream_key(queue_name: &str) -> String {
+    format!("{}:stream", queue_name)
+}
+
+/// Entries not yet delivered to the group, oldest first, at most `limit`
+/// whose job satisfies `keep`. Entries delivered but not acknowledged are
+/// processing, not pending.
+async fn undelivered(
+    conn: &mut ConnectionManager,
+    stream: &str,
+    group: &str,
+    limit: usize,
+    keep: impl Fn(&Job) -> bool,
+) -> Result<Vec<(String, Job)>, JobQueueError> {
+    let groups: StreamInfoGroupsReply = conn
+        .xinfo_groups(stream)
+        .await
+        .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+    let last_delivered = groups
+        .groups
+        .into_iter()
+        .find(|g| g.name == group)
+        .map(|g| g.last_delivered_id)
+        .unwrap_or_else(|| "0-0".to_string());
+
+    let mut found = Vec::new();
+    let mut after = last_delivered;
+    while found.len() < limit {
+        let reply: StreamRangeReply = conn
+            .xrange_count(stream, format!("({}", after), "+", SCAN_CHUNK)
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        let exhausted = reply.ids.len() < SCAN_CHUNK;
+        if let Some(last) = reply.ids.last() {
+            after = last.id.clone();
+        }
+
+        found.extend(reply.ids.into_iter().filter_map(|entry| {
+            let job = parse_entry(&entry)?;
+            keep(&job).then_some((entry.id, job))
+        }));
+        if exhausted {
+            break;
+        }
+    }
+    found.truncate(limit);
+    Ok(found)
+}
+
+/// Entries delivered to the group's consumers but not yet acknowledged,
+/// oldest first, at most `limit` whose job satisfies `keep`.
+async fn unacknowledged(
+    conn: &mut ConnectionManager,
+    stream: &str,
+    group: &str,
+    limit: usize,
+    keep: impl Fn(&Job) -> bool,
+) -> Result<Vec<Job>, JobQueueError> {
+    let mut found = Vec::new();
+    let mut start = "-".to_string();
+    while found.len() < limit {
+        let reply: StreamPendingCountReply = conn
+            .xpending_count(stream, group, &start, "+", SCAN_CHUNK)
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        let exhausted = reply.ids.len() < SCAN_CHUNK;
+        if let Some(last) = reply.ids.last() {
+            start = format!("({}", last.id);
+        }
+
+        for pending in reply.ids {
+            let entries: StreamRangeReply = conn
+                .xrange(stream, &pending.id, &pending.id)
+                .await
+                .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+            found.extend(
+                entries
+                    .ids
+                    .iter()
+                    .filter_map(parse_entry)
+                    .filter(|job| keep(job)),
+            );
+        }
+        if exhausted {
+            break;
+        }
+    }
+    found.truncate(limit);
+    Ok(found)
+}
+
+fn parse_entry(entry: &StreamId) -> Option<Job> {
+    let json = entry.get::<String>(JOB_FIELD)?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Next entry for a consumer: an abandoned entry idle for at least
+/// `claim_idle_ms` if there is one, otherwise a new entry.
+async fn next_entry(
+    conn: &mut ConnectionManager,
+    stream: &str,
+    group: &str,
+    consumer: &str,
+    claim_cursor: &mut String,
+    claim_idle_ms: u64,
+    block_ms: usize,
+) -> redis::RedisResult<Option<StreamId>> {
+    let claimed: StreamAutoClaimReply = conn
+        .xautoclaim_options(
+            stream,
+            group,
+            consumer,
+            claim_idle_ms,
+            claim_cursor.as_str(),
+            StreamAutoClaimOptions::default().count(1),
+        )
+        .await?;
+    *claim_cursor = claimed.next_stream_id;
+    if let Some(entry) = claimed.claimed.into_iter().next() {
+        tracing::warn!(entry_id = %entry.id, consumer = %consumer, "Claimed abandoned job");
+        return Ok(Some(entry));
+    }
+
+    let options = StreamReadOptions::default()
+        .group(group, consumer)
+        .count(1)
+        .block(block_ms);
+    let reply: Option<StreamReadReply> = conn.xread_options(&[stream], &[">"], &options).await?;
+    Ok(reply
+        .and_then(|reply| reply.keys.into_iter().next())
+        .and_then(|key| key.ids.into_iter().next()))
+}
+
+/// Acknowledge a handled entry and drop it from the stream.
+async fn ack(conn: &mut ConnectionManager, stream: &str, group: &str, entry_id: &str) {
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .xack(stream, group, &[entry_id])
+        .ignore()
+        .xdel(stream, &[entry_id])
+        .ignore();
+    if let Err(e) = pipe.query_async::<()>(conn).await {
+        tracing::warn!(error = %e, entry_id = %entry_id, "Failed to acknowledge job");
+    }
+}
+
+/// Atomically replace an entry with a new one carrying `payload`, at the end
+/// of the stream.
+async fn requeue(
+    conn: &mut ConnectionManager,
+    stream: &str,
+    group: &str,
+    entry_id: &str,
+    payload: &str,
+) -> redis::RedisResult<()> {
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .xadd(stream, "*", &[(JOB_FIELD, payload)])
+        .ignore()
+        .xack(stream, group, &[entry_id])
+        .ignore()
+        .xdel(stream, &[entry_id])
+        .ignore();
+    pipe.query_async(conn).await
+}
+
+#[async_trait]
+impl JobQueue for RedisStreamJobQueue {
+    async fn enqueue(&self, mut job: Job) -> Result<(), JobQueueError> {
+        if self.workers.is_stopping() {
+            return Err(JobQueueError::ShuttingDown);
+        }
+        crate::context::attach(&mut job);
+        self.middleware.enqueue(&mut job).await?;
+
+        let mut conn = self.conn.clone();
+        let job_json =
+            serde_json::to_string(&job).map_err(|e| JobQueueError::EnqueueError(e.to_string()))?;
+
+        if !claim_unique_key(
+            &mut conn,
+            &self.config.queue_name,
+            &job,
+            self.config.unique_ttl,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        set_status(
+            &mut conn,
+            &self.config.queue_name,
+            &job.id,
+            &JobStatus::Pending,
+            self.config.result_ttl,
+        )
+        .await;
+        if let Err(e) = conn
+            .xadd::<_, _, _, _, ()>(
+                stream_key(&self.config.queue_name),
+                "*",
+                &[(JOB_FIELD, &job_json)],
+            )
+            .await
+        {
+            // Or retries would be skipped as duplicates until the key expires
+            release_unique_key(&mut conn, &self.config.queue_name, &job).await;
+            return Err(JobQueueError::Backend(e.to_string()));
+        }
+
+        self.stats.pending.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(job_id = %job.id, job_type = %job.job_type, "Job enqueued");
+
+        Ok(())
+    }
+
+    async fn enqueue_recurring(
+        self: Arc<Self>,
+        job: Job,
+        schedule: &str,
+    ) -> Result<(), JobQueueError> {
+        recurring::register(&self, &self.workers, job, schedule)
+    }
+
+    async fn start_worker<F>(&self, handler: F) -> Result<(), JobQueueError>
+    where
+        F: Fn(Job) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync + 'static,
+    {
+        let handler = self.middleware.wrap(handler);
+
+        for worker_id in 0..self.config.workers {
+            let mut conn = self.conn.clone();
+            let stream = stream_key(&self.config.queue_name);
+            let group = self.config.group.clone();
+            let consumer = format!("{}-{}", self.config.consumer, worker_id);
+            let stats = self.stats.clone();
+            let stop = self.workers.stop_signal();
+            let in_flight = self.workers.in_flight();
+            let handler = handler.clone();
+            let queue_name = self.config.queue_name.clone();
+            let type_limits = self.config.type_limits.clone();
+            let result_ttl = self.config.result_ttl;
+            let dead_letter_limit = self.config.dead_letter_limit;
+            let claim_idle_ms = self.config.claim_idle * 1000;
+            let block_ms = (self.config.block_timeout * 1000) as usize;
+
+            self.workers.spawn(async move {
+                tracing::info!(
+                    worker_id = worker_id,
+                    queue = %queue_name,
+                    consumer = %consumer,
+                    "Stream job worker started"
+                );
+
+                let mut claim_cursor = "0-0".to_string();
+
+                loop {
+                    if *stop.borrow() {
+                        tracing::info!(worker_id = worker_id, "Worker stopping");
+                        break;
+                    }
+
+                    let entry = match next_entry(
+                        &mut conn,
+                        &stream,
+                        &group,
+                        &consumer,
+                        &mut claim_cursor,
+                        claim_idle_ms,
+                        block_ms,
+                    )
+                    .await
+                    {
+                        Ok(Some(entry)) => entry,
+                        Ok(None) => continue, // Timeout, loop again
+                        Err(e) => {
+                            tracing::error!(error = %e, "Redis stream read error");
+                            tokio::time::sleep(Duration::from_secs(1)).await;
+                            continue;
+                        }
+                    };
+
+                    let Some(job_json) = entry.get::<String>(JOB_FIELD) else {
+                        tracing::error!(entry_id = %entry.id, "Stream entry has no job");
+                        ack(&mut conn, &stream, &group, &entry.id).await;
+                        continue;
+                    };
+
+                    if *stop.borrow() {
+                        // Shutdown began while we were waiting; hand the job back
+                        if let Err(e) =
+                            requeue(&mut conn, &stream, &group, &entry.id, &job_json).await
+                        {
+                            tracing::error!(error = %e, "Failed to return job to queue on shutdown");
+                        }
+                        continue;
+                    }
+
+                    let mut job: Job = match serde_json::from_str(&job_json) {
+                        Ok(j) => j,
+                        Err(e) => {
+                            tracing::error!(error = %e, "Failed to deserialize job");
+                            ack(&mut conn, &stream, &group, &entry.id).await;
+                            stats.failed.fetch_add(1, Ordering::Relaxed);
+                            continue;
+                        }
+                    };
+
+                    // Defer jobs whose type is at its concurrency limit
+                    let slot_key = match type_limits.get(&job.job_type) {
+                        Some(&limit) => {
+                            let key = running_key(&queue_name, &job.job_type);
+                            let acquired = acquire_type_slot(&mut conn, &key, limit)
+                                .await
+                                .unwrap_or_else(|e| {
+                                    tracing::error!(error = %e, "Failed to claim job type slot");
+                                    false
+                                });
+                            if !acquired {
+                                if let Err(e) =
+                                    requeue(&mut conn, &stream, &group, &entry.id, &job_json)
+                                        .await
+                                {
+                                    tracing::error!(error = %e, job_id = %job.id, "Failed to re-enqueue deferred job");
+                                }
+                                tokio::time::sleep(DEFERRAL_DELAY).await;
+                                continue;
+                            }
+                            Some(key)
+                        }
+                        None => None,
+                    };
+
+                    stats.pending.fetch_sub(1, Ordering::Relaxed);
+                    stats.processing.fetch_add(1, Ordering::Relaxed);
+
+                    in_flight.start(&job);
+                    job.attempts += 1;
+                    let job_id = job.id.clone();
+                    set_status(
+                        &mut conn,
+                        &queue_name,
+                        &job_id,
+                        &JobStatus::Processing,
+                        result_ttl,
+                    )
+                    .await;
+
+                    tracing::debug!(
+                        worker_id = worker_id,
+                        job_id = %job_id,
+                        job_type = %job.job_type,
+                        attempt = job.attempts,
+                        "Processing job"
+                    );
+
+                    let result = handler(job.clone()).await;
+                    in_flight.finish(&job_id);
+
+                    let status = status_for(&result, &job);
+                    set_status(&mut conn, &queue_name, &job_id, &status, result_ttl).await;
+                    stats.processing.fetch_sub(1, Ordering::Relaxed);
+
+                    match result {
+                        JobResult::Retry(reason) if job.attempts < job.max_attempts => {
+                            let retry_json = serde_json::to_string(&job).unwrap();
+                            if let Err(e) =
+                                requeue(&mut conn, &stream, &group, &entry.id, &retry_json).await
+                            {
+                                // Left unacknowledged, so it is claimed again later
+                                tracing::error!(error = %e, "Failed to re-enqueue job for retry");
+                            } else {
+                                stats.pending.fetch_add(1, Ordering::Relaxed);
+                                tracing::warn!(
+                                    job_id = %job_id,
+                                    attempt = job.attempts,
+                                    reason = %reason,
+                                    "Job queued for retry"
+                                );
+                            }
+                        }
+                        JobResult::Success | JobResult::SuccessWith(_) => {
+                            ack(&mut conn, &stream, &group, &entry.id).await;
+                            release_unique_key(&mut conn, &queue_name, &job).await;
+                            stats.completed.fetch_add(1, Ordering::Relaxed);
+                            tracing::debug!(job_id = %job_id, "Job completed successfully");
+                        }
+                        JobResult::Retry(reason) | JobResult::Failed(reason) => {
+                            ack(&mut conn, &stream, &group, &entry.id).await;
+                            release_unique_key(&mut conn, &queue_name, &job).await;
+                            stats.failed.fetch_add(1, Ordering::Relaxed);
+                            tracing::error!(job_id = %job_id, reason = %reason, "Job failed");
+                            bury(&mut conn, &queue_name, job, &reason, dead_letter_limit).await;
+                        }
+                    }
+
+                    if let Some(key) = slot_key {
+                        release_type_slot(&mut conn, &key).await;
+                    }
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn stats(&self) -> Result<QueueStats, JobQueueError> {
+        let dead = dead_count(&mut self.conn.clone(), &self.config.queue_name).await?;
+        Ok(QueueStats {
+            dead,
+            ..self.stats.snapshot()
+        })
+    }
+
+    async fn get_status(&self, job_id: &str) -> Result<Option<JobStatus>, JobQueueError> {
+        get_status(&mut self.conn.clone(), &self.config.queue_name, job_id).await
+    }
+
+    async fn list_pending(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError> {
+        let stream = stream_key(&self.config.queue_name);
+        let found = undelivered(
+            &mut self.conn.clone(),
+            &stream,
+            &self.config.group,
+            limit,
+            |job| matches_type(job, job_type),
+        )
+        .await?;
+        Ok(found.into_iter().map(|(_, job)| job).collect())
+    }
+
+    async fn list_processing(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Job>, JobQueueError> {
+        let stream = stream_key(&self.config.queue_name);
+        unacknowledged(
+            &mut self.conn.clone(),
+            &stream,
+            &self.config.group,
+            limit,
+            |job| matches_type(job, job_type),
+        )
+        .await
+    }
+
+    async fn list_dead(
+        &self,
+        job_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DeadJob>, JobQueueError> {
+        list_dead(
+            &mut self.conn.clone(),
+            &self.config.queue_name,
+            job_type,
+            limit,
+        )
+        .await
+    }
+
+    async fn retry_dead(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        let mut conn = self.conn.clone();
+        let Some(dead) = take_dead(&mut conn, &self.config.queue_name, job_id).await? else {
+            return Ok(false);
+        };
+
+        let mut job = dead.job.clone();
+        job.attempts = 0;
+        if let Err(e) = self.enqueue(job).await {
+            let limit = self.config.dead_letter_limit;
+            bury(
+                &mut conn,
+                &self.config.queue_name,
+                dead.job,
+                &dead.error,
+                limit,
+            )
+            .await;
+            return Err(e);
+        }
+        tracing::info!(job_id = %job_id, "Dead job re-enqueued");
+        Ok(true)
+    }
+
+    async fn delete_pending(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        let mut conn = self.conn.clone();
+        let stream = stream_key(&self.config.queue_name);
+        let Some((entry_id, job)) = undelivered(&mut conn, &stream, &self.config.group, 1, |job| {
+            job.id == job_id
+        })
+        .await?
+        .pop() else {
+            return Ok(false);
+        };
+
+        let deleted: usize = conn
+            .xdel(&stream, &[&entry_id])
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+        if deleted == 0 {
+            return Ok(false);
+        }
+        forget_jobs(&mut conn, &self.config.queue_name, &[job]).await?;
+
+        let _ = self
+            .stats
+            .pending
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        tracing::info!(job_id = %job_id, "Pending job deleted");
+        Ok(true)
+    }
+
+    async fn delete_dead(&self, job_id: &str) -> Result<bool, JobQueueError> {
+        let taken = take_dead(&mut self.conn.clone(), &self.config.queue_name, job_id).await?;
+        Ok(taken.is_some())
+    }
+
+    async fn purge(&self) -> Result<usize, JobQueueError> {
+        let mut conn = self.conn.clone();
+        let stream = stream_key(&self.config.queue_name);
+        let entries =
+            undelivered(&mut conn, &stream, &self.config.group, usize::MAX, |_| true).await?;
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        // An entry read by a worker after the range was taken is still
+        // processed; XDEL only drops it from the stream
+        let ids: Vec<&str> = entries.iter().map(|(id, _)| id.as_str()).collect();
+        let deleted: usize = conn
+            .xdel(&stream, &ids)
+            .await
+            .map_err(|e| JobQueueError::Backend(e.to_string()))?;
+
+        let purged: Vec<Job> = entries.into_iter().map(|(_, job)| job).collect();
+        forget_jobs(&mut conn, &self.config.queue_name, &purged).await?;
+
+        // The local count only covers jobs this instance enqueued, all of
+        // which are gone now
+        self.stats.pending.store(0, Ordering::Relaxed);
+        tracing::info!(queue = %self.config.queue_name, purged = deleted, "Job queue purged");
+        Ok(deleted)
+    }
+
+    async fn shutdown(&self, timeout: Duration) -> Result<(), JobQueueError> {
+        let interrupted = self.workers.shutdown(timeout).await;
+        if interrupted.is_empty() {
+            tracing::info!(queue = %self.config.queue_name, "Job queue stopped");
+            return Ok(());
+        }
+
+        // Interrupted entries stay unacknowledged in the group, so another
+        // consumer claims them once they have been idle for `claim_idle`
+        let mut conn = self.conn.clone();
+        for job in &interrupted {
+            set_status(
+                &mut conn,
+                &self.config.queue_name,
+                &job.id,
+                &JobStatus::Pending,
+                self.config.result_ttl,
+            )
+            .await;
+            if self.config.type_limits.contains_key(&job.job_type) {
+                let key = running_key(&self.config.queue_name, &job.job_type);
+                release_type_slot(&mut conn, &key).await;
+            }
+        }
+
+        self.stats
+            .processing
+            .fetch_sub(interrupted.len(), Ordering::Relaxed);
+        self.stats
+            .pending
+            .fetch_add(interrupted.len(), Ordering::Relaxed);
+        tracing::warn!(
+            queue = %self.config.queue_name,
+            count = interrupted.len(),
+            "Jobs interrupted by shutdown left for other consumers to claim"
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    async fn get_test_job_queue(consumer: &str) -> Option<RedisStreamJobQueue> {
+        let config = RedisStreamJobQueueConfig {
+            redis: RedisConfig {
+                url: env::REDIS_URL
+                    .raw()
+                    .unwrap_or_else(|| "redis://localhost:6389".to_string()),
+                connect_timeout: Duration::from_secs(1),
+                fallback_to_memory: false,
+            },
+            queue_name: "test_stream_jobs".to_string(),
+            group: "test_workers".to_string(),
+            consumer: consumer.to_string(),
+            workers: 1,
+            block_timeout: 1,
+            claim_idle: 1,
+            unique_ttl: 60,
+            result_ttl: 60,
+            type_limits: HashMap::new(),
+            dead_letter_limit: 10,
+        };
+
+        RedisStreamJobQueue::new(config).await.ok()
+    }
+
+    #[tokio::test]
+    async fn test_redis_stream_job_queue() {
+        let queue = match get_test_job_queue("test-consumer").await {
+            Some(q) => q,
+            None => return,
+        };
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let payload = serde_json::json!({"foo": "bar"});
+        let job = Job::new("test_job", payload.clone());
+        let job_id = job.id.clone();
+
+        queue
+            .start_worker(move |job| {
+                let tx = tx.clone();
+                Box::pin(async move {
+                    tx.send(job.payload).await.unwrap();
+                    JobResult::Success
+                })
+            })
+            .await
+            .unwrap();
+
+        queue.enqueue(job).await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(received.unwrap(), payload);
+
+        queue.shutdown(Duration::from_secs(5)).await.unwrap();
+        assert!(matches!(
+            queue.get_status(&job_id).await.unwrap(),
+            Some(JobStatus::Completed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_entries_are_claimed() {
+        let queue = match get_test_job_queue("test-claimer").await {
+            Some(q) => q,
+            None => return,
+        };
+        let mut conn = queue.conn.clone();
+        let stream = stream_key(&queue.config.queue_name);
+
+        // A consumer read the job and died without acknowledging it
+        let job = Job::new("test_job", serde_json::json!({}));
+        let json = serde_json::to_string(&job).unwrap();
+        conn.xadd::<_, _, _, _, ()>(&stream, "*", &[(JOB_FIELD, &json)])
+            .await
+            .unwrap();
+        let mut cursor = "0-0".to_string();
+        let taken = next_entry(
+            &mut conn,
+            &stream,
+            "test_workers",
+            "dead",
+            &mut cursor,
+            1000,
+            100,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let mut cursor = "0-0".to_string();
+        let claimed = next_entry(
+            &mut conn,
+            &stream,
+            "test_workers",
+            "alive",
+            &mut cursor,
+            1000,
+            100,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(claimed.id, taken.id);
+        ack(&mut conn, &stream, "test_workers", &claimed.id).await;
+    }
+}
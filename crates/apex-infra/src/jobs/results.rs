@@ -0,0 +1,93 @@
+//! In-process job status storage with expiry for finished jobs.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use apex_core::ports::JobStatus;
+
+/// Job statuses keyed by job id.
+///
+/// Pending and processing jobs are kept until they finish; final statuses
+/// expire after the TTL.
+pub(crate) struct JobStatuses {
+    ttl: Duration,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    statuses: HashMap<String, (JobStatus, Option<Instant>)>,
+    /// Expiry times in insertion order; the TTL is fixed, so this is sorted.
+    expiries: VecDeque<(Instant, String)>,
+}
+
+impl JobStatuses {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub(crate) fn set(&self, job_id: &str, status: JobStatus) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.purge_expired(now);
+
+        let expires_at = status.is_terminal().then(|| now + self.ttl);
+        if let Some(at) = expires_at {
+            inner.expiries.push_back((at, job_id.to_string()));
+        }
+        inner
+            .statuses
+            .insert(job_id.to_string(), (status, expires_at));
+    }
+
+    pub(crate) fn remove(&self, job_id: &str) {
+        self.inner.lock().unwrap().statuses.remove(job_id);
+    }
+
+    pub(crate) fn get(&self, job_id: &str) -> Option<JobStatus> {
+        let inner = self.inner.lock().unwrap();
+        match inner.statuses.get(job_id) {
+            Some((_, Some(expires_at))) if *expires_at <= Instant::now() => None,
+            Some((status, _)) => Some(status.clone()),
+            None => None,
+        }
+    }
+}
+
+impl Inner {
+    fn purge_expired(&mut self, now: Instant) {
+        while let Some((at, _)) = self.expiries.front()
+            && *at <= now
+        {
+            let (at, job_id) = self.expiries.pop_front().expect("front exists");
+            // Only drop the entry if it still carries this expiry
+            if matches!(self.statuses.get(&job_id), Some((_, Some(expires_at))) if *expires_at == at)
+            {
+                self.statuses.remove(&job_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_final_statuses_expire() {
+        let statuses = JobStatuses::new(Duration::from_millis(10));
+        statuses.set("running", JobStatus::Processing);
+        statuses.set("done", JobStatus::Completed { result: None });
+
+        std::thread::sleep(Duration::from_millis(20));
+        statuses.set("other", JobStatus::Pending);
+
+        assert_eq!(statuses.get("done"), None);
+        assert_eq!(statuses.get("running"), Some(JobStatus::Processing));
+        assert!(statuses.inner.lock().unwrap().expiries.is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! Per-job-type rate limits.
+//!
+//! Where concurrency limits cap how many jobs of a type run at once, these
+//! cap how often they start, so bursts of e.g. emails don't exceed an
+//! outbound provider's API limits.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use apex_core::ports::{Job, JobResult, RateLimiter};
+
+use super::middleware::{JobMiddleware, Next};
+#[cfg(feature = "rate-limit")]
+use crate::env;
+
+/// Shortest wait between two checks of a throttled type's limiter.
+const MIN_WAIT: Duration = Duration::from_millis(10);
+
+/// Parse rates of the form `email=10/s,sms=100/m,report=5/30s`: a number of
+/// jobs per `s`, `m`, `h` or a count of those.
+///
+/// Malformed entries are skipped with a warning.
+pub fn parse_type_rates(spec: &str) -> HashMap<String, (u32, Duration)> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.split_once('=').and_then(|(job_type, rate)| {
+                let (count, window) = rate.trim().split_once('/')?;
+                let count: u32 = count.trim().parse().ok()?;
+                let window = parse_window(window.trim())?;
+                let job_type = job_type.trim();
+                (!job_type.is_empty() && count > 0).then(|| (job_type.to_string(), (count, window)))
+            });
+            if parsed.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed job type rate limit");
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Parse a window such as `s`, `m`, `h` or `30s`.
+pub(crate) fn parse_window(window: &str) -> Option<Duration> {
+    let unit = window.chars().last()?;
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        _ => return None,
+    };
+    let count = match &window[..window.len() - 1] {
+        "" => 1,
+        count => count.parse().ok().filter(|count| *count > 0)?,
+    };
+    Some(Duration::from_secs(count * seconds))
+}
+
+/// Holds jobs of rate-limited types until their limiter lets them start.
+///
+/// Each type gets its own [`RateLimiter`], checked with the key
+/// `job:<type>` so a keyed (e.g. Redis) limiter shared by several instances
+/// enforces the rate across all of them. Waiting occupies the worker, so
+/// pair a tight rate with a concurrency limit for the type
+/// (`JOB_TYPE_CONCURRENCY`) to keep throttled jobs from tying up every
+/// worker. Limiter errors let the job through.
+#[derive(Default)]
+pub struct ThrottleJobMiddleware {
+    limiters: HashMap<String, Arc<dyn RateLimiter>>,
+}
+
+impl ThrottleJobMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit how often jobs of `job_type` start.
+    pub fn limit(mut self, job_type: impl Into<String>, limiter: Arc<dyn RateLimiter>) -> Self {
+        self.limiters.insert(job_type.into(), limiter);
+        self
+    }
+
+    /// Whether any job type is throttled.
+    pub fn is_empty(&self) -> bool {
+        self.limiters.is_empty()
+    }
+
+    /// In-process limiters for the rates in `JOB_TYPE_RATE_LIMITS`.
+    #[cfg(feature = "rate-limit")]
+    pub fn from_env() -> Self {
+        use crate::rate_limit::{InMemoryRateLimiter, RateLimitConfig};
+
+        parse_type_rates(&env::JOB_TYPE_RATE_LIMITS.string())
+            .into_iter()
+            .fold(
+                Self::new(),
+                |throttle, (job_type, (max_requests, window))| {
+                    throttle.limit(
+                        job_type,
+                        Arc::new(InMemoryRateLimiter::new(RateLimitConfig {
+                            max_requests,
+                            window,
+                            ..Default::default()
+                        })),
+                    )
+                },
+            )
+    }
+}
+
+#[async_trait]
+impl JobMiddleware for ThrottleJobMiddleware {
+    async fn around(&self, job: Job, next: Next) -> JobResult {
+        if let Some(limiter) = self.limiters.get(&job.job_type) {
+            let key = format!("job:{}", job.job_type);
+            loop {
+                match limiter.check(&key).await {
+                    Ok(result) if result.allowed => break,
+                    Ok(result) => {
+                        tracing::trace!(job_id = %job.id, job_type = %job.job_type, "Job throttled");
+                        tokio::time::sleep(result.reset_after.max(MIN_WAIT)).await;
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, job_type = %job.job_type, "Job rate limiter error, running job");
+                        break;
+                    }
+                }
+            }
+        }
+        next.run(job).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::JobMiddlewareStack;
+    use apex_core::ports::{RateLimitError, RateLimitResult};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Denies the first `denials` checks.
+    struct DenyFirst {
+        denials: u32,
+        checks: AtomicU32,
+    }
+
+    #[async_trait]
+    impl RateLimiter for DenyFirst {
+        async fn check(&self, key: &str) -> Result<RateLimitResult, RateLimitError> {
+            assert_eq!(key, "job:email");
+            let check = self.checks.fetch_add(1, Ordering::SeqCst);
+            Ok(RateLimitResult {
+                allowed: check >= self.denials,
+                limit: 1,
+                remaining: 0,
+                reset_after: Duration::from_millis(1),
+            })
+        }
+    }
+
+    #[test]
+    fn test_parse_type_rates() {
+        let rates = parse_type_rates("email=10/s, sms = 100/m,report=5/30s,bogus,zero=0/s,x=1/d");
+        assert_eq!(rates.len(), 3);
+        assert_eq!(rates["email"], (10, Duration::from_secs(1)));
+        assert_eq!(rates["sms"], (100, Duration::from_secs(60)));
+        assert_eq!(rates["report"], (5, Duration::from_secs(30)));
+    }
+
+    #[tokio::test]
+    async fn test_throttled_job_waits_for_its_limiter() {
+        let limiter = Arc::new(DenyFirst {
+            denials: 3,
+            checks: AtomicU32::new(0),
+        });
+        let mut stack = JobMiddlewareStack::default();
+        stack.push(ThrottleJobMiddleware::new().limit("email", limiter.clone()));
+        let handler = stack.wrap(|_job: Job| Box::pin(async { JobResult::Success }));
+
+        let result = handler(Job::new("email", serde_json::json!({}))).await;
+        assert!(matches!(result, JobResult::Success));
+        assert_eq!(limiter.checks.load(Ordering::SeqCst), 4);
+
+        // Other types are not checked at all
+        handler(Job::new("cleanup", serde_json::json!({}))).await;
+        assert_eq!(limiter.checks.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,114 @@
+//! Worker task bookkeeping shared by the job queue backends.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use apex_core::ports::Job;
+
+/// Worker tasks of a queue, with the stop signal and the jobs they are running.
+pub(crate) struct Workers {
+    stop: watch::Sender<bool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    in_flight: InFlight,
+}
+
+/// Jobs currently being processed, keyed by job id.
+#[derive(Clone, Default)]
+pub(crate) struct InFlight(Arc<Mutex<HashMap<String, Job>>>);
+
+impl InFlight {
+    /// Record a job as started. The job is stored as it should be re-enqueued
+    /// if it gets interrupted.
+    pub(crate) fn start(&self, job: &Job) {
+        self.0.lock().unwrap().insert(job.id.clone(), job.clone());
+    }
+
+    pub(crate) fn finish(&self, job_id: &str) {
+        self.0.lock().unwrap().remove(job_id);
+    }
+
+    /// Running jobs, longest running first.
+    pub(crate) fn list(&self, job_type: Option<&str>, limit: usize) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .0
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|job| super::matches_type(job, job_type))
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        jobs.truncate(limit);
+        jobs
+    }
+}
+
+impl Workers {
+    pub(crate) fn new() -> Self {
+        Self {
+            stop: watch::channel(false).0,
+            tasks: Mutex::new(Vec::new()),
+            in_flight: InFlight::default(),
+        }
+    }
+
+    /// Whether shutdown has begun. Queues reject new jobs once it has.
+    pub(crate) fn is_stopping(&self) -> bool {
+        *self.stop.borrow()
+    }
+
+    /// Receiver that flips to `true` when shutdown begins.
+    pub(crate) fn stop_signal(&self) -> watch::Receiver<bool> {
+        self.stop.subscribe()
+    }
+
+    pub(crate) fn in_flight(&self) -> InFlight {
+        self.in_flight.clone()
+    }
+
+    pub(crate) fn spawn<F>(&self, worker: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.lock().unwrap().push(tokio::spawn(worker));
+    }
+
+    /// Signal workers to stop and wait up to `timeout` for them to finish
+    /// their current jobs.
+    ///
+    /// Workers still running when the timeout elapses are aborted; the jobs
+    /// they were processing are returned so the caller can re-enqueue them.
+    pub(crate) async fn shutdown(&self, timeout: Duration) -> Vec<Job> {
+        self.stop.send_replace(true);
+
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        let drained = tokio::time::timeout(timeout, futures::future::join_all(tasks.iter_mut()))
+            .await
+            .is_ok();
+
+        if !drained {
+            tasks.retain(|task| !task.is_finished());
+            tracing::warn!(
+                workers = tasks.len(),
+                "Job workers did not finish in time, aborting"
+            );
+            for task in &tasks {
+                task.abort();
+            }
+            futures::future::join_all(tasks).await;
+        }
+
+        self.in_flight
+            .0
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, job)| job)
+            .collect()
+    }
+}
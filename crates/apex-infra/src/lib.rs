@@ -7,40 +7,131 @@
 //!
 //! - `full` (default) - All features enabled
 //! - `minimal` - No external dependencies, in-memory only
-//! - `postgres` - PostgreSQL database support via SeaORM
+//! - `postgres` - PostgreSQL database support via SeaORM, sensitive columns
+//!   encrypted at rest
 //! - `auth` - JWT + Argon2 authentication
 //! - `rate-limit` - Rate limiting via governor
 //! - `redis` - Redis support for cache, pubsub, rate limiting, and job queue
+//! - `kafka` - Kafka pubsub via rdkafka (not in `full`; builds librdkafka)
+//! - `mongo` - MongoDB user and post repositories (not in `full`)
+//! - `webhooks` - HTTP webhook delivery via reqwest
+//! - `billing` - Stripe webhook verification
+//! - `storage` - Local file storage with signed download links
+//! - `encryption` - AES-GCM encryption of job payloads at rest
 
+pub mod announcements;
+pub mod api_quota;
+pub mod approvals;
+pub mod billing;
 pub mod cache;
+pub mod consent;
+pub mod context;
 pub mod database;
+pub mod domains;
+pub mod entitlements;
+pub mod env;
+pub mod events;
+pub mod feeds;
+pub mod health;
 pub mod jobs;
+pub mod metering;
+pub mod notifications;
 pub mod pubsub;
+pub mod secrets;
+pub mod seed;
+pub mod settings;
+pub mod shadow;
+pub mod storage_quota;
+pub mod swap;
+pub mod tenancy;
+pub mod user_events;
+pub mod views;
+pub mod webhook;
+pub mod well_known;
 
 #[cfg(feature = "auth")]
 pub mod auth;
 
+#[cfg(feature = "mongo")]
+pub mod mongo;
+
 #[cfg(feature = "rate-limit")]
 pub mod rate_limit;
 
+#[cfg(all(feature = "postgres", feature = "storage"))]
+pub mod reports;
+
+#[cfg(feature = "storage")]
+pub mod storage;
+
 // Re-exports - In-Memory
+pub use announcements::AnnouncementBoard;
+pub use api_quota::ApiQuotas;
+pub use approvals::AdminApprovals;
+pub use billing::SubscriptionService;
 pub use cache::InMemoryCache;
-pub use database::DatabaseConnections;
+pub use consent::ConsentService;
+#[cfg(any(feature = "postgres", feature = "mongo"))]
+pub use database::{ColumnCipher, ColumnEncryptionError, EncryptedString};
+pub use database::{
+    DatabaseConnections, InMemoryAttachmentRepository, InMemoryCommentRepository,
+    InMemoryPasswordHistoryRepository, InMemoryPostRepository, InMemoryTagRepository,
+    InMemoryUserRepository,
+};
+pub use domains::TenantDomains;
+pub use entitlements::EntitlementResolver;
+pub use events::EventFanOut;
+pub use feeds::PublicFeeds;
+pub use health::{DependencyWatchdog, HealthCheckRegistry, WatchdogConfig};
 pub use jobs::InMemoryJobQueue;
-pub use pubsub::InMemoryPubSub;
+pub use metering::UsageMeter;
+pub use notifications::NotificationLog;
+pub use pubsub::{InMemoryPubSub, InMemoryPubSubConfig, OverflowPolicy, TypedPubSub};
+pub use secrets::EnvSecrets;
+pub use seed::{SeedContext, SeedEnvironment, SeedError, SeedRunner, Seeder};
+pub use settings::SettingsStore;
+pub use storage_quota::StorageQuotas;
+pub use swap::HotSwap;
+pub use tenancy::TenantScopedPostRepository;
+pub use user_events::UserEventHub;
+pub use views::PostViews;
+pub use webhook::{AuditedWebhookSender, RecordingWebhookSender};
+pub use well_known::WellKnown;
 
 #[cfg(feature = "auth")]
-pub use auth::{Argon2PasswordService, JwtTokenService};
+pub use auth::{Argon2PasswordService, JwtTokenService, password_policy_from_env};
 
 #[cfg(feature = "rate-limit")]
-pub use rate_limit::{InMemoryRateLimiter, RateLimitConfig};
+pub use rate_limit::{InMemoryRateLimiter, RateLimitAlgorithm, RateLimitConfig, RateLimitPolicy};
+
+#[cfg(all(feature = "postgres", feature = "storage"))]
+pub use reports::ReportGenerator;
+
+#[cfg(feature = "storage")]
+pub use storage::LocalStorage;
+
+#[cfg(feature = "webhooks")]
+pub use webhook::HttpWebhookSender;
+
+#[cfg(feature = "billing")]
+pub use billing::stripe::StripeWebhookVerifier;
 
 // Re-exports - Redis
 #[cfg(feature = "redis")]
 pub use cache::{RedisCache, RedisConfig};
 #[cfg(feature = "redis")]
-pub use jobs::{RedisJobQueue, RedisJobQueueConfig};
+pub use jobs::{
+    RedisJobQueue, RedisJobQueueConfig, RedisStreamJobQueue, RedisStreamJobQueueConfig,
+};
 #[cfg(feature = "redis")]
-pub use pubsub::RedisPubSub;
+pub use pubsub::{RedisPubSub, RedisPubSubConfig};
 #[cfg(all(feature = "redis", feature = "rate-limit"))]
 pub use rate_limit::{RedisRateLimitConfig, RedisRateLimiter};
+
+// Re-exports - Kafka
+#[cfg(feature = "kafka")]
+pub use pubsub::{KafkaConfig, KafkaPubSub};
+
+// Re-exports - MongoDB
+#[cfg(feature = "mongo")]
+pub use mongo::{MongoConfig, MongoPostRepository, MongoUserRepository};
@@ -0,0 +1,231 @@
+//! Usage metering - in-process counters periodically rolled up into storage.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use apex_core::domain::{UsageMetric, UsageTotal, billing_period};
+use apex_core::error::RepoError;
+use apex_core::ports::UsageRepository;
+
+type CounterKey = (Uuid, UsageMetric, NaiveDate);
+
+/// Records billable events cheaply and flushes them to monthly rollups.
+///
+/// Counters are keyed by billing period at record time, so events are
+/// attributed to the right month even if the flush happens after rollover.
+pub struct UsageMeter {
+    repo: Arc<dyn UsageRepository>,
+    counters: RwLock<HashMap<CounterKey, AtomicI64>>,
+}
+
+impl UsageMeter {
+    pub fn new(repo: Arc<dyn UsageRepository>) -> Self {
+        Self {
+            repo,
+            counters: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record `quantity` units of a metric for an account.
+    pub fn record(&self, account_id: Uuid, metric: UsageMetric, quantity: i64) {
+        let key = (account_id, metric, billing_period(chrono::Utc::now()));
+        self.add(key, quantity);
+    }
+
+    fn add(&self, key: CounterKey, quantity: i64) {
+        if let Some(counter) = self.counters.read().unwrap().get(&key) {
+            counter.fetch_add(quantity, Ordering::Relaxed);
+            return;
+        }
+        self.counters
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .fetch_add(quantity, Ordering::Relaxed);
+    }
+
+    /// Write pending counts to storage. Returns the number of rollups updated.
+    ///
+    /// On failure the counts are kept for the next flush.
+    pub async fn flush(&self) -> Result<usize, RepoError> {
+        let drained = std::mem::take(&mut *self.counters.write().unwrap());
+        let increments: Vec<UsageTotal> = drained
+            .into_iter()
+            .map(|((account_id, metric, period_start), count)| UsageTotal {
+                account_id,
+                metric,
+                period_start,
+                quantity: count.into_inner(),
+            })
+            .filter(|total| total.quantity != 0)
+            .collect();
+
+        if increments.is_empty() {
+            return Ok(0);
+        }
+
+        let count = increments.len();
+        if let Err(e) = self.repo.increment(increments.clone()).await {
+            for total in increments {
+                self.add(
+                    (total.account_id, total.metric, total.period_start),
+                    total.quantity,
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(count)
+    }
+
+    /// Usage for an account in a billing period, including counts not yet flushed.
+    ///
+    /// Every metric is present, zero if unused.
+    pub async fn totals(
+        &self,
+        account_id: Uuid,
+        period_start: NaiveDate,
+    ) -> Result<Vec<UsageTotal>, RepoError> {
+        let stored = self.repo.totals(account_id, period_start).await?;
+        let counters = self.counters.read().unwrap();
+
+        Ok(UsageMetric::ALL
+            .into_iter()
+            .map(|metric| {
+                let persisted: i64 = stored
+                    .iter()
+                    .filter(|t| t.metric == metric)
+                    .map(|t| t.quantity)
+                    .sum();
+                let pending = counters
+                    .get(&(account_id, metric, period_start))
+                    .map(|c| c.load(Ordering::Relaxed))
+                    .unwrap_or(0);
+                UsageTotal {
+                    account_id,
+                    metric,
+                    period_start,
+                    quantity: persisted + pending,
+                }
+            })
+            .collect())
+    }
+
+    /// Flush on a fixed interval in the background.
+    pub fn start_flusher(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let meter = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match meter.flush().await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::debug!(rollups = count, "Usage counters flushed"),
+                    Err(e) => tracing::warn!(error = %e, "Failed to flush usage counters"),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicBool;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryUsage {
+        totals: Mutex<HashMap<CounterKey, i64>>,
+        fail: AtomicBool,
+    }
+
+    #[async_trait]
+    impl UsageRepository for MemoryUsage {
+        async fn increment(&self, increments: Vec<UsageTotal>) -> Result<(), RepoError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(RepoError::Connection("down".to_string()));
+            }
+            let mut totals = self.totals.lock().await;
+            for t in increments {
+                *totals
+                    .entry((t.account_id, t.metric, t.period_start))
+                    .or_default() += t.quantity;
+            }
+            Ok(())
+        }
+
+        async fn totals(
+            &self,
+            account_id: Uuid,
+            period_start: NaiveDate,
+        ) -> Result<Vec<UsageTotal>, RepoError> {
+            Ok(self
+                .totals
+                .lock()
+                .await
+                .iter()
+                .filter(|((a, _, p), _)| *a == account_id && *p == period_start)
+                .map(|((a, m, p), q)| UsageTotal {
+                    account_id: *a,
+                    metric: *m,
+                    period_start: *p,
+                    quantity: *q,
+                })
+                .collect())
+        }
+    }
+
+    fn period() -> NaiveDate {
+        billing_period(chrono::Utc::now())
+    }
+
+    fn quantity(totals: &[UsageTotal], metric: UsageMetric) -> i64 {
+        totals.iter().find(|t| t.metric == metric).unwrap().quantity
+    }
+
+    #[tokio::test]
+    async fn test_flush_rolls_up_counts() {
+        let repo = Arc::new(MemoryUsage::default());
+        let meter = UsageMeter::new(repo.clone());
+        let account = Uuid::new_v4();
+
+        for _ in 0..3 {
+            meter.record(account, UsageMetric::ApiCalls, 1);
+        }
+        meter.record(account, UsageMetric::StorageBytes, 1024);
+
+        assert_eq!(meter.flush().await.unwrap(), 2);
+        meter.record(account, UsageMetric::ApiCalls, 1);
+
+        let totals = meter.totals(account, period()).await.unwrap();
+        assert_eq!(quantity(&totals, UsageMetric::ApiCalls), 4);
+        assert_eq!(quantity(&totals, UsageMetric::StorageBytes), 1024);
+        assert_eq!(quantity(&totals, UsageMetric::JobsExecuted), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_keeps_counts() {
+        let repo = Arc::new(MemoryUsage::default());
+        let meter = UsageMeter::new(repo.clone());
+        let account = Uuid::new_v4();
+
+        meter.record(account, UsageMetric::JobsExecuted, 2);
+        repo.fail.store(true, Ordering::SeqCst);
+        assert!(meter.flush().await.is_err());
+
+        repo.fail.store(false, Ordering::SeqCst);
+        assert_eq!(meter.flush().await.unwrap(), 1);
+
+        let totals = meter.totals(account, period()).await.unwrap();
+        assert_eq!(quantity(&totals, UsageMetric::JobsExecuted), 2);
+    }
+}
@@ -0,0 +1,88 @@
+//! MongoDB repository implementations, for deployments whose primary store
+//! is document-based.
+//!
+//! Users and posts are stored one document per entity, keyed by the
+//! hyphenated UUID in `_id`. Timestamps are BSON dates, which keep
+//! milliseconds: anything finer is dropped on write.
+
+mod repo;
+
+use std::time::Duration;
+
+use mongodb::bson::doc;
+use mongodb::options::ClientOptions;
+use mongodb::{Client, Database};
+
+use apex_core::error::RepoError;
+
+use crate::env;
+
+pub use repo::{MongoPostRepository, MongoUserRepository};
+
+/// MongoDB connection configuration.
+#[derive(Debug, Clone)]
+pub struct MongoConfig {
+    /// Connection string (e.g., mongodb://localhost:27017)
+    pub uri: String,
+    /// Database holding the `users` and `posts` collections.
+    pub database: String,
+    /// How long to wait for a reachable server.
+    pub connect_timeout: Duration,
+}
+
+impl Default for MongoConfig {
+    fn default() -> Self {
+        Self {
+            uri: "mongodb://localhost:27017".to_string(),
+            database: "apex".to_string(),
+            connect_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl MongoConfig {
+    /// Load configuration from environment variables.
+    pub fn from_env() -> Self {
+        Self {
+            uri: env::MONGODB_URI.string(),
+            database: env::MONGODB_DATABASE.string(),
+            connect_timeout: env::MONGODB_CONNECT_TIMEOUT_SECS.secs(),
+        }
+    }
+
+    /// Connect and check the server answers.
+    pub async fn connect(&self) -> Result<Database, RepoError> {
+        let mut options = ClientOptions::parse(&self.uri)
+            .await
+            .map_err(|e| RepoError::Connection(e.to_string()))?;
+        options.connect_timeout = Some(self.connect_timeout);
+        options.server_selection_timeout = Some(self.connect_timeout);
+
+        let client =
+            Client::with_options(options).map_err(|e| RepoError::Connection(e.to_string()))?;
+        let database = client.database(&self.database);
+        database
+            .run_command(doc! { "ping": 1 })
+            .await
+            .map_err(|e| RepoError::Connection(e.to_string()))?;
+
+        tracing::info!(database = %self.database, "Connected to MongoDB");
+        Ok(database)
+    }
+}
+
+/// Repository error for a failed write, telling duplicate keys (code 11000)
+/// apart.
+pub(crate) fn map_mongo_error(e: mongodb::error::Error) -> RepoError {
+    use mongodb::error::{ErrorKind, WriteFailure};
+
+    let code = match e.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(write)) => Some(write.code),
+        ErrorKind::Command(command) => Some(command.code),
+        _ => None,
+    };
+    match code {
+        Some(11000) => RepoError::Constraint("Entity already exists".to_string()),
+        _ => RepoError::Query(e.to_string()),
+    }
+}
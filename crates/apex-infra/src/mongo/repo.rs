@@ -0,0 +1,784 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::bson::{self, Document, doc};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use apex_core::domain::{
+    CompareOp, Email, Filter, FilterValue, Page, PageCursor, PageRequest, Post, SortDirection,
+    SyncCursor, User,
+};
+use apex_core::error::RepoError;
+use apex_core::pii::Sensitive;
+use apex_core::ports::{BaseRepository, PostRepository, UserRepository};
+
+use super::map_mongo_error;
+use crate::context;
+use crate::database::EncryptedString;
+
+const USER_FIELDS: &[&str] = &["id", "email", "password_hash", "created_at", "updated_at"];
+
+const POST_FIELDS: &[&str] = &[
+    "id",
+    "user_id",
+    "organization_id",
+    "title",
+    "content",
+    "version",
+    "deleted_at",
+    "view_count",
+    "published_at",
+    "created_at",
+    "updated_at",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct UserDocument {
+    #[serde(rename = "_id")]
+    id: String,
+    email: String,
+    password_hash: String,
+    #[serde(default = "User::default_roles")]
+    roles: Vec<String>,
+    #[serde(default)]
+    phone_number: Option<EncryptedString>,
+    #[serde(default)]
+    two_factor_secret: Option<EncryptedString>,
+    created_at: bson::DateTime,
+    updated_at: bson::DateTime,
+    #[serde(default)]
+    created_by: Option<String>,
+    #[serde(default)]
+    updated_by: Option<String>,
+}
+
+impl From<User> for UserDocument {
+    fn from(user: User) -> Self {
+        let (created_by, updated_by) = context::audit_stamp(user.created_by, user.updated_by);
+        Self {
+            id: user.id.to_string(),
+            email: user.email.into(),
+            password_hash: user.password_hash,
+            roles: user.roles,
+            phone_number: user.phone_number.map(EncryptedString::from),
+            two_factor_secret: user.two_factor_secret.map(EncryptedString::from),
+            created_at: to_bson_date(user.created_at),
+            updated_at: to_bson_date(user.updated_at),
+            created_by: created_by.map(|id| id.to_string()),
+            updated_by: updated_by.map(|id| id.to_string()),
+        }
+    }
+}
+
+impl TryFrom<UserDocument> for User {
+    type Error = RepoError;
+
+    fn try_from(document: UserDocument) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: parse_id(&document.id)?,
+            email: Email::from_stored(document.email),
+            password_hash: document.password_hash,
+            roles: document.roles,
+            phone_number: document.phone_number.map(EncryptedString::into_inner),
+            two_factor_secret: document.two_factor_secret.map(EncryptedString::into_inner),
+            created_at: to_chrono(document.created_at),
+            updated_at: to_chrono(document.updated_at),
+            created_by: document.created_by.as_deref().map(parse_id).transpose()?,
+            updated_by: document.updated_by.as_deref().map(parse_id).transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PostDocument {
+    #[serde(rename = "_id")]
+    id: String,
+    user_id: String,
+    organization_id: Option<String>,
+    title: String,
+    content: String,
+    version: i64,
+    deleted_at: Option<bson::DateTime>,
+    #[serde(default)]
+    view_count: i64,
+    published_at: Option<bson::DateTime>,
+    created_at: bson::DateTime,
+    updated_at: bson::DateTime,
+    #[serde(default)]
+    created_by: Option<String>,
+    #[serde(default)]
+    updated_by: Option<String>,
+}
+
+impl PostDocument {
+    /// The fields a save writes: all but the id, the view count, which
+    /// only `add_views` changes, and the creator, written once.
+    fn fields(&self) -> Result<Document, RepoError> {
+        let mut fields = bson::to_document(self).map_err(|e| RepoError::Query(e.to_string()))?;
+        fields.remove("_id");
+        fields.remove("view_count");
+        fields.remove("created_by");
+        Ok(fields)
+    }
+}
+
+impl From<Post> for PostDocument {
+    fn from(post: Post) -> Self {
+        let (created_by, updated_by) = context::audit_stamp(post.created_by, post.updated_by);
+        Self {
+            id: post.id.to_string(),
+            user_id: post.user_id.to_string(),
+            organization_id: post.organization_id.map(|id| id.to_string()),
+            title: post.title,
+            content: post.content,
+            version: post.version,
+            deleted_at: post.deleted_at.map(to_bson_date),
+            view_count: post.view_count,
+            published_at: post.published_at.map(to_bson_date),
+            created_at: to_bson_date(post.created_at),
+            updated_at: to_bson_date(post.updated_at),
+            created_by: created_by.map(|id| id.to_string()),
+            updated_by: updated_by.map(|id| id.to_string()),
+        }
+    }
+}
+
+impl TryFrom<PostDocument> for Post {
+    type Error = RepoError;
+
+    fn try_from(document: PostDocument) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: parse_id(&document.id)?,
+            user_id: parse_id(&document.user_id)?,
+            organization_id: document
+                .organization_id
+                .as_deref()
+                .map(parse_id)
+                .transpose()?,
+            title: document.title,
+            content: document.content,
+            version: document.version,
+            deleted_at: document.deleted_at.map(to_chrono),
+            view_count: document.view_count,
+            published_at: document.published_at.map(to_chrono),
+            created_at: to_chrono(document.created_at),
+            updated_at: to_chrono(document.updated_at),
+            created_by: document.created_by.as_deref().map(parse_id).transpose()?,
+            updated_by: document.updated_by.as_deref().map(parse_id).transpose()?,
+        })
+    }
+}
+
+fn to_bson_date(at: DateTime<Utc>) -> bson::DateTime {
+    bson::DateTime::from_millis(at.timestamp_millis())
+}
+
+fn to_chrono(at: bson::DateTime) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(at.timestamp_millis()).unwrap_or_default()
+}
+
+fn parse_id(id: &str) -> Result<Uuid, RepoError> {
+    id.parse()
+        .map_err(|_| RepoError::Query(format!("Invalid stored id: {}", id)))
+}
+
+fn id_filter(id: Uuid) -> Document {
+    doc! { "_id": id.to_string() }
+}
+
+/// Sort document for `request`, by its field if it has one, then by id so
+/// documents with equal sort values keep their order from page to page.
+fn sort_document(request: &PageRequest, fields: &[&str]) -> Result<Document, RepoError> {
+    let mut sort = Document::new();
+    if let Some(requested) = &request.sort {
+        if !fields.contains(&requested.field.as_str()) {
+            return Err(RepoError::Query(format!(
+                "Unknown sort field: {}",
+                requested.field
+            )));
+        }
+        let field = match requested.field.as_str() {
+            "id" => "_id",
+            field => field,
+        };
+        let order = match requested.direction {
+            SortDirection::Asc => 1,
+            SortDirection::Desc => -1,
+        };
+        sort.insert(field, order);
+    }
+    if !sort.contains_key("_id") {
+        sort.insert("_id", 1);
+    }
+    Ok(sort)
+}
+
+fn bson_value(value: &FilterValue) -> bson::Bson {
+    match value {
+        FilterValue::Uuid(id) => bson::Bson::String(id.to_string()),
+        FilterValue::Text(text) => bson::Bson::String(text.clone()),
+        FilterValue::Number(number) => bson::Bson::Int64(*number),
+        FilterValue::Time(time) => bson::Bson::DateTime(to_bson_date(*time)),
+    }
+}
+
+/// Query document for `filter`, on the stored `fields`. Comparisons never
+/// match a missing or null field, as in SQL.
+fn filter_document(filter: &Filter, fields: &[&str]) -> Result<Document, RepoError> {
+    let field = |field: &str| -> Result<String, RepoError> {
+        match field {
+            _ if !fields.contains(&field) => {
+                Err(RepoError::Query(format!("Unknown filter field: {}", field)))
+            }
+            "id" => Ok("_id".to_string()),
+            field => Ok(field.to_string()),
+        }
+    };
+    let nested = |filters: &[Filter]| -> Result<Vec<Document>, RepoError> {
+        filters.iter().map(|f| filter_document(f, fields)).collect()
+    };
+    Ok(match filter {
+        Filter::Compare {
+            field: name,
+            op,
+            value,
+        } => {
+            let name = field(name)?;
+            let value = bson_value(value);
+            match op {
+                CompareOp::Eq => doc! { name: value },
+                // $ne alone would match null fields too
+                CompareOp::Ne => doc! {
+                    "$and": [{ name.as_str(): { "$ne": value } }, { name.as_str(): { "$ne": null } }]
+                },
+                CompareOp::Lt => doc! { name: { "$lt": value } },
+                CompareOp::Lte => doc! { name: { "$lte": value } },
+                CompareOp::Gt => doc! { name: { "$gt": value } },
+                CompareOp::Gte => doc! { name: { "$gte": value } },
+            }
+        }
+        Filter::In {
+            field: name,
+            values,
+        } => {
+            let values: Vec<bson::Bson> = values.iter().map(bson_value).collect();
+            doc! { field(name)?: { "$in": values } }
+        }
+        Filter::Null {
+            field: name,
+            null: true,
+        } => doc! { field(name)?: null },
+        Filter::Null {
+            field: name,
+            null: false,
+        } => doc! { field(name)?: { "$ne": null } },
+        // Mongo rejects empty $and and $or
+        Filter::And(filters) if filters.is_empty() => Document::new(),
+        Filter::Or(filters) if filters.is_empty() => doc! { "$expr": false },
+        Filter::And(filters) => doc! { "$and": nested(filters)? },
+        Filter::Or(filters) => doc! { "$or": nested(filters)? },
+    })
+}
+
+/// The page of `filter` that `request` asks for, counting the documents of
+/// the whole selection for the total.
+async fn fetch_page<D, T>(
+    collection: &Collection<D>,
+    filter: Document,
+    request: &PageRequest,
+    fields: &[&str],
+) -> Result<Page<T>, RepoError>
+where
+    D: Send + Sync + serde::de::DeserializeOwned,
+    T: TryFrom<D, Error = RepoError>,
+{
+    let sort = sort_document(request, fields)?;
+    let filter = match &request.filter {
+        Some(requested) => doc! { "$and": [filter, filter_document(requested, fields)?] },
+        None => filter,
+    };
+    let total = collection
+        .count_documents(filter.clone())
+        .await
+        .map_err(map_mongo_error)?;
+    let items = collect(
+        collection
+            .find(filter)
+            .sort(sort)
+            .skip(request.offset)
+            .limit(request.limit as i64)
+            .await,
+    )
+    .await?;
+
+    Ok(Page {
+        items,
+        total,
+        offset: request.offset,
+        limit: request.limit,
+    })
+}
+
+/// Every document of a find, converted.
+async fn collect<D, T>(
+    cursor: Result<mongodb::Cursor<D>, mongodb::error::Error>,
+) -> Result<Vec<T>, RepoError>
+where
+    D: Send + Sync + serde::de::DeserializeOwned,
+    T: TryFrom<D, Error = RepoError>,
+{
+    let documents: Vec<D> = cursor
+        .map_err(map_mongo_error)?
+        .try_collect()
+        .await
+        .map_err(map_mongo_error)?;
+    documents.into_iter().map(T::try_from).collect()
+}
+
+/// MongoDB user repository, over the `users` collection.
+#[derive(Clone)]
+pub struct MongoUserRepository {
+    users: Collection<UserDocument>,
+}
+
+impl MongoUserRepository {
+    pub fn new(database: &Database) -> Self {
+        Self {
+            users: database.collection("users"),
+        }
+    }
+
+    /// Create the indexes lookups rely on, including the one keeping emails
+    /// unique. Safe to run on every start.
+    pub async fn ensure_indexes(&self) -> Result<(), RepoError> {
+        let unique = IndexOptions::builder().unique(true).build();
+        self.users
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "email": 1 })
+                    .options(unique)
+                    .build(),
+            )
+            .await
+            .map_err(map_mongo_error)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BaseRepository<User, Uuid> for MongoUserRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, RepoError> {
+        self.users
+            .find_one(id_filter(id))
+            .await
+            .map_err(map_mongo_error)?
+            .map(User::try_from)
+            .transpose()
+    }
+
+    async fn save(&self, entity: User) -> Result<User, RepoError> {
+        self.users
+            .replace_one(id_filter(entity.id), UserDocument::from(entity.clone()))
+            .upsert(true)
+            .await
+            .map_err(map_mongo_error)?;
+        Ok(entity)
+    }
+
+    async fn insert(&self, entity: User) -> Result<User, RepoError> {
+        self.users
+            .insert_one(UserDocument::from(entity.clone()))
+            .await
+            .map_err(map_mongo_error)?;
+        Ok(entity)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        let result = self
+            .users
+            .delete_one(id_filter(id))
+            .await
+            .map_err(map_mongo_error)?;
+        if result.deleted_count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<User>, RepoError> {
+        fetch_page(&self.users, Document::new(), &request, USER_FIELDS).await
+    }
+}
+
+#[async_trait]
+impl UserRepository for MongoUserRepository {
+    async fn find_by_email(&self, email: &Email) -> Result<Option<User>, RepoError> {
+        tracing::debug!(user_email = %Sensitive(email), "Finding user by email");
+
+        self.users
+            .find_one(doc! { "email": email.as_str() })
+            .await
+            .map_err(map_mongo_error)?
+            .map(User::try_from)
+            .transpose()
+    }
+}
+
+/// MongoDB post repository, over the `posts` collection.
+#[derive(Clone)]
+pub struct MongoPostRepository {
+    posts: Collection<PostDocument>,
+}
+
+impl MongoPostRepository {
+    pub fn new(database: &Database) -> Self {
+        Self {
+            posts: database.collection("posts"),
+        }
+    }
+
+    /// Create the indexes the listings and sync rely on. Safe to run on every
+    /// start.
+    pub async fn ensure_indexes(&self) -> Result<(), RepoError> {
+        let indexes = [
+            doc! { "user_id": 1, "updated_at": 1, "_id": 1 },
+            doc! { "organization_id": 1 },
+            doc! { "published_at": -1, "_id": -1 },
+        ];
+        self.posts
+            .create_indexes(
+                indexes
+                    .into_iter()
+                    .map(|keys| IndexModel::builder().keys(keys).build()),
+            )
+            .await
+            .map_err(map_mongo_error)?;
+        Ok(())
+    }
+
+    /// Write `post` over the document `filter` matches, creating it when
+    /// `upsert` is set. Returns whether a document matched or was created.
+    async fn write(&self, filter: Document, post: Post, upsert: bool) -> Result<bool, RepoError> {
+        let document = PostDocument::from(post);
+        let fields = document.fields()?;
+        let result = self
+            .posts
+            .update_one(
+                filter,
+                doc! {
+                    "$set": fields,
+                    "$setOnInsert": { "view_count": 0_i64, "created_by": document.created_by },
+                },
+            )
+            .upsert(upsert)
+            .await
+            .map_err(map_mongo_error)?;
+        Ok(result.matched_count == 1 || result.upserted_id.is_some())
+    }
+}
+
+#[async_trait]
+impl BaseRepository<Post, Uuid> for MongoPostRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Post>, RepoError> {
+        self.posts
+            .find_one(id_filter(id))
+            .await
+            .map_err(map_mongo_error)?
+            .map(Post::try_from)
+            .transpose()
+    }
+
+    async fn save(&self, entity: Post) -> Result<Post, RepoError> {
+        self.write(id_filter(entity.id), entity.clone(), true)
+            .await?;
+        Ok(entity)
+    }
+
+    async fn insert(&self, entity: Post) -> Result<Post, RepoError> {
+        self.posts
+            .insert_one(PostDocument::from(entity.clone()))
+            .await
+            .map_err(map_mongo_error)?;
+        Ok(entity)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        let result = self
+            .posts
+            .delete_one(id_filter(id))
+            .await
+            .map_err(map_mongo_error)?;
+        if result.deleted_count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<Post>, RepoError> {
+        fetch_page(&self.posts, Document::new(), &request, POST_FIELDS).await
+    }
+}
+
+#[async_trait]
+impl PostRepository for MongoPostRepository {
+    async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<Post>, RepoError> {
+        collect(
+            self.posts
+                .find(doc! { "user_id": user_id.to_string(), "deleted_at": null })
+                .await,
+        )
+        .await
+    }
+
+    async fn find_page_by_user_id(
+        &self,
+        user_id: Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        let filter = doc! { "user_id": user_id.to_string(), "deleted_at": null };
+        fetch_page(&self.posts, filter, &request, POST_FIELDS).await
+    }
+
+    async fn find_by_user_id_after(
+        &self,
+        user_id: Uuid,
+        after: Option<PageCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let mut filter = doc! { "user_id": user_id.to_string(), "deleted_at": null };
+        if let Some(after) = after {
+            let created_at = to_bson_date(after.created_at);
+            filter.insert(
+                "$or",
+                vec![
+                    doc! { "created_at": { "$lt": created_at } },
+                    doc! { "created_at": created_at, "_id": { "$lt": after.id.to_string() } },
+                ],
+            );
+        }
+
+        collect(
+            self.posts
+                .find(filter)
+                .sort(doc! { "created_at": -1, "_id": -1 })
+                .limit(limit as i64)
+                .await,
+        )
+        .await
+    }
+
+    async fn find_by_organization_id(&self, organization_id: Uuid) -> Result<Vec<Post>, RepoError> {
+        collect(
+            self.posts
+                .find(doc! {
+                    "organization_id": organization_id.to_string(),
+                    "deleted_at": null,
+                })
+                .await,
+        )
+        .await
+    }
+
+    async fn find_page_by_organization_id(
+        &self,
+        organization_id: Uuid,
+        user_id: Option<Uuid>,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        let mut filter =
+            doc! { "organization_id": organization_id.to_string(), "deleted_at": null };
+        if let Some(user_id) = user_id {
+            filter.insert("user_id", user_id.to_string());
+        }
+        fetch_page(&self.posts, filter, &request, POST_FIELDS).await
+    }
+
+    async fn list_changes(
+        &self,
+        user_id: Uuid,
+        after: Option<SyncCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let mut filter = doc! { "user_id": user_id.to_string() };
+        if let Some(after) = after {
+            let updated_at = to_bson_date(after.updated_at);
+            filter.insert(
+                "$or",
+                vec![
+                    doc! { "updated_at": { "$gt": updated_at } },
+                    doc! { "updated_at": updated_at, "_id": { "$gt": after.id.to_string() } },
+                ],
+            );
+        }
+
+        collect(
+            self.posts
+                .find(filter)
+                .sort(doc! { "updated_at": 1, "_id": 1 })
+                .limit(limit as i64)
+                .await,
+        )
+        .await
+    }
+
+    async fn save_if_version(
+        &self,
+        post: Post,
+        expected_version: Option<i64>,
+    ) -> Result<(), RepoError> {
+        let id = post.id;
+        let written = match expected_version {
+            None => match self.posts.insert_one(PostDocument::from(post)).await {
+                Ok(_) => true,
+                Err(e) => match map_mongo_error(e) {
+                    RepoError::Constraint(_) => false,
+                    e => return Err(e),
+                },
+            },
+            Some(version) => {
+                let filter = doc! { "_id": id.to_string(), "version": version };
+                self.write(filter, post, false).await?
+            }
+        };
+        if !written {
+            return Err(RepoError::stale_version(id, expected_version));
+        }
+        Ok(())
+    }
+
+    async fn add_views(&self, views: Vec<(Uuid, i64)>) -> Result<(), RepoError> {
+        if views.is_empty() {
+            return Ok(());
+        }
+
+        // One update for the batch: view_count + $switch on _id
+        let ids: Vec<String> = views.iter().map(|(id, _)| id.to_string()).collect();
+        let branches: Vec<Document> = views
+            .iter()
+            .map(|(id, views)| doc! { "case": { "$eq": ["$_id", id.to_string()] }, "then": views })
+            .collect();
+        let increment = doc! { "$switch": { "branches": branches, "default": 0_i64 } };
+
+        self.posts
+            .update_many(
+                doc! { "_id": { "$in": ids } },
+                vec![doc! {
+                    "$set": { "view_count": { "$add": [{ "$ifNull": ["$view_count", 0_i64] }, increment] } }
+                }],
+            )
+            .await
+            .map_err(map_mongo_error)?;
+
+        Ok(())
+    }
+
+    async fn list_published(&self, now: DateTime<Utc>, limit: u64) -> Result<Vec<Post>, RepoError> {
+        collect(
+            self.posts
+                .find(doc! {
+                    "published_at": { "$ne": null, "$lte": to_bson_date(now) },
+                    "deleted_at": null,
+                })
+                .sort(doc! { "published_at": -1, "_id": -1 })
+                .limit(limit as i64)
+                .await,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apex_core::domain::Sort;
+
+    #[test]
+    fn test_post_documents_round_trip_to_the_millisecond() {
+        let mut post = Post::new(Uuid::new_v4(), "Title".to_string(), "Body".to_string());
+        post.organization_id = Some(Uuid::new_v4());
+        post.view_count = 7;
+
+        let document = PostDocument::from(post.clone());
+        assert_eq!(document.id, post.id.to_string());
+        let bson = bson::to_document(&document).unwrap();
+        let loaded: PostDocument = bson::from_document(bson).unwrap();
+        let loaded = Post::try_from(loaded).unwrap();
+
+        assert_eq!(loaded.id, post.id);
+        assert_eq!(loaded.organization_id, post.organization_id);
+        assert_eq!(loaded.view_count, 7);
+        assert_eq!(
+            loaded.created_at.timestamp_millis(),
+            post.created_at.timestamp_millis()
+        );
+    }
+
+    #[test]
+    fn test_saves_leave_the_view_count_alone() {
+        let post = Post::new(Uuid::new_v4(), "Title".to_string(), "Body".to_string());
+        let fields = PostDocument::from(post).fields().unwrap();
+
+        assert!(!fields.contains_key("view_count"));
+        assert!(!fields.contains_key("_id"));
+        assert!(fields.contains_key("version"));
+    }
+
+    #[test]
+    fn test_sort_documents_end_with_the_id() {
+        let request = PageRequest::default().sorted_by(Sort::desc("created_at"));
+        let sort = sort_document(&request, POST_FIELDS).unwrap();
+        assert_eq!(sort, doc! { "created_at": -1, "_id": 1 });
+
+        let by_id = PageRequest::default().sorted_by(Sort::desc("id"));
+        assert_eq!(
+            sort_document(&by_id, USER_FIELDS).unwrap(),
+            doc! { "_id": -1 }
+        );
+
+        let unknown = PageRequest::default().sorted_by(Sort::asc("password"));
+        assert!(matches!(
+            sort_document(&unknown, USER_FIELDS),
+            Err(RepoError::Query(_))
+        ));
+    }
+
+    #[test]
+    fn test_filters_translate_to_query_documents() {
+        let author = Uuid::new_v4();
+        let filter = Filter::eq("user_id", author)
+            .and(Filter::is_null("deleted_at"))
+            .and(Filter::ne("id", author).or(Filter::gte("view_count", 10)));
+        assert_eq!(
+            filter_document(&filter, POST_FIELDS).unwrap(),
+            doc! { "$and": [
+                { "user_id": author.to_string() },
+                { "deleted_at": null },
+                { "$or": [
+                    { "$and": [{ "_id": { "$ne": author.to_string() } }, { "_id": { "$ne": null } }] },
+                    { "view_count": { "$gte": 10_i64 } },
+                ] },
+            ] }
+        );
+        assert_eq!(
+            filter_document(&Filter::Or(Vec::new()), POST_FIELDS).unwrap(),
+            doc! { "$expr": false }
+        );
+        assert!(matches!(
+            filter_document(&Filter::is_null("password"), POST_FIELDS),
+            Err(RepoError::Query(_))
+        ));
+    }
+
+    #[test]
+    fn test_stored_ids_must_be_uuids() {
+        let mut document = UserDocument::from(User::new(
+            "a@example.com".parse().unwrap(),
+            "hash".to_string(),
+        ));
+        document.id = "not-a-uuid".to_string();
+        assert!(matches!(User::try_from(document), Err(RepoError::Query(_))));
+    }
+}
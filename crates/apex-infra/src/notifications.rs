@@ -0,0 +1,250 @@
+//! Recent realtime notifications, for clients that long-poll instead of
+//! holding a WebSocket.
+//!
+//! The log subscribes to the pub/sub channels WebSocket clients are pushed
+//! from and keeps the latest notifications, numbered in arrival order. A
+//! poll returns those after the client's cursor, or waits for the next one.
+//! Cursors are per instance, like the in-process pub/sub feeding the log.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use apex_core::domain::Audience;
+use apex_core::ports::{Envelope, PubSub, PubSubError, SubscriptionHandle};
+
+use crate::announcements::ANNOUNCEMENTS_CHANNEL;
+use crate::env;
+use crate::pubsub::TypedPubSub;
+
+/// A notification as delivered to polling clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// Position in the log; polling with it as the cursor returns what
+    /// came after.
+    pub seq: u64,
+    pub id: Uuid,
+    pub event: String,
+    /// Who may see it.
+    pub audience: Audience,
+    pub timestamp: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+/// Notifications after a cursor.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NotificationPage {
+    pub notifications: Vec<Notification>,
+    /// Cursor for the next poll.
+    pub cursor: u64,
+    /// Notifications after the cursor were dropped from the log before
+    /// this poll, or the cursor is not from this log (e.g. issued before a
+    /// restart); the client should refetch what it shows.
+    pub truncated: bool,
+}
+
+struct Entries {
+    notifications: VecDeque<Notification>,
+    last_seq: u64,
+}
+
+/// The latest notifications, bounded in number.
+pub struct NotificationLog {
+    entries: Mutex<Entries>,
+    capacity: usize,
+    /// Latest sequence number, for waking pollers.
+    latest: watch::Sender<u64>,
+}
+
+impl NotificationLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                notifications: VecDeque::with_capacity(capacity),
+                last_seq: 0,
+            }),
+            capacity: capacity.max(1),
+            latest: watch::Sender::new(0),
+        }
+    }
+
+    /// Capacity from `NOTIFICATION_LOG_SIZE`, 1000 by default.
+    pub fn from_env() -> Self {
+        Self::new(env::NOTIFICATION_LOG_SIZE.get())
+    }
+
+    /// Append a notification, returning its sequence number.
+    pub fn record(&self, envelope: Envelope<serde_json::Value>, audience: Audience) -> u64 {
+        let seq = {
+            let mut entries = self.entries.lock().expect("notification log lock");
+            entries.last_seq += 1;
+            let seq = entries.last_seq;
+            if entries.notifications.len() == self.capacity {
+                entries.notifications.pop_front();
+            }
+            entries.notifications.push_back(Notification {
+                seq,
+                id: envelope.id,
+                event: envelope.event,
+                audience,
+                timestamp: envelope.timestamp,
+                data: envelope.data,
+            });
+            seq
+        };
+        self.latest.send_replace(seq);
+        seq
+    }
+
+    /// Sequence number of the newest notification, 0 before the first.
+    pub fn latest(&self) -> u64 {
+        *self.latest.borrow()
+    }
+
+    /// Notifications after `cursor` that `visible` lets through. The
+    /// returned cursor skips past invisible ones too.
+    pub fn since(&self, cursor: u64, visible: impl Fn(&Notification) -> bool) -> NotificationPage {
+        let entries = self.entries.lock().expect("notification log lock");
+        if cursor > entries.last_seq {
+            return NotificationPage {
+                notifications: Vec::new(),
+                cursor: entries.last_seq,
+                truncated: true,
+            };
+        }
+        let oldest = entries
+            .notifications
+            .front()
+            .map_or(entries.last_seq + 1, |n| n.seq);
+        NotificationPage {
+            notifications: entries
+                .notifications
+                .iter()
+                .filter(|n| n.seq > cursor && visible(n))
+                .cloned()
+                .collect(),
+            cursor: entries.last_seq,
+            truncated: cursor + 1 < oldest && cursor < entries.last_seq,
+        }
+    }
+
+    /// Notifications after `cursor` (from now on without one), waiting up to
+    /// `timeout` for one `visible` lets through if there is none yet.
+    pub async fn poll(
+        &self,
+        cursor: Option<u64>,
+        timeout: Duration,
+        visible: impl Fn(&Notification) -> bool,
+    ) -> NotificationPage {
+        let mut latest = self.latest.subscribe();
+        let mut cursor = cursor.unwrap_or_else(|| *latest.borrow_and_update());
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            latest.mark_unchanged();
+            let page = self.since(cursor, &visible);
+            if !page.notifications.is_empty() || page.truncated {
+                return page;
+            }
+            cursor = page.cursor;
+            match tokio::time::timeout_at(deadline, latest.changed()).await {
+                Ok(Ok(())) => continue,
+                // Timed out, or the log is gone
+                _ => return page,
+            }
+        }
+    }
+
+    /// Record every announcement published on `pubsub`, for as long as the
+    /// returned handle lives.
+    pub async fn follow_announcements<P: PubSub + 'static>(
+        self: Arc<Self>,
+        pubsub: &TypedPubSub<P>,
+    ) -> Result<SubscriptionHandle, PubSubError> {
+        pubsub
+            .subscribe_json(
+                ANNOUNCEMENTS_CHANNEL,
+                move |envelope: Envelope<serde_json::Value>| {
+                    let log = self.clone();
+                    async move {
+                        let audience = envelope.data["audience"]
+                            .as_str()
+                            .and_then(|audience| audience.parse().ok());
+                        match audience {
+                            Some(audience) => {
+                                log.record(envelope, audience);
+                            }
+                            None => tracing::warn!(
+                                id = %envelope.id,
+                                "Dropping announcement without a valid audience"
+                            ),
+                        }
+                    }
+                },
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope() -> Envelope<serde_json::Value> {
+        Envelope::new("announcement.published", "test", serde_json::json!({}))
+    }
+
+    #[tokio::test]
+    async fn test_poll_returns_what_came_after_the_cursor() {
+        let log = NotificationLog::new(3);
+        let admins = Audience::Role("admin".to_string());
+        log.record(envelope(), Audience::Everyone);
+        log.record(envelope(), admins.clone());
+        log.record(envelope(), Audience::Everyone);
+
+        let everyone_only = |n: &Notification| n.audience == Audience::Everyone;
+        let page = log.since(0, everyone_only);
+        assert_eq!(
+            page.notifications.iter().map(|n| n.seq).collect::<Vec<_>>(),
+            [1, 3]
+        );
+        assert_eq!((page.cursor, page.truncated), (3, false));
+
+        // Two more push the first two out of the log
+        log.record(envelope(), Audience::Everyone);
+        log.record(envelope(), Audience::Everyone);
+        assert!(log.since(1, |_| true).truncated);
+        assert!(!log.since(2, |_| true).truncated);
+        assert!(!log.since(5, |_| true).truncated);
+        assert_eq!(log.since(9, |_| true).cursor, 5);
+        assert!(log.since(9, |_| true).truncated);
+    }
+
+    #[tokio::test]
+    async fn test_poll_waits_for_a_visible_notification() {
+        let log = Arc::new(NotificationLog::new(10));
+        let cursor = log.record(envelope(), Audience::Everyone);
+
+        let timeout = Duration::from_millis(50);
+        let page = log.poll(Some(cursor), timeout, |_| true).await;
+        assert!(page.notifications.is_empty());
+        assert_eq!(page.cursor, cursor);
+
+        let publisher = log.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            publisher.record(envelope(), Audience::Role("admin".to_string()));
+            publisher.record(envelope(), Audience::Everyone);
+        });
+        let page = log
+            .poll(None, Duration::from_secs(5), |n| {
+                n.audience == Audience::Everyone
+            })
+            .await;
+        assert_eq!(page.notifications.len(), 1);
+        assert_eq!(page.cursor, cursor + 2);
+    }
+}
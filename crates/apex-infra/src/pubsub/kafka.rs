@@ -0,0 +1,316 @@
+//! Kafka PubSub implementation.
+//!
+//! Channels map to topics, so events published by the app land in Kafka
+//! where downstream pipelines can consume them alongside the app itself.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use apex_core::ports::{PubSub, PubSubError, PubSubMessage, PubSubStats, SubscriptionHandle};
+use async_trait::async_trait;
+use rdkafka::ClientConfig;
+use rdkafka::Message;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+
+use super::stats::PubSubCounters;
+use super::subscriptions::Subscriptions;
+use crate::env;
+
+/// When consumed offsets are committed back to Kafka.
+///
+/// Offsets only advance past messages whose handler has finished, so both
+/// policies deliver at least once; they differ in how many messages are
+/// redelivered after a crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetCommit {
+    /// Commit handled offsets in the background every interval.
+    Interval(Duration),
+    /// Commit after every handled message. Slower, but nothing handled is
+    /// redelivered after a clean shutdown.
+    EveryMessage,
+}
+
+/// Kafka connection configuration.
+#[derive(Debug, Clone)]
+pub struct KafkaConfig {
+    /// Comma-separated bootstrap servers, e.g. `localhost:9092`.
+    pub brokers: String,
+    pub client_id: String,
+    /// Consumer group subscriptions join unless overridden in `groups`.
+    /// Instances sharing a group split a topic's messages between them;
+    /// give each instance its own group to have all of them see every one.
+    pub group_id: String,
+    /// Prefix of the topic a channel maps to when it is not in `topics`.
+    pub topic_prefix: String,
+    /// Explicit channel to topic mapping.
+    pub topics: HashMap<String, String>,
+    /// Per-channel consumer group overrides.
+    pub groups: HashMap<String, String>,
+    pub commit: OffsetCommit,
+    /// Where a new consumer group starts reading: `latest` or `earliest`.
+    pub offset_reset: String,
+    /// How long a publish may wait for the broker to acknowledge.
+    pub send_timeout: Duration,
+}
+
+impl Default for KafkaConfig {
+    fn default() -> Self {
+        Self {
+            brokers: "localhost:9092".to_string(),
+            client_id: "apex".to_string(),
+            group_id: "apex".to_string(),
+            topic_prefix: String::new(),
+            topics: HashMap::new(),
+            groups: HashMap::new(),
+            commit: OffsetCommit::Interval(Duration::from_secs(5)),
+            offset_reset: "latest".to_string(),
+            send_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl KafkaConfig {
+    /// Load configuration from environment variables.
+    ///
+    /// `KAFKA_TOPICS` and `KAFKA_GROUPS` take `channel=name` pairs, e.g.
+    /// `announcements=apex.announcements`. `KAFKA_COMMIT` is `interval` (every
+    /// `KAFKA_COMMIT_INTERVAL_MS`) or `message`.
+    pub fn from_env() -> Self {
+        Self {
+            brokers: env::KAFKA_BROKERS.string(),
+            client_id: env::KAFKA_CLIENT_ID.string(),
+            group_id: env::KAFKA_GROUP_ID.string(),
+            topic_prefix: env::KAFKA_TOPIC_PREFIX.string(),
+            topics: parse_mapping(&env::KAFKA_TOPICS.string()),
+            groups: parse_mapping(&env::KAFKA_GROUPS.string()),
+            commit: if env::KAFKA_COMMIT.string().eq_ignore_ascii_case("message") {
+                OffsetCommit::EveryMessage
+            } else {
+                OffsetCommit::Interval(env::KAFKA_COMMIT_INTERVAL_MS.millis())
+            },
+            offset_reset: env::KAFKA_OFFSET_RESET.string(),
+            send_timeout: env::KAFKA_SEND_TIMEOUT_MS.millis(),
+        }
+    }
+
+    /// Topic messages on `channel` are published to.
+    pub fn topic(&self, channel: &str) -> String {
+        self.topics
+            .get(channel)
+            .cloned()
+            .unwrap_or_else(|| format!("{}{}", self.topic_prefix, channel))
+    }
+
+    /// Consumer group subscriptions to `channel` join.
+    pub fn group(&self, channel: &str) -> &str {
+        self.groups
+            .get(channel)
+            .map(String::as_str)
+            .unwrap_or(&self.group_id)
+    }
+}
+
+/// Parse `channel=name` pairs, skipping malformed entries.
+fn parse_mapping(spec: &str) -> HashMap<String, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once('=')
+                .map(|(channel, name)| (channel.trim(), name.trim()))
+                .filter(|(channel, name)| !channel.is_empty() && !name.is_empty());
+            if parsed.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed Kafka mapping");
+            }
+            parsed.map(|(channel, name)| (channel.to_string(), name.to_string()))
+        })
+        .collect()
+}
+
+/// Kafka-backed PubSub implementation.
+pub struct KafkaPubSub {
+    producer: FutureProducer,
+    config: KafkaConfig,
+    subscriptions: Subscriptions,
+    counters: Arc<PubSubCounters>,
+}
+
+impl KafkaPubSub {
+    /// Create the producer. Brokers are connected to lazily, so this does not
+    /// fail when Kafka is down.
+    pub fn new(config: KafkaConfig) -> Result<Self, PubSubError> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("client.id", &config.client_id)
+            .set(
+                "message.timeout.ms",
+                config.send_timeout.as_millis().to_string(),
+            )
+            .create()
+            .map_err(|e| PubSubError::Connection(e.to_string()))?;
+
+        tracing::info!(brokers = %config.brokers, "Kafka PubSub configured");
+
+        Ok(Self {
+            producer,
+            config,
+            subscriptions: Subscriptions::default(),
+            counters: Arc::new(PubSubCounters::default()),
+        })
+    }
+
+    /// Create from environment configuration.
+    pub fn from_env() -> Result<Self, PubSubError> {
+        Self::new(KafkaConfig::from_env())
+    }
+
+    pub fn config(&self) -> &KafkaConfig {
+        &self.config
+    }
+
+    fn consumer(&self, channel: &str) -> Result<StreamConsumer, PubSubError> {
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", &self.config.brokers)
+            .set("client.id", &self.config.client_id)
+            .set("group.id", self.config.group(channel))
+            .set("auto.offset.reset", &self.config.offset_reset)
+            // Offsets are stored once the handler is done, never on receipt
+            .set("enable.auto.offset.store", "false");
+        match self.config.commit {
+            OffsetCommit::Interval(interval) => config
+                .set("enable.auto.commit", "true")
+                .set("auto.commit.interval.ms", interval.as_millis().to_string()),
+            OffsetCommit::EveryMessage => config.set("enable.auto.commit", "false"),
+        };
+
+        config
+            .create()
+            .map_err(|e| PubSubError::SubscribeError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl PubSub for KafkaPubSub {
+    async fn publish(&self, channel: &str, message: &str) -> Result<(), PubSubError> {
+        let topic = self.config.topic(channel);
+        self.producer
+            .send(
+                FutureRecord::<(), str>::to(&topic).payload(message),
+                self.config.send_timeout,
+            )
+            .await
+            .map_err(|(e, _)| PubSubError::PublishError(e.to_string()))?;
+        self.counters.published();
+        Ok(())
+    }
+
+    /// Each subscription is its own consumer in the channel's group, so
+    /// several handlers on one channel split its partitions between them.
+    async fn subscribe<F>(
+        &self,
+        channel: &str,
+        handler: F,
+    ) -> Result<SubscriptionHandle, PubSubError>
+    where
+        F: Fn(PubSubMessage) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static,
+    {
+        let topic = self.config.topic(channel);
+        let consumer = self.consumer(channel)?;
+        consumer
+            .subscribe(&[&topic])
+            .map_err(|e| PubSubError::SubscribeError(e.to_string()))?;
+
+        let commit = self.config.commit;
+        let channel_name = channel.to_string();
+        let counters = self.counters.clone();
+        let handle = tokio::spawn(async move {
+            tracing::debug!(channel = %channel_name, topic = %topic, "Subscribed to Kafka topic");
+
+            loop {
+                let msg = match consumer.recv().await {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        tracing::warn!(topic = %topic, error = %e, "Kafka consumer error");
+                        continue;
+                    }
+                };
+
+                match msg.payload_view::<str>() {
+                    Some(Ok(payload)) => {
+                        counters
+                            .deliver(handler(PubSubMessage {
+                                channel: channel_name.clone(),
+                                payload: payload.to_string(),
+                            }))
+                            .await;
+                    }
+                    Some(Err(e)) => {
+                        tracing::warn!(topic = %topic, error = %e, "Skipping non-UTF-8 message");
+                    }
+                    None => {}
+                }
+
+                let committed = match commit {
+                    OffsetCommit::Interval(_) => consumer.store_offset_from_message(&msg),
+                    OffsetCommit::EveryMessage => consumer.commit_message(&msg, CommitMode::Async),
+                };
+                if let Err(e) = committed {
+                    tracing::warn!(topic = %topic, error = %e, "Failed to commit Kafka offset");
+                }
+            }
+        });
+
+        Ok(self.subscriptions.register(channel, handle))
+    }
+
+    async fn unsubscribe(&self, channel: &str) -> Result<(), PubSubError> {
+        if self.subscriptions.cancel_channel(channel) > 0 {
+            tracing::debug!(channel = %channel, "Unsubscribed from Kafka topic");
+        }
+        Ok(())
+    }
+
+    /// Not supported: a temporary reply channel would be a topic created and
+    /// dropped per request. Use Redis or in-memory pub/sub for RPC.
+    async fn request(
+        &self,
+        _channel: &str,
+        _payload: &str,
+        _timeout: Duration,
+    ) -> Result<String, PubSubError> {
+        Err(PubSubError::Unsupported(
+            "request/reply over Kafka".to_string(),
+        ))
+    }
+
+    fn stats(&self) -> PubSubStats {
+        self.counters.snapshot(self.subscriptions.count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels_map_to_topics_and_groups() {
+        let config = KafkaConfig {
+            topic_prefix: "apex.".to_string(),
+            topics: parse_mapping("announcements=public.announcements, broken, =x"),
+            groups: parse_mapping("audit=audit-sink"),
+            ..KafkaConfig::default()
+        };
+
+        assert_eq!(config.topic("announcements"), "public.announcements");
+        assert_eq!(config.topic("orders"), "apex.orders");
+        assert_eq!(config.group("audit"), "audit-sink");
+        assert_eq!(config.group("orders"), "apex");
+        assert_eq!(config.topics.len(), 1);
+    }
+}
@@ -2,28 +2,177 @@
 //!
 //! This is a fallback when Redis is not available.
 //! Works within a single process only.
+//!
+//! Every subscriber has its own bounded queue. When a slow subscriber's
+//! queue is full, the channel's [`OverflowPolicy`] decides what gives.
 
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use tokio::sync::{RwLock, broadcast};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use apex_core::ports::{
+    PubSub, PubSubError, PubSubMessage, PubSubStats, RpcReply, RpcRequest, SubscriptionHandle,
+};
 
-use apex_core::ports::{PubSub, PubSubError, PubSubMessage};
+use super::queue::{Offer, SubscriberQueue};
+use super::stats::PubSubCounters;
+use super::subscriptions::Subscriptions;
+use crate::env;
+
+/// What a publish does when a subscriber's queue is full. Every message
+/// lost to it counts as lagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Make room by dropping the subscriber's oldest queued message.
+    DropOldest,
+    /// Drop the message being published, for that subscriber only.
+    DropNewest,
+    /// Wait up to the timeout for the subscriber to catch up, then drop the
+    /// message. Slows publishers down to the slowest subscriber.
+    Block(Duration),
+    /// Cut the subscriber off; its pending messages are dropped.
+    Disconnect,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    /// `drop-oldest`, `drop-newest`, `disconnect`, or `block:<ms>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "drop-newest" => Ok(Self::DropNewest),
+            "disconnect" => Ok(Self::Disconnect),
+            other => other
+                .strip_prefix("block:")
+                .and_then(|ms| ms.parse().ok())
+                .map(|ms| Self::Block(Duration::from_millis(ms)))
+                .ok_or_else(|| format!("Unknown overflow policy: {}", other)),
+        }
+    }
+}
+
+/// In-memory pub/sub configuration.
+#[derive(Debug, Clone)]
+pub struct InMemoryPubSubConfig {
+    /// Messages each subscriber can have waiting.
+    pub buffer_size: usize,
+    /// Policy for channels not in `channel_overflow`.
+    pub overflow: OverflowPolicy,
+    pub channel_overflow: HashMap<String, OverflowPolicy>,
+}
+
+impl Default for InMemoryPubSubConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size: 100,
+            overflow: OverflowPolicy::DropOldest,
+            channel_overflow: HashMap::new(),
+        }
+    }
+}
+
+impl InMemoryPubSubConfig {
+    /// Load configuration from environment variables.
+    ///
+    /// `PUBSUB_CHANNEL_OVERFLOW` takes `channel=policy` pairs, e.g.
+    /// `billing=block:500,presence=drop-newest`.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let policy = |spec: &str| {
+            spec.parse::<OverflowPolicy>()
+                .map_err(|e| tracing::warn!(error = %e, "Ignoring pub/sub overflow policy"))
+                .ok()
+        };
+
+        Self {
+            buffer_size: env::PUBSUB_BUFFER_SIZE.get(),
+            overflow: env::PUBSUB_OVERFLOW
+                .value()
+                .and_then(|s| policy(&s))
+                .unwrap_or(defaults.overflow),
+            channel_overflow: env::PUBSUB_CHANNEL_OVERFLOW
+                .list()
+                .iter()
+                .filter_map(|entry| entry.split_once('='))
+                .filter_map(|(channel, spec)| Some((channel.trim().to_string(), policy(spec)?)))
+                .collect(),
+        }
+    }
+
+    /// Overflow policy of `channel`.
+    pub fn overflow(&self, channel: &str) -> OverflowPolicy {
+        self.channel_overflow
+            .get(channel)
+            .copied()
+            .unwrap_or(self.overflow)
+    }
+}
+
+struct Subscriber {
+    id: Uuid,
+    queue: Arc<SubscriberQueue>,
+}
+
+/// Closes a subscriber's queue when its task ends, however it ends, so
+/// publishers stop queueing for it.
+struct CloseOnDrop(Arc<SubscriberQueue>);
+
+impl Drop for CloseOnDrop {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
 
 /// In-memory pub/sub system.
 pub struct InMemoryPubSub {
-    channels: Arc<RwLock<HashMap<String, broadcast::Sender<String>>>>,
-    buffer_size: usize,
+    channels: RwLock<HashMap<String, Vec<Subscriber>>>,
+    config: InMemoryPubSubConfig,
+    counters: Arc<PubSubCounters>,
+    subscriptions: Subscriptions,
 }
 
 impl InMemoryPubSub {
     pub fn new(buffer_size: usize) -> Self {
-        Self {
-            channels: Arc::new(RwLock::new(HashMap::new())),
+        Self::with_config(InMemoryPubSubConfig {
             buffer_size,
+            ..InMemoryPubSubConfig::default()
+        })
+    }
+
+    pub fn with_config(config: InMemoryPubSubConfig) -> Self {
+        Self {
+            channels: RwLock::new(HashMap::new()),
+            config,
+            counters: Arc::new(PubSubCounters::default()),
+            subscriptions: Subscriptions::default(),
+        }
+    }
+
+    /// Create from environment configuration.
+    pub fn from_env() -> Self {
+        Self::with_config(InMemoryPubSubConfig::from_env())
+    }
+
+    pub fn config(&self) -> &InMemoryPubSubConfig {
+        &self.config
+    }
+
+    /// Stop delivering to a subscriber and forget it.
+    async fn remove_subscribers(&self, channel: &str, ids: &[Uuid]) {
+        let mut channels = self.channels.write().await;
+        if let Some(subscribers) = channels.get_mut(channel) {
+            subscribers.retain(|s| !ids.contains(&s.id));
+            if subscribers.is_empty() {
+                channels.remove(channel);
+            }
         }
     }
 }
@@ -37,68 +186,289 @@ impl Default for InMemoryPubSub {
 #[async_trait]
 impl PubSub for InMemoryPubSub {
     async fn publish(&self, channel: &str, message: &str) -> Result<(), PubSubError> {
-        let channels = self.channels.read().await;
+        // Offer outside the lock: a blocking policy may wait on a subscriber
+        let queues: Vec<(Uuid, Arc<SubscriberQueue>)> =
+            match self.channels.read().await.get(channel) {
+                Some(subscribers) => subscribers
+                    .iter()
+                    .map(|s| (s.id, s.queue.clone()))
+                    .collect(),
+                None => Vec::new(),
+            };
+        self.counters.published();
 
-        if let Some(sender) = channels.get(channel) {
-            // Ignore send errors (no subscribers)
-            let _ = sender.send(message.to_string());
-            tracing::debug!(channel = %channel, "Message published");
-        } else {
+        if queues.is_empty() {
             tracing::debug!(channel = %channel, "No subscribers for channel");
+            return Ok(());
+        }
+
+        let policy = self.config.overflow(channel);
+        let mut gone = Vec::new();
+        for (id, queue) in queues {
+            match queue.offer(message.to_string(), policy).await {
+                Offer::Queued => {}
+                Offer::QueuedDroppingOldest | Offer::Dropped => self.counters.lagged(1),
+                Offer::Overflowed => {
+                    tracing::warn!(
+                        channel = %channel,
+                        subscription = %id,
+                        "Disconnecting subscriber that fell behind"
+                    );
+                    queue.close();
+                    self.subscriptions.cancel(channel, id);
+                    self.counters.lagged(1);
+                    self.counters.disconnected();
+                    gone.push(id);
+                }
+                Offer::Closed => gone.push(id),
+            }
+        }
+        if !gone.is_empty() {
+            self.remove_subscribers(channel, &gone).await;
         }
 
+        tracing::debug!(channel = %channel, "Message published");
         Ok(())
     }
 
-    async fn subscribe<F>(&self, channel: &str, handler: F) -> Result<(), PubSubError>
+    async fn subscribe<F>(
+        &self,
+        channel: &str,
+        handler: F,
+    ) -> Result<SubscriptionHandle, PubSubError>
     where
         F: Fn(PubSubMessage) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static,
     {
-        let mut channels = self.channels.write().await;
-
-        // Create channel if it doesn't exist
-        let sender = channels
-            .entry(channel.to_string())
-            .or_insert_with(|| broadcast::channel(self.buffer_size).0);
-
-        let mut receiver = sender.subscribe();
+        let queue = Arc::new(SubscriberQueue::new(self.config.buffer_size));
         let channel_name = channel.to_string();
-        let handler = Arc::new(handler);
+        let counters = self.counters.clone();
+        let closer = CloseOnDrop(queue.clone());
 
-        tokio::spawn(async move {
+        let task = tokio::spawn(async move {
             tracing::info!(channel = %channel_name, "Subscribed to channel");
 
-            loop {
-                match receiver.recv().await {
-                    Ok(payload) => {
-                        let msg = PubSubMessage {
-                            channel: channel_name.clone(),
-                            payload,
-                        };
-                        handler(msg).await;
-                    }
-                    Err(broadcast::error::RecvError::Lagged(count)) => {
-                        tracing::warn!(
-                            channel = %channel_name,
-                            lagged = count,
-                            "Subscriber lagged behind"
-                        );
-                    }
-                    Err(broadcast::error::RecvError::Closed) => {
-                        tracing::info!(channel = %channel_name, "Channel closed");
-                        break;
-                    }
-                }
+            while let Some(payload) = closer.0.pop().await {
+                let msg = PubSubMessage {
+                    channel: channel_name.clone(),
+                    payload,
+                };
+                counters.deliver(handler(msg)).await;
             }
+            tracing::info!(channel = %channel_name, "Subscription closed");
         });
 
-        Ok(())
+        let mut channels = self.channels.write().await;
+        let handle = self.subscriptions.register(channel, task);
+        channels
+            .entry(channel.to_string())
+            .or_default()
+            .push(Subscriber {
+                id: handle.id(),
+                queue,
+            });
+        Ok(handle)
     }
 
     async fn unsubscribe(&self, channel: &str) -> Result<(), PubSubError> {
-        let mut channels = self.channels.write().await;
-        channels.remove(channel);
+        let subscribers = self.channels.write().await.remove(channel);
+        for subscriber in subscribers.into_iter().flatten() {
+            subscriber.queue.close();
+        }
+        self.subscriptions.cancel_channel(channel);
         tracing::info!(channel = %channel, "Unsubscribed from channel");
         Ok(())
     }
+
+    async fn request(
+        &self,
+        channel: &str,
+        payload: &str,
+        timeout: Duration,
+    ) -> Result<String, PubSubError> {
+        let request = RpcRequest::new(channel, payload);
+        let message = serde_json::to_string(&request)
+            .map_err(|e| PubSubError::InvalidMessage(e.to_string()))?;
+
+        // Listen before publishing so a fast reply cannot be missed
+        let queue = Arc::new(SubscriberQueue::new(self.config.buffer_size));
+        self.channels.write().await.insert(
+            request.reply_to.clone(),
+            vec![Subscriber {
+                id: Uuid::new_v4(),
+                queue: queue.clone(),
+            }],
+        );
+
+        let reply = async {
+            self.publish(channel, &message).await?;
+            while let Some(payload) = queue.pop().await {
+                if let Ok(reply) = serde_json::from_str::<RpcReply>(&payload)
+                    && reply.correlation_id == request.correlation_id
+                {
+                    return Ok(reply.payload);
+                }
+            }
+            Err(PubSubError::Connection("Reply channel closed".to_string()))
+        };
+        let result = tokio::time::timeout(timeout, reply)
+            .await
+            .unwrap_or(Err(PubSubError::Timeout(timeout)));
+
+        self.channels.write().await.remove(&request.reply_to);
+        result
+    }
+
+    fn stats(&self) -> PubSubStats {
+        self.counters.snapshot(self.subscriptions.count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_gets_the_reply_or_times_out() {
+        let pubsub = Arc::new(InMemoryPubSub::default());
+        let responder = pubsub.clone();
+        let _echo = pubsub
+            .subscribe("echo", move |msg| {
+                let responder = responder.clone();
+                Box::pin(async move {
+                    let request: RpcRequest = serde_json::from_str(&msg.payload).unwrap();
+                    let answer = request.payload.to_uppercase();
+                    responder.reply(&request, &answer).await.unwrap();
+                })
+            })
+            .await
+            .unwrap();
+
+        let reply = pubsub
+            .request("echo", "hello", Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(reply, "HELLO");
+        // The temporary reply channel is gone again
+        assert_eq!(pubsub.channels.read().await.len(), 1);
+
+        let unanswered = pubsub
+            .request("nobody", "hello", Duration::from_millis(20))
+            .await;
+        assert!(matches!(unanswered, Err(PubSubError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_deliveries_and_lag() {
+        let pubsub = InMemoryPubSub::new(1);
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        let handler_gate = gate.clone();
+        let _events = pubsub
+            .subscribe("events", move |_| {
+                let gate = handler_gate.clone();
+                Box::pin(async move {
+                    gate.acquire().await.unwrap().forget();
+                })
+            })
+            .await
+            .unwrap();
+
+        // The subscriber is stuck in its handler while the buffer overflows
+        for i in 0..4 {
+            pubsub.publish("events", &i.to_string()).await.unwrap();
+        }
+        gate.add_permits(4);
+
+        let stats = tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                let stats = pubsub.stats();
+                if stats.delivered + stats.lagged == 4 {
+                    return stats;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(stats.published, 4);
+        assert_eq!(stats.subscriptions, 1);
+        assert!(stats.lagged >= 2);
+        assert!(stats.mean_handler_time().is_some());
+
+        pubsub.unsubscribe("events").await.unwrap();
+        assert_eq!(pubsub.stats().subscriptions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_handlers_on_a_channel_are_cancelled_individually() {
+        let pubsub = InMemoryPubSub::default();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut handles = Vec::new();
+        for name in ["first", "second"] {
+            let tx = tx.clone();
+            let handle = pubsub
+                .subscribe("events", move |msg| {
+                    let tx = tx.clone();
+                    Box::pin(async move {
+                        tx.send((name, msg.payload)).unwrap();
+                    })
+                })
+                .await
+                .unwrap();
+            handles.push(handle);
+        }
+        assert_eq!(pubsub.stats().subscriptions, 2);
+
+        pubsub.publish("events", "a").await.unwrap();
+        let mut received = vec![rx.recv().await.unwrap(), rx.recv().await.unwrap()];
+        received.sort();
+        assert_eq!(received, [("first", "a".into()), ("second", "a".into())]);
+
+        // Cancelling one handle leaves the other subscribed
+        handles.remove(0).cancel();
+        assert_eq!(pubsub.stats().subscriptions, 1);
+        pubsub.publish("events", "b").await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), ("second", "b".into()));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_policy_cuts_off_a_stuck_subscriber() {
+        let pubsub = InMemoryPubSub::with_config(InMemoryPubSubConfig {
+            buffer_size: 1,
+            channel_overflow: HashMap::from([("events".to_string(), OverflowPolicy::Disconnect)]),
+            ..InMemoryPubSubConfig::default()
+        });
+        assert_eq!(
+            pubsub.config().overflow("other"),
+            OverflowPolicy::DropOldest
+        );
+
+        let _stuck = pubsub
+            .subscribe("events", |_| Box::pin(std::future::pending()))
+            .await
+            .unwrap();
+        // One message is in the handler, one queued; the third overflows
+        for i in 0..3 {
+            pubsub.publish("events", &i.to_string()).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let stats = pubsub.stats();
+        assert_eq!(stats.disconnected, 1);
+        assert_eq!(stats.lagged, 1);
+        assert_eq!(stats.subscriptions, 0);
+        assert!(pubsub.channels.read().await.is_empty());
+    }
+
+    #[test]
+    fn test_overflow_policies_parse() {
+        assert_eq!("drop-newest".parse(), Ok(OverflowPolicy::DropNewest));
+        assert_eq!(
+            "block:250".parse(),
+            Ok(OverflowPolicy::Block(Duration::from_millis(250)))
+        );
+        assert!("block".parse::<OverflowPolicy>().is_err());
+    }
 }
@@ -1,10 +1,20 @@
 //! Pub/Sub implementations.
 
 mod memory;
+mod queue;
+mod stats;
+mod subscriptions;
+mod typed;
 
-pub use memory::InMemoryPubSub;
+pub use memory::{InMemoryPubSub, InMemoryPubSubConfig, OverflowPolicy};
+pub use typed::TypedPubSub;
 
 #[cfg(feature = "redis")]
 mod redis;
 #[cfg(feature = "redis")]
-pub use self::redis::RedisPubSub;
+pub use self::redis::{RedisPubSub, RedisPubSubConfig};
+
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "kafka")]
+pub use self::kafka::{KafkaConfig, KafkaPubSub, OffsetCommit};
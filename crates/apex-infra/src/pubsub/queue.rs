@@ -0,0 +1,179 @@
+//! Bounded per-subscriber queue behind the in-memory pub/sub.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use super::memory::OverflowPolicy;
+
+/// What became of a message offered to a subscriber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Offer {
+    Queued,
+    /// Queued after dropping the oldest message.
+    QueuedDroppingOldest,
+    /// The queue stayed full; the message was dropped.
+    Dropped,
+    /// The queue is full and the subscriber is to be disconnected.
+    Overflowed,
+    /// The subscriber is gone.
+    Closed,
+}
+
+/// Messages waiting for one subscriber's handler.
+pub(crate) struct SubscriberQueue {
+    messages: Mutex<VecDeque<String>>,
+    capacity: usize,
+    /// Wakes the subscriber when a message arrives or the queue closes.
+    ready: Notify,
+    /// Wakes publishers blocked on a full queue.
+    space: Notify,
+    closed: AtomicBool,
+}
+
+impl SubscriberQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            messages: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            ready: Notify::new(),
+            space: Notify::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Queue `message`, applying `policy` if the queue is full.
+    pub async fn offer(&self, message: String, policy: OverflowPolicy) -> Offer {
+        let deadline = match policy {
+            OverflowPolicy::Block(timeout) => Some(Instant::now() + timeout),
+            _ => None,
+        };
+
+        loop {
+            if self.is_closed() {
+                return Offer::Closed;
+            }
+
+            // Register for space before checking, so a pop in between is not missed
+            let space = self.space.notified();
+            {
+                let mut messages = self.messages.lock().unwrap();
+                let mut offer = Offer::Queued;
+                if messages.len() >= self.capacity {
+                    match policy {
+                        OverflowPolicy::DropOldest => {
+                            messages.pop_front();
+                            offer = Offer::QueuedDroppingOldest;
+                        }
+                        OverflowPolicy::DropNewest => return Offer::Dropped,
+                        OverflowPolicy::Disconnect => return Offer::Overflowed,
+                        OverflowPolicy::Block(_) => offer = Offer::Dropped,
+                    }
+                }
+                if offer != Offer::Dropped {
+                    messages.push_back(message);
+                    drop(messages);
+                    self.ready.notify_one();
+                    return offer;
+                }
+            }
+
+            // Full under `Block`: wait for the subscriber to make room
+            let Some(deadline) = deadline else {
+                return Offer::Dropped;
+            };
+            if tokio::time::timeout_at(deadline, space).await.is_err() {
+                return Offer::Dropped;
+            }
+        }
+    }
+
+    /// Wait for the next message; `None` once the queue is closed.
+    pub async fn pop(&self) -> Option<String> {
+        loop {
+            if self.is_closed() {
+                return None;
+            }
+            if let Some(message) = self.messages.lock().unwrap().pop_front() {
+                self.space.notify_waiters();
+                return Some(message);
+            }
+            // Single consumer: a notification sent since the check is kept
+            // as a permit, so none is lost
+            self.ready.notified().await;
+        }
+    }
+
+    /// Stop the queue: pending messages are discarded and blocked
+    /// publishers give up.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.messages.lock().unwrap().clear();
+        self.ready.notify_one();
+        self.space.notify_waiters();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    async fn fill(queue: &SubscriberQueue, policy: OverflowPolicy) {
+        for i in 0..2 {
+            assert_eq!(queue.offer(i.to_string(), policy).await, Offer::Queued);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_follows_the_overflow_policy() {
+        let queue = SubscriberQueue::new(2);
+        fill(&queue, OverflowPolicy::DropOldest).await;
+        assert_eq!(
+            queue.offer("2".into(), OverflowPolicy::DropOldest).await,
+            Offer::QueuedDroppingOldest
+        );
+        assert_eq!(queue.pop().await.as_deref(), Some("1"));
+        assert_eq!(queue.pop().await.as_deref(), Some("2"));
+
+        let queue = SubscriberQueue::new(2);
+        fill(&queue, OverflowPolicy::DropNewest).await;
+        assert_eq!(
+            queue.offer("2".into(), OverflowPolicy::DropNewest).await,
+            Offer::Dropped
+        );
+        assert_eq!(
+            queue.offer("2".into(), OverflowPolicy::Disconnect).await,
+            Offer::Overflowed
+        );
+        assert_eq!(queue.pop().await.as_deref(), Some("0"));
+    }
+
+    #[tokio::test]
+    async fn test_blocked_offer_waits_for_space_or_times_out() {
+        let queue = Arc::new(SubscriberQueue::new(2));
+        let policy = OverflowPolicy::Block(Duration::from_millis(20));
+        fill(&queue, policy).await;
+        assert_eq!(queue.offer("2".into(), policy).await, Offer::Dropped);
+
+        let consumer = queue.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            consumer.pop().await;
+        });
+        let policy = OverflowPolicy::Block(Duration::from_secs(1));
+        assert_eq!(queue.offer("2".into(), policy).await, Offer::Queued);
+
+        queue.close();
+        assert_eq!(queue.pop().await, None);
+        assert_eq!(queue.offer("3".into(), policy).await, Offer::Closed);
+    }
+}
@@ -1,54 +1,293 @@
 //! Redis PubSub implementation.
+//!
+//! Plain channels use Redis pub/sub: fast, but a message is gone when no
+//! subscriber is listening. Channels listed as durable are backed by a Redis
+//! Stream read through a consumer group instead. Entries are acknowledged
+//! once the handler has finished; ones left unacknowledged (the handler
+//! panicked or the instance died) are redelivered after `claim_idle`, so
+//! delivery is at-least-once and handlers must be idempotent.
 
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
+use apex_core::ports::{
+    PubSub, PubSubError, PubSubMessage, PubSubStats, RpcReply, RpcRequest, SubscriptionHandle,
+};
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use redis::aio::ConnectionManager;
+use redis::streams::{
+    StreamAutoClaimOptions, StreamAutoClaimReply, StreamId, StreamMaxlen, StreamPendingCountReply,
+    StreamReadOptions, StreamReadReply,
+};
 use redis::{AsyncCommands, Client};
-use tokio::sync::RwLock;
 
-use apex_core::ports::{PubSub, PubSubError, PubSubMessage};
+use super::stats::PubSubCounters;
+use super::subscriptions::Subscriptions;
 
 use crate::cache::RedisConfig;
+use crate::env;
+
+/// Stream entry field holding the message.
+const PAYLOAD_FIELD: &str = "payload";
+
+/// Redis PubSub configuration.
+#[derive(Debug, Clone)]
+pub struct RedisPubSubConfig {
+    /// Redis connection config
+    pub redis: RedisConfig,
+    /// Channels delivered at-least-once through a stream
+    pub durable_channels: HashSet<String>,
+    /// Consumer group durable subscriptions join. Instances sharing a group
+    /// split a channel's messages between them; give each instance its own
+    /// (stable) group to have all of them see every message.
+    pub group: String,
+    /// Consumer name, unique per instance
+    pub consumer: String,
+    /// Timeout for blocking reads (milliseconds)
+    pub block_timeout_ms: usize,
+    /// Milliseconds a message may go unacknowledged before it is redelivered
+    pub claim_idle_ms: u64,
+    /// Deliveries after which an unacknowledged message is dropped
+    pub max_deliveries: usize,
+    /// Approximate number of messages kept per durable channel
+    pub max_len: usize,
+}
+
+impl Default for RedisPubSubConfig {
+    fn default() -> Self {
+        Self {
+            redis: RedisConfig::default(),
+            durable_channels: HashSet::new(),
+            group: "pubsub".to_string(),
+            consumer: uuid::Uuid::new_v4().to_string(),
+            block_timeout_ms: 5000,
+            claim_idle_ms: 30_000,
+            max_deliveries: 5,
+            max_len: 10_000,
+        }
+    }
+}
+
+impl RedisPubSubConfig {
+    /// Load configuration from environment variables.
+    ///
+    /// `PUBSUB_DURABLE_CHANNELS` is a comma-separated list of channel names.
+    pub fn from_env() -> Self {
+        Self {
+            redis: RedisConfig::from_env(),
+            durable_channels: env::PUBSUB_DURABLE_CHANNELS.list().into_iter().collect(),
+            group: env::PUBSUB_GROUP.string(),
+            consumer: env::PUBSUB_CONSUMER
+                .raw()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            block_timeout_ms: env::PUBSUB_BLOCK_TIMEOUT_MS.get(),
+            claim_idle_ms: env::PUBSUB_CLAIM_IDLE_MS.get(),
+            max_deliveries: env::PUBSUB_MAX_DELIVERIES.get(),
+            max_len: env::PUBSUB_STREAM_MAX_LEN.get(),
+        }
+    }
+
+    pub fn is_durable(&self, channel: &str) -> bool {
+        self.durable_channels.contains(channel)
+    }
+}
 
 /// Redis-backed PubSub implementation.
 pub struct RedisPubSub {
     conn: ConnectionManager,
     client: Client,
-    subscriptions: Arc<RwLock<HashMap<String, tokio::task::JoinHandle<()>>>>,
-    #[allow(dead_code)]
-    config: RedisConfig,
+    subscriptions: Subscriptions,
+    config: RedisPubSubConfig,
+    counters: Arc<PubSubCounters>,
 }
 
 impl RedisPubSub {
-    pub async fn new(config: RedisConfig) -> Result<Self, PubSubError> {
-        let client = Client::open(config.url.as_str())
+    pub async fn new(config: RedisPubSubConfig) -> Result<Self, PubSubError> {
+        let client = Client::open(config.redis.url.as_str())
             .map_err(|e| PubSubError::Connection(e.to_string()))?;
 
         // Use timeout to prevent hanging if Redis is unreachable
         let conn_manager_fut = ConnectionManager::new(client.clone());
-        let conn = tokio::time::timeout(config.connect_timeout, conn_manager_fut)
+        let conn = tokio::time::timeout(config.redis.connect_timeout, conn_manager_fut)
             .await
             .map_err(|_| PubSubError::Connection("Connection timed out".to_string()))?
             .map_err(|e| PubSubError::Connection(e.to_string()))?;
 
-        tracing::info!(url = %config.url, "Connected to Redis PubSub");
+        tracing::info!(
+            url = %config.redis.url,
+            durable_channels = config.durable_channels.len(),
+            "Connected to Redis PubSub"
+        );
 
         Ok(Self {
             conn,
             client,
-            subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            subscriptions: Subscriptions::default(),
             config,
+            counters: Arc::new(PubSubCounters::default()),
         })
     }
 
     /// Create from environment configuration.
     pub async fn from_env() -> Result<Self, PubSubError> {
-        Self::new(RedisConfig::from_env()).await
+        Self::new(RedisPubSubConfig::from_env()).await
+    }
+
+    pub fn config(&self) -> &RedisPubSubConfig {
+        &self.config
+    }
+
+    /// Read a durable channel's stream through the consumer group until
+    /// unsubscribed. Several handlers on one channel are consumers in the
+    /// same group, so they share its messages rather than each seeing all.
+    async fn subscribe_durable<F>(
+        &self,
+        channel: &str,
+        handler: F,
+    ) -> Result<SubscriptionHandle, PubSubError>
+    where
+        F: Fn(PubSubMessage) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static,
+    {
+        let stream = stream_key(channel);
+        let config = self.config.clone();
+
+        // Start the group at the beginning of the stream so messages
+        // published before the first subscriber came up are not skipped
+        let created: redis::RedisResult<()> = self
+            .conn
+            .clone()
+            .xgroup_create_mkstream(&stream, &config.group, "0")
+            .await;
+        if let Err(e) = created
+            && e.code() != Some("BUSYGROUP")
+        {
+            return Err(PubSubError::SubscribeError(e.to_string()));
+        }
+
+        // Blocking reads get their own connection so publishes don't queue
+        // behind them
+        let mut conn = ConnectionManager::new(self.client.clone())
+            .await
+            .map_err(|e| PubSubError::Connection(e.to_string()))?;
+        let channel_name = channel.to_string();
+        let counters = self.counters.clone();
+
+        let handle = tokio::spawn(async move {
+            tracing::debug!(channel = %channel_name, group = %config.group, "Subscribed to Redis stream");
+
+            let mut claim_cursor = "0-0".to_string();
+            loop {
+                let entry = match next_entry(
+                    &mut conn,
+                    &stream,
+                    &config,
+                    &counters,
+                    &mut claim_cursor,
+                )
+                .await
+                {
+                    Ok(Some(entry)) => entry,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        tracing::error!(channel = %channel_name, error = %e, "Redis stream read error");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                };
+
+                let Some(payload) = entry.get::<String>(PAYLOAD_FIELD) else {
+                    tracing::warn!(entry_id = %entry.id, "Stream entry has no payload");
+                    ack(&mut conn, &stream, &config.group, &entry.id).await;
+                    continue;
+                };
+
+                let msg = PubSubMessage {
+                    channel: channel_name.clone(),
+                    payload,
+                };
+                // A panicking handler leaves the message unacknowledged, to
+                // be redelivered once it has been idle for `claim_idle_ms`
+                let handled = AssertUnwindSafe(handler(msg)).catch_unwind();
+                match counters.deliver(handled).await {
+                    Ok(()) => ack(&mut conn, &stream, &config.group, &entry.id).await,
+                    Err(_) => tracing::error!(
+                        channel = %channel_name,
+                        entry_id = %entry.id,
+                        "Handler panicked; message will be redelivered"
+                    ),
+                }
+            }
+        });
+
+        Ok(self.subscriptions.register(channel, handle))
+    }
+}
+
+fn stream_key(channel: &str) -> String {
+    format!("pubsub:{}:stream", channel)
+}
+
+/// Next entry for the consumer: an unacknowledged entry idle for at least
+/// `claim_idle_ms` if there is one, otherwise a new entry. Entries delivered
+/// `max_deliveries` times already are dropped instead of handed out again.
+async fn next_entry(
+    conn: &mut ConnectionManager,
+    stream: &str,
+    config: &RedisPubSubConfig,
+    counters: &PubSubCounters,
+    claim_cursor: &mut String,
+) -> redis::RedisResult<Option<StreamId>> {
+    let claimed: StreamAutoClaimReply = conn
+        .xautoclaim_options(
+            stream,
+            &config.group,
+            &config.consumer,
+            config.claim_idle_ms,
+            claim_cursor.as_str(),
+            StreamAutoClaimOptions::default().count(1),
+        )
+        .await?;
+    *claim_cursor = claimed.next_stream_id;
+    if let Some(entry) = claimed.claimed.into_iter().next() {
+        let pending: StreamPendingCountReply = conn
+            .xpending_count(stream, &config.group, &entry.id, &entry.id, 1)
+            .await?;
+        let deliveries = pending.ids.first().map_or(0, |p| p.times_delivered);
+        if deliveries > config.max_deliveries {
+            tracing::error!(
+                stream = %stream,
+                entry_id = %entry.id,
+                deliveries = deliveries,
+                "Dropping message that was never acknowledged"
+            );
+            ack(conn, stream, &config.group, &entry.id).await;
+            return Ok(None);
+        }
+        tracing::warn!(entry_id = %entry.id, deliveries = deliveries, "Redelivering message");
+        counters.redelivered();
+        return Ok(Some(entry));
+    }
+
+    let options = StreamReadOptions::default()
+        .group(&config.group, &config.consumer)
+        .count(1)
+        .block(config.block_timeout_ms);
+    let reply: Option<StreamReadReply> = conn.xread_options(&[stream], &[">"], &options).await?;
+    Ok(reply
+        .and_then(|reply| reply.keys.into_iter().next())
+        .and_then(|key| key.ids.into_iter().next()))
+}
+
+/// Acknowledge a handled entry. It stays in the stream for other groups
+/// until trimmed.
+async fn ack(conn: &mut ConnectionManager, stream: &str, group: &str, entry_id: &str) {
+    if let Err(e) = conn.xack::<_, _, _, ()>(stream, group, &[entry_id]).await {
+        tracing::warn!(error = %e, entry_id = %entry_id, "Failed to acknowledge message");
     }
 }
 
@@ -56,19 +295,40 @@ impl RedisPubSub {
 impl PubSub for RedisPubSub {
     async fn publish(&self, channel: &str, message: &str) -> Result<(), PubSubError> {
         let mut conn = self.conn.clone();
+        self.counters.published();
+        if self.config.is_durable(channel) {
+            conn.xadd_maxlen::<_, _, _, _, ()>(
+                stream_key(channel),
+                StreamMaxlen::Approx(self.config.max_len),
+                "*",
+                &[(PAYLOAD_FIELD, message)],
+            )
+            .await
+            .map_err(|e| PubSubError::PublishError(e.to_string()))?;
+            return Ok(());
+        }
         conn.publish::<_, _, ()>(channel, message)
             .await
             .map_err(|e| PubSubError::PublishError(e.to_string()))?;
         Ok(())
     }
 
-    async fn subscribe<F>(&self, channel: &str, handler: F) -> Result<(), PubSubError>
+    async fn subscribe<F>(
+        &self,
+        channel: &str,
+        handler: F,
+    ) -> Result<SubscriptionHandle, PubSubError>
     where
         F: Fn(PubSubMessage) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static,
     {
+        if self.config.is_durable(channel) {
+            return self.subscribe_durable(channel, handler).await;
+        }
+
         let client = self.client.clone();
         let channel_name = channel.to_string();
         let handler = Arc::new(handler);
+        let counters = self.counters.clone();
 
         let handle = tokio::spawn(async move {
             let conn = match client.get_async_pubsub().await {
@@ -99,41 +359,89 @@ impl PubSub for RedisPubSub {
 
                 let channel: String = msg.get_channel_name().to_string();
                 let pubsub_msg = PubSubMessage { channel, payload };
-                handler(pubsub_msg).await;
+                counters.deliver(handler(pubsub_msg)).await;
             }
 
             tracing::info!(channel = %channel_name, "PubSub connection closed");
         });
 
-        self.subscriptions
-            .write()
-            .await
-            .insert(channel.to_string(), handle);
-
-        Ok(())
+        Ok(self.subscriptions.register(channel, handle))
     }
 
     async fn unsubscribe(&self, channel: &str) -> Result<(), PubSubError> {
-        if let Some(handle) = self.subscriptions.write().await.remove(channel) {
-            handle.abort();
+        if self.subscriptions.cancel_channel(channel) > 0 {
             tracing::debug!(channel = %channel, "Unsubscribed from Redis channel");
         }
         Ok(())
     }
+
+    async fn request(
+        &self,
+        channel: &str,
+        payload: &str,
+        timeout: Duration,
+    ) -> Result<String, PubSubError> {
+        let request = RpcRequest::new(channel, payload);
+        let message = serde_json::to_string(&request)
+            .map_err(|e| PubSubError::InvalidMessage(e.to_string()))?;
+
+        // Subscribe to the reply channel on its own connection before
+        // publishing; dropping the connection unsubscribes again.
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| PubSubError::Connection(e.to_string()))?;
+        pubsub
+            .subscribe(&request.reply_to)
+            .await
+            .map_err(|e| PubSubError::SubscribeError(e.to_string()))?;
+
+        self.publish(channel, &message).await?;
+
+        let mut stream = pubsub.on_message();
+        let reply = async {
+            while let Some(msg) = stream.next().await {
+                if let Ok(payload) = msg.get_payload::<String>()
+                    && let Ok(reply) = serde_json::from_str::<RpcReply>(&payload)
+                    && reply.correlation_id == request.correlation_id
+                {
+                    return Ok(reply.payload);
+                }
+            }
+            Err(PubSubError::Connection(
+                "PubSub connection closed".to_string(),
+            ))
+        };
+        tokio::time::timeout(timeout, reply)
+            .await
+            .unwrap_or(Err(PubSubError::Timeout(timeout)))
+    }
+
+    fn stats(&self) -> PubSubStats {
+        self.counters.snapshot(self.subscriptions.count())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
     use tokio::sync::mpsc;
 
     async fn get_test_pubsub() -> Option<RedisPubSub> {
-        let config = RedisConfig {
-            url: std::env::var("REDIS_URL")
-                .unwrap_or_else(|_| "redis://localhost:6389".to_string()),
-            connect_timeout: Duration::from_secs(1),
-            fallback_to_memory: false,
+        let config = RedisPubSubConfig {
+            redis: RedisConfig {
+                url: env::REDIS_URL
+                    .raw()
+                    .unwrap_or_else(|| "redis://localhost:6389".to_string()),
+                connect_timeout: Duration::from_secs(1),
+                fallback_to_memory: false,
+            },
+            durable_channels: HashSet::from(["test_durable_channel".to_string()]),
+            group: "test_pubsub".to_string(),
+            block_timeout_ms: 100,
+            claim_idle_ms: 200,
+            ..RedisPubSubConfig::default()
         };
 
         RedisPubSub::new(config).await.ok()
@@ -150,7 +458,7 @@ mod tests {
         let message = "test_message";
         let (tx, mut rx) = mpsc::channel(1);
 
-        pubsub
+        let _subscription = pubsub
             .subscribe(channel, move |msg| {
                 let tx = tx.clone();
                 Box::pin(async move {
@@ -172,4 +480,74 @@ mod tests {
 
         pubsub.unsubscribe(channel).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_redis_request_reply() {
+        let pubsub = match get_test_pubsub().await {
+            Some(p) => Arc::new(p),
+            None => return,
+        };
+
+        let channel = "test_rpc_channel";
+        let responder = pubsub.clone();
+        let _subscription = pubsub
+            .subscribe(channel, move |msg| {
+                let responder = responder.clone();
+                Box::pin(async move {
+                    let request: RpcRequest = serde_json::from_str(&msg.payload).unwrap();
+                    responder.reply(&request, "pong").await.unwrap();
+                })
+            })
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let reply = pubsub
+            .request(channel, "ping", Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(reply, "pong");
+
+        pubsub.unsubscribe(channel).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_durable_channel_keeps_and_redelivers_messages() {
+        let pubsub = match get_test_pubsub().await {
+            Some(p) => p,
+            None => return,
+        };
+        let channel = "test_durable_channel";
+        let mut conn = pubsub.conn.clone();
+        let _: redis::RedisResult<()> = conn.del(stream_key(channel)).await;
+
+        // Published while nobody is subscribed
+        pubsub.publish(channel, "kept").await.unwrap();
+
+        // The first delivery panics and is never acknowledged
+        let (tx, mut rx) = mpsc::channel(4);
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let _subscription = pubsub
+            .subscribe(channel, move |msg| {
+                let tx = tx.clone();
+                let attempt = attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Box::pin(async move {
+                    if attempt == 0 {
+                        panic!("handler failed");
+                    }
+                    tx.send(msg.payload).await.unwrap();
+                })
+            })
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(3), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(received.unwrap(), "kept");
+
+        pubsub.unsubscribe(channel).await.unwrap();
+        let _: redis::RedisResult<()> = conn.del(stream_key(channel)).await;
+    }
 }
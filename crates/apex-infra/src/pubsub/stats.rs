@@ -0,0 +1,62 @@
+//! Counters shared by the pub/sub backends.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use apex_core::ports::PubSubStats;
+
+/// Lock-free counters behind [`PubSubStats`].
+#[derive(Default)]
+pub(crate) struct PubSubCounters {
+    published: AtomicU64,
+    delivered: AtomicU64,
+    lagged: AtomicU64,
+    redelivered: AtomicU64,
+    disconnected: AtomicU64,
+    handler_nanos: AtomicU64,
+    max_handler_nanos: AtomicU64,
+}
+
+impl PubSubCounters {
+    pub fn published(&self) {
+        self.published.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn lagged(&self, count: u64) {
+        self.lagged.fetch_add(count, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "redis")]
+    pub fn redelivered(&self) {
+        self.redelivered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn disconnected(&self) {
+        self.disconnected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Run a handler, counting the delivery and how long it took.
+    pub async fn deliver<F: Future>(&self, handler: F) -> F::Output {
+        let started = Instant::now();
+        let output = handler.await;
+        let nanos = started.elapsed().as_nanos().min(u64::MAX as u128) as u64;
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+        self.handler_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_handler_nanos.fetch_max(nanos, Ordering::Relaxed);
+        output
+    }
+
+    pub fn snapshot(&self, subscriptions: usize) -> PubSubStats {
+        PubSubStats {
+            published: self.published.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            lagged: self.lagged.load(Ordering::Relaxed),
+            redelivered: self.redelivered.load(Ordering::Relaxed),
+            disconnected: self.disconnected.load(Ordering::Relaxed),
+            subscriptions,
+            handler_time: Duration::from_nanos(self.handler_nanos.load(Ordering::Relaxed)),
+            max_handler_time: Duration::from_nanos(self.max_handler_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Subscription bookkeeping shared by the pub/sub backends.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::task::{AbortHandle, JoinHandle};
+use uuid::Uuid;
+
+use apex_core::ports::SubscriptionHandle;
+
+type Tasks = Arc<Mutex<HashMap<String, HashMap<Uuid, AbortHandle>>>>;
+
+/// The task behind every live subscription, by channel.
+#[derive(Default)]
+pub(crate) struct Subscriptions {
+    tasks: Tasks,
+}
+
+impl Subscriptions {
+    /// Track the task running a subscription to `channel`; cancelling the
+    /// returned handle aborts it.
+    pub fn register(&self, channel: &str, task: JoinHandle<()>) -> SubscriptionHandle {
+        let id = Uuid::new_v4();
+        self.tasks
+            .lock()
+            .unwrap()
+            .entry(channel.to_string())
+            .or_default()
+            .insert(id, task.abort_handle());
+
+        let tasks = self.tasks.clone();
+        let channel_name = channel.to_string();
+        SubscriptionHandle::new(id, channel, move || {
+            if let Some(task) = remove(&tasks, &channel_name, id) {
+                task.abort();
+                tracing::debug!(channel = %channel_name, subscription = %id, "Subscription cancelled");
+            }
+        })
+    }
+
+    /// Abort one subscription, as if its handle had been cancelled.
+    pub fn cancel(&self, channel: &str, id: Uuid) -> bool {
+        let task = remove(&self.tasks, channel, id);
+        if let Some(task) = &task {
+            task.abort();
+        }
+        task.is_some()
+    }
+
+    /// Abort every subscription to `channel`, returning how many there were.
+    pub fn cancel_channel(&self, channel: &str) -> usize {
+        let tasks = self
+            .tasks
+            .lock()
+            .unwrap()
+            .remove(channel)
+            .unwrap_or_default();
+        for task in tasks.values() {
+            task.abort();
+        }
+        tasks.len()
+    }
+
+    /// Live subscriptions over all channels.
+    pub fn count(&self) -> usize {
+        self.tasks.lock().unwrap().values().map(HashMap::len).sum()
+    }
+}
+
+fn remove(tasks: &Tasks, channel: &str, id: Uuid) -> Option<AbortHandle> {
+    let mut tasks = tasks.lock().unwrap();
+    let channel_tasks = tasks.get_mut(channel)?;
+    let task = channel_tasks.remove(&id);
+    if channel_tasks.is_empty() {
+        tasks.remove(channel);
+    }
+    task
+}
@@ -0,0 +1,136 @@
+//! Typed JSON messages over any pub/sub backend.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use apex_core::ports::{Envelope, PubSub, PubSubError, SubscriptionHandle};
+
+/// Publishes and receives [`Envelope`]s instead of raw strings.
+///
+/// Every message carries its event name, timestamp and the producer that
+/// sent it, so consumers can tell events apart on a shared channel and
+/// trace where they came from.
+pub struct TypedPubSub<P> {
+    inner: Arc<P>,
+    producer: String,
+}
+
+impl<P: PubSub> TypedPubSub<P> {
+    /// Wrap `inner`, stamping published messages with `producer`.
+    pub fn new(inner: Arc<P>, producer: impl Into<String>) -> Self {
+        Self {
+            inner,
+            producer: producer.into(),
+        }
+    }
+
+    /// The raw pub/sub underneath.
+    pub fn inner(&self) -> &Arc<P> {
+        &self.inner
+    }
+
+    pub fn producer(&self) -> &str {
+        &self.producer
+    }
+
+    /// Publish `data` as a JSON envelope for `event`.
+    pub async fn publish_json<T: Serialize>(
+        &self,
+        channel: &str,
+        event: &str,
+        data: T,
+    ) -> Result<(), PubSubError> {
+        let envelope = Envelope::new(event, self.producer.as_str(), data);
+        let message = serde_json::to_string(&envelope)
+            .map_err(|e| PubSubError::InvalidMessage(e.to_string()))?;
+        self.inner.publish(channel, &message).await
+    }
+
+    /// Subscribe to envelopes carrying `T`. Messages that don't decode as
+    /// one are logged and skipped.
+    pub async fn subscribe_json<T, F, Fut>(
+        &self,
+        channel: &str,
+        handler: F,
+    ) -> Result<SubscriptionHandle, PubSubError>
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Fn(Envelope<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.inner
+            .subscribe(
+                channel,
+                move |msg| -> Pin<Box<dyn Future<Output = ()> + Send>> {
+                    match serde_json::from_str::<Envelope<T>>(&msg.payload) {
+                        Ok(envelope) => Box::pin(handler(envelope)),
+                        Err(e) => {
+                            tracing::warn!(
+                                channel = %msg.channel,
+                                error = %e,
+                                "Skipping message that is not a valid envelope"
+                            );
+                            Box::pin(async {})
+                        }
+                    }
+                },
+            )
+            .await
+    }
+
+    pub async fn unsubscribe(&self, channel: &str) -> Result<(), PubSubError> {
+        self.inner.unsubscribe(channel).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pubsub::InMemoryPubSub;
+    use serde::Deserialize;
+    use tokio::sync::mpsc;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Greeting {
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_envelopes_round_trip_and_invalid_messages_are_skipped() {
+        let raw = Arc::new(InMemoryPubSub::default());
+        let pubsub = TypedPubSub::new(raw.clone(), "api-1");
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _greetings = pubsub
+            .subscribe_json("greetings", move |envelope: Envelope<Greeting>| {
+                let tx = tx.clone();
+                async move {
+                    tx.send(envelope).unwrap();
+                }
+            })
+            .await
+            .unwrap();
+
+        raw.publish("greetings", "not json").await.unwrap();
+        pubsub
+            .publish_json(
+                "greetings",
+                "greeting.sent",
+                Greeting {
+                    name: "Ada".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let envelope = rx.recv().await.unwrap();
+        assert_eq!(envelope.event, "greeting.sent");
+        assert_eq!(envelope.producer, "api-1");
+        assert_eq!(envelope.data.name, "Ada");
+        assert!(rx.try_recv().is_err());
+    }
+}
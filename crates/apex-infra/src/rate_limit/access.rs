@@ -0,0 +1,335 @@
+//! Clients exempt from rate limiting, or refused outright, and the address
+//! they are known by.
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+use uuid::Uuid;
+
+use crate::env;
+
+/// What happens to a client's request before it reaches a limiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientAccess {
+    /// Allowlisted: never limited.
+    Allow,
+    /// Denylisted: always refused.
+    Deny,
+    /// Counted against its limit as usual.
+    Limit,
+}
+
+/// An address or a CIDR block, e.g. `10.0.0.0/8` or `::1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IpRange {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    fn parse(s: &str) -> Option<Self> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse().ok()?)),
+            None => (s.parse::<IpAddr>().ok()?, None),
+        };
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(bits);
+        (prefix <= bits).then_some(Self { addr, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        // Clients on IPv4-mapped IPv6 addresses match IPv4 ranges
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+            v4 => v4,
+        };
+        match (self.addr, ip) {
+            (IpAddr::V4(range), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(range) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(range), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(range) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Client addresses, CIDR blocks and user ids.
+#[derive(Debug, Clone, Default)]
+pub struct ClientList {
+    ips: Vec<IpRange>,
+    users: HashSet<Uuid>,
+}
+
+impl ClientList {
+    /// Parse entries of the form `10.0.0.0/8,127.0.0.1,user:<uuid>`.
+    ///
+    /// Malformed entries are skipped with a warning.
+    pub fn parse(spec: &str) -> Self {
+        let mut list = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if let Some(user) = entry.strip_prefix("user:") {
+                match user.trim().parse() {
+                    Ok(user) => {
+                        list.users.insert(user);
+                    }
+                    Err(_) => tracing::warn!(entry = %entry, "Ignoring malformed client entry"),
+                }
+            } else {
+                match IpRange::parse(entry) {
+                    Some(range) => list.ips.push(range),
+                    None => tracing::warn!(entry = %entry, "Ignoring malformed client entry"),
+                }
+            }
+        }
+        list
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ips.is_empty() && self.users.is_empty()
+    }
+
+    /// Whether the client at `ip`, signed in as `user`, is listed.
+    pub fn contains(&self, ip: Option<IpAddr>, user: Option<Uuid>) -> bool {
+        ip.is_some_and(|ip| self.ips.iter().any(|range| range.contains(ip)))
+            || user.is_some_and(|user| self.users.contains(&user))
+    }
+}
+
+/// Proxies whose forwarding headers are believed, e.g. a load balancer.
+///
+/// A request's client is the peer of its connection, unless that peer is a
+/// trusted proxy: then it is the nearest address in `Forwarded` (or, without
+/// it, `X-Forwarded-For`) not itself a trusted proxy. Anything further along
+/// was written by the client and could be anything.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    ips: Vec<IpRange>,
+}
+
+impl TrustedProxies {
+    /// Parse addresses and CIDR blocks, e.g. `10.0.0.0/8,127.0.0.1`.
+    ///
+    /// Malformed entries are skipped with a warning.
+    pub fn parse(spec: &str) -> Self {
+        let ips = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .filter_map(|entry| {
+                let range = IpRange::parse(entry);
+                if range.is_none() {
+                    tracing::warn!(entry = %entry, "Ignoring malformed trusted proxy");
+                }
+                range
+            })
+            .collect();
+        Self { ips }
+    }
+
+    /// `TRUSTED_PROXIES`, in the format of [`TrustedProxies::parse`]; none
+    /// by default, so forwarding headers are ignored.
+    pub fn from_env() -> Self {
+        Self::parse(&env::TRUSTED_PROXIES.string())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ips.is_empty()
+    }
+
+    fn trusts(&self, ip: IpAddr) -> bool {
+        self.ips.iter().any(|range| range.contains(ip))
+    }
+
+    /// The client of a request from `peer` carrying the given `Forwarded`
+    /// and `X-Forwarded-For` values (several headers joined by commas).
+    pub fn client_ip(
+        &self,
+        peer: Option<IpAddr>,
+        forwarded: Option<&str>,
+        x_forwarded_for: Option<&str>,
+    ) -> Option<IpAddr> {
+        let peer = peer?;
+        if !self.trusts(peer) {
+            return Some(peer);
+        }
+        let hops: Vec<&str> = match (forwarded, x_forwarded_for) {
+            (Some(forwarded), _) => forwarded
+                .split(',')
+                .filter_map(|element| {
+                    element.split(';').find_map(|pair| {
+                        let (name, value) = pair.split_once('=')?;
+                        name.trim()
+                            .eq_ignore_ascii_case("for")
+                            .then(|| value.trim().trim_matches('"'))
+                    })
+                })
+                .collect(),
+            (None, Some(x_forwarded_for)) => x_forwarded_for.split(',').collect(),
+            (None, None) => Vec::new(),
+        };
+
+        // Walk back from the peer while each hop vouches for the one before
+        let mut client = peer;
+        for hop in hops.iter().rev() {
+            match parse_hop(hop) {
+                Some(ip) => client = ip,
+                // e.g. `unknown` or an obfuscated `_node`
+                None => break,
+            }
+            if !self.trusts(client) {
+                break;
+            }
+        }
+        Some(client)
+    }
+}
+
+/// An address as a forwarding header gives it: `203.0.113.7`,
+/// `203.0.113.7:4711`, `2001:db8::1` or `[2001:db8::1]:4711`.
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim();
+    if let Ok(ip) = hop.parse() {
+        return Some(ip);
+    }
+    if let Some(rest) = hop.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+    hop.rsplit_once(':')?.0.parse().ok()
+}
+
+/// Allowlist and denylist consulted before a request is counted. A client
+/// on both is denied.
+#[derive(Debug, Clone, Default)]
+pub struct ClientAccessLists {
+    pub allow: ClientList,
+    pub deny: ClientList,
+}
+
+impl ClientAccessLists {
+    /// `RATE_LIMIT_ALLOWLIST` and `RATE_LIMIT_DENYLIST`, in the format of
+    /// [`ClientList::parse`]; both empty by default.
+    pub fn from_env() -> Self {
+        Self {
+            allow: ClientList::parse(&env::RATE_LIMIT_ALLOWLIST.string()),
+            deny: ClientList::parse(&env::RATE_LIMIT_DENYLIST.string()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    pub fn check(&self, ip: Option<IpAddr>, user: Option<Uuid>) -> ClientAccess {
+        if self.deny.contains(ip, user) {
+            ClientAccess::Deny
+        } else if self.allow.contains(ip, user) {
+            ClientAccess::Allow
+        } else {
+            ClientAccess::Limit
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> Option<IpAddr> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_parse_client_list() {
+        let user = Uuid::new_v4();
+        let list = ClientList::parse(&format!(
+            "10.0.0.0/8, 192.168.1.7,fd00::/8,user:{},10.0.0.0/33,user:bob,nonsense",
+            user
+        ));
+        assert_eq!((list.ips.len(), list.users.len()), (3, 1));
+
+        assert!(list.contains(ip("10.20.30.40"), None));
+        assert!(list.contains(ip("::ffff:10.1.2.3"), None));
+        assert!(list.contains(ip("192.168.1.7"), None));
+        assert!(!list.contains(ip("192.168.1.8"), None));
+        assert!(list.contains(ip("fd12::1"), None));
+        assert!(!list.contains(ip("fe80::1"), None));
+        assert!(list.contains(None, Some(user)));
+        assert!(!list.contains(None, Some(Uuid::new_v4())));
+        assert!(ClientList::parse("0.0.0.0/0").contains(ip("8.8.8.8"), None));
+    }
+
+    #[test]
+    fn test_deny_wins_over_allow() {
+        let lists = ClientAccessLists {
+            allow: ClientList::parse("10.0.0.0/8"),
+            deny: ClientList::parse("10.6.6.6"),
+        };
+        assert_eq!(lists.check(ip("10.1.1.1"), None), ClientAccess::Allow);
+        assert_eq!(lists.check(ip("10.6.6.6"), None), ClientAccess::Deny);
+        assert_eq!(lists.check(ip("8.8.8.8"), None), ClientAccess::Limit);
+        assert_eq!(lists.check(None, None), ClientAccess::Limit);
+    }
+
+    #[test]
+    fn test_forwarding_headers_count_only_from_trusted_proxies() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8, ::1, bogus");
+        assert_eq!(proxies.ips.len(), 2);
+        let lists = ClientAccessLists {
+            allow: ClientList::parse("192.168.0.0/16"),
+            deny: ClientList::parse("203.0.113.7"),
+        };
+        let check = |peer: &str, forwarded: Option<&str>, xff: Option<&str>| {
+            lists.check(proxies.client_ip(ip(peer), forwarded, xff), None)
+        };
+
+        // A client talking to the server directly can't pass as another
+        assert_eq!(
+            check("203.0.113.7", None, Some("192.168.1.1")),
+            ClientAccess::Deny
+        );
+        assert_eq!(
+            check("203.0.113.7", Some("for=192.168.1.1"), None),
+            ClientAccess::Deny
+        );
+        assert_eq!(
+            check("8.8.8.8", None, Some("192.168.1.1")),
+            ClientAccess::Limit
+        );
+
+        // Behind a proxy, the client is the hop the proxy saw, whatever the
+        // client put in front of it
+        assert_eq!(
+            check("10.0.0.1", None, Some("192.168.1.1, 203.0.113.7, 10.0.0.2")),
+            ClientAccess::Deny
+        );
+        assert_eq!(
+            check("10.0.0.1", None, Some("192.168.1.1")),
+            ClientAccess::Allow
+        );
+        assert_eq!(
+            check(
+                "::1",
+                Some(r#"for="[2001:db8::1]:4711", for=203.0.113.7:80;proto=https"#),
+                None
+            ),
+            ClientAccess::Deny
+        );
+        assert_eq!(check("10.0.0.1", None, None), ClientAccess::Limit);
+        assert_eq!(
+            proxies.client_ip(ip("10.0.0.1"), None, Some("unknown, 10.0.0.9")),
+            ip("10.0.0.9")
+        );
+        assert_eq!(
+            TrustedProxies::default().client_ip(None, None, Some("8.8.8.8")),
+            None
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Rate limiting algorithms.
+//!
+//! All of them allow `max_requests` per `window` on average; they differ in
+//! how bursts are treated:
+//!
+//! - **GCRA** spaces requests evenly, with a burst of up to `max_requests`.
+//! - **Fixed window** counts requests per window. Cheap, but a client can
+//!   spend a full budget at the end of one window and another at the start
+//!   of the next: up to 2x the limit in a short span.
+//! - **Sliding window log** remembers the time of every request in the last
+//!   window. Exact, at the cost of memory per request.
+//! - **Token bucket** refills `max_requests` tokens per window, one request
+//!   spending one token.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use apex_core::ports::RateLimitResult;
+
+use super::memory::RateLimitConfig;
+use crate::env;
+
+/// How a limiter decides whether a request fits the budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitAlgorithm {
+    #[default]
+    Gcra,
+    FixedWindow,
+    SlidingWindowLog,
+    TokenBucket,
+}
+
+impl RateLimitAlgorithm {
+    /// `RATE_LIMIT_ALGORITHM`, GCRA when unset or unknown.
+    pub fn from_env() -> Self {
+        env::RATE_LIMIT_ALGORITHM
+            .raw()
+            .and_then(|s| {
+                s.parse()
+                    .map_err(|e| tracing::warn!(error = %e, "Ignoring rate limit algorithm"))
+                    .ok()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gcra => "gcra",
+            Self::FixedWindow => "fixed-window",
+            Self::SlidingWindowLog => "sliding-log",
+            Self::TokenBucket => "token-bucket",
+        }
+    }
+}
+
+impl FromStr for RateLimitAlgorithm {
+    type Err = String;
+
+    /// `gcra`, `fixed-window`, `sliding-log` or `token-bucket`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "gcra" => Ok(Self::Gcra),
+            "fixed-window" => Ok(Self::FixedWindow),
+            "sliding-log" => Ok(Self::SlidingWindowLog),
+            "token-bucket" => Ok(Self::TokenBucket),
+            other => Err(format!("Unknown rate limit algorithm: {}", other)),
+        }
+    }
+}
+
+/// One key's budget under the algorithms the in-memory limiter implements
+/// itself (GCRA comes from `governor`).
+#[derive(Debug)]
+pub(crate) enum WindowState {
+    FixedWindow { started: Instant, count: u32 },
+    SlidingWindowLog(VecDeque<Instant>),
+    TokenBucket { tokens: f64, refilled: Instant },
+}
+
+impl WindowState {
+    /// A full budget under the configured algorithm; `None` for GCRA.
+    pub(crate) fn new(config: &RateLimitConfig, now: Instant) -> Option<Self> {
+        match config.algorithm {
+            RateLimitAlgorithm::Gcra => None,
+            RateLimitAlgorithm::FixedWindow => Some(Self::FixedWindow {
+                started: now,
+                count: 0,
+            }),
+            RateLimitAlgorithm::SlidingWindowLog => Some(Self::SlidingWindowLog(VecDeque::new())),
+            RateLimitAlgorithm::TokenBucket => Some(Self::TokenBucket {
+                tokens: f64::from(config.max_requests),
+                refilled: now,
+            }),
+        }
+    }
+
+    /// Count a request at `now` if the budget allows it.
+    pub(crate) fn check(&mut self, config: &RateLimitConfig, now: Instant) -> RateLimitResult {
+        let (max, window) = (config.max_requests, config.window);
+        let result = |allowed, remaining, reset_after| RateLimitResult {
+            allowed,
+            limit: max,
+            remaining,
+            reset_after,
+        };
+
+        match self {
+            Self::FixedWindow { started, count } => {
+                if now.duration_since(*started) >= window {
+                    (*started, *count) = (now, 0);
+                }
+                let reset_after = window.saturating_sub(now.duration_since(*started));
+                if *count < max {
+                    *count += 1;
+                    result(true, max - *count, reset_after)
+                } else {
+                    result(false, 0, reset_after)
+                }
+            }
+            Self::SlidingWindowLog(log) => {
+                while log
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) >= window)
+                {
+                    log.pop_front();
+                }
+                if (log.len() as u32) < max {
+                    log.push_back(now);
+                    result(true, max - log.len() as u32, window)
+                } else {
+                    // The oldest request leaving the window frees a slot
+                    let oldest = log.front().copied().unwrap_or(now);
+                    result(false, 0, window.saturating_sub(now.duration_since(oldest)))
+                }
+            }
+            Self::TokenBucket { tokens, refilled } => {
+                let per_token = window.as_secs_f64() / f64::from(max);
+                let elapsed = now.duration_since(*refilled).as_secs_f64();
+                *tokens = (*tokens + elapsed / per_token).min(f64::from(max));
+                *refilled = now;
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    let missing = f64::from(max) - *tokens;
+                    result(
+                        true,
+                        *tokens as u32,
+                        Duration::from_secs_f64(missing * per_token),
+                    )
+                } else {
+                    let missing = 1.0 - *tokens;
+                    result(false, 0, Duration::from_secs_f64(missing * per_token))
+                }
+            }
+        }
+    }
+
+    /// Whether the budget is full again at `now`, so the state can be dropped.
+    pub(crate) fn is_idle(&self, config: &RateLimitConfig, now: Instant) -> bool {
+        match self {
+            Self::FixedWindow { started, .. } => now.duration_since(*started) >= config.window,
+            Self::SlidingWindowLog(log) => log
+                .back()
+                .is_none_or(|t| now.duration_since(*t) >= config.window),
+            Self::TokenBucket { refilled, .. } => now.duration_since(*refilled) >= config.window,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(algorithm: RateLimitAlgorithm) -> RateLimitConfig {
+        RateLimitConfig {
+            max_requests: 10,
+            window: Duration::from_secs(60),
+            algorithm,
+        }
+    }
+
+    fn allowed(state: &mut WindowState, config: &RateLimitConfig, now: Instant, n: u32) -> u32 {
+        (0..n).filter(|_| state.check(config, now).allowed).count() as u32
+    }
+
+    #[test]
+    fn test_parse_algorithm() {
+        for algorithm in [
+            RateLimitAlgorithm::Gcra,
+            RateLimitAlgorithm::FixedWindow,
+            RateLimitAlgorithm::SlidingWindowLog,
+            RateLimitAlgorithm::TokenBucket,
+        ] {
+            assert_eq!(algorithm.as_str().parse(), Ok(algorithm));
+        }
+        assert!("leaky".parse::<RateLimitAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_fixed_window_allows_a_burst_at_the_window_edge() {
+        let config = config(RateLimitAlgorithm::FixedWindow);
+        let start = Instant::now();
+        let mut state = WindowState::new(&config, start).unwrap();
+
+        // A full budget just before the window ends and another just after
+        let edge = start + Duration::from_secs(59);
+        assert_eq!(allowed(&mut state, &config, edge, 10), 10);
+        let next = start + Duration::from_secs(60);
+        assert_eq!(allowed(&mut state, &config, next, 11), 10);
+    }
+
+    #[test]
+    fn test_sliding_log_has_no_window_edge() {
+        let config = config(RateLimitAlgorithm::SlidingWindowLog);
+        let start = Instant::now();
+        let mut state = WindowState::new(&config, start).unwrap();
+
+        let edge = start + Duration::from_secs(59);
+        assert_eq!(allowed(&mut state, &config, edge, 10), 10);
+        let next = start + Duration::from_secs(60);
+        let denied = state.check(&config, next);
+        assert!(!denied.allowed);
+        assert_eq!(denied.reset_after, Duration::from_secs(59));
+
+        // The whole budget is back one window after it was spent
+        let later = edge + Duration::from_secs(60);
+        assert_eq!(allowed(&mut state, &config, later, 11), 10);
+    }
+
+    #[test]
+    fn test_token_bucket_refills_gradually() {
+        let config = config(RateLimitAlgorithm::TokenBucket);
+        let start = Instant::now();
+        let mut state = WindowState::new(&config, start).unwrap();
+
+        assert_eq!(allowed(&mut state, &config, start, 11), 10);
+        let denied = state.check(&config, start);
+        assert_eq!(denied.reset_after, Duration::from_secs(6));
+
+        // One token every six seconds
+        let later = start + Duration::from_secs(12);
+        assert_eq!(allowed(&mut state, &config, later, 5), 2);
+        assert!(!state.is_idle(&config, later));
+        assert!(state.is_idle(&config, later + Duration::from_secs(60)));
+    }
+}
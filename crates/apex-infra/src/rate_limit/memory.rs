@@ -1,17 +1,31 @@
 //! In-memory rate limiter using governor crate.
 
+use std::collections::HashMap;
 use std::num::NonZeroU32;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use governor::clock::DefaultClock;
-use governor::state::{InMemoryState, NotKeyed};
+use governor::clock::{Clock, DefaultClock};
+use governor::middleware::StateInformationMiddleware;
+use governor::state::keyed::DefaultKeyedStateStore;
 use governor::{Quota, RateLimiter as GovernorRateLimiter};
 
-use apex_core::ports::{RateLimitError, RateLimitResult, RateLimiter};
+use apex_core::ports::{RateLimitError, RateLimitResult, RateLimitTier, RateLimiter};
 
-type DirectRateLimiter = GovernorRateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+use super::algorithm::{RateLimitAlgorithm, WindowState};
+use crate::env;
+
+/// Keys tracked before those back at a full budget are forgotten.
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Keyed limiter reporting the remaining budget of allowed requests.
+type KeyedRateLimiter = GovernorRateLimiter<
+    String,
+    DefaultKeyedStateStore<String>,
+    DefaultClock,
+    StateInformationMiddleware,
+>;
 
 /// In-memory rate limiter configuration.
 #[derive(Debug, Clone)]
@@ -20,6 +34,8 @@ pub struct RateLimitConfig {
     pub max_requests: u32,
     /// Window duration.
     pub window: Duration,
+    /// How bursts within the window are treated.
+    pub algorithm: RateLimitAlgorithm,
 }
 
 impl Default for RateLimitConfig {
@@ -27,65 +43,227 @@ impl Default for RateLimitConfig {
         Self {
             max_requests: 100,
             window: Duration::from_secs(60),
+            algorithm: RateLimitAlgorithm::default(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// `RATE_LIMIT_MAX_REQUESTS` per `RATE_LIMIT_WINDOW_SECS`, counted with
+    /// `RATE_LIMIT_ALGORITHM`.
+    pub fn from_env() -> Self {
+        Self {
+            max_requests: env::RATE_LIMIT_MAX_REQUESTS.get(),
+            window: env::RATE_LIMIT_WINDOW_SECS.secs(),
+            algorithm: RateLimitAlgorithm::from_env(),
         }
     }
 }
 
-/// In-memory rate limiter using the GCRA algorithm.
+enum Backend {
+    Gcra(KeyedRateLimiter),
+    Windows(Mutex<HashMap<String, WindowState>>),
+}
+
+/// Budgets of one configuration, per key.
+struct Budgets {
+    backend: Backend,
+    config: RateLimitConfig,
+}
+
+/// In-memory rate limiter with a budget per key, GCRA by default.
+///
+/// Tiers given their own config with [`with_tier`](Self::with_tier) count
+/// in separate budgets; the others use the base config.
 ///
 /// This is the fallback when Redis is not available.
 /// Note: Limits are per-process, not distributed across instances.
 pub struct InMemoryRateLimiter {
-    limiter: Arc<DirectRateLimiter>,
-    config: RateLimitConfig,
+    base: Budgets,
+    tiers: HashMap<RateLimitTier, Budgets>,
 }
 
 impl InMemoryRateLimiter {
     pub fn new(config: RateLimitConfig) -> Self {
-        let quota = Quota::with_period(config.window / config.max_requests)
-            .expect("Valid quota")
-            .allow_burst(NonZeroU32::new(config.max_requests).expect("Non-zero"));
+        Self {
+            base: Budgets::new(config),
+            tiers: HashMap::new(),
+        }
+    }
 
-        let limiter = Arc::new(DirectRateLimiter::direct(quota));
+    pub fn from_env() -> Self {
+        Self::new(RateLimitConfig::from_env())
+    }
 
-        Self { limiter, config }
+    /// Give callers of `tier` their own limit.
+    pub fn with_tier(mut self, tier: RateLimitTier, config: RateLimitConfig) -> Self {
+        self.tiers.insert(tier, Budgets::new(config));
+        self
     }
+}
 
-    pub fn from_env() -> Self {
-        let config = RateLimitConfig {
-            max_requests: std::env::var("RATE_LIMIT_MAX_REQUESTS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(100),
-            window: Duration::from_secs(
-                std::env::var("RATE_LIMIT_WINDOW_SECS")
-                    .ok()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(60),
-            ),
+impl Budgets {
+    fn new(config: RateLimitConfig) -> Self {
+        let backend = match config.algorithm {
+            RateLimitAlgorithm::Gcra => {
+                let quota = Quota::with_period(config.window / config.max_requests)
+                    .expect("Valid quota")
+                    .allow_burst(NonZeroU32::new(config.max_requests).expect("Non-zero"));
+                Backend::Gcra(GovernorRateLimiter::keyed(quota).with_middleware())
+            }
+            _ => Backend::Windows(Mutex::new(HashMap::new())),
         };
-        Self::new(config)
+
+        Self { backend, config }
     }
-}
 
-#[async_trait]
-impl RateLimiter for InMemoryRateLimiter {
-    async fn check(&self, _key: &str) -> Result<RateLimitResult, RateLimitError> {
-        // Note: InMemoryRateLimiter is global, not per-key
-        // For per-key limiting, use the keyed variant with DashMap
-        match self.limiter.check() {
-            Ok(_) => Ok(RateLimitResult {
-                allowed: true,
-                remaining: self.config.max_requests, // Approximate
-                reset_after: self.config.window,
-            }),
-            Err(not_until) => Ok(RateLimitResult {
+    fn check_window(
+        &self,
+        windows: &Mutex<HashMap<String, WindowState>>,
+        key: &str,
+    ) -> RateLimitResult {
+        let now = Instant::now();
+        let mut windows = windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() > MAX_TRACKED_KEYS {
+            windows.retain(|_, state| !state.is_idle(&self.config, now));
+        }
+
+        windows
+            .entry(key.to_string())
+            .or_insert_with(|| WindowState::new(&self.config, now).expect("Windowed algorithm"))
+            .check(&self.config, now)
+    }
+
+    fn check(&self, key: &str) -> RateLimitResult {
+        let limiter = match &self.backend {
+            Backend::Gcra(limiter) => limiter,
+            Backend::Windows(windows) => return self.check_window(windows, key),
+        };
+
+        if limiter.len() > MAX_TRACKED_KEYS {
+            limiter.retain_recent();
+        }
+
+        match limiter.check_key(&key.to_string()) {
+            Ok(snapshot) => {
+                let remaining = snapshot.remaining_burst_capacity();
+                // Each spent request is back after one replenish interval
+                let spent = self.config.max_requests.saturating_sub(remaining);
+                RateLimitResult {
+                    allowed: true,
+                    limit: self.config.max_requests,
+                    remaining,
+                    reset_after: snapshot.quota().replenish_interval() * spent,
+                }
+            }
+            Err(not_until) => RateLimitResult {
                 allowed: false,
+                limit: self.config.max_requests,
                 remaining: 0,
-                reset_after: not_until.wait_time_from(governor::clock::Clock::now(
-                    &governor::clock::DefaultClock::default(),
-                )),
-            }),
+                reset_after: not_until.wait_time_from(DefaultClock::default().now()),
+            },
         }
     }
 }
+
+#[async_trait]
+impl RateLimiter for InMemoryRateLimiter {
+    async fn check(&self, key: &str) -> Result<RateLimitResult, RateLimitError> {
+        Ok(self.base.check(key))
+    }
+
+    async fn check_tier(
+        &self,
+        key: &str,
+        tier: RateLimitTier,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        Ok(self.tiers.get(&tier).unwrap_or(&self.base).check(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_each_key_has_its_own_budget() {
+        let limiter = InMemoryRateLimiter::new(RateLimitConfig {
+            max_requests: 1,
+            window: Duration::from_secs(60),
+            ..Default::default()
+        });
+
+        assert!(limiter.check("user:a").await.unwrap().allowed);
+        assert!(!limiter.check("user:a").await.unwrap().allowed);
+        assert!(limiter.check("user:b").await.unwrap().allowed);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_checks_report_the_remaining_budget() {
+        let limiter = InMemoryRateLimiter::new(RateLimitConfig {
+            max_requests: 3,
+            window: Duration::from_secs(60),
+            ..Default::default()
+        });
+
+        let first = limiter.check("ip").await.unwrap();
+        assert_eq!((first.limit, first.remaining), (3, 2));
+        assert_eq!(first.reset_after, Duration::from_secs(20));
+        let second = limiter.check("ip").await.unwrap();
+        assert_eq!(second.remaining, 1);
+        assert!(second.reset_after > Duration::from_secs(39));
+    }
+
+    #[tokio::test]
+    async fn test_configured_algorithm_is_used() {
+        let limiter = InMemoryRateLimiter::new(RateLimitConfig {
+            max_requests: 2,
+            window: Duration::from_secs(60),
+            algorithm: RateLimitAlgorithm::FixedWindow,
+        });
+
+        let first = limiter.check("ip").await.unwrap();
+        assert_eq!(first.remaining, 1);
+        // A fixed window resets all at once, at its end
+        assert!(first.reset_after > Duration::from_secs(59));
+        assert!(limiter.check("ip").await.unwrap().allowed);
+        assert!(!limiter.check("ip").await.unwrap().allowed);
+        assert!(limiter.check("other").await.unwrap().allowed);
+    }
+
+    #[tokio::test]
+    async fn test_tiers_have_their_own_budgets() {
+        let config = |max_requests| RateLimitConfig {
+            max_requests,
+            window: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let limiter = InMemoryRateLimiter::new(config(1))
+            .with_tier(RateLimitTier::Premium, config(3))
+            .with_tier(RateLimitTier::Admin, config(5));
+
+        let premium = limiter
+            .check_tier("user:a", RateLimitTier::Premium)
+            .await
+            .unwrap();
+        assert_eq!((premium.limit, premium.remaining), (3, 2));
+        assert_eq!(
+            limiter
+                .check_tier("user:a", RateLimitTier::Admin)
+                .await
+                .unwrap()
+                .remaining,
+            4
+        );
+
+        // Tiers without a config share the base budget
+        assert!(
+            limiter
+                .check_tier("user:b", RateLimitTier::User)
+                .await
+                .unwrap()
+                .allowed
+        );
+        assert!(!limiter.check("user:b").await.unwrap().allowed);
+    }
+}
@@ -1,8 +1,16 @@
 //! Rate limiting implementations.
 
+mod access;
+mod algorithm;
 mod memory;
+mod policy;
 
+pub use access::{ClientAccess, ClientAccessLists, ClientList, TrustedProxies};
+pub use algorithm::RateLimitAlgorithm;
 pub use memory::{InMemoryRateLimiter, RateLimitConfig};
+pub use policy::{
+    RateLimitPolicy, RateLimitRule, parse_route_rates, parse_tier_rates, tier_rates_from_env,
+};
 
 #[cfg(feature = "redis")]
 mod redis;
@@ -0,0 +1,266 @@
+//! Per-route rate limits.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use apex_core::ports::{RateLimitTier, RateLimiter};
+
+use super::access::{ClientAccessLists, TrustedProxies};
+use super::memory::{InMemoryRateLimiter, RateLimitConfig};
+use crate::env;
+use crate::jobs::parse_window;
+
+/// A limiter and the routes it covers.
+pub struct RateLimitRule {
+    /// `/api/auth/*` covers `/api/auth` and everything below it; a pattern
+    /// without the `*` covers exactly that path.
+    pub pattern: String,
+    pub limiter: Arc<dyn RateLimiter>,
+}
+
+impl RateLimitRule {
+    fn matches(&self, path: &str) -> bool {
+        match self.pattern.strip_suffix("/*") {
+            Some(prefix) => {
+                path == prefix
+                    || path
+                        .strip_prefix(prefix)
+                        .is_some_and(|r| r.starts_with('/'))
+            }
+            None => path == self.pattern,
+        }
+    }
+}
+
+/// Which limiter a request counts against, by path.
+///
+/// The most specific matching rule wins (the longest pattern), so a request
+/// to `/api/auth/login` counts against the `/api/auth/*` limiter only, not
+/// also against `/api/*`. Paths no rule covers use the default limiter.
+/// Clients on the [`ClientAccessLists`] skip the limiters altogether.
+pub struct RateLimitPolicy {
+    rules: Vec<RateLimitRule>,
+    default: Arc<dyn RateLimiter>,
+    access: ClientAccessLists,
+    proxies: TrustedProxies,
+}
+
+impl RateLimitPolicy {
+    /// A policy sending every path to `default`.
+    pub fn new(default: Arc<dyn RateLimiter>) -> Self {
+        Self {
+            rules: Vec::new(),
+            default,
+            access: ClientAccessLists::default(),
+            proxies: TrustedProxies::default(),
+        }
+    }
+
+    /// Exempt or refuse clients before they are counted.
+    pub fn with_access(mut self, access: ClientAccessLists) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Believe the forwarding headers of requests from `proxies`.
+    pub fn with_proxies(mut self, proxies: TrustedProxies) -> Self {
+        self.proxies = proxies;
+        self
+    }
+
+    /// Limit the routes matching `pattern` with their own limiter.
+    pub fn route(mut self, pattern: impl Into<String>, limiter: Arc<dyn RateLimiter>) -> Self {
+        let pattern = pattern.into();
+        self.rules.retain(|rule| rule.pattern != pattern);
+        self.rules.push(RateLimitRule { pattern, limiter });
+        // Longest first, so the first match is the most specific one
+        self.rules
+            .sort_by_key(|rule| std::cmp::Reverse(rule.pattern.len()));
+        self
+    }
+
+    /// In-process limiters: the default from `RATE_LIMIT_MAX_REQUESTS` and
+    /// `RATE_LIMIT_WINDOW_SECS` with per-tier limits from `RATE_LIMIT_TIERS`,
+    /// per-route ones from `RATE_LIMIT_ROUTES` (e.g.
+    /// `/api/auth/*=10/m,/api/admin/*=1000/h`; defaults to
+    /// `/api/auth/*=10/m`). All of them use `RATE_LIMIT_ALGORITHM`. Clients
+    /// are exempted and refused per [`ClientAccessLists::from_env`], and
+    /// known by address per [`TrustedProxies::from_env`].
+    pub fn from_env() -> Self {
+        let default = RateLimitConfig::from_env();
+        let limiter = tier_rates_from_env(&default).into_iter().fold(
+            InMemoryRateLimiter::new(default.clone()),
+            |limiter, (tier, config)| limiter.with_tier(tier, config),
+        );
+        parse_route_rates(&env::RATE_LIMIT_ROUTES.string())
+            .into_iter()
+            .fold(
+                Self::new(Arc::new(limiter))
+                    .with_access(ClientAccessLists::from_env())
+                    .with_proxies(TrustedProxies::from_env()),
+                |policy, (pattern, config)| {
+                    let config = RateLimitConfig {
+                        algorithm: default.algorithm,
+                        ..config
+                    };
+                    policy.route(pattern, Arc::new(InMemoryRateLimiter::new(config)))
+                },
+            )
+    }
+
+    /// The pattern and limiter a request to `path` counts against; the
+    /// pattern is `None` for the default limiter.
+    pub fn limiter_for(&self, path: &str) -> (Option<&str>, &Arc<dyn RateLimiter>) {
+        match self.rules.iter().find(|rule| rule.matches(path)) {
+            Some(rule) => (Some(&rule.pattern), &rule.limiter),
+            None => (None, &self.default),
+        }
+    }
+
+    /// Clients exempt from limiting or refused outright.
+    pub fn access(&self) -> &ClientAccessLists {
+        &self.access
+    }
+
+    /// Proxies whose forwarding headers say who the client is.
+    pub fn proxies(&self) -> &TrustedProxies {
+        &self.proxies
+    }
+
+    /// Per-route rules, most specific first.
+    pub fn rules(&self) -> &[RateLimitRule] {
+        &self.rules
+    }
+}
+
+/// Parse limits of the form `/api/auth/*=10/m,/api/*=100/30s`: a number of
+/// requests per `s`, `m`, `h` or a count of those.
+///
+/// Malformed entries are skipped with a warning.
+pub fn parse_route_rates(spec: &str) -> Vec<(String, RateLimitConfig)> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.split_once('=').and_then(|(pattern, rate)| {
+                let pattern = pattern.trim();
+                let config = parse_rate(rate)?;
+                pattern
+                    .starts_with('/')
+                    .then(|| (pattern.to_string(), config))
+            });
+            if parsed.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed route rate limit");
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Per-tier limits of the default limiter from `RATE_LIMIT_TIERS`, e.g.
+/// `anonymous=30/m,user=100/m,premium=1000/m,admin=10000/m`, counted with
+/// the algorithm of `base`. Tiers not listed use `base`; none are by default.
+pub fn tier_rates_from_env(base: &RateLimitConfig) -> HashMap<RateLimitTier, RateLimitConfig> {
+    parse_tier_rates(&env::RATE_LIMIT_TIERS.string())
+        .into_iter()
+        .map(|(tier, config)| {
+            let config = RateLimitConfig {
+                algorithm: base.algorithm,
+                ..config
+            };
+            (tier, config)
+        })
+        .collect()
+}
+
+/// Parse limits of the form `anonymous=30/m,premium=1000/m`, with the
+/// rates of [`parse_route_rates`].
+///
+/// Malformed entries are skipped with a warning.
+pub fn parse_tier_rates(spec: &str) -> HashMap<RateLimitTier, RateLimitConfig> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.split_once('=').and_then(|(tier, rate)| {
+                Some((
+                    tier.trim().parse::<RateLimitTier>().ok()?,
+                    parse_rate(rate)?,
+                ))
+            });
+            if parsed.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed tier rate limit");
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// `10/m`: a number of requests, more than none, per window.
+fn parse_rate(rate: &str) -> Option<RateLimitConfig> {
+    let (count, window) = rate.trim().split_once('/')?;
+    let max_requests: u32 = count.trim().parse().ok()?;
+    let window: Duration = parse_window(window.trim())?;
+    (max_requests > 0).then(|| RateLimitConfig {
+        max_requests,
+        window,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(max_requests: u32) -> Arc<dyn RateLimiter> {
+        Arc::new(InMemoryRateLimiter::new(RateLimitConfig {
+            max_requests,
+            window: Duration::from_secs(60),
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn test_parse_route_rates() {
+        let rates = parse_route_rates("/api/auth/*=10/m, /api/* = 100/30s,api=1/s,/x=0/s,/y=1/d");
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[0].0, "/api/auth/*");
+        assert_eq!(rates[0].1.max_requests, 10);
+        assert_eq!(rates[0].1.window, Duration::from_secs(60));
+        assert_eq!(rates[1].0, "/api/*");
+        assert_eq!(rates[1].1.window, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_parse_tier_rates() {
+        let rates = parse_tier_rates("anonymous=30/m, premium = 1000/m,gold=5/s,admin=0/s");
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[&RateLimitTier::Anonymous].max_requests, 30);
+        assert_eq!(
+            rates[&RateLimitTier::Premium].window,
+            Duration::from_secs(60)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_most_specific_route_wins() {
+        let policy = RateLimitPolicy::new(limiter(100))
+            .route("/api/*", limiter(100))
+            .route("/api/auth/*", limiter(1))
+            .route("/api/health", limiter(100));
+
+        assert_eq!(policy.limiter_for("/api/auth/login").0, Some("/api/auth/*"));
+        assert_eq!(policy.limiter_for("/api/auth").0, Some("/api/auth/*"));
+        assert_eq!(policy.limiter_for("/api/authors").0, Some("/api/*"));
+        assert_eq!(policy.limiter_for("/api/health").0, Some("/api/health"));
+        assert_eq!(policy.limiter_for("/metrics").0, None);
+
+        // The auth limiter is spent after one request; the broader one is not touched
+        let (_, auth) = policy.limiter_for("/api/auth/login");
+        assert!(auth.check("ip").await.unwrap().allowed);
+        assert!(!auth.check("ip").await.unwrap().allowed);
+        let (_, api) = policy.limiter_for("/api/orgs");
+        assert!(api.check("ip").await.unwrap().allowed);
+    }
+}
@@ -1,24 +1,36 @@
-//! Redis rate limiter implementation using sliding window counter.
+//! Redis rate limiter implementation.
+//!
+//! Each algorithm is a Lua script, so a check is one atomic round trip.
+//! Scripts take the time from Redis rather than from the calling instance,
+//! so instances with drifting clocks still share consistent budgets; this
+//! needs Redis 5 or later.
 
+use std::collections::HashMap;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use redis::aio::ConnectionManager;
 use redis::{Client, Script};
+use uuid::Uuid;
 
-use apex_core::ports::{RateLimitError, RateLimitResult, RateLimiter};
+use apex_core::ports::{RateLimitError, RateLimitResult, RateLimitTier, RateLimiter};
 
+use super::algorithm::RateLimitAlgorithm;
+use super::memory::RateLimitConfig;
+use super::policy::tier_rates_from_env;
 use crate::cache::RedisConfig;
+use crate::env;
 
 /// Redis rate limiter configuration.
 #[derive(Debug, Clone)]
 pub struct RedisRateLimitConfig {
     /// Redis connection config
     pub redis: RedisConfig,
-    /// Maximum requests per window
-    pub max_requests: u32,
-    /// Window duration
-    pub window: Duration,
+    /// Requests per window and the algorithm counting them
+    pub rate: RateLimitConfig,
+    /// Requests per window of tiers with their own limit; only the count and
+    /// window are used, the algorithm is always that of `rate`
+    pub tiers: HashMap<RateLimitTier, RateLimitConfig>,
     /// Key prefix for rate limit keys
     pub key_prefix: String,
 }
@@ -27,8 +39,8 @@ impl Default for RedisRateLimitConfig {
     fn default() -> Self {
         Self {
             redis: RedisConfig::default(),
-            max_requests: 100,
-            window: Duration::from_secs(60),
+            rate: RateLimitConfig::default(),
+            tiers: HashMap::new(),
             key_prefix: "ratelimit".to_string(),
         }
     }
@@ -36,29 +48,104 @@ impl Default for RedisRateLimitConfig {
 
 impl RedisRateLimitConfig {
     pub fn from_env() -> Self {
+        let rate = RateLimitConfig::from_env();
         Self {
             redis: RedisConfig::from_env(),
-            max_requests: std::env::var("RATE_LIMIT_MAX_REQUESTS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(100),
-            window: Duration::from_secs(
-                std::env::var("RATE_LIMIT_WINDOW_SECS")
-                    .ok()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(60),
-            ),
-            key_prefix: std::env::var("RATE_LIMIT_KEY_PREFIX")
-                .unwrap_or_else(|_| "ratelimit".to_string()),
+            tiers: tier_rates_from_env(&rate),
+            rate,
+            key_prefix: env::RATE_LIMIT_KEY_PREFIX.string(),
         }
     }
 }
 
-/// Redis-backed rate limiter using sliding window counter.
+// Every script takes the key, the limit, the window in milliseconds and a
+// unique request id, and returns {allowed, remaining, reset_after_ms}.
+const NOW_MS: &str = r#"
+    local time = redis.call('TIME')
+    local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+    local key = KEYS[1]
+    local max = tonumber(ARGV[1])
+    local window = tonumber(ARGV[2])
+"#;
+
+/// Theoretical arrival time of the next request, one interval per request.
+const GCRA: &str = r#"
+    local interval = window / max
+    local tat = math.max(tonumber(redis.call('GET', key)) or now, now)
+    local next_tat = tat + interval
+    local allow_at = next_tat - window
+    if now < allow_at then
+        return {0, 0, math.ceil(allow_at - now)}
+    end
+    redis.call('SET', key, next_tat, 'PX', math.ceil(next_tat - now))
+    return {1, math.floor((window - (next_tat - now)) / interval), math.ceil(next_tat - now)}
+"#;
+
+const FIXED_WINDOW: &str = r#"
+    local current = redis.call('INCR', key)
+    if current == 1 then
+        redis.call('PEXPIRE', key, window)
+    end
+    local ttl = redis.call('PTTL', key)
+    if ttl < 0 then
+        redis.call('PEXPIRE', key, window)
+        ttl = window
+    end
+    if current <= max then
+        return {1, max - current, ttl}
+    end
+    return {0, 0, ttl}
+"#;
+
+/// A sorted set of request times, trimmed to the last window.
+const SLIDING_WINDOW_LOG: &str = r#"
+    redis.call('ZREMRANGEBYSCORE', key, '-inf', now - window)
+    local count = redis.call('ZCARD', key)
+    if count < max then
+        redis.call('ZADD', key, now, ARGV[3])
+        redis.call('PEXPIRE', key, window)
+        return {1, max - count - 1, window}
+    end
+    local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+    return {0, 0, tonumber(oldest[2]) + window - now}
+"#;
+
+/// Tokens left and when they were last refilled.
+const TOKEN_BUCKET: &str = r#"
+    local per_token = window / max
+    local state = redis.call('HMGET', key, 'tokens', 'refilled')
+    local tokens = tonumber(state[1]) or max
+    local refilled = tonumber(state[2]) or now
+    tokens = math.min(max, tokens + (now - refilled) / per_token)
+    local allowed = 0
+    local reset
+    if tokens >= 1 then
+        tokens = tokens - 1
+        allowed = 1
+        reset = (max - tokens) * per_token
+    else
+        reset = (1 - tokens) * per_token
+    end
+    redis.call('HSET', key, 'tokens', tokens, 'refilled', now)
+    redis.call('PEXPIRE', key, window)
+    return {allowed, math.floor(tokens), math.ceil(reset)}
+"#;
+
+fn script(algorithm: RateLimitAlgorithm) -> Script {
+    let body = match algorithm {
+        RateLimitAlgorithm::Gcra => GCRA,
+        RateLimitAlgorithm::FixedWindow => FIXED_WINDOW,
+        RateLimitAlgorithm::SlidingWindowLog => SLIDING_WINDOW_LOG,
+        RateLimitAlgorithm::TokenBucket => TOKEN_BUCKET,
+    };
+    Script::new(&format!("{}{}", NOW_MS, body))
+}
+
+/// Redis-backed rate limiter, shared by every instance using the same Redis.
 pub struct RedisRateLimiter {
     conn: ConnectionManager,
     config: RedisRateLimitConfig,
-    /// Lua script for atomic increment with expiry
+    /// Lua script of the configured algorithm
     script: Script,
 }
 
@@ -74,30 +161,16 @@ impl RedisRateLimiter {
             .map_err(|_| RateLimitError::Backend("Connection timed out".to_string()))?
             .map_err(|e| RateLimitError::Backend(e.to_string()))?;
 
-        // Lua script for atomic increment with TTL
-        // Returns: [current_count, ttl_remaining]
-        let script = Script::new(
-            r#"
-            local key = KEYS[1]
-            local max_requests = tonumber(ARGV[1])
-            local window_secs = tonumber(ARGV[2])
-            
-            local current = redis.call('INCR', key)
-            if current == 1 then
-                redis.call('EXPIRE', key, window_secs)
-            end
-            
-            local ttl = redis.call('TTL', key)
-            return {current, ttl}
-            "#,
+        tracing::info!(
+            url = %config.redis.url,
+            algorithm = config.rate.algorithm.as_str(),
+            "Connected to Redis rate limiter"
         );
 
-        tracing::info!(url = %config.redis.url, "Connected to Redis rate limiter");
-
         Ok(Self {
             conn,
+            script: script(config.rate.algorithm),
             config,
-            script,
         })
     }
 
@@ -109,56 +182,83 @@ impl RedisRateLimiter {
     fn make_key(&self, key: &str) -> String {
         format!("{}:{}", self.config.key_prefix, key)
     }
-}
 
-#[async_trait]
-impl RateLimiter for RedisRateLimiter {
-    async fn check(&self, key: &str) -> Result<RateLimitResult, RateLimitError> {
-        let redis_key = self.make_key(key);
+    async fn check_with(
+        &self,
+        redis_key: String,
+        rate: &RateLimitConfig,
+    ) -> Result<RateLimitResult, RateLimitError> {
         let mut conn = self.conn.clone();
-
         let result: Vec<i64> = self
             .script
             .key(&redis_key)
-            .arg(self.config.max_requests)
-            .arg(self.config.window.as_secs())
+            .arg(rate.max_requests)
+            .arg(rate.window.as_millis() as u64)
+            .arg(Uuid::new_v4().to_string())
             .invoke_async(&mut conn)
             .await
             .map_err(|e| RateLimitError::Backend(e.to_string()))?;
 
-        let current_count = result.first().copied().unwrap_or(1) as u32;
-        let ttl_secs = result.get(1).copied().unwrap_or(60).max(1) as u64;
-
-        let allowed = current_count <= self.config.max_requests;
-        let remaining = if allowed {
-            self.config.max_requests.saturating_sub(current_count)
-        } else {
-            0
-        };
+        let value = |i: usize| result.get(i).copied().unwrap_or(0).max(0) as u64;
 
         Ok(RateLimitResult {
-            allowed,
-            remaining,
-            reset_after: Duration::from_secs(ttl_secs),
+            allowed: value(0) == 1,
+            limit: rate.max_requests,
+            remaining: value(1) as u32,
+            reset_after: Duration::from_millis(value(2)),
         })
     }
 }
 
+#[async_trait]
+impl RateLimiter for RedisRateLimiter {
+    async fn check(&self, key: &str) -> Result<RateLimitResult, RateLimitError> {
+        self.check_with(self.make_key(key), &self.config.rate).await
+    }
+
+    async fn check_tier(
+        &self,
+        key: &str,
+        tier: RateLimitTier,
+    ) -> Result<RateLimitResult, RateLimitError> {
+        match self.config.tiers.get(&tier) {
+            // Keyed by tier too, so a caller changing tiers starts afresh
+            Some(rate) => {
+                let redis_key = format!("{}:{}:{}", self.config.key_prefix, tier, key);
+                self.check_with(redis_key, rate).await
+            }
+            None => self.check(key).await,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::time::Duration;
 
-    async fn get_test_ratelimiter() -> Option<RedisRateLimiter> {
+    async fn get_test_ratelimiter(algorithm: RateLimitAlgorithm) -> Option<RedisRateLimiter> {
         let config = RedisRateLimitConfig {
             redis: RedisConfig {
-                url: std::env::var("REDIS_URL")
-                    .unwrap_or_else(|_| "redis://localhost:6389".to_string()),
+                url: env::REDIS_URL
+                    .raw()
+                    .unwrap_or_else(|| "redis://localhost:6389".to_string()),
                 connect_timeout: Duration::from_secs(1),
                 fallback_to_memory: false,
             },
-            max_requests: 2,
-            window: Duration::from_secs(1),
+            rate: RateLimitConfig {
+                max_requests: 2,
+                window: Duration::from_secs(1),
+                algorithm,
+            },
+            tiers: HashMap::from([(
+                RateLimitTier::Premium,
+                RateLimitConfig {
+                    max_requests: 4,
+                    window: Duration::from_secs(1),
+                    algorithm,
+                },
+            )]),
             key_prefix: "test_ratelimit".to_string(),
         };
 
@@ -167,7 +267,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_redis_ratelimiter() {
-        let limiter = match get_test_ratelimiter().await {
+        let limiter = match get_test_ratelimiter(RateLimitAlgorithm::FixedWindow).await {
             Some(l) => l,
             None => return,
         };
@@ -195,4 +295,54 @@ mod tests {
         let res = limiter.check(key).await.unwrap();
         assert!(res.allowed);
     }
+
+    #[tokio::test]
+    async fn test_every_algorithm_enforces_the_limit() {
+        for algorithm in [
+            RateLimitAlgorithm::Gcra,
+            RateLimitAlgorithm::FixedWindow,
+            RateLimitAlgorithm::SlidingWindowLog,
+            RateLimitAlgorithm::TokenBucket,
+        ] {
+            let Some(limiter) = get_test_ratelimiter(algorithm).await else {
+                return;
+            };
+            let key = format!("test_{}_{}", algorithm.as_str(), Uuid::new_v4());
+
+            let first = limiter.check(&key).await.unwrap();
+            assert!(first.allowed, "{:?}", algorithm);
+            assert_eq!(first.remaining, 1, "{:?}", algorithm);
+            assert!(
+                limiter.check(&key).await.unwrap().allowed,
+                "{:?}",
+                algorithm
+            );
+
+            let denied = limiter.check(&key).await.unwrap();
+            assert!(!denied.allowed, "{:?}", algorithm);
+            assert!(
+                denied.reset_after <= Duration::from_secs(1),
+                "{:?}",
+                algorithm
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tiers_use_their_own_limit() {
+        let Some(limiter) = get_test_ratelimiter(RateLimitAlgorithm::FixedWindow).await else {
+            return;
+        };
+        let key = format!("test_tier_{}", Uuid::new_v4());
+
+        let premium = limiter
+            .check_tier(&key, RateLimitTier::Premium)
+            .await
+            .unwrap();
+        assert_eq!((premium.limit, premium.remaining), (4, 3));
+
+        // Tiers without a limit of their own use the base one
+        let user = limiter.check_tier(&key, RateLimitTier::User).await.unwrap();
+        assert_eq!((user.limit, user.remaining), (2, 1));
+    }
 }
@@ -0,0 +1,345 @@
+//! Scheduled reports - SQL aggregates rendered to files in storage.
+//!
+//! Reports are defined in the JSON file at `REPORTS_FILE`, e.g.
+//!
+//! ```json
+//! [{
+//!     "name": "daily_signups",
+//!     "database": "analytics",
+//!     "sql": "SELECT date_trunc('day', created_at) AS day, count(*) FROM users GROUP BY 1",
+//!     "format": "csv",
+//!     "schedule": "0 0 6 * * *"
+//! }]
+//! ```
+//!
+//! Queries run through the read-only [`SqlConsole`] against the named
+//! secondary database. Each run stores the rendered report and announces it
+//! to admins with a signed download link that expires with the announcement.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Deserialize;
+
+use apex_core::domain::{Announcement, Audience};
+use apex_core::error::{DomainError, RepoError};
+use apex_core::ports::{StorageError, StorageService};
+
+use crate::announcements::AnnouncementBoard;
+use crate::database::{
+    ConsoleRows, DatabaseConnections, SqlConsole, SqlConsoleConfig, SqlConsoleError,
+};
+use crate::env;
+use crate::storage::validate_key;
+
+/// File format of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "text/csv",
+            ReportFormat::Json => "application/json",
+        }
+    }
+
+    /// Render rows: CSV with a header line, or a JSON array of objects.
+    pub fn render(&self, rows: &ConsoleRows) -> Vec<u8> {
+        match self {
+            ReportFormat::Csv => {
+                let mut csv = csv_line(rows.columns.iter().map(|c| c.as_str().into()));
+                for row in &rows.rows {
+                    csv.push_str(&csv_line(row.iter().map(|value| match value {
+                        serde_json::Value::Null => "".into(),
+                        serde_json::Value::String(s) => s.as_str().into(),
+                        other => other.to_string().into(),
+                    })));
+                }
+                csv.into_bytes()
+            }
+            ReportFormat::Json => {
+                let objects: Vec<serde_json::Map<String, serde_json::Value>> = rows
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        rows.columns
+                            .iter()
+                            .cloned()
+                            .zip(row.iter().cloned())
+                            .collect()
+                    })
+                    .collect();
+                serde_json::to_vec(&objects).expect("JSON values serialize")
+            }
+        }
+    }
+}
+
+fn csv_line<'a>(fields: impl Iterator<Item = std::borrow::Cow<'a, str>>) -> String {
+    let mut line = fields
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push('\n');
+    line
+}
+
+/// A report and when it runs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportDefinition {
+    /// Identifies the report; also the folder its files are stored in.
+    pub name: String,
+    /// Secondary database the query runs against.
+    pub database: String,
+    /// A single SELECT or WITH query.
+    pub sql: String,
+    pub format: ReportFormat,
+    /// Six-field cron expression, e.g. `0 0 6 * * *` for 06:00 daily.
+    pub schedule: String,
+}
+
+/// Report definitions from the JSON file at `REPORTS_FILE`; none when unset.
+/// An unreadable file is logged and yields no reports, as do names that are
+/// not usable as a storage folder.
+pub fn reports_from_env() -> Vec<ReportDefinition> {
+    let Some(path) = env::REPORTS_FILE.raw() else {
+        return Vec::new();
+    };
+    let parsed = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            serde_json::from_str::<Vec<ReportDefinition>>(&json).map_err(|e| e.to_string())
+        });
+    match parsed {
+        Ok(reports) => reports
+            .into_iter()
+            .filter(|report| {
+                let valid = !report.name.contains('/') && validate_key(&report.name).is_ok();
+                if !valid {
+                    tracing::error!(report = %report.name, "Ignoring report with an invalid name");
+                }
+                valid
+            })
+            .collect(),
+        Err(e) => {
+            tracing::error!(path = %path, error = %e, "Failed to load report definitions");
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    #[error("Unknown report: {0}")]
+    UnknownReport(String),
+
+    #[error(transparent)]
+    Query(#[from] SqlConsoleError),
+
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error("Invalid report announcement: {0}")]
+    Announcement(#[from] DomainError),
+
+    #[error("Failed to notify admins: {0}")]
+    Notify(#[from] RepoError),
+}
+
+/// Where a report run was stored.
+#[derive(Debug, Clone)]
+pub struct ReportRun {
+    pub key: String,
+    pub rows: usize,
+    /// Signed download link.
+    pub url: String,
+}
+
+/// Report generator configuration.
+#[derive(Debug, Clone)]
+pub struct ReportConfig {
+    /// Most rows a report contains.
+    pub row_limit: u64,
+    pub statement_timeout: Duration,
+    /// How long download links (and the announcements carrying them) last.
+    pub link_ttl: Duration,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            row_limit: 100_000,
+            statement_timeout: Duration::from_secs(60),
+            link_ttl: Duration::from_secs(7 * 24 * 3600),
+        }
+    }
+}
+
+impl ReportConfig {
+    pub fn from_env() -> Self {
+        Self {
+            row_limit: env::REPORT_ROW_LIMIT.get(),
+            statement_timeout: env::REPORT_TIMEOUT_SECS.secs(),
+            link_ttl: Duration::from_secs(env::REPORT_LINK_TTL_HOURS.get::<u64>() * 3600),
+        }
+    }
+}
+
+/// Runs report queries and delivers their results.
+pub struct ReportGenerator {
+    reports: Vec<ReportDefinition>,
+    console: SqlConsole,
+    storage: Arc<dyn StorageService>,
+    announcements: Arc<AnnouncementBoard>,
+    link_ttl: Duration,
+}
+
+impl ReportGenerator {
+    pub fn new(
+        reports: Vec<ReportDefinition>,
+        connections: Arc<DatabaseConnections>,
+        storage: Arc<dyn StorageService>,
+        announcements: Arc<AnnouncementBoard>,
+        config: ReportConfig,
+    ) -> Self {
+        let console = SqlConsole::new(
+            connections,
+            SqlConsoleConfig {
+                enabled: true,
+                row_limit: config.row_limit,
+                statement_timeout: config.statement_timeout,
+            },
+        );
+        Self {
+            reports,
+            console,
+            storage,
+            announcements,
+            link_ttl: config.link_ttl,
+        }
+    }
+
+    pub fn reports(&self) -> &[ReportDefinition] {
+        &self.reports
+    }
+
+    /// Run a report now: query, store the file and announce it to admins.
+    pub async fn run(&self, name: &str) -> Result<ReportRun, ReportError> {
+        let report = self
+            .reports
+            .iter()
+            .find(|report| report.name == name)
+            .ok_or_else(|| ReportError::UnknownReport(name.to_string()))?;
+
+        let rows = self
+            .console
+            .query(&report.database, &report.sql, &[], None)
+            .await?;
+        if rows.truncated {
+            tracing::warn!(report = %name, "Report cut off at the row limit");
+        }
+
+        let now = Utc::now();
+        let key = format!(
+            "reports/{}/{}.{}",
+            report.name,
+            now.format("%Y-%m-%dT%H%M%SZ"),
+            report.format.extension()
+        );
+        self.storage
+            .put(
+                &key,
+                report.format.render(&rows),
+                report.format.content_type(),
+            )
+            .await?;
+        let url = self.storage.signed_url(&key, self.link_ttl)?;
+
+        let announcement = Announcement::new(
+            format!("Report ready: {}", report.name),
+            format!(
+                "{} rows, download until the link expires: {}",
+                rows.rows.len(),
+                url
+            ),
+            Audience::Role("admin".to_string()),
+            now,
+            Some(now + self.link_ttl),
+        )?;
+        self.announcements.create(announcement).await?;
+
+        Ok(ReportRun {
+            key,
+            rows: rows.rows.len(),
+            url,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> ConsoleRows {
+        ConsoleRows {
+            columns: vec!["day".to_string(), "note".to_string(), "count".to_string()],
+            rows: vec![
+                vec!["2026-01-19".into(), "plain".into(), 3.into()],
+                vec![
+                    "2026-01-20".into(),
+                    "a, \"quoted\" note".into(),
+                    serde_json::Value::Null,
+                ],
+            ],
+            truncated: false,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let csv = String::from_utf8(ReportFormat::Csv.render(&rows())).unwrap();
+        assert_eq!(
+            csv,
+            "day,note,count\n2026-01-19,plain,3\n2026-01-20,\"a, \"\"quoted\"\" note\",\n"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json: serde_json::Value =
+            serde_json::from_slice(&ReportFormat::Json.render(&rows())).unwrap();
+        assert_eq!(json[0]["count"], 3);
+        assert_eq!(json[1]["note"], "a, \"quoted\" note");
+    }
+
+    #[test]
+    fn test_parse_definitions() {
+        let reports: Vec<ReportDefinition> = serde_json::from_str(
+            r#"[{"name": "signups", "database": "analytics", "sql": "SELECT 1",
+                 "format": "json", "schedule": "0 0 6 * * *"}]"#,
+        )
+        .unwrap();
+        assert_eq!(reports[0].format, ReportFormat::Json);
+        assert_eq!(reports[0].format.content_type(), "application/json");
+    }
+}
@@ -0,0 +1,30 @@
+//! Secrets from the environment.
+
+use async_trait::async_trait;
+
+use apex_core::ports::{SecretsError, SecretsProvider};
+
+/// Reads secret `NAME` from the `NAME` environment variable, or from the
+/// file `NAME_FILE` points to (e.g. a mounted Docker or Kubernetes secret).
+/// The variable wins when both are set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvSecrets;
+
+#[async_trait]
+impl SecretsProvider for EnvSecrets {
+    async fn secret(&self, name: &str) -> Result<Option<String>, SecretsError> {
+        if let Ok(value) = std::env::var(name) {
+            return Ok(Some(value));
+        }
+        let Ok(path) = std::env::var(format!("{}_FILE", name)) else {
+            return Ok(None);
+        };
+        match tokio::fs::read_to_string(&path).await {
+            Ok(value) => Ok(Some(value.trim_end().to_string())),
+            Err(e) => Err(SecretsError::Unavailable {
+                name: name.to_string(),
+                reason: format!("{}: {}", path, e),
+            }),
+        }
+    }
+}
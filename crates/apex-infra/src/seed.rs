@@ -0,0 +1,321 @@
+//! Fixture data for development and CI.
+//!
+//! Seeders write the same users and posts on every run: ids and timestamps
+//! are fixed, so a fresh checkout, a CI job and a teammate's machine all see
+//! the same dataset, and running them again leaves it as it was. Seeding
+//! refuses to run in production.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use apex_core::domain::{Email, Post, User};
+use apex_core::error::RepoError;
+use apex_core::ports::{PostRepository, UserRepository};
+
+/// Password of every seeded user.
+pub const SEED_PASSWORD: &str = "apex-seed-password";
+
+/// Creation time of every seeded row, 2026-01-01T00:00:00Z.
+const SEED_EPOCH: i64 = 1_767_225_600;
+
+/// Seeding errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SeedError {
+    #[error("Refusing to seed a production database")]
+    Production,
+
+    #[error("Seeder '{seeder}' failed: {source}")]
+    Seeder {
+        seeder: &'static str,
+        #[source]
+        source: RepoError,
+    },
+}
+
+/// Which dataset to seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedEnvironment {
+    /// Enough data to click around in.
+    Development,
+    /// The smallest dataset that exercises every flow, for CI.
+    Test,
+}
+
+impl SeedEnvironment {
+    /// The environment `RUST_ENV` names.
+    pub fn from_env() -> Result<Self, SeedError> {
+        Self::parse(crate::env::RUST_ENV.raw().as_deref())
+    }
+
+    /// `test` or `ci` for [`Self::Test`], anything else but production for
+    /// [`Self::Development`].
+    pub fn parse(rust_env: Option<&str>) -> Result<Self, SeedError> {
+        match rust_env.map(str::to_ascii_lowercase).as_deref() {
+            Some("production" | "prod") => Err(SeedError::Production),
+            Some("test" | "ci") => Ok(Self::Test),
+            _ => Ok(Self::Development),
+        }
+    }
+
+    fn users(self) -> u32 {
+        match self {
+            Self::Development => 5,
+            Self::Test => 2,
+        }
+    }
+
+    fn posts_per_user(self) -> u32 {
+        match self {
+            Self::Development => 4,
+            Self::Test => 2,
+        }
+    }
+}
+
+/// What seeders write to, and what earlier seeders left for later ones.
+pub struct SeedContext {
+    pub environment: SeedEnvironment,
+    pub users: Arc<dyn UserRepository>,
+    pub posts: Arc<dyn PostRepository>,
+    /// Hash of [`SEED_PASSWORD`], set on every seeded user.
+    pub password_hash: String,
+    /// Users seeded so far, for later seeders to give data to.
+    pub seeded_users: Vec<User>,
+}
+
+impl SeedContext {
+    pub fn new(
+        environment: SeedEnvironment,
+        users: Arc<dyn UserRepository>,
+        posts: Arc<dyn PostRepository>,
+        password_hash: String,
+    ) -> Self {
+        Self {
+            environment,
+            users,
+            posts,
+            password_hash,
+            seeded_users: Vec::new(),
+        }
+    }
+}
+
+/// Writes one kind of fixture data.
+#[async_trait]
+pub trait Seeder: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Write this seeder's rows, returning how many were written. Running it
+    /// again must leave the same data behind.
+    async fn seed(&self, ctx: &mut SeedContext) -> Result<u64, RepoError>;
+}
+
+/// Runs seeders in the order they were added.
+pub struct SeedRunner {
+    seeders: Vec<Box<dyn Seeder>>,
+}
+
+impl SeedRunner {
+    pub fn new() -> Self {
+        Self {
+            seeders: Vec::new(),
+        }
+    }
+
+    /// Users, then posts for each of them.
+    pub fn fixtures() -> Self {
+        Self::new().with(UserSeeder).with(PostSeeder)
+    }
+
+    pub fn with(mut self, seeder: impl Seeder + 'static) -> Self {
+        self.seeders.push(Box::new(seeder));
+        self
+    }
+
+    /// Run every seeder, returning the rows each wrote. Stops at the first
+    /// failure.
+    pub async fn run(&self, ctx: &mut SeedContext) -> Result<Vec<(&'static str, u64)>, SeedError> {
+        let mut written = Vec::with_capacity(self.seeders.len());
+        for seeder in &self.seeders {
+            let rows = seeder.seed(ctx).await.map_err(|source| SeedError::Seeder {
+                seeder: seeder.name(),
+                source,
+            })?;
+            tracing::info!(seeder = seeder.name(), rows, "Seeded");
+            written.push((seeder.name(), rows));
+        }
+        Ok(written)
+    }
+}
+
+impl Default for SeedRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `user1@example.com`, `user2@example.com`, ... signing in with
+/// [`SEED_PASSWORD`]. An account already registered under one of these
+/// emails is kept as it is and given the seeded posts.
+pub struct UserSeeder;
+
+#[async_trait]
+impl Seeder for UserSeeder {
+    fn name(&self) -> &'static str {
+        "users"
+    }
+
+    async fn seed(&self, ctx: &mut SeedContext) -> Result<u64, RepoError> {
+        let mut written = 0;
+        for n in 1..=ctx.environment.users() {
+            let email = Email::from_stored(format!("user{n}@example.com"));
+            let user = match ctx.users.find_by_email(&email).await? {
+                Some(user) => user,
+                None => {
+                    let mut user = User::new(email, ctx.password_hash.clone());
+                    // An admin, so the admin endpoints can be tried out
+                    if n == 1 {
+                        user.roles.push(User::ADMIN_ROLE.to_string());
+                    }
+                    user.id = seed_id(1, n);
+                    user.created_at = seed_time(0);
+                    user.updated_at = seed_time(0);
+                    written += 1;
+                    ctx.users.save(user).await?
+                }
+            };
+            ctx.seeded_users.push(user);
+        }
+        Ok(written)
+    }
+}
+
+/// Posts for every seeded user, every other one published. Rewritten on
+/// each run, so edits made to them are undone.
+pub struct PostSeeder;
+
+#[async_trait]
+impl Seeder for PostSeeder {
+    fn name(&self) -> &'static str {
+        "posts"
+    }
+
+    async fn seed(&self, ctx: &mut SeedContext) -> Result<u64, RepoError> {
+        let mut posts = Vec::new();
+        for (u, user) in (1..).zip(&ctx.seeded_users) {
+            for p in 1..=ctx.environment.posts_per_user() {
+                let mut post = Post::new(
+                    user.id,
+                    format!("Post {p} by user {u}"),
+                    format!("Seeded post {p} of {}.", user.email),
+                );
+                post.id = seed_id(2, u * 1000 + p);
+                post.created_at = seed_time(p);
+                post.updated_at = seed_time(p);
+                if p % 2 == 1 {
+                    post.published_at = Some(seed_time(p));
+                }
+                posts.push(post);
+            }
+        }
+        let written = posts.len() as u64;
+        ctx.posts.save_many(posts).await?;
+        Ok(written)
+    }
+}
+
+/// The `n`th seeded row of a `kind`, the same on every run.
+fn seed_id(kind: u32, n: u32) -> Uuid {
+    Uuid::from_u128(0x5eed_0000_0000_4000_8000_0000_0000_0000 | (kind as u128) << 32 | n as u128)
+}
+
+fn seed_time(hours: u32) -> DateTime<Utc> {
+    DateTime::from_timestamp(SEED_EPOCH, 0).unwrap() + Duration::hours(hours as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemoryPostRepository, InMemoryUserRepository};
+
+    fn context(environment: SeedEnvironment) -> SeedContext {
+        SeedContext::new(
+            environment,
+            Arc::new(InMemoryUserRepository::new()),
+            Arc::new(InMemoryPostRepository::new()),
+            "hash".to_string(),
+        )
+    }
+
+    async fn posts_of(ctx: &SeedContext) -> Vec<Post> {
+        let mut posts = Vec::new();
+        for user in &ctx.seeded_users {
+            posts.extend(ctx.posts.find_by_user_id(user.id).await.unwrap());
+        }
+        posts.sort_by_key(|post| post.id);
+        posts
+    }
+
+    #[tokio::test]
+    async fn test_seeding_twice_leaves_the_same_data() {
+        let mut ctx = context(SeedEnvironment::Development);
+        let first = SeedRunner::fixtures().run(&mut ctx).await.unwrap();
+        assert_eq!(first, vec![("users", 5), ("posts", 20)]);
+        let posts = posts_of(&ctx).await;
+
+        ctx.seeded_users.clear();
+        let second = SeedRunner::fixtures().run(&mut ctx).await.unwrap();
+        // Users are already there; posts are rewritten as they were
+        assert_eq!(second, vec![("users", 0), ("posts", 20)]);
+        assert_eq!(posts_of(&ctx).await, posts);
+        assert_eq!(posts[0].id, seed_id(2, 1001));
+        assert_eq!(
+            posts[0].created_at.to_rfc3339(),
+            "2026-01-01T01:00:00+00:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ci_gets_a_smaller_dataset() {
+        let mut ctx = context(SeedEnvironment::Test);
+        let written = SeedRunner::fixtures().run(&mut ctx).await.unwrap();
+        assert_eq!(written, vec![("users", 2), ("posts", 4)]);
+
+        let roles: Vec<_> = ctx.seeded_users.iter().map(|u| u.roles.clone()).collect();
+        assert_eq!(roles, vec![vec!["user", "admin"], vec!["user"]]);
+    }
+
+    #[tokio::test]
+    async fn test_registered_accounts_are_kept() {
+        let mut ctx = context(SeedEnvironment::Test);
+        let existing = User::new("user1@example.com".parse().unwrap(), "theirs".into());
+        ctx.users.save(existing.clone()).await.unwrap();
+
+        SeedRunner::fixtures().run(&mut ctx).await.unwrap();
+        let user = ctx.users.find_by_id(existing.id).await.unwrap().unwrap();
+        assert_eq!(user.password_hash, "theirs");
+        assert_eq!(
+            ctx.posts.find_by_user_id(existing.id).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_production_is_never_seeded() {
+        assert!(matches!(
+            SeedEnvironment::parse(Some("production")),
+            Err(SeedError::Production)
+        ));
+        assert_eq!(
+            SeedEnvironment::parse(Some("ci")).unwrap(),
+            SeedEnvironment::Test
+        );
+        assert_eq!(
+            SeedEnvironment::parse(None).unwrap(),
+            SeedEnvironment::Development
+        );
+    }
+}
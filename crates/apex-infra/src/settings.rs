@@ -0,0 +1,163 @@
+//! Typed, cache-backed settings access.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use apex_core::domain::{Patch, SettingsSchema, SettingsScope};
+use apex_core::ports::{Cache, SettingsError, SettingsRepository};
+
+/// Reads and updates typed settings, caching the decoded document per scope.
+pub struct SettingsStore {
+    repo: Arc<dyn SettingsRepository>,
+    cache: Arc<dyn Cache>,
+    ttl: Duration,
+}
+
+impl SettingsStore {
+    pub fn new(repo: Arc<dyn SettingsRepository>, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            repo,
+            cache,
+            ttl: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Load settings for a scope, filling in defaults.
+    pub async fn get<S: SettingsSchema>(&self, scope: SettingsScope) -> Result<S, SettingsError> {
+        let key = cache_key(scope);
+        if let Some(cached) = self.cache.get(&key).await {
+            match serde_json::from_str(&cached) {
+                Ok(settings) => return Ok(settings),
+                Err(e) => {
+                    tracing::warn!(error = %e, key = %key, "Discarding unreadable cached settings")
+                }
+            }
+        }
+
+        let settings = S::from_stored(self.repo.get(scope).await?)?;
+        self.cache_settings(&key, &settings).await;
+        Ok(settings)
+    }
+
+    /// Apply a patch to a scope's settings and persist the result.
+    pub async fn update<S: SettingsSchema>(
+        &self,
+        scope: SettingsScope,
+        patch: &Patch,
+    ) -> Result<S, SettingsError> {
+        // Patch against storage, not the cache, so concurrent writers on other
+        // instances are not overwritten with stale values
+        let current = S::from_stored(self.repo.get(scope).await?)?;
+        let updated = current.patched(patch)?;
+
+        let values = serde_json::to_value(&updated).map_err(|e| {
+            apex_core::DomainError::Internal(format!("Failed to encode settings: {}", e))
+        })?;
+        self.repo.put(scope, values).await?;
+
+        self.cache_settings(&cache_key(scope), &updated).await;
+        Ok(updated)
+    }
+
+    async fn cache_settings<S: SettingsSchema>(&self, key: &str, settings: &S) {
+        let Ok(json) = serde_json::to_string(settings) else {
+            return;
+        };
+        if let Err(e) = self.cache.set(key, &json, Some(self.ttl)).await {
+            tracing::warn!(error = %e, key = %key, "Failed to cache settings");
+        }
+    }
+}
+
+fn cache_key(scope: SettingsScope) -> String {
+    format!("settings:{}:{}", scope.kind(), scope.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use apex_core::domain::OrgSettings;
+    use apex_core::error::RepoError;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemorySettings {
+        values: Mutex<HashMap<SettingsScope, serde_json::Value>>,
+        reads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SettingsRepository for MemorySettings {
+        async fn get(&self, scope: SettingsScope) -> Result<Option<serde_json::Value>, RepoError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(self.values.lock().await.get(&scope).cloned())
+        }
+
+        async fn put(
+            &self,
+            scope: SettingsScope,
+            values: serde_json::Value,
+        ) -> Result<(), RepoError> {
+            self.values.lock().await.insert(scope, values);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_are_cached() {
+        let repo = Arc::new(MemorySettings::default());
+        let store = SettingsStore::new(repo.clone(), Arc::new(InMemoryCache::new()));
+        let scope = SettingsScope::Organization(uuid::Uuid::new_v4());
+
+        let first: OrgSettings = store.get(scope).await.unwrap();
+        let second: OrgSettings = store.get(scope).await.unwrap();
+
+        assert_eq!(first, OrgSettings::default());
+        assert_eq!(first, second);
+        assert_eq!(repo.reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_persists_and_refreshes_cache() {
+        let repo = Arc::new(MemorySettings::default());
+        let store = SettingsStore::new(repo.clone(), Arc::new(InMemoryCache::new()));
+        let scope = SettingsScope::Organization(uuid::Uuid::new_v4());
+
+        let _: OrgSettings = store.get(scope).await.unwrap();
+        let updated: OrgSettings = store
+            .update(scope, &Patch::Merge(serde_json::json!({"max_members": 25})))
+            .await
+            .unwrap();
+        let read: OrgSettings = store.get(scope).await.unwrap();
+
+        assert_eq!(updated.max_members, Some(25));
+        assert_eq!(read, updated);
+        assert_eq!(
+            repo.values.lock().await[&scope]["max_members"],
+            serde_json::json!(25)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_update_is_not_persisted() {
+        let repo = Arc::new(MemorySettings::default());
+        let store = SettingsStore::new(repo.clone(), Arc::new(InMemoryCache::new()));
+        let scope = SettingsScope::Organization(uuid::Uuid::new_v4());
+
+        let result = store
+            .update::<OrgSettings>(scope, &Patch::Merge(serde_json::json!({"max_members": 0})))
+            .await;
+
+        assert!(matches!(result, Err(SettingsError::Invalid(_))));
+        assert!(repo.values.lock().await.is_empty());
+    }
+}
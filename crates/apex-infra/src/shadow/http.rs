@@ -0,0 +1,61 @@
+//! HTTP traffic mirror using reqwest.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use apex_core::ports::{MirrorError, MirrorRequest, MirrorResponse, TrafficMirror};
+
+/// Replays requests against a shadow deployment over HTTP.
+pub struct HttpTrafficMirror {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpTrafficMirror {
+    pub fn new(base_url: impl Into<String>, timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl TrafficMirror for HttpTrafficMirror {
+    async fn send(&self, request: &MirrorRequest) -> Result<MirrorResponse, MirrorError> {
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .map_err(|e| MirrorError::Transport(e.to_string()))?;
+        let mut builder = self
+            .client
+            .request(method, format!("{}{}", self.base_url, request.path))
+            .body(request.body.clone());
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder.send().await.map_err(|e| {
+            if e.is_timeout() {
+                MirrorError::Timeout
+            } else {
+                MirrorError::Transport(e.to_string())
+            }
+        })?;
+
+        let status = response.status().as_u16();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| MirrorError::Transport(e.to_string()))?;
+
+        Ok(MirrorResponse {
+            status,
+            body: body.to_vec(),
+        })
+    }
+}
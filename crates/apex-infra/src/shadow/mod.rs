@@ -0,0 +1,452 @@
+//! Shadow traffic: mirror sampled production requests to a shadow
+//! deployment and record where its responses differ.
+//!
+//! Used to validate a rewrite or a new backend against real traffic before
+//! it serves anyone. Mirroring happens after the production response is
+//! sent, so the shadow never affects latency or results.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use apex_core::ports::{MirrorRequest, TrafficMirror};
+
+use crate::env;
+
+#[cfg(feature = "webhooks")]
+mod http;
+#[cfg(feature = "webhooks")]
+pub use self::http::HttpTrafficMirror;
+
+/// Headers never sent to the shadow: credentials, and hop-by-hop headers
+/// the client sets itself.
+const DROPPED_HEADERS: [&str; 9] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+    "x-csrf-token",
+    "host",
+    "content-length",
+    "connection",
+    "transfer-encoding",
+];
+
+/// Query parameters whose values are redacted before mirroring.
+const SENSITIVE_PARAMS: [&str; 7] = [
+    "token",
+    "access_token",
+    "code",
+    "password",
+    "secret",
+    "key",
+    "signature",
+];
+
+/// Most differences recorded per response pair.
+const MAX_DIFFERENCES: usize = 20;
+
+/// Shadow traffic configuration.
+#[derive(Debug, Clone)]
+pub struct ShadowConfig {
+    /// Base URL of the shadow deployment. Mirroring is off without one.
+    pub url: Option<String>,
+    /// Fraction of eligible requests mirrored, 0.0 to 1.0.
+    pub sample_rate: f64,
+    /// Methods that are mirrored. Defaults to reads only, since a shadow
+    /// sharing state with production would otherwise apply writes twice.
+    pub methods: Vec<String>,
+    /// Requests and responses with larger bodies are not mirrored.
+    pub max_body_bytes: usize,
+    /// Most differing responses kept; the oldest are dropped.
+    pub diff_limit: usize,
+    /// How long to wait for the shadow before counting it as failed.
+    pub timeout: Duration,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            sample_rate: 0.01,
+            methods: vec!["GET".to_string(), "HEAD".to_string()],
+            max_body_bytes: 64 * 1024,
+            diff_limit: 500,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ShadowConfig {
+    pub fn from_env() -> Self {
+        Self {
+            url: env::SHADOW_URL.raw(),
+            sample_rate: env::SHADOW_SAMPLE_RATE.get::<f64>().clamp(0.0, 1.0),
+            methods: env::SHADOW_METHODS
+                .list()
+                .iter()
+                .map(|m| m.to_uppercase())
+                .collect(),
+            max_body_bytes: env::SHADOW_MAX_BODY_BYTES.get(),
+            diff_limit: env::SHADOW_DIFF_LIMIT.get(),
+            timeout: env::SHADOW_TIMEOUT_MS.millis(),
+        }
+    }
+}
+
+/// The production side of a mirrored request.
+#[derive(Debug, Clone)]
+pub struct PrimaryResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub latency: Duration,
+}
+
+/// A mirrored request whose shadow response did not match production.
+#[derive(Debug, Clone)]
+pub struct ShadowDiff {
+    pub id: Uuid,
+    pub method: String,
+    /// Path and query, with sensitive parameters redacted.
+    pub path: String,
+    pub primary_status: u16,
+    /// Missing when the shadow could not be reached.
+    pub shadow_status: Option<u16>,
+    /// Where the responses differ, e.g. `status: 200 != 500` or `$.items[0].name`.
+    pub differences: Vec<String>,
+    pub error: Option<String>,
+    pub primary_latency_ms: u64,
+    pub shadow_latency_ms: u64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Counters since startup.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShadowStats {
+    pub mirrored: u64,
+    pub matched: u64,
+    pub mismatched: u64,
+    /// Shadow unreachable or timed out.
+    pub failed: u64,
+}
+
+/// Samples requests, mirrors them and keeps the responses that differed.
+pub struct ShadowTraffic {
+    config: ShadowConfig,
+    mirror: Arc<dyn TrafficMirror>,
+    diffs: Mutex<VecDeque<ShadowDiff>>,
+    mirrored: AtomicU64,
+    matched: AtomicU64,
+    mismatched: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl ShadowTraffic {
+    pub fn new(config: ShadowConfig, mirror: Arc<dyn TrafficMirror>) -> Self {
+        Self {
+            config,
+            mirror,
+            diffs: Mutex::new(VecDeque::new()),
+            mirrored: AtomicU64::new(0),
+            matched: AtomicU64::new(0),
+            mismatched: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &ShadowConfig {
+        &self.config
+    }
+
+    /// Whether to mirror a request with this method and body size.
+    pub fn sample(&self, method: &str, body_len: usize) -> bool {
+        if body_len > self.config.max_body_bytes || !self.config.methods.iter().any(|m| m == method)
+        {
+            return false;
+        }
+        // 1 in 10,000 resolution is plenty for a sample rate
+        let roll = (Uuid::new_v4().as_u128() % 10_000) as f64 / 10_000.0;
+        roll < self.config.sample_rate
+    }
+
+    /// Replay `request` against the shadow in the background and record
+    /// how its response compares to `primary`. Credentials and sensitive
+    /// query parameters are removed first.
+    pub fn mirror(self: &Arc<Self>, request: MirrorRequest, primary: PrimaryResponse) {
+        let request = sanitize(request);
+        let shadow = self.clone();
+
+        tokio::spawn(async move {
+            shadow.mirrored.fetch_add(1, Ordering::Relaxed);
+            let started = Instant::now();
+            let result = shadow.mirror.send(&request).await;
+            let shadow_latency = started.elapsed();
+
+            let (shadow_status, differences, error) = match result {
+                Ok(response) => {
+                    let differences = compare(
+                        primary.status,
+                        &primary.body,
+                        response.status,
+                        &response.body,
+                    );
+                    if differences.is_empty() {
+                        shadow.matched.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    shadow.mismatched.fetch_add(1, Ordering::Relaxed);
+                    (Some(response.status), differences, None)
+                }
+                Err(e) => {
+                    shadow.failed.fetch_add(1, Ordering::Relaxed);
+                    (None, vec![], Some(e.to_string()))
+                }
+            };
+
+            tracing::debug!(
+                method = %request.method,
+                path = %request.path,
+                differences = differences.len(),
+                "Shadow response differs"
+            );
+            shadow.record(ShadowDiff {
+                id: Uuid::new_v4(),
+                method: request.method,
+                path: request.path,
+                primary_status: primary.status,
+                shadow_status,
+                differences,
+                error,
+                primary_latency_ms: primary.latency.as_millis() as u64,
+                shadow_latency_ms: shadow_latency.as_millis() as u64,
+                recorded_at: Utc::now(),
+            });
+        });
+    }
+
+    fn record(&self, diff: ShadowDiff) {
+        let mut diffs = self.diffs.lock().unwrap();
+        diffs.push_front(diff);
+        diffs.truncate(self.config.diff_limit);
+    }
+
+    /// Recorded differences, newest first.
+    pub fn diffs(&self, limit: usize) -> Vec<ShadowDiff> {
+        self.diffs
+            .lock()
+            .unwrap()
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Forget recorded differences, e.g. after deploying a fix to the shadow.
+    pub fn clear(&self) {
+        self.diffs.lock().unwrap().clear();
+    }
+
+    pub fn stats(&self) -> ShadowStats {
+        ShadowStats {
+            mirrored: self.mirrored.load(Ordering::Relaxed),
+            matched: self.matched.load(Ordering::Relaxed),
+            mismatched: self.mismatched.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Strip credentials and hop-by-hop headers, redact sensitive query
+/// parameters, and mark the request as shadow traffic.
+fn sanitize(mut request: MirrorRequest) -> MirrorRequest {
+    request
+        .headers
+        .retain(|(name, _)| !DROPPED_HEADERS.contains(&name.to_ascii_lowercase().as_str()));
+    request
+        .headers
+        .push(("x-shadow-request".to_string(), "1".to_string()));
+
+    if let Some((path, query)) = request.path.split_once('?') {
+        let query: Vec<String> = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((name, _))
+                    if SENSITIVE_PARAMS.contains(&name.to_ascii_lowercase().as_str()) =>
+                {
+                    format!("{}=REDACTED", name)
+                }
+                _ => pair.to_string(),
+            })
+            .collect();
+        request.path = format!("{}?{}", path, query.join("&"));
+    }
+    request
+}
+
+/// Where two responses differ. JSON bodies are compared structurally, so
+/// key order and formatting don't count; other bodies byte for byte.
+fn compare(primary_status: u16, primary: &[u8], shadow_status: u16, shadow: &[u8]) -> Vec<String> {
+    let mut differences = vec![];
+    if primary_status != shadow_status {
+        differences.push(format!("status: {} != {}", primary_status, shadow_status));
+    }
+
+    match (
+        serde_json::from_slice::<serde_json::Value>(primary),
+        serde_json::from_slice::<serde_json::Value>(shadow),
+    ) {
+        (Ok(primary), Ok(shadow)) => json_differences("$", &primary, &shadow, &mut differences),
+        _ if primary != shadow => differences.push("body".to_string()),
+        _ => {}
+    }
+
+    differences.truncate(MAX_DIFFERENCES);
+    differences
+}
+
+fn json_differences(
+    path: &str,
+    primary: &serde_json::Value,
+    shadow: &serde_json::Value,
+    differences: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    if differences.len() >= MAX_DIFFERENCES {
+        return;
+    }
+    match (primary, shadow) {
+        (Value::Object(primary), Value::Object(shadow)) => {
+            let mut keys: Vec<&String> = primary.keys().chain(shadow.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = format!("{}.{}", path, key);
+                match (primary.get(key), shadow.get(key)) {
+                    (Some(p), Some(s)) => json_differences(&path, p, s, differences),
+                    _ => differences.push(path),
+                }
+            }
+        }
+        (Value::Array(primary), Value::Array(shadow)) => {
+            if primary.len() != shadow.len() {
+                differences.push(format!(
+                    "{}: length {} != {}",
+                    path,
+                    primary.len(),
+                    shadow.len()
+                ));
+            }
+            for (i, (p, s)) in primary.iter().zip(shadow).enumerate() {
+                json_differences(&format!("{}[{}]", path, i), p, s, differences);
+            }
+        }
+        _ if primary != shadow => differences.push(path.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apex_core::ports::{MirrorError, MirrorResponse};
+    use async_trait::async_trait;
+
+    struct FixedShadow(u16, &'static str);
+
+    #[async_trait]
+    impl TrafficMirror for FixedShadow {
+        async fn send(&self, request: &MirrorRequest) -> Result<MirrorResponse, MirrorError> {
+            assert!(
+                request
+                    .headers
+                    .iter()
+                    .all(|(name, _)| name != "Authorization")
+            );
+            Ok(MirrorResponse {
+                status: self.0,
+                body: self.1.as_bytes().to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_sanitize_strips_credentials() {
+        let request = sanitize(MirrorRequest {
+            method: "GET".to_string(),
+            path: "/api/items?page=2&access_token=abc".to_string(),
+            headers: vec![
+                ("Authorization".to_string(), "Bearer abc".to_string()),
+                ("Cookie".to_string(), "session=abc".to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ],
+            body: vec![],
+        });
+
+        assert_eq!(request.path, "/api/items?page=2&access_token=REDACTED");
+        assert_eq!(
+            request.headers,
+            vec![
+                ("Accept".to_string(), "application/json".to_string()),
+                ("x-shadow-request".to_string(), "1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_ignores_json_key_order() {
+        assert!(compare(200, br#"{"a":1,"b":[1,2]}"#, 200, br#"{"b":[1,2], "a":1}"#).is_empty());
+        assert_eq!(
+            compare(
+                200,
+                br#"{"a":1,"b":[1,2]}"#,
+                500,
+                br#"{"a":2,"b":[1],"c":0}"#
+            ),
+            vec!["status: 200 != 500", "$.a", "$.b: length 2 != 1", "$.c"]
+        );
+        assert_eq!(compare(200, b"ok", 200, b"OK"), vec!["body"]);
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_shadow_response_is_recorded() {
+        let config = ShadowConfig {
+            sample_rate: 1.0,
+            ..ShadowConfig::default()
+        };
+        let shadow = Arc::new(ShadowTraffic::new(
+            config,
+            Arc::new(FixedShadow(200, r#"{"plan":"pro"}"#)),
+        ));
+        assert!(shadow.sample("GET", 0));
+        assert!(!shadow.sample("POST", 0));
+
+        let request = MirrorRequest {
+            method: "GET".to_string(),
+            path: "/api/plan".to_string(),
+            headers: vec![("Authorization".to_string(), "Bearer abc".to_string())],
+            body: vec![],
+        };
+        for body in [r#"{"plan":"pro"}"#, r#"{"plan":"free"}"#] {
+            shadow.mirror(
+                request.clone(),
+                PrimaryResponse {
+                    status: 200,
+                    body: body.as_bytes().to_vec(),
+                    latency: Duration::from_millis(5),
+                },
+            );
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let stats = shadow.stats();
+        assert_eq!((stats.mirrored, stats.matched, stats.mismatched), (2, 1, 1));
+        let diffs = shadow.diffs(10);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].differences, vec!["$.plan"]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Object storage on the local filesystem.
+//!
+//! Suitable for development and single-instance deployments; objects are
+//! not shared between hosts. Each object's media type is kept next to it in
+//! a `.content-type` file.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use apex_core::ports::{ByteStream, StorageError, StorageService, StoredObject};
+
+use super::{UrlSigner, validate_key};
+use crate::env;
+
+/// Local storage configuration.
+#[derive(Debug, Clone)]
+pub struct LocalStorageConfig {
+    /// Directory objects are stored under.
+    pub root: PathBuf,
+    /// Public base URL of this API, the prefix of signed download links.
+    pub public_url: String,
+    /// Secret signing download links.
+    pub signing_secret: String,
+}
+
+impl Default for LocalStorageConfig {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("data/storage"),
+            public_url: "http://localhost:8080".to_string(),
+            signing_secret: "change-me-in-production".to_string(),
+        }
+    }
+}
+
+impl LocalStorageConfig {
+    pub fn from_env() -> Self {
+        Self {
+            root: PathBuf::from(env::STORAGE_DIR.string()),
+            public_url: env::PUBLIC_URL.string(),
+            signing_secret: env::STORAGE_SIGNING_SECRET.string(),
+        }
+    }
+}
+
+/// Stores objects as files under a root directory.
+///
+/// Signed URLs point at `GET {public_url}/api/files/{key}`, which checks
+/// them with [`verify`](Self::verify).
+pub struct LocalStorage {
+    config: LocalStorageConfig,
+    signer: UrlSigner,
+}
+
+impl LocalStorage {
+    pub fn new(config: LocalStorageConfig) -> Self {
+        Self {
+            signer: UrlSigner::new(config.signing_secret.clone()),
+            config,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(LocalStorageConfig::from_env())
+    }
+
+    /// Whether a download link for `key` is genuine and unexpired.
+    pub fn verify(&self, key: &str, expires: i64, signature: &str) -> bool {
+        self.signer
+            .verify(key, expires, signature, chrono::Utc::now().timestamp())
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(self.config.root.join(key))
+    }
+}
+
+fn content_type_path(path: &std::path::Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".content-type");
+    PathBuf::from(name)
+}
+
+fn backend(e: std::io::Error) -> StorageError {
+    StorageError::Backend(e.to_string())
+}
+
+#[async_trait]
+impl StorageService for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(backend)?;
+        }
+        tokio::fs::write(&path, bytes).await.map_err(backend)?;
+        tokio::fs::write(content_type_path(&path), content_type)
+            .await
+            .map_err(backend)
+    }
+
+    /// Chunks go to a `.part` file next to the object, which replaces it
+    /// only once every chunk was written.
+    async fn put_stream(
+        &self,
+        key: &str,
+        bytes: &mut dyn ByteStream,
+        content_type: &str,
+    ) -> Result<u64, StorageError> {
+        use tokio::io::AsyncWriteExt;
+
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(backend)?;
+        }
+        let mut part = path.as_os_str().to_owned();
+        part.push(".part");
+        let part = PathBuf::from(part);
+
+        let mut file = tokio::fs::File::create(&part).await.map_err(backend)?;
+        let written = async {
+            let mut size = 0u64;
+            while let Some(chunk) = bytes.next_chunk().await? {
+                file.write_all(&chunk).await.map_err(backend)?;
+                size += chunk.len() as u64;
+            }
+            file.flush().await.map_err(backend)?;
+            Ok(size)
+        }
+        .await;
+        drop(file);
+        let size = match written {
+            Ok(size) => size,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&part).await;
+                return Err(e);
+            }
+        };
+
+        tokio::fs::rename(&part, &path).await.map_err(backend)?;
+        tokio::fs::write(content_type_path(&path), content_type)
+            .await
+            .map_err(backend)?;
+        Ok(size)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, StorageError> {
+        let path = self.path(key)?;
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(backend(e)),
+        };
+        let content_type = tokio::fs::read_to_string(content_type_path(&path))
+            .await
+            .unwrap_or_else(|_| "application/octet-stream".to_string());
+        Ok(Some(StoredObject {
+            bytes,
+            content_type,
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        for path in [content_type_path(&path), path] {
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(backend(e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String, StorageError> {
+        validate_key(key)?;
+        let expires = chrono::Utc::now().timestamp() + expires_in.as_secs() as i64;
+        Ok(format!(
+            "{}/api/files/{}?expires={}&signature={}",
+            self.config.public_url.trim_end_matches('/'),
+            key,
+            expires,
+            self.signer.sign(key, expires)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage() -> LocalStorage {
+        LocalStorage::new(LocalStorageConfig {
+            root: std::env::temp_dir().join(format!("apex-storage-{}", uuid::Uuid::new_v4())),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete() {
+        let storage = storage();
+
+        storage
+            .put("reports/a.csv", b"a,b\n".to_vec(), "text/csv")
+            .await
+            .unwrap();
+        let object = storage.get("reports/a.csv").await.unwrap().unwrap();
+        assert_eq!(object.bytes, b"a,b\n");
+        assert_eq!(object.content_type, "text/csv");
+
+        storage.delete("reports/a.csv").await.unwrap();
+        assert!(storage.get("reports/a.csv").await.unwrap().is_none());
+        storage.delete("reports/a.csv").await.unwrap();
+
+        assert!(storage.get("../outside").await.is_err());
+        let _ = std::fs::remove_dir_all(&storage.config.root);
+    }
+
+    struct Chunks(Vec<Result<Vec<u8>, StorageError>>);
+
+    #[async_trait]
+    impl ByteStream for Chunks {
+        async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, StorageError> {
+            if self.0.is_empty() {
+                return Ok(None);
+            }
+            self.0.remove(0).map(Some)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_stream_keeps_only_complete_objects() {
+        let storage = storage();
+
+        let mut chunks = Chunks(vec![Ok(b"a,b\n".to_vec()), Ok(b"1,2\n".to_vec())]);
+        let size = storage
+            .put_stream("reports/a.csv", &mut chunks, "text/csv")
+            .await
+            .unwrap();
+        assert_eq!(size, 8);
+        let object = storage.get("reports/a.csv").await.unwrap().unwrap();
+        assert_eq!(object.bytes, b"a,b\n1,2\n");
+        assert_eq!(object.content_type, "text/csv");
+
+        let mut chunks = Chunks(vec![Ok(b"a,b\n".to_vec()), Err(StorageError::TooLarge(4))]);
+        let result = storage
+            .put_stream("reports/b.csv", &mut chunks, "text/csv")
+            .await;
+        assert!(matches!(result, Err(StorageError::TooLarge(4))));
+        assert!(storage.get("reports/b.csv").await.unwrap().is_none());
+        assert!(!storage.config.root.join("reports/b.csv.part").exists());
+        let _ = std::fs::remove_dir_all(&storage.config.root);
+    }
+
+    #[test]
+    fn test_signed_urls_verify() {
+        let storage = storage();
+        let url = storage
+            .signed_url("reports/a.csv", Duration::from_secs(60))
+            .unwrap();
+
+        let query = url.split_once('?').unwrap().1;
+        let (expires, signature) = query.split_once('&').unwrap();
+        let expires: i64 = expires.trim_start_matches("expires=").parse().unwrap();
+        let signature = signature.trim_start_matches("signature=");
+        assert!(url.starts_with("http://localhost:8080/api/files/reports/a.csv?"));
+        assert!(storage.verify("reports/a.csv", expires, signature));
+        assert!(!storage.verify("reports/b.csv", expires, signature));
+    }
+}
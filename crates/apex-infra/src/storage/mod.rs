@@ -0,0 +1,50 @@
+//! Object storage implementations.
+
+mod local;
+mod signing;
+
+pub use local::{LocalStorage, LocalStorageConfig};
+pub use signing::UrlSigner;
+
+use apex_core::ports::StorageError;
+
+/// Check that `key` is a relative path of plain segments (letters, digits,
+/// `.`, `_` and `-`), so it can't escape the storage root.
+pub fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_stay_under_the_root() {
+        assert!(validate_key("reports/signups/2026-01-20T06.csv").is_ok());
+        for key in [
+            "",
+            "/etc/passwd",
+            "a//b",
+            "../a",
+            "a/./b",
+            "a/b/",
+            "a b",
+            "a\\b",
+        ] {
+            assert!(validate_key(key).is_err(), "{key}");
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Storage quotas - bytes stored per account, limited by plan.
+//!
+//! Quotas are soft: a write is checked against the usage at the time, not
+//! reserved, so concurrent uploads can take an account slightly over its
+//! quota. The next upload after that is refused.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use apex_core::domain::{Plan, StorageUsage, UsageMetric};
+use apex_core::error::RepoError;
+use apex_core::ports::{StorageQuotaError, StorageUsageRepository};
+
+use crate::entitlements::EntitlementResolver;
+use crate::env;
+use crate::metering::UsageMeter;
+
+/// Tracks the bytes each account stores and enforces its plan's quota.
+pub struct StorageQuotas {
+    repo: Arc<dyn StorageUsageRepository>,
+    entitlements: Arc<EntitlementResolver>,
+    usage: Arc<UsageMeter>,
+    /// Bytes per plan; plans missing here are unlimited.
+    quotas: HashMap<Plan, i64>,
+}
+
+impl StorageQuotas {
+    pub fn new(
+        repo: Arc<dyn StorageUsageRepository>,
+        entitlements: Arc<EntitlementResolver>,
+        usage: Arc<UsageMeter>,
+        quotas: HashMap<Plan, i64>,
+    ) -> Self {
+        Self {
+            repo,
+            entitlements,
+            usage,
+            quotas,
+        }
+    }
+
+    /// Bytes the account stores and its quota.
+    pub async fn usage(&self, account_id: Uuid) -> Result<StorageUsage, RepoError> {
+        let plan = self.entitlements.plan_for(account_id).await?;
+        Ok(StorageUsage {
+            account_id,
+            plan,
+            used_bytes: self.repo.bytes_stored(account_id).await?,
+            quota_bytes: self.quotas.get(&plan).copied(),
+        })
+    }
+
+    /// Succeeds if the account has room for `bytes` more. Call before
+    /// accepting an upload, and [`record`](Self::record) once it is stored.
+    pub async fn check(
+        &self,
+        account_id: Uuid,
+        bytes: i64,
+    ) -> Result<StorageUsage, StorageQuotaError> {
+        let usage = self.usage(account_id).await?;
+        match usage.quota_bytes {
+            Some(quota) if !usage.fits(bytes) => Err(StorageQuotaError::Exceeded {
+                plan: usage.plan,
+                used: usage.used_bytes,
+                quota,
+                requested: bytes,
+                upgrade: self.upgrade_for(usage.plan, usage.used_bytes.saturating_add(bytes)),
+            }),
+            _ => Ok(usage),
+        }
+    }
+
+    /// Count `delta` bytes stored (negative when files are deleted). Also
+    /// metered, so storage shows up in the monthly usage.
+    pub async fn record(&self, account_id: Uuid, delta: i64) -> Result<i64, RepoError> {
+        let total = self.repo.add_bytes(account_id, delta).await?;
+        self.usage
+            .record(account_id, UsageMetric::StorageBytes, delta);
+        Ok(total)
+    }
+
+    /// Cheapest plan above `plan` with room for `bytes` in total.
+    fn upgrade_for(&self, plan: Plan, bytes: i64) -> Option<Plan> {
+        Plan::ALL
+            .into_iter()
+            .filter(|candidate| *candidate > plan)
+            .find(|candidate| {
+                self.quotas
+                    .get(candidate)
+                    .is_none_or(|quota| bytes <= *quota)
+            })
+    }
+}
+
+/// Quotas per plan from `STORAGE_QUOTAS`, e.g. `free=1GiB,pro=100GiB`
+/// (the default). Plans not listed are unlimited.
+pub fn storage_quotas_from_env() -> HashMap<Plan, i64> {
+    parse_storage_quotas(&env::STORAGE_QUOTAS.string())
+}
+
+/// Parse `plan=size` pairs. Sizes are bytes, optionally with a `KB`, `MB`,
+/// `GB`, `TB` or `KiB`, `MiB`, `GiB`, `TiB` suffix. Malformed entries are
+/// skipped with a warning.
+pub fn parse_storage_quotas(spec: &str) -> HashMap<Plan, i64> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.split_once('=').and_then(|(plan, size)| {
+                Some((plan.trim().parse::<Plan>().ok()?, parse_size(size.trim())?))
+            });
+            if parsed.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed storage quota");
+            }
+            parsed
+        })
+        .collect()
+}
+
+fn parse_size(size: &str) -> Option<i64> {
+    let split = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(size.len());
+    let (count, unit) = size.split_at(split);
+    let multiplier: i64 = match unit.trim() {
+        "" | "B" => 1,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        _ => return None,
+    };
+    count.parse::<i64>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use apex_core::domain::UsageTotal;
+    use apex_core::ports::{PlanRepository, UsageRepository};
+    use async_trait::async_trait;
+    use chrono::NaiveDate;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryStorage(Mutex<HashMap<Uuid, i64>>);
+
+    #[async_trait]
+    impl StorageUsageRepository for MemoryStorage {
+        async fn bytes_stored(&self, account_id: Uuid) -> Result<i64, RepoError> {
+            Ok(self.0.lock().await.get(&account_id).copied().unwrap_or(0))
+        }
+        async fn add_bytes(&self, account_id: Uuid, delta: i64) -> Result<i64, RepoError> {
+            let mut stored = self.0.lock().await;
+            let total = stored.entry(account_id).or_default();
+            *total += delta;
+            Ok(*total)
+        }
+    }
+
+    struct FreePlans;
+
+    #[async_trait]
+    impl PlanRepository for FreePlans {
+        async fn get_plan(&self, _account_id: Uuid) -> Result<Option<Plan>, RepoError> {
+            Ok(None)
+        }
+        async fn set_plan(&self, _account_id: Uuid, _plan: Plan) -> Result<(), RepoError> {
+            Ok(())
+        }
+    }
+
+    struct NoUsage;
+
+    #[async_trait]
+    impl UsageRepository for NoUsage {
+        async fn increment(&self, _increments: Vec<UsageTotal>) -> Result<(), RepoError> {
+            Ok(())
+        }
+        async fn totals(
+            &self,
+            _id: Uuid,
+            _period: NaiveDate,
+        ) -> Result<Vec<UsageTotal>, RepoError> {
+            Ok(vec![])
+        }
+    }
+
+    fn quotas(spec: &str) -> StorageQuotas {
+        StorageQuotas::new(
+            Arc::new(MemoryStorage::default()),
+            Arc::new(EntitlementResolver::new(
+                Arc::new(FreePlans),
+                Arc::new(InMemoryCache::new()),
+            )),
+            Arc::new(UsageMeter::new(Arc::new(NoUsage))),
+            parse_storage_quotas(spec),
+        )
+    }
+
+    #[test]
+    fn test_parse_storage_quotas() {
+        let parsed = parse_storage_quotas("free=1GiB, pro = 500MB,enterprise=10,gold=1GB,free=1XB");
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[&Plan::Free], 1 << 30);
+        assert_eq!(parsed[&Plan::Pro], 500_000_000);
+        assert_eq!(parsed[&Plan::Enterprise], 10);
+    }
+
+    #[tokio::test]
+    async fn test_uploads_beyond_the_quota_are_refused() {
+        let quotas = quotas("free=1000,pro=5000");
+        let account = Uuid::new_v4();
+
+        quotas.check(account, 600).await.unwrap();
+        assert_eq!(quotas.record(account, 600).await.unwrap(), 600);
+
+        match quotas.check(account, 600).await {
+            Err(StorageQuotaError::Exceeded {
+                used,
+                quota,
+                upgrade,
+                ..
+            }) => {
+                assert_eq!((used, quota), (600, 1000));
+                assert_eq!(upgrade, Some(Plan::Pro));
+            }
+            other => panic!("expected quota error, got {:?}", other),
+        }
+
+        // Deleting files frees room
+        quotas.record(account, -200).await.unwrap();
+        assert_eq!(quotas.check(account, 600).await.unwrap().used_bytes, 400);
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_hint_needs_room_for_the_upload() {
+        let quotas = quotas("free=1000,pro=5000,enterprise=10000");
+        let account = Uuid::new_v4();
+
+        match quotas.check(account, 8000).await {
+            Err(StorageQuotaError::Exceeded { upgrade, .. }) => {
+                assert_eq!(upgrade, Some(Plan::Enterprise))
+            }
+            other => panic!("expected quota error, got {:?}", other),
+        }
+        match quotas.check(account, 20000).await {
+            Err(StorageQuotaError::Exceeded { upgrade, .. }) => assert_eq!(upgrade, None),
+            other => panic!("expected quota error, got {:?}", other),
+        }
+    }
+}
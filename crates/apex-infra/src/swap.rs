@@ -0,0 +1,113 @@
+//! Hot-swappable port implementations.
+//!
+//! A [`HotSwap`] holds the current implementation of a port and lets it be
+//! replaced while the server runs: stubs for Postgres once the database is
+//! back, the in-memory cache for Redis after a failover. Clones share the
+//! slot, so a swap is seen by every worker at once.
+//!
+//! A caller that loaded the old implementation finishes with it; the next
+//! load returns the new one. Anything built from a loaded `Arc` keeps that
+//! snapshot, which is why `HotSwap<dyn Cache>` is itself a [`Cache`]: the
+//! services built on it follow swaps without being rebuilt.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+
+use apex_core::ports::{Cache, CacheError};
+
+/// A port implementation that can be replaced at runtime.
+pub struct HotSwap<T: ?Sized> {
+    current: Arc<ArcSwap<Arc<T>>>,
+}
+
+impl<T: ?Sized> HotSwap<T> {
+    pub fn new(implementation: Arc<T>) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(implementation)),
+        }
+    }
+
+    /// The current implementation.
+    pub fn load(&self) -> Arc<T> {
+        Arc::clone(&self.current.load())
+    }
+
+    /// Replace the implementation, returning the previous one.
+    pub fn swap(&self, implementation: Arc<T>) -> Arc<T> {
+        Arc::unwrap_or_clone(self.current.swap(Arc::new(implementation)))
+    }
+}
+
+impl<T: ?Sized> Clone for HotSwap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            current: self.current.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Cache for HotSwap<dyn Cache> {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.load().get(key).await
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Option<Duration>) -> Result<(), CacheError> {
+        self.load().set(key, value, ttl).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CacheError> {
+        self.load().delete(key).await
+    }
+
+    async fn exists(&self, key: &str) -> bool {
+        self.load().exists(key).await
+    }
+
+    async fn incr(&self, key: &str, delta: i64) -> Result<i64, CacheError> {
+        self.load().incr(key, delta).await
+    }
+
+    async fn incr_expiring(&self, key: &str, delta: i64, ttl: Duration) -> Result<i64, CacheError> {
+        self.load().incr_expiring(key, delta, ttl).await
+    }
+
+    async fn take(&self, key: &str) -> Result<Option<String>, CacheError> {
+        self.load().take(key).await
+    }
+
+    async fn keys(&self, prefix: &str) -> Result<Vec<String>, CacheError> {
+        self.load().keys(prefix).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+
+    #[test]
+    fn test_swap_is_seen_by_clones() {
+        let slot: HotSwap<str> = HotSwap::new(Arc::from("stub"));
+        let clone = slot.clone();
+
+        let previous = slot.swap(Arc::from("postgres"));
+        assert_eq!(&*previous, "stub");
+        assert_eq!(&*clone.load(), "postgres");
+    }
+
+    #[tokio::test]
+    async fn test_cache_follows_swaps() {
+        let cache: HotSwap<dyn Cache> = HotSwap::new(Arc::new(InMemoryCache::new()));
+        let service_cache: Arc<dyn Cache> = Arc::new(cache.clone());
+        service_cache.set("key", "old", None).await.unwrap();
+
+        cache.swap(Arc::new(InMemoryCache::new()));
+        assert_eq!(service_cache.get("key").await, None);
+        service_cache.set("key", "new", None).await.unwrap();
+        assert_eq!(cache.load().get("key").await.as_deref(), Some("new"));
+    }
+}
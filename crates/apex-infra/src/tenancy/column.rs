@@ -0,0 +1,339 @@
+//! Posts scoped to the current tenant by their `organization_id`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use apex_core::domain::{Page, PageCursor, PageRequest, Post, SyncCursor};
+use apex_core::error::RepoError;
+use apex_core::ports::{BaseRepository, PostRepository};
+
+use super::current_tenant;
+
+/// Narrows a post repository to the current tenant's posts.
+///
+/// Reads skip other tenants' posts, and writes stamp posts with the tenant
+/// and refuse posts of another one. Pages are read by organization from the
+/// wrapped repository; the public listing is picked from the tenant's posts
+/// in memory.
+pub struct TenantScopedPostRepository {
+    inner: Arc<dyn PostRepository>,
+}
+
+impl TenantScopedPostRepository {
+    pub fn new(inner: Arc<dyn PostRepository>) -> Self {
+        Self { inner }
+    }
+
+    /// Check `post` may be written for `tenant`, stamping it if it has no
+    /// tenant yet, and that it does not replace another tenant's post.
+    async fn claim(&self, post: &mut Post, tenant: Uuid) -> Result<(), RepoError> {
+        let stored = self.inner.find_by_id(post.id).await?;
+        let owner = stored
+            .as_ref()
+            .map_or(post.organization_id, |stored| stored.organization_id);
+        if owner.is_some_and(|owner| owner != tenant)
+            || post.organization_id.is_some_and(|org| org != tenant)
+        {
+            return Err(RepoError::Constraint(
+                "Post belongs to another tenant".to_string(),
+            ));
+        }
+        post.organization_id = Some(tenant);
+        Ok(())
+    }
+}
+
+fn in_tenant(post: &Post, tenant: Uuid) -> bool {
+    post.organization_id == Some(tenant)
+}
+
+#[async_trait]
+impl BaseRepository<Post, Uuid> for TenantScopedPostRepository {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Post>, RepoError> {
+        let post = self.inner.find_by_id(id).await?;
+        Ok(match current_tenant() {
+            Some(tenant) => post.filter(|post| in_tenant(post, tenant)),
+            None => post,
+        })
+    }
+
+    async fn save(&self, mut entity: Post) -> Result<Post, RepoError> {
+        if let Some(tenant) = current_tenant() {
+            self.claim(&mut entity, tenant).await?;
+        }
+        self.inner.save(entity).await
+    }
+
+    async fn insert(&self, mut entity: Post) -> Result<Post, RepoError> {
+        if let Some(tenant) = current_tenant() {
+            self.claim(&mut entity, tenant).await?;
+        }
+        self.inner.insert(entity).await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepoError> {
+        if self.find_by_id(id).await?.is_none() {
+            return Err(RepoError::NotFound);
+        }
+        self.inner.delete(id).await
+    }
+
+    async fn save_many(&self, mut entities: Vec<Post>) -> Result<(), RepoError> {
+        if let Some(tenant) = current_tenant() {
+            for entity in &mut entities {
+                self.claim(entity, tenant).await?;
+            }
+        }
+        self.inner.save_many(entities).await
+    }
+
+    /// Other tenants' posts are skipped, as if they did not exist.
+    async fn delete_many(&self, mut ids: Vec<Uuid>) -> Result<u64, RepoError> {
+        if current_tenant().is_some() {
+            let mut ours = Vec::with_capacity(ids.len());
+            for id in ids {
+                if self.find_by_id(id).await?.is_some() {
+                    ours.push(id);
+                }
+            }
+            ids = ours;
+        }
+        self.inner.delete_many(ids).await
+    }
+
+    async fn find_page(&self, request: PageRequest) -> Result<Page<Post>, RepoError> {
+        match current_tenant() {
+            Some(tenant) => {
+                self.inner
+                    .find_page_by_organization_id(tenant, None, request)
+                    .await
+            }
+            None => self.inner.find_page(request).await,
+        }
+    }
+}
+
+#[async_trait]
+impl PostRepository for TenantScopedPostRepository {
+    async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<Post>, RepoError> {
+        let mut posts = self.inner.find_by_user_id(user_id).await?;
+        if let Some(tenant) = current_tenant() {
+            posts.retain(|post| in_tenant(post, tenant));
+        }
+        Ok(posts)
+    }
+
+    async fn find_page_by_user_id(
+        &self,
+        user_id: Uuid,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        match current_tenant() {
+            Some(tenant) => {
+                self.inner
+                    .find_page_by_organization_id(tenant, Some(user_id), request)
+                    .await
+            }
+            None => self.inner.find_page_by_user_id(user_id, request).await,
+        }
+    }
+
+    async fn find_by_user_id_after(
+        &self,
+        user_id: Uuid,
+        after: Option<PageCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let Some(tenant) = current_tenant() else {
+            return self
+                .inner
+                .find_by_user_id_after(user_id, after, limit)
+                .await;
+        };
+
+        // As with changes: read on past other tenants' posts
+        let mut posts = Vec::new();
+        let mut cursor = after;
+        loop {
+            let batch = self
+                .inner
+                .find_by_user_id_after(user_id, cursor, limit)
+                .await?;
+            let exhausted = (batch.len() as u64) < limit;
+            cursor = batch
+                .last()
+                .map(|post| PageCursor::new(post.created_at, post.id))
+                .or(cursor);
+            posts.extend(batch.into_iter().filter(|post| in_tenant(post, tenant)));
+            if exhausted || posts.len() as u64 >= limit {
+                break;
+            }
+        }
+        posts.truncate(limit as usize);
+        Ok(posts)
+    }
+
+    async fn find_by_organization_id(&self, organization_id: Uuid) -> Result<Vec<Post>, RepoError> {
+        if current_tenant().is_some_and(|tenant| tenant != organization_id) {
+            return Ok(Vec::new());
+        }
+        self.inner.find_by_organization_id(organization_id).await
+    }
+
+    async fn find_page_by_organization_id(
+        &self,
+        organization_id: Uuid,
+        user_id: Option<Uuid>,
+        request: PageRequest,
+    ) -> Result<Page<Post>, RepoError> {
+        if current_tenant().is_some_and(|tenant| tenant != organization_id) {
+            return Ok(Page::empty(&request));
+        }
+        self.inner
+            .find_page_by_organization_id(organization_id, user_id, request)
+            .await
+    }
+
+    async fn list_changes(
+        &self,
+        user_id: Uuid,
+        after: Option<SyncCursor>,
+        limit: u64,
+    ) -> Result<Vec<Post>, RepoError> {
+        let Some(tenant) = current_tenant() else {
+            return self.inner.list_changes(user_id, after, limit).await;
+        };
+
+        // Other tenants' changes are skipped, so keep reading until the page
+        // is full or there are no more
+        let mut changes = Vec::new();
+        let mut cursor = after;
+        loop {
+            let batch = self.inner.list_changes(user_id, cursor, limit).await?;
+            let exhausted = (batch.len() as u64) < limit;
+            cursor = batch.last().map(SyncCursor::after).or(cursor);
+            changes.extend(batch.into_iter().filter(|post| in_tenant(post, tenant)));
+            if exhausted || changes.len() as u64 >= limit {
+                break;
+            }
+        }
+        changes.truncate(limit as usize);
+        Ok(changes)
+    }
+
+    async fn save_if_version(
+        &self,
+        mut post: Post,
+        expected_version: Option<i64>,
+    ) -> Result<(), RepoError> {
+        if let Some(tenant) = current_tenant() {
+            self.claim(&mut post, tenant).await?;
+        }
+        self.inner.save_if_version(post, expected_version).await
+    }
+
+    async fn add_views(&self, views: Vec<(Uuid, i64)>) -> Result<(), RepoError> {
+        self.inner.add_views(views).await
+    }
+
+    async fn list_published(&self, now: DateTime<Utc>, limit: u64) -> Result<Vec<Post>, RepoError> {
+        let Some(tenant) = current_tenant() else {
+            return self.inner.list_published(now, limit).await;
+        };
+
+        let mut posts = self.inner.find_by_organization_id(tenant).await?;
+        posts.retain(|post| post.is_published(now));
+        posts.sort_by_key(|post| std::cmp::Reverse((post.published_at, post.id)));
+        posts.truncate(limit as usize);
+        Ok(posts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context;
+    use crate::database::InMemoryPostRepository;
+    use apex_core::RequestContext;
+
+    fn as_tenant(tenant: Option<Uuid>) -> RequestContext {
+        let mut context = RequestContext::new("req");
+        context.tenant_id = tenant;
+        context
+    }
+
+    #[tokio::test]
+    async fn test_reads_and_writes_stay_in_the_tenant() {
+        let inner = Arc::new(InMemoryPostRepository::new());
+        let repo = TenantScopedPostRepository::new(inner.clone());
+        let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+        let author = Uuid::new_v4();
+
+        let theirs = Post::new(author, "Globex".into(), "".into()).in_organization(globex);
+        inner.save(theirs.clone()).await.unwrap();
+
+        context::scope(as_tenant(Some(acme)), async {
+            // Stamped with the tenant
+            let ours = repo
+                .save(Post::new(author, "Acme".into(), "".into()))
+                .await
+                .unwrap();
+            assert_eq!(ours.organization_id, Some(acme));
+
+            assert!(repo.find_by_id(theirs.id).await.unwrap().is_none());
+            assert_eq!(
+                repo.find_by_user_id(author).await.unwrap(),
+                vec![ours.clone()]
+            );
+            assert_eq!(
+                repo.find_page(PageRequest::default()).await.unwrap().total,
+                1
+            );
+            assert_eq!(
+                repo.find_page_by_user_id(author, PageRequest::default())
+                    .await
+                    .unwrap()
+                    .items,
+                vec![ours]
+            );
+            assert!(matches!(
+                repo.save(theirs.clone()).await,
+                Err(RepoError::Constraint(_))
+            ));
+            assert!(matches!(
+                repo.delete(theirs.id).await,
+                Err(RepoError::NotFound)
+            ));
+            assert_eq!(repo.delete_many(vec![theirs.id]).await.unwrap(), 0);
+        })
+        .await;
+
+        // Outside a tenant, everything
+        context::scope(as_tenant(None), async {
+            assert_eq!(repo.find_by_user_id(author).await.unwrap().len(), 2);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_changes_fill_the_page_past_other_tenants() {
+        let inner = Arc::new(InMemoryPostRepository::new());
+        let repo = TenantScopedPostRepository::new(inner.clone());
+        let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+        let author = Uuid::new_v4();
+
+        for tenant in [globex, globex, acme, globex, acme] {
+            let post = Post::new(author, "Post".into(), "".into()).in_organization(tenant);
+            inner.save(post).await.unwrap();
+        }
+
+        let changes = context::scope(as_tenant(Some(acme)), repo.list_changes(author, None, 2))
+            .await
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|post| in_tenant(post, acme)));
+    }
+}
@@ -0,0 +1,26 @@
+//! Tenant-scoped repositories.
+//!
+//! The tenant is the organization a request acts for, read from the
+//! [request context](crate::context): the organization of the caller's token,
+//! or for anonymous requests the one their host or header names. With
+//! [`TenancyMode::Column`](crate::database::TenancyMode), tenant-owned rows
+//! share tables and carry the tenant in `organization_id`;
+//! [`TenantScopedPostRepository`] narrows every query to the current
+//! tenant's rows and stamps the rows it writes.
+//!
+//! Without a tenant (an unscoped token, an anonymous request naming none, or
+//! no context at all, as at startup and in background tasks) it acts as the
+//! repository it wraps. Scoping only ever narrows what a handler sees; it
+//! does not replace the handler's own ownership checks. Users, organizations and memberships
+//! are not scoped: they decide who belongs to which tenant.
+
+mod column;
+
+use uuid::Uuid;
+
+pub use column::TenantScopedPostRepository;
+
+/// Organization the current request acts for, if any.
+pub fn current_tenant() -> Option<Uuid> {
+    crate::context::current().and_then(|context| context.tenant_id)
+}
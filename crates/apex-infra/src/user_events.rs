@@ -0,0 +1,310 @@
+//! Per-user event streams, for Server-Sent Events clients.
+//!
+//! Events about a user are published on their [`user_channel`]. The hub
+//! follows a user's channel from the moment their first stream opens and
+//! keeps their latest events, numbered in arrival order, so a client that
+//! reconnects with the number of the last event it saw is sent what it
+//! missed. A channel is followed until `linger` after the user's last stream
+//! closed. Numbers are per instance, like the in-process pub/sub feeding
+//! the hub.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use apex_core::ports::{Envelope, PubSub, PubSubError, SubscriptionHandle};
+
+use crate::env;
+use crate::events::user_channel;
+use crate::pubsub::TypedPubSub;
+
+/// An event as sent to a user's streams.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserEvent {
+    /// Position in the hub; reconnecting with it resumes after this event.
+    pub seq: u64,
+    pub id: Uuid,
+    pub event: String,
+    pub timestamp: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+struct Recent {
+    events: VecDeque<UserEvent>,
+    /// Number of the last event no longer kept, or issued before the feed
+    /// started.
+    dropped: u64,
+    /// Streams open for the user, and since when there are none.
+    streams: usize,
+    idle_since: Option<Instant>,
+}
+
+/// One followed user: their channel's subscription and latest events.
+struct Feed {
+    recent: Mutex<Recent>,
+    live: broadcast::Sender<UserEvent>,
+    subscription: Mutex<Option<SubscriptionHandle>>,
+}
+
+impl Feed {
+    fn record(&self, seq: u64, envelope: Envelope<serde_json::Value>, capacity: usize) {
+        let event = UserEvent {
+            seq,
+            id: envelope.id,
+            event: envelope.event,
+            timestamp: envelope.timestamp,
+            data: envelope.data,
+        };
+        let mut recent = self.recent.lock().expect("user feed lock");
+        if recent.events.len() == capacity
+            && let Some(dropped) = recent.events.pop_front()
+        {
+            recent.dropped = dropped.seq;
+        }
+        recent.events.push_back(event.clone());
+        // Sent under the lock, so a stream opening now sees it once
+        let _ = self.live.send(event);
+    }
+}
+
+/// Follows the channels of users with open streams.
+pub struct UserEventHub<P> {
+    pubsub: Arc<TypedPubSub<P>>,
+    feeds: Mutex<HashMap<Uuid, Arc<Feed>>>,
+    /// Last number issued, across users.
+    seq: Arc<AtomicU64>,
+    capacity: usize,
+    linger: Duration,
+}
+
+impl<P: PubSub + 'static> UserEventHub<P> {
+    pub fn new(pubsub: Arc<TypedPubSub<P>>, capacity: usize, linger: Duration) -> Self {
+        Self {
+            pubsub,
+            feeds: Mutex::new(HashMap::new()),
+            seq: Arc::new(AtomicU64::new(0)),
+            capacity: capacity.max(1),
+            linger,
+        }
+    }
+
+    /// Events kept per user from `SSE_REPLAY_SIZE`, 100 by default, and
+    /// channels followed for `SSE_LINGER_SECS`, 300 by default, after their
+    /// last stream closed.
+    pub fn from_env(pubsub: Arc<TypedPubSub<P>>) -> Self {
+        Self::new(
+            pubsub,
+            env::SSE_REPLAY_SIZE.get(),
+            env::SSE_LINGER_SECS.secs(),
+        )
+    }
+
+    /// Open a stream of `user_id`'s events, starting after `last_seen` if
+    /// the client saw some before.
+    pub async fn open(
+        &self,
+        user_id: Uuid,
+        last_seen: Option<u64>,
+    ) -> Result<UserEventStream, PubSubError> {
+        let feed = self.feed(user_id).await?;
+
+        let mut recent = feed.recent.lock().expect("user feed lock");
+        recent.streams += 1;
+        recent.idle_since = None;
+        let live = feed.live.subscribe();
+        let (missed, truncated) = match last_seen {
+            None => (Vec::new(), false),
+            Some(last_seen) => {
+                let newest = recent.events.back().map_or(recent.dropped, |e| e.seq);
+                let missed = recent
+                    .events
+                    .iter()
+                    .filter(|e| e.seq > last_seen)
+                    .cloned()
+                    .collect();
+                // Events after it are gone, or it is not from this hub
+                // (e.g. issued before a restart)
+                (missed, last_seen < recent.dropped || last_seen > newest)
+            }
+        };
+        drop(recent);
+
+        Ok(UserEventStream {
+            missed: missed.into(),
+            truncated,
+            live,
+            feed,
+        })
+    }
+
+    /// Users whose channel is followed.
+    pub fn followed(&self) -> usize {
+        self.feeds.lock().expect("user feeds lock").len()
+    }
+
+    /// The user's feed, following their channel if it is not yet. Feeds
+    /// idle for longer than `linger` are dropped on the way.
+    async fn feed(&self, user_id: Uuid) -> Result<Arc<Feed>, PubSubError> {
+        let feed = {
+            let mut feeds = self.feeds.lock().expect("user feeds lock");
+            feeds.retain(|_, feed| {
+                let recent = feed.recent.lock().expect("user feed lock");
+                recent
+                    .idle_since
+                    .is_none_or(|since| since.elapsed() < self.linger)
+            });
+            if let Some(feed) = feeds.get(&user_id) {
+                return Ok(feed.clone());
+            }
+            let feed = Arc::new(Feed {
+                recent: Mutex::new(Recent {
+                    events: VecDeque::with_capacity(self.capacity),
+                    dropped: self.seq.load(Ordering::SeqCst),
+                    streams: 0,
+                    idle_since: None,
+                }),
+                live: broadcast::Sender::new(self.capacity),
+                subscription: Mutex::new(None),
+            });
+            feeds.insert(user_id, feed.clone());
+            feed
+        };
+
+        let recorder = Arc::downgrade(&feed);
+        let seq = self.seq.clone();
+        let capacity = self.capacity;
+        let subscribed = self
+            .pubsub
+            .subscribe_json(
+                &user_channel(user_id),
+                move |envelope: Envelope<serde_json::Value>| {
+                    if let Some(feed) = recorder.upgrade() {
+                        let seq = seq.fetch_add(1, Ordering::SeqCst) + 1;
+                        feed.record(seq, envelope, capacity);
+                    }
+                    async {}
+                },
+            )
+            .await;
+        match subscribed {
+            Ok(subscription) => {
+                *feed.subscription.lock().expect("user feed lock") = Some(subscription);
+                Ok(feed)
+            }
+            Err(e) => {
+                self.feeds.lock().expect("user feeds lock").remove(&user_id);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// One client's stream: the events it missed, then new ones as they come.
+pub struct UserEventStream {
+    missed: VecDeque<UserEvent>,
+    /// Events after the client's last one may be gone; it should refetch
+    /// what it shows.
+    pub truncated: bool,
+    live: broadcast::Receiver<UserEvent>,
+    feed: Arc<Feed>,
+}
+
+impl UserEventStream {
+    /// The next event, waiting for one. `None` once the stream fell too far
+    /// behind to continue; the client should reconnect to catch up.
+    pub async fn next(&mut self) -> Option<UserEvent> {
+        if let Some(event) = self.missed.pop_front() {
+            return Some(event);
+        }
+        self.live.recv().await.ok()
+    }
+}
+
+impl Drop for UserEventStream {
+    fn drop(&mut self) {
+        let mut recent = self.feed.recent.lock().expect("user feed lock");
+        recent.streams -= 1;
+        if recent.streams == 0 {
+            recent.idle_since = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pubsub::InMemoryPubSub;
+
+    fn hub(
+        capacity: usize,
+        linger: Duration,
+    ) -> (
+        Arc<TypedPubSub<InMemoryPubSub>>,
+        UserEventHub<InMemoryPubSub>,
+    ) {
+        let pubsub = Arc::new(TypedPubSub::new(Arc::new(InMemoryPubSub::new(16)), "test"));
+        (pubsub.clone(), UserEventHub::new(pubsub, capacity, linger))
+    }
+
+    async fn publish(pubsub: &TypedPubSub<InMemoryPubSub>, user_id: Uuid, n: u64) {
+        pubsub
+            .publish_json(
+                &user_channel(user_id),
+                "post.created",
+                serde_json::json!({ "n": n }),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_streams_resume_after_the_last_event_seen() {
+        let (pubsub, hub) = hub(3, Duration::from_secs(60));
+        let (user, other) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut stream = hub.open(user, None).await.unwrap();
+        let _other = hub.open(other, None).await.unwrap();
+        publish(&pubsub, other, 0).await;
+        for n in 1..=2 {
+            publish(&pubsub, user, n).await;
+        }
+        let first = stream.next().await.unwrap();
+        assert_eq!(first.data["n"], 1);
+        assert_eq!(stream.next().await.unwrap().data["n"], 2);
+        drop(stream);
+
+        // Reconnecting gets what came after, then new events
+        publish(&pubsub, user, 3).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let mut resumed = hub.open(user, Some(first.seq)).await.unwrap();
+        assert!(!resumed.truncated);
+        assert_eq!(resumed.next().await.unwrap().data["n"], 2);
+        assert_eq!(resumed.next().await.unwrap().data["n"], 3);
+        for n in 4..=5 {
+            publish(&pubsub, user, n).await;
+            assert_eq!(resumed.next().await.unwrap().data["n"], n);
+        }
+
+        // Events after the first were pushed out, or the id is unknown
+        assert!(hub.open(user, Some(first.seq)).await.unwrap().truncated);
+        assert!(hub.open(user, Some(1000)).await.unwrap().truncated);
+    }
+
+    #[tokio::test]
+    async fn test_idle_feeds_are_dropped_after_lingering() {
+        let (_pubsub, hub) = hub(3, Duration::ZERO);
+        let (user, other) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let stream = hub.open(user, None).await.unwrap();
+        let _other = hub.open(other, None).await.unwrap();
+        assert_eq!(hub.followed(), 2);
+        drop(stream);
+        let _other = hub.open(other, None).await.unwrap();
+        assert_eq!(hub.followed(), 1);
+    }
+}
@@ -0,0 +1,233 @@
+//! Post view counting - cache counters flushed to storage in batches.
+//!
+//! A view is a cache increment rather than a database write. A recurring
+//! job drains the counters and adds them to the posts in one statement per
+//! batch, so a burst of page views costs a handful of writes. With a shared
+//! cache (Redis) every instance feeds the same counters.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use apex_core::error::RepoError;
+use apex_core::ports::{Cache, PostRepository};
+
+const KEY_PREFIX: &str = "post_views:";
+
+/// Posts written per statement when flushing.
+const BATCH_SIZE: usize = 500;
+
+/// Counts post views and flushes them to the post repository.
+pub struct PostViews {
+    cache: Arc<dyn Cache>,
+    posts: Arc<dyn PostRepository>,
+}
+
+impl PostViews {
+    pub fn new(cache: Arc<dyn Cache>, posts: Arc<dyn PostRepository>) -> Self {
+        Self { cache, posts }
+    }
+
+    /// Count a view of a post. A cache failure loses the view, not the request.
+    pub async fn record(&self, post_id: Uuid) {
+        if let Err(e) = self.cache.incr(&key(post_id), 1).await {
+            tracing::warn!(post_id = %post_id, error = %e, "Failed to count post view");
+        }
+    }
+
+    /// Views of a post not flushed yet.
+    pub async fn pending(&self, post_id: Uuid) -> i64 {
+        self.cache
+            .get(&key(post_id))
+            .await
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Move pending views to storage. Returns the number of posts updated.
+    ///
+    /// Counters are taken atomically, so views recorded meanwhile go to the
+    /// next flush; a batch that fails to write is put back for it too.
+    pub async fn flush(&self) -> Result<usize, RepoError> {
+        let keys = self
+            .cache
+            .keys(KEY_PREFIX)
+            .await
+            .map_err(|e| RepoError::Connection(e.to_string()))?;
+
+        let mut views = Vec::with_capacity(keys.len());
+        for key in keys {
+            let Some(post_id) = key
+                .strip_prefix(KEY_PREFIX)
+                .and_then(|id| id.parse::<Uuid>().ok())
+            else {
+                continue;
+            };
+            let taken = self
+                .cache
+                .take(&key)
+                .await
+                .map_err(|e| RepoError::Connection(e.to_string()))?;
+            match taken.and_then(|count| count.parse::<i64>().ok()) {
+                Some(count) if count > 0 => views.push((post_id, count)),
+                _ => {}
+            }
+        }
+
+        let mut flushed = 0;
+        let mut batches = views.chunks(BATCH_SIZE);
+        while let Some(batch) = batches.next() {
+            if let Err(e) = self.posts.add_views(batch.to_vec()).await {
+                for (post_id, count) in batch.iter().chain(batches.flatten()) {
+                    let _ = self.cache.incr(&key(*post_id), *count).await;
+                }
+                return Err(e);
+            }
+            flushed += batch.len();
+        }
+        Ok(flushed)
+    }
+}
+
+fn key(post_id: Uuid) -> String {
+    format!("{}{}", KEY_PREFIX, post_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::InMemoryCache;
+    use apex_core::domain::{Page, PageCursor, PageRequest, Post, SyncCursor};
+    use apex_core::ports::BaseRepository;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct ViewLog {
+        views: Mutex<HashMap<Uuid, i64>>,
+        writes: Mutex<usize>,
+        fail: AtomicBool,
+    }
+
+    #[async_trait]
+    impl BaseRepository<Post, Uuid> for ViewLog {
+        async fn find_by_id(&self, _id: Uuid) -> Result<Option<Post>, RepoError> {
+            Ok(None)
+        }
+        async fn save(&self, post: Post) -> Result<Post, RepoError> {
+            Ok(post)
+        }
+        async fn insert(&self, post: Post) -> Result<Post, RepoError> {
+            Ok(post)
+        }
+        async fn delete(&self, _id: Uuid) -> Result<(), RepoError> {
+            Ok(())
+        }
+        async fn find_page(&self, request: PageRequest) -> Result<Page<Post>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl PostRepository for ViewLog {
+        async fn find_by_user_id(&self, _user_id: Uuid) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn find_page_by_user_id(
+            &self,
+            _user_id: Uuid,
+            request: PageRequest,
+        ) -> Result<Page<Post>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+        async fn find_by_user_id_after(
+            &self,
+            _user_id: Uuid,
+            _after: Option<PageCursor>,
+            _limit: u64,
+        ) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn find_by_organization_id(&self, _org: Uuid) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn find_page_by_organization_id(
+            &self,
+            _org: Uuid,
+            _user_id: Option<Uuid>,
+            request: PageRequest,
+        ) -> Result<Page<Post>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+        async fn list_changes(
+            &self,
+            _user_id: Uuid,
+            _after: Option<SyncCursor>,
+            _limit: u64,
+        ) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn save_if_version(&self, _post: Post, _v: Option<i64>) -> Result<(), RepoError> {
+            Ok(())
+        }
+        async fn list_published(
+            &self,
+            _now: chrono::DateTime<chrono::Utc>,
+            _limit: u64,
+        ) -> Result<Vec<Post>, RepoError> {
+            Ok(vec![])
+        }
+        async fn add_views(&self, views: Vec<(Uuid, i64)>) -> Result<(), RepoError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(RepoError::Connection("down".to_string()));
+            }
+            *self.writes.lock().await += 1;
+            let mut stored = self.views.lock().await;
+            for (id, count) in views {
+                *stored.entry(id).or_default() += count;
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_views_are_coalesced_into_one_write() {
+        let repo = Arc::new(ViewLog::default());
+        let views = PostViews::new(Arc::new(InMemoryCache::new()), repo.clone());
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        for _ in 0..5 {
+            views.record(a).await;
+        }
+        views.record(b).await;
+        assert_eq!(views.pending(a).await, 5);
+
+        assert_eq!(views.flush().await.unwrap(), 2);
+        assert_eq!(*repo.writes.lock().await, 1);
+        assert_eq!(repo.views.lock().await[&a], 5);
+        assert_eq!(views.pending(a).await, 0);
+
+        // Nothing pending, nothing written
+        assert_eq!(views.flush().await.unwrap(), 0);
+        assert_eq!(*repo.writes.lock().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_keeps_the_views() {
+        let repo = Arc::new(ViewLog::default());
+        let views = PostViews::new(Arc::new(InMemoryCache::new()), repo.clone());
+        let post = Uuid::new_v4();
+
+        views.record(post).await;
+        repo.fail.store(true, Ordering::SeqCst);
+        assert!(views.flush().await.is_err());
+        views.record(post).await;
+        assert_eq!(views.pending(post).await, 2);
+
+        repo.fail.store(false, Ordering::SeqCst);
+        views.flush().await.unwrap();
+        assert_eq!(repo.views.lock().await[&post], 2);
+    }
+}
@@ -0,0 +1,185 @@
+//! Audited webhook sender - records every delivery attempt for inspection and replay.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use apex_core::domain::WebhookDelivery;
+use apex_core::error::RepoError;
+use apex_core::ports::{
+    WebhookDeliveryRepository, WebhookError, WebhookRequest, WebhookResponse, WebhookSender,
+};
+
+/// Decorator that writes a `WebhookDelivery` record for each attempt made
+/// through the wrapped sender.
+///
+/// Recording failures are logged and never fail the delivery itself.
+pub struct AuditedWebhookSender {
+    inner: Arc<dyn WebhookSender>,
+    deliveries: Arc<dyn WebhookDeliveryRepository>,
+}
+
+impl AuditedWebhookSender {
+    pub fn new(
+        inner: Arc<dyn WebhookSender>,
+        deliveries: Arc<dyn WebhookDeliveryRepository>,
+    ) -> Self {
+        Self { inner, deliveries }
+    }
+
+    /// Re-send a previously recorded delivery, recording the result as the
+    /// next attempt of its chain.
+    pub async fn replay(&self, original: &WebhookDelivery) -> Result<WebhookDelivery, RepoError> {
+        let chain_id = original.chain_id();
+        let latest = self
+            .deliveries
+            .latest_attempt(chain_id)
+            .await?
+            .unwrap_or_default()
+            .max(original.attempt);
+        let request = WebhookRequest::new(original.url.clone(), original.payload.clone());
+        let (delivery, _) = self.deliver(&request, latest + 1, Some(chain_id)).await;
+
+        self.deliveries.save(delivery).await
+    }
+
+    async fn deliver(
+        &self,
+        request: &WebhookRequest,
+        attempt: u32,
+        replay_of: Option<Uuid>,
+    ) -> (WebhookDelivery, Result<WebhookResponse, WebhookError>) {
+        let started = Instant::now();
+        let result = self.inner.send(request).await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let (response_status, response_body, error) = match &result {
+            Ok(response) => (Some(response.status), Some(response.body.clone()), None),
+            Err(e) => (None, None, Some(e.to_string())),
+        };
+
+        let delivery = WebhookDelivery {
+            id: Uuid::new_v4(),
+            url: request.url.clone(),
+            payload: request.payload.clone(),
+            response_status,
+            response_body,
+            error,
+            latency_ms,
+            attempt,
+            replay_of,
+            request_id: crate::context::current().map(|context| context.request_id),
+            created_at: chrono::Utc::now(),
+        };
+
+        (delivery, result)
+    }
+}
+
+#[async_trait]
+impl WebhookSender for AuditedWebhookSender {
+    async fn send(&self, request: &WebhookRequest) -> Result<WebhookResponse, WebhookError> {
+        let (delivery, result) = self.deliver(request, 1, None).await;
+
+        if !delivery.succeeded() {
+            tracing::warn!(
+                delivery_id = %delivery.id,
+                url = %delivery.url,
+                status = ?delivery.response_status,
+                "Webhook delivery failed"
+            );
+        }
+
+        if let Err(e) = self.deliveries.save(delivery).await {
+            tracing::warn!(error = %e, "Failed to record webhook delivery");
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhook::RecordingWebhookSender;
+    use apex_core::domain::{Page, PageRequest};
+    use apex_core::ports::BaseRepository;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryDeliveries(Mutex<Vec<WebhookDelivery>>);
+
+    #[async_trait]
+    impl BaseRepository<WebhookDelivery, Uuid> for MemoryDeliveries {
+        async fn find_by_id(&self, id: Uuid) -> Result<Option<WebhookDelivery>, RepoError> {
+            Ok(self.0.lock().await.iter().find(|d| d.id == id).cloned())
+        }
+        async fn save(&self, delivery: WebhookDelivery) -> Result<WebhookDelivery, RepoError> {
+            self.0.lock().await.push(delivery.clone());
+            Ok(delivery)
+        }
+        async fn insert(&self, delivery: WebhookDelivery) -> Result<WebhookDelivery, RepoError> {
+            self.save(delivery).await
+        }
+        async fn delete(&self, _id: Uuid) -> Result<(), RepoError> {
+            Ok(())
+        }
+        async fn find_page(
+            &self,
+            request: PageRequest,
+        ) -> Result<Page<WebhookDelivery>, RepoError> {
+            Ok(Page::empty(&request))
+        }
+    }
+
+    #[async_trait]
+    impl WebhookDeliveryRepository for MemoryDeliveries {
+        async fn list_recent(
+            &self,
+            _failed_only: bool,
+            _limit: u64,
+        ) -> Result<Vec<WebhookDelivery>, RepoError> {
+            Ok(self.0.lock().await.clone())
+        }
+        async fn latest_attempt(&self, chain_id: Uuid) -> Result<Option<u32>, RepoError> {
+            Ok(self
+                .0
+                .lock()
+                .await
+                .iter()
+                .filter(|d| d.chain_id() == chain_id)
+                .map(|d| d.attempt)
+                .max())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_and_replays_delivery() {
+        let deliveries = Arc::new(MemoryDeliveries::default());
+        let sender = AuditedWebhookSender::new(
+            Arc::new(RecordingWebhookSender::default()),
+            deliveries.clone(),
+        );
+        let request = WebhookRequest::new("https://example.com/hook", serde_json::json!({"a": 1}));
+
+        sender.send(&request).await.unwrap();
+
+        let original = deliveries.list_recent(false, 10).await.unwrap().remove(0);
+        assert_eq!(original.attempt, 1);
+        assert!(original.succeeded());
+
+        let replayed = sender.replay(&original).await.unwrap();
+        assert_eq!(replayed.attempt, 2);
+        assert_eq!(replayed.replay_of, Some(original.id));
+        assert_eq!(replayed.payload, original.payload);
+
+        // Replaying any delivery of the chain numbers the attempt after its
+        // latest one
+        assert_eq!(sender.replay(&original).await.unwrap().attempt, 3);
+        let again = sender.replay(&replayed).await.unwrap();
+        assert_eq!(again.attempt, 4);
+        assert_eq!(again.replay_of, Some(original.id));
+    }
+}
@@ -0,0 +1,92 @@
+//! HTTP webhook sender using reqwest.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use apex_core::ports::{WebhookError, WebhookRequest, WebhookResponse, WebhookSender};
+
+use crate::env;
+
+/// Maximum number of response body bytes kept.
+const MAX_RESPONSE_BODY: usize = 4096;
+
+/// HTTP webhook sender configuration.
+#[derive(Debug, Clone)]
+pub struct HttpWebhookConfig {
+    /// Per-request timeout.
+    pub timeout: Duration,
+}
+
+impl Default for HttpWebhookConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HttpWebhookConfig {
+    pub fn from_env() -> Self {
+        Self {
+            timeout: env::WEBHOOK_TIMEOUT_SECS.secs(),
+        }
+    }
+}
+
+/// Webhook sender that POSTs JSON payloads over HTTP.
+pub struct HttpWebhookSender {
+    client: reqwest::Client,
+}
+
+impl HttpWebhookSender {
+    pub fn new(config: HttpWebhookConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .unwrap_or_default();
+
+        Self { client }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(HttpWebhookConfig::from_env())
+    }
+}
+
+impl Default for HttpWebhookSender {
+    fn default() -> Self {
+        Self::new(HttpWebhookConfig::default())
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(&self, request: &WebhookRequest) -> Result<WebhookResponse, WebhookError> {
+        let response = self
+            .client
+            .post(&request.url)
+            .json(&request.payload)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    WebhookError::Timeout
+                } else {
+                    WebhookError::Delivery(e.to_string())
+                }
+            })?;
+
+        let status = response.status().as_u16();
+        let mut body = response.text().await.unwrap_or_default();
+        if body.len() > MAX_RESPONSE_BODY {
+            let mut end = MAX_RESPONSE_BODY;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.truncate(end);
+        }
+
+        Ok(WebhookResponse { status, body })
+    }
+}
@@ -0,0 +1,12 @@
+//! Webhook delivery implementations - HTTP and recording (sandbox) fallback.
+
+mod audited;
+mod recording;
+
+pub use audited::AuditedWebhookSender;
+pub use recording::{RecordedWebhook, RecordingWebhookSender};
+
+#[cfg(feature = "webhooks")]
+mod http;
+#[cfg(feature = "webhooks")]
+pub use self::http::{HttpWebhookConfig, HttpWebhookSender};
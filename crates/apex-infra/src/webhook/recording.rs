@@ -0,0 +1,106 @@
+//! Recording webhook sender - used in sandbox mode.
+//!
+//! Nothing leaves the process: deliveries are logged and kept in a bounded
+//! in-memory buffer so staging environments can inspect what would have been sent.
+
+use std::collections::VecDeque;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+
+use apex_core::ports::{WebhookError, WebhookRequest, WebhookResponse, WebhookSender};
+
+/// A webhook captured by the recording sender.
+#[derive(Debug, Clone)]
+pub struct RecordedWebhook {
+    pub url: String,
+    pub payload: serde_json::Value,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Webhook sender that records deliveries instead of performing them.
+pub struct RecordingWebhookSender {
+    recorded: RwLock<VecDeque<RecordedWebhook>>,
+    capacity: usize,
+}
+
+impl RecordingWebhookSender {
+    /// Create a recorder keeping at most `capacity` deliveries (oldest evicted first).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            recorded: RwLock::new(VecDeque::with_capacity(capacity.min(1024))),
+            capacity,
+        }
+    }
+
+    /// Snapshot of recorded deliveries, oldest first.
+    pub async fn recorded(&self) -> Vec<RecordedWebhook> {
+        self.recorded.read().await.iter().cloned().collect()
+    }
+}
+
+impl Default for RecordingWebhookSender {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+#[async_trait]
+impl WebhookSender for RecordingWebhookSender {
+    async fn send(&self, request: &WebhookRequest) -> Result<WebhookResponse, WebhookError> {
+        tracing::info!(
+            url = %request.url,
+            payload = %request.payload,
+            "Sandbox: webhook recorded, not delivered"
+        );
+
+        if self.capacity > 0 {
+            let mut recorded = self.recorded.write().await;
+            if recorded.len() >= self.capacity {
+                recorded.pop_front();
+            }
+            recorded.push_back(RecordedWebhook {
+                url: request.url.clone(),
+                payload: request.payload.clone(),
+                recorded_at: Utc::now(),
+            });
+        }
+
+        Ok(WebhookResponse {
+            status: 202,
+            body: String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_instead_of_sending() {
+        let sender = RecordingWebhookSender::default();
+        let request = WebhookRequest::new("https://example.com/hook", serde_json::json!({"a": 1}));
+
+        let response = sender.send(&request).await.unwrap();
+
+        assert!(response.is_success());
+        let recorded = sender.recorded().await;
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].url, "https://example.com/hook");
+    }
+
+    #[tokio::test]
+    async fn test_evicts_oldest_when_full() {
+        let sender = RecordingWebhookSender::new(2);
+        for i in 0..3 {
+            let request =
+                WebhookRequest::new(format!("https://example.com/{i}"), serde_json::json!({}));
+            sender.send(&request).await.unwrap();
+        }
+
+        let urls: Vec<_> = sender.recorded().await.into_iter().map(|r| r.url).collect();
+        assert_eq!(urls, vec!["https://example.com/1", "https://example.com/2"]);
+    }
+}
@@ -0,0 +1,290 @@
+//! Site files clients look for at fixed paths: `/robots.txt` and documents
+//! under `/.well-known/` (RFC 8615), e.g. `security.txt`, a JWKS or OpenID
+//! discovery.
+//!
+//! Documents come from configuration and from the app registering them at
+//! startup, so each one is not a handler of its own.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Days, Utc};
+
+use crate::env;
+
+/// A document served under `/.well-known/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WellKnownDocument {
+    pub content_type: String,
+    pub body: String,
+}
+
+impl WellKnownDocument {
+    pub fn text(body: impl Into<String>) -> Self {
+        Self {
+            content_type: "text/plain; charset=utf-8".to_string(),
+            body: body.into(),
+        }
+    }
+
+    pub fn json(value: &serde_json::Value) -> Self {
+        Self {
+            content_type: "application/json".to_string(),
+            body: value.to_string(),
+        }
+    }
+}
+
+/// What `/.well-known/security.txt` (RFC 9116) says.
+#[derive(Debug, Clone)]
+pub struct SecurityTxt {
+    /// Where to report vulnerabilities, e.g. `mailto:security@example.com`.
+    pub contacts: Vec<String>,
+    /// Vulnerability disclosure policy.
+    pub policy: Option<String>,
+    /// When the file goes stale; clients ignore it afterwards.
+    pub expires: DateTime<Utc>,
+}
+
+impl SecurityTxt {
+    /// `SECURITY_CONTACT` (comma separated), `SECURITY_POLICY_URL` and
+    /// `SECURITY_TXT_EXPIRES` (RFC 3339, a year from now by default). `None`
+    /// without a contact, which the format requires.
+    pub fn from_env() -> Option<Self> {
+        let contacts = env::SECURITY_CONTACT.list();
+        if contacts.is_empty() {
+            return None;
+        }
+        let expires = env::SECURITY_TXT_EXPIRES
+            .raw()
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc::now() + Days::new(365));
+        Some(Self {
+            contacts,
+            policy: env::SECURITY_POLICY_URL.raw(),
+            expires,
+        })
+    }
+
+    /// The file, with `canonical` as the URL it is served from.
+    pub fn render(&self, canonical: &str) -> String {
+        let mut txt = String::new();
+        for contact in &self.contacts {
+            txt.push_str(&format!("Contact: {}\n", contact));
+        }
+        txt.push_str(&format!(
+            "Expires: {}\n",
+            self.expires.format("%Y-%m-%dT%H:%M:%SZ")
+        ));
+        if let Some(policy) = &self.policy {
+            txt.push_str(&format!("Policy: {}\n", policy));
+        }
+        txt.push_str(&format!("Canonical: {}\n", canonical));
+        txt
+    }
+}
+
+/// Serves `robots.txt` and the registered well-known documents.
+pub struct WellKnown {
+    site_url: String,
+    /// Replaces the generated `robots.txt` when set.
+    robots: Option<String>,
+    sitemaps: RwLock<Vec<String>>,
+    documents: RwLock<BTreeMap<String, WellKnownDocument>>,
+}
+
+impl WellKnown {
+    /// Nothing registered, links under `site_url`.
+    pub fn new(site_url: &str) -> Self {
+        Self {
+            site_url: site_url.trim_end_matches('/').to_string(),
+            robots: None,
+            sitemaps: RwLock::default(),
+            documents: RwLock::default(),
+        }
+    }
+
+    /// `PUBLIC_URL` for links, `ROBOTS_TXT_PATH` to serve a file as
+    /// `robots.txt`, every file in `WELL_KNOWN_DIR` as a document of its name
+    /// and `security.txt` from [`SecurityTxt::from_env`]. Files that cannot
+    /// be read are skipped with a warning.
+    pub fn from_env() -> Self {
+        let mut well_known = Self::new(&env::PUBLIC_URL.string());
+
+        if let Some(path) = env::ROBOTS_TXT_PATH.raw() {
+            match std::fs::read_to_string(&path) {
+                Ok(robots) => well_known.robots = Some(robots),
+                Err(e) => tracing::warn!(path = %path, error = %e, "Failed to read robots.txt"),
+            }
+        }
+        if let Some(dir) = env::WELL_KNOWN_DIR.raw() {
+            well_known.load_dir(Path::new(&dir));
+        }
+        if let Some(security) = SecurityTxt::from_env() {
+            let canonical = well_known.url("security.txt");
+            well_known.register(
+                "security.txt",
+                WellKnownDocument::text(security.render(&canonical)),
+            );
+        }
+        well_known
+    }
+
+    /// Serve `document` at `/.well-known/{name}`, replacing any document
+    /// of that name. Names are one path segment of lowercase letters,
+    /// digits, `-`, `_` and `.`; others are refused with a warning.
+    pub fn register(&self, name: &str, document: WellKnownDocument) {
+        if !valid_name(name) {
+            tracing::warn!(name = %name, "Ignoring well-known document with an invalid name");
+            return;
+        }
+        self.documents
+            .write()
+            .expect("well-known documents lock")
+            .insert(name.to_string(), document);
+    }
+
+    /// List the sitemap at `path` (below the site URL) in `robots.txt`.
+    pub fn add_sitemap(&self, path: &str) {
+        let url = format!("{}/{}", self.site_url, path.trim_start_matches('/'));
+        self.sitemaps.write().expect("sitemaps lock").push(url);
+    }
+
+    pub fn get(&self, name: &str) -> Option<WellKnownDocument> {
+        self.documents
+            .read()
+            .expect("well-known documents lock")
+            .get(name)
+            .cloned()
+    }
+
+    /// Names of the registered documents, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let documents = self.documents.read().expect("well-known documents lock");
+        documents.keys().cloned().collect()
+    }
+
+    /// The configured `robots.txt`, or one keeping crawlers out of the API
+    /// and pointing them at the sitemaps.
+    pub fn robots(&self) -> String {
+        if let Some(robots) = &self.robots {
+            return robots.clone();
+        }
+        let mut robots = String::from("User-agent: *\nDisallow: /api/\n");
+        for sitemap in self.sitemaps.read().expect("sitemaps lock").iter() {
+            robots.push_str(&format!("Sitemap: {}\n", sitemap));
+        }
+        robots
+    }
+
+    /// Public URL of the document `name`.
+    pub fn url(&self, name: &str) -> String {
+        format!("{}/.well-known/{}", self.site_url, name)
+    }
+
+    fn load_dir(&self, dir: &Path) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(dir = %dir.display(), error = %e, "Failed to read WELL_KNOWN_DIR");
+                return;
+            }
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !path.is_file() {
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(body) => self.register(name, document_for(name, body)),
+                Err(e) => tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "Failed to read well-known document"
+                ),
+            }
+        }
+    }
+}
+
+/// A file's document: JSON for `.json` files and for extensionless ones
+/// holding JSON (e.g. `apple-app-site-association`), text otherwise.
+fn document_for(name: &str, body: String) -> WellKnownDocument {
+    let json = name.ends_with(".json")
+        || (!name.contains('.') && serde_json::from_str::<serde_json::Value>(&body).is_ok());
+    if json {
+        WellKnownDocument {
+            content_type: "application/json".to_string(),
+            body,
+        }
+    } else {
+        WellKnownDocument::text(body)
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_documents_are_served() {
+        let well_known = WellKnown::new("https://example.com/");
+        well_known.register(
+            "openid-configuration",
+            WellKnownDocument::json(&serde_json::json!({"issuer": "https://example.com"})),
+        );
+        well_known.register("../secrets", WellKnownDocument::text("x"));
+        well_known.register("Upper", WellKnownDocument::text("x"));
+
+        assert_eq!(well_known.names(), ["openid-configuration"]);
+        let document = well_known.get("openid-configuration").unwrap();
+        assert_eq!(document.content_type, "application/json");
+        assert_eq!(document.body, r#"{"issuer":"https://example.com"}"#);
+
+        assert_eq!(
+            document_for("apple-app-site-association", "{}".to_string()).content_type,
+            "application/json"
+        );
+        assert!(
+            document_for("change-password", "x".to_string())
+                .content_type
+                .starts_with("text/plain")
+        );
+    }
+
+    #[test]
+    fn test_robots_and_security_txt() {
+        let well_known = WellKnown::new("https://example.com");
+        assert_eq!(well_known.robots(), "User-agent: *\nDisallow: /api/\n");
+        well_known.add_sitemap("/sitemap.xml");
+        assert!(
+            well_known
+                .robots()
+                .ends_with("Sitemap: https://example.com/sitemap.xml\n")
+        );
+
+        let security = SecurityTxt {
+            contacts: vec!["mailto:security@example.com".to_string()],
+            policy: None,
+            expires: "2027-01-01T00:00:00Z".parse().unwrap(),
+        };
+        assert_eq!(
+            security.render(&well_known.url("security.txt")),
+            "Contact: mailto:security@example.com\n\
+             Expires: 2027-01-01T00:00:00Z\n\
+             Canonical: https://example.com/.well-known/security.txt\n"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Client-side view of API errors.
+//!
+//! Maps the status codes and problem details the server answers with to a
+//! [`ClientError`], so callers handle an expired session, rate limiting or
+//! invalid input the same way on every endpoint.
+
+use thiserror::Error;
+
+use crate::response::{ErrorResponse, FieldError};
+
+/// Title of the 401 answered when the access token has expired, as opposed
+/// to a missing or invalid one.
+pub const TOKEN_EXPIRED_TITLE: &str = "Token Expired";
+
+/// An error response from the API, by the server error it stands for.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ClientError {
+    /// 400: the request was malformed.
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    /// 401 with an expired token: refresh it, or log in again.
+    #[error("Session expired")]
+    SessionExpired,
+
+    /// 401 otherwise: no or an invalid token was sent.
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    /// 402: the account's plan lacks a feature another plan has.
+    #[error("Upgrade to {required_plan} required")]
+    UpgradeRequired { required_plan: String },
+
+    #[error("Forbidden")]
+    Forbidden,
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// 422: the input failed validation; one message per problem, e.g.
+    /// "email is not a valid email address".
+    #[error("Validation failed: {}", .0.join(", "))]
+    Validation(Vec<String>),
+
+    /// 429: wait `retry_after` seconds, when known, before trying again.
+    #[error("Rate limited")]
+    RateLimited { retry_after: Option<u64> },
+
+    /// 451: these policies have to be accepted first.
+    #[error("Consent required: {}", .0.join(", "))]
+    ConsentRequired(Vec<String>),
+
+    /// 502, 503 or 504: the API is temporarily unreachable.
+    #[error("Service unavailable")]
+    Unavailable,
+
+    /// Any other 5xx.
+    #[error("Server error ({0})")]
+    Server(u16),
+
+    /// A status this client does not know about.
+    #[error("Unexpected response ({0})")]
+    Unexpected(u16),
+}
+
+impl ClientError {
+    /// Whether the same request may succeed when sent again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ClientError::RateLimited { .. } | ClientError::Unavailable
+        )
+    }
+
+    /// Whether the user has to (re-)authenticate.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            ClientError::SessionExpired | ClientError::Unauthorized
+        )
+    }
+}
+
+/// Map an error status and its problem details, if the body had any, to a
+/// [`ClientError`].
+pub fn map_status_to_error(status: u16, problem: Option<&ErrorResponse>) -> ClientError {
+    let detail = || problem.and_then(|p| p.detail.clone()).unwrap_or_default();
+    let extension = |key: &str| problem.and_then(|p| p.extensions.get(key));
+    let strings = |key: &str| -> Vec<String> {
+        extension(key)
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| match item.as_str() {
+                        Some(message) => Some(message.to_string()),
+                        None => serde_json::from_value::<FieldError>(item.clone())
+                            .ok()
+                            .map(|error| error.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    match status {
+        400 => ClientError::BadRequest(detail()),
+        401 if problem.is_some_and(|p| p.title == TOKEN_EXPIRED_TITLE) => {
+            ClientError::SessionExpired
+        }
+        401 => ClientError::Unauthorized,
+        402 => ClientError::UpgradeRequired {
+            required_plan: extension("required_plan")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        },
+        403 => ClientError::Forbidden,
+        404 => ClientError::NotFound(detail()),
+        409 => ClientError::Conflict(detail()),
+        422 => {
+            let errors = strings("errors");
+            if errors.is_empty() {
+                ClientError::Validation(vec![detail()])
+            } else {
+                ClientError::Validation(errors)
+            }
+        }
+        429 => ClientError::RateLimited {
+            retry_after: extension("retry_after").and_then(|v| v.as_u64()),
+        },
+        451 => ClientError::ConsentRequired(strings("policies")),
+        502..=504 => ClientError::Unavailable,
+        500..=599 => ClientError::Server(status),
+        _ => ClientError::Unexpected(status),
+    }
+}
+
+/// Map a raw error response body to a [`ClientError`]. Bodies that are not
+/// problem details, e.g. from a proxy, map by status alone.
+pub fn map_response_to_error(status: u16, body: &str) -> ClientError {
+    let problem = serde_json::from_str::<ErrorResponse>(body).ok();
+    map_status_to_error(status, problem.as_ref())
+}
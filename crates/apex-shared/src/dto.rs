@@ -1,18 +1,46 @@
 //! Data Transfer Objects - request/response types for the API.
+//!
+//! Request types derive [`Validate`] with the checks that need no state, so
+//! the API rejects malformed input field by field before it reaches the
+//! domain, which still enforces its own rules.
 
 use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// Rejects strings that are empty or only whitespace.
+fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::new("required").with_message("is required".into()));
+    }
+    Ok(())
+}
 
 /// Request to register a new user.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct RegisterUserRequest {
+    #[validate(email(message = "is not a valid email address"))]
     pub email: String,
+    // The minimum length is the configurable password policy's, which
+    // AuthService enforces
+    #[validate(length(min = 1, message = "is required"))]
     pub password: String,
 }
 
+/// Request to change the caller's password.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "is required"))]
+    pub current_password: String,
+    #[validate(length(min = 1, message = "is required"))]
+    pub new_password: String,
+}
+
 /// Request to login.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct LoginRequest {
+    #[validate(custom(function = "not_blank"))]
     pub email: String,
+    #[validate(length(min = 1, message = "is required"))]
     pub password: String,
 }
 
@@ -22,6 +50,9 @@ pub struct UserResponse {
     pub id: String,
     pub email: String,
     pub created_at: String,
+    /// Storage of the user's personal account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<StorageUsageResponse>,
 }
 
 /// Response containing authentication tokens.
@@ -31,3 +62,861 @@ pub struct AuthResponse {
     pub token_type: String,
     pub expires_in: u64,
 }
+
+/// Request to create an organization.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateOrganizationRequest {
+    /// Client-generated UUID (v4 or v7) for the new resource. Retrying with
+    /// the same id returns what the first attempt created.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[validate(custom(function = "not_blank"))]
+    pub name: String,
+    #[validate(length(min = 1, max = 64, message = "must be 1-64 characters"))]
+    pub slug: String,
+}
+
+/// Response containing an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationResponse {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub created_at: String,
+    /// Only returned for the active organization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<StorageUsageResponse>,
+}
+
+/// Response describing a member of an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MembershipResponse {
+    pub organization_id: String,
+    pub user_id: String,
+    pub role: String,
+    pub created_at: String,
+}
+
+/// Request to invite someone to an organization.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateInvitationRequest {
+    /// Client-generated UUID (v4 or v7) for the new resource. Retrying with
+    /// the same id returns what the first attempt created.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[validate(email(message = "is not a valid email address"))]
+    pub email: String,
+    /// Role granted on acceptance; defaults to "member".
+    pub role: Option<String>,
+}
+
+/// Response describing an invitation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvitationResponse {
+    pub id: String,
+    pub organization_id: String,
+    pub email: String,
+    pub role: String,
+    pub token: String,
+    pub expires_at: String,
+}
+
+/// Response describing a recorded outbound webhook delivery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: String,
+    pub url: String,
+    pub payload: serde_json::Value,
+    pub response_status: Option<u16>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+    pub attempt: u32,
+    pub replay_of: Option<String>,
+    /// Request during which the attempt was made.
+    pub request_id: Option<String>,
+    pub succeeded: bool,
+    pub created_at: String,
+}
+
+/// Announcement as shown to users.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnouncementResponse {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    /// `everyone`, `role:<role>`, `plan:<plan>` or `org:<id>`.
+    pub audience: String,
+    pub starts_at: String,
+    pub ends_at: Option<String>,
+}
+
+/// Request to create or replace an announcement.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AnnouncementRequest {
+    /// Client-generated UUID (v4 or v7) when creating, ignored on updates.
+    /// Retrying a create with the same id returns what the first attempt
+    /// created.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[validate(custom(function = "not_blank"))]
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    /// Defaults to everyone.
+    pub audience: Option<String>,
+    /// Defaults to now.
+    pub starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ends_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Usage of a single metric in a billing period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageMetricResponse {
+    pub metric: String,
+    pub quantity: i64,
+}
+
+/// Usage report for an account's billing period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageResponse {
+    pub account_id: String,
+    /// Billing month, formatted as YYYY-MM.
+    pub period: String,
+    pub metrics: Vec<UsageMetricResponse>,
+    /// Daily and monthly request quotas of the account's plan, if any.
+    #[serde(default)]
+    pub quotas: Vec<QuotaUsageResponse>,
+}
+
+/// An account's plan and what it unlocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanResponse {
+    pub account_id: String,
+    pub plan: String,
+    pub entitlements: Vec<String>,
+}
+
+/// An account's subscription and where it is in its lifecycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionResponse {
+    pub account_id: String,
+    pub plan: String,
+    /// One of trialing, active, past_due, canceled.
+    pub status: String,
+    pub trial_ends_at: Option<String>,
+    pub current_period_end: Option<String>,
+}
+
+/// Request to start a trial of a plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartTrialRequest {
+    pub plan: String,
+}
+
+/// Request to assign a plan to an account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPlanRequest {
+    pub plan: String,
+}
+
+/// A policy users have to accept and where the caller stands on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyConsentResponse {
+    /// `terms` or `privacy`.
+    pub policy: String,
+    pub current_version: String,
+    /// Latest version the caller accepted, if any.
+    pub accepted_version: Option<String>,
+    pub accepted_at: Option<String>,
+    /// Whether the caller still has to accept the current version.
+    pub required: bool,
+}
+
+/// Request to accept a version of a policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptPolicyRequest {
+    pub policy: String,
+    pub version: String,
+}
+
+/// Request to register or update an OAuth client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientRequest {
+    /// Client-generated UUID (v4 or v7) when registering, ignored on
+    /// updates. Retrying with the same id returns the client registered the
+    /// first time, without its secret.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    /// Exact callback URIs: https, or http on localhost.
+    pub redirect_uris: Vec<String>,
+    /// Any of openid, profile, email, offline_access, api.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// A registered OAuth client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientResponse {
+    pub id: String,
+    pub client_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    /// Only returned when the client is created or its secret is rotated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+/// Job queue counters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueStatsResponse {
+    pub pending: usize,
+    pub processing: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub dead: usize,
+}
+
+/// A queued job, with a preview of its payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResponse {
+    pub id: String,
+    pub job_type: String,
+    /// Start of the serialized payload.
+    pub payload_preview: String,
+    pub payload_truncated: bool,
+    pub payload_bytes: usize,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub unique_key: Option<String>,
+    pub created_at: String,
+    pub scheduled_at: Option<String>,
+}
+
+/// A job in the dead letter queue and why it failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadJobResponse {
+    pub job: JobResponse,
+    pub error: String,
+    pub failed_at: String,
+}
+
+/// Result of purging a queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeQueueResponse {
+    pub purged: usize,
+}
+
+/// Request to run a read-only query in the SQL console.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlQueryRequest {
+    /// Name of a secondary database.
+    pub database: String,
+    /// A single SELECT or WITH query, with `$1`, `$2`, ... placeholders.
+    pub query: String,
+    #[serde(default)]
+    pub params: Vec<serde_json::Value>,
+    pub limit: Option<u64>,
+}
+
+/// Rows returned by a SQL console query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlQueryResponse {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub row_count: usize,
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Feature flags as evaluated for the caller.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlagsResponse {
+    pub flags: std::collections::BTreeMap<String, bool>,
+    /// Whether an `X-Feature-Override` header was applied.
+    pub overridden: bool,
+}
+
+/// Traffic of one side of a canary rollout since startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryVariantResponse {
+    /// stable or canary.
+    pub variant: String,
+    pub requests: u64,
+    pub errors: u64,
+    /// Percentage of requests answered with a 5xx.
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+}
+
+/// A canary rollout and how its variants compare.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryRolloutResponse {
+    pub name: String,
+    /// Share of users sent to the canary.
+    pub percent: u8,
+    pub variants: Vec<CanaryVariantResponse>,
+}
+
+/// Shadow traffic settings and counters since startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowStatsResponse {
+    pub shadow_url: String,
+    pub sample_rate: f64,
+    pub methods: Vec<String>,
+    pub mirrored: u64,
+    pub matched: u64,
+    pub mismatched: u64,
+    /// Shadow unreachable or timed out.
+    pub failed: u64,
+}
+
+/// A mirrored request whose shadow response differed from production.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowDiffResponse {
+    pub id: String,
+    pub method: String,
+    pub path: String,
+    pub primary_status: u16,
+    pub shadow_status: Option<u16>,
+    /// Where the responses differ, e.g. `status: 200 != 500` or `$.items[0].name`.
+    pub differences: Vec<String>,
+    pub error: Option<String>,
+    pub primary_latency_ms: u64,
+    pub shadow_latency_ms: u64,
+    pub recorded_at: String,
+}
+
+/// A post as seen by a syncing client. Deleted posts come as tombstones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPostResponse {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub version: i64,
+    pub deleted: bool,
+    pub published_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// The caller's profile: account and personal settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncProfileResponse {
+    pub id: String,
+    pub email: String,
+    pub settings: serde_json::Value,
+}
+
+/// Changes since the client's cursor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPullResponse {
+    pub posts: Vec<SyncPostResponse>,
+    pub profile: SyncProfileResponse,
+    /// Pass back on the next pull; `None` until there has been a change.
+    pub cursor: Option<String>,
+    /// More changes are waiting; pull again right away.
+    pub has_more: bool,
+}
+
+/// A change made on the client, applied only if the post is still at
+/// `base_version`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncMutationRequest {
+    /// Create a post (no `base_version`) or replace its title and content.
+    Upsert {
+        id: String,
+        base_version: Option<i64>,
+        title: String,
+        content: String,
+    },
+    Delete {
+        id: String,
+        base_version: i64,
+    },
+}
+
+/// Mutations queued on the client, applied in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPushRequest {
+    pub mutations: Vec<SyncMutationRequest>,
+}
+
+/// Outcome of one pushed mutation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncMutationResult {
+    pub id: String,
+    /// `applied`, `unchanged`, `conflict` or `rejected`.
+    pub status: String,
+    /// The server copy after the mutation; on conflict, the copy to merge
+    /// with. `None` if the post does not exist.
+    pub post: Option<SyncPostResponse>,
+    pub error: Option<String>,
+}
+
+/// Results of a push, in the order the mutations were sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPushResponse {
+    pub results: Vec<SyncMutationResult>,
+}
+
+/// One request of a batch, run as if sent on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemRequest {
+    /// `GET`, `POST`, `PUT`, `PATCH` or `DELETE`.
+    pub method: String,
+    /// Path under `/api`, including any query string.
+    pub path: String,
+    /// JSON body, if the request has one.
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+    /// Media type of `body`, e.g. `application/merge-patch+json`;
+    /// `application/json` by default.
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+/// Requests run in order with the caller's credentials.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequest {
+    pub requests: Vec<BatchItemRequest>,
+}
+
+/// Response to one request of a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResponse {
+    pub status: u16,
+    /// The JSON response body, the raw text if it is not JSON, or `null`
+    /// if there was none.
+    pub body: serde_json::Value,
+}
+
+/// Responses of a batch, in the order the requests were sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResponse {
+    pub responses: Vec<BatchItemResponse>,
+}
+
+/// Pub/sub counters since startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PubSubStatsResponse {
+    pub published: u64,
+    pub delivered: u64,
+    /// Messages subscribers missed because they fell too far behind.
+    pub lagged: u64,
+    pub redelivered: u64,
+    /// Subscribers cut off for falling behind.
+    pub disconnected: u64,
+    pub subscriptions: usize,
+    pub avg_handler_ms: f64,
+    pub max_handler_ms: f64,
+}
+
+/// A backend the server depends on, as last checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendStatusResponse {
+    /// e.g. `database`, `database:analytics`, `job_queue`.
+    pub name: String,
+    /// e.g. `postgres` or `memory`.
+    pub kind: String,
+    /// `up`, `down`, `disabled`, or `in_process` for backends living in the
+    /// server itself.
+    pub status: String,
+    /// Round trip of a ping; `None` for in-process backends.
+    pub latency_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// Effective number of workers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerCountsResponse {
+    pub http: usize,
+    pub jobs: usize,
+}
+
+/// What this instance is running with: build, listeners and backends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeResponse {
+    pub version: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Compile-time features this build includes.
+    pub features: Vec<String>,
+    pub sandbox: bool,
+    pub addresses: Vec<String>,
+    pub workers: WorkerCountsResponse,
+    pub backends: Vec<BackendStatusResponse>,
+}
+
+/// One worker thread of a tokio runtime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokioWorkerResponse {
+    pub worker: usize,
+    /// Time spent running tasks since startup.
+    pub busy_ms: f64,
+    /// Times the worker ran out of work and went idle.
+    pub park_count: u64,
+    /// Only on builds with `--cfg tokio_unstable`, like the fields below.
+    pub poll_count: Option<u64>,
+    pub mean_poll_us: Option<f64>,
+    pub local_queue_depth: Option<usize>,
+}
+
+/// Scheduler state of one tokio runtime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokioRuntimeResponse {
+    /// `main`, or `http` for the worker that served the request.
+    pub name: String,
+    /// `current_thread` or `multi_thread`.
+    pub flavor: String,
+    pub alive_tasks: usize,
+    /// Tasks waiting to be picked up by any worker.
+    pub global_queue_depth: usize,
+    pub blocking_threads: Option<usize>,
+    pub workers: Vec<TokioWorkerResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokioMetricsResponse {
+    pub runtimes: Vec<TokioRuntimeResponse>,
+}
+
+/// Allocator counters, in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryStatsResponse {
+    pub allocator: String,
+    /// Held by the application.
+    pub allocated_bytes: usize,
+    /// In pages the allocator has handed out; at least `allocated_bytes`.
+    pub active_bytes: usize,
+    /// Physically resident in the allocator's mappings.
+    pub resident_bytes: usize,
+    pub mapped_bytes: usize,
+    /// Unmapped but kept as virtual address space for reuse.
+    pub retained_bytes: usize,
+    /// Whether heap profiles can be taken.
+    pub profiling: bool,
+}
+
+/// A heap profile written on the server, to be read with `jeprof`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeapProfileResponse {
+    pub path: String,
+}
+
+/// A route and what it takes to call it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteResponse {
+    pub method: String,
+    pub path: String,
+    pub handler: String,
+    /// `public`, `optional`, `authenticated` or `admin`.
+    pub access: String,
+    /// Middleware wrapping the route, e.g. `ConsentCheck`.
+    pub middleware: Vec<String>,
+    /// Route pattern of the rate limit the route counts against, `default`
+    /// for the default limit; absent without rate limiting.
+    pub rate_limit: Option<String>,
+}
+
+/// A post, with its view count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostResponse {
+    pub id: String,
+    pub user_id: String,
+    pub organization_id: Option<String>,
+    pub title: String,
+    pub content: String,
+    pub version: i64,
+    /// Flushed views plus those still counted in the cache.
+    pub view_count: i64,
+    /// When the post went public, listed in the sitemap and feed.
+    pub published_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Request to write a post. It starts out unpublished.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreatePostRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+/// Request to replace a post's title and content.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdatePostRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+    /// Version the edit is based on; a post changed since fails with 409.
+    #[serde(default)]
+    pub version: Option<i64>,
+}
+
+/// An uploaded file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentResponse {
+    pub id: String,
+    /// User who uploaded it.
+    pub user_id: String,
+    pub organization_id: Option<String>,
+    /// Post it is attached to, if any.
+    pub post_id: Option<String>,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: String,
+    /// Signed link to the bytes; expires after a few minutes.
+    pub download_url: String,
+}
+
+/// A comment on a post.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentResponse {
+    pub id: String,
+    pub post_id: String,
+    /// User who wrote it.
+    pub user_id: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Request to comment on a post.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateCommentRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub body: String,
+}
+
+/// Request to replace a post's tags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetTagsRequest {
+    /// Lowercased; letters, digits and inner dashes. Empty removes them all.
+    pub tags: Vec<String>,
+}
+
+/// The tags on a post.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostTagsResponse {
+    pub post_id: String,
+    /// By name.
+    pub tags: Vec<String>,
+}
+
+/// Bytes an account stores against its plan's quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUsageResponse {
+    pub plan: String,
+    pub used_bytes: i64,
+    /// Absent when the plan has no storage quota.
+    pub quota_bytes: Option<i64>,
+}
+
+/// Request to add a custom domain to an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCustomDomainRequest {
+    pub hostname: String,
+}
+
+/// DNS record proving ownership of a custom domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainChallengeResponse {
+    /// Always `TXT`.
+    pub record_type: String,
+    pub name: String,
+    pub value: String,
+}
+
+/// An organization's custom domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomDomainResponse {
+    pub id: String,
+    pub hostname: String,
+    pub verified: bool,
+    pub verified_at: Option<String>,
+    /// Record to publish before verifying.
+    pub verification: DomainChallengeResponse,
+}
+
+/// Requests an account made against a quota in its current window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUsageResponse {
+    /// One of day, month.
+    pub period: String,
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    pub resets_at: String,
+}
+
+/// A realtime notification, as delivered to long-polling and event stream
+/// clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationResponse {
+    pub id: String,
+    /// What happened, e.g. `announcement.published`.
+    pub event: String,
+    pub timestamp: String,
+    /// The event's payload, as pushed to WebSocket clients.
+    pub data: serde_json::Value,
+}
+
+/// Notifications after a long-poll cursor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPollResponse {
+    pub notifications: Vec<NotificationResponse>,
+    /// Pass as `cursor` on the next poll.
+    pub cursor: String,
+    /// Notifications were missed; refetch what is shown.
+    pub truncated: bool,
+}
+
+/// WebSocket connection quality of one namespace since startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamespaceMetricsResponse {
+    pub namespace: String,
+    /// Open connections.
+    pub connected: u64,
+    pub connects: u64,
+    /// Connects of clients reconnecting after losing their connection.
+    pub reconnects: u64,
+    pub disconnects: u64,
+    /// Heartbeats clients acknowledged.
+    pub heartbeats: u64,
+    /// Heartbeats not acknowledged in time.
+    pub missed_heartbeats: u64,
+    /// Heartbeat round trips over the latest samples; `None` before the
+    /// first ack.
+    pub rtt_p50_ms: Option<f64>,
+    pub rtt_p99_ms: Option<f64>,
+    pub rtt_max_ms: Option<f64>,
+}
+
+/// WebSocket connection quality across namespaces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeMetricsResponse {
+    /// Open connections in all namespaces.
+    pub connected: u64,
+    pub namespaces: Vec<NamespaceMetricsResponse>,
+}
+
+/// An admin placing or releasing a legal hold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHoldChangeResponse {
+    pub id: String,
+    pub held: bool,
+    pub reason: String,
+    /// Admin who made the change.
+    pub changed_by: String,
+    pub changed_at: String,
+}
+
+/// Whether a user's data is under legal hold, with the changes to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHoldResponse {
+    pub user_id: String,
+    /// While held, the user's data is not deleted or anonymized.
+    pub held: bool,
+    /// Newest first.
+    pub history: Vec<LegalHoldChangeResponse>,
+}
+
+/// Request to place or release a legal hold.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SetLegalHoldRequest {
+    pub held: bool,
+    /// Recorded in the audit trail, e.g. the case the hold is for.
+    #[validate(custom(function = "not_blank"))]
+    pub reason: String,
+}
+
+/// Roles granted to a user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRolesResponse {
+    pub user_id: String,
+    pub roles: Vec<String>,
+}
+
+/// A destructive admin action staged for a second admin's approval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingOperationResponse {
+    pub id: String,
+    /// The staged action, e.g. `{"type": "purge_pending_jobs"}`.
+    pub action: serde_json::Value,
+    /// pending, approved, executed, failed or rejected.
+    pub status: String,
+    /// Admin who staged it.
+    pub requested_by: String,
+    pub requested_at: String,
+    /// Pending operations cannot be approved after this.
+    pub expires_at: String,
+    /// Admin who approved or rejected it.
+    pub decided_by: Option<String>,
+    pub decided_at: Option<String>,
+    /// What running it did, or why it failed.
+    pub outcome: Option<String>,
+}
+
+/// A metric family and how many series it has.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricFamilyResponse {
+    pub name: String,
+    /// counter or summary.
+    pub kind: String,
+    pub labels: Vec<String>,
+    pub series: usize,
+    /// Observations folded into the overflow series after the family hit
+    /// its series cap.
+    pub overflowed: u64,
+}
+
+/// Cardinality of the metrics served at `/metrics`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsReportResponse {
+    /// Whether `/metrics` is served.
+    pub exposed: bool,
+    pub max_series_per_family: usize,
+    pub total_series: usize,
+    pub families: Vec<MetricFamilyResponse>,
+}
+
+/// Where a watched dependency stands, as of its last check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyStatusResponse {
+    /// e.g. `database`, `database:analytics` or `redis`.
+    pub name: String,
+    pub degraded: bool,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_checked: Option<chrono::DateTime<chrono::Utc>>,
+    /// When it was last flagged degraded or healthy.
+    pub since: chrono::DateTime<chrono::Utc>,
+}
+
+/// One page of a listing paged by cursor, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    /// Pass as `after` for the next page; `None` on the last one.
+    pub next_cursor: Option<String>,
+}
+
+/// One page of a listing paged by offset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OffsetPage<T> {
+    pub items: Vec<T>,
+    /// Items in the listing across all pages.
+    pub total: u64,
+    pub offset: u64,
+    pub limit: u64,
+    /// Pass as `offset` for the next page; `None` on the last one.
+    pub next_offset: Option<u64>,
+}
@@ -3,7 +3,9 @@
 //! Shared types between frontend and backend.
 //! In a full-stack Rust setup, this crate is compiled for both server and WASM.
 
+pub mod client_error;
 pub mod dto;
 pub mod response;
 
-pub use response::{ApiResponse, ErrorResponse};
+pub use client_error::{ClientError, map_response_to_error, map_status_to_error};
+pub use response::{ApiResponse, ErrorResponse, FieldError};
@@ -55,6 +55,28 @@ pub struct ErrorResponse {
     /// Request ID for debugging purposes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub request_id: Option<String>,
+
+    /// Problem-type specific members, serialized alongside the standard ones.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An input field that failed validation, one of the `errors` of a 422
+/// problem.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Path to the field, e.g. `email` or `items[0].title`.
+    pub field: String,
+    /// Machine-readable rule that failed, e.g. `length` or `email`.
+    pub code: String,
+    /// What is wrong with the value, e.g. "must be at least 8 characters".
+    pub message: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
 }
 
 impl ErrorResponse {
@@ -66,6 +88,7 @@ impl ErrorResponse {
             detail: None,
             instance: None,
             request_id: None,
+            extensions: serde_json::Map::new(),
         }
     }
 
@@ -79,6 +102,15 @@ impl ErrorResponse {
         self
     }
 
+    pub fn with_extension(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.extensions.insert(key.into(), value.into());
+        self
+    }
+
     // Common error constructors
     pub fn bad_request(detail: impl Into<String>) -> Self {
         Self::new(400, "Bad Request").with_detail(detail)
@@ -89,7 +89,7 @@ case "${1:-up}" in
     
     health)
         log_info "Checking service health..."
-        curl -s http://localhost:8080/api/health | jq .
+        curl -s http://localhost:8080/readyz | jq .
         ;;
     
     *)